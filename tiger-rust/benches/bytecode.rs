@@ -0,0 +1,87 @@
+//! Compares the bytecode VM with the tree-walking interpreter on the
+//! terminating tigcases programs. Both run on the calling thread, so a
+//! run's time is the engine's alone; the `()` row is what a run costs
+//! before the program does anything.
+//!
+//! Run with `cargo bench --bench bytecode`.
+
+use std::fs;
+use std::io;
+use std::time::{Duration, Instant};
+
+use tiger_rust::bytecode::{compile, vm};
+use tiger_rust::interp::interp;
+use tiger_rust::parser::parser::compile as parse;
+use tiger_rust::semant::semant::trans_prog;
+use tiger_rust::test::util::NON_TERMINATING;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+const STDIN: &str = "5 3 1 ;\n4 2 ;\n";
+
+/// Time per run of `run`, repeated for at least 200ms.
+fn measure(mut run: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || start.elapsed() < Duration::from_millis(200) {
+        run();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    let mut names: Vec<String> = fs::read_dir(TEST_DATA_PATH)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".tig") && !NON_TERMINATING.contains(&name.as_str()))
+        .collect();
+    names.sort();
+    println!(
+        "{:>12} | {:>12} {:>12} {:>12} | {:>8}",
+        "program", "interp us", "compile us", "vm us", "speedup"
+    );
+    let (mut interp_total, mut vm_total) = (Duration::ZERO, Duration::ZERO);
+    let programs = names.into_iter().map(|name| {
+        let input = fs::read_to_string(format!("{}{}", TEST_DATA_PATH, name)).unwrap();
+        (name, input)
+    });
+    for (name, input) in [(String::from("()"), String::from("()"))]
+        .into_iter()
+        .chain(programs)
+    {
+        let Ok(Ok(program)) = parse(&input).map(|exp| trans_prog(&exp)) else {
+            continue;
+        };
+        let interp_time = measure(|| {
+            let _ = interp::run(&program, &mut STDIN.as_bytes(), &mut io::sink());
+        });
+        let compile_time = measure(|| {
+            compile::compile(&program);
+        });
+        let module = compile::compile(&program);
+        let vm_time = measure(|| {
+            let _ = vm::run(&module, &mut STDIN.as_bytes(), &mut io::sink());
+        });
+        if name != "()" {
+            interp_total += interp_time;
+            vm_total += vm_time;
+        }
+        println!(
+            "{:>12} | {:>12.1} {:>12.1} {:>12.1} | {:>7.1}x",
+            name,
+            interp_time.as_secs_f64() * 1e6,
+            compile_time.as_secs_f64() * 1e6,
+            vm_time.as_secs_f64() * 1e6,
+            interp_time.as_secs_f64() / vm_time.as_secs_f64()
+        );
+    }
+    println!(
+        "{:>12} | {:>12.1} {:>12} {:>12.1} | {:>7.1}x",
+        "total",
+        interp_total.as_secs_f64() * 1e6,
+        "",
+        vm_total.as_secs_f64() * 1e6,
+        interp_total.as_secs_f64() / vm_total.as_secs_f64()
+    );
+}
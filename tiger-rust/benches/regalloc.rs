@@ -0,0 +1,109 @@
+//! Compares the coloring and linear-scan allocators on generated programs.
+//!
+//! Run with `cargo bench --bench regalloc`.
+
+use std::time::{Duration, Instant};
+
+use tiger_rust::assem::assem::Instr;
+use tiger_rust::codegen::codegen::instructions;
+use tiger_rust::frame::x86_64::X86_64Frame;
+use tiger_rust::parser::parser::compile;
+use tiger_rust::regalloc::regalloc::{allocate, Allocator};
+use tiger_rust::semant::semant::trans_prog;
+use tiger_rust::temp::temp::Temp;
+use tiger_rust::translate::translate::{translate, Frag};
+
+/// A program whose main body has `vars` variables and `stmts` assignments
+/// mixing them, with calls in between so that values live across them.
+fn generate(vars: usize, stmts: usize) -> String {
+    let mut seed: u64 = 0x2545f4914f6cdd1d;
+    let mut next = |bound: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as usize
+    };
+    let mut out = String::from("let function f(x: int): int = x + 1\n");
+    for v in 0..vars {
+        out.push_str(&format!("var v{} := f({})\n", v, v));
+    }
+    out.push_str("in (\n");
+    for _ in 0..stmts {
+        let (a, b, c) = (next(vars), next(vars), next(vars));
+        match next(3) {
+            0 => out.push_str(&format!("v{} := v{} + v{} * 3;\n", a, b, c)),
+            1 => out.push_str(&format!("v{} := f(v{}) - v{};\n", a, b, c)),
+            _ => out.push_str(&format!("if v{} > v{} then v{} := v{} / 2;\n", a, b, c, a)),
+        }
+    }
+    let sum: Vec<String> = (0..vars).map(|v| format!("v{}", v)).collect();
+    out.push_str(&sum.join(" + "));
+    out.push_str(") end\n");
+    out
+}
+
+struct Stats {
+    time: Duration,
+    spills: usize,
+    moves: usize,
+}
+
+fn measure(procs: &[(X86_64Frame, Vec<Instr>)], allocator: Allocator) -> Stats {
+    let mut stats = Stats {
+        time: Duration::ZERO,
+        spills: 0,
+        moves: 0,
+    };
+    for (frame, instrs) in procs {
+        let (mut frame, instrs) = (frame.clone(), instrs.clone());
+        let start = Instant::now();
+        let allocated = allocate(&mut frame, instrs, allocator);
+        stats.time += start.elapsed();
+        stats.spills += allocated.spilled.len();
+        let register = |temp: Temp| allocated.allocation.get(&temp).copied().unwrap_or(temp);
+        stats.moves += allocated
+            .instrs
+            .iter()
+            .filter(|instr| {
+                matches!(instr, Instr::Move { dst, src, .. } if register(*dst) != register(*src))
+            })
+            .count();
+    }
+    stats
+}
+
+fn main() {
+    println!(
+        "{:>6} {:>6} {:>7} | {:>10} {:>6} {:>6} | {:>10} {:>6} {:>6}",
+        "vars", "stmts", "instrs", "color ms", "spills", "moves", "linear ms", "spills", "moves"
+    );
+    for (vars, stmts) in [(20, 200), (50, 1000), (100, 3000), (200, 6000)] {
+        let input = generate(vars, stmts);
+        let program = trans_prog(&compile(&input).unwrap()).unwrap();
+        let procs: Vec<(X86_64Frame, Vec<Instr>)> = translate::<X86_64Frame>(&program)
+            .into_iter()
+            .filter_map(|frag| match frag {
+                Frag::Proc { body, mut frame } => {
+                    let (instrs, _) = instructions(&mut frame, body);
+                    Some((frame, instrs))
+                }
+                Frag::String { .. } => None,
+            })
+            .collect();
+        let size: usize = procs.iter().map(|(_, instrs)| instrs.len()).sum();
+        let color = measure(&procs, Allocator::Coloring);
+        let linear = measure(&procs, Allocator::LinearScan);
+        println!(
+            "{:>6} {:>6} {:>7} | {:>10.1} {:>6} {:>6} | {:>10.1} {:>6} {:>6}",
+            vars,
+            stmts,
+            size,
+            color.time.as_secs_f64() * 1000.0,
+            color.spills,
+            color.moves,
+            linear.time.as_secs_f64() * 1000.0,
+            linear.spills,
+            linear.moves
+        );
+    }
+}
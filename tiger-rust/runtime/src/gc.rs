@@ -0,0 +1,325 @@
+//! A Cheney copying collector for records, arrays and strings.
+//!
+//! Every heap object is a header word followed by its payload, and is
+//! referred to by the address of its payload. The header of a record is the
+//! address of its descriptor: a string with one `p` or `n` per field, telling
+//! whether the field holds a pointer. Other objects have one of the headers
+//! below. Pointers outside the heap, such as string literals, are left alone.
+//!
+//! The roots are the pointer slots of the compiled code's frames, found by
+//! walking the frame-pointer chain and looking each return address up in the
+//! frame maps the compiler emits, plus whatever the runtime itself holds.
+//! The same walk gives the backtrace of a runtime error. Code compiled to C
+//! instead links its frames into [`tig_c_frames`], each saying which of its
+//! slots hold pointers.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+
+use crate::{bytes, TigerString};
+
+pub const STRING: i64 = 2;
+pub const ARRAY: i64 = 4;
+pub const POINTER_ARRAY: i64 = 6;
+/// Set in the header of an object already copied, whose new address makes
+/// up the rest of it.
+const FORWARDED: i64 = 1;
+
+/// Bytes in each semispace unless `TIGER_HEAP` says otherwise.
+const DEFAULT_HEAP: usize = 8 << 20;
+
+/// Where compiled code called into the runtime from.
+#[derive(Clone, Copy)]
+pub struct Caller {
+    /// The caller's frame pointer.
+    pub fp: *const i64,
+    /// The return address of the call.
+    pub ra: usize,
+}
+
+/// The frame of an active function compiled to C, followed by its slots.
+#[repr(C)]
+pub struct CFrame {
+    prev: *const CFrame,
+    /// Null for slots that only hold roots, which backtraces leave out.
+    name: *const TigerString,
+    /// One `p` or `n` per slot, as for the fields of a record.
+    descriptor: *const TigerString,
+    slots: [i64; 0],
+}
+
+/// The innermost active frame of code compiled to C, or null.
+#[no_mangle]
+pub static mut tig_c_frames: *const CFrame = std::ptr::null();
+
+/// The frames of code compiled to C, innermost first.
+unsafe fn c_frames() -> impl Iterator<Item = &'static CFrame> {
+    let mut frame = tig_c_frames;
+    std::iter::from_fn(move || {
+        let current = frame.as_ref()?;
+        frame = current.prev;
+        Some(current)
+    })
+}
+
+extern "C" {
+    /// The frame maps of the program, one per function, ending in null.
+    static tig_frame_maps: [*const i64; 0];
+}
+
+struct Heap {
+    space: Box<[i64]>,
+    /// Index of the first free word of `space`.
+    next: usize,
+    /// The live slot offsets of each call site, by return address.
+    sites: Option<HashMap<usize, Vec<i64>>>,
+    /// Collect before every allocation, to shake out missing roots.
+    stress: bool,
+    stats: bool,
+    collections: u64,
+    allocated: u64,
+}
+
+thread_local! {
+    static HEAP: RefCell<Heap> = RefCell::new(Heap::new());
+}
+
+impl Heap {
+    fn new() -> Heap {
+        let bytes = env::var("TIGER_HEAP")
+            .ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(DEFAULT_HEAP);
+        Heap {
+            space: zeroed(bytes / 8),
+            next: 0,
+            sites: None,
+            stress: env::var_os("TIGER_GC_STRESS").is_some(),
+            stats: env::var_os("TIGER_GC_STATS").is_some(),
+            collections: 0,
+            allocated: 0,
+        }
+    }
+
+    /// Copies everything reachable into a new space, making it at least
+    /// twice as large as the live data plus `needed` words.
+    unsafe fn collect(&mut self, needed: usize, roots: &mut [&mut i64], caller: Caller) {
+        let sites = self.sites.take().unwrap_or_else(|| read_maps());
+        let mut size = self.space.len();
+        loop {
+            let start = self.space.as_ptr() as usize;
+            let mut copier = Copier {
+                from: start..start + self.next * 8,
+                to: zeroed(size),
+                next: 0,
+            };
+            for root in roots.iter_mut() {
+                **root = copier.copy(**root);
+            }
+            let (mut fp, mut ra) = (caller.fp, caller.ra);
+            while let Some(live) = sites.get(&ra) {
+                for offset in live {
+                    let slot = fp.offset(*offset as isize / 8) as *mut i64;
+                    *slot = copier.copy(*slot);
+                }
+                ra = *fp.add(1) as usize;
+                fp = *fp as *const i64;
+            }
+            for frame in c_frames() {
+                let slots = frame.slots.as_ptr() as *mut i64;
+                for (i, kind) in bytes(frame.descriptor).iter().enumerate() {
+                    if *kind == b'p' {
+                        *slots.add(i) = copier.copy(*slots.add(i));
+                    }
+                }
+            }
+            copier.scan();
+            self.space = copier.to;
+            self.next = copier.next;
+            self.collections += 1;
+            if (self.next + needed) * 2 <= size {
+                self.sites = Some(sites);
+                return;
+            }
+            size = (self.next + needed) * 2;
+        }
+    }
+}
+
+struct Copier {
+    /// Addresses of the objects in the old space.
+    from: std::ops::Range<usize>,
+    to: Box<[i64]>,
+    next: usize,
+}
+
+impl Copier {
+    /// The new address of the object at `p`, copying it on first sight.
+    unsafe fn copy(&mut self, p: i64) -> i64 {
+        let address = p as usize;
+        if address <= self.from.start || address > self.from.end {
+            return p;
+        }
+        let header = (p as *mut i64).sub(1);
+        if *header & FORWARDED != 0 {
+            return *header & !FORWARDED;
+        }
+        let words = payload(*header, p as *const i64);
+        let q = self.next;
+        self.to[q] = *header;
+        std::ptr::copy_nonoverlapping(p as *const i64, self.to.as_mut_ptr().add(q + 1), words);
+        self.next += 1 + words;
+        let new = self.to.as_ptr().add(q + 1) as i64;
+        *header = new | FORWARDED;
+        new
+    }
+
+    /// Copies what the objects already copied point to, breadth first.
+    unsafe fn scan(&mut self) {
+        let mut q = 0;
+        while q < self.next {
+            let header = self.to[q];
+            let words = payload(header, self.to.as_ptr().add(q + 1));
+            match header {
+                STRING | ARRAY => (),
+                POINTER_ARRAY => {
+                    for i in q + 2..q + 1 + words {
+                        self.to[i] = self.copy(self.to[i]);
+                    }
+                }
+                descriptor => {
+                    let fields = bytes(descriptor as *const TigerString);
+                    for (i, kind) in fields.iter().enumerate() {
+                        if *kind == b'p' {
+                            self.to[q + 1 + i] = self.copy(self.to[q + 1 + i]);
+                        }
+                    }
+                }
+            }
+            q += 1 + words;
+        }
+    }
+}
+
+/// Words in the payload of the object at `p`.
+unsafe fn payload(header: i64, p: *const i64) -> usize {
+    match header {
+        STRING => 1 + (*p as usize).div_ceil(8),
+        ARRAY | POINTER_ARRAY => 1 + *p as usize,
+        descriptor => (*(descriptor as *const TigerString)).length as usize,
+    }
+}
+
+fn zeroed(words: usize) -> Box<[i64]> {
+    vec![0; words].into_boxed_slice()
+}
+
+/// One function's frame map.
+struct Map {
+    name: *const TigerString,
+    /// Frame-pointer offsets of the pointer slots.
+    slots: &'static [i64],
+    /// The return address of each call site, and a bitmap of the slots live
+    /// there.
+    sites: Vec<(usize, &'static [u64])>,
+}
+
+/// Each map points to the function's name, then holds the number of pointer
+/// slots and their frame-pointer offsets, then the number of call sites and
+/// for each its return address and a bitmap of the slots live there.
+unsafe fn maps() -> Vec<Map> {
+    let mut result = Vec::new();
+    let mut maps = std::ptr::addr_of!(tig_frame_maps) as *const *const i64;
+    while !(*maps).is_null() {
+        let mut p = *maps;
+        let name = *p as *const TigerString;
+        let slots = std::slice::from_raw_parts(p.add(2), *p.add(1) as usize);
+        p = p.add(2 + slots.len());
+        let count = *p;
+        p = p.add(1);
+        let words = slots.len().div_ceil(64);
+        let mut sites = Vec::new();
+        for _ in 0..count {
+            let bits = std::slice::from_raw_parts(p.add(1) as *const u64, words);
+            sites.push((*p as usize, bits));
+            p = p.add(1 + words);
+        }
+        result.push(Map { name, slots, sites });
+        maps = maps.add(1);
+    }
+    result
+}
+
+/// The live slot offsets of each call site, by return address.
+unsafe fn read_maps() -> HashMap<usize, Vec<i64>> {
+    let mut sites = HashMap::new();
+    for map in maps() {
+        for (ra, bits) in map.sites {
+            let live = map
+                .slots
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| bits[i / 64] >> (i % 64) & 1 == 1)
+                .map(|(_, offset)| *offset)
+                .collect();
+            sites.insert(ra, live);
+        }
+    }
+    sites
+}
+
+/// The names of the compiled functions active at `caller`, innermost
+/// first.
+pub unsafe fn backtrace(caller: Caller) -> Vec<String> {
+    let mut names = HashMap::new();
+    for map in maps() {
+        for (ra, _) in map.sites {
+            names.insert(ra, map.name);
+        }
+    }
+    let mut result = Vec::new();
+    let (mut fp, mut ra) = (caller.fp, caller.ra);
+    while let Some(name) = names.get(&ra) {
+        result.push(String::from_utf8_lossy(bytes(*name)).into_owned());
+        ra = *fp.add(1) as usize;
+        fp = *fp as *const i64;
+    }
+    for frame in c_frames().filter(|frame| !frame.name.is_null()) {
+        result.push(String::from_utf8_lossy(bytes(frame.name)).into_owned());
+    }
+    result
+}
+
+/// A zeroed object of `words` payload words, collecting first if the heap
+/// is full. `roots` are pointers the runtime still needs, updated if their
+/// objects move.
+pub unsafe fn alloc(header: i64, words: usize, roots: &mut [&mut i64], caller: Caller) -> *mut i64 {
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        let size = 1 + words;
+        if heap.stress || heap.next + size > heap.space.len() {
+            heap.collect(size, roots, caller);
+        }
+        let q = heap.next;
+        heap.next += size;
+        heap.allocated += size as u64 * 8;
+        heap.space[q] = header;
+        heap.space.as_mut_ptr().add(q + 1)
+    })
+}
+
+/// Prints what the collector did, if `TIGER_GC_STATS` is set.
+pub fn report() {
+    HEAP.with(|heap| {
+        let heap = heap.borrow();
+        if heap.stats {
+            eprintln!(
+                "gc: {} collections, {} bytes allocated, {} byte heap",
+                heap.collections,
+                heap.allocated,
+                heap.space.len() * 8
+            );
+        }
+    })
+}
@@ -0,0 +1,432 @@
+//! Runtime for Tiger programs compiled to native code.
+//!
+//! A Tiger string is a pointer to a word holding its length followed by its
+//! bytes. An array is likewise a pointer to its length, followed by its
+//! elements. Records, arrays and strings built at run time live in a heap
+//! managed by the collector in [`gc`]. Every entry point is prefixed with
+//! `tig_` so that names such as `exit` and `getchar` do not clash with libc.
+//!
+//! The entry points are only ever called by compiled code, which passes
+//! pointers it got from this runtime or from its own string literals.
+#![allow(clippy::missing_safety_doc)]
+
+mod gc;
+#[cfg(not(test))]
+mod stack;
+
+use std::io::{self, Read, Write};
+use std::process;
+
+use gc::Caller;
+
+const WORD: usize = std::mem::size_of::<i64>();
+
+#[repr(C)]
+pub struct TigerString {
+    length: i64,
+    chars: [u8; 0],
+}
+
+/// A string of one byte, laid out like a `TigerString`.
+#[repr(C)]
+struct Char {
+    length: i64,
+    chars: [u8; 1],
+}
+
+static CHARS: [Char; 256] = {
+    let mut chars = [const {
+        Char {
+            length: 1,
+            chars: [0],
+        }
+    }; 256];
+    let mut i = 0;
+    while i < 256 {
+        chars[i].chars[0] = i as u8;
+        i += 1;
+    }
+    chars
+};
+
+static EMPTY: TigerString = TigerString {
+    length: 0,
+    chars: [],
+};
+
+pub fn fatal(message: &str) -> ! {
+    let _ = io::stdout().flush();
+    eprintln!("{}", message);
+    process::exit(1)
+}
+
+/// Lines kept at each end of a backtrace, as in the interpreters.
+const BACKTRACE_ENDS: usize = 10;
+
+/// Reports a runtime error at the source position `pos`, packed as the
+/// compiler packs it, with the functions active at `caller`. Like the
+/// interpreters, it gives a run of calls of one function one line, and
+/// folds the lines between the first and last [`BACKTRACE_ENDS`] into one.
+unsafe fn fail(pos: i64, message: &str, caller: Caller) -> ! {
+    let mut report = format!("{}.{}: {}", pos >> 16, pos & 0xffff, message);
+    let names = gc::backtrace(caller);
+    let runs: Vec<_> = names.chunk_by(|a, b| a == b).collect();
+    let folded = match runs.len() > 2 * BACKTRACE_ENDS {
+        true => BACKTRACE_ENDS..runs.len() - BACKTRACE_ENDS,
+        false => 0..0,
+    };
+    for (i, run) in runs.iter().enumerate() {
+        if folded.contains(&i) {
+            if i == folded.start {
+                let count: usize = runs[folded.clone()].iter().map(|run| run.len()).sum();
+                report.push_str(&format!("\n  ... {} more", count));
+            }
+            continue;
+        }
+        report.push_str("\n  in ");
+        report.push_str(&run[0]);
+        if run.len() > 1 {
+            report.push_str(&format!(" (x{})", run.len()));
+        }
+    }
+    fatal(&report)
+}
+
+unsafe fn new_string(length: usize, roots: &mut [&mut i64], caller: Caller) -> *mut TigerString {
+    let s = gc::alloc(gc::STRING, 1 + length.div_ceil(WORD), roots, caller) as *mut TigerString;
+    (*s).length = length as i64;
+    s
+}
+
+unsafe fn bytes<'a>(s: *const TigerString) -> &'a [u8] {
+    std::slice::from_raw_parts((*s).chars.as_ptr(), (*s).length as usize)
+}
+
+fn char_string(c: u8) -> *const TigerString {
+    &CHARS[c as usize] as *const Char as *const TigerString
+}
+
+// The entry points that allocate can start a collection, and those that
+// fail report a backtrace, both of which walk the frames of the compiled
+// code. Each is reached through a stub that passes the caller's frame
+// pointer and return address on, in the argument registers after the entry
+// point's own.
+#[cfg(target_arch = "x86_64")]
+std::arch::global_asm!(
+    ".globl tig_initArray",
+    "tig_initArray:",
+    "movq %rbp, %r8",
+    "movq (%rsp), %r9",
+    "jmp {init_array}",
+    ".globl tig_allocRecord",
+    "tig_allocRecord:",
+    "movq %rbp, %rdx",
+    "movq (%rsp), %rcx",
+    "jmp {alloc_record}",
+    ".globl tig_chr",
+    "tig_chr:",
+    "movq %rbp, %rdx",
+    "movq (%rsp), %rcx",
+    "jmp {chr}",
+    ".globl tig_substring",
+    "tig_substring:",
+    "movq %rbp, %r8",
+    "movq (%rsp), %r9",
+    "jmp {substring}",
+    ".globl tig_concat",
+    "tig_concat:",
+    "movq %rbp, %rdx",
+    "movq (%rsp), %rcx",
+    "jmp {concat}",
+    ".globl tig_nilRecord",
+    "tig_nilRecord:",
+    "movq %rbp, %rsi",
+    "movq (%rsp), %rdx",
+    "jmp {nil_record}",
+    ".globl tig_indexOutOfRange",
+    "tig_indexOutOfRange:",
+    "movq %rbp, %rcx",
+    "movq (%rsp), %r8",
+    "jmp {index_out_of_range}",
+    ".globl tig_divisionByZero",
+    "tig_divisionByZero:",
+    "movq %rbp, %rsi",
+    "movq (%rsp), %rdx",
+    "jmp {division_by_zero}",
+    init_array = sym init_array,
+    alloc_record = sym alloc_record,
+    chr = sym chr,
+    substring = sym substring,
+    concat = sym concat,
+    nil_record = sym nil_record,
+    index_out_of_range = sym index_out_of_range,
+    division_by_zero = sym division_by_zero,
+    options(att_syntax)
+);
+
+/// Elsewhere only code compiled to C or LLVM IR can call in. It keeps its
+/// frames in `tig_c_frames`, so the entry points get no caller frame.
+#[cfg(not(target_arch = "x86_64"))]
+mod entry {
+    use std::ptr::null;
+
+    use super::*;
+
+    #[no_mangle]
+    pub unsafe extern "C" fn tig_initArray(
+        size: i64,
+        init: i64,
+        pointers: i64,
+        pos: i64,
+    ) -> *mut i64 {
+        init_array(size, init, pointers, pos, null(), 0)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn tig_allocRecord(
+        size: i64,
+        descriptor: *const TigerString,
+    ) -> *mut i64 {
+        alloc_record(size, descriptor, null(), 0)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn tig_chr(i: i64, pos: i64) -> *const TigerString {
+        chr(i, pos, null(), 0)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn tig_substring(
+        s: *const TigerString,
+        first: i64,
+        n: i64,
+        pos: i64,
+    ) -> *const TigerString {
+        substring(s, first, n, pos, null(), 0)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn tig_concat(
+        a: *const TigerString,
+        b: *const TigerString,
+    ) -> *const TigerString {
+        concat(a, b, null(), 0)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn tig_nilRecord(pos: i64) -> ! {
+        nil_record(pos, null(), 0)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn tig_indexOutOfRange(i: i64, size: i64, pos: i64) -> ! {
+        index_out_of_range(i, size, pos, null(), 0)
+    }
+
+    #[no_mangle]
+    pub unsafe extern "C" fn tig_divisionByZero(pos: i64) -> ! {
+        division_by_zero(pos, null(), 0)
+    }
+}
+
+/// `tig_initArray(size, init, pointers, pos)`, where `pointers` tells
+/// whether `init` is a heap pointer.
+unsafe extern "C" fn init_array(
+    size: i64,
+    init: i64,
+    pointers: i64,
+    pos: i64,
+    fp: *const i64,
+    ra: usize,
+) -> *mut i64 {
+    if size < 0 {
+        fail(
+            pos,
+            &format!("array size {} is negative", size),
+            Caller { fp, ra },
+        );
+    }
+    let mut init = init;
+    let (header, roots): (i64, &mut [&mut i64]) = if pointers != 0 {
+        (gc::POINTER_ARRAY, &mut [&mut init])
+    } else {
+        (gc::ARRAY, &mut [])
+    };
+    let a = gc::alloc(header, 1 + size as usize, roots, Caller { fp, ra });
+    *a = size;
+    for i in 1..=size as usize {
+        *a.add(i) = init;
+    }
+    a
+}
+
+/// `tig_allocRecord(size, descriptor)`: a record of `size` bytes, laid out
+/// as `descriptor` says.
+unsafe extern "C" fn alloc_record(
+    size: i64,
+    descriptor: *const TigerString,
+    fp: *const i64,
+    ra: usize,
+) -> *mut i64 {
+    let words = (size as usize).div_ceil(WORD);
+    gc::alloc(descriptor as i64, words, &mut [], Caller { fp, ra })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tig_stringEqual(a: *const TigerString, b: *const TigerString) -> i64 {
+    (a == b || bytes(a) == bytes(b)) as i64
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tig_stringCompare(a: *const TigerString, b: *const TigerString) -> i64 {
+    bytes(a).cmp(bytes(b)) as i64
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tig_print(s: *const TigerString) {
+    let _ = io::stdout().write_all(bytes(s));
+}
+
+#[no_mangle]
+pub extern "C" fn tig_flush() {
+    let _ = io::stdout().flush();
+}
+
+#[no_mangle]
+pub extern "C" fn tig_getchar() -> *const TigerString {
+    let mut byte = [0u8];
+    match io::stdin().read(&mut byte) {
+        Ok(1) => char_string(byte[0]),
+        _ => &EMPTY,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tig_ord(s: *const TigerString) -> i64 {
+    match bytes(s).first() {
+        Some(c) => *c as i64,
+        None => -1,
+    }
+}
+
+unsafe extern "C" fn chr(i: i64, pos: i64, fp: *const i64, ra: usize) -> *const TigerString {
+    match u8::try_from(i) {
+        Ok(c) => char_string(c),
+        Err(_) => fail(pos, &format!("chr({}) out of range", i), Caller { fp, ra }),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tig_size(s: *const TigerString) -> i64 {
+    (*s).length
+}
+
+unsafe extern "C" fn substring(
+    s: *const TigerString,
+    first: i64,
+    n: i64,
+    pos: i64,
+    fp: *const i64,
+    ra: usize,
+) -> *const TigerString {
+    let chars = bytes(s);
+    if first < 0 || n < 0 || first + n > chars.len() as i64 {
+        let message = format!(
+            "substring({:?}, {}, {}) out of range",
+            String::from_utf8_lossy(chars),
+            first,
+            n
+        );
+        fail(pos, &message, Caller { fp, ra });
+    }
+    let (first, n) = (first as usize, n as usize);
+    match n {
+        0 => &EMPTY,
+        1 => char_string(chars[first]),
+        _ => {
+            let mut s = s as i64;
+            let t = new_string(n, &mut [&mut s], Caller { fp, ra });
+            let part = &bytes(s as *const TigerString)[first..first + n];
+            std::ptr::copy_nonoverlapping(part.as_ptr(), (*t).chars.as_mut_ptr(), n);
+            t
+        }
+    }
+}
+
+unsafe extern "C" fn concat(
+    a: *const TigerString,
+    b: *const TigerString,
+    fp: *const i64,
+    ra: usize,
+) -> *const TigerString {
+    let (a_length, b_length) = ((*a).length as usize, (*b).length as usize);
+    if a_length == 0 {
+        return b;
+    }
+    if b_length == 0 {
+        return a;
+    }
+    let (mut a, mut b) = (a as i64, b as i64);
+    let t = new_string(
+        a_length + b_length,
+        &mut [&mut a, &mut b],
+        Caller { fp, ra },
+    );
+    let chars = (*t).chars.as_mut_ptr();
+    let (a, b) = (a as *const TigerString, b as *const TigerString);
+    std::ptr::copy_nonoverlapping(bytes(a).as_ptr(), chars, a_length);
+    std::ptr::copy_nonoverlapping(bytes(b).as_ptr(), chars.add(a_length), b_length);
+    t
+}
+
+unsafe extern "C" fn nil_record(pos: i64, fp: *const i64, ra: usize) -> ! {
+    fail(pos, "nil record dereferenced", Caller { fp, ra })
+}
+
+unsafe extern "C" fn index_out_of_range(
+    i: i64,
+    size: i64,
+    pos: i64,
+    fp: *const i64,
+    ra: usize,
+) -> ! {
+    let message = format!("index {} out of range for array of size {}", i, size);
+    fail(pos, &message, Caller { fp, ra })
+}
+
+unsafe extern "C" fn division_by_zero(pos: i64, fp: *const i64, ra: usize) -> ! {
+    fail(pos, "division by zero", Caller { fp, ra })
+}
+
+#[no_mangle]
+pub extern "C" fn tig_not(i: i64) -> i64 {
+    (i == 0) as i64
+}
+
+#[no_mangle]
+pub extern "C" fn tig_exit(code: i64) -> ! {
+    let _ = io::stdout().flush();
+    gc::report();
+    process::exit(code as i32)
+}
+
+#[cfg(not(test))]
+extern "C" {
+    fn tigermain(static_link: i64) -> i64;
+}
+
+/// The process entry point: runs the compiled program.
+///
+/// # Safety
+///
+/// Only to be called by the C startup code, once.
+#[cfg(not(test))]
+#[no_mangle]
+pub unsafe extern "C" fn main() -> i32 {
+    let top = 0u8;
+    stack::install(&top as *const u8 as usize);
+    tigermain(0);
+    let _ = io::stdout().flush();
+    gc::report();
+    0
+}
@@ -0,0 +1,61 @@
+//! Reports a program running out of stack as a runtime error instead of a
+//! crash. The stack ends in a guard gap, so overflowing it faults; the
+//! handler runs on a stack of its own, there being none left, and tells
+//! such faults from others by their address.
+
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bytes of the stack the handler runs on.
+const SIGNAL_STACK: usize = 64 << 10;
+
+/// Bytes below the stack limit that the kernel keeps unmapped.
+const GUARD: usize = 1 << 20;
+
+/// The addresses a stack overflow faults at.
+static LOW: AtomicUsize = AtomicUsize::new(0);
+static HIGH: AtomicUsize = AtomicUsize::new(0);
+
+/// Installs the handler, for the stack `top` is near the top of. Failing
+/// that, an overflow crashes the program as it would have.
+pub unsafe fn install(top: usize) {
+    let mut limit: libc::rlimit = std::mem::zeroed();
+    let size = match libc::getrlimit(libc::RLIMIT_STACK, &mut limit) {
+        0 if limit.rlim_cur != libc::RLIM_INFINITY => limit.rlim_cur as usize,
+        _ => top,
+    };
+    LOW.store(top.saturating_sub(size + GUARD), Ordering::Relaxed);
+    HIGH.store(top, Ordering::Relaxed);
+    let stack = libc::mmap(
+        ptr::null_mut(),
+        SIGNAL_STACK,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+        -1,
+        0,
+    );
+    if stack == libc::MAP_FAILED {
+        return;
+    }
+    let signal_stack = libc::stack_t {
+        ss_sp: stack,
+        ss_flags: 0,
+        ss_size: SIGNAL_STACK,
+    };
+    if libc::sigaltstack(&signal_stack, ptr::null_mut()) != 0 {
+        return;
+    }
+    let mut action: libc::sigaction = std::mem::zeroed();
+    action.sa_sigaction = handle as *const () as usize;
+    action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK;
+    libc::sigaction(libc::SIGSEGV, &action, ptr::null_mut());
+}
+
+extern "C" fn handle(_: libc::c_int, info: *mut libc::siginfo_t, _: *mut libc::c_void) {
+    let address = unsafe { (*info).si_addr() } as usize;
+    if (LOW.load(Ordering::Relaxed)..HIGH.load(Ordering::Relaxed)).contains(&address) {
+        crate::fatal("stack overflow");
+    }
+    // Any other fault takes the program down once the handler returns.
+    unsafe { libc::signal(libc::SIGSEGV, libc::SIG_DFL) };
+}
@@ -0,0 +1,121 @@
+use crate::temp::temp::{Label, Temp};
+
+/// A machine instruction with its registers left abstract.
+///
+/// In `assem`, `` `s0 `` names the first source temp, `` `d0 `` the first
+/// destination and `` `j0 `` the first jump target.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instr {
+    Oper {
+        assem: String,
+        dst: Vec<Temp>,
+        src: Vec<Temp>,
+        /// Where control may go next; `None` means the next instruction.
+        jump: Option<Vec<Label>>,
+    },
+    Label {
+        assem: String,
+        label: Label,
+    },
+    /// A register-to-register copy, which the allocator may remove.
+    Move {
+        assem: String,
+        dst: Temp,
+        src: Temp,
+    },
+}
+
+impl Instr {
+    pub fn oper(assem: impl Into<String>, dst: Vec<Temp>, src: Vec<Temp>) -> Instr {
+        Instr::Oper {
+            assem: assem.into(),
+            dst,
+            src,
+            jump: None,
+        }
+    }
+
+    pub fn defs(&self) -> &[Temp] {
+        match self {
+            Instr::Oper { dst, .. } => dst,
+            Instr::Label { .. } => &[],
+            Instr::Move { dst, .. } => std::slice::from_ref(dst),
+        }
+    }
+
+    pub fn uses(&self) -> &[Temp] {
+        match self {
+            Instr::Oper { src, .. } => src,
+            Instr::Label { .. } => &[],
+            Instr::Move { src, .. } => std::slice::from_ref(src),
+        }
+    }
+
+    /// Renames every temp with `f`, keeping the instruction otherwise as is.
+    pub fn map_temps(self, mut f: impl FnMut(Temp) -> Temp) -> Instr {
+        match self {
+            Instr::Oper {
+                assem,
+                dst,
+                src,
+                jump,
+            } => Instr::Oper {
+                assem,
+                dst: dst.into_iter().map(&mut f).collect(),
+                src: src.into_iter().map(&mut f).collect(),
+                jump,
+            },
+            Instr::Move { assem, dst, src } => Instr::Move {
+                assem,
+                dst: f(dst),
+                src: f(src),
+            },
+            label => label,
+        }
+    }
+
+    /// The assembly text, with temps named by `name`.
+    pub fn format(&self, name: impl Fn(Temp) -> String) -> String {
+        let (assem, dst, src, jump): (_, &[Temp], &[Temp], &[Label]) = match self {
+            Instr::Oper {
+                assem,
+                dst,
+                src,
+                jump,
+            } => (assem, dst, src, jump.as_deref().unwrap_or(&[])),
+            Instr::Label { assem, .. } => (assem, &[], &[], &[]),
+            Instr::Move { assem, dst, src } => (
+                assem,
+                std::slice::from_ref(dst),
+                std::slice::from_ref(src),
+                &[],
+            ),
+        };
+        let mut out = String::new();
+        let mut chars = assem.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '`' {
+                out.push(c);
+                continue;
+            }
+            let kind = chars.next().expect("dangling ` in assembly");
+            let mut index = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                index.push(digit);
+            }
+            let index: usize = index.parse().expect("` without an operand index");
+            match kind {
+                's' => out.push_str(&name(src[index])),
+                'd' => out.push_str(&name(dst[index])),
+                'j' => out.push_str(jump[index].name()),
+                _ => panic!("unknown operand kind `{}", kind),
+            }
+        }
+        out
+    }
+
+    /// Whether this is a move from a temp to itself once named by `name`.
+    pub fn is_redundant_move(&self, name: impl Fn(Temp) -> String) -> bool {
+        matches!(self, Instr::Move { dst, src, .. } if name(*dst) == name(*src))
+    }
+}
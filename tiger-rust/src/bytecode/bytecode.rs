@@ -0,0 +1,495 @@
+//! A compact bytecode for Tiger programs.
+//!
+//! A [`Module`] holds a constant pool and a function table whose first entry
+//! is the main program. Each function's code is a byte string of opcodes,
+//! each followed by its operands in little-endian order, and comes with a
+//! line table mapping code offsets back to source positions.
+//!
+//! Every function's frame is a row of local slots, parameters first, below
+//! its operand stack. Variables of enclosing functions are reached by
+//! following static links a known number of hops.
+
+/// An instruction. The comment of each names its operands, and what it pops
+/// and pushes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Op {
+    /// `const k:u16`: pushes constant `k`.
+    Const,
+    /// Pushes nil.
+    Nil,
+    /// Pops a value.
+    Pop,
+    /// `load slot:u16`: pushes a local.
+    Load,
+    /// `store slot:u16`: pops into a local.
+    Store,
+    /// `load_outer hops:u8 slot:u16`: pushes a local of an enclosing frame.
+    LoadOuter,
+    /// `store_outer hops:u8 slot:u16`: pops into a local of an enclosing
+    /// frame.
+    StoreOuter,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    /// Compare ints, or records and arrays by identity.
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Compare strings by contents.
+    StrEq,
+    StrNe,
+    StrLt,
+    StrLe,
+    StrGt,
+    StrGe,
+    /// `jump target:u32`.
+    Jump,
+    /// `jump_if_false target:u32`: pops a condition.
+    JumpIfFalse,
+    /// `record n:u16`: pops `n` fields, pushes a new record.
+    Record,
+    /// Pops a size and an initial value, pushes a new array.
+    Array,
+    /// `get_field i:u16`: pops a record, pushes its field.
+    GetField,
+    /// `set_field i:u16`: pops a value and a record.
+    SetField,
+    /// Pops an index and an array, pushes the element.
+    GetElem,
+    /// Pops a value, an index and an array.
+    SetElem,
+    /// `call f:u16 hops:u8`: calls function `f` on the arguments at the top
+    /// of the stack, its static link being the frame `hops` links out.
+    Call,
+    /// `builtin b:u8`: calls a [`Builtin`] on the arguments at the top of
+    /// the stack.
+    Builtin,
+    /// Returns from the function, with the value at the top of the stack if
+    /// it has one.
+    Return,
+}
+
+const OPS: [Op; 34] = [
+    Op::Const,
+    Op::Nil,
+    Op::Pop,
+    Op::Load,
+    Op::Store,
+    Op::LoadOuter,
+    Op::StoreOuter,
+    Op::Add,
+    Op::Sub,
+    Op::Mul,
+    Op::Div,
+    Op::Eq,
+    Op::Ne,
+    Op::Lt,
+    Op::Le,
+    Op::Gt,
+    Op::Ge,
+    Op::StrEq,
+    Op::StrNe,
+    Op::StrLt,
+    Op::StrLe,
+    Op::StrGt,
+    Op::StrGe,
+    Op::Jump,
+    Op::JumpIfFalse,
+    Op::Record,
+    Op::Array,
+    Op::GetField,
+    Op::SetField,
+    Op::GetElem,
+    Op::SetElem,
+    Op::Call,
+    Op::Builtin,
+    Op::Return,
+];
+
+impl Op {
+    pub fn from_u8(byte: u8) -> Option<Op> {
+        OPS.get(byte as usize).copied()
+    }
+
+    /// Byte widths of the operands.
+    pub fn operands(self) -> &'static [usize] {
+        match self {
+            Op::Const | Op::Load | Op::Store | Op::Record | Op::GetField | Op::SetField => &[2],
+            Op::LoadOuter | Op::StoreOuter => &[1, 2],
+            Op::Jump | Op::JumpIfFalse => &[4],
+            Op::Call => &[2, 1],
+            Op::Builtin => &[1],
+            _ => &[],
+        }
+    }
+
+    /// Bytes taken by the opcode and its operands.
+    pub fn size(self) -> usize {
+        match self {
+            Op::Const | Op::Load | Op::Store | Op::Record | Op::GetField | Op::SetField => 3,
+            Op::LoadOuter | Op::StoreOuter => 4,
+            Op::Jump | Op::JumpIfFalse => 5,
+            Op::Call => 4,
+            Op::Builtin => 2,
+            _ => 1,
+        }
+    }
+
+    /// Whether the instruction can stop the program with an error.
+    pub fn can_fail(self) -> bool {
+        matches!(
+            self,
+            Op::Div
+                | Op::Record
+                | Op::Array
+                | Op::GetField
+                | Op::SetField
+                | Op::GetElem
+                | Op::SetElem
+                | Op::Call
+                | Op::Builtin
+        )
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Op::Const => "const",
+            Op::Nil => "nil",
+            Op::Pop => "pop",
+            Op::Load => "load",
+            Op::Store => "store",
+            Op::LoadOuter => "load_outer",
+            Op::StoreOuter => "store_outer",
+            Op::Add => "add",
+            Op::Sub => "sub",
+            Op::Mul => "mul",
+            Op::Div => "div",
+            Op::Eq => "eq",
+            Op::Ne => "ne",
+            Op::Lt => "lt",
+            Op::Le => "le",
+            Op::Gt => "gt",
+            Op::Ge => "ge",
+            Op::StrEq => "str_eq",
+            Op::StrNe => "str_ne",
+            Op::StrLt => "str_lt",
+            Op::StrLe => "str_le",
+            Op::StrGt => "str_gt",
+            Op::StrGe => "str_ge",
+            Op::Jump => "jump",
+            Op::JumpIfFalse => "jump_if_false",
+            Op::Record => "record",
+            Op::Array => "array",
+            Op::GetField => "get_field",
+            Op::SetField => "set_field",
+            Op::GetElem => "get_elem",
+            Op::SetElem => "set_elem",
+            Op::Call => "call",
+            Op::Builtin => "builtin",
+            Op::Return => "return",
+        }
+    }
+}
+
+/// The standard library functions, called with [`Op::Builtin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Builtin {
+    Print,
+    Flush,
+    Getchar,
+    Ord,
+    Chr,
+    Size,
+    Substring,
+    Concat,
+    Not,
+    Exit,
+}
+
+const BUILTINS: [Builtin; 10] = [
+    Builtin::Print,
+    Builtin::Flush,
+    Builtin::Getchar,
+    Builtin::Ord,
+    Builtin::Chr,
+    Builtin::Size,
+    Builtin::Substring,
+    Builtin::Concat,
+    Builtin::Not,
+    Builtin::Exit,
+];
+
+impl Builtin {
+    pub fn from_u8(byte: u8) -> Option<Builtin> {
+        BUILTINS.get(byte as usize).copied()
+    }
+
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        BUILTINS
+            .iter()
+            .copied()
+            .find(|builtin| builtin.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::Print => "print",
+            Builtin::Flush => "flush",
+            Builtin::Getchar => "getchar",
+            Builtin::Ord => "ord",
+            Builtin::Chr => "chr",
+            Builtin::Size => "size",
+            Builtin::Substring => "substring",
+            Builtin::Concat => "concat",
+            Builtin::Not => "not",
+            Builtin::Exit => "exit",
+        }
+    }
+
+    pub fn arity(self) -> usize {
+        match self {
+            Builtin::Flush | Builtin::Getchar => 0,
+            Builtin::Substring => 3,
+            Builtin::Concat => 2,
+            _ => 1,
+        }
+    }
+
+    /// Whether the builtin pushes a result.
+    pub fn returns(self) -> bool {
+        !matches!(self, Builtin::Print | Builtin::Flush | Builtin::Exit)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Const {
+    Int(i64),
+    String(Vec<u8>),
+}
+
+/// The source position of the code from `pc` up to the next entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line {
+    pub pc: u32,
+    pub line: u32,
+    pub col: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub name: String,
+    pub params: u8,
+    /// Slots in the frame, parameters included.
+    pub locals: u16,
+    /// Whether the function returns a value.
+    pub returns: bool,
+    pub code: Vec<u8>,
+    pub lines: Vec<Line>,
+}
+
+impl Function {
+    /// The source position of the instruction at `pc`.
+    pub fn line(&self, pc: usize) -> Option<Line> {
+        let next = self.lines.partition_point(|line| line.pc as usize <= pc);
+        next.checked_sub(1).map(|i| self.lines[i])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub consts: Vec<Const>,
+    /// The functions, the main program first.
+    pub funcs: Vec<Function>,
+}
+
+const MAGIC: &[u8; 4] = b"TIGB";
+const VERSION: u8 = 1;
+
+/// Reads the little-endian operand of `width` bytes at `pc`.
+pub fn operand(code: &[u8], pc: usize, width: usize) -> usize {
+    let mut bytes = [0; 8];
+    bytes[..width].copy_from_slice(&code[pc..pc + width]);
+    u64::from_le_bytes(bytes) as usize
+}
+
+impl Module {
+    /// The module as a byte string, which [`Module::decode`] reads back.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        out.extend((self.consts.len() as u32).to_le_bytes());
+        for constant in &self.consts {
+            match constant {
+                Const::Int(i) => {
+                    out.push(0);
+                    out.extend(i.to_le_bytes());
+                }
+                Const::String(s) => {
+                    out.push(1);
+                    out.extend((s.len() as u32).to_le_bytes());
+                    out.extend(s);
+                }
+            }
+        }
+        out.extend((self.funcs.len() as u32).to_le_bytes());
+        for func in &self.funcs {
+            out.extend((func.name.len() as u32).to_le_bytes());
+            out.extend(func.name.as_bytes());
+            out.push(func.params);
+            out.extend(func.locals.to_le_bytes());
+            out.push(func.returns as u8);
+            out.extend((func.code.len() as u32).to_le_bytes());
+            out.extend(&func.code);
+            out.extend((func.lines.len() as u32).to_le_bytes());
+            for line in &func.lines {
+                out.extend(line.pc.to_le_bytes());
+                out.extend(line.line.to_le_bytes());
+                out.extend(line.col.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    /// Reads a module written by [`Module::encode`], checking that its code
+    /// only refers to constants, functions and offsets that exist.
+    pub fn decode(bytes: &[u8]) -> Result<Module, String> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(4)? != MAGIC {
+            return Err(String::from("not a Tiger bytecode module"));
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(format!("unsupported bytecode version {}", version));
+        }
+        let consts = (0..reader.u32()?)
+            .map(|_| match reader.u8()? {
+                0 => Ok(Const::Int(reader.u64()? as i64)),
+                1 => {
+                    let len = reader.u32()? as usize;
+                    Ok(Const::String(reader.take(len)?.to_vec()))
+                }
+                tag => Err(format!("bad constant tag {}", tag)),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let funcs = (0..reader.u32()?)
+            .map(|_| {
+                let len = reader.u32()? as usize;
+                let name = String::from_utf8(reader.take(len)?.to_vec())
+                    .map_err(|_| String::from("bad function name"))?;
+                let params = reader.u8()?;
+                let locals = reader.u16()?;
+                let returns = reader.u8()? != 0;
+                let len = reader.u32()? as usize;
+                let code = reader.take(len)?.to_vec();
+                let lines = (0..reader.u32()?)
+                    .map(|_| {
+                        Ok(Line {
+                            pc: reader.u32()?,
+                            line: reader.u32()?,
+                            col: reader.u32()?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(Function {
+                    name,
+                    params,
+                    locals,
+                    returns,
+                    code,
+                    lines,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if reader.pos != bytes.len() {
+            return Err(String::from("trailing bytes after module"));
+        }
+        let module = Module { consts, funcs };
+        module.verify()?;
+        Ok(module)
+    }
+
+    fn verify(&self) -> Result<(), String> {
+        if self.funcs.is_empty() {
+            return Err(String::from("no main function"));
+        }
+        for func in &self.funcs {
+            let error = |pc: usize, msg: &str| Err(format!("{} at {}: {}", func.name, pc, msg));
+            if func.params as u16 > func.locals {
+                return error(0, "more parameters than locals");
+            }
+            let mut starts = Vec::new();
+            let mut pc = 0;
+            while pc < func.code.len() {
+                let Some(op) = Op::from_u8(func.code[pc]) else {
+                    return error(pc, "bad opcode");
+                };
+                if pc + op.size() > func.code.len() {
+                    return error(pc, "truncated instruction");
+                }
+                starts.push((pc, op));
+                pc += op.size();
+            }
+            for &(pc, op) in &starts {
+                let arg = |i: usize| {
+                    let offset: usize = op.operands()[..i].iter().sum();
+                    operand(&func.code, pc + 1 + offset, op.operands()[i])
+                };
+                let ok = match op {
+                    Op::Const => arg(0) < self.consts.len(),
+                    Op::Load | Op::Store => arg(0) < func.locals as usize,
+                    Op::Jump | Op::JumpIfFalse => starts
+                        .binary_search_by_key(&arg(0), |(start, _)| *start)
+                        .is_ok(),
+                    Op::Call => arg(0) < self.funcs.len(),
+                    Op::Builtin => Builtin::from_u8(arg(0) as u8).is_some(),
+                    _ => true,
+                };
+                if !ok {
+                    return error(pc, "operand out of range");
+                }
+            }
+            if starts.last().map(|(_, op)| *op) != Some(Op::Return) {
+                return error(pc, "code does not end in return");
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| String::from("truncated module"))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(operand(self.take(2)?, 0, 2) as u16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(operand(self.take(4)?, 0, 4) as u32)
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(operand(self.take(8)?, 0, 8) as u64)
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+
+use crate::bytecode::bytecode::{Builtin, Const, Function, Line, Module, Op};
+use crate::error::error::{PosInfo, MAX_COL};
+use crate::semant::tast::{
+    FunId, FunKind, Oper, Program, TDec, TExp, TExpKind, TVar, TVarKind, VarId,
+};
+use crate::semant::types::Type;
+
+/// Compiles a type-checked program to bytecode.
+pub fn compile(program: &Program) -> Module {
+    let mut compiler = Compiler {
+        program,
+        consts: Vec::new(),
+        const_index: HashMap::new(),
+        funcs: HashMap::new(),
+        queue: Vec::new(),
+        slots: HashMap::new(),
+    };
+    compiler.index(program.main);
+    let mut funcs = Vec::new();
+    while funcs.len() < compiler.queue.len() {
+        let fun = compiler.queue[funcs.len()];
+        funcs.push(compiler.function(fun));
+    }
+    Module {
+        consts: compiler.consts,
+        funcs,
+    }
+}
+
+struct Compiler<'a> {
+    program: &'a Program,
+    consts: Vec<Const>,
+    const_index: HashMap<Const, u16>,
+    /// Index in the function table of each function seen so far.
+    funcs: HashMap<FunId, u16>,
+    /// The functions in table order; those past the ones compiled so far
+    /// are still to be compiled.
+    queue: Vec<FunId>,
+    slots: HashMap<VarId, u16>,
+}
+
+/// The bytecode of the function being compiled, with its line table.
+struct Body {
+    fun: FunId,
+    code: Vec<u8>,
+    lines: Vec<Line>,
+    locals: u16,
+    /// Values on the operand stack at this point of the code.
+    depth: usize,
+    /// For each enclosing loop, the stack depth at its start and the jumps
+    /// of its `break`s, to patch with its end.
+    loops: Vec<(usize, Vec<usize>)>,
+}
+
+impl Body {
+    /// Emits `op` with `operands` of the widths it expects, noting that
+    /// `pos` is where it comes from.
+    fn emit(&mut self, op: Op, operands: &[usize], pos: &PosInfo) {
+        let line = Line {
+            pc: self.code.len() as u32,
+            line: pos.line as u32,
+            col: pos.col.min(MAX_COL) as u32,
+        };
+        match self.lines.last() {
+            Some(last) if (last.line, last.col) == (line.line, line.col) => (),
+            _ => self.lines.push(line),
+        }
+        self.code.push(op as u8);
+        for (value, width) in operands.iter().zip(op.operands()) {
+            self.code.extend_from_slice(&value.to_le_bytes()[..*width]);
+        }
+        self.depth = match op {
+            Op::Const | Op::Nil | Op::Load | Op::LoadOuter => self.depth + 1,
+            Op::Record => self.depth + 1 - operands[0],
+            Op::SetField => self.depth - 2,
+            Op::SetElem => self.depth - 3,
+            Op::GetField | Op::Jump | Op::Call | Op::Builtin | Op::Return => self.depth,
+            _ => self.depth - 1,
+        };
+    }
+
+    /// Emits a jump to be patched, returning where its target goes.
+    fn jump(&mut self, op: Op, pos: &PosInfo) -> usize {
+        self.emit(op, &[0], pos);
+        self.code.len() - 4
+    }
+
+    /// Points the jump at `site` to the end of the code.
+    fn patch(&mut self, site: usize) {
+        let target = self.code.len() as u32;
+        self.code[site..site + 4].copy_from_slice(&target.to_le_bytes());
+    }
+
+    fn slot(&mut self) -> u16 {
+        self.locals += 1;
+        self.locals - 1
+    }
+}
+
+impl Compiler<'_> {
+    /// The index of `fun` in the function table, queueing it for
+    /// compilation the first time.
+    fn index(&mut self, fun: FunId) -> u16 {
+        if let Some(index) = self.funcs.get(&fun) {
+            return *index;
+        }
+        let index = self.queue.len() as u16;
+        self.funcs.insert(fun, index);
+        self.queue.push(fun);
+        index
+    }
+
+    fn constant(&mut self, constant: Const) -> usize {
+        if let Some(index) = self.const_index.get(&constant) {
+            return *index as usize;
+        }
+        let index = u16::try_from(self.consts.len()).expect("too many constants");
+        self.consts.push(constant.clone());
+        self.const_index.insert(constant, index);
+        index as usize
+    }
+
+    fn function(&mut self, fun: FunId) -> Function {
+        let info = self.program.func(fun);
+        let FunKind::User { body } = &info.kind else {
+            unreachable!("builtins are not compiled")
+        };
+        let mut code = Body {
+            fun,
+            code: Vec::new(),
+            lines: Vec::new(),
+            locals: 0,
+            depth: 0,
+            loops: Vec::new(),
+        };
+        for param in &info.params {
+            let slot = code.slot();
+            self.slots.insert(*param, slot);
+        }
+        self.exp(&mut code, body);
+        code.emit(Op::Return, &[], &body.pos);
+        Function {
+            name: info.name.clone(),
+            params: info.params.len() as u8,
+            locals: code.locals,
+            returns: self.has_value(body),
+            code: code.code,
+            lines: code.lines,
+        }
+    }
+
+    fn has_value(&self, exp: &TExp) -> bool {
+        self.program.types.actual(exp.ty) != Type::Unit
+    }
+
+    /// Compiles `exp`, leaving its value on the stack if it has one.
+    fn exp(&mut self, code: &mut Body, exp: &TExp) {
+        let pos = &exp.pos;
+        match &exp.kind {
+            TExpKind::Int(i) => {
+                let k = self.constant(Const::Int(*i));
+                code.emit(Op::Const, &[k], pos);
+            }
+            TExpKind::String(value) => {
+                let k = self.constant(Const::String(value.as_bytes().to_vec()));
+                code.emit(Op::Const, &[k], pos);
+            }
+            TExpKind::Nil => code.emit(Op::Nil, &[], pos),
+            TExpKind::Var(var) => self.var(code, var),
+            TExpKind::Call(fun, args) => {
+                for arg in args {
+                    self.value(code, arg);
+                }
+                let info = self.program.func(*fun);
+                let (op, operands) = match &info.kind {
+                    FunKind::Builtin => {
+                        let builtin = Builtin::from_name(&info.name).expect("unknown builtin");
+                        (Op::Builtin, vec![builtin as usize])
+                    }
+                    FunKind::User { .. } => {
+                        let hops = self.program.depth(code.fun) + 1 - self.program.depth(*fun);
+                        (Op::Call, vec![self.index(*fun) as usize, hops])
+                    }
+                };
+                code.emit(op, &operands, pos);
+                code.depth -= args.len();
+                if self.has_value(exp) {
+                    code.depth += 1;
+                }
+            }
+            TExpKind::Op(oper, left, right) => {
+                self.exp(code, left);
+                self.exp(code, right);
+                let string = self.program.types.actual(left.ty) == Type::String;
+                let op = match (oper, string) {
+                    (Oper::Plus, _) => Op::Add,
+                    (Oper::Minus, _) => Op::Sub,
+                    (Oper::Times, _) => Op::Mul,
+                    (Oper::Divide, _) => Op::Div,
+                    (Oper::Eq, false) => Op::Eq,
+                    (Oper::Neq, false) => Op::Ne,
+                    (Oper::Lt, false) => Op::Lt,
+                    (Oper::Le, false) => Op::Le,
+                    (Oper::Gt, false) => Op::Gt,
+                    (Oper::Ge, false) => Op::Ge,
+                    (Oper::Eq, true) => Op::StrEq,
+                    (Oper::Neq, true) => Op::StrNe,
+                    (Oper::Lt, true) => Op::StrLt,
+                    (Oper::Le, true) => Op::StrLe,
+                    (Oper::Gt, true) => Op::StrGt,
+                    (Oper::Ge, true) => Op::StrGe,
+                };
+                code.emit(op, &[], pos);
+            }
+            TExpKind::And(left, right) => {
+                self.exp(code, left);
+                let left_false = code.jump(Op::JumpIfFalse, pos);
+                self.exp(code, right);
+                let right_false = code.jump(Op::JumpIfFalse, pos);
+                self.boolean(code, &[left_false, right_false], pos);
+            }
+            TExpKind::Or(left, right) => {
+                self.exp(code, left);
+                let left_false = code.jump(Op::JumpIfFalse, pos);
+                let one = self.constant(Const::Int(1));
+                code.emit(Op::Const, &[one], pos);
+                let left_true = code.jump(Op::Jump, pos);
+                code.depth -= 1;
+                code.patch(left_false);
+                self.exp(code, right);
+                let right_false = code.jump(Op::JumpIfFalse, pos);
+                self.boolean(code, &[right_false], pos);
+                code.patch(left_true);
+            }
+            TExpKind::Record(_, fields) => {
+                for field in fields {
+                    self.value(code, field);
+                }
+                code.emit(Op::Record, &[fields.len()], pos);
+            }
+            TExpKind::Array(_, size, init) => {
+                self.exp(code, size);
+                self.value(code, init);
+                code.emit(Op::Array, &[], pos);
+            }
+            TExpKind::Seq(exps) => {
+                for (i, exp) in exps.iter().enumerate() {
+                    self.exp(code, exp);
+                    if i + 1 < exps.len() && self.has_value(exp) {
+                        code.emit(Op::Pop, &[], &exp.pos);
+                    }
+                }
+            }
+            TExpKind::Assign(var, value) => self.assign(code, var, value),
+            TExpKind::If(test, then, els) => {
+                self.exp(code, test);
+                let skip_then = code.jump(Op::JumpIfFalse, pos);
+                let depth = code.depth;
+                self.exp(code, then);
+                match els {
+                    Some(els) => {
+                        let skip_else = code.jump(Op::Jump, pos);
+                        code.depth = depth;
+                        code.patch(skip_then);
+                        self.exp(code, els);
+                        code.patch(skip_else);
+                    }
+                    None => code.patch(skip_then),
+                }
+            }
+            TExpKind::While(test, body) => {
+                let top = code.code.len();
+                self.exp(code, test);
+                let done = code.jump(Op::JumpIfFalse, pos);
+                code.loops.push((code.depth, vec![done]));
+                self.exp(code, body);
+                code.emit(Op::Jump, &[top], pos);
+                self.end_loop(code);
+            }
+            TExpKind::For(var, lo, hi, body) => {
+                let i = code.slot();
+                self.slots.insert(*var, i);
+                let limit = code.slot() as usize;
+                let i = i as usize;
+                self.exp(code, lo);
+                code.emit(Op::Store, &[i], pos);
+                self.exp(code, hi);
+                code.emit(Op::Store, &[limit], pos);
+                code.emit(Op::Load, &[i], pos);
+                code.emit(Op::Load, &[limit], pos);
+                code.emit(Op::Le, &[], pos);
+                let empty = code.jump(Op::JumpIfFalse, pos);
+                code.loops.push((code.depth, vec![empty]));
+                let top = code.code.len();
+                self.exp(code, body);
+                code.emit(Op::Load, &[i], pos);
+                code.emit(Op::Load, &[limit], pos);
+                code.emit(Op::Lt, &[], pos);
+                let done = code.jump(Op::JumpIfFalse, pos);
+                code.loops.last_mut().unwrap().1.push(done);
+                let one = self.constant(Const::Int(1));
+                code.emit(Op::Load, &[i], pos);
+                code.emit(Op::Const, &[one], pos);
+                code.emit(Op::Add, &[], pos);
+                code.emit(Op::Store, &[i], pos);
+                code.emit(Op::Jump, &[top], pos);
+                self.end_loop(code);
+            }
+            TExpKind::Break => {
+                // The code after the break is never reached, so the depth
+                // it sees is that before the pops.
+                let depth = code.depth;
+                let (start, _) = code.loops.last().expect("break outside a loop");
+                for _ in *start..depth {
+                    code.emit(Op::Pop, &[], pos);
+                }
+                let site = code.jump(Op::Jump, pos);
+                code.loops.last_mut().unwrap().1.push(site);
+                code.depth = depth;
+            }
+            TExpKind::Let(decs, body) => {
+                for dec in decs {
+                    match dec {
+                        TDec::Var(var, init) => {
+                            self.value(code, init);
+                            let slot = code.slot();
+                            self.slots.insert(*var, slot);
+                            code.emit(Op::Store, &[slot as usize], &init.pos);
+                        }
+                        TDec::Funcs(funs) => {
+                            for fun in funs {
+                                self.index(*fun);
+                            }
+                        }
+                    }
+                }
+                self.exp(code, body);
+            }
+        }
+    }
+
+    /// Compiles `exp`, pushing nil in place of a missing value.
+    fn value(&mut self, code: &mut Body, exp: &TExp) {
+        self.exp(code, exp);
+        if !self.has_value(exp) {
+            code.emit(Op::Nil, &[], &exp.pos);
+        }
+    }
+
+    /// Finishes a condition whose false `jumps` go to pushing 0, with 1
+    /// pushed otherwise.
+    fn boolean(&mut self, code: &mut Body, jumps: &[usize], pos: &PosInfo) {
+        let one = self.constant(Const::Int(1));
+        let zero = self.constant(Const::Int(0));
+        code.emit(Op::Const, &[one], pos);
+        let done = code.jump(Op::Jump, pos);
+        code.depth -= 1;
+        for site in jumps {
+            code.patch(*site);
+        }
+        code.emit(Op::Const, &[zero], pos);
+        code.patch(done);
+    }
+
+    fn end_loop(&mut self, code: &mut Body) {
+        let (depth, sites) = code.loops.pop().unwrap();
+        for site in sites {
+            code.patch(site);
+        }
+        code.depth = depth;
+    }
+
+    /// The slot of `var` and how many static links away its frame is.
+    fn local(&self, code: &Body, var: VarId) -> (usize, usize) {
+        let hops = self.program.depth(code.fun) - self.program.depth(self.program.var(var).fun);
+        (self.slots[&var] as usize, hops)
+    }
+
+    fn var(&mut self, code: &mut Body, var: &TVar) {
+        match &var.kind {
+            TVarKind::Simple(id) => match self.local(code, *id) {
+                (slot, 0) => code.emit(Op::Load, &[slot], &var.pos),
+                (slot, hops) => code.emit(Op::LoadOuter, &[hops, slot], &var.pos),
+            },
+            TVarKind::Field(record, index) => {
+                self.exp(code, record);
+                code.emit(Op::GetField, &[*index], &record.pos);
+            }
+            TVarKind::Subscript(array, index) => {
+                self.exp(code, array);
+                self.exp(code, index);
+                code.emit(Op::GetElem, &[], &index.pos);
+            }
+        }
+    }
+
+    fn assign(&mut self, code: &mut Body, var: &TVar, value: &TExp) {
+        match &var.kind {
+            TVarKind::Simple(id) => {
+                self.value(code, value);
+                match self.local(code, *id) {
+                    (slot, 0) => code.emit(Op::Store, &[slot], &var.pos),
+                    (slot, hops) => code.emit(Op::StoreOuter, &[hops, slot], &var.pos),
+                }
+            }
+            TVarKind::Field(record, index) => {
+                self.exp(code, record);
+                self.value(code, value);
+                code.emit(Op::SetField, &[*index], &record.pos);
+            }
+            TVarKind::Subscript(array, index) => {
+                self.exp(code, array);
+                self.exp(code, index);
+                self.value(code, value);
+                code.emit(Op::SetElem, &[], &index.pos);
+            }
+        }
+    }
+}
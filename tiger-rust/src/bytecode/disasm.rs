@@ -0,0 +1,68 @@
+use std::fmt::Write;
+
+use crate::bytecode::bytecode::{operand, Builtin, Const, Module, Op};
+
+fn constant(constant: &Const) -> String {
+    match constant {
+        Const::Int(i) => i.to_string(),
+        Const::String(s) => format!("{:?}", String::from_utf8_lossy(s)),
+    }
+}
+
+/// A listing of `module`: its constants, then the code of each function
+/// with the source position wherever it changes.
+pub fn disassemble(module: &Module) -> String {
+    let mut out = String::from("constants:\n");
+    for (k, c) in module.consts.iter().enumerate() {
+        writeln!(out, "  {:>4}  {}", k, constant(c)).unwrap();
+    }
+    for (f, func) in module.funcs.iter().enumerate() {
+        writeln!(
+            out,
+            "function {} {} (params {}, locals {}{}):",
+            f,
+            func.name,
+            func.params,
+            func.locals,
+            if func.returns { ", returns" } else { "" }
+        )
+        .unwrap();
+        let mut lines = func.lines.iter().peekable();
+        let mut pc = 0;
+        while pc < func.code.len() {
+            let op = Op::from_u8(func.code[pc]).expect("bad opcode");
+            let pos = match lines.next_if(|line| line.pc as usize == pc) {
+                Some(line) => format!("{}.{}", line.line, line.col),
+                None => String::new(),
+            };
+            let mut args = Vec::new();
+            let mut offset = pc + 1;
+            for width in op.operands() {
+                args.push(operand(&func.code, offset, *width));
+                offset += width;
+            }
+            let text = args
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let comment = match op {
+                Op::Const => format!("  ; {}", constant(&module.consts[args[0]])),
+                Op::Call => format!("  ; {}", module.funcs[args[0]].name),
+                Op::Builtin => format!("  ; {}", Builtin::from_u8(args[0] as u8).unwrap().name()),
+                _ => String::new(),
+            };
+            let line = format!(
+                "  {:>5}  {:<8} {:<14}{}{}",
+                pc,
+                pos,
+                op.name(),
+                text,
+                comment
+            );
+            writeln!(out, "{}", line.trim_end()).unwrap();
+            pc += op.size();
+        }
+    }
+    out
+}
@@ -0,0 +1,4 @@
+pub mod bytecode;
+pub mod compile;
+pub mod disasm;
+pub mod vm;
@@ -0,0 +1,584 @@
+use std::io::{Read, Write};
+
+use crate::bytecode::bytecode::{operand, Builtin, Const, Module, Op};
+use crate::interp::interp::backtrace;
+use crate::interp::limits::{Limits, Meter};
+
+/// A value on the stack or in the heap. Strings, records and arrays are
+/// indices of heap objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Nil,
+    Ref(u32),
+}
+
+#[derive(Debug)]
+enum Object {
+    String(Box<[u8]>),
+    Record(Box<[Value]>),
+    Array(Box<[Value]>),
+    Free,
+}
+
+impl Object {
+    /// Bytes of the contents, as counted against the heap limit.
+    fn size(&self) -> usize {
+        match self {
+            Object::String(s) => s.len(),
+            Object::Record(values) | Object::Array(values) => 8 * values.len(),
+            Object::Free => 0,
+        }
+    }
+}
+
+/// Objects are never moved; a mark-sweep collection frees the unreachable
+/// ones for reuse.
+struct Heap {
+    objects: Vec<Object>,
+    marks: Vec<bool>,
+    free: Vec<u32>,
+    live: usize,
+    /// Live objects at which to collect next.
+    threshold: usize,
+    collections: usize,
+}
+
+/// Objects the heap holds before its first collection.
+const MIN_THRESHOLD: usize = 4096;
+
+impl Heap {
+    fn alloc(&mut self, object: Object) -> Value {
+        self.live += 1;
+        match self.free.pop() {
+            Some(index) => {
+                self.objects[index as usize] = object;
+                Value::Ref(index)
+            }
+            None => {
+                self.objects.push(object);
+                self.marks.push(false);
+                Value::Ref(self.objects.len() as u32 - 1)
+            }
+        }
+    }
+
+    fn string(&self, value: Value) -> &[u8] {
+        match value {
+            Value::Ref(index) => match &self.objects[index as usize] {
+                Object::String(s) => s,
+                object => unreachable!("{:?} is not a string", object),
+            },
+            value => unreachable!("{:?} is not a string", value),
+        }
+    }
+
+    /// Frees the objects not reachable from `roots`, returning the bytes
+    /// they held.
+    fn collect(&mut self, roots: impl Iterator<Item = Value>) -> usize {
+        let mut gray: Vec<u32> = Vec::new();
+        let mark = |value: Value, marks: &mut Vec<bool>, gray: &mut Vec<u32>| {
+            if let Value::Ref(index) = value {
+                if !marks[index as usize] {
+                    marks[index as usize] = true;
+                    gray.push(index);
+                }
+            }
+        };
+        for root in roots {
+            mark(root, &mut self.marks, &mut gray);
+        }
+        while let Some(index) = gray.pop() {
+            if let Object::Record(values) | Object::Array(values) = &self.objects[index as usize] {
+                for value in values.iter() {
+                    mark(*value, &mut self.marks, &mut gray);
+                }
+            }
+        }
+        self.live = 0;
+        let mut freed = 0;
+        for (index, object) in self.objects.iter_mut().enumerate() {
+            if std::mem::take(&mut self.marks[index]) {
+                self.live += 1;
+            } else if !matches!(object, Object::Free) {
+                freed += object.size();
+                *object = Object::Free;
+                self.free.push(index as u32);
+            }
+        }
+        self.threshold = MIN_THRESHOLD.max(self.live * 2);
+        self.collections += 1;
+        freed
+    }
+}
+
+struct Frame {
+    func: usize,
+    /// Where to resume the caller.
+    pc: usize,
+    /// Stack index of the first local.
+    base: usize,
+    /// Index of the frame of the enclosing function.
+    link: usize,
+}
+
+/// Runs a bytecode module.
+pub struct Vm<'a> {
+    module: &'a Module,
+    input: &'a mut dyn Read,
+    output: &'a mut dyn Write,
+    heap: Heap,
+    /// The constants, with strings allocated in the heap.
+    consts: Vec<Value>,
+    /// The empty string and the strings of one byte, which `getchar` and
+    /// `chr` share, each allocated when first needed.
+    chars: Vec<Value>,
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    /// Use of the resources under limits. The constants and the strings of
+    /// `chars` are not counted against the heap limit.
+    meter: Meter,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(module: &'a Module, input: &'a mut dyn Read, output: &'a mut dyn Write) -> Vm<'a> {
+        Vm::with_limits(module, Limits::default(), input, output)
+    }
+
+    /// A VM whose runs fail once they exceed `limits`.
+    pub fn with_limits(
+        module: &'a Module,
+        limits: Limits,
+        input: &'a mut dyn Read,
+        output: &'a mut dyn Write,
+    ) -> Vm<'a> {
+        let mut heap = Heap {
+            objects: Vec::new(),
+            marks: Vec::new(),
+            free: Vec::new(),
+            live: 0,
+            threshold: MIN_THRESHOLD,
+            collections: 0,
+        };
+        let consts = module
+            .consts
+            .iter()
+            .map(|constant| match constant {
+                Const::Int(i) => Value::Int(*i),
+                Const::String(s) => heap.alloc(Object::String(s.clone().into_boxed_slice())),
+            })
+            .collect();
+        Vm {
+            module,
+            input,
+            output,
+            heap,
+            consts,
+            chars: vec![Value::Nil; 257],
+            stack: Vec::new(),
+            frames: Vec::new(),
+            meter: Meter::new(limits),
+        }
+    }
+
+    /// Collections done so far.
+    pub fn collections(&self) -> usize {
+        self.heap.collections
+    }
+
+    /// Objects in the heap, free slots included.
+    pub fn heap_size(&self) -> usize {
+        self.heap.objects.len()
+    }
+
+    /// Runs the main program, returning the status passed to `exit`, or 0.
+    pub fn run(&mut self) -> Result<i64, String> {
+        let main = &self.module.funcs[0];
+        self.stack = vec![Value::Nil; main.locals as usize];
+        self.frames = vec![Frame {
+            func: 0,
+            pc: 0,
+            base: 0,
+            link: usize::MAX,
+        }];
+        let status = self.meter.enter().and_then(|()| self.execute());
+        self.output.flush().map_err(|err| err.to_string())?;
+        status
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("operand stack underflow")
+    }
+
+    fn pop_int(&mut self) -> i64 {
+        match self.pop() {
+            Value::Int(i) => i,
+            value => unreachable!("{:?} is not an int", value),
+        }
+    }
+
+    fn push_bool(&mut self, b: bool) {
+        self.stack.push(Value::Int(b as i64));
+    }
+
+    fn collect(&mut self) {
+        let roots = self.stack.iter().chain(&self.consts).chain(&self.chars);
+        let freed = self.heap.collect(roots.copied());
+        self.meter.free(freed);
+    }
+
+    /// Collects if the heap is due, before an instruction that allocates
+    /// while its operands are still on the stack.
+    fn reserve(&mut self) {
+        if self.heap.live >= self.heap.threshold {
+            self.collect();
+        }
+    }
+
+    /// Counts an object of `bytes` against the heap limit, collecting first
+    /// if it would not fit otherwise. Its operands must still be on the
+    /// stack.
+    fn charge(&mut self, bytes: usize) -> Result<(), String> {
+        if !self.meter.fits(bytes) {
+            self.collect();
+        }
+        self.meter.alloc(bytes)
+    }
+
+    /// The string of `c`, or the empty string.
+    fn char_string(&mut self, c: Option<u8>) -> Value {
+        let index = c.map_or(0, |c| 1 + c as usize);
+        if self.chars[index] == Value::Nil {
+            let chars: Box<[u8]> = c.into_iter().collect();
+            self.chars[index] = self.heap.alloc(Object::String(chars));
+        }
+        self.chars[index]
+    }
+
+    /// The frame `hops` static links out from the current one.
+    fn outer(&self, hops: usize) -> usize {
+        let mut frame = self.frames.len() - 1;
+        for _ in 0..hops {
+            frame = self.frames[frame].link;
+        }
+        frame
+    }
+
+    /// The values of the object `value` refers to, as a record or array.
+    fn values(&mut self, value: Value) -> Option<&mut [Value]> {
+        match value {
+            Value::Ref(index) => match &mut self.heap.objects[index as usize] {
+                Object::Record(values) | Object::Array(values) => Some(values),
+                object => unreachable!("{:?} is not a record or array", object),
+            },
+            Value::Nil => None,
+            value => unreachable!("{:?} is not a record or array", value),
+        }
+    }
+
+    fn field(&mut self, record: Value, index: usize) -> Result<&mut Value, String> {
+        match self.values(record) {
+            Some(fields) => Ok(&mut fields[index]),
+            None => Err(String::from("nil record dereferenced")),
+        }
+    }
+
+    fn element(&mut self, array: Value, index: i64) -> Result<&mut Value, String> {
+        let elems = self.values(array).expect("nil array");
+        let size = elems.len();
+        match usize::try_from(index) {
+            Ok(i) if i < size => Ok(&mut elems[i]),
+            _ => Err(format!(
+                "index {} out of range for array of size {}",
+                index, size
+            )),
+        }
+    }
+
+    /// Runs until the main program returns, reporting errors at the source
+    /// position of the failing instruction, with the functions active then.
+    fn execute(&mut self) -> Result<i64, String> {
+        loop {
+            match self.step() {
+                Ok(None) => (),
+                Ok(Some(status)) => return Ok(status),
+                Err(msg) => {
+                    let frame = self.frames.last().unwrap();
+                    let err = match self.module.funcs[frame.func].line(frame.pc) {
+                        Some(line) => format!("{}.{}: {}", line.line, line.col, msg),
+                        None => msg,
+                    };
+                    return Err(self.frames.iter().rev().fold(err, |err, frame| {
+                        backtrace(err, &self.module.funcs[frame.func].name)
+                    }));
+                }
+            }
+        }
+    }
+
+    /// Runs instructions up to the next call, return or error, returning
+    /// the exit status once the program is done.
+    fn step(&mut self) -> Result<Option<i64>, String> {
+        let module = self.module;
+        let frame = self.frames.len() - 1;
+        let base = self.frames[frame].base;
+        let code = &module.funcs[self.frames[frame].func].code;
+        let mut pc = self.frames[frame].pc;
+        loop {
+            let op = Op::from_u8(code[pc]).expect("bad opcode");
+            // The operand `width` bytes wide, `offset` bytes past the opcode.
+            let arg = |offset: usize, width: usize| operand(code, pc + 1 + offset, width);
+            let next = pc + op.size();
+            if let Err(err) = self.meter.step() {
+                self.frames[frame].pc = pc;
+                return Err(err);
+            }
+            if op.can_fail() {
+                self.frames[frame].pc = pc;
+            }
+            match op {
+                Op::Const => self.stack.push(self.consts[arg(0, 2)]),
+                Op::Nil => self.stack.push(Value::Nil),
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Load => self.stack.push(self.stack[base + arg(0, 2)]),
+                Op::Store => {
+                    let value = self.pop();
+                    self.stack[base + arg(0, 2)] = value;
+                }
+                Op::LoadOuter => {
+                    let outer = self.frames[self.outer(arg(0, 1))].base;
+                    self.stack.push(self.stack[outer + arg(1, 2)]);
+                }
+                Op::StoreOuter => {
+                    let outer = self.frames[self.outer(arg(0, 1))].base;
+                    let value = self.pop();
+                    self.stack[outer + arg(1, 2)] = value;
+                }
+                Op::Add | Op::Sub | Op::Mul | Op::Div | Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+                    let b = self.pop_int();
+                    let a = self.pop_int();
+                    let value = match op {
+                        Op::Add => a.wrapping_add(b),
+                        Op::Sub => a.wrapping_sub(b),
+                        Op::Mul => a.wrapping_mul(b),
+                        Op::Div if b == 0 => return Err(String::from("division by zero")),
+                        Op::Div => a.wrapping_div(b),
+                        Op::Lt => (a < b) as i64,
+                        Op::Le => (a <= b) as i64,
+                        Op::Gt => (a > b) as i64,
+                        _ => (a >= b) as i64,
+                    };
+                    self.stack.push(Value::Int(value));
+                }
+                Op::Eq | Op::Ne => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.push_bool((a == b) == (op == Op::Eq));
+                }
+                Op::StrEq | Op::StrNe | Op::StrLt | Op::StrLe | Op::StrGt | Op::StrGe => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    let order = self.heap.string(a).cmp(self.heap.string(b));
+                    self.push_bool(match op {
+                        Op::StrEq => order.is_eq(),
+                        Op::StrNe => order.is_ne(),
+                        Op::StrLt => order.is_lt(),
+                        Op::StrLe => order.is_le(),
+                        Op::StrGt => order.is_gt(),
+                        _ => order.is_ge(),
+                    });
+                }
+                Op::Jump => {
+                    pc = arg(0, 4);
+                    continue;
+                }
+                Op::JumpIfFalse => {
+                    if self.pop_int() == 0 {
+                        pc = arg(0, 4);
+                        continue;
+                    }
+                }
+                Op::Record => {
+                    self.reserve();
+                    self.charge(8 * arg(0, 2))?;
+                    let fields = self.stack.split_off(self.stack.len() - arg(0, 2));
+                    let record = self.heap.alloc(Object::Record(fields.into_boxed_slice()));
+                    self.stack.push(record);
+                }
+                Op::Array => {
+                    self.reserve();
+                    let size = match self.stack[self.stack.len() - 2] {
+                        Value::Int(size) => size,
+                        value => unreachable!("{:?} is not an int", value),
+                    };
+                    if size < 0 {
+                        return Err(format!("array size {} is negative", size));
+                    }
+                    self.charge((size as usize).saturating_mul(8))?;
+                    let init = self.pop();
+                    self.pop();
+                    let elems = vec![init; size as usize].into_boxed_slice();
+                    let array = self.heap.alloc(Object::Array(elems));
+                    self.stack.push(array);
+                }
+                Op::GetField => {
+                    let record = self.pop();
+                    let value = *self.field(record, arg(0, 2))?;
+                    self.stack.push(value);
+                }
+                Op::SetField => {
+                    let value = self.pop();
+                    let record = self.pop();
+                    *self.field(record, arg(0, 2))? = value;
+                }
+                Op::GetElem => {
+                    let index = self.pop_int();
+                    let array = self.pop();
+                    let value = *self.element(array, index)?;
+                    self.stack.push(value);
+                }
+                Op::SetElem => {
+                    let value = self.pop();
+                    let index = self.pop_int();
+                    let array = self.pop();
+                    *self.element(array, index)? = value;
+                }
+                Op::Call => {
+                    let callee = &module.funcs[arg(0, 2)];
+                    self.meter.enter()?;
+                    let link = self.outer(arg(2, 1));
+                    let base = self.stack.len() - callee.params as usize;
+                    self.stack.resize(base + callee.locals as usize, Value::Nil);
+                    self.frames[frame].pc = next;
+                    self.frames.push(Frame {
+                        func: arg(0, 2),
+                        pc: 0,
+                        base,
+                        link,
+                    });
+                    return Ok(None);
+                }
+                Op::Builtin => {
+                    let builtin = Builtin::from_u8(arg(0, 1) as u8).expect("bad builtin");
+                    if let Some(status) = self.builtin(builtin)? {
+                        return Ok(Some(status));
+                    }
+                }
+                Op::Return => {
+                    let result = module.funcs[self.frames[frame].func]
+                        .returns
+                        .then(|| self.pop());
+                    self.frames.pop();
+                    self.meter.leave();
+                    if self.frames.is_empty() {
+                        return Ok(Some(0));
+                    }
+                    self.stack.truncate(base);
+                    self.stack.extend(result);
+                    return Ok(None);
+                }
+            }
+            pc = next;
+        }
+    }
+
+    /// Runs `builtin` on the arguments on the stack, returning the status
+    /// if it exits.
+    fn builtin(&mut self, builtin: Builtin) -> Result<Option<i64>, String> {
+        let io_error = |err: std::io::Error| err.to_string();
+        match builtin {
+            Builtin::Print => {
+                let s = self.pop();
+                self.meter.output(self.heap.string(s).len())?;
+                self.output
+                    .write_all(self.heap.string(s))
+                    .map_err(io_error)?;
+            }
+            Builtin::Flush => self.output.flush().map_err(io_error)?,
+            Builtin::Getchar => {
+                let mut byte = [0u8];
+                let s = match self.input.read(&mut byte).map_err(io_error)? {
+                    1 => self.char_string(Some(byte[0])),
+                    _ => self.char_string(None),
+                };
+                self.stack.push(s);
+            }
+            Builtin::Ord => {
+                let s = self.pop();
+                let c = self.heap.string(s).first().map_or(-1, |c| *c as i64);
+                self.stack.push(Value::Int(c));
+            }
+            Builtin::Chr => {
+                let i = self.pop_int();
+                match u8::try_from(i) {
+                    Ok(c) => {
+                        let s = self.char_string(Some(c));
+                        self.stack.push(s);
+                    }
+                    Err(_) => return Err(format!("chr({}) out of range", i)),
+                }
+            }
+            Builtin::Size => {
+                let s = self.pop();
+                let size = self.heap.string(s).len() as i64;
+                self.stack.push(Value::Int(size));
+            }
+            Builtin::Substring => {
+                self.reserve();
+                let n = self.pop_int();
+                let first = self.pop_int();
+                let chars = self.heap.string(*self.stack.last().unwrap());
+                if first < 0 || n < 0 || first + n > chars.len() as i64 {
+                    return Err(format!(
+                        "substring({:?}, {}, {}) out of range",
+                        String::from_utf8_lossy(chars),
+                        first,
+                        n
+                    ));
+                }
+                self.charge(n as usize)?;
+                let s = self.pop();
+                let chars = self.heap.string(s);
+                let part: Box<[u8]> = chars[first as usize..(first + n) as usize].into();
+                let t = self.heap.alloc(Object::String(part));
+                self.stack.push(t);
+            }
+            Builtin::Concat => {
+                self.reserve();
+                let top = self.stack.len();
+                let size = self.heap.string(self.stack[top - 2]).len()
+                    + self.heap.string(self.stack[top - 1]).len();
+                self.charge(size)?;
+                let b = self.pop();
+                let a = self.pop();
+                let s = [self.heap.string(a), self.heap.string(b)].concat();
+                let t = self.heap.alloc(Object::String(s.into_boxed_slice()));
+                self.stack.push(t);
+            }
+            Builtin::Not => {
+                let i = self.pop_int();
+                self.push_bool(i == 0);
+            }
+            Builtin::Exit => return Ok(Some(self.pop_int())),
+        }
+        Ok(None)
+    }
+}
+
+/// Runs `module`, with `getchar` reading from `input` and `print` writing
+/// to `output`. Returns the status passed to `exit`, or 0.
+pub fn run(module: &Module, input: &mut dyn Read, output: &mut dyn Write) -> Result<i64, String> {
+    Vm::new(module, input, output).run()
+}
+
+/// Runs `module` as [`run`] does, failing once it exceeds `limits`.
+pub fn run_with(
+    module: &Module,
+    limits: Limits,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+) -> Result<i64, String> {
+    Vm::with_limits(module, limits, input, output).run()
+}
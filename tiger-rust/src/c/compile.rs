@@ -0,0 +1,574 @@
+//! Compiles the typed AST to a single C file, linked with the runtime
+//! library as the native backend's assembly is.
+//!
+//! Every Tiger value is an `int64_t`, strings, records and arrays being
+//! laid out as the runtime expects. Each function keeps its variables and
+//! the intermediate values of its expressions in a frame struct on the C
+//! stack, which starts with a pointer to the frame of the enclosing
+//! function. While the function runs the frame is linked into the
+//! runtime's `tig_c_frames`, with a descriptor telling the collector which
+//! slots hold pointers and the function's name for backtraces. As every
+//! intermediate value has a slot, the collector can move what they point
+//! to at any allocation, and expressions are evaluated left to right by
+//! the statements they compile to.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::error::error::{pack_pos, PosInfo};
+use crate::semant::tast::{
+    FunId, FunKind, Oper, Program, TDec, TExp, TExpKind, TVar, TVarKind, VarId,
+};
+use crate::semant::types::Type;
+
+const PRELUDE: &str = "#include <stdint.h>
+
+struct tig_frame {
+    const struct tig_frame *prev;
+    int64_t name;
+    int64_t descriptor;
+};
+
+extern const struct tig_frame *tig_c_frames;
+
+/* No frames of native code to describe to the collector. */
+const void *const tig_frame_maps[1] = {0};
+
+void tig_print(int64_t s);
+void tig_flush(void);
+int64_t tig_getchar(void);
+int64_t tig_ord(int64_t s);
+int64_t tig_chr(int64_t i, int64_t pos);
+int64_t tig_size(int64_t s);
+int64_t tig_substring(int64_t s, int64_t first, int64_t n, int64_t pos);
+int64_t tig_concat(int64_t a, int64_t b);
+int64_t tig_not(int64_t i);
+_Noreturn void tig_exit(int64_t code);
+int64_t tig_stringEqual(int64_t a, int64_t b);
+int64_t tig_stringCompare(int64_t a, int64_t b);
+int64_t tig_initArray(int64_t size, int64_t init, int64_t pointers, int64_t pos);
+int64_t tig_allocRecord(int64_t size, int64_t descriptor);
+_Noreturn void tig_nilRecord(int64_t pos);
+_Noreturn void tig_indexOutOfRange(int64_t i, int64_t size, int64_t pos);
+_Noreturn void tig_divisionByZero(int64_t pos);
+";
+
+/// Compiles a type-checked program to C defining `tigermain`, for the
+/// runtime library to call.
+pub fn compile(program: &Program) -> String {
+    let mut compiler = Compiler {
+        program,
+        strings: Vec::new(),
+        string_index: HashMap::new(),
+        funcs: Vec::new(),
+        queued: HashSet::new(),
+    };
+    compiler.queue(program.main);
+    let mut functions = Vec::new();
+    while functions.len() < compiler.funcs.len() {
+        let fun = compiler.funcs[functions.len()];
+        functions.push(compiler.function(fun));
+    }
+    let mut out = String::from(PRELUDE);
+    out.push('\n');
+    for (k, value) in compiler.strings.iter().enumerate() {
+        writeln!(
+            out,
+            "static const struct {{ int64_t length; char chars[{}]; }} str{} = {{{}, {}}};",
+            value.len().max(1),
+            k,
+            value.len(),
+            literal(value)
+        )
+        .unwrap();
+    }
+    out.push('\n');
+    for fun in &compiler.funcs {
+        writeln!(out, "struct {};", frame(program, *fun)).unwrap();
+    }
+    for fun in &compiler.funcs {
+        writeln!(out, "{};", compiler.signature(*fun)).unwrap();
+    }
+    for function in functions {
+        out.push('\n');
+        out.push_str(&function);
+    }
+    out
+}
+
+/// `bytes` as a C string literal.
+fn literal(bytes: &[u8]) -> String {
+    let mut out = String::from("\"");
+    for byte in bytes {
+        match byte {
+            b' '..=b'~' if !matches!(byte, b'"' | b'\\' | b'?') => out.push(*byte as char),
+            _ => write!(out, "\\{:03o}", byte).unwrap(),
+        }
+    }
+    out + "\""
+}
+
+/// The struct tag of the frame of `fun`.
+fn frame(program: &Program, fun: FunId) -> String {
+    format!("{}_frame", program.func(fun).label)
+}
+
+/// The letter for a slot or field in a descriptor.
+fn kind(pointer: bool) -> char {
+    if pointer {
+        'p'
+    } else {
+        'n'
+    }
+}
+
+struct Compiler<'a> {
+    program: &'a Program,
+    strings: Vec<Vec<u8>>,
+    string_index: HashMap<Vec<u8>, usize>,
+    /// The functions to compile, in order of first use.
+    funcs: Vec<FunId>,
+    queued: HashSet<FunId>,
+}
+
+/// The C statements of the function being compiled.
+struct Body {
+    fun: FunId,
+    lines: Vec<String>,
+    indent: usize,
+    /// Whether each temporary slot holds a pointer.
+    temps: Vec<bool>,
+}
+
+impl Body {
+    fn line(&mut self, line: String) {
+        self.lines
+            .push(format!("{:width$}{}", "", line, width = 4 * self.indent));
+    }
+
+    fn open(&mut self, line: String) {
+        self.line(line);
+        self.indent += 1;
+    }
+
+    fn close(&mut self, line: &str) {
+        self.indent -= 1;
+        self.line(String::from(line));
+    }
+
+    fn temp(&mut self, pointer: bool) -> String {
+        self.temps.push(pointer);
+        format!("f.t{}", self.temps.len() - 1)
+    }
+}
+
+impl Compiler<'_> {
+    fn queue(&mut self, fun: FunId) {
+        if self.queued.insert(fun) {
+            self.funcs.push(fun);
+        }
+    }
+
+    /// An expression for the address of the string `value`.
+    fn string(&mut self, value: &[u8]) -> String {
+        let k = match self.string_index.get(value) {
+            Some(k) => *k,
+            None => {
+                self.strings.push(value.to_vec());
+                self.string_index
+                    .insert(value.to_vec(), self.strings.len() - 1);
+                self.strings.len() - 1
+            }
+        };
+        format!("(int64_t)&str{}", k)
+    }
+
+    fn is_pointer(&self, ty: Type) -> bool {
+        self.program.types.is_pointer(ty)
+    }
+
+    fn has_value(&self, exp: &TExp) -> bool {
+        self.program.types.actual(exp.ty) != Type::Unit
+    }
+
+    /// The variables whose home is the frame of `fun`, parameters first.
+    fn vars(&self, fun: FunId) -> Vec<VarId> {
+        let params = &self.program.func(fun).params;
+        let others = (0..self.program.vars.len())
+            .map(VarId)
+            .filter(|var| self.program.var(*var).fun == fun && !params.contains(var));
+        params.iter().copied().chain(others).collect()
+    }
+
+    fn var_name(&self, var: VarId) -> String {
+        format!("{}_{}", self.program.var(var).name, var.0)
+    }
+
+    /// The declaration of the static link of `fun`.
+    fn link(&self, fun: FunId) -> String {
+        match self.program.func(fun).parent {
+            Some(parent) => format!("struct {} *link", frame(self.program, parent)),
+            None => String::from("void *link"),
+        }
+    }
+
+    fn signature(&self, fun: FunId) -> String {
+        let info = self.program.func(fun);
+        let params: Vec<_> = std::iter::once(self.link(fun))
+            .chain(
+                info.params
+                    .iter()
+                    .map(|param| format!("int64_t {}", self.var_name(*param))),
+            )
+            .collect();
+        format!("int64_t {}({})", info.label, params.join(", "))
+    }
+
+    fn function(&mut self, fun: FunId) -> String {
+        let info = self.program.func(fun);
+        let FunKind::User { body } = &info.kind else {
+            unreachable!("builtins are not compiled")
+        };
+        let mut code = Body {
+            fun,
+            lines: Vec::new(),
+            indent: 1,
+            temps: Vec::new(),
+        };
+        let result = self.value(&mut code, body);
+        let vars = self.vars(fun);
+        let mut descriptor = String::from("n");
+        let mut out = format!(
+            "struct {} {{\n    struct tig_frame header;\n    {};\n",
+            frame(self.program, fun),
+            self.link(fun)
+        );
+        for var in &vars {
+            writeln!(out, "    int64_t {};", self.var_name(*var)).unwrap();
+            descriptor.push(kind(self.is_pointer(self.program.var(*var).ty)));
+        }
+        for (t, pointer) in code.temps.iter().enumerate() {
+            writeln!(out, "    int64_t t{};", t).unwrap();
+            descriptor.push(kind(*pointer));
+        }
+        out.push_str("};\n\n");
+        let name = self.string(info.name.as_bytes());
+        let descriptor = self.string(descriptor.as_bytes());
+        let mut init = vec![String::from("link")];
+        init.extend(info.params.iter().map(|param| self.var_name(*param)));
+        writeln!(
+            out,
+            "{}\n{{\n    struct {} f = {{{{tig_c_frames, {}, {}}}, {}}};\n    tig_c_frames = &f.header;",
+            self.signature(fun),
+            frame(self.program, fun),
+            name,
+            descriptor,
+            init.join(", ")
+        )
+        .unwrap();
+        for line in code.lines {
+            writeln!(out, "{}", line).unwrap();
+        }
+        writeln!(
+            out,
+            "    tig_c_frames = f.header.prev;\n    return {};\n}}",
+            result
+        )
+        .unwrap();
+        out
+    }
+
+    /// Compiles `exp` to statements, returning an expression for its value
+    /// that stays valid until the next statement, or `0` if it has none.
+    fn value(&mut self, code: &mut Body, exp: &TExp) -> String {
+        let pos = &exp.pos;
+        match &exp.kind {
+            TExpKind::Int(i) if *i == i64::MIN => String::from("INT64_MIN"),
+            TExpKind::Int(i) if *i < 0 => format!("({})", i),
+            TExpKind::Int(i) => i.to_string(),
+            TExpKind::String(value) => self.string(value.as_bytes()),
+            TExpKind::Nil => String::from("0"),
+            TExpKind::Var(var) => self.var(code, var),
+            TExpKind::Call(fun, args) => {
+                let mut values: Vec<_> = args.iter().map(|arg| self.value(code, arg)).collect();
+                let info = self.program.func(*fun);
+                let callee = match &info.kind {
+                    FunKind::Builtin => {
+                        if matches!(info.name.as_str(), "chr" | "substring") {
+                            values.push(pack_pos(pos).to_string());
+                        }
+                        format!("tig_{}", info.name)
+                    }
+                    FunKind::User { .. } => {
+                        self.queue(*fun);
+                        let hops = self.program.depth(code.fun) + 1 - self.program.depth(*fun);
+                        let link = match hops {
+                            0 => String::from("&f"),
+                            hops => vec!["link"; hops].join("->"),
+                        };
+                        values.insert(0, link);
+                        info.label.to_string()
+                    }
+                };
+                let call = format!("{}({})", callee, values.join(", "));
+                if !self.has_value(exp) {
+                    code.line(format!("{};", call));
+                    return String::from("0");
+                }
+                let t = code.temp(self.is_pointer(exp.ty));
+                code.line(format!("{} = {};", t, call));
+                t
+            }
+            TExpKind::Op(oper, left, right) => {
+                let l = self.value(code, left);
+                let r = self.value(code, right);
+                let string = self.program.types.actual(left.ty) == Type::String;
+                let value = match (oper, string) {
+                    (Oper::Plus, _) => format!("(int64_t)((uint64_t){} + (uint64_t){})", l, r),
+                    (Oper::Minus, _) => format!("(int64_t)((uint64_t){} - (uint64_t){})", l, r),
+                    (Oper::Times, _) => format!("(int64_t)((uint64_t){} * (uint64_t){})", l, r),
+                    (Oper::Divide, _) => {
+                        code.line(format!(
+                            "if ({} == 0) tig_divisionByZero({});",
+                            r,
+                            pack_pos(pos)
+                        ));
+                        format!(
+                            "{} == -1 ? (int64_t)(0 - (uint64_t){}) : {} / {}",
+                            r, l, l, r
+                        )
+                    }
+                    (Oper::Eq, true) => format!("tig_stringEqual({}, {})", l, r),
+                    (Oper::Neq, true) => format!("!tig_stringEqual({}, {})", l, r),
+                    (oper, true) => {
+                        format!("tig_stringCompare({}, {}) {} 0", l, r, comparison(*oper))
+                    }
+                    (oper, false) => format!("{} {} {}", l, comparison(*oper), r),
+                };
+                let t = code.temp(false);
+                code.line(format!("{} = {};", t, value));
+                t
+            }
+            TExpKind::And(left, right) => {
+                let t = code.temp(false);
+                code.line(format!("{} = 0;", t));
+                let l = self.value(code, left);
+                code.open(format!("if ({} != 0) {{", l));
+                let r = self.value(code, right);
+                code.line(format!("{} = {} != 0;", t, r));
+                code.close("}");
+                t
+            }
+            TExpKind::Or(left, right) => {
+                let t = code.temp(false);
+                code.line(format!("{} = 1;", t));
+                let l = self.value(code, left);
+                code.open(format!("if ({} == 0) {{", l));
+                let r = self.value(code, right);
+                code.line(format!("{} = {} != 0;", t, r));
+                code.close("}");
+                t
+            }
+            TExpKind::Record(_, fields) => {
+                let values: Vec<_> = fields.iter().map(|field| self.value(code, field)).collect();
+                let types = &self.program.types;
+                let descriptor: String = types
+                    .record_fields(exp.ty)
+                    .expect("record of non-record type")
+                    .iter()
+                    .map(|(_, ty)| kind(types.is_pointer(*ty)))
+                    .collect();
+                let descriptor = self.string(descriptor.as_bytes());
+                let t = code.temp(true);
+                code.line(format!(
+                    "{} = tig_allocRecord({}, {});",
+                    t,
+                    8 * fields.len(),
+                    descriptor
+                ));
+                for (i, value) in values.iter().enumerate() {
+                    code.line(format!("((int64_t *){})[{}] = {};", t, i, value));
+                }
+                t
+            }
+            TExpKind::Array(_, size, init) => {
+                let size = self.value(code, size);
+                let pointers = self.is_pointer(init.ty) as i64;
+                let init = self.value(code, init);
+                let t = code.temp(true);
+                code.line(format!(
+                    "{} = tig_initArray({}, {}, {}, {});",
+                    t,
+                    size,
+                    init,
+                    pointers,
+                    pack_pos(pos)
+                ));
+                t
+            }
+            TExpKind::Seq(exps) => {
+                let mut result = String::from("0");
+                for exp in exps {
+                    result = self.value(code, exp);
+                }
+                result
+            }
+            TExpKind::Assign(var, value) => {
+                self.assign(code, var, value);
+                String::from("0")
+            }
+            TExpKind::If(test, then, els) => {
+                let t = self
+                    .has_value(exp)
+                    .then(|| code.temp(self.is_pointer(exp.ty)));
+                let c = self.value(code, test);
+                code.open(format!("if ({} != 0) {{", c));
+                let value = self.value(code, then);
+                if let Some(t) = &t {
+                    code.line(format!("{} = {};", t, value));
+                }
+                if let Some(els) = els {
+                    code.close("} else {");
+                    code.indent += 1;
+                    let value = self.value(code, els);
+                    if let Some(t) = &t {
+                        code.line(format!("{} = {};", t, value));
+                    }
+                }
+                code.close("}");
+                t.unwrap_or_else(|| String::from("0"))
+            }
+            TExpKind::While(test, body) => {
+                code.open(String::from("while (1) {"));
+                let c = self.value(code, test);
+                code.line(format!("if ({} == 0) break;", c));
+                self.value(code, body);
+                code.close("}");
+                String::from("0")
+            }
+            TExpKind::For(var, lo, hi, body) => {
+                let i = format!("f.{}", self.var_name(*var));
+                let lo = self.value(code, lo);
+                code.line(format!("{} = {};", i, lo));
+                let hi = self.value(code, hi);
+                let limit = code.temp(false);
+                code.line(format!("{} = {};", limit, hi));
+                code.open(format!("if ({} <= {}) {{", i, limit));
+                code.open(String::from("while (1) {"));
+                self.value(code, body);
+                code.line(format!("if ({} >= {}) break;", i, limit));
+                code.line(format!("{}++;", i));
+                code.close("}");
+                code.close("}");
+                String::from("0")
+            }
+            TExpKind::Break => {
+                code.line(String::from("break;"));
+                String::from("0")
+            }
+            TExpKind::Let(decs, body) => {
+                for dec in decs {
+                    match dec {
+                        TDec::Var(var, init) => {
+                            let value = self.value(code, init);
+                            code.line(format!("f.{} = {};", self.var_name(*var), value));
+                        }
+                        TDec::Funcs(funs) => {
+                            for fun in funs {
+                                self.queue(*fun);
+                            }
+                        }
+                    }
+                }
+                self.value(code, body)
+            }
+        }
+    }
+
+    /// The variable `var` as seen from the function being compiled.
+    fn access(&self, code: &Body, var: VarId) -> String {
+        let hops = self.program.depth(code.fun) - self.program.depth(self.program.var(var).fun);
+        match hops {
+            0 => format!("f.{}", self.var_name(var)),
+            hops => format!("{}->{}", vec!["link"; hops].join("->"), self.var_name(var)),
+        }
+    }
+
+    /// The address of the field `index` of `record`, checked not nil.
+    fn field(&self, code: &mut Body, record: &str, index: usize, pos: &PosInfo) -> String {
+        code.line(format!(
+            "if ({} == 0) tig_nilRecord({});",
+            record,
+            pack_pos(pos)
+        ));
+        format!("((int64_t *){})[{}]", record, index)
+    }
+
+    /// The element `i` of `array`, checked in range.
+    fn element(&self, code: &mut Body, array: &str, i: &str, pos: &PosInfo) -> String {
+        let size = format!("((int64_t *){})[0]", array);
+        code.line(format!(
+            "if ((uint64_t){} >= (uint64_t){}) tig_indexOutOfRange({}, {}, {});",
+            i,
+            size,
+            i,
+            size,
+            pack_pos(pos)
+        ));
+        format!("((int64_t *){})[1 + {}]", array, i)
+    }
+
+    fn var(&mut self, code: &mut Body, var: &TVar) -> String {
+        let value = match &var.kind {
+            TVarKind::Simple(id) => self.access(code, *id),
+            TVarKind::Field(record, index) => {
+                let r = self.value(code, record);
+                self.field(code, &r, *index, &record.pos)
+            }
+            TVarKind::Subscript(array, index) => {
+                let a = self.value(code, array);
+                let i = self.value(code, index);
+                self.element(code, &a, &i, &index.pos)
+            }
+        };
+        let t = code.temp(self.is_pointer(var.ty));
+        code.line(format!("{} = {};", t, value));
+        t
+    }
+
+    /// Compiles an assignment as one C assignment, naming the value before
+    /// `field` or `element` checks the target so the checks come last.
+    fn assign(&mut self, code: &mut Body, var: &TVar, value: &TExp) {
+        let target = match &var.kind {
+            TVarKind::Simple(id) => {
+                let value = self.value(code, value);
+                let target = self.access(code, *id);
+                return code.line(format!("{} = {};", target, value));
+            }
+            TVarKind::Field(record, index) => {
+                let r = self.value(code, record);
+                let v = self.value(code, value);
+                (self.field(code, &r, *index, &record.pos), v)
+            }
+            TVarKind::Subscript(array, index) => {
+                let a = self.value(code, array);
+                let i = self.value(code, index);
+                let v = self.value(code, value);
+                (self.element(code, &a, &i, &index.pos), v)
+            }
+        };
+        code.line(format!("{} = {};", target.0, target.1));
+    }
+}
+
+fn comparison(oper: Oper) -> &'static str {
+    match oper {
+        Oper::Eq => "==",
+        Oper::Neq => "!=",
+        Oper::Lt => "<",
+        Oper::Le => "<=",
+        Oper::Gt => ">",
+        Oper::Ge => ">=",
+        oper => unreachable!("{:?} is not a comparison", oper),
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree::{Exp, Stm};
+
+/// Removes every `ESeq` and `Seq` from `stm` and lifts each `Call` so its
+/// parent is an `Exp` or a `Move` to a `Temp`. The statements of the result
+/// run in order and compute what `stm` computed.
+pub fn linearize(stm: Stm) -> Vec<Stm> {
+    let mut stms = Vec::new();
+    flatten(do_stm(stm), &mut stms);
+    stms
+}
+
+/// Splits a linearized statement list into basic blocks: each starts with a
+/// `Label` and ends with a `Jump` or `CJump`, with neither inside. Falling
+/// off the end jumps to the returned label.
+pub fn basic_blocks(stms: Vec<Stm>) -> (Vec<Vec<Stm>>, Label) {
+    let done = Label::new();
+    let mut blocks: Vec<Vec<Stm>> = Vec::new();
+    let mut current: Option<Vec<Stm>> = None;
+    for stm in stms {
+        let block = match (current.take(), &stm) {
+            (Some(mut block), Stm::Label(label)) => {
+                block.push(jump(label.clone()));
+                blocks.push(block);
+                Vec::new()
+            }
+            (Some(block), _) => block,
+            (None, Stm::Label(_)) => Vec::new(),
+            (None, _) => vec![Stm::Label(Label::new())],
+        };
+        let ends = matches!(stm, Stm::Jump(..) | Stm::CJump(..));
+        let mut block = block;
+        block.push(stm);
+        if ends {
+            blocks.push(block);
+        } else {
+            current = Some(block);
+        }
+    }
+    if let Some(mut block) = current {
+        block.push(jump(done.clone()));
+        blocks.push(block);
+    }
+    (blocks, done)
+}
+
+/// Orders basic blocks into traces so that every `CJump` is immediately
+/// followed by its false label, and drops jumps to the very next label.
+/// The result ends with the `done` label.
+pub fn trace_schedule(blocks: Vec<Vec<Stm>>, done: Label) -> Vec<Stm> {
+    let mut table: HashMap<Label, usize> = HashMap::new();
+    for (index, block) in blocks.iter().enumerate() {
+        table.insert(block_label(block).clone(), index);
+    }
+    let mut marked: HashSet<usize> = HashSet::new();
+    let mut order = Vec::new();
+    for start in 0..blocks.len() {
+        let mut next = Some(start);
+        while let Some(index) = next.filter(|index| marked.insert(*index)) {
+            order.push(index);
+            let successors: &[&Label] = &match blocks[index].last() {
+                Some(Stm::Jump(_, labels)) => labels.iter().collect::<Vec<_>>(),
+                Some(Stm::CJump(_, _, _, t, f)) => vec![f, t],
+                _ => unreachable!("basic block without a jump"),
+            };
+            next = successors
+                .iter()
+                .filter_map(|label| table.get(*label))
+                .copied()
+                .find(|index| !marked.contains(index));
+        }
+    }
+    let mut blocks: Vec<Option<Vec<Stm>>> = blocks.into_iter().map(Some).collect();
+    let mut stms: Vec<Stm> = Vec::new();
+    let mut order = order.into_iter().peekable();
+    while let Some(index) = order.next() {
+        let mut block = blocks[index].take().unwrap();
+        let last = block.pop().unwrap();
+        stms.extend(block);
+        let next_label = match order.peek() {
+            Some(next) => block_label(blocks[*next].as_ref().unwrap()).clone(),
+            None => done.clone(),
+        };
+        match last {
+            Stm::Jump(_, labels) if labels.len() == 1 && labels[0] == next_label => (),
+            Stm::CJump(op, a, b, t, f) => {
+                if f == next_label {
+                    stms.push(Stm::CJump(op, a, b, t, f));
+                } else if t == next_label {
+                    stms.push(Stm::CJump(op.negate(), a, b, f, t));
+                } else {
+                    let fresh = Label::new();
+                    stms.push(Stm::CJump(op, a, b, t, fresh.clone()));
+                    stms.push(Stm::Label(fresh));
+                    stms.push(jump(f));
+                }
+            }
+            last => stms.push(last),
+        }
+    }
+    stms.push(Stm::Label(done));
+    stms
+}
+
+/// `linearize`, `basic_blocks` and `trace_schedule` in one go.
+pub fn canonicalize(stm: Stm) -> Vec<Stm> {
+    let (blocks, done) = basic_blocks(linearize(stm));
+    trace_schedule(blocks, done)
+}
+
+fn block_label(block: &[Stm]) -> &Label {
+    match block.first() {
+        Some(Stm::Label(label)) => label,
+        _ => unreachable!("basic block without a label"),
+    }
+}
+
+fn jump(label: Label) -> Stm {
+    Stm::Jump(Box::new(Exp::Name(label.clone())), vec![label])
+}
+
+fn nop() -> Stm {
+    Stm::Exp(Box::new(Exp::Const(0)))
+}
+
+fn is_nop(stm: &Stm) -> bool {
+    matches!(stm, Stm::Exp(exp) if matches!(exp.as_ref(), Exp::Const(_)))
+}
+
+/// Sequences two statements, dropping no-ops.
+fn then(a: Stm, b: Stm) -> Stm {
+    if is_nop(&a) {
+        b
+    } else if is_nop(&b) {
+        a
+    } else {
+        Stm::Seq(Box::new(a), Box::new(b))
+    }
+}
+
+/// Whether `stm` can run after `exp` is evaluated without changing its
+/// value. Conservative: only constants, no-ops and position marks commute
+/// with anything.
+fn commute(stm: &Stm, exp: &Exp) -> bool {
+    is_nop(stm) || matches!(stm, Stm::Pos(_)) || matches!(exp, Exp::Name(_) | Exp::Const(_))
+}
+
+/// Pulls the side effects out of `exps`, returning them as one statement
+/// together with side-effect-free expressions that still evaluate to the
+/// same values afterwards.
+fn reorder(exps: Vec<Exp>) -> (Stm, Vec<Exp>) {
+    let mut pending: Vec<(Stm, Exp)> = exps
+        .into_iter()
+        .map(|exp| match exp {
+            Exp::Call(..) => {
+                let t = Temp::new();
+                do_exp(Exp::ESeq(
+                    Box::new(Stm::Move(Box::new(Exp::Temp(t)), Box::new(exp))),
+                    Box::new(Exp::Temp(t)),
+                ))
+            }
+            exp => do_exp(exp),
+        })
+        .collect();
+    // Walk from the right so each expression knows the effects after it.
+    let mut stm = nop();
+    let mut values = Vec::with_capacity(pending.len());
+    while let Some((effects, exp)) = pending.pop() {
+        if commute(&stm, &exp) {
+            values.push(exp);
+            stm = then(effects, stm);
+        } else {
+            let t = Temp::new();
+            values.push(Exp::Temp(t));
+            stm = then(
+                effects,
+                then(Stm::Move(Box::new(Exp::Temp(t)), Box::new(exp)), stm),
+            );
+        }
+    }
+    values.reverse();
+    (stm, values)
+}
+
+fn do_exp(exp: Exp) -> (Stm, Exp) {
+    match exp {
+        Exp::BinOp(op, a, b) => {
+            let (stm, mut values) = reorder(vec![*a, *b]);
+            let b = values.pop().unwrap();
+            let a = values.pop().unwrap();
+            (stm, Exp::BinOp(op, Box::new(a), Box::new(b)))
+        }
+        Exp::Mem(a) => {
+            let (stm, mut values) = reorder(vec![*a]);
+            (stm, Exp::Mem(Box::new(values.pop().unwrap())))
+        }
+        Exp::ESeq(s, e) => {
+            let a = do_stm(*s);
+            let (b, mut values) = reorder(vec![*e]);
+            (then(a, b), values.pop().unwrap())
+        }
+        Exp::Call(f, args) => {
+            let (stm, mut values) = reorder(std::iter::once(*f).chain(args).collect());
+            let args = values.split_off(1);
+            (stm, Exp::Call(Box::new(values.pop().unwrap()), args))
+        }
+        exp => (nop(), exp),
+    }
+}
+
+fn do_stm(stm: Stm) -> Stm {
+    match stm {
+        Stm::Seq(a, b) => {
+            // Long sequences nest one `Seq` per statement, so take the spine
+            // apart in a loop and only recurse into the statements on it.
+            let mut stms = Vec::new();
+            flatten(Stm::Seq(a, b), &mut stms);
+            let stms: Vec<Stm> = stms.into_iter().map(do_stm).collect();
+            stms.into_iter()
+                .rev()
+                .fold(nop(), |rest, stm| then(stm, rest))
+        }
+        Stm::Jump(e, labels) => {
+            let (stm, mut values) = reorder(vec![*e]);
+            then(stm, Stm::Jump(Box::new(values.pop().unwrap()), labels))
+        }
+        Stm::CJump(op, a, b, t, f) => {
+            let (stm, mut values) = reorder(vec![*a, *b]);
+            let b = values.pop().unwrap();
+            let a = values.pop().unwrap();
+            then(stm, Stm::CJump(op, Box::new(a), Box::new(b), t, f))
+        }
+        Stm::Move(dst, src) => match (*dst, *src) {
+            (Exp::Temp(t), Exp::Call(f, args)) => {
+                let (stm, mut values) = reorder(std::iter::once(*f).chain(args).collect());
+                let args = values.split_off(1);
+                let call = Exp::Call(Box::new(values.pop().unwrap()), args);
+                then(stm, Stm::Move(Box::new(Exp::Temp(t)), Box::new(call)))
+            }
+            (Exp::Temp(t), Exp::ESeq(s, e)) => {
+                do_stm(Stm::Seq(s, Box::new(Stm::Move(Box::new(Exp::Temp(t)), e))))
+            }
+            (Exp::Temp(t), src) => {
+                let (stm, mut values) = reorder(vec![src]);
+                let src = values.pop().unwrap();
+                then(stm, Stm::Move(Box::new(Exp::Temp(t)), Box::new(src)))
+            }
+            (Exp::Mem(addr), src) => {
+                let (stm, mut values) = reorder(vec![*addr, src]);
+                let src = values.pop().unwrap();
+                let addr = values.pop().unwrap();
+                then(
+                    stm,
+                    Stm::Move(Box::new(Exp::Mem(Box::new(addr))), Box::new(src)),
+                )
+            }
+            (Exp::ESeq(s, e), src) => do_stm(Stm::Seq(s, Box::new(Stm::Move(e, Box::new(src))))),
+            (dst, _) => unreachable!("cannot move into {:?}", dst),
+        },
+        Stm::Exp(exp) => match *exp {
+            Exp::ESeq(s, e) => do_stm(Stm::Seq(s, Box::new(Stm::Exp(e)))),
+            Exp::Call(f, args) => {
+                let (stm, mut values) = reorder(std::iter::once(*f).chain(args).collect());
+                let args = values.split_off(1);
+                let call = Exp::Call(Box::new(values.pop().unwrap()), args);
+                then(stm, Stm::Exp(Box::new(call)))
+            }
+            exp => {
+                let (stm, mut values) = reorder(vec![exp]);
+                then(stm, Stm::Exp(Box::new(values.pop().unwrap())))
+            }
+        },
+        stm => stm,
+    }
+}
+
+fn flatten(stm: Stm, out: &mut Vec<Stm>) {
+    let mut stack = vec![stm];
+    while let Some(stm) = stack.pop() {
+        match stm {
+            Stm::Seq(a, b) => {
+                stack.push(*b);
+                stack.push(*a);
+            }
+            stm if is_nop(&stm) => (),
+            stm => out.push(stm),
+        }
+    }
+}
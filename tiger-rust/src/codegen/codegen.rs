@@ -0,0 +1,226 @@
+use crate::assem::assem::Instr;
+use crate::canon::canon::canonicalize;
+use crate::frame::frame::{Access, Frame};
+use crate::gc::gc::{is_call, spill_pointers, CallSite, FrameMap};
+use crate::regalloc::regalloc::{allocate, Allocation, Allocator};
+use crate::temp::temp::{Label, Temp};
+use crate::translate::translate::Frag;
+use crate::tree::tree::{BinOp, Exp, Stm};
+
+/// A function ready to print: entry code, allocated body and exit code.
+pub struct Proc {
+    pub prolog: String,
+    pub body: Vec<Instr>,
+    pub epilog: String,
+}
+
+/// What a frame needs beyond its layout to be compiled to machine code.
+pub trait Codegen: Frame {
+    /// Registers the allocator may assign to temps.
+    fn registers() -> Vec<Temp>;
+    /// Registers a call may overwrite.
+    fn caller_saves() -> Vec<Temp>;
+    /// Registers a function must give back as it found them.
+    fn callee_saves() -> Vec<Temp>;
+    fn register_name(register: Temp) -> String;
+
+    /// Tiles one canonical statement. Each call is followed by a label on
+    /// its return address.
+    fn codegen(&self, stm: Stm) -> Vec<Instr>;
+    /// Loads the word at `offset` from the frame pointer into `dst`.
+    fn load(dst: Temp, offset: i64) -> Instr;
+    /// Stores `src` into the word at `offset` from the frame pointer.
+    fn store(src: Temp, offset: i64) -> Instr;
+
+    /// Moves the formals from where the caller put them to where the body
+    /// expects them, and keeps the callee-save registers in temps.
+    fn proc_entry_exit1(&self, body: Stm) -> Stm;
+    /// Marks the registers live at the end of the function.
+    fn proc_entry_exit2(&self, body: Vec<Instr>) -> Vec<Instr>;
+    /// Wraps the allocated body in the code that sets up the frame.
+    fn proc_entry_exit3(&self, body: Vec<Instr>) -> Proc;
+
+    fn string(label: &Label, value: &str) -> String;
+    /// The whole assembly file, from the functions, the string data and
+    /// the frame maps for the collector.
+    fn program(text: &str, data: &str, maps: &[FrameMap]) -> String;
+}
+
+/// Compiles the fragments of a program to one assembly file.
+pub fn compile<F: Codegen>(frags: Vec<Frag<F>>) -> String {
+    compile_with(frags, Allocator::default())
+}
+
+/// [`compile`], choosing the register allocator.
+pub fn compile_with<F: Codegen>(frags: Vec<Frag<F>>, allocator: Allocator) -> String {
+    let mut text = String::new();
+    let mut data = String::new();
+    let mut maps = Vec::new();
+    for frag in frags {
+        match frag {
+            Frag::Proc { body, mut frame } => {
+                let (instrs, map) = instructions(&mut frame, body);
+                text.push_str(&proc(&mut frame, instrs, allocator));
+                maps.push(map);
+            }
+            Frag::String { label, value } => data.push_str(&F::string(&label, &value)),
+        }
+    }
+    F::program(&text, &data, &maps)
+}
+
+/// The instructions of one function before register allocation, and the
+/// map of its pointer slots at each call.
+pub fn instructions<F: Codegen>(frame: &mut F, body: Stm) -> (Vec<Instr>, FrameMap) {
+    let body = frame.proc_entry_exit1(body);
+    let (stms, slots, live) = spill_pointers(frame, canonicalize(body));
+    let mut live = live.into_iter();
+    let mut map = FrameMap {
+        label: Label::new(),
+        name: frame.name().source_name().to_string(),
+        slots,
+        sites: Vec::new(),
+    };
+    let mut instrs = Vec::new();
+    for stm in stms {
+        let call = is_call(&stm);
+        let code = frame.codegen(stm);
+        if call {
+            let ret = code
+                .iter()
+                .find_map(|instr| match instr {
+                    Instr::Label { label, .. } => Some(label.clone()),
+                    _ => None,
+                })
+                .expect("call without a return label");
+            map.sites.push(CallSite {
+                ret,
+                live: live.next().unwrap(),
+            });
+        }
+        instrs.extend(code);
+    }
+    (frame.proc_entry_exit2(instrs), map)
+}
+
+fn proc<F: Codegen>(frame: &mut F, instrs: Vec<Instr>, allocator: Allocator) -> String {
+    let allocated = allocate(frame, instrs, allocator);
+    format_proc::<F>(
+        frame.proc_entry_exit3(allocated.instrs),
+        &allocated.allocation,
+    )
+}
+
+/// The text of an allocated function, leaving out moves that became no-ops.
+pub fn format_proc<F: Codegen>(proc: Proc, allocation: &Allocation) -> String {
+    let name = |temp: Temp| F::register_name(allocation.get(&temp).copied().unwrap_or(temp));
+    let mut out = proc.prolog;
+    for instr in &proc.body {
+        if instr.is_redundant_move(name) {
+            continue;
+        }
+        if let Instr::Oper { assem, .. } = instr {
+            if assem.is_empty() {
+                continue;
+            }
+        }
+        out.push_str(&instr.format(name));
+        out.push('\n');
+    }
+    out.push_str(&proc.epilog);
+    out
+}
+
+/// A string as GNU assembler data for a 64-bit target: its length as a
+/// quad, then its bytes.
+pub fn string_data(label: &Label, value: &str) -> String {
+    let mut ascii = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' => {
+                ascii.push('\\');
+                ascii.push(byte as char);
+            }
+            0x20..=0x7e => ascii.push(byte as char),
+            _ => ascii.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    format!(
+        "\t.p2align 3\n{}:\n\t.quad {}\n\t.ascii \"{}\"\n",
+        label,
+        value.len(),
+        ascii
+    )
+}
+
+/// The frame maps as GNU assembler data for a 64-bit target. Each map
+/// points to the function's name as a Tiger string, then lists its pointer
+/// slots, then each call site as its return address and a bitmap of the
+/// slots live there. The runtime finds the maps through the null-terminated
+/// `tig_frame_maps`.
+pub fn frame_tables(maps: &[FrameMap]) -> String {
+    let mut tables = String::from("\t.section .data.rel.ro,\"aw\"\n");
+    for map in maps {
+        tables.push_str(&format!(
+            "\t.p2align 3\n{0}_name:\n\t.quad {1}\n\t.ascii \"{2}\"\n\t.p2align 3\n",
+            map.label,
+            map.name.len(),
+            map.name
+        ));
+        tables.push_str(&format!(
+            "{0}:\n\t.quad {0}_name\n\t.quad {1}\n",
+            map.label,
+            map.slots.len()
+        ));
+        for offset in &map.slots {
+            tables.push_str(&format!("\t.quad {}\n", offset));
+        }
+        tables.push_str(&format!("\t.quad {}\n", map.sites.len()));
+        for site in &map.sites {
+            let mut bits = vec![0u64; map.slots.len().div_ceil(64)];
+            for index in &site.live {
+                bits[index / 64] |= 1 << (index % 64);
+            }
+            tables.push_str(&format!("\t.quad {}", site.ret));
+            for word in bits {
+                tables.push_str(&format!(", {:#x}", word));
+            }
+            tables.push('\n');
+        }
+    }
+    tables.push_str("\t.globl tig_frame_maps\ntig_frame_maps:\n");
+    for map in maps {
+        tables.push_str(&format!("\t.quad {}\n", map.label));
+    }
+    tables.push_str("\t.quad 0\n");
+    tables
+}
+
+/// The frame-pointer offset of a fresh stack slot.
+pub fn alloc_slot<F: Frame>(frame: &mut F) -> i64 {
+    match frame.alloc_local(true) {
+        Access::InFrame(offset) => offset,
+        Access::InReg(_) => unreachable!("escaping local given a register"),
+    }
+}
+
+/// Splits an address into the expression of its base and a constant
+/// displacement, when the displacement `fits` the target's load and store
+/// offsets.
+pub fn split_address(addr: Exp, fits: fn(i64) -> bool) -> (i64, Exp) {
+    match addr {
+        Exp::BinOp(BinOp::Plus, a, b) if matches!(*b, Exp::Const(c) if fits(c)) => {
+            let Exp::Const(c) = *b else { unreachable!() };
+            (c, *a)
+        }
+        Exp::BinOp(BinOp::Plus, a, b) if matches!(*a, Exp::Const(c) if fits(c)) => {
+            let Exp::Const(c) = *a else { unreachable!() };
+            (c, *b)
+        }
+        Exp::BinOp(BinOp::Minus, a, b) if matches!(*b, Exp::Const(c) if fits(-c)) => {
+            let Exp::Const(c) = *b else { unreachable!() };
+            (-c, *a)
+        }
+        addr => (0, addr),
+    }
+}
@@ -0,0 +1,121 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// File name of the runtime built by the `tiger-runtime` crate.
+pub const RUNTIME_LIB: &str = "libtiger_runtime.a";
+
+/// System libraries the Rust standard library inside the runtime needs.
+const NATIVE_LIBS: [&str; 7] = [
+    "-lgcc_s",
+    "-lutil",
+    "-lrt",
+    "-lpthread",
+    "-lm",
+    "-ldl",
+    "-lc",
+];
+
+/// The runtime library: `$TIGER_RUNTIME` if set, else the one cargo built
+/// next to the running executable.
+pub fn find_runtime() -> Result<PathBuf, String> {
+    if let Some(path) = env::var_os("TIGER_RUNTIME") {
+        return Ok(PathBuf::from(path));
+    }
+    let exe = env::current_exe().map_err(|err| err.to_string())?;
+    exe.ancestors()
+        .skip(1)
+        .take(2)
+        .map(|dir| dir.join(RUNTIME_LIB))
+        .find(|path| path.exists())
+        .ok_or_else(|| {
+            format!(
+                "cannot find {}; build it with `cargo build -p tiger-runtime` or set TIGER_RUNTIME",
+                RUNTIME_LIB
+            )
+        })
+}
+
+/// The major version in the `--version` output `text` of an LLVM tool.
+pub fn parse_llvm_version(text: &str) -> Option<u32> {
+    text.split("version ")
+        .nth(1)?
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// The major version of the LLVM tool `tool`, if it runs.
+pub fn llvm_version(tool: &str) -> Option<u32> {
+    let output = Command::new(tool).arg("--version").output().ok()?;
+    parse_llvm_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Whether `tool`, if it runs, reads the opaque pointers of the LLVM
+/// backend only when given `-opaque-pointers`, as LLVM 14 and before do.
+pub fn needs_opaque_pointers_flag(tool: &str) -> Option<bool> {
+    llvm_version(tool).map(|major| major <= 14)
+}
+
+/// Assembles `asm` and links it with `runtime` into the executable `output`.
+pub fn link(asm: &str, output: &Path, runtime: &Path) -> Result<(), String> {
+    build("cc", &[], asm, "s", output, runtime)
+}
+
+/// Compiles the C file `c` and links it with `runtime` into the executable
+/// `output`.
+pub fn link_c(c: &str, output: &Path, runtime: &Path) -> Result<(), String> {
+    build("cc", &[], c, "c", output, runtime)
+}
+
+/// Compiles the LLVM module `ll` with `clang` and links it with `runtime`
+/// into the executable `output`.
+pub fn link_llvm(ll: &str, output: &Path, runtime: &Path) -> Result<(), String> {
+    let flags: &[&str] = match needs_opaque_pointers_flag("clang") {
+        Some(true) => &["-mllvm", "-opaque-pointers"],
+        _ => &[],
+    };
+    build("clang", flags, ll, "ll", output, runtime)
+}
+
+/// Has the C compiler `compiler` build `output` from `source` with the
+/// extra `flags`, the source written to a scratch directory of its own
+/// under a name ending in `extension`.
+fn build(
+    compiler: &str,
+    flags: &[&str],
+    source: &str,
+    extension: &str,
+    output: &Path,
+    runtime: &Path,
+) -> Result<(), String> {
+    static BUILDS: AtomicUsize = AtomicUsize::new(0);
+    let dir = env::temp_dir().join(format!(
+        "tiger-{}-{}",
+        process::id(),
+        BUILDS.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+    let source_path = dir.join("main").with_extension(extension);
+    let status = fs::write(&source_path, source)
+        .map_err(|err| format!("{}: {}", source_path.display(), err))
+        .and_then(|()| {
+            Command::new(compiler)
+                .args(flags)
+                .arg("-o")
+                .arg(output)
+                .arg(&source_path)
+                .arg(runtime)
+                .args(NATIVE_LIBS)
+                .status()
+                .map_err(|err| format!("{}: {}", compiler, err))
+        });
+    let _ = fs::remove_dir_all(&dir);
+    match status? {
+        status if status.success() => Ok(()),
+        status => Err(format!("{} failed with {}", compiler, status)),
+    }
+}
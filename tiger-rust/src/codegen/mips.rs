@@ -0,0 +1,424 @@
+//! Instruction selection for 32-bit MIPS. A Tiger `int` is a machine word,
+//! so arithmetic here wraps at 32 bits where the other backends wrap at 64.
+
+use crate::assem::assem::Instr;
+use crate::codegen::codegen::{split_address, Codegen, Proc};
+use crate::frame::frame::Frame;
+use crate::frame::mips::*;
+use crate::gc::gc::FrameMap;
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree::{seq, BinOp, Exp, RelOp, Stm};
+
+const CALLER_SAVES: [Temp; 16] = [
+    V0, V1, A0, A1, A2, A3, T0, T1, T2, T3, T4, T5, T6, T7, T8, T9,
+];
+const CALLEE_SAVES: [Temp; 8] = [S0, S1, S2, S3, S4, S5, S6, S7];
+
+/// The runtime functions the compiled program calls, written for SPIM.
+/// Appended to the output of [`compile_with`], it makes a file SPIM runs;
+/// the simulator has them built in and does without.
+///
+/// [`compile_with`]: crate::codegen::codegen::compile_with
+pub const RUNTIME: &str = include_str!("mips_runtime.s");
+
+impl Codegen for MipsFrame {
+    fn registers() -> Vec<Temp> {
+        CALLER_SAVES.iter().chain(&CALLEE_SAVES).copied().collect()
+    }
+
+    fn caller_saves() -> Vec<Temp> {
+        CALLER_SAVES.to_vec()
+    }
+
+    fn callee_saves() -> Vec<Temp> {
+        CALLEE_SAVES.to_vec()
+    }
+
+    fn register_name(register: Temp) -> String {
+        match REGISTER_NAMES.get(register.index() as usize) {
+            Some(name) => format!("${}", name),
+            None => format!("${}", register),
+        }
+    }
+
+    fn codegen(&self, stm: Stm) -> Vec<Instr> {
+        let mut munch = Munch { instrs: Vec::new() };
+        munch.stm(stm);
+        munch.instrs
+    }
+
+    fn load(dst: Temp, offset: i64) -> Instr {
+        Instr::oper(format!("\tlw `d0, {}($fp)", offset), vec![dst], vec![])
+    }
+
+    fn store(src: Temp, offset: i64) -> Instr {
+        Instr::oper(format!("\tsw `s0, {}($fp)", offset), vec![], vec![src])
+    }
+
+    fn proc_entry_exit1(&self, body: Stm) -> Stm {
+        let saved: Vec<(Temp, Temp)> = CALLEE_SAVES
+            .iter()
+            .map(|register| (Temp::new(), *register))
+            .collect();
+        let mut stms: Vec<Stm> = saved
+            .iter()
+            .map(|(temp, register)| move_temp(*temp, Exp::Temp(*register)))
+            .collect();
+        for (index, access) in self.formals().iter().enumerate() {
+            let src = match ARG_REGISTERS.get(index) {
+                Some(register) => Exp::Temp(*register),
+                None => Exp::Mem(Box::new(Exp::BinOp(
+                    BinOp::Plus,
+                    Box::new(Exp::Temp(FP)),
+                    Box::new(Exp::Const(
+                        Self::WORD_SIZE * (index - ARG_REGISTERS.len()) as i64,
+                    )),
+                ))),
+            };
+            let dst = Self::exp(*access, Exp::Temp(FP));
+            stms.push(Stm::Move(Box::new(dst), Box::new(src)));
+        }
+        stms.push(body);
+        stms.extend(
+            saved
+                .iter()
+                .map(|(temp, register)| move_temp(*register, Exp::Temp(*temp))),
+        );
+        seq(stms)
+    }
+
+    fn proc_entry_exit2(&self, mut body: Vec<Instr>) -> Vec<Instr> {
+        body.push(Instr::Oper {
+            assem: String::new(),
+            dst: vec![],
+            src: [V0, ZERO, SP, FP]
+                .iter()
+                .chain(&CALLEE_SAVES)
+                .copied()
+                .collect(),
+            jump: Some(vec![]),
+        });
+        body
+    }
+
+    fn proc_entry_exit3(&self, body: Vec<Instr>) -> Proc {
+        let size = (self.frame_size() + SAVED + 7) / 8 * 8;
+        let name = self.name();
+        let prolog = format!(
+            "\t.globl {0}\n{0}:\n\taddiu $sp, $sp, -{1}\n\tsw $ra, {2}($sp)\n\
+             \tsw $fp, {3}($sp)\n\taddiu $fp, $sp, {1}\n",
+            name,
+            size,
+            size - 4,
+            size - 8
+        );
+        Proc {
+            prolog,
+            body,
+            epilog: String::from(
+                "\tlw $ra, -4($fp)\n\tmove $sp, $fp\n\tlw $fp, -8($fp)\n\tjr $ra\n\n",
+            ),
+        }
+    }
+
+    /// SPIM's `.ascii` only knows a few escapes, so other bytes go in
+    /// `.byte` directives of their own.
+    fn string(label: &Label, value: &str) -> String {
+        let mut out = format!("\t.align 2\n{}:\n\t.word {}\n", label, value.len());
+        let mut ascii = String::new();
+        for byte in value.bytes() {
+            match byte {
+                b'"' | b'\\' => {
+                    ascii.push('\\');
+                    ascii.push(byte as char);
+                }
+                b'\n' => ascii.push_str("\\n"),
+                b'\t' => ascii.push_str("\\t"),
+                0x20..=0x7e => ascii.push(byte as char),
+                _ => {
+                    if !ascii.is_empty() {
+                        out.push_str(&format!("\t.ascii \"{}\"\n", ascii));
+                        ascii.clear();
+                    }
+                    out.push_str(&format!("\t.byte {}\n", byte));
+                }
+            }
+        }
+        if !ascii.is_empty() {
+            out.push_str(&format!("\t.ascii \"{}\"\n", ascii));
+        }
+        out
+    }
+
+    /// SPIM starts at `main`, which runs the program and exits. Nothing
+    /// collects the heap on this target, so the frame maps are left out,
+    /// and the runtime functions are left to [`RUNTIME`].
+    fn program(text: &str, data: &str, _maps: &[FrameMap]) -> String {
+        format!(
+            "\t.text\n\t.globl main\nmain:\n\tli $a0, 0\n\tjal tigermain\n\
+             \tli $v0, 10\n\tsyscall\n\n{}\t.data\n{}",
+            text, data
+        )
+    }
+}
+
+fn move_temp(dst: Temp, src: Exp) -> Stm {
+    Stm::Move(Box::new(Exp::Temp(dst)), Box::new(src))
+}
+
+/// Whether `value` fits the sign-extended 16-bit immediate of most
+/// instructions.
+fn imm16(value: i64) -> bool {
+    i16::try_from(value).is_ok()
+}
+
+/// A constant as the 32-bit word the target computes with.
+fn word(value: i64) -> i32 {
+    value as i32
+}
+
+fn branch(op: RelOp) -> &'static str {
+    match op {
+        RelOp::Eq => "beq",
+        RelOp::Ne => "bne",
+        RelOp::Lt => "blt",
+        RelOp::Gt => "bgt",
+        RelOp::Le => "ble",
+        RelOp::Ge => "bge",
+        RelOp::Ult => "bltu",
+        RelOp::Ule => "bleu",
+        RelOp::Ugt => "bgtu",
+        RelOp::Uge => "bgeu",
+    }
+}
+
+/// Maximal munch over canonical trees, in SPIM syntax.
+struct Munch {
+    instrs: Vec<Instr>,
+}
+
+impl Munch {
+    fn emit(&mut self, instr: Instr) {
+        self.instrs.push(instr)
+    }
+
+    fn oper(&mut self, assem: String, dst: Vec<Temp>, src: Vec<Temp>) {
+        self.emit(Instr::oper(assem, dst, src))
+    }
+
+    fn mov(&mut self, dst: Temp, src: Temp) {
+        self.emit(Instr::Move {
+            assem: String::from("\tmove `d0, `s0"),
+            dst,
+            src,
+        })
+    }
+
+    /// The `offset(base)` operand of a `lw` or `sw` of `addr`, folding an
+    /// offset that fits 16 bits.
+    fn address(&mut self, addr: Exp) -> (i64, Temp) {
+        let (offset, base) = split_address(addr, imm16);
+        (offset, self.exp(base))
+    }
+
+    fn stm(&mut self, stm: Stm) {
+        match stm {
+            Stm::Seq(a, b) => {
+                self.stm(*a);
+                self.stm(*b);
+            }
+            Stm::Label(label) => self.emit(Instr::Label {
+                assem: format!("{}:", label),
+                label,
+            }),
+            // Left as a comment for the simulator, which reports errors at
+            // the position marked last before them.
+            Stm::Pos(pos) => self.oper(
+                format!("\t# {}.{}", pos >> 16, pos & 0xffff),
+                vec![],
+                vec![],
+            ),
+            Stm::Jump(target, labels) => match *target {
+                Exp::Name(label) => self.emit(Instr::Oper {
+                    assem: String::from("\tj `j0"),
+                    dst: vec![],
+                    src: vec![],
+                    jump: Some(vec![label]),
+                }),
+                target => {
+                    let target = self.exp(target);
+                    self.emit(Instr::Oper {
+                        assem: String::from("\tjr `s0"),
+                        dst: vec![],
+                        src: vec![target],
+                        jump: Some(labels),
+                    })
+                }
+            },
+            Stm::CJump(op, a, b, t, f) => {
+                let (op, a, b) = match (*a, *b) {
+                    (a @ Exp::Const(_), b) if !matches!(b, Exp::Const(_)) => (op.commute(), b, a),
+                    (a, b) => (op, a, b),
+                };
+                let a = self.exp(a);
+                let (operand, src) = match b {
+                    Exp::Const(0) => (String::from("$zero"), vec![a]),
+                    Exp::Const(c) if imm16(c) => (c.to_string(), vec![a]),
+                    b => (String::from("`s1"), vec![a, self.exp(b)]),
+                };
+                self.emit(Instr::Oper {
+                    assem: format!("\t{} `s0, {}, `j0", branch(op), operand),
+                    dst: vec![],
+                    src,
+                    jump: Some(vec![t, f]),
+                })
+            }
+            Stm::Move(dst, src) => match (*dst, *src) {
+                (Exp::Temp(t), Exp::Call(f, args)) => {
+                    self.call(*f, args);
+                    self.mov(t, V0)
+                }
+                (Exp::Temp(t), src) => self.exp_into(t, src),
+                (Exp::Mem(addr), src) => {
+                    let (offset, base) = self.address(*addr);
+                    match src {
+                        Exp::Const(0) => {
+                            self.oper(format!("\tsw $zero, {}(`s0)", offset), vec![], vec![base])
+                        }
+                        src => {
+                            let src = self.exp(src);
+                            self.oper(
+                                format!("\tsw `s0, {}(`s1)", offset),
+                                vec![],
+                                vec![src, base],
+                            )
+                        }
+                    }
+                }
+                (dst, _) => unreachable!("cannot move into {:?}", dst),
+            },
+            Stm::Exp(exp) => match *exp {
+                Exp::Call(f, args) => self.call(*f, args),
+                exp => {
+                    self.exp(exp);
+                }
+            },
+        }
+    }
+
+    /// Computes `exp` into `dst` with one `li`, `la`, `lw` or operation
+    /// when it can, constants cut to a word.
+    fn exp_into(&mut self, dst: Temp, exp: Exp) {
+        match exp {
+            Exp::Temp(src) => self.mov(dst, src),
+            Exp::Const(c) => self.oper(format!("\tli `d0, {}", word(c)), vec![dst], vec![]),
+            Exp::Name(label) => self.oper(format!("\tla `d0, {}", label), vec![dst], vec![]),
+            Exp::Mem(addr) => {
+                let (offset, base) = self.address(*addr);
+                self.oper(format!("\tlw `d0, {}(`s0)", offset), vec![dst], vec![base])
+            }
+            Exp::BinOp(op, a, b) => self.binop_into(dst, op, *a, *b),
+            exp => {
+                let src = self.exp(exp);
+                self.mov(dst, src)
+            }
+        }
+    }
+
+    fn exp(&mut self, exp: Exp) -> Temp {
+        match exp {
+            Exp::Temp(t) => t,
+            Exp::Call(f, args) => {
+                self.call(*f, args);
+                let r = Temp::new();
+                self.mov(r, V0);
+                r
+            }
+            Exp::ESeq(..) => unreachable!("ESeq in canonical tree"),
+            exp => {
+                let r = Temp::new();
+                self.exp_into(r, exp);
+                r
+            }
+        }
+    }
+
+    fn binop_into(&mut self, dst: Temp, op: BinOp, a: Exp, b: Exp) {
+        let (name, immediate) = match op {
+            BinOp::Plus => ("addu", Some("addiu")),
+            BinOp::Minus => ("subu", None),
+            BinOp::Mul => ("mul", None),
+            BinOp::And => ("and", Some("andi")),
+            BinOp::Or => ("or", Some("ori")),
+            BinOp::Xor => ("xor", Some("xori")),
+            BinOp::LShift => ("sllv", Some("sll")),
+            BinOp::RShift => ("srlv", Some("srl")),
+            BinOp::ArShift => ("srav", Some("sra")),
+            BinOp::Div => {
+                let (a, b) = (self.exp(a), self.exp(b));
+                self.oper(
+                    String::from("\tdiv `s0, `s1\n\tmflo `d0"),
+                    vec![dst],
+                    vec![a, b],
+                );
+                return;
+            }
+        };
+        let fits = |c: i64| match op {
+            BinOp::Plus => imm16(c),
+            BinOp::Minus => imm16(-c),
+            BinOp::And | BinOp::Or | BinOp::Xor => (0..=0xffff).contains(&c),
+            BinOp::Mul => false,
+            _ => (0..32).contains(&c),
+        };
+        match b {
+            Exp::Const(c) if fits(c) => {
+                let (name, c) = match op {
+                    BinOp::Minus => ("addiu", -c),
+                    _ => (immediate.unwrap(), c),
+                };
+                let a = self.exp(a);
+                self.oper(format!("\t{} `d0, `s0, {}", name, c), vec![dst], vec![a])
+            }
+            b => {
+                let (a, b) = (self.exp(a), self.exp(b));
+                self.oper(format!("\t{} `d0, `s0, `s1", name), vec![dst], vec![a, b])
+            }
+        }
+    }
+
+    /// Passes the first four arguments in registers and the rest in an
+    /// outgoing area at the stack pointer, kept 8-byte aligned.
+    fn call(&mut self, f: Exp, args: Vec<Exp>) {
+        let args: Vec<Temp> = args.into_iter().map(|arg| self.exp(arg)).collect();
+        let (in_regs, on_stack) = args.split_at(args.len().min(ARG_REGISTERS.len()));
+        let bytes = on_stack.len().div_ceil(2) * 8;
+        if !on_stack.is_empty() {
+            self.oper(format!("\taddiu $sp, $sp, -{}", bytes), vec![], vec![]);
+        }
+        for (index, arg) in on_stack.iter().enumerate() {
+            self.oper(format!("\tsw `s0, {}($sp)", 4 * index), vec![], vec![*arg]);
+        }
+        for (register, arg) in ARG_REGISTERS.iter().zip(in_regs) {
+            self.mov(*register, *arg);
+        }
+        let mut src = ARG_REGISTERS[..in_regs.len()].to_vec();
+        let assem = match f {
+            Exp::Name(label) => format!("\tjal {}", label),
+            f => {
+                src.insert(0, self.exp(f));
+                String::from("\tjalr `s0")
+            }
+        };
+        let dst = CALLER_SAVES.iter().chain([&RA]).copied().collect();
+        self.oper(assem, dst, src);
+        let ret = Label::new();
+        self.emit(Instr::Label {
+            assem: format!("{}:", ret),
+            label: ret,
+        });
+        if !on_stack.is_empty() {
+            self.oper(format!("\taddiu $sp, $sp, {}", bytes), vec![], vec![]);
+        }
+    }
+}
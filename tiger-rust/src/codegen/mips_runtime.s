@@ -0,0 +1,353 @@
+# The runtime functions of the MIPS backend, on SPIM's system calls.
+# Strings are a length word followed by the bytes, arrays a length word
+# followed by the elements. The heap comes from sbrk and is never freed.
+# Runtime errors go to standard output, since SPIM has no other, and exit
+# with status 1.
+
+	.text
+tig_print:
+	lw $t0, 0($a0)
+	addiu $t1, $a0, 4
+	addu $t0, $t1, $t0
+	li $v0, 11
+tig_print_loop:
+	beq $t1, $t0, tig_print_done
+	lbu $a0, 0($t1)
+	syscall
+	addiu $t1, $t1, 1
+	b tig_print_loop
+tig_print_done:
+	jr $ra
+
+tig_flush:
+	jr $ra
+
+tig_getchar:
+	li $v0, 12
+	syscall
+	move $t0, $v0
+	li $a0, 8
+	li $v0, 9
+	syscall
+	bltz $t0, tig_getchar_eof
+	li $t1, 1
+	sw $t1, 0($v0)
+	sb $t0, 4($v0)
+	jr $ra
+tig_getchar_eof:
+	sw $zero, 0($v0)
+	jr $ra
+
+tig_ord:
+	lw $t0, 0($a0)
+	li $v0, -1
+	beqz $t0, tig_ord_done
+	lbu $v0, 4($a0)
+tig_ord_done:
+	jr $ra
+
+tig_chr:
+	bltz $a0, tig_chr_range
+	bgt $a0, 255, tig_chr_range
+	move $t0, $a0
+	li $a0, 8
+	li $v0, 9
+	syscall
+	li $t1, 1
+	sw $t1, 0($v0)
+	sb $t0, 4($v0)
+	jr $ra
+tig_chr_range:
+	move $t0, $a0
+	move $a0, $a1
+	jal tig_fail_at
+	la $a0, tig_chr_msg
+	li $v0, 4
+	syscall
+	move $a0, $t0
+	li $v0, 1
+	syscall
+	la $a0, tig_range_msg
+	li $v0, 4
+	syscall
+	j tig_fail
+
+tig_size:
+	lw $v0, 0($a0)
+	jr $ra
+
+tig_substring:
+	lw $t0, 0($a0)
+	bltz $a1, tig_substring_range
+	bltz $a2, tig_substring_range
+	addu $t1, $a1, $a2
+	bgt $t1, $t0, tig_substring_range
+	move $t2, $a0
+	addiu $a0, $a2, 7
+	li $t9, -4
+	and $a0, $a0, $t9
+	li $v0, 9
+	syscall
+	sw $a2, 0($v0)
+	addiu $t3, $t2, 4
+	addu $t3, $t3, $a1
+	addu $t5, $t3, $a2
+	addiu $t4, $v0, 4
+tig_substring_loop:
+	beq $t3, $t5, tig_substring_done
+	lbu $t6, 0($t3)
+	sb $t6, 0($t4)
+	addiu $t3, $t3, 1
+	addiu $t4, $t4, 1
+	b tig_substring_loop
+tig_substring_done:
+	jr $ra
+tig_substring_range:
+	move $t2, $a0
+	move $t3, $a1
+	move $t4, $a2
+	move $a0, $a3
+	jal tig_fail_at
+	la $a0, tig_substring_msg
+	li $v0, 4
+	syscall
+	move $a0, $t2
+	jal tig_print
+	la $a0, tig_quote_msg
+	li $v0, 4
+	syscall
+	move $a0, $t3
+	li $v0, 1
+	syscall
+	la $a0, tig_comma_msg
+	li $v0, 4
+	syscall
+	move $a0, $t4
+	li $v0, 1
+	syscall
+	la $a0, tig_range_msg
+	li $v0, 4
+	syscall
+	j tig_fail
+
+tig_concat:
+	lw $t0, 0($a0)
+	lw $t1, 0($a1)
+	addu $t2, $t0, $t1
+	move $t3, $a0
+	move $t4, $a1
+	addiu $a0, $t2, 7
+	li $t9, -4
+	and $a0, $a0, $t9
+	li $v0, 9
+	syscall
+	sw $t2, 0($v0)
+	addiu $t5, $v0, 4
+	addiu $t6, $t3, 4
+	addu $t7, $t6, $t0
+tig_concat_first:
+	beq $t6, $t7, tig_concat_second
+	lbu $t8, 0($t6)
+	sb $t8, 0($t5)
+	addiu $t6, $t6, 1
+	addiu $t5, $t5, 1
+	b tig_concat_first
+tig_concat_second:
+	addiu $t6, $t4, 4
+	addu $t7, $t6, $t1
+tig_concat_loop:
+	beq $t6, $t7, tig_concat_done
+	lbu $t8, 0($t6)
+	sb $t8, 0($t5)
+	addiu $t6, $t6, 1
+	addiu $t5, $t5, 1
+	b tig_concat_loop
+tig_concat_done:
+	jr $ra
+
+tig_not:
+	sltiu $v0, $a0, 1
+	jr $ra
+
+tig_exit:
+	li $v0, 17
+	syscall
+
+tig_stringEqual:
+	lw $t0, 0($a0)
+	lw $t1, 0($a1)
+	li $v0, 0
+	bne $t0, $t1, tig_stringEqual_done
+	addiu $t2, $a0, 4
+	addiu $t3, $a1, 4
+	addu $t4, $t2, $t0
+tig_stringEqual_loop:
+	beq $t2, $t4, tig_stringEqual_equal
+	lbu $t5, 0($t2)
+	lbu $t6, 0($t3)
+	bne $t5, $t6, tig_stringEqual_done
+	addiu $t2, $t2, 1
+	addiu $t3, $t3, 1
+	b tig_stringEqual_loop
+tig_stringEqual_equal:
+	li $v0, 1
+tig_stringEqual_done:
+	jr $ra
+
+tig_stringCompare:
+	lw $t0, 0($a0)
+	lw $t1, 0($a1)
+	addiu $t2, $a0, 4
+	addiu $t3, $a1, 4
+	move $t4, $t0
+	ble $t0, $t1, tig_stringCompare_shorter
+	move $t4, $t1
+tig_stringCompare_shorter:
+	addu $t4, $t2, $t4
+tig_stringCompare_loop:
+	beq $t2, $t4, tig_stringCompare_lengths
+	lbu $t5, 0($t2)
+	lbu $t6, 0($t3)
+	bltu $t5, $t6, tig_stringCompare_less
+	bgtu $t5, $t6, tig_stringCompare_greater
+	addiu $t2, $t2, 1
+	addiu $t3, $t3, 1
+	b tig_stringCompare_loop
+tig_stringCompare_lengths:
+	blt $t0, $t1, tig_stringCompare_less
+	bgt $t0, $t1, tig_stringCompare_greater
+	li $v0, 0
+	jr $ra
+tig_stringCompare_less:
+	li $v0, -1
+	jr $ra
+tig_stringCompare_greater:
+	li $v0, 1
+	jr $ra
+
+tig_initArray:
+	bltz $a0, tig_initArray_negative
+	move $t0, $a0
+	sll $a0, $a0, 2
+	addiu $a0, $a0, 4
+	li $v0, 9
+	syscall
+	sw $t0, 0($v0)
+	addiu $t1, $v0, 4
+	sll $t2, $t0, 2
+	addu $t2, $t1, $t2
+tig_initArray_loop:
+	beq $t1, $t2, tig_initArray_done
+	sw $a1, 0($t1)
+	addiu $t1, $t1, 4
+	b tig_initArray_loop
+tig_initArray_done:
+	jr $ra
+tig_initArray_negative:
+	move $t0, $a0
+	move $a0, $a3
+	jal tig_fail_at
+	la $a0, tig_size_msg
+	li $v0, 4
+	syscall
+	move $a0, $t0
+	li $v0, 1
+	syscall
+	la $a0, tig_negative_msg
+	li $v0, 4
+	syscall
+	j tig_fail
+
+tig_allocRecord:
+	addiu $a0, $a0, 3
+	li $t9, -4
+	and $a0, $a0, $t9
+	li $v0, 9
+	syscall
+	jr $ra
+
+tig_nilRecord:
+	jal tig_fail_at
+	la $a0, tig_nil_msg
+	li $v0, 4
+	syscall
+	j tig_fail
+
+tig_indexOutOfRange:
+	move $t0, $a0
+	move $t1, $a1
+	move $a0, $a2
+	jal tig_fail_at
+	la $a0, tig_index_msg
+	li $v0, 4
+	syscall
+	move $a0, $t0
+	li $v0, 1
+	syscall
+	la $a0, tig_bounds_msg
+	li $v0, 4
+	syscall
+	move $a0, $t1
+	li $v0, 1
+	syscall
+	j tig_fail
+
+tig_divisionByZero:
+	jal tig_fail_at
+	la $a0, tig_zero_msg
+	li $v0, 4
+	syscall
+	j tig_fail
+
+# Prints the source position in $a0, packed as the compiler packs it, as
+# the start of an error message.
+tig_fail_at:
+	move $t9, $a0
+	sra $a0, $t9, 16
+	li $v0, 1
+	syscall
+	li $a0, 46
+	li $v0, 11
+	syscall
+	andi $a0, $t9, 0xffff
+	li $v0, 1
+	syscall
+	la $a0, tig_colon_msg
+	li $v0, 4
+	syscall
+	jr $ra
+
+# Ends the error message and the program.
+tig_fail:
+	li $a0, 10
+	li $v0, 11
+	syscall
+	li $a0, 1
+	li $v0, 17
+	syscall
+
+	.data
+tig_colon_msg:
+	.asciiz ": "
+tig_chr_msg:
+	.asciiz "chr("
+tig_range_msg:
+	.asciiz ") out of range"
+tig_substring_msg:
+	.asciiz "substring(\""
+tig_quote_msg:
+	.ascii "\""
+tig_comma_msg:
+	.asciiz ", "
+tig_size_msg:
+	.asciiz "array size "
+tig_negative_msg:
+	.asciiz " is negative"
+tig_nil_msg:
+	.asciiz "nil record dereferenced"
+tig_index_msg:
+	.asciiz "index "
+tig_bounds_msg:
+	.asciiz " out of range for array of size "
+tig_zero_msg:
+	.asciiz "division by zero"
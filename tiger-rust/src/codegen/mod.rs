@@ -0,0 +1,5 @@
+pub mod codegen;
+pub mod link;
+pub mod mips;
+pub mod riscv64;
+pub mod x86_64;
@@ -0,0 +1,370 @@
+use crate::assem::assem::Instr;
+use crate::codegen::codegen::{frame_tables, split_address, string_data, Codegen, Proc};
+use crate::frame::frame::Frame;
+use crate::frame::riscv64::*;
+use crate::gc::gc::FrameMap;
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree::{seq, BinOp, Exp, RelOp, Stm};
+
+const CALLER_SAVES: [Temp; 15] = [T0, T1, T2, T3, T4, T5, T6, A0, A1, A2, A3, A4, A5, A6, A7];
+const CALLEE_SAVES: [Temp; 11] = [S1, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11];
+
+impl Codegen for Riscv64Frame {
+    fn registers() -> Vec<Temp> {
+        CALLER_SAVES.iter().chain(&CALLEE_SAVES).copied().collect()
+    }
+
+    fn caller_saves() -> Vec<Temp> {
+        CALLER_SAVES.to_vec()
+    }
+
+    fn callee_saves() -> Vec<Temp> {
+        CALLEE_SAVES.to_vec()
+    }
+
+    fn register_name(register: Temp) -> String {
+        match REGISTER_NAMES.get(register.index() as usize) {
+            Some(name) => name.to_string(),
+            None => register.to_string(),
+        }
+    }
+
+    fn codegen(&self, stm: Stm) -> Vec<Instr> {
+        let mut munch = Munch { instrs: Vec::new() };
+        munch.stm(stm);
+        munch.instrs
+    }
+
+    fn load(dst: Temp, offset: i64) -> Instr {
+        Instr::oper(format!("\tld `d0, {}(s0)", offset), vec![dst], vec![])
+    }
+
+    fn store(src: Temp, offset: i64) -> Instr {
+        Instr::oper(format!("\tsd `s0, {}(s0)", offset), vec![], vec![src])
+    }
+
+    fn proc_entry_exit1(&self, body: Stm) -> Stm {
+        let saved: Vec<(Temp, Temp)> = CALLEE_SAVES
+            .iter()
+            .map(|register| (Temp::new(), *register))
+            .collect();
+        let mut stms: Vec<Stm> = saved
+            .iter()
+            .map(|(temp, register)| move_temp(*temp, Exp::Temp(*register)))
+            .collect();
+        for (index, access) in self.formals().iter().enumerate() {
+            let src = match ARG_REGISTERS.get(index) {
+                Some(register) => Exp::Temp(*register),
+                None => Exp::Mem(Box::new(Exp::BinOp(
+                    BinOp::Plus,
+                    Box::new(Exp::Temp(S0)),
+                    Box::new(Exp::Const(16 + 8 * (index - ARG_REGISTERS.len()) as i64)),
+                ))),
+            };
+            let dst = Self::exp(*access, Exp::Temp(S0));
+            stms.push(Stm::Move(Box::new(dst), Box::new(src)));
+        }
+        stms.push(body);
+        stms.extend(
+            saved
+                .iter()
+                .map(|(temp, register)| move_temp(*register, Exp::Temp(*temp))),
+        );
+        seq(stms)
+    }
+
+    fn proc_entry_exit2(&self, mut body: Vec<Instr>) -> Vec<Instr> {
+        body.push(Instr::Oper {
+            assem: String::new(),
+            dst: vec![],
+            src: [A0, ZERO, SP, S0]
+                .iter()
+                .chain(&CALLEE_SAVES)
+                .copied()
+                .collect(),
+            jump: Some(vec![]),
+        });
+        body
+    }
+
+    /// Saves `ra` and `s0` as x86-64's `call` and `pushq %rbp` would, then
+    /// makes room for the locals. Frames too big for an immediate go
+    /// through `t0`, which no argument lives in.
+    fn proc_entry_exit3(&self, body: Vec<Instr>) -> Proc {
+        let size = (self.frame_size() + 15) / 16 * 16;
+        let name = self.name();
+        let mut prolog = format!("\t.globl {0}\n\t.type {0}, @function\n{0}:\n", name);
+        prolog.push_str("\taddi sp, sp, -16\n\tsd ra, 8(sp)\n\tsd s0, 0(sp)\n\tmv s0, sp\n");
+        if imm12(-size) && size > 0 {
+            prolog.push_str(&format!("\taddi sp, sp, -{}\n", size));
+        } else if size > 0 {
+            prolog.push_str(&format!("\tli t0, {}\n\tsub sp, sp, t0\n", size));
+        }
+        Proc {
+            prolog,
+            body,
+            epilog: format!(
+                "\tmv sp, s0\n\tld ra, 8(sp)\n\tld s0, 0(sp)\n\taddi sp, sp, 16\n\tret\n\
+                 \t.size {0}, .-{0}\n\n",
+                name
+            ),
+        }
+    }
+
+    fn string(label: &Label, value: &str) -> String {
+        string_data(label, value)
+    }
+
+    /// The frame maps go in the tables the runtime reads, as
+    /// [`frame_tables`] lays them out.
+    fn program(text: &str, data: &str, maps: &[FrameMap]) -> String {
+        format!(
+            "\t.text\n{}\t.section .rodata\n{}{}\t.section .note.GNU-stack,\"\",@progbits\n",
+            text,
+            data,
+            frame_tables(maps)
+        )
+    }
+}
+
+fn move_temp(dst: Temp, src: Exp) -> Stm {
+    Stm::Move(Box::new(Exp::Temp(dst)), Box::new(src))
+}
+
+/// Whether `value` fits the sign-extended 12-bit immediate of I-type and
+/// S-type instructions.
+fn imm12(value: i64) -> bool {
+    (-2048..2048).contains(&value)
+}
+
+/// The branch for `op`, taking both operands in registers. `bgt`, `ble`,
+/// `bgtu` and `bleu` are assembler aliases with the operands swapped.
+fn branch(op: RelOp) -> &'static str {
+    match op {
+        RelOp::Eq => "beq",
+        RelOp::Ne => "bne",
+        RelOp::Lt => "blt",
+        RelOp::Gt => "bgt",
+        RelOp::Le => "ble",
+        RelOp::Ge => "bge",
+        RelOp::Ult => "bltu",
+        RelOp::Ule => "bleu",
+        RelOp::Ugt => "bgtu",
+        RelOp::Uge => "bgeu",
+    }
+}
+
+/// Maximal munch over canonical trees, in GNU assembler syntax.
+struct Munch {
+    instrs: Vec<Instr>,
+}
+
+impl Munch {
+    fn emit(&mut self, instr: Instr) {
+        self.instrs.push(instr)
+    }
+
+    fn oper(&mut self, assem: String, dst: Vec<Temp>, src: Vec<Temp>) {
+        self.emit(Instr::oper(assem, dst, src))
+    }
+
+    fn mov(&mut self, dst: Temp, src: Temp) {
+        self.emit(Instr::Move {
+            assem: String::from("\tmv `d0, `s0"),
+            dst,
+            src,
+        })
+    }
+
+    /// The `offset(base)` operand of a load or store of `addr`, folding
+    /// an offset that fits 12 bits.
+    fn address(&mut self, addr: Exp) -> (i64, Temp) {
+        let (offset, base) = split_address(addr, imm12);
+        (offset, self.exp(base))
+    }
+
+    fn stm(&mut self, stm: Stm) {
+        match stm {
+            Stm::Seq(a, b) => {
+                self.stm(*a);
+                self.stm(*b);
+            }
+            Stm::Label(label) => self.emit(Instr::Label {
+                assem: format!("{}:", label),
+                label,
+            }),
+            Stm::Pos(_) => (),
+            Stm::Jump(target, labels) => match *target {
+                Exp::Name(label) => self.emit(Instr::Oper {
+                    assem: String::from("\tj `j0"),
+                    dst: vec![],
+                    src: vec![],
+                    jump: Some(vec![label]),
+                }),
+                target => {
+                    let target = self.exp(target);
+                    self.emit(Instr::Oper {
+                        assem: String::from("\tjr `s0"),
+                        dst: vec![],
+                        src: vec![target],
+                        jump: Some(labels),
+                    })
+                }
+            },
+            Stm::CJump(op, a, b, t, f) => {
+                let (op, a, b) = match (*a, *b) {
+                    (a @ Exp::Const(_), b) if !matches!(b, Exp::Const(_)) => (op.commute(), b, a),
+                    (a, b) => (op, a, b),
+                };
+                let a = self.exp(a);
+                let (operand, src) = match b {
+                    Exp::Const(0) => ("zero", vec![a]),
+                    b => ("`s1", vec![a, self.exp(b)]),
+                };
+                self.emit(Instr::Oper {
+                    assem: format!("\t{} `s0, {}, `j0", branch(op), operand),
+                    dst: vec![],
+                    src,
+                    jump: Some(vec![t, f]),
+                })
+            }
+            Stm::Move(dst, src) => match (*dst, *src) {
+                (Exp::Temp(t), Exp::Call(f, args)) => {
+                    self.call(*f, args);
+                    self.mov(t, A0)
+                }
+                (Exp::Temp(t), src) => self.exp_into(t, src),
+                (Exp::Mem(addr), src) => {
+                    let (offset, base) = self.address(*addr);
+                    match src {
+                        Exp::Const(0) => {
+                            self.oper(format!("\tsd zero, {}(`s0)", offset), vec![], vec![base])
+                        }
+                        src => {
+                            let src = self.exp(src);
+                            self.oper(
+                                format!("\tsd `s0, {}(`s1)", offset),
+                                vec![],
+                                vec![src, base],
+                            )
+                        }
+                    }
+                }
+                (dst, _) => unreachable!("cannot move into {:?}", dst),
+            },
+            Stm::Exp(exp) => match *exp {
+                Exp::Call(f, args) => self.call(*f, args),
+                exp => {
+                    self.exp(exp);
+                }
+            },
+        }
+    }
+
+    /// Computes `exp` into `dst` with one `li`, `la`, `ld` or operation
+    /// when it can, instead of a move from a fresh temp.
+    fn exp_into(&mut self, dst: Temp, exp: Exp) {
+        match exp {
+            Exp::Temp(src) => self.mov(dst, src),
+            Exp::Const(c) => self.oper(format!("\tli `d0, {}", c), vec![dst], vec![]),
+            Exp::Name(label) => self.oper(format!("\tla `d0, {}", label), vec![dst], vec![]),
+            Exp::Mem(addr) => {
+                let (offset, base) = self.address(*addr);
+                self.oper(format!("\tld `d0, {}(`s0)", offset), vec![dst], vec![base])
+            }
+            Exp::BinOp(op, a, b) => self.binop_into(dst, op, *a, *b),
+            exp => {
+                let src = self.exp(exp);
+                self.mov(dst, src)
+            }
+        }
+    }
+
+    fn exp(&mut self, exp: Exp) -> Temp {
+        match exp {
+            Exp::Temp(t) => t,
+            Exp::Call(f, args) => {
+                self.call(*f, args);
+                let r = Temp::new();
+                self.mov(r, A0);
+                r
+            }
+            Exp::ESeq(..) => unreachable!("ESeq in canonical tree"),
+            exp => {
+                let r = Temp::new();
+                self.exp_into(r, exp);
+                r
+            }
+        }
+    }
+
+    fn binop_into(&mut self, dst: Temp, op: BinOp, a: Exp, b: Exp) {
+        let (name, immediate) = match op {
+            BinOp::Plus => ("add", Some("addi")),
+            BinOp::Minus => ("sub", Some("addi")),
+            BinOp::Mul => ("mul", None),
+            BinOp::Div => ("div", None),
+            BinOp::And => ("and", Some("andi")),
+            BinOp::Or => ("or", Some("ori")),
+            BinOp::Xor => ("xor", Some("xori")),
+            BinOp::LShift => ("sll", Some("slli")),
+            BinOp::RShift => ("srl", Some("srli")),
+            BinOp::ArShift => ("sra", Some("srai")),
+        };
+        let fits = |c: i64| match op {
+            BinOp::Minus => imm12(-c),
+            BinOp::LShift | BinOp::RShift | BinOp::ArShift => (0..64).contains(&c),
+            _ => imm12(c),
+        };
+        match (immediate, b) {
+            (Some(immediate), Exp::Const(c)) if fits(c) => {
+                let c = if op == BinOp::Minus { -c } else { c };
+                let a = self.exp(a);
+                self.oper(
+                    format!("\t{} `d0, `s0, {}", immediate, c),
+                    vec![dst],
+                    vec![a],
+                )
+            }
+            (_, b) => {
+                let (a, b) = (self.exp(a), self.exp(b));
+                self.oper(format!("\t{} `d0, `s0, `s1", name), vec![dst], vec![a, b])
+            }
+        }
+    }
+
+    /// Passes the first eight arguments in registers and the rest in an
+    /// outgoing area at the stack pointer, kept 16-byte aligned.
+    fn call(&mut self, f: Exp, args: Vec<Exp>) {
+        let args: Vec<Temp> = args.into_iter().map(|arg| self.exp(arg)).collect();
+        let (in_regs, on_stack) = args.split_at(args.len().min(ARG_REGISTERS.len()));
+        let bytes = on_stack.len().div_ceil(2) * 16;
+        if !on_stack.is_empty() {
+            self.oper(format!("\taddi sp, sp, -{}", bytes), vec![], vec![]);
+        }
+        for (index, arg) in on_stack.iter().enumerate() {
+            self.oper(format!("\tsd `s0, {}(sp)", 8 * index), vec![], vec![*arg]);
+        }
+        for (register, arg) in ARG_REGISTERS.iter().zip(in_regs) {
+            self.mov(*register, *arg);
+        }
+        let mut src = ARG_REGISTERS[..in_regs.len()].to_vec();
+        let assem = match f {
+            Exp::Name(label) => format!("\tcall {}", label),
+            f => {
+                src.insert(0, self.exp(f));
+                String::from("\tjalr `s0")
+            }
+        };
+        let dst = CALLER_SAVES.iter().chain([&RA]).copied().collect();
+        self.oper(assem, dst, src);
+        let ret = Label::new();
+        self.emit(Instr::Label {
+            assem: format!("{}:", ret),
+            label: ret,
+        });
+        if !on_stack.is_empty() {
+            self.oper(format!("\taddi sp, sp, {}", bytes), vec![], vec![]);
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use crate::assem::assem::Instr;
+use crate::codegen::codegen::{frame_tables, split_address, string_data, Codegen, Proc};
+use crate::frame::frame::Frame;
+use crate::frame::x86_64::*;
+use crate::gc::gc::FrameMap;
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree::{seq, BinOp, Exp, RelOp, Stm};
+
+const CALLER_SAVES: [Temp; 9] = [RAX, RCX, RDX, RSI, RDI, R8, R9, R10, R11];
+const CALLEE_SAVES: [Temp; 5] = [RBX, R12, R13, R14, R15];
+
+impl Codegen for X86_64Frame {
+    fn registers() -> Vec<Temp> {
+        CALLER_SAVES.iter().chain(&CALLEE_SAVES).copied().collect()
+    }
+
+    fn caller_saves() -> Vec<Temp> {
+        CALLER_SAVES.to_vec()
+    }
+
+    fn callee_saves() -> Vec<Temp> {
+        CALLEE_SAVES.to_vec()
+    }
+
+    fn register_name(register: Temp) -> String {
+        match REGISTER_NAMES.get(register.index() as usize) {
+            Some(name) => format!("%{}", name),
+            None => format!("%{}", register),
+        }
+    }
+
+    fn codegen(&self, stm: Stm) -> Vec<Instr> {
+        let mut munch = Munch { instrs: Vec::new() };
+        munch.stm(stm);
+        munch.instrs
+    }
+
+    fn load(dst: Temp, offset: i64) -> Instr {
+        Instr::oper(format!("\tmovq {}(%rbp), `d0", offset), vec![dst], vec![])
+    }
+
+    fn store(src: Temp, offset: i64) -> Instr {
+        Instr::oper(format!("\tmovq `s0, {}(%rbp)", offset), vec![], vec![src])
+    }
+
+    fn proc_entry_exit1(&self, body: Stm) -> Stm {
+        let saved: Vec<(Temp, Temp)> = CALLEE_SAVES
+            .iter()
+            .map(|register| (Temp::new(), *register))
+            .collect();
+        let mut stms: Vec<Stm> = saved
+            .iter()
+            .map(|(temp, register)| move_temp(*temp, Exp::Temp(*register)))
+            .collect();
+        for (index, access) in self.formals().iter().enumerate() {
+            let src = match ARG_REGISTERS.get(index) {
+                Some(register) => Exp::Temp(*register),
+                None => Exp::Mem(Box::new(Exp::BinOp(
+                    BinOp::Plus,
+                    Box::new(Exp::Temp(RBP)),
+                    Box::new(Exp::Const(16 + 8 * (index - ARG_REGISTERS.len()) as i64)),
+                ))),
+            };
+            let dst = Self::exp(*access, Exp::Temp(RBP));
+            stms.push(Stm::Move(Box::new(dst), Box::new(src)));
+        }
+        stms.push(body);
+        stms.extend(
+            saved
+                .iter()
+                .map(|(temp, register)| move_temp(*register, Exp::Temp(*temp))),
+        );
+        seq(stms)
+    }
+
+    fn proc_entry_exit2(&self, mut body: Vec<Instr>) -> Vec<Instr> {
+        body.push(Instr::Oper {
+            assem: String::new(),
+            dst: vec![],
+            src: [RAX, RSP, RBP]
+                .iter()
+                .chain(&CALLEE_SAVES)
+                .copied()
+                .collect(),
+            jump: Some(vec![]),
+        });
+        body
+    }
+
+    fn proc_entry_exit3(&self, body: Vec<Instr>) -> Proc {
+        let size = (self.frame_size() + 15) / 16 * 16;
+        let name = self.name();
+        let mut prolog = format!("\t.globl {0}\n\t.type {0}, @function\n{0}:\n", name);
+        prolog.push_str("\tpushq %rbp\n\tmovq %rsp, %rbp\n");
+        if size > 0 {
+            prolog.push_str(&format!("\tsubq ${}, %rsp\n", size));
+        }
+        Proc {
+            prolog,
+            body,
+            epilog: format!("\tleave\n\tret\n\t.size {0}, .-{0}\n\n", name),
+        }
+    }
+
+    fn string(label: &Label, value: &str) -> String {
+        string_data(label, value)
+    }
+
+    /// The frame maps go in the tables the runtime reads, as
+    /// [`frame_tables`] lays them out.
+    fn program(text: &str, data: &str, maps: &[FrameMap]) -> String {
+        let tables = frame_tables(maps);
+        format!(
+            "\t.text\n{}\t.section .rodata\n{}{}\t.section .note.GNU-stack,\"\",@progbits\n",
+            text, data, tables
+        )
+    }
+}
+
+fn move_temp(dst: Temp, src: Exp) -> Stm {
+    Stm::Move(Box::new(Exp::Temp(dst)), Box::new(src))
+}
+
+/// Whether `value` fits the sign-extended 32-bit immediate of most
+/// instructions.
+fn imm32(value: i64) -> bool {
+    i32::try_from(value).is_ok()
+}
+
+fn jcc(op: RelOp) -> &'static str {
+    match op {
+        RelOp::Eq => "je",
+        RelOp::Ne => "jne",
+        RelOp::Lt => "jl",
+        RelOp::Gt => "jg",
+        RelOp::Le => "jle",
+        RelOp::Ge => "jge",
+        RelOp::Ult => "jb",
+        RelOp::Ule => "jbe",
+        RelOp::Ugt => "ja",
+        RelOp::Uge => "jae",
+    }
+}
+
+/// Maximal munch over canonical trees, in AT&T syntax.
+struct Munch {
+    instrs: Vec<Instr>,
+}
+
+impl Munch {
+    fn emit(&mut self, instr: Instr) {
+        self.instrs.push(instr)
+    }
+
+    fn oper(&mut self, assem: String, dst: Vec<Temp>, src: Vec<Temp>) {
+        self.emit(Instr::oper(assem, dst, src))
+    }
+
+    fn mov(&mut self, dst: Temp, src: Temp) {
+        self.emit(Instr::Move {
+            assem: String::from("\tmovq `s0, `d0"),
+            dst,
+            src,
+        })
+    }
+
+    /// The `offset(base)` memory operand of `addr`, folding a displacement
+    /// that fits 32 bits.
+    fn address(&mut self, addr: Exp) -> (i64, Temp) {
+        let (offset, base) = split_address(addr, imm32);
+        (offset, self.exp(base))
+    }
+
+    fn stm(&mut self, stm: Stm) {
+        match stm {
+            Stm::Seq(a, b) => {
+                self.stm(*a);
+                self.stm(*b);
+            }
+            Stm::Label(label) => self.emit(Instr::Label {
+                assem: format!("{}:", label),
+                label,
+            }),
+            Stm::Pos(_) => (),
+            Stm::Jump(target, labels) => match *target {
+                Exp::Name(label) => self.emit(Instr::Oper {
+                    assem: String::from("\tjmp `j0"),
+                    dst: vec![],
+                    src: vec![],
+                    jump: Some(vec![label]),
+                }),
+                target => {
+                    let target = self.exp(target);
+                    self.emit(Instr::Oper {
+                        assem: String::from("\tjmp *`s0"),
+                        dst: vec![],
+                        src: vec![target],
+                        jump: Some(labels),
+                    })
+                }
+            },
+            Stm::CJump(op, a, b, t, f) => {
+                let (op, a, b) = match (*a, *b) {
+                    (a @ Exp::Const(_), b) if !matches!(b, Exp::Const(_)) => (op.commute(), b, a),
+                    (a, b) => (op, a, b),
+                };
+                let a = self.exp(a);
+                match b {
+                    Exp::Const(c) if imm32(c) => {
+                        self.oper(format!("\tcmpq ${}, `s0", c), vec![], vec![a])
+                    }
+                    b => {
+                        let b = self.exp(b);
+                        self.oper(String::from("\tcmpq `s1, `s0"), vec![], vec![a, b])
+                    }
+                }
+                self.emit(Instr::Oper {
+                    assem: format!("\t{} `j0", jcc(op)),
+                    dst: vec![],
+                    src: vec![],
+                    jump: Some(vec![t, f]),
+                })
+            }
+            Stm::Move(dst, src) => match (*dst, *src) {
+                (Exp::Temp(t), Exp::Call(f, args)) => {
+                    self.call(*f, args);
+                    self.mov(t, RAX)
+                }
+                (Exp::Temp(t), src) => self.exp_into(t, src),
+                (Exp::Mem(addr), src) => {
+                    let (offset, base) = self.address(*addr);
+                    match src {
+                        Exp::Const(c) if imm32(c) => self.oper(
+                            format!("\tmovq ${}, {}(`s0)", c, offset),
+                            vec![],
+                            vec![base],
+                        ),
+                        src => {
+                            let src = self.exp(src);
+                            self.oper(
+                                format!("\tmovq `s0, {}(`s1)", offset),
+                                vec![],
+                                vec![src, base],
+                            )
+                        }
+                    }
+                }
+                (dst, _) => unreachable!("cannot move into {:?}", dst),
+            },
+            Stm::Exp(exp) => match *exp {
+                Exp::Call(f, args) => self.call(*f, args),
+                exp => {
+                    self.exp(exp);
+                }
+            },
+        }
+    }
+
+    /// Computes `exp` into `dst`, moving constants, labels and loads there
+    /// directly instead of through a fresh temp.
+    fn exp_into(&mut self, dst: Temp, exp: Exp) {
+        match exp {
+            Exp::Temp(src) => self.mov(dst, src),
+            Exp::Const(c) if imm32(c) => {
+                self.oper(format!("\tmovq ${}, `d0", c), vec![dst], vec![])
+            }
+            Exp::Const(c) => self.oper(format!("\tmovabsq ${}, `d0", c), vec![dst], vec![]),
+            Exp::Name(label) => {
+                self.oper(format!("\tleaq {}(%rip), `d0", label), vec![dst], vec![])
+            }
+            Exp::Mem(addr) => {
+                let (offset, base) = self.address(*addr);
+                self.oper(
+                    format!("\tmovq {}(`s0), `d0", offset),
+                    vec![dst],
+                    vec![base],
+                )
+            }
+            exp => {
+                let src = self.exp(exp);
+                self.mov(dst, src)
+            }
+        }
+    }
+
+    fn exp(&mut self, exp: Exp) -> Temp {
+        match exp {
+            Exp::Temp(t) => t,
+            Exp::BinOp(op, a, b) => self.binop(op, *a, *b),
+            Exp::Call(f, args) => {
+                self.call(*f, args);
+                let r = Temp::new();
+                self.mov(r, RAX);
+                r
+            }
+            Exp::ESeq(..) => unreachable!("ESeq in canonical tree"),
+            exp => {
+                let r = Temp::new();
+                self.exp_into(r, exp);
+                r
+            }
+        }
+    }
+
+    fn binop(&mut self, op: BinOp, a: Exp, b: Exp) -> Temp {
+        let r = Temp::new();
+        let name = match op {
+            BinOp::Plus => "addq",
+            BinOp::Minus => "subq",
+            BinOp::Mul => "imulq",
+            BinOp::And => "andq",
+            BinOp::Or => "orq",
+            BinOp::Xor => "xorq",
+            BinOp::LShift => "salq",
+            BinOp::RShift => "shrq",
+            BinOp::ArShift => "sarq",
+            BinOp::Div => {
+                self.exp_into(RAX, a);
+                let b = self.exp(b);
+                self.oper(String::from("\tcqto"), vec![RDX], vec![RAX]);
+                self.oper(
+                    String::from("\tidivq `s0"),
+                    vec![RAX, RDX],
+                    vec![b, RAX, RDX],
+                );
+                self.mov(r, RAX);
+                return r;
+            }
+        };
+        self.exp_into(r, a);
+        match b {
+            Exp::Const(c) if imm32(c) => {
+                self.oper(format!("\t{} ${}, `d0", name, c), vec![r], vec![r])
+            }
+            b if matches!(op, BinOp::LShift | BinOp::RShift | BinOp::ArShift) => {
+                self.exp_into(RCX, b);
+                self.oper(format!("\t{} %cl, `d0", name), vec![r], vec![r, RCX])
+            }
+            b => {
+                let b = self.exp(b);
+                self.oper(format!("\t{} `s0, `d0", name), vec![r], vec![b, r])
+            }
+        }
+        r
+    }
+
+    /// Passes the first six arguments in registers and the rest on the
+    /// stack, keeping `%rsp` 16-byte aligned at the call.
+    fn call(&mut self, f: Exp, args: Vec<Exp>) {
+        let args: Vec<Temp> = args.into_iter().map(|arg| self.exp(arg)).collect();
+        let (in_regs, on_stack) = args.split_at(args.len().min(ARG_REGISTERS.len()));
+        let padding = on_stack.len() % 2;
+        if padding == 1 {
+            self.oper(String::from("\tsubq $8, %rsp"), vec![], vec![]);
+        }
+        for arg in on_stack.iter().rev() {
+            self.oper(String::from("\tpushq `s0"), vec![], vec![*arg]);
+        }
+        for (register, arg) in ARG_REGISTERS.iter().zip(in_regs) {
+            self.mov(*register, *arg);
+        }
+        let mut src = ARG_REGISTERS[..in_regs.len()].to_vec();
+        let assem = match f {
+            Exp::Name(label) => format!("\tcall {}", label),
+            f => {
+                src.insert(0, self.exp(f));
+                String::from("\tcall *`s0")
+            }
+        };
+        self.oper(assem, CALLER_SAVES.to_vec(), src);
+        let ret = Label::new();
+        self.emit(Instr::Label {
+            assem: format!("{}:", ret),
+            label: ret,
+        });
+        if !on_stack.is_empty() {
+            let bytes = (on_stack.len() + padding) * 8;
+            self.oper(format!("\taddq ${}, %rsp", bytes), vec![], vec![]);
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use std::sync::Mutex;
+use std::cell::RefCell;
 
 use serde::{Deserialize, Serialize};
 
-lazy_static! {
-    static ref LINE_POS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+thread_local! {
+    static LINE_POS: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -13,7 +13,10 @@ pub struct PosInfo {
 }
 
 pub fn parse_line_pos(s: &str) {
-    let mut line_pos = LINE_POS.lock().unwrap();
+    LINE_POS.with(|line_pos| push_line_starts(&mut line_pos.borrow_mut(), s))
+}
+
+fn push_line_starts(line_pos: &mut Vec<usize>, s: &str) {
     line_pos.push(0);
     let byte = s.as_bytes();
     let len = byte.len();
@@ -36,27 +39,35 @@ pub fn parse_line_pos(s: &str) {
 }
 
 pub fn get_position_info(pos: usize) -> PosInfo {
-    let line_pos = LINE_POS.lock().unwrap();
-    let pos_index = line_pos.partition_point(|x| *x < pos);
-    let len_start_pos = match line_pos.get(pos_index) {
-        Some(pos) => pos.clone(),
-        None => 0 as usize,
-    };
+    let (line, line_start) = LINE_POS.with(|line_pos| {
+        let line_pos = line_pos.borrow();
+        let line = line_pos.partition_point(|x| *x <= pos);
+        let line_start = match line.checked_sub(1).and_then(|i| line_pos.get(i)) {
+            Some(start) => start.clone(),
+            None => 0 as usize,
+        };
+        (line, line_start)
+    });
     PosInfo {
-        line: pos_index,
-        col: if len_start_pos == 0 {
-            pos
-        } else {
-            len_start_pos - pos
-        },
+        line: line.max(1),
+        col: pos - line_start + 1,
     }
 }
 
+/// The widest column a packed position holds; later ones report as it.
+pub const MAX_COL: usize = 0xffff;
+
+/// `pos` packed into one word for the runtimes' error reports: the line
+/// above the low 16 bits, and the column, clamped to [`MAX_COL`], in them.
+pub fn pack_pos(pos: &PosInfo) -> i64 {
+    ((pos.line as i64) << 16) | pos.col.min(MAX_COL) as i64
+}
+
 pub fn emit_error(pos: usize, msg: &str) {
     let info = get_position_info(pos);
     println!("{}.{}:{}", info.line, info.col, msg)
 }
 
 pub fn reset_line_pos() {
-    LINE_POS.lock().unwrap().clear();
+    LINE_POS.with(|line_pos| line_pos.borrow_mut().clear());
 }
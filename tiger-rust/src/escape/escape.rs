@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use crate::parser::ast::*;
+use crate::parser::visitor::{self, Visitor};
+
+/// Appel's `FindEscape`: marks every variable, parameter and `for` counter
+/// that is used from a function nested deeper than the one declaring it.
+///
+/// Each binding is first reset to non-escaping, so the pass can be rerun on
+/// the same tree. Record type fields are not bindings and are left alone.
+pub fn find_escape(exp: &Exp) {
+    let mut finder = FindEscape {
+        depth: 0,
+        env: HashMap::new(),
+        scopes: Vec::new(),
+    };
+    finder.visit_exp(exp);
+}
+
+struct FindEscape<'ast> {
+    depth: usize,
+    env: HashMap<&'ast str, Vec<(usize, &'ast Escape)>>,
+    scopes: Vec<Vec<&'ast str>>,
+}
+
+impl<'ast> FindEscape<'ast> {
+    fn begin_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn end_scope(&mut self) {
+        for id in self.scopes.pop().unwrap() {
+            self.env.get_mut(id).unwrap().pop();
+        }
+    }
+
+    fn bind(&mut self, id: &'ast str, escape: &'ast Escape) {
+        escape.set(false);
+        self.env.entry(id).or_default().push((self.depth, escape));
+        self.scopes.last_mut().unwrap().push(id);
+    }
+
+    fn use_var(&mut self, id: &str) {
+        if let Some(&(depth, escape)) = self.env.get(id).and_then(|bindings| bindings.last()) {
+            if depth < self.depth {
+                escape.set(true);
+            }
+        }
+    }
+
+    fn for_loop(&mut self, id: &'ast str, escape: &'ast Escape, start: &'ast Exp, end: &'ast Exp) {
+        self.visit_exp(start);
+        self.visit_exp(end);
+        self.begin_scope();
+        self.bind(id, escape);
+    }
+}
+
+impl<'ast> Visitor<'ast> for FindEscape<'ast> {
+    fn visit_type_dec(&mut self, _: &'ast TypeDec) {}
+
+    fn visit_some_ty_fields(&mut self, node: &'ast SomeTyFields) {
+        match node {
+            SomeTyFields::Some { id, escape, .. } | SomeTyFields::List { id, escape, .. } => {
+                self.bind(&id.v, escape)
+            }
+        }
+        visitor::walk_some_ty_fields(self, node)
+    }
+
+    // Long sequences are walked in a loop rather than one call per `;`.
+    fn visit_some_exp_seq(&mut self, node: &'ast SomeExpSeq) {
+        let mut seq = node;
+        loop {
+            match seq {
+                SomeExpSeq::Some(exp) => break self.visit_exp(exp),
+                SomeExpSeq::List(exp, tail) => {
+                    self.visit_exp(exp);
+                    seq = tail;
+                }
+            }
+        }
+    }
+
+    // So are operator chains, which nest down their left operands; the
+    // operands are collected first to keep the left-to-right order.
+    fn visit_sheet(&mut self, mut node: &'ast Sheet) {
+        let mut rights = Vec::new();
+        while let Sheet::Or(sheet, piece) = node {
+            rights.push(piece);
+            node = sheet;
+        }
+        visitor::walk_sheet(self, node);
+        for piece in rights.into_iter().rev() {
+            self.visit_piece(piece);
+        }
+    }
+
+    fn visit_piece(&mut self, mut node: &'ast Piece) {
+        let mut rights = Vec::new();
+        while let Piece::And(piece, bit) = node {
+            rights.push(bit);
+            node = piece;
+        }
+        visitor::walk_piece(self, node);
+        for bit in rights.into_iter().rev() {
+            self.visit_bit(bit);
+        }
+    }
+
+    fn visit_item(&mut self, mut node: &'ast Item) {
+        let mut rights = Vec::new();
+        while let Item::Plus(item, term) | Item::Minus(item, term) = node {
+            rights.push(term);
+            node = item;
+        }
+        visitor::walk_item(self, node);
+        for term in rights.into_iter().rev() {
+            self.visit_term(term);
+        }
+    }
+
+    fn visit_term(&mut self, mut node: &'ast Term) {
+        let mut rights = Vec::new();
+        while let Term::Times(term, factor) | Term::Divide(term, factor) = node {
+            rights.push(factor);
+            node = term;
+        }
+        visitor::walk_term(self, node);
+        for factor in rights.into_iter().rev() {
+            self.visit_factor(factor);
+        }
+    }
+
+    fn visit_var_dec(&mut self, node: &'ast VarDec) {
+        visitor::walk_var_dec(self, node);
+        match node {
+            VarDec::DefaultInit(id, _, escape) => self.bind(&id.v, escape),
+            VarDec::TypeInit { var_id, escape, .. } => self.bind(&var_id.v, escape),
+        }
+    }
+
+    fn visit_fun_dec(&mut self, node: &'ast FunDec) {
+        self.depth += 1;
+        self.begin_scope();
+        visitor::walk_fun_dec(self, node);
+        self.end_scope();
+        self.depth -= 1;
+    }
+
+    fn visit_match(&mut self, node: &'ast Match) {
+        match node {
+            Match::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            } => {
+                self.for_loop(&id.v, escape, start, end);
+                self.visit_match(body);
+                self.end_scope();
+            }
+            _ => visitor::walk_match(self, node),
+        }
+    }
+
+    fn visit_unmatch(&mut self, node: &'ast Unmatch) {
+        match node {
+            Unmatch::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            } => {
+                self.for_loop(&id.v, escape, start, end);
+                self.visit_unmatch(body);
+                self.end_scope();
+            }
+            _ => visitor::walk_unmatch(self, node),
+        }
+    }
+
+    fn visit_lvalue(&mut self, node: &'ast LValue) {
+        if let LValue::Id(id) = node {
+            self.use_var(&id.v);
+        }
+        visitor::walk_lvalue(self, node)
+    }
+
+    fn visit_refer(&mut self, node: &'ast Refer) {
+        if let Refer::Field(id, _) | Refer::Array(id, _) = node {
+            self.use_var(&id.v);
+        }
+        visitor::walk_refer(self, node)
+    }
+
+    fn visit_meta(&mut self, node: &'ast Meta) {
+        match node {
+            Meta::Id(id) => self.use_var(&id.v),
+            Meta::Let(_, _) => {
+                self.begin_scope();
+                visitor::walk_meta(self, node);
+                self.end_scope();
+                return;
+            }
+            _ => (),
+        }
+        visitor::walk_meta(self, node)
+    }
+}
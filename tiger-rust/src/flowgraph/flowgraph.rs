@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::assem::assem::Instr;
+use crate::temp::temp::Temp;
+
+/// One instruction seen as a node of the control-flow graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowNode {
+    pub def: Vec<Temp>,
+    pub uses: Vec<Temp>,
+    /// Whether the instruction only copies its use into its def, so the two
+    /// may share a register.
+    pub is_move: bool,
+    pub succ: Vec<usize>,
+    pub pred: Vec<usize>,
+}
+
+/// Control-flow graph with one node per instruction, numbered as in the
+/// instruction list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowGraph {
+    pub nodes: Vec<FlowNode>,
+}
+
+impl FlowGraph {
+    pub fn new(instrs: &[Instr]) -> FlowGraph {
+        let labels: HashMap<_, usize> = instrs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, instr)| match instr {
+                Instr::Label { label, .. } => Some((label, index)),
+                _ => None,
+            })
+            .collect();
+        let mut nodes: Vec<FlowNode> = instrs
+            .iter()
+            .map(|instr| FlowNode {
+                def: dedup(instr.defs()),
+                uses: dedup(instr.uses()),
+                is_move: matches!(instr, Instr::Move { .. }),
+                succ: Vec::new(),
+                pred: Vec::new(),
+            })
+            .collect();
+        for (index, instr) in instrs.iter().enumerate() {
+            let succ: Vec<usize> = match instr {
+                // Targets outside the function, such as the epilogue,
+                // have no node.
+                Instr::Oper {
+                    jump: Some(targets),
+                    ..
+                } => targets
+                    .iter()
+                    .filter_map(|label| labels.get(label).copied())
+                    .collect(),
+                _ if index + 1 < instrs.len() => vec![index + 1],
+                _ => Vec::new(),
+            };
+            for next in &succ {
+                if !nodes[*next].pred.contains(&index) {
+                    nodes[*next].pred.push(index);
+                }
+            }
+            nodes[index].succ = dedup(&succ);
+        }
+        FlowGraph { nodes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+fn dedup<T: Copy + PartialEq>(items: &[T]) -> Vec<T> {
+    let mut out: Vec<T> = Vec::with_capacity(items.len());
+    for item in items {
+        if !out.contains(item) {
+            out.push(*item);
+        }
+    }
+    out
+}
@@ -0,0 +1,50 @@
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree::{self, BinOp};
+
+/// Where a formal parameter or local variable lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// At this byte offset from the frame pointer.
+    InFrame(i64),
+    InReg(Temp),
+}
+
+/// Machine-specific layout of one function activation.
+///
+/// A frame knows where each formal is seen from inside the callee and hands
+/// out space for locals; escaping values always go in memory, the rest in
+/// fresh temps. It also notes which of them hold heap pointers.
+pub trait Frame: Clone {
+    const WORD_SIZE: i64;
+
+    /// `formals` holds one escape flag per parameter, in order.
+    fn new(name: Label, formals: &[bool]) -> Self;
+    fn name(&self) -> &Label;
+    fn formals(&self) -> &[Access];
+    fn alloc_local(&mut self, escape: bool) -> Access;
+    /// Bytes taken by the locals and spills allocated so far.
+    fn frame_size(&self) -> i64;
+    /// Records that `access` holds a heap pointer the collector must trace.
+    fn add_pointer(&mut self, access: Access);
+    fn pointers(&self) -> &[Access];
+
+    fn fp() -> Temp;
+    fn rv() -> Temp;
+
+    /// The location of `access`, given the address of the frame holding it.
+    fn exp(access: Access, fp: tree::Exp) -> tree::Exp {
+        match access {
+            Access::InFrame(offset) => tree::Exp::Mem(Box::new(tree::Exp::BinOp(
+                BinOp::Plus,
+                Box::new(fp),
+                Box::new(tree::Exp::Const(offset)),
+            ))),
+            Access::InReg(temp) => tree::Exp::Temp(temp),
+        }
+    }
+
+    /// A call to a runtime function, which takes no static link.
+    fn external_call(name: &str, args: Vec<tree::Exp>) -> tree::Exp {
+        tree::Exp::Call(Box::new(tree::Exp::Name(Label::named(name))), args)
+    }
+}
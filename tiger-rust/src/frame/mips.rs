@@ -0,0 +1,121 @@
+use crate::frame::frame::{Access, Frame};
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree;
+
+pub const ZERO: Temp = Temp::precolored(0);
+pub const AT: Temp = Temp::precolored(1);
+pub const V0: Temp = Temp::precolored(2);
+pub const V1: Temp = Temp::precolored(3);
+pub const A0: Temp = Temp::precolored(4);
+pub const A1: Temp = Temp::precolored(5);
+pub const A2: Temp = Temp::precolored(6);
+pub const A3: Temp = Temp::precolored(7);
+pub const T0: Temp = Temp::precolored(8);
+pub const T1: Temp = Temp::precolored(9);
+pub const T2: Temp = Temp::precolored(10);
+pub const T3: Temp = Temp::precolored(11);
+pub const T4: Temp = Temp::precolored(12);
+pub const T5: Temp = Temp::precolored(13);
+pub const T6: Temp = Temp::precolored(14);
+pub const T7: Temp = Temp::precolored(15);
+pub const S0: Temp = Temp::precolored(16);
+pub const S1: Temp = Temp::precolored(17);
+pub const S2: Temp = Temp::precolored(18);
+pub const S3: Temp = Temp::precolored(19);
+pub const S4: Temp = Temp::precolored(20);
+pub const S5: Temp = Temp::precolored(21);
+pub const S6: Temp = Temp::precolored(22);
+pub const S7: Temp = Temp::precolored(23);
+pub const T8: Temp = Temp::precolored(24);
+pub const T9: Temp = Temp::precolored(25);
+pub const SP: Temp = Temp::precolored(29);
+pub const FP: Temp = Temp::precolored(30);
+pub const RA: Temp = Temp::precolored(31);
+
+/// Register names by number, as SPIM spells them after the `$`.
+pub const REGISTER_NAMES: [&str; 32] = [
+    "zero", "at", "v0", "v1", "a0", "a1", "a2", "a3", "t0", "t1", "t2", "t3", "t4", "t5", "t6",
+    "t7", "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "t8", "t9", "k0", "k1", "gp", "sp", "fp",
+    "ra",
+];
+
+pub const ARG_REGISTERS: [Temp; 4] = [A0, A1, A2, A3];
+
+/// 32-bit MIPS frame, as SPIM runs it. The frame pointer holds the stack
+/// pointer from entry; the return address and the caller's frame pointer
+/// are saved below it at `-4` and `-8`, and locals and escaping formals
+/// below those at `-12`, `-16`, ... Each formal is copied on entry into the
+/// access chosen here, from `$a0`-`$a3` or, past the fourth, from the
+/// caller's outgoing arguments at `0`, `4`, ... above the frame pointer.
+#[derive(Debug, Clone)]
+pub struct MipsFrame {
+    name: Label,
+    formals: Vec<Access>,
+    locals: i64,
+    pointers: Vec<Access>,
+}
+
+/// Bytes of the saved return address and frame pointer.
+pub const SAVED: i64 = 8;
+
+impl Frame for MipsFrame {
+    const WORD_SIZE: i64 = 4;
+
+    fn new(name: Label, formals: &[bool]) -> Self {
+        let mut frame = MipsFrame {
+            name,
+            formals: Vec::new(),
+            locals: 0,
+            pointers: Vec::new(),
+        };
+        frame.formals = formals
+            .iter()
+            .map(|escape| frame.alloc_local(*escape))
+            .collect();
+        frame
+    }
+
+    fn name(&self) -> &Label {
+        &self.name
+    }
+
+    fn formals(&self) -> &[Access] {
+        &self.formals
+    }
+
+    fn alloc_local(&mut self, escape: bool) -> Access {
+        if escape {
+            self.locals += 1;
+            Access::InFrame(-SAVED - self.locals * Self::WORD_SIZE)
+        } else {
+            Access::InReg(Temp::new())
+        }
+    }
+
+    /// Bytes below the saved registers.
+    fn frame_size(&self) -> i64 {
+        self.locals * Self::WORD_SIZE
+    }
+
+    fn add_pointer(&mut self, access: Access) {
+        self.pointers.push(access);
+    }
+
+    fn pointers(&self) -> &[Access] {
+        &self.pointers
+    }
+
+    fn fp() -> Temp {
+        FP
+    }
+
+    fn rv() -> Temp {
+        V0
+    }
+
+    /// Runtime functions carry the same `tig_` prefix as on x86-64.
+    fn external_call(name: &str, args: Vec<tree::Exp>) -> tree::Exp {
+        let name = Label::named(&format!("tig_{}", name));
+        tree::Exp::Call(Box::new(tree::Exp::Name(name)), args)
+    }
+}
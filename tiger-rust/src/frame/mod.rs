@@ -0,0 +1,4 @@
+pub mod frame;
+pub mod mips;
+pub mod riscv64;
+pub mod x86_64;
@@ -0,0 +1,122 @@
+use crate::frame::frame::{Access, Frame};
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree;
+
+pub const ZERO: Temp = Temp::precolored(0);
+pub const RA: Temp = Temp::precolored(1);
+pub const SP: Temp = Temp::precolored(2);
+pub const GP: Temp = Temp::precolored(3);
+pub const TP: Temp = Temp::precolored(4);
+pub const T0: Temp = Temp::precolored(5);
+pub const T1: Temp = Temp::precolored(6);
+pub const T2: Temp = Temp::precolored(7);
+pub const S0: Temp = Temp::precolored(8);
+pub const S1: Temp = Temp::precolored(9);
+pub const A0: Temp = Temp::precolored(10);
+pub const A1: Temp = Temp::precolored(11);
+pub const A2: Temp = Temp::precolored(12);
+pub const A3: Temp = Temp::precolored(13);
+pub const A4: Temp = Temp::precolored(14);
+pub const A5: Temp = Temp::precolored(15);
+pub const A6: Temp = Temp::precolored(16);
+pub const A7: Temp = Temp::precolored(17);
+pub const S2: Temp = Temp::precolored(18);
+pub const S3: Temp = Temp::precolored(19);
+pub const S4: Temp = Temp::precolored(20);
+pub const S5: Temp = Temp::precolored(21);
+pub const S6: Temp = Temp::precolored(22);
+pub const S7: Temp = Temp::precolored(23);
+pub const S8: Temp = Temp::precolored(24);
+pub const S9: Temp = Temp::precolored(25);
+pub const S10: Temp = Temp::precolored(26);
+pub const S11: Temp = Temp::precolored(27);
+pub const T3: Temp = Temp::precolored(28);
+pub const T4: Temp = Temp::precolored(29);
+pub const T5: Temp = Temp::precolored(30);
+pub const T6: Temp = Temp::precolored(31);
+
+/// ABI names of `x0`-`x31`.
+pub const REGISTER_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+/// Integer argument registers of the standard calling convention.
+pub const ARG_REGISTERS: [Temp; 8] = [A0, A1, A2, A3, A4, A5, A6, A7];
+
+/// RV64 frame under the standard calling convention, with `s0` as the frame
+/// pointer. It is laid out as on x86-64 so the runtime walks both alike:
+/// the frame pointer holds the saved caller's `s0`, with the return address
+/// above it at `8`. Locals and escaping formals sit below it at `-8`, `-16`,
+/// ... Each formal is copied on entry into the access chosen here, from its
+/// argument register or, past the eighth, from the caller's outgoing
+/// arguments at `16`, `24`, ...
+#[derive(Debug, Clone)]
+pub struct Riscv64Frame {
+    name: Label,
+    formals: Vec<Access>,
+    locals: i64,
+    pointers: Vec<Access>,
+}
+
+impl Frame for Riscv64Frame {
+    const WORD_SIZE: i64 = 8;
+
+    fn new(name: Label, formals: &[bool]) -> Self {
+        let mut frame = Riscv64Frame {
+            name,
+            formals: Vec::new(),
+            locals: 0,
+            pointers: Vec::new(),
+        };
+        frame.formals = formals
+            .iter()
+            .map(|escape| frame.alloc_local(*escape))
+            .collect();
+        frame
+    }
+
+    fn name(&self) -> &Label {
+        &self.name
+    }
+
+    fn formals(&self) -> &[Access] {
+        &self.formals
+    }
+
+    fn alloc_local(&mut self, escape: bool) -> Access {
+        if escape {
+            self.locals += 1;
+            Access::InFrame(-self.locals * Self::WORD_SIZE)
+        } else {
+            Access::InReg(Temp::new())
+        }
+    }
+
+    /// Bytes below the saved `s0`.
+    fn frame_size(&self) -> i64 {
+        self.locals * Self::WORD_SIZE
+    }
+
+    fn add_pointer(&mut self, access: Access) {
+        self.pointers.push(access);
+    }
+
+    fn pointers(&self) -> &[Access] {
+        &self.pointers
+    }
+
+    fn fp() -> Temp {
+        S0
+    }
+
+    fn rv() -> Temp {
+        A0
+    }
+
+    fn external_call(name: &str, args: Vec<tree::Exp>) -> tree::Exp {
+        let name = Label::named(&format!("tig_{}", name));
+        tree::Exp::Call(Box::new(tree::Exp::Name(name)), args)
+    }
+}
@@ -0,0 +1,103 @@
+use crate::frame::frame::{Access, Frame};
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree;
+
+pub const RAX: Temp = Temp::precolored(0);
+pub const RBX: Temp = Temp::precolored(1);
+pub const RCX: Temp = Temp::precolored(2);
+pub const RDX: Temp = Temp::precolored(3);
+pub const RSI: Temp = Temp::precolored(4);
+pub const RDI: Temp = Temp::precolored(5);
+pub const RBP: Temp = Temp::precolored(6);
+pub const RSP: Temp = Temp::precolored(7);
+pub const R8: Temp = Temp::precolored(8);
+pub const R9: Temp = Temp::precolored(9);
+pub const R10: Temp = Temp::precolored(10);
+pub const R11: Temp = Temp::precolored(11);
+pub const R12: Temp = Temp::precolored(12);
+pub const R13: Temp = Temp::precolored(13);
+pub const R14: Temp = Temp::precolored(14);
+pub const R15: Temp = Temp::precolored(15);
+
+pub const REGISTER_NAMES: [&str; 16] = [
+    "rax", "rbx", "rcx", "rdx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12", "r13",
+    "r14", "r15",
+];
+
+/// Integer argument registers of the System V AMD64 calling convention.
+pub const ARG_REGISTERS: [Temp; 6] = [RDI, RSI, RDX, RCX, R8, R9];
+
+/// System V AMD64 frame. Locals and escaping formals sit below the saved
+/// `rbp`, at `-8`, `-16`, ... Each formal is copied on entry into the access
+/// chosen here, from its argument register or, past the sixth, from above the
+/// return address at `16`, `24`, ...
+#[derive(Debug, Clone)]
+pub struct X86_64Frame {
+    name: Label,
+    formals: Vec<Access>,
+    locals: i64,
+    pointers: Vec<Access>,
+}
+
+impl Frame for X86_64Frame {
+    const WORD_SIZE: i64 = 8;
+
+    fn new(name: Label, formals: &[bool]) -> Self {
+        let mut frame = X86_64Frame {
+            name,
+            formals: Vec::new(),
+            locals: 0,
+            pointers: Vec::new(),
+        };
+        frame.formals = formals
+            .iter()
+            .map(|escape| frame.alloc_local(*escape))
+            .collect();
+        frame
+    }
+
+    fn name(&self) -> &Label {
+        &self.name
+    }
+
+    fn formals(&self) -> &[Access] {
+        &self.formals
+    }
+
+    fn alloc_local(&mut self, escape: bool) -> Access {
+        if escape {
+            self.locals += 1;
+            Access::InFrame(-self.locals * Self::WORD_SIZE)
+        } else {
+            Access::InReg(Temp::new())
+        }
+    }
+
+    /// Bytes below the saved `rbp`.
+    fn frame_size(&self) -> i64 {
+        self.locals * Self::WORD_SIZE
+    }
+
+    fn add_pointer(&mut self, access: Access) {
+        self.pointers.push(access);
+    }
+
+    fn pointers(&self) -> &[Access] {
+        &self.pointers
+    }
+
+    fn fp() -> Temp {
+        RBP
+    }
+
+    fn rv() -> Temp {
+        RAX
+    }
+
+    /// Runtime functions carry a `tig_` prefix, since libc already owns
+    /// names such as `exit` and `getchar`.
+    fn external_call(name: &str, args: Vec<tree::Exp>) -> tree::Exp {
+        let name = Label::named(&format!("tig_{}", name));
+        tree::Exp::Call(Box::new(tree::Exp::Name(name)), args)
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::codegen::codegen::alloc_slot;
+use crate::frame::frame::{Access, Frame};
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree::{BinOp, Exp, Stm};
+
+/// Where the collector finds the heap pointers of one function's frames.
+pub struct FrameMap {
+    /// Labels the map in the data section.
+    pub label: Label,
+    /// The source name of the function, for backtraces.
+    pub name: String,
+    /// Frame-pointer offsets of the slots that may hold heap pointers.
+    pub slots: Vec<i64>,
+    pub sites: Vec<CallSite>,
+}
+
+/// The pointer slots live while a call is in progress.
+pub struct CallSite {
+    /// The return address of the call.
+    pub ret: Label,
+    /// Indices into [`FrameMap::slots`].
+    pub live: Vec<usize>,
+}
+
+/// Whether a canonical statement makes a call.
+pub fn is_call(stm: &Stm) -> bool {
+    match stm {
+        Stm::Exp(exp) | Stm::Move(_, exp) => matches!(**exp, Exp::Call(..)),
+        _ => false,
+    }
+}
+
+/// Moves each pointer temp that is live across a call of the canonical
+/// `stms` into a slot of `frame`, where the collector can find and update
+/// it, and zeroes every pointer slot on entry. Returns the new statements,
+/// the pointer slots, and for each call in order the slots live across it.
+pub fn spill_pointers<F: Frame>(
+    frame: &mut F,
+    stms: Vec<Stm>,
+) -> (Vec<Stm>, Vec<i64>, Vec<Vec<usize>>) {
+    let mut slots = Vec::new();
+    let mut pointers = BTreeSet::new();
+    for access in frame.pointers() {
+        match access {
+            Access::InFrame(offset) => slots.push(*offset),
+            Access::InReg(temp) => {
+                pointers.insert(*temp);
+            }
+        }
+    }
+    let escaping = slots.len();
+    // Canon copies a value into a fresh temp when it has to evaluate it
+    // early, so a copy of a pointer is a pointer.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for stm in &stms {
+            if let Stm::Move(dst, src) = stm {
+                if let (Exp::Temp(dst), Exp::Temp(src)) = (&**dst, &**src) {
+                    changed |= pointers.contains(src) && pointers.insert(*dst);
+                }
+            }
+        }
+    }
+    let live_out = live_out(&stms, &pointers);
+    let mut across = Vec::new();
+    for (stm, live) in stms.iter().zip(live_out) {
+        if is_call(stm) {
+            let mut live = live;
+            if let Stm::Move(dst, _) = stm {
+                if let Exp::Temp(temp) = **dst {
+                    live.remove(&temp);
+                }
+            }
+            across.push(live);
+        }
+    }
+    let mut spilled = HashMap::new();
+    for temp in across.iter().flatten() {
+        if !spilled.contains_key(temp) {
+            spilled.insert(*temp, (slots.len(), alloc_slot(frame)));
+            slots.push(spilled[temp].1);
+        }
+    }
+    let fp = F::fp();
+    let mut out: Vec<Stm> = slots
+        .iter()
+        .map(|offset| Stm::Move(Box::new(slot(fp, *offset)), Box::new(Exp::Const(0))))
+        .collect();
+    out.extend(stms.into_iter().map(|stm| {
+        rewrite_stm(stm, &|temp| {
+            spilled.get(&temp).map(|(_, offset)| slot(fp, *offset))
+        })
+    }));
+    let live = across
+        .into_iter()
+        .map(|live| {
+            (0..escaping)
+                .chain(live.iter().map(|temp| spilled[temp].0))
+                .collect()
+        })
+        .collect();
+    (out, slots, live)
+}
+
+fn slot(fp: Temp, offset: i64) -> Exp {
+    Exp::Mem(Box::new(Exp::BinOp(
+        BinOp::Plus,
+        Box::new(Exp::Temp(fp)),
+        Box::new(Exp::Const(offset)),
+    )))
+}
+
+/// The temps of `pointers` live out of each statement of `stms`.
+fn live_out(stms: &[Stm], pointers: &BTreeSet<Temp>) -> Vec<BTreeSet<Temp>> {
+    let mut live_out = vec![BTreeSet::new(); stms.len()];
+    if pointers.is_empty() {
+        return live_out;
+    }
+    let labels: HashMap<&Label, usize> = stms
+        .iter()
+        .enumerate()
+        .filter_map(|(index, stm)| match stm {
+            Stm::Label(label) => Some((label, index)),
+            _ => None,
+        })
+        .collect();
+    let mut succ = Vec::with_capacity(stms.len());
+    let mut defs = Vec::with_capacity(stms.len());
+    let mut uses = Vec::with_capacity(stms.len());
+    for (index, stm) in stms.iter().enumerate() {
+        succ.push(match stm {
+            Stm::Jump(_, targets) => targets.iter().map(|label| labels[label]).collect(),
+            Stm::CJump(_, _, _, t, f) => vec![labels[t], labels[f]],
+            _ if index + 1 < stms.len() => vec![index + 1],
+            _ => vec![],
+        });
+        let mut used = BTreeSet::new();
+        let def = match stm {
+            Stm::Move(dst, src) => {
+                exp_temps(src, &mut used);
+                match &**dst {
+                    Exp::Temp(temp) => Some(*temp),
+                    dst => {
+                        exp_temps(dst, &mut used);
+                        None
+                    }
+                }
+            }
+            Stm::Exp(exp) | Stm::Jump(exp, _) => {
+                exp_temps(exp, &mut used);
+                None
+            }
+            Stm::CJump(_, a, b, _, _) => {
+                exp_temps(a, &mut used);
+                exp_temps(b, &mut used);
+                None
+            }
+            Stm::Label(_) | Stm::Seq(..) | Stm::Pos(_) => None,
+        };
+        used.retain(|temp| pointers.contains(temp));
+        defs.push(def.filter(|temp| pointers.contains(temp)));
+        uses.push(used);
+    }
+    let mut live_in = vec![BTreeSet::new(); stms.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for index in (0..stms.len()).rev() {
+            let out: BTreeSet<Temp> = succ[index]
+                .iter()
+                .flat_map(|succ| live_in[*succ].iter().copied())
+                .collect();
+            let mut live = out.clone();
+            if let Some(def) = defs[index] {
+                live.remove(&def);
+            }
+            live.extend(uses[index].iter().copied());
+            if live != live_in[index] {
+                live_in[index] = live;
+                changed = true;
+            }
+            live_out[index] = out;
+        }
+    }
+    live_out
+}
+
+fn exp_temps(exp: &Exp, temps: &mut BTreeSet<Temp>) {
+    match exp {
+        Exp::Temp(temp) => {
+            temps.insert(*temp);
+        }
+        Exp::BinOp(_, a, b) => {
+            exp_temps(a, temps);
+            exp_temps(b, temps);
+        }
+        Exp::Mem(exp) => exp_temps(exp, temps),
+        Exp::Call(f, args) => {
+            exp_temps(f, temps);
+            args.iter().for_each(|arg| exp_temps(arg, temps));
+        }
+        Exp::ESeq(..) => unreachable!("ESeq in canonical tree"),
+        Exp::Name(_) | Exp::Const(_) => (),
+    }
+}
+
+fn rewrite_stm(stm: Stm, slot: &impl Fn(Temp) -> Option<Exp>) -> Stm {
+    let exp = |exp: Box<Exp>| Box::new(rewrite_exp(*exp, slot));
+    match stm {
+        Stm::Move(dst, src) => Stm::Move(exp(dst), exp(src)),
+        Stm::Exp(e) => Stm::Exp(exp(e)),
+        Stm::Jump(e, labels) => Stm::Jump(exp(e), labels),
+        Stm::CJump(op, a, b, t, f) => Stm::CJump(op, exp(a), exp(b), t, f),
+        stm => stm,
+    }
+}
+
+fn rewrite_exp(exp: Exp, slot: &impl Fn(Temp) -> Option<Exp>) -> Exp {
+    let sub = |exp: Box<Exp>| Box::new(rewrite_exp(*exp, slot));
+    match exp {
+        Exp::Temp(temp) => slot(temp).unwrap_or(exp),
+        Exp::BinOp(op, a, b) => Exp::BinOp(op, sub(a), sub(b)),
+        Exp::Mem(a) => Exp::Mem(sub(a)),
+        Exp::Call(f, args) => Exp::Call(
+            sub(f),
+            args.into_iter().map(|arg| rewrite_exp(arg, slot)).collect(),
+        ),
+        exp => exp,
+    }
+}
@@ -0,0 +1,480 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+use crate::error::error::{pack_pos, PosInfo};
+use crate::interp::limits::{grow, Limits, Meter};
+use crate::interp::runtime::at;
+use crate::semant::tast::{
+    FunId, FunKind, Oper, Program, TDec, TExp, TExpKind, TVar, TVarKind, VarId,
+};
+
+/// A Tiger value. Records and arrays are shared, and compare by identity.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    String(Rc<[u8]>),
+    Record(Rc<RefCell<Vec<Value>>>),
+    Array(Rc<RefCell<Vec<Value>>>),
+    Nil,
+    Unit,
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Record(a), Value::Record(b)) | (Value::Array(a), Value::Array(b)) => {
+                Rc::ptr_eq(a, b)
+            }
+            (Value::Nil, Value::Nil) | (Value::Unit, Value::Unit) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Why evaluation stopped before reaching the end of an expression.
+enum Stop {
+    Break,
+    Exit(i64),
+    Error(String),
+}
+
+type Eval<T> = Result<T, Stop>;
+
+fn error<T>(pos: &PosInfo, msg: String) -> Eval<T> {
+    Err(Stop::Error(at(pack_pos(pos), &msg)))
+}
+
+/// The variables of one activation of a function.
+struct Frame {
+    fun: FunId,
+    vars: RefCell<HashMap<VarId, Value>>,
+    /// The frame of the lexically enclosing function.
+    link: Option<Rc<Frame>>,
+}
+
+struct Interp<'a> {
+    program: &'a Program,
+    input: &'a mut dyn Read,
+    output: &'a mut dyn Write,
+    frame: Rc<Frame>,
+    meter: Meter,
+}
+
+/// Runs `program`, with `getchar` reading from `input` and `print` writing
+/// to `output`. Returns the status passed to `exit`, or 0.
+pub fn run(
+    program: &Program,
+    input: &mut (dyn Read + Send),
+    output: &mut (dyn Write + Send),
+) -> Result<i64, String> {
+    run_with(program, Limits::default(), input, output)
+}
+
+/// Runs `program` as [`run`] does, failing once it exceeds `limits`.
+pub fn run_with(
+    program: &Program,
+    limits: Limits,
+    input: &mut (dyn Read + Send),
+    output: &mut (dyn Write + Send),
+) -> Result<i64, String> {
+    eval(program, limits, input, output)
+}
+
+fn eval(
+    program: &Program,
+    limits: Limits,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+) -> Result<i64, String> {
+    let body = match &program.func(program.main).kind {
+        FunKind::User { body } => body,
+        FunKind::Builtin => unreachable!("the main program is not a builtin"),
+    };
+    let mut interp = Interp {
+        program,
+        input,
+        output,
+        frame: Rc::new(Frame {
+            fun: program.main,
+            vars: RefCell::new(HashMap::new()),
+            link: None,
+        }),
+        meter: Meter::new(limits),
+    };
+    let status = match interp
+        .meter
+        .enter()
+        .map_err(Stop::Error)
+        .and_then(|()| interp.exp(body))
+    {
+        Ok(_) => Ok(0),
+        Err(Stop::Exit(code)) => Ok(code),
+        Err(Stop::Error(err)) => Err(backtrace(err, &program.func(program.main).name)),
+        Err(Stop::Break) => unreachable!("break outside a loop"),
+    };
+    interp.output.flush().map_err(|err| err.to_string())?;
+    status
+}
+
+impl Interp<'_> {
+    fn exp(&mut self, exp: &TExp) -> Eval<Value> {
+        grow(|| self.eval_exp(exp))
+    }
+
+    fn eval_exp(&mut self, exp: &TExp) -> Eval<Value> {
+        if let Err(err) = self.meter.step() {
+            return error(&exp.pos, err);
+        }
+        match &exp.kind {
+            TExpKind::Int(i) => Ok(Value::Int(*i)),
+            TExpKind::String(value) => Ok(Value::String(value.as_bytes().into())),
+            TExpKind::Nil => Ok(Value::Nil),
+            TExpKind::Var(var) => self.var(var),
+            TExpKind::Call(fun, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.exp(arg))
+                    .collect::<Eval<Vec<_>>>()?;
+                self.call(*fun, args, &exp.pos)
+            }
+            TExpKind::Op(oper, left, right) => {
+                let left = self.exp(left)?;
+                let right = self.exp(right)?;
+                op(*oper, left, right, &exp.pos)
+            }
+            TExpKind::And(left, right) => Ok(Value::Int(
+                (self.int(left)? != 0 && self.int(right)? != 0) as i64,
+            )),
+            TExpKind::Or(left, right) => Ok(Value::Int(
+                (self.int(left)? != 0 || self.int(right)? != 0) as i64,
+            )),
+            TExpKind::Record(_, fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| self.exp(field))
+                    .collect::<Eval<Vec<_>>>()?;
+                self.alloc(8 * fields.len(), &exp.pos)?;
+                Ok(Value::Record(Rc::new(RefCell::new(fields))))
+            }
+            TExpKind::Array(_, size, init) => {
+                let size = self.int(size)?;
+                let init = self.exp(init)?;
+                if size < 0 {
+                    return error(&exp.pos, format!("array size {} is negative", size));
+                }
+                self.alloc((size as usize).saturating_mul(8), &exp.pos)?;
+                Ok(Value::Array(Rc::new(RefCell::new(vec![
+                    init;
+                    size as usize
+                ]))))
+            }
+            TExpKind::Seq(exps) => {
+                let mut value = Value::Unit;
+                for exp in exps {
+                    value = self.exp(exp)?;
+                }
+                Ok(value)
+            }
+            TExpKind::Assign(var, value) => {
+                self.assign(var, value)?;
+                Ok(Value::Unit)
+            }
+            TExpKind::If(test, then, els) => {
+                if self.int(test)? != 0 {
+                    self.exp(then)
+                } else if let Some(els) = els {
+                    self.exp(els)
+                } else {
+                    Ok(Value::Unit)
+                }
+            }
+            TExpKind::While(test, body) => {
+                while self.int(test)? != 0 {
+                    match self.exp(body) {
+                        Err(Stop::Break) => break,
+                        result => result?,
+                    };
+                }
+                Ok(Value::Unit)
+            }
+            TExpKind::For(var, lo, hi, body) => {
+                let lo = self.int(lo)?;
+                let hi = self.int(hi)?;
+                for i in lo..=hi {
+                    self.frame.vars.borrow_mut().insert(*var, Value::Int(i));
+                    match self.exp(body) {
+                        Err(Stop::Break) => break,
+                        result => result?,
+                    };
+                }
+                Ok(Value::Unit)
+            }
+            TExpKind::Break => Err(Stop::Break),
+            TExpKind::Let(decs, body) => {
+                for dec in decs {
+                    if let TDec::Var(var, init) = dec {
+                        let value = self.exp(init)?;
+                        self.frame.vars.borrow_mut().insert(*var, value);
+                    }
+                }
+                self.exp(body)
+            }
+        }
+    }
+
+    /// Counts an allocation of `bytes` for the heap limit.
+    fn alloc(&mut self, bytes: usize, pos: &PosInfo) -> Eval<()> {
+        match self.meter.alloc(bytes) {
+            Ok(()) => Ok(()),
+            Err(err) => error(pos, err),
+        }
+    }
+
+    fn int(&mut self, exp: &TExp) -> Eval<i64> {
+        match self.exp(exp)? {
+            Value::Int(i) => Ok(i),
+            value => unreachable!("{:?} is not an int", value),
+        }
+    }
+
+    /// The frame of the innermost active call of `fun`, reached through the
+    /// static links.
+    fn frame_of(&self, fun: FunId) -> Rc<Frame> {
+        let mut frame = self.frame.clone();
+        while frame.fun != fun {
+            frame = frame.link.clone().expect("no enclosing frame");
+        }
+        frame
+    }
+
+    fn var(&mut self, var: &TVar) -> Eval<Value> {
+        match &var.kind {
+            TVarKind::Simple(id) => {
+                let frame = self.frame_of(self.program.var(*id).fun);
+                let value = frame.vars.borrow()[id].clone();
+                Ok(value)
+            }
+            TVarKind::Field(record, index) => {
+                let fields = fields(self.exp(record)?, &record.pos)?;
+                let value = fields.borrow()[*index].clone();
+                Ok(value)
+            }
+            TVarKind::Subscript(array, index) => {
+                let elems = self.exp(array)?;
+                let (elems, i) = element(elems, self.int(index)?, &index.pos)?;
+                let value = elems.borrow()[i].clone();
+                Ok(value)
+            }
+        }
+    }
+
+    /// Evaluates the location `var`, then `value`, then checks the location
+    /// is valid, as compiled code does.
+    fn assign(&mut self, var: &TVar, value: &TExp) -> Eval<()> {
+        match &var.kind {
+            TVarKind::Simple(id) => {
+                let value = self.exp(value)?;
+                let frame = self.frame_of(self.program.var(*id).fun);
+                frame.vars.borrow_mut().insert(*id, value);
+            }
+            TVarKind::Field(record, index) => {
+                let record_value = self.exp(record)?;
+                let value = self.exp(value)?;
+                fields(record_value, &record.pos)?.borrow_mut()[*index] = value;
+            }
+            TVarKind::Subscript(array, index) => {
+                let elems = self.exp(array)?;
+                let i = self.int(index)?;
+                let value = self.exp(value)?;
+                let (elems, i) = element(elems, i, &index.pos)?;
+                elems.borrow_mut()[i] = value;
+            }
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, fun: FunId, args: Vec<Value>, pos: &PosInfo) -> Eval<Value> {
+        let info = self.program.func(fun);
+        let body = match &info.kind {
+            FunKind::Builtin => return self.builtin(&info.name, args, pos),
+            FunKind::User { body } => body,
+        };
+        let frame = Rc::new(Frame {
+            fun,
+            vars: RefCell::new(info.params.iter().copied().zip(args).collect()),
+            link: info.parent.map(|parent| self.frame_of(parent)),
+        });
+        if let Err(err) = self.meter.enter() {
+            return error(pos, err);
+        }
+        let caller = std::mem::replace(&mut self.frame, frame);
+        let result = self.exp(body);
+        self.frame = caller;
+        self.meter.leave();
+        result.map_err(|stop| match stop {
+            Stop::Error(err) => Stop::Error(backtrace(err, &info.name)),
+            stop => stop,
+        })
+    }
+
+    fn builtin(&mut self, name: &str, args: Vec<Value>, pos: &PosInfo) -> Eval<Value> {
+        let io_error = |err: std::io::Error| Stop::Error(err.to_string());
+        match (name, args.as_slice()) {
+            ("print", [Value::String(s)]) => {
+                if let Err(err) = self.meter.output(s.len()) {
+                    return error(pos, err);
+                }
+                self.output.write_all(s).map_err(io_error)?;
+                Ok(Value::Unit)
+            }
+            ("flush", []) => {
+                self.output.flush().map_err(io_error)?;
+                Ok(Value::Unit)
+            }
+            ("getchar", []) => {
+                let mut byte = [0u8];
+                match self.input.read(&mut byte) {
+                    Ok(1) => {
+                        self.alloc(1, pos)?;
+                        Ok(Value::String(byte.into()))
+                    }
+                    Ok(_) => Ok(Value::String(Rc::from([]))),
+                    Err(err) => Err(io_error(err)),
+                }
+            }
+            ("ord", [Value::String(s)]) => Ok(Value::Int(s.first().map_or(-1, |c| *c as i64))),
+            ("chr", [Value::Int(i)]) => match u8::try_from(*i) {
+                Ok(c) => {
+                    self.alloc(1, pos)?;
+                    Ok(Value::String([c].into()))
+                }
+                Err(_) => error(pos, format!("chr({}) out of range", i)),
+            },
+            ("size", [Value::String(s)]) => Ok(Value::Int(s.len() as i64)),
+            ("substring", [Value::String(s), Value::Int(first), Value::Int(n)]) => {
+                if *first < 0 || *n < 0 || first + n > s.len() as i64 {
+                    return error(
+                        pos,
+                        format!(
+                            "substring({:?}, {}, {}) out of range",
+                            String::from_utf8_lossy(s),
+                            first,
+                            n
+                        ),
+                    );
+                }
+                let (first, n) = (*first as usize, *n as usize);
+                self.alloc(n, pos)?;
+                Ok(Value::String(s[first..first + n].into()))
+            }
+            ("concat", [Value::String(a), Value::String(b)]) => {
+                self.alloc(a.len() + b.len(), pos)?;
+                Ok(Value::String([&a[..], &b[..]].concat().into()))
+            }
+            ("not", [Value::Int(i)]) => Ok(Value::Int((*i == 0) as i64)),
+            ("exit", [Value::Int(code)]) => Err(Stop::Exit(*code)),
+            _ => unreachable!("bad call of builtin {}", name),
+        }
+    }
+}
+
+/// Lines kept at each end of a backtrace; those between fold into one
+/// `... K more` line, so that deep recursion gives a short backtrace.
+pub const BACKTRACE_ENDS: usize = 10;
+
+/// `err` with `name` added to its backtrace, as the function the error
+/// unwound out of. A run of activations of the same function takes one
+/// line, `in f (x3)`, and only the innermost and outermost
+/// [`BACKTRACE_ENDS`] lines are kept.
+pub fn backtrace(err: String, name: &str) -> String {
+    let frame = format!("  in {}", name);
+    let (message, trace) = match err.find("\n  ") {
+        Some(start) => (&err[..start], &err[start + 1..]),
+        None => (&err[..], ""),
+    };
+    let mut lines: Vec<String> = trace.lines().map(String::from).collect();
+    match lines.last_mut() {
+        Some(last) if last.split(" (x").next() == Some(&frame) => {
+            *last = format!("{} (x{})", frame, frames(last) + 1);
+        }
+        _ => lines.push(frame),
+    }
+    let folded = lines
+        .get(BACKTRACE_ENDS)
+        .is_some_and(|line| line.starts_with("  ... "));
+    if lines.len() > 2 * BACKTRACE_ENDS + usize::from(folded) {
+        let mut count = frames(&lines.remove(BACKTRACE_ENDS));
+        if folded {
+            count += frames(&lines.remove(BACKTRACE_ENDS));
+        }
+        lines.insert(BACKTRACE_ENDS, format!("  ... {} more", count));
+    }
+    format!("{}\n{}", message, lines.join("\n"))
+}
+
+/// The activations a line of a backtrace stands for.
+fn frames(line: &str) -> usize {
+    let count = match line.strip_prefix("  ... ") {
+        Some(rest) => rest.strip_suffix(" more"),
+        None => line
+            .rsplit_once(" (x")
+            .and_then(|(_, rest)| rest.strip_suffix(')')),
+    };
+    count.and_then(|count| count.parse().ok()).unwrap_or(1)
+}
+
+fn fields(record: Value, pos: &PosInfo) -> Eval<Rc<RefCell<Vec<Value>>>> {
+    match record {
+        Value::Record(fields) => Ok(fields),
+        Value::Nil => error(pos, String::from("nil record dereferenced")),
+        value => unreachable!("{:?} is not a record", value),
+    }
+}
+
+/// The elements of `array` and `i` checked as an index into them.
+fn element(array: Value, i: i64, pos: &PosInfo) -> Eval<(Rc<RefCell<Vec<Value>>>, usize)> {
+    let elems = match array {
+        Value::Array(elems) => elems,
+        value => unreachable!("{:?} is not an array", value),
+    };
+    let size = elems.borrow().len();
+    match usize::try_from(i) {
+        Ok(i) if i < size => Ok((elems, i)),
+        _ => error(
+            pos,
+            format!("index {} out of range for array of size {}", i, size),
+        ),
+    }
+}
+
+fn op(oper: Oper, left: Value, right: Value, pos: &PosInfo) -> Eval<Value> {
+    let int = |b: bool| Ok(Value::Int(b as i64));
+    match (oper, &left, &right) {
+        (Oper::Eq, _, _) => int(left == right),
+        (Oper::Neq, _, _) => int(left != right),
+        (_, Value::Int(a), Value::Int(b)) => match oper {
+            Oper::Plus => Ok(Value::Int(a.wrapping_add(*b))),
+            Oper::Minus => Ok(Value::Int(a.wrapping_sub(*b))),
+            Oper::Times => Ok(Value::Int(a.wrapping_mul(*b))),
+            Oper::Divide if *b == 0 => error(pos, String::from("division by zero")),
+            Oper::Divide => Ok(Value::Int(a.wrapping_div(*b))),
+            Oper::Lt => int(a < b),
+            Oper::Le => int(a <= b),
+            Oper::Gt => int(a > b),
+            Oper::Ge => int(a >= b),
+            Oper::Eq | Oper::Neq => unreachable!(),
+        },
+        (_, Value::String(a), Value::String(b)) => match oper {
+            Oper::Lt => int(a < b),
+            Oper::Le => int(a <= b),
+            Oper::Gt => int(a > b),
+            Oper::Ge => int(a >= b),
+            _ => unreachable!("{:?} on strings", oper),
+        },
+        _ => unreachable!("{:?} on {:?} and {:?}", oper, left, right),
+    }
+}
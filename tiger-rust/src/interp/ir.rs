@@ -0,0 +1,552 @@
+//! An interpreter for Tree IR, to check translation and canonicalization
+//! against the source-level interpreter.
+//!
+//! Memory is a flat space of 64-bit words: string literals and the heap
+//! from [`DATA_BASE`] up, and the stack below [`STACK_TOP`]. A call gets a
+//! frame of `frame_size` bytes below its frame pointer and its own temps;
+//! the arguments are moved into the callee's formals, as the view shift of
+//! the back end would. Calls to the runtime are carried out here, with heap
+//! objects laid out as the runtime lays them out. Nothing is ever freed,
+//! and the string literals count against the heap limit along with the heap.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+use crate::canon::canon::canonicalize;
+use crate::frame::frame::{Access, Frame};
+use crate::interp::interp::backtrace;
+use crate::interp::limits::{grow, Limits, Meter};
+use crate::interp::runtime::{at, labels, Runtime};
+use crate::temp::temp::{Label, Temp};
+use crate::translate::translate::Frag;
+use crate::tree::tree::{BinOp, Exp, RelOp, Stm};
+
+/// Address of the first word of static data.
+pub const DATA_BASE: i64 = 0x1000;
+/// Address just above the stack.
+pub const STACK_TOP: i64 = 0x7fff_0000_0000;
+const STACK_WORDS: usize = 1 << 20;
+
+/// Object headers, as in the runtime's collector.
+const STRING: i64 = 2;
+const ARRAY: i64 = 4;
+const POINTER_ARRAY: i64 = 6;
+
+/// Why execution left the statement it was running.
+enum Stop {
+    /// A jump to a label not in the statement, to be found further out.
+    Goto(Label),
+    Exit(i64),
+    Error(String),
+}
+
+type Eval<T> = Result<T, Stop>;
+
+fn error<T>(msg: String) -> Eval<T> {
+    Err(Stop::Error(msg))
+}
+
+fn error_at<T>(pos: i64, msg: String) -> Eval<T> {
+    error(at(pos, &msg))
+}
+
+/// A run of statements with nested `Seq`s flattened, and where each label
+/// among them is.
+struct Block<'a> {
+    stms: Vec<&'a Stm>,
+    labels: HashMap<&'a Label, usize>,
+}
+
+impl<'a> Block<'a> {
+    fn new(stms: impl IntoIterator<Item = &'a Stm>) -> Block<'a> {
+        fn flatten<'a>(stm: &'a Stm, out: &mut Vec<&'a Stm>) {
+            match stm {
+                Stm::Seq(first, second) => {
+                    flatten(first, out);
+                    flatten(second, out);
+                }
+                stm => out.push(stm),
+            }
+        }
+        let mut flat = Vec::new();
+        for stm in stms {
+            flatten(stm, &mut flat);
+        }
+        let labels = flat
+            .iter()
+            .enumerate()
+            .filter_map(|(i, stm)| match stm {
+                Stm::Label(label) => Some((label, i)),
+                _ => None,
+            })
+            .collect();
+        Block { stms: flat, labels }
+    }
+}
+
+struct Proc<'a, F> {
+    frame: &'a F,
+    body: Block<'a>,
+}
+
+/// The state of one call.
+struct Activation {
+    fp: i64,
+    temps: HashMap<Temp, i64>,
+    /// The source position of the last `Pos` run, for limit errors.
+    pos: Option<i64>,
+}
+
+struct Machine<'a, F: Frame> {
+    procs: HashMap<&'a Label, Rc<Proc<'a, F>>>,
+    runtime: HashMap<Label, Runtime>,
+    /// Addresses of the string literals.
+    strings: HashMap<&'a Label, i64>,
+    /// Blocks of the `Seq`s met so far, by address.
+    blocks: HashMap<*const Stm, Rc<Block<'a>>>,
+    data: Vec<i64>,
+    stack: Vec<i64>,
+    sp: i64,
+    activation: Activation,
+    meter: Meter,
+    input: &'a mut dyn Read,
+    output: &'a mut dyn Write,
+}
+
+/// Runs the fragments of a program, canonicalizing each body first if
+/// `canon` is set. The main program is the last procedure, as `translate`
+/// emits it. Returns the status passed to `exit`, or 0.
+pub fn run<F: Frame + Send>(
+    frags: Vec<Frag<F>>,
+    canon: bool,
+    input: &mut (dyn Read + Send),
+    output: &mut (dyn Write + Send),
+) -> Result<i64, String> {
+    run_with(frags, canon, Limits::default(), input, output)
+}
+
+/// Runs the fragments as [`run`] does, failing once they exceed `limits`.
+pub fn run_with<F: Frame + Send>(
+    frags: Vec<Frag<F>>,
+    canon: bool,
+    limits: Limits,
+    input: &mut (dyn Read + Send),
+    output: &mut (dyn Write + Send),
+) -> Result<i64, String> {
+    let mut bodies = Vec::new();
+    let mut literals = Vec::new();
+    for frag in frags {
+        match frag {
+            Frag::Proc { body, frame } if canon => bodies.push((frame, canonicalize(body))),
+            Frag::Proc { body, frame } => bodies.push((frame, vec![body])),
+            Frag::String { label, value } => literals.push((label, value)),
+        }
+    }
+    execute(bodies, literals, limits, input, output)
+}
+
+fn execute<F: Frame>(
+    bodies: Vec<(F, Vec<Stm>)>,
+    literals: Vec<(Label, String)>,
+    limits: Limits,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+) -> Result<i64, String> {
+    let (main, _) = bodies.last().expect("no main program");
+    let main = main.name().clone();
+    let mut machine = Machine {
+        procs: bodies
+            .iter()
+            .map(|(frame, body)| {
+                let proc = Proc {
+                    frame,
+                    body: Block::new(body),
+                };
+                (frame.name(), Rc::new(proc))
+            })
+            .collect(),
+        runtime: labels::<F>(),
+        strings: HashMap::new(),
+        blocks: HashMap::new(),
+        data: Vec::new(),
+        stack: vec![0; STACK_WORDS],
+        sp: STACK_TOP,
+        activation: Activation {
+            fp: STACK_TOP,
+            temps: HashMap::new(),
+            pos: None,
+        },
+        meter: Meter::new(limits),
+        input,
+        output,
+    };
+    let literals = literals.iter().try_for_each(|(label, value)| {
+        let s = machine.alloc_string(value.as_bytes())?;
+        machine.strings.insert(label, s);
+        Ok(())
+    });
+    let status = match literals.and_then(|()| machine.call(&main, vec![0])) {
+        Ok(_) => Ok(0),
+        Err(Stop::Exit(status)) => Ok(status),
+        Err(Stop::Error(err)) => Err(err),
+        Err(Stop::Goto(label)) => Err(format!("jump to unknown label {}", label)),
+    };
+    machine.output.flush().map_err(|err| err.to_string())?;
+    status
+}
+
+impl<'a, F: Frame> Machine<'a, F> {
+    fn word(&mut self, address: i64) -> Eval<&mut i64> {
+        let stack_base = STACK_TOP - STACK_WORDS as i64 * F::WORD_SIZE;
+        let index = if address % F::WORD_SIZE != 0 {
+            None
+        } else if (stack_base..STACK_TOP).contains(&address) {
+            return Ok(&mut self.stack[((address - stack_base) / F::WORD_SIZE) as usize]);
+        } else if address >= DATA_BASE {
+            Some(((address - DATA_BASE) / F::WORD_SIZE) as usize)
+        } else {
+            None
+        };
+        match index.and_then(|index| self.data.get_mut(index)) {
+            Some(word) => Ok(word),
+            None => error(format!("bad address {:#x}", address)),
+        }
+    }
+
+    /// Stops with `err`, a limit exceeded, at the position last marked.
+    fn limit(&self, err: String) -> Stop {
+        match self.activation.pos {
+            Some(pos) => Stop::Error(at(pos, &err)),
+            None => Stop::Error(err),
+        }
+    }
+
+    fn load(&mut self, address: i64) -> Eval<i64> {
+        self.word(address).map(|word| *word)
+    }
+
+    fn store(&mut self, address: i64, value: i64) -> Eval<()> {
+        *self.word(address)? = value;
+        Ok(())
+    }
+
+    /// A heap object of `words` payload words after `header`, returning the
+    /// address of the payload.
+    fn alloc(&mut self, header: i64, words: usize) -> Eval<i64> {
+        let bytes = words
+            .saturating_add(1)
+            .saturating_mul(F::WORD_SIZE as usize);
+        self.meter.alloc(bytes).map_err(|err| self.limit(err))?;
+        self.data.push(header);
+        let address = DATA_BASE + self.data.len() as i64 * F::WORD_SIZE;
+        self.data.resize(self.data.len() + words, 0);
+        Ok(address)
+    }
+
+    fn alloc_string(&mut self, bytes: &[u8]) -> Eval<i64> {
+        let word = F::WORD_SIZE as usize;
+        let s = self.alloc(STRING, 1 + bytes.len().div_ceil(word))?;
+        let start = ((s - DATA_BASE) / F::WORD_SIZE) as usize;
+        self.data[start] = bytes.len() as i64;
+        for (i, chunk) in bytes.chunks(word).enumerate() {
+            let mut buf = [0u8; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            self.data[start + 1 + i] = i64::from_le_bytes(buf);
+        }
+        Ok(s)
+    }
+
+    fn string(&mut self, s: i64) -> Eval<Vec<u8>> {
+        let len = self.load(s)?;
+        let mut bytes = Vec::with_capacity(len as usize);
+        for i in 0..(len as usize).div_ceil(F::WORD_SIZE as usize) {
+            let word = self.load(s + (1 + i as i64) * F::WORD_SIZE)?;
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes.truncate(len as usize);
+        Ok(bytes)
+    }
+
+    fn call(&mut self, label: &Label, args: Vec<i64>) -> Eval<i64> {
+        if let Some(runtime) = self.runtime.get(label) {
+            return self.runtime(*runtime, args);
+        }
+        let Some(proc) = self.procs.get(label).cloned() else {
+            return error(format!("call of unknown function {}", label));
+        };
+        let frame = proc.frame;
+        if args.len() != frame.formals().len() {
+            return error(format!(
+                "{} takes {} arguments, got {}",
+                label,
+                frame.formals().len(),
+                args.len()
+            ));
+        }
+        let fp = self.sp;
+        let sp = fp - frame.frame_size();
+        if sp < STACK_TOP - STACK_WORDS as i64 * F::WORD_SIZE {
+            return Err(self.limit(String::from("stack overflow")));
+        }
+        self.meter.enter().map_err(|err| self.limit(err))?;
+        let caller = std::mem::replace(
+            &mut self.activation,
+            Activation {
+                fp,
+                temps: HashMap::from([(F::fp(), fp)]),
+                pos: None,
+            },
+        );
+        let saved_sp = std::mem::replace(&mut self.sp, sp);
+        let result = self.body(&proc, args);
+        self.sp = saved_sp;
+        let callee = std::mem::replace(&mut self.activation, caller);
+        self.meter.leave();
+        result?;
+        Ok(callee.temps.get(&F::rv()).copied().unwrap_or(0))
+    }
+
+    fn body(&mut self, proc: &Proc<'a, F>, args: Vec<i64>) -> Eval<()> {
+        let name = proc.frame.name().source_name();
+        for (access, arg) in proc.frame.formals().iter().zip(args) {
+            match access {
+                Access::InFrame(offset) => self.store(self.activation.fp + offset, arg)?,
+                Access::InReg(temp) => {
+                    self.activation.temps.insert(*temp, arg);
+                }
+            }
+        }
+        match self.block(&proc.body) {
+            Err(Stop::Goto(target)) => Err(Stop::Error(backtrace(
+                format!("jump to unknown label {}", target),
+                name,
+            ))),
+            Err(Stop::Error(err)) => Err(Stop::Error(backtrace(err, name))),
+            result => result,
+        }
+    }
+
+    /// Runs `block`, following the jumps to its own labels.
+    fn block(&mut self, block: &Block<'a>) -> Eval<()> {
+        let mut i = 0;
+        while i < block.stms.len() {
+            match self.stm(block.stms[i]) {
+                Ok(()) => i += 1,
+                Err(Stop::Goto(label)) => match block.labels.get(&label) {
+                    Some(target) => i = target + 1,
+                    None => return Err(Stop::Goto(label)),
+                },
+                Err(stop) => return Err(stop),
+            }
+        }
+        Ok(())
+    }
+
+    fn stm(&mut self, stm: &'a Stm) -> Eval<()> {
+        if let Stm::Pos(pos) = stm {
+            self.activation.pos = Some(*pos);
+            return Ok(());
+        }
+        self.meter.step().map_err(|err| self.limit(err))?;
+        match stm {
+            Stm::Move(dst, src) => self.assign(dst, src),
+            Stm::Exp(exp) => self.exp(exp).map(|_| ()),
+            Stm::Jump(target, _) => match &**target {
+                Exp::Name(label) => Err(Stop::Goto(label.clone())),
+                target => error(format!("jump to {:?}", target)),
+            },
+            Stm::CJump(op, left, right, t, f) => {
+                let left = self.exp(left)?;
+                let right = self.exp(right)?;
+                let target = if relop(*op, left, right) { t } else { f };
+                Err(Stop::Goto(target.clone()))
+            }
+            Stm::Seq(..) => {
+                let key = stm as *const Stm;
+                let block = match self.blocks.get(&key) {
+                    Some(block) => block.clone(),
+                    None => {
+                        let block = Rc::new(Block::new([stm]));
+                        self.blocks.insert(key, block.clone());
+                        block
+                    }
+                };
+                self.block(&block)
+            }
+            Stm::Label(_) | Stm::Pos(_) => Ok(()),
+        }
+    }
+
+    fn assign(&mut self, dst: &'a Exp, src: &'a Exp) -> Eval<()> {
+        match dst {
+            Exp::Temp(temp) => {
+                let value = self.exp(src)?;
+                self.activation.temps.insert(*temp, value);
+                Ok(())
+            }
+            Exp::Mem(address) => {
+                let address = self.exp(address)?;
+                let value = self.exp(src)?;
+                self.store(address, value)
+            }
+            Exp::ESeq(stm, dst) => {
+                self.stm(stm)?;
+                self.assign(dst, src)
+            }
+            dst => error(format!("move to {:?}", dst)),
+        }
+    }
+
+    fn exp(&mut self, exp: &'a Exp) -> Eval<i64> {
+        grow(|| self.eval_exp(exp))
+    }
+
+    fn eval_exp(&mut self, exp: &'a Exp) -> Eval<i64> {
+        self.meter.step().map_err(|err| self.limit(err))?;
+        match exp {
+            Exp::Const(i) => Ok(*i),
+            Exp::Name(label) => match self.strings.get(label) {
+                Some(address) => Ok(*address),
+                None => error(format!("address of unknown data {}", label)),
+            },
+            Exp::Temp(temp) => match self.activation.temps.get(temp) {
+                Some(value) => Ok(*value),
+                None => error(format!("temp {} read before it is set", temp)),
+            },
+            Exp::BinOp(op, left, right) => {
+                let left = self.exp(left)?;
+                let right = self.exp(right)?;
+                binop(*op, left, right)
+            }
+            Exp::Mem(address) => {
+                let address = self.exp(address)?;
+                self.load(address)
+            }
+            Exp::Call(fun, args) => {
+                let Exp::Name(label) = &**fun else {
+                    return error(format!("call of {:?}", fun));
+                };
+                let args = args
+                    .iter()
+                    .map(|arg| self.exp(arg))
+                    .collect::<Eval<Vec<_>>>()?;
+                self.call(label, args)
+            }
+            Exp::ESeq(stm, exp) => {
+                self.stm(stm)?;
+                self.exp(exp)
+            }
+        }
+    }
+
+    fn runtime(&mut self, runtime: Runtime, args: Vec<i64>) -> Eval<i64> {
+        let io_error = |err: std::io::Error| Stop::Error(err.to_string());
+        match (runtime, args.as_slice()) {
+            (Runtime::Print, [s]) => {
+                let bytes = self.string(*s)?;
+                self.meter
+                    .output(bytes.len())
+                    .map_err(|err| self.limit(err))?;
+                self.output.write_all(&bytes).map_err(io_error)?;
+                Ok(0)
+            }
+            (Runtime::Flush, []) => {
+                self.output.flush().map_err(io_error)?;
+                Ok(0)
+            }
+            (Runtime::Getchar, []) => {
+                let mut byte = [0u8];
+                let n = self.input.read(&mut byte).map_err(io_error)?;
+                self.alloc_string(&byte[..n])
+            }
+            (Runtime::Ord, [s]) => Ok(self.string(*s)?.first().map_or(-1, |c| *c as i64)),
+            (Runtime::Chr, [i, pos]) => match u8::try_from(*i) {
+                Ok(c) => self.alloc_string(&[c]),
+                Err(_) => error_at(*pos, format!("chr({}) out of range", i)),
+            },
+            (Runtime::Size, [s]) => self.load(*s),
+            (Runtime::Substring, [s, first, n, pos]) => {
+                let bytes = self.string(*s)?;
+                if *first < 0 || *n < 0 || first + n > bytes.len() as i64 {
+                    return error_at(
+                        *pos,
+                        format!(
+                            "substring({:?}, {}, {}) out of range",
+                            String::from_utf8_lossy(&bytes),
+                            first,
+                            n
+                        ),
+                    );
+                }
+                self.alloc_string(&bytes[*first as usize..(first + n) as usize])
+            }
+            (Runtime::Concat, [a, b]) => {
+                let bytes = [self.string(*a)?, self.string(*b)?].concat();
+                self.alloc_string(&bytes)
+            }
+            (Runtime::Not, [i]) => Ok((*i == 0) as i64),
+            (Runtime::Exit, [status]) => Err(Stop::Exit(*status)),
+            (Runtime::StringEqual, [a, b]) => Ok((self.string(*a)? == self.string(*b)?) as i64),
+            (Runtime::StringCompare, [a, b]) => Ok(self.string(*a)?.cmp(&self.string(*b)?) as i64),
+            (Runtime::InitArray, [size, init, pointers, pos]) => {
+                if *size < 0 {
+                    return error_at(*pos, format!("array size {} is negative", size));
+                }
+                let header = if *pointers != 0 { POINTER_ARRAY } else { ARRAY };
+                let a = self.alloc(header, (*size as usize).saturating_add(1))?;
+                let start = ((a - DATA_BASE) / F::WORD_SIZE) as usize;
+                self.data[start] = *size;
+                self.data[start + 1..].fill(*init);
+                Ok(a)
+            }
+            (Runtime::AllocRecord, [size, descriptor]) => {
+                let words = (*size as usize).div_ceil(F::WORD_SIZE as usize);
+                self.alloc(*descriptor, words)
+            }
+            (Runtime::NilRecord, [pos]) => error_at(*pos, String::from("nil record dereferenced")),
+            (Runtime::IndexOutOfRange, [i, size, pos]) => error_at(
+                *pos,
+                format!("index {} out of range for array of size {}", i, size),
+            ),
+            (Runtime::DivisionByZero, [pos]) => error_at(*pos, String::from("division by zero")),
+            (runtime, args) => error(format!(
+                "{:?} called with {} arguments",
+                runtime,
+                args.len()
+            )),
+        }
+    }
+}
+
+fn binop(op: BinOp, left: i64, right: i64) -> Eval<i64> {
+    Ok(match op {
+        BinOp::Plus => left.wrapping_add(right),
+        BinOp::Minus => left.wrapping_sub(right),
+        BinOp::Mul => left.wrapping_mul(right),
+        BinOp::Div if right == 0 => return error(String::from("division by zero")),
+        BinOp::Div => left.wrapping_div(right),
+        BinOp::And => left & right,
+        BinOp::Or => left | right,
+        BinOp::LShift => left.wrapping_shl(right as u32),
+        BinOp::RShift => ((left as u64).wrapping_shr(right as u32)) as i64,
+        BinOp::ArShift => left.wrapping_shr(right as u32),
+        BinOp::Xor => left ^ right,
+    })
+}
+
+fn relop(op: RelOp, left: i64, right: i64) -> bool {
+    let (uleft, uright) = (left as u64, right as u64);
+    match op {
+        RelOp::Eq => left == right,
+        RelOp::Ne => left != right,
+        RelOp::Lt => left < right,
+        RelOp::Gt => left > right,
+        RelOp::Le => left <= right,
+        RelOp::Ge => left >= right,
+        RelOp::Ult => uleft < uright,
+        RelOp::Ule => uleft <= uright,
+        RelOp::Ugt => uleft > uright,
+        RelOp::Uge => uleft >= uright,
+    }
+}
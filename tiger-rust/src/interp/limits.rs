@@ -0,0 +1,168 @@
+//! Resource limits for the execution engines, so that untrusted programs
+//! can be run without hanging or exhausting the host.
+
+use std::time::{Duration, Instant};
+
+/// Limits on one run of a program. `None` leaves a resource unlimited; the
+/// default limits leave every resource but the depth unlimited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limits {
+    /// Steps of the engine: expressions evaluated, IR nodes run or
+    /// instructions executed.
+    pub steps: Option<u64>,
+    /// Function activations live at once, the main program included.
+    pub depth: Option<usize>,
+    /// Bytes of strings, records and arrays, as the engine represents them.
+    /// Engines without a collector count every allocation.
+    pub heap: Option<usize>,
+    /// Bytes written by `print`.
+    pub output: Option<usize>,
+    /// Wall-clock time of the run.
+    pub time: Option<Duration>,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits {
+            steps: None,
+            depth: Some(DEFAULT_DEPTH),
+            heap: None,
+            output: None,
+            time: None,
+        }
+    }
+}
+
+/// The depth limit unless one is given: ample for real programs.
+pub const DEFAULT_DEPTH: usize = 50_000;
+
+/// Host stack an interpreter recursing on it may use between two calls of
+/// [`grow`].
+const RED_ZONE: usize = 128 << 10;
+
+/// Host stack [`grow`] adds at a time.
+const STACK_SEGMENT: usize = 1 << 20;
+
+/// Runs `f`, on a new segment of host stack if the current one is nearly
+/// used up. An interpreter that recurses on the host stack calls it at
+/// every level, so that deep recursion stops at the depth limit rather
+/// than overflowing the stack, while shallow programs use no more stack
+/// than they need.
+#[inline]
+pub fn grow<T>(f: impl FnOnce() -> T) -> T {
+    stacker::maybe_grow(RED_ZONE, STACK_SEGMENT, f)
+}
+
+/// Steps between two readings of the clock.
+const CLOCK_STEPS: u64 = 1024;
+
+/// Tracks a run's use of each resource against its limits.
+pub struct Meter {
+    limits: Limits,
+    start: Instant,
+    steps: u64,
+    /// The step at which to check the step and time limits next.
+    next_check: u64,
+    depth: usize,
+    heap: usize,
+    output: usize,
+}
+
+impl Meter {
+    pub fn new(limits: Limits) -> Meter {
+        let mut meter = Meter {
+            limits,
+            start: Instant::now(),
+            steps: 0,
+            next_check: 0,
+            depth: 0,
+            heap: 0,
+            output: 0,
+        };
+        meter.next_check = meter.next_check();
+        meter
+    }
+
+    fn next_check(&self) -> u64 {
+        let steps = self
+            .limits
+            .steps
+            .map_or(u64::MAX, |max| max.saturating_add(1));
+        match self.limits.time {
+            Some(_) => steps.min(self.steps + CLOCK_STEPS),
+            None => steps,
+        }
+    }
+
+    /// Counts one step.
+    #[inline]
+    pub fn step(&mut self) -> Result<(), String> {
+        self.steps += 1;
+        if self.steps < self.next_check {
+            return Ok(());
+        }
+        self.check()
+    }
+
+    #[cold]
+    fn check(&mut self) -> Result<(), String> {
+        if let Some(max) = self.limits.steps.filter(|max| self.steps > *max) {
+            return Err(format!("step limit of {} exceeded", max));
+        }
+        if let Some(max) = self.limits.time.filter(|max| self.start.elapsed() > *max) {
+            return Err(format!("time limit of {:?} exceeded", max));
+        }
+        self.next_check = self.next_check();
+        Ok(())
+    }
+
+    /// Counts the start of a function activation.
+    pub fn enter(&mut self) -> Result<(), String> {
+        if let Some(max) = self.limits.depth.filter(|max| self.depth >= *max) {
+            return Err(format!("recursion depth limit of {} exceeded", max));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Counts the end of a function activation.
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Whether `bytes` more fit in the heap.
+    pub fn fits(&self, bytes: usize) -> bool {
+        self.limits
+            .heap
+            .is_none_or(|max| self.heap.saturating_add(bytes) <= max)
+    }
+
+    /// Counts an allocation of `bytes`, failing if it does not fit.
+    pub fn alloc(&mut self, bytes: usize) -> Result<(), String> {
+        match self.limits.heap {
+            Some(max) if !self.fits(bytes) => Err(format!("heap limit of {} bytes exceeded", max)),
+            _ => {
+                self.heap = self.heap.saturating_add(bytes);
+                Ok(())
+            }
+        }
+    }
+
+    /// Counts `bytes` of the heap freed by a collection.
+    pub fn free(&mut self, bytes: usize) {
+        self.heap -= bytes;
+    }
+
+    /// Counts `bytes` about to be written, failing before any of them are
+    /// if they do not all fit.
+    pub fn output(&mut self, bytes: usize) -> Result<(), String> {
+        let output = self.output.saturating_add(bytes);
+        match self.limits.output {
+            Some(max) if output > max => Err(format!("output limit of {} bytes exceeded", max)),
+            _ => {
+                self.output = output;
+                Ok(())
+            }
+        }
+    }
+}
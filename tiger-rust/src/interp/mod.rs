@@ -0,0 +1,4 @@
+pub mod interp;
+pub mod ir;
+pub mod limits;
+pub mod runtime;
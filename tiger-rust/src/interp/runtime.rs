@@ -0,0 +1,71 @@
+//! The runtime functions compiled code calls, for the engines that carry
+//! them out themselves rather than link the runtime crate.
+
+use std::collections::HashMap;
+
+use crate::frame::frame::Frame;
+use crate::temp::temp::Label;
+use crate::tree::tree::Exp;
+
+/// The runtime functions compiled code can call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runtime {
+    Print,
+    Flush,
+    Getchar,
+    Ord,
+    Chr,
+    Size,
+    Substring,
+    Concat,
+    Not,
+    Exit,
+    StringEqual,
+    StringCompare,
+    InitArray,
+    AllocRecord,
+    NilRecord,
+    IndexOutOfRange,
+    DivisionByZero,
+}
+
+pub const RUNTIME: [(&str, Runtime); 17] = [
+    ("print", Runtime::Print),
+    ("flush", Runtime::Flush),
+    ("getchar", Runtime::Getchar),
+    ("ord", Runtime::Ord),
+    ("chr", Runtime::Chr),
+    ("size", Runtime::Size),
+    ("substring", Runtime::Substring),
+    ("concat", Runtime::Concat),
+    ("not", Runtime::Not),
+    ("exit", Runtime::Exit),
+    ("stringEqual", Runtime::StringEqual),
+    ("stringCompare", Runtime::StringCompare),
+    ("initArray", Runtime::InitArray),
+    ("allocRecord", Runtime::AllocRecord),
+    ("nilRecord", Runtime::NilRecord),
+    ("indexOutOfRange", Runtime::IndexOutOfRange),
+    ("divisionByZero", Runtime::DivisionByZero),
+];
+
+/// The label `F` calls each runtime function by.
+pub fn labels<F: Frame>() -> HashMap<Label, Runtime> {
+    RUNTIME
+        .iter()
+        .map(|(name, runtime)| match F::external_call(name, Vec::new()) {
+            Exp::Call(fun, _) => match *fun {
+                Exp::Name(label) => (label, *runtime),
+                fun => panic!("runtime function {} at {:?}", name, fun),
+            },
+            call => panic!("runtime call {:?}", call),
+        })
+        .collect()
+}
+
+/// `msg` at the source position `pos`, packed by [`pack_pos`].
+///
+/// [`pack_pos`]: crate::error::error::pack_pos
+pub fn at(pos: i64, msg: &str) -> String {
+    format!("{}.{}: {}", pos >> 16, pos & 0xffff, msg)
+}
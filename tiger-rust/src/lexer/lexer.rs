@@ -21,14 +21,73 @@ fn parse_string(lex: &mut logos::Lexer<Token>) -> ValueInfo<String> {
     let escape_ignore_reg = Regex::new("\\\\[ \\n\\t\\r\\f]+\\\\").unwrap();
     ValueInfo::<String> {
         info: error::get_position_info(lex.span().start),
-        v: String::from(escape_ignore_reg.replace(lex.slice(), "")),
+        v: String::from(escape_ignore_reg.replace_all(lex.slice(), "")),
     }
 }
 
-fn parse_int(lex: &mut logos::Lexer<Token>) -> ValueInfo<u64> {
-    ValueInfo::<u64> {
+/// Decodes the source text of a string literal (quotes included, as stored by
+/// the lexer) into the characters it denotes.
+pub fn unescape(literal: &str) -> String {
+    let body = literal
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(literal);
+    let mut result = String::new();
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some('^') => {
+                if let Some(ctrl) = chars.next() {
+                    result.push(((ctrl.to_ascii_uppercase() as u8) ^ 0x40) as char);
+                }
+            }
+            // `\f___f\`: whitespace between backslashes is dropped, so long
+            // strings can continue on the next line.
+            Some(' ' | '\n' | '\t' | '\r' | '\x0c') => {
+                for c in chars.by_ref() {
+                    if c == '\\' {
+                        break;
+                    }
+                }
+            }
+            Some(d) if d.is_ascii_digit() => {
+                let mut code = d.to_digit(10).unwrap();
+                for _ in 0..2 {
+                    if let Some(next) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        code = code * 10 + next;
+                        chars.next();
+                    }
+                }
+                result.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+            }
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Literals too large for a `u64` lex as [`Token::Error`].
+fn parse_int(lex: &mut logos::Lexer<Token>) -> Option<ValueInfo<u64>> {
+    Some(ValueInfo::<u64> {
         info: error::get_position_info(lex.span().start),
-        v: lex.slice().parse().unwrap(),
+        v: lex.slice().parse().ok()?,
+    })
+}
+
+fn error_message(slice: &str) -> String {
+    if slice.bytes().all(|b| b.is_ascii_digit()) {
+        format!("integer literal out of range: {}", slice)
+    } else {
+        format!("wrong token: {}", slice)
     }
 }
 
@@ -214,7 +273,7 @@ where
                 Token::Error => {
                     let slice = self.slice();
                     let error_pos = self.span().start;
-                    error::emit_error(error_pos, format!("wrong token: {}", slice).as_str());
+                    error::emit_error(error_pos, &error_message(slice));
                     Some(token)
                 }
                 _ => Some(token),
@@ -250,7 +309,7 @@ pub fn tokenize(input: &str) -> Vec<Token> {
             Token::Error => {
                 let slice = lex.slice();
                 let error_pos = lex.span().start;
-                error::emit_error(error_pos, format!("wrong token: {}", slice).as_str());
+                error::emit_error(error_pos, &error_message(slice));
             }
             _ => result.push(token),
         }
@@ -1,9 +1,36 @@
-#[macro_use]
-extern crate lazy_static;
+#![allow(
+    clippy::module_inception,
+    clippy::clone_on_copy,
+    clippy::unnecessary_cast,
+    clippy::needless_return,
+    clippy::needless_borrow,
+    clippy::needless_borrows_for_generic_args,
+    clippy::unused_enumerate_index,
+    clippy::expect_fun_call
+)]
 #[macro_use]
 extern crate lalrpop_util;
 
+pub mod assem;
+pub mod bytecode;
+pub mod c;
+pub mod canon;
+pub mod codegen;
 pub mod error;
+pub mod escape;
+pub mod flowgraph;
+pub mod frame;
+pub mod gc;
+pub mod interp;
 pub mod lexer;
+pub mod liveness;
+pub mod llvm;
 pub mod parser;
+pub mod regalloc;
+pub mod semant;
+pub mod sim;
+pub mod temp;
 pub mod test;
+pub mod translate;
+pub mod tree;
+pub mod wasm;
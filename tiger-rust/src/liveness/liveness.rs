@@ -0,0 +1,241 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::assem::assem::Instr;
+use crate::flowgraph::flowgraph::FlowGraph;
+use crate::temp::temp::Temp;
+
+/// A set of temps, as bits indexed by the dense numbering of a `Liveness`.
+#[derive(Debug, Clone, PartialEq)]
+struct TempSet(Vec<u64>);
+
+impl TempSet {
+    fn new(len: usize) -> TempSet {
+        TempSet(vec![0; len.div_ceil(64)])
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.0[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Adds `other` in; returns whether anything was new.
+    fn union_with(&mut self, other: &TempSet) -> bool {
+        let mut changed = false;
+        for (word, other) in self.0.iter_mut().zip(&other.0) {
+            let new = *word | other;
+            changed |= new != *word;
+            *word = new;
+        }
+        changed
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(at, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(at * 64 + bit)
+            })
+        })
+    }
+}
+
+/// Temps live into and out of each node of a flow graph.
+pub struct Liveness {
+    temps: Vec<Temp>,
+    index: HashMap<Temp, usize>,
+    live_in: Vec<TempSet>,
+    live_out: Vec<TempSet>,
+}
+
+impl Liveness {
+    /// Solves the liveness equations backwards with a work list.
+    pub fn new(flow: &FlowGraph) -> Liveness {
+        let temps: Vec<Temp> = flow
+            .nodes
+            .iter()
+            .flat_map(|node| node.def.iter().chain(&node.uses))
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let index: HashMap<Temp, usize> = temps
+            .iter()
+            .enumerate()
+            .map(|(index, temp)| (*temp, index))
+            .collect();
+        let empty = TempSet::new(temps.len());
+        let mut live_in = vec![empty.clone(); flow.len()];
+        let mut live_out = vec![empty; flow.len()];
+        let mut work: Vec<usize> = (0..flow.len()).collect();
+        let mut queued = vec![true; flow.len()];
+        while let Some(node) = work.pop() {
+            queued[node] = false;
+            let mut out = TempSet::new(temps.len());
+            for succ in &flow.nodes[node].succ {
+                out.union_with(&live_in[*succ]);
+            }
+            let mut new_in = out.clone();
+            for def in &flow.nodes[node].def {
+                new_in.remove(index[def]);
+            }
+            for temp in &flow.nodes[node].uses {
+                new_in.insert(index[temp]);
+            }
+            live_out[node] = out;
+            if new_in != live_in[node] {
+                live_in[node] = new_in;
+                for pred in &flow.nodes[node].pred {
+                    if !queued[*pred] {
+                        queued[*pred] = true;
+                        work.push(*pred);
+                    }
+                }
+            }
+        }
+        Liveness {
+            temps,
+            index,
+            live_in,
+            live_out,
+        }
+    }
+
+    /// Every temp the flow graph mentions, in order.
+    pub fn temps(&self) -> &[Temp] {
+        &self.temps
+    }
+
+    pub fn live_in(&self, node: usize) -> Vec<Temp> {
+        self.live_in[node].iter().map(|i| self.temps[i]).collect()
+    }
+
+    pub fn live_out(&self, node: usize) -> Vec<Temp> {
+        self.live_out[node].iter().map(|i| self.temps[i]).collect()
+    }
+
+    pub fn is_live_out(&self, node: usize, temp: Temp) -> bool {
+        self.index
+            .get(&temp)
+            .is_some_and(|index| self.live_out[node].contains(*index))
+    }
+}
+
+/// Which temps may not share a register, and which are related by moves.
+pub struct InterferenceGraph {
+    temps: Vec<Temp>,
+    index: HashMap<Temp, usize>,
+    adj: Vec<BTreeSet<usize>>,
+    moves: Vec<(Temp, Temp)>,
+}
+
+impl InterferenceGraph {
+    /// Each definition interferes with everything live after it, except
+    /// that a move does not make its destination interfere with its source.
+    pub fn new(flow: &FlowGraph, liveness: &Liveness) -> InterferenceGraph {
+        let mut graph = InterferenceGraph {
+            temps: liveness.temps.clone(),
+            index: liveness.index.clone(),
+            adj: vec![BTreeSet::new(); liveness.temps.len()],
+            moves: Vec::new(),
+        };
+        for (node, flow_node) in flow.nodes.iter().enumerate() {
+            let src = match (flow_node.is_move, flow_node.uses.as_slice()) {
+                (true, [src]) => Some(graph.index[src]),
+                _ => None,
+            };
+            if let (Some(src), [dst]) = (src, flow_node.def.as_slice()) {
+                let pair = (*dst, graph.temps[src]);
+                if pair.0 != pair.1 && !graph.moves.contains(&pair) {
+                    graph.moves.push(pair);
+                }
+            }
+            for def in &flow_node.def {
+                let def = graph.index[def];
+                for live in liveness.live_out[node].iter() {
+                    if live != def && Some(live) != src {
+                        graph.adj[def].insert(live);
+                        graph.adj[live].insert(def);
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    pub fn temps(&self) -> &[Temp] {
+        &self.temps
+    }
+
+    pub fn adjacent(&self, temp: Temp) -> Vec<Temp> {
+        match self.index.get(&temp) {
+            Some(index) => self.adj[*index].iter().map(|i| self.temps[*i]).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn interferes(&self, a: Temp, b: Temp) -> bool {
+        match (self.index.get(&a), self.index.get(&b)) {
+            (Some(a), Some(b)) => self.adj[*a].contains(b),
+            _ => false,
+        }
+    }
+
+    /// Register-to-register moves as `(dst, src)`, each listed once.
+    pub fn moves(&self) -> &[(Temp, Temp)] {
+        &self.moves
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.adj.iter().map(BTreeSet::len).sum::<usize>() / 2
+    }
+}
+
+/// A readable account of the liveness of one function: each instruction
+/// with its live-out set, then the interference graph and the moves.
+pub fn dump(instrs: &[Instr], name: impl Fn(Temp) -> String) -> String {
+    let flow = FlowGraph::new(instrs);
+    let liveness = Liveness::new(&flow);
+    let graph = InterferenceGraph::new(&flow, &liveness);
+    let names = |temps: &[Temp]| -> String {
+        temps
+            .iter()
+            .map(|temp| name(*temp))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let mut out = String::from("instructions:\n");
+    for (node, instr) in instrs.iter().enumerate() {
+        let text = instr.format(&name);
+        let text = text.trim();
+        let text = if text.is_empty() { "<sink>" } else { text };
+        let _ = writeln!(
+            out,
+            "{:5}  {:<32} out: {}",
+            node,
+            text,
+            names(&liveness.live_out(node))
+        );
+    }
+    out.push_str("interference:\n");
+    for temp in graph.temps() {
+        let _ = writeln!(out, "  {}: {}", name(*temp), names(&graph.adjacent(*temp)));
+    }
+    out.push_str("moves:\n");
+    for (dst, src) in graph.moves() {
+        let _ = writeln!(out, "  {} <- {}", name(*dst), name(*src));
+    }
+    out
+}
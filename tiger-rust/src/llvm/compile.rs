@@ -0,0 +1,944 @@
+//! Compiles the typed AST to textual LLVM IR, linked with the runtime
+//! library as the C backend's output is.
+//!
+//! Every Tiger value is an `i64`, strings, records and arrays being laid
+//! out as the runtime expects. Each function allocates a frame struct that
+//! starts with the `tig_frame` header the collector and backtraces walk
+//! and the static link, followed by a slot for every variable a nested
+//! function reaches through that link. The other variables and the
+//! intermediate values that point into the heap are roots, kept in a
+//! second struct with a header of its own and no name. The collector finds
+//! and moves those at any allocation, so pointers are reloaded from their
+//! slots where they are used. The remaining variables get an `alloca` of
+//! their own, which `mem2reg` turns into registers.
+//!
+//! Pointers are opaque `ptr`s, which LLVM 14 reads only when given
+//! `-opaque-pointers`; [`link_llvm`] passes it to a `clang` that old.
+//!
+//! [`link_llvm`]: crate::codegen::link::link_llvm
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::error::error::{pack_pos, PosInfo};
+use crate::semant::tast::{
+    FunId, FunKind, Oper, Program, TDec, TExp, TExpKind, TVar, TVarKind, VarId,
+};
+use crate::semant::types::Type;
+
+const PRELUDE: &str = "%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+";
+
+/// Compiles a type-checked program to an LLVM module defining
+/// `tigermain`, for the runtime library to call.
+pub fn compile(program: &Program) -> String {
+    let mut compiler = Compiler {
+        program,
+        slots: HashMap::new(),
+        roots: HashMap::new(),
+        strings: Vec::new(),
+        string_index: HashMap::new(),
+        funcs: Vec::new(),
+        queued: HashSet::new(),
+    };
+    for fun in (0..program.funcs.len()).map(FunId) {
+        let (escaping, others): (Vec<_>, Vec<_>) = compiler
+            .vars(fun)
+            .into_iter()
+            .partition(|var| program.var(*var).escape);
+        for (slot, var) in escaping.into_iter().enumerate() {
+            compiler.slots.insert(var, slot);
+        }
+        let roots = others
+            .into_iter()
+            .filter(|var| program.types.is_pointer(program.var(*var).ty));
+        for (root, var) in roots.enumerate() {
+            compiler.roots.insert(var, root);
+        }
+    }
+    compiler.queue(program.main);
+    let mut functions = Vec::new();
+    while functions.len() < compiler.funcs.len() {
+        let fun = compiler.funcs[functions.len()];
+        functions.push(compiler.function(fun));
+    }
+    let mut out = String::from(PRELUDE);
+    out.push('\n');
+    for (k, value) in compiler.strings.iter().enumerate() {
+        writeln!(
+            out,
+            "@str{} = private unnamed_addr constant {{ i64, [{} x i8] }} {{ i64 {}, [{} x i8] {} }}, align 8",
+            k,
+            value.len(),
+            value.len(),
+            value.len(),
+            literal(value)
+        )
+        .unwrap();
+    }
+    for (fun, function) in compiler.funcs.iter().zip(functions) {
+        out.push('\n');
+        let mut fields = vec![String::from("%tig_frame"), String::from("ptr")];
+        fields.extend(function.slots.iter().map(|_| String::from("i64")));
+        writeln!(
+            out,
+            "{} = type {{ {} }}",
+            frame(program, *fun),
+            fields.join(", ")
+        )
+        .unwrap();
+        if function.roots > 0 {
+            writeln!(
+                out,
+                "{} = type {{ %tig_frame{} }}",
+                roots(program, *fun),
+                ", i64".repeat(function.roots)
+            )
+            .unwrap();
+        }
+        out.push('\n');
+        out.push_str(&function.text);
+    }
+    out
+}
+
+/// `bytes` as an LLVM string constant.
+fn literal(bytes: &[u8]) -> String {
+    let mut out = String::from("c\"");
+    for byte in bytes {
+        match byte {
+            b' '..=b'~' if !matches!(byte, b'"' | b'\\') => out.push(*byte as char),
+            _ => write!(out, "\\{:02X}", byte).unwrap(),
+        }
+    }
+    out + "\""
+}
+
+/// The type of the frame of `fun`.
+fn frame(program: &Program, fun: FunId) -> String {
+    format!("%{}.frame", program.func(fun).label)
+}
+
+/// The type of the roots of `fun`.
+fn roots(program: &Program, fun: FunId) -> String {
+    format!("%{}.roots", program.func(fun).label)
+}
+
+/// The letter for a slot or field in a descriptor.
+fn kind(pointer: bool) -> char {
+    if pointer {
+        'p'
+    } else {
+        'n'
+    }
+}
+
+/// Where a compiled expression left its value.
+enum Value {
+    /// A constant or register, which the collector never changes.
+    Operand(String),
+    /// A root holding a pointer into the heap.
+    Root(usize),
+}
+
+struct Compiler<'a> {
+    program: &'a Program,
+    /// The frame slot of each variable a nested function reaches.
+    slots: HashMap<VarId, usize>,
+    /// The root of each other variable that points into the heap.
+    roots: HashMap<VarId, usize>,
+    strings: Vec<Vec<u8>>,
+    string_index: HashMap<Vec<u8>, usize>,
+    /// The functions to compile, in order of first use.
+    funcs: Vec<FunId>,
+    queued: HashSet<FunId>,
+}
+
+/// A compiled function and the slots and roots it needs.
+struct Function {
+    text: String,
+    slots: Vec<bool>,
+    roots: usize,
+}
+
+/// The LLVM instructions of the function being compiled and the size of
+/// its roots record.
+struct Body {
+    fun: FunId,
+    /// The type of the roots.
+    roots_type: String,
+    lines: Vec<String>,
+    /// Numbers the registers and blocks of the function.
+    next: usize,
+    /// The block being filled, for `phi`.
+    block: String,
+    /// The number of roots, which grows as intermediate values need them.
+    roots: usize,
+    /// Where `break` goes in each enclosing loop.
+    exits: Vec<String>,
+}
+
+impl Body {
+    fn line(&mut self, line: String) {
+        self.lines.push(format!("  {}", line));
+    }
+
+    /// A fresh register.
+    fn register(&mut self) -> String {
+        self.next += 1;
+        format!("%v{}", self.next)
+    }
+
+    /// A fresh block name.
+    fn label(&mut self) -> String {
+        self.next += 1;
+        format!("b{}", self.next)
+    }
+
+    fn start(&mut self, label: String) {
+        self.lines.push(format!("{}:", label));
+        self.block = label;
+    }
+
+    fn root(&mut self) -> usize {
+        self.roots += 1;
+        self.roots - 1
+    }
+
+    /// The address of `field` in the frame of `frame` at `address`.
+    fn field(&mut self, frame: &str, address: &str, field: usize) -> String {
+        let r = self.register();
+        self.line(format!(
+            "{} = getelementptr {}, ptr {}, i32 0, i32 {}",
+            r, frame, address, field
+        ));
+        r
+    }
+
+    /// The address of the root `root`.
+    fn root_address(&mut self, root: usize) -> String {
+        let roots = self.roots_type.clone();
+        self.field(&roots, "%r", 1 + root)
+    }
+}
+
+impl Compiler<'_> {
+    fn queue(&mut self, fun: FunId) {
+        if self.queued.insert(fun) {
+            self.funcs.push(fun);
+        }
+    }
+
+    /// A constant for the address of the string `value`.
+    fn string(&mut self, value: &[u8]) -> String {
+        let k = match self.string_index.get(value) {
+            Some(k) => *k,
+            None => {
+                self.strings.push(value.to_vec());
+                self.string_index
+                    .insert(value.to_vec(), self.strings.len() - 1);
+                self.strings.len() - 1
+            }
+        };
+        format!("ptrtoint (ptr @str{} to i64)", k)
+    }
+
+    fn is_pointer(&self, ty: Type) -> bool {
+        self.program.types.is_pointer(ty)
+    }
+
+    fn has_value(&self, exp: &TExp) -> bool {
+        self.program.types.actual(exp.ty) != Type::Unit
+    }
+
+    /// The variables whose home is the frame of `fun`, parameters first.
+    fn vars(&self, fun: FunId) -> Vec<VarId> {
+        let params = &self.program.func(fun).params;
+        let others = (0..self.program.vars.len())
+            .map(VarId)
+            .filter(|var| self.program.var(*var).fun == fun && !params.contains(var));
+        params.iter().copied().chain(others).collect()
+    }
+
+    fn var_name(&self, var: VarId) -> String {
+        format!("%{}_{}", self.program.var(var).name, var.0)
+    }
+
+    fn function(&mut self, fun: FunId) -> Function {
+        let info = self.program.func(fun);
+        let FunKind::User { body } = &info.kind else {
+            unreachable!("builtins are not compiled")
+        };
+        let frame = frame(self.program, fun);
+        let roots_type = roots(self.program, fun);
+        let vars = self.vars(fun);
+        let slots: Vec<bool> = vars
+            .iter()
+            .filter(|var| self.slots.contains_key(var))
+            .map(|var| self.is_pointer(self.program.var(*var).ty))
+            .collect();
+        let mut code = Body {
+            fun,
+            roots_type: roots_type.clone(),
+            lines: Vec::new(),
+            next: 0,
+            block: String::from("entry"),
+            roots: vars
+                .iter()
+                .filter(|var| self.roots.contains_key(var))
+                .count(),
+            exits: Vec::new(),
+        };
+        let result = self.value(&mut code, body);
+        let result = self.load(&mut code, &result);
+        let descriptor: String = std::iter::once('n')
+            .chain(slots.iter().map(|pointer| kind(*pointer)))
+            .collect();
+        let name = self.string(info.name.as_bytes());
+        let descriptor = self.string(descriptor.as_bytes());
+        // The roots come after the frame in the runtime's list, unnamed so
+        // that backtraces leave them out.
+        let mut records = vec![(
+            "header",
+            "%f",
+            &frame,
+            String::from("%prev"),
+            name,
+            descriptor,
+        )];
+        if code.roots > 0 {
+            let descriptor = self.string("p".repeat(code.roots).as_bytes());
+            records.push((
+                "roots",
+                "%r",
+                &roots_type,
+                String::from("%f"),
+                String::from("0"),
+                descriptor,
+            ));
+        }
+        let mut params = vec![String::from("ptr %link")];
+        params.extend(
+            info.params
+                .iter()
+                .map(|param| format!("i64 {}", self.var_name(*param))),
+        );
+        let mut out = format!(
+            "define i64 @{}({}) {{\nentry:\n",
+            info.label,
+            params.join(", "),
+        );
+        for (_, record, ty, ..) in &records {
+            writeln!(out, "  {} = alloca {}", record, ty).unwrap();
+        }
+        for var in vars.iter().filter(|var| self.home(**var).is_none()) {
+            writeln!(out, "  {}.addr = alloca i64", self.var_name(*var)).unwrap();
+        }
+        writeln!(out, "  %prev = load ptr, ptr @tig_c_frames").unwrap();
+        for (prefix, record, ty, prev, name, descriptor) in &records {
+            writeln!(out, "  store {} zeroinitializer, ptr {}", ty, record).unwrap();
+            for (index, (field, field_ty, value)) in [
+                ("prev", "ptr", prev),
+                ("name", "i64", name),
+                ("descriptor", "i64", descriptor),
+            ]
+            .into_iter()
+            .enumerate()
+            {
+                writeln!(
+                    out,
+                    "  %{0}.{1} = getelementptr {2}, ptr {3}, i32 0, i32 0, i32 {4}\n  store {5} {6}, ptr %{0}.{1}",
+                    prefix, field, ty, record, index, field_ty, value
+                )
+                .unwrap();
+            }
+        }
+        writeln!(
+            out,
+            "  %f.link = getelementptr {}, ptr %f, i32 0, i32 1\n  store ptr %link, ptr %f.link",
+            frame
+        )
+        .unwrap();
+        for param in &info.params {
+            let name = self.var_name(*param);
+            let address = match self.home(*param) {
+                Some((record, ty, field)) => {
+                    writeln!(
+                        out,
+                        "  {}.slot = getelementptr {}, ptr {}, i32 0, i32 {}",
+                        name, ty, record, field
+                    )
+                    .unwrap();
+                    format!("{}.slot", name)
+                }
+                None => format!("{}.addr", name),
+            };
+            writeln!(out, "  store i64 {}, ptr {}", name, address).unwrap();
+        }
+        let (_, innermost, ..) = records.last().unwrap();
+        writeln!(out, "  store ptr {}, ptr @tig_c_frames", innermost).unwrap();
+        for line in code.lines {
+            writeln!(out, "{}", line).unwrap();
+        }
+        writeln!(
+            out,
+            "  store ptr %prev, ptr @tig_c_frames\n  ret i64 {}\n}}",
+            result
+        )
+        .unwrap();
+        Function {
+            text: out,
+            slots,
+            roots: code.roots,
+        }
+    }
+
+    /// An operand for `value`, loading it from its slot if it has one, so
+    /// it is valid until the next allocation.
+    fn load(&mut self, code: &mut Body, value: &Value) -> String {
+        match value {
+            Value::Operand(operand) => operand.clone(),
+            Value::Root(root) => {
+                let address = code.root_address(*root);
+                let r = code.register();
+                code.line(format!("{} = load i64, ptr {}", r, address));
+                r
+            }
+        }
+    }
+
+    /// The value of type `ty` in the register `r`, moved to a root if the
+    /// collector has to see it.
+    fn result(&mut self, code: &mut Body, r: String, ty: Type) -> Value {
+        if !self.is_pointer(ty) {
+            return Value::Operand(r);
+        }
+        let root = code.root();
+        let address = code.root_address(root);
+        code.line(format!("store i64 {}, ptr {}", r, address));
+        Value::Root(root)
+    }
+
+    /// Branches to a block calling the runtime function `call`, which does
+    /// not return, when `cond` holds.
+    fn check(&mut self, code: &mut Body, cond: String, call: String) {
+        let err = code.label();
+        let ok = code.label();
+        code.line(format!("br i1 {}, label %{}, label %{}", cond, err, ok));
+        code.start(err);
+        code.line(call);
+        code.line(String::from("unreachable"));
+        code.start(ok);
+    }
+
+    /// Compiles `exp`, returning where its value is, or `0` if it has none.
+    fn value(&mut self, code: &mut Body, exp: &TExp) -> Value {
+        let pos = &exp.pos;
+        match &exp.kind {
+            TExpKind::Int(i) => Value::Operand(i.to_string()),
+            TExpKind::String(value) => Value::Operand(self.string(value.as_bytes())),
+            TExpKind::Nil => Value::Operand(String::from("0")),
+            TExpKind::Var(var) => self.var(code, var),
+            TExpKind::Call(fun, args) => {
+                let values: Vec<_> = args.iter().map(|arg| self.value(code, arg)).collect();
+                let mut operands: Vec<_> = values
+                    .iter()
+                    .map(|value| format!("i64 {}", self.load(code, value)))
+                    .collect();
+                let info = self.program.func(*fun);
+                let (callee, result) = match &info.kind {
+                    FunKind::Builtin => {
+                        if matches!(info.name.as_str(), "chr" | "substring") {
+                            operands.push(format!("i64 {}", pack_pos(pos)));
+                        }
+                        let result = match self.has_value(exp) {
+                            true => "i64",
+                            false => "void",
+                        };
+                        (format!("@tig_{}", info.name), result)
+                    }
+                    FunKind::User { .. } => {
+                        self.queue(*fun);
+                        let parent = info.parent.expect("function without parent");
+                        let link = self.frame_of(code, parent);
+                        operands.insert(0, format!("ptr {}", link));
+                        (format!("@{}", info.label), "i64")
+                    }
+                };
+                let call = format!("call {} {}({})", result, callee, operands.join(", "));
+                if !self.has_value(exp) {
+                    code.line(call);
+                    return Value::Operand(String::from("0"));
+                }
+                let r = code.register();
+                code.line(format!("{} = {}", r, call));
+                self.result(code, r, exp.ty)
+            }
+            TExpKind::Op(oper, left, right) => {
+                let l = self.value(code, left);
+                let r = self.value(code, right);
+                let l = self.load(code, &l);
+                let r = self.load(code, &r);
+                let string = self.program.types.actual(left.ty) == Type::String;
+                let v = code.register();
+                match (oper, string) {
+                    (Oper::Plus | Oper::Minus | Oper::Times, _) => {
+                        let op = match oper {
+                            Oper::Plus => "add",
+                            Oper::Minus => "sub",
+                            _ => "mul",
+                        };
+                        code.line(format!("{} = {} i64 {}, {}", v, op, l, r));
+                        return Value::Operand(v);
+                    }
+                    (Oper::Divide, _) => {
+                        let zero = code.register();
+                        code.line(format!("{} = icmp eq i64 {}, 0", zero, r));
+                        let call = format!("call void @tig_divisionByZero(i64 {})", pack_pos(pos));
+                        self.check(code, zero, call);
+                        // Dividing the least integer by -1 overflows, which
+                        // `sdiv` leaves undefined.
+                        let (minus, divisor, quotient, negated) = (
+                            code.register(),
+                            code.register(),
+                            code.register(),
+                            code.register(),
+                        );
+                        code.line(format!("{} = icmp eq i64 {}, -1", minus, r));
+                        code.line(format!(
+                            "{} = select i1 {}, i64 1, i64 {}",
+                            divisor, minus, r
+                        ));
+                        code.line(format!("{} = sdiv i64 {}, {}", quotient, l, divisor));
+                        code.line(format!("{} = sub i64 0, {}", negated, l));
+                        code.line(format!(
+                            "{} = select i1 {}, i64 {}, i64 {}",
+                            v, minus, negated, quotient
+                        ));
+                        return Value::Operand(v);
+                    }
+                    (Oper::Eq | Oper::Neq, true) => {
+                        let equal = code.register();
+                        code.line(format!(
+                            "{} = call i64 @tig_stringEqual(i64 {}, i64 {})",
+                            equal, l, r
+                        ));
+                        let pred = match oper {
+                            Oper::Eq => "ne",
+                            _ => "eq",
+                        };
+                        code.line(format!("{} = icmp {} i64 {}, 0", v, pred, equal));
+                    }
+                    (oper, true) => {
+                        let order = code.register();
+                        code.line(format!(
+                            "{} = call i64 @tig_stringCompare(i64 {}, i64 {})",
+                            order, l, r
+                        ));
+                        code.line(format!(
+                            "{} = icmp {} i64 {}, 0",
+                            v,
+                            comparison(*oper),
+                            order
+                        ));
+                    }
+                    (oper, false) => {
+                        code.line(format!(
+                            "{} = icmp {} i64 {}, {}",
+                            v,
+                            comparison(*oper),
+                            l,
+                            r
+                        ));
+                    }
+                }
+                let t = code.register();
+                code.line(format!("{} = zext i1 {} to i64", t, v));
+                Value::Operand(t)
+            }
+            TExpKind::And(left, right) => self.short_circuit(code, left, right, true),
+            TExpKind::Or(left, right) => self.short_circuit(code, left, right, false),
+            TExpKind::Record(_, fields) => {
+                let values: Vec<_> = fields.iter().map(|field| self.value(code, field)).collect();
+                let types = &self.program.types;
+                let descriptor: String = types
+                    .record_fields(exp.ty)
+                    .expect("record of non-record type")
+                    .iter()
+                    .map(|(_, ty)| kind(types.is_pointer(*ty)))
+                    .collect();
+                let descriptor = self.string(descriptor.as_bytes());
+                let r = code.register();
+                code.line(format!(
+                    "{} = call i64 @tig_allocRecord(i64 {}, i64 {})",
+                    r,
+                    8 * fields.len(),
+                    descriptor
+                ));
+                let record = code.register();
+                code.line(format!("{} = inttoptr i64 {} to ptr", record, r));
+                for (i, value) in values.iter().enumerate() {
+                    let value = self.load(code, value);
+                    let address = code.register();
+                    code.line(format!(
+                        "{} = getelementptr i64, ptr {}, i64 {}",
+                        address, record, i
+                    ));
+                    code.line(format!("store i64 {}, ptr {}", value, address));
+                }
+                self.result(code, r, exp.ty)
+            }
+            TExpKind::Array(_, size, init) => {
+                let size = self.value(code, size);
+                let pointers = self.is_pointer(init.ty) as i64;
+                let init = self.value(code, init);
+                let size = self.load(code, &size);
+                let init = self.load(code, &init);
+                let r = code.register();
+                code.line(format!(
+                    "{} = call i64 @tig_initArray(i64 {}, i64 {}, i64 {}, i64 {})",
+                    r,
+                    size,
+                    init,
+                    pointers,
+                    pack_pos(pos)
+                ));
+                self.result(code, r, exp.ty)
+            }
+            TExpKind::Seq(exps) => {
+                let mut result = Value::Operand(String::from("0"));
+                for exp in exps {
+                    result = self.value(code, exp);
+                }
+                result
+            }
+            TExpKind::Assign(var, value) => {
+                self.assign(code, var, value);
+                Value::Operand(String::from("0"))
+            }
+            TExpKind::If(test, then, els) => {
+                let c = self.value(code, test);
+                let c = self.load(code, &c);
+                let cond = code.register();
+                code.line(format!("{} = icmp ne i64 {}, 0", cond, c));
+                let (then_label, else_label, end) = (code.label(), code.label(), code.label());
+                let else_target = match els {
+                    Some(_) => &else_label,
+                    None => &end,
+                };
+                code.line(format!(
+                    "br i1 {}, label %{}, label %{}",
+                    cond, then_label, else_target
+                ));
+                // Values of pointer type meet in a root, integers in a phi.
+                let root = (self.has_value(exp) && self.is_pointer(exp.ty)).then(|| code.root());
+                let mut incoming = Vec::new();
+                let mut branches = vec![(then_label, then)];
+                if let Some(els) = els {
+                    branches.push((else_label, els));
+                }
+                for (label, branch) in branches {
+                    code.start(label);
+                    let value = self.value(code, branch);
+                    if self.has_value(exp) {
+                        let value = self.load(code, &value);
+                        match root {
+                            Some(root) => {
+                                let address = code.root_address(root);
+                                code.line(format!("store i64 {}, ptr {}", value, address));
+                            }
+                            None => incoming.push(format!("[ {}, %{} ]", value, code.block)),
+                        }
+                    }
+                    code.line(format!("br label %{}", end));
+                }
+                code.start(end);
+                match root {
+                    Some(root) => Value::Root(root),
+                    None if self.has_value(exp) => {
+                        let r = code.register();
+                        code.line(format!("{} = phi i64 {}", r, incoming.join(", ")));
+                        Value::Operand(r)
+                    }
+                    None => Value::Operand(String::from("0")),
+                }
+            }
+            TExpKind::While(test, body) => {
+                let (test_label, body_label, exit) = (code.label(), code.label(), code.label());
+                code.line(format!("br label %{}", test_label));
+                code.start(test_label.clone());
+                let c = self.value(code, test);
+                let c = self.load(code, &c);
+                let cond = code.register();
+                code.line(format!("{} = icmp ne i64 {}, 0", cond, c));
+                code.line(format!(
+                    "br i1 {}, label %{}, label %{}",
+                    cond, body_label, exit
+                ));
+                code.start(body_label);
+                code.exits.push(exit.clone());
+                self.value(code, body);
+                code.exits.pop();
+                code.line(format!("br label %{}", test_label));
+                code.start(exit);
+                Value::Operand(String::from("0"))
+            }
+            TExpKind::For(var, lo, hi, body) => {
+                let lo = self.value(code, lo);
+                let lo = self.load(code, &lo);
+                let i = self.address(code, *var);
+                code.line(format!("store i64 {}, ptr {}", lo, i));
+                let hi = self.value(code, hi);
+                let limit = self.load(code, &hi);
+                let (body_label, next, exit) = (code.label(), code.label(), code.label());
+                let (first, enter) = (code.register(), code.register());
+                code.line(format!("{} = load i64, ptr {}", first, i));
+                code.line(format!("{} = icmp sle i64 {}, {}", enter, first, limit));
+                code.line(format!(
+                    "br i1 {}, label %{}, label %{}",
+                    enter, body_label, exit
+                ));
+                code.start(body_label.clone());
+                code.exits.push(exit.clone());
+                self.value(code, body);
+                code.exits.pop();
+                let (current, done) = (code.register(), code.register());
+                code.line(format!("{} = load i64, ptr {}", current, i));
+                code.line(format!("{} = icmp sge i64 {}, {}", done, current, limit));
+                code.line(format!("br i1 {}, label %{}, label %{}", done, exit, next));
+                code.start(next);
+                let incremented = code.register();
+                code.line(format!("{} = add i64 {}, 1", incremented, current));
+                code.line(format!("store i64 {}, ptr {}", incremented, i));
+                code.line(format!("br label %{}", body_label));
+                code.start(exit);
+                Value::Operand(String::from("0"))
+            }
+            TExpKind::Break => {
+                let exit = code.exits.last().expect("break outside loop").clone();
+                code.line(format!("br label %{}", exit));
+                // Whatever follows the break in its sequence is unreachable.
+                let dead = code.label();
+                code.start(dead);
+                Value::Operand(String::from("0"))
+            }
+            TExpKind::Let(decs, body) => {
+                for dec in decs {
+                    match dec {
+                        TDec::Var(var, init) => {
+                            let value = self.value(code, init);
+                            let value = self.load(code, &value);
+                            let address = self.address(code, *var);
+                            code.line(format!("store i64 {}, ptr {}", value, address));
+                        }
+                        TDec::Funcs(funs) => {
+                            for fun in funs {
+                                self.queue(*fun);
+                            }
+                        }
+                    }
+                }
+                self.value(code, body)
+            }
+        }
+    }
+
+    /// `left & right` if `and`, else `left | right`.
+    fn short_circuit(&mut self, code: &mut Body, left: &TExp, right: &TExp, and: bool) -> Value {
+        let l = self.value(code, left);
+        let l = self.load(code, &l);
+        let cond = code.register();
+        code.line(format!("{} = icmp ne i64 {}, 0", cond, l));
+        let from = code.block.clone();
+        let (right_label, end) = (code.label(), code.label());
+        let (on_true, on_false) = match and {
+            true => (&right_label, &end),
+            false => (&end, &right_label),
+        };
+        code.line(format!(
+            "br i1 {}, label %{}, label %{}",
+            cond, on_true, on_false
+        ));
+        code.start(right_label);
+        let r = self.value(code, right);
+        let r = self.load(code, &r);
+        let (rcond, rvalue) = (code.register(), code.register());
+        code.line(format!("{} = icmp ne i64 {}, 0", rcond, r));
+        code.line(format!("{} = zext i1 {} to i64", rvalue, rcond));
+        let right_end = code.block.clone();
+        code.line(format!("br label %{}", end));
+        code.start(end);
+        let v = code.register();
+        code.line(format!(
+            "{} = phi i64 [ {}, %{} ], [ {}, %{} ]",
+            v,
+            (!and) as i64,
+            from,
+            rvalue,
+            right_end
+        ));
+        Value::Operand(v)
+    }
+
+    /// A pointer to the frame of `target`, following static links from the
+    /// function being compiled.
+    fn frame_of(&self, code: &mut Body, target: FunId) -> String {
+        let mut fun = code.fun;
+        let mut address = String::from("%f");
+        while fun != target {
+            let link = code.field(&frame(self.program, fun), &address, 1);
+            address = code.register();
+            code.line(format!("{} = load ptr, ptr {}", address, link));
+            fun = self
+                .program
+                .func(fun)
+                .parent
+                .expect("target is not enclosing");
+        }
+        address
+    }
+
+    /// The record holding `var` in its own function, with the record's
+    /// type and the field, or `None` if it has an `alloca` of its own.
+    fn home(&self, var: VarId) -> Option<(&'static str, String, usize)> {
+        let fun = self.program.var(var).fun;
+        match (self.slots.get(&var), self.roots.get(&var)) {
+            (Some(slot), _) => Some(("%f", frame(self.program, fun), 2 + slot)),
+            (None, Some(root)) => Some(("%r", roots(self.program, fun), 1 + root)),
+            (None, None) => None,
+        }
+    }
+
+    /// The address of the variable `var` as seen from the function being
+    /// compiled. Only variables in the frame are reached from nested
+    /// functions.
+    fn address(&self, code: &mut Body, var: VarId) -> String {
+        match self.home(var) {
+            Some(("%f", ty, field)) => {
+                let address = self.frame_of(code, self.program.var(var).fun);
+                code.field(&ty, &address, field)
+            }
+            Some((record, ty, field)) => code.field(&ty, record, field),
+            None => format!("{}.addr", self.var_name(var)),
+        }
+    }
+
+    /// The address of the field `index` of `record`, checked not nil.
+    fn field(&mut self, code: &mut Body, record: &Value, index: usize, pos: &PosInfo) -> String {
+        let record = self.load(code, record);
+        let nil = code.register();
+        code.line(format!("{} = icmp eq i64 {}, 0", nil, record));
+        let call = format!("call void @tig_nilRecord(i64 {})", pack_pos(pos));
+        self.check(code, nil, call);
+        let (pointer, address) = (code.register(), code.register());
+        code.line(format!("{} = inttoptr i64 {} to ptr", pointer, record));
+        code.line(format!(
+            "{} = getelementptr i64, ptr {}, i64 {}",
+            address, pointer, index
+        ));
+        address
+    }
+
+    /// The address of the element `i` of `array`, checked in range.
+    fn element(&mut self, code: &mut Body, array: &Value, i: &Value, pos: &PosInfo) -> String {
+        let array = self.load(code, array);
+        let i = self.load(code, i);
+        let (pointer, size, out) = (code.register(), code.register(), code.register());
+        code.line(format!("{} = inttoptr i64 {} to ptr", pointer, array));
+        code.line(format!("{} = load i64, ptr {}", size, pointer));
+        code.line(format!("{} = icmp uge i64 {}, {}", out, i, size));
+        let call = format!(
+            "call void @tig_indexOutOfRange(i64 {}, i64 {}, i64 {})",
+            i,
+            size,
+            pack_pos(pos)
+        );
+        self.check(code, out, call);
+        let (k, address) = (code.register(), code.register());
+        code.line(format!("{} = add i64 {}, 1", k, i));
+        code.line(format!(
+            "{} = getelementptr i64, ptr {}, i64 {}",
+            address, pointer, k
+        ));
+        address
+    }
+
+    fn var(&mut self, code: &mut Body, var: &TVar) -> Value {
+        let address = match &var.kind {
+            TVarKind::Simple(id) => self.address(code, *id),
+            TVarKind::Field(record, index) => {
+                let r = self.value(code, record);
+                self.field(code, &r, *index, &record.pos)
+            }
+            TVarKind::Subscript(array, index) => {
+                let a = self.value(code, array);
+                let i = self.value(code, index);
+                self.element(code, &a, &i, &index.pos)
+            }
+        };
+        let r = code.register();
+        code.line(format!("{} = load i64, ptr {}", r, address));
+        self.result(code, r, var.ty)
+    }
+
+    /// Compiles an assignment to a `store`, computing the value before the
+    /// field or element address whose checks may fail.
+    fn assign(&mut self, code: &mut Body, var: &TVar, value: &TExp) {
+        let (address, value) = match &var.kind {
+            TVarKind::Simple(id) => {
+                let value = self.value(code, value);
+                let value = self.load(code, &value);
+                (self.address(code, *id), value)
+            }
+            TVarKind::Field(record, index) => {
+                let r = self.value(code, record);
+                let v = self.value(code, value);
+                let address = self.field(code, &r, *index, &record.pos);
+                (address, self.load(code, &v))
+            }
+            TVarKind::Subscript(array, index) => {
+                let a = self.value(code, array);
+                let i = self.value(code, index);
+                let v = self.value(code, value);
+                let address = self.element(code, &a, &i, &index.pos);
+                (address, self.load(code, &v))
+            }
+        };
+        code.line(format!("store i64 {}, ptr {}", value, address));
+    }
+}
+
+fn comparison(oper: Oper) -> &'static str {
+    match oper {
+        Oper::Eq => "eq",
+        Oper::Neq => "ne",
+        Oper::Lt => "slt",
+        Oper::Le => "sle",
+        Oper::Gt => "sgt",
+        Oper::Ge => "sge",
+        oper => unreachable!("{:?} is not a comparison", oper),
+    }
+}
@@ -1,3 +1,227 @@
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs, io, process};
+
+use tiger_rust::bytecode::disasm::disassemble;
+use tiger_rust::bytecode::{compile, vm};
+use tiger_rust::c::compile::compile as compile_c;
+use tiger_rust::canon::canon::canonicalize;
+use tiger_rust::codegen::codegen::{compile_with, instructions, Codegen};
+use tiger_rust::codegen::link::{find_runtime, link, link_c, link_llvm};
+use tiger_rust::codegen::mips::RUNTIME as MIPS_RUNTIME;
+use tiger_rust::frame::frame::Frame;
+use tiger_rust::frame::mips::MipsFrame;
+use tiger_rust::frame::riscv64::Riscv64Frame;
+use tiger_rust::frame::x86_64::X86_64Frame;
+use tiger_rust::interp::limits::{Limits, DEFAULT_DEPTH};
+use tiger_rust::interp::{interp, ir};
+use tiger_rust::liveness::liveness;
+use tiger_rust::llvm::compile::compile as compile_llvm;
+use tiger_rust::parser::arena::Ast;
+use tiger_rust::parser::dot::DotOptions;
+use tiger_rust::parser::parser::compile_to_arena;
+use tiger_rust::regalloc::regalloc::Allocator;
+use tiger_rust::semant::semant::trans_ast;
+use tiger_rust::sim::mips;
+use tiger_rust::translate::translate::{translate, Frag};
+use tiger_rust::wasm::compile::compile as compile_wasm;
+use tiger_rust::wasm::wat::to_wat;
+
+const USAGE: &str =
+    "usage: tiger-rust [--absyn | --json | --yaml | --dot | --dot-collapsed | --ir | --canon | --asm | --mips | --mips-sim | --riscv64 | --c | --llvm | --liveness | --interp | --interp-ir | --interp-canon | --bytecode | --vm | --wat | --wasm | -o <exe>] [--linear-scan] [--max-steps <n>] [--max-depth <n>] [--max-heap <bytes>] [--max-output <bytes>] [--timeout <secs>] <file.tig | ast.json | ast.yaml>
+
+--mips and --mips-sim target 32-bit MIPS, where integers wrap at 32 bits
+rather than 64.";
+
+/// Removes `flag` and the value after it from `args`, parsing the value.
+fn take_option<T: FromStr>(args: &mut Vec<String>, flag: &str) -> Option<T> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    match args.get(index + 1).and_then(|value| value.parse().ok()) {
+        Some(value) => {
+            args.drain(index..index + 2);
+            Some(value)
+        }
+        None => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
+
 fn main() {
-    println!("Hello, world!");
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let allocator = match args.iter().position(|arg| arg == "--linear-scan") {
+        Some(index) => {
+            args.remove(index);
+            Allocator::LinearScan
+        }
+        None => Allocator::Coloring,
+    };
+    let limits = Limits {
+        steps: take_option(&mut args, "--max-steps"),
+        depth: Some(take_option(&mut args, "--max-depth").unwrap_or(DEFAULT_DEPTH)),
+        heap: take_option(&mut args, "--max-heap"),
+        output: take_option(&mut args, "--max-output"),
+        time: take_option(&mut args, "--timeout").map(|secs: f64| {
+            Duration::try_from_secs_f64(secs).unwrap_or_else(|_| {
+                eprintln!("{}", USAGE);
+                process::exit(2);
+            })
+        }),
+    };
+    let exe = match args.iter().position(|arg| arg == "-o") {
+        Some(index) if index + 1 < args.len() => {
+            args.remove(index);
+            Some(args.remove(index))
+        }
+        Some(_) => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+        None => None,
+    };
+    let (emit, path) = match args.as_slice() {
+        [path] if exe.is_some() => ("-o", path),
+        [path] => ("--absyn", path),
+        [emit, path] => (emit.as_str(), path),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    let input = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("{}: {}", path, err);
+        process::exit(1);
+    });
+    let ast = if path.ends_with(".json") {
+        Ast::from_json_str(&input)
+    } else if path.ends_with(".yaml") || path.ends_with(".yml") {
+        Ast::from_yaml_str(&input)
+    } else {
+        compile_to_arena(&input).map_err(|err| format!("syntax error: {:?}", err))
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("{}: {}", path, err);
+        process::exit(1);
+    });
+    let output = match emit {
+        "--absyn" => Ok(ast.to_absyn_string()),
+        "--json" => ast.to_json_string(),
+        "--yaml" => ast.to_yaml_string(),
+        "--dot" => Ok(ast.to_dot()),
+        "--dot-collapsed" => Ok(ast.to_dot_with(&DotOptions {
+            collapse_wrappers: true,
+        })),
+        "--ir" => trans_ast(&ast).map(|program| {
+            translate::<X86_64Frame>(&program)
+                .iter()
+                .map(|frag| frag.to_tree_string())
+                .collect()
+        }),
+        "--canon" => trans_ast(&ast).map(|program| {
+            translate::<X86_64Frame>(&program)
+                .into_iter()
+                .map(|frag| match frag {
+                    Frag::Proc { body, frame } => canonicalize(body)
+                        .iter()
+                        .fold(format!("PROC {}:\n", frame.name()), |out, stm| {
+                            out + &stm.to_tree_string()
+                        }),
+                    frag => frag.to_tree_string(),
+                })
+                .collect()
+        }),
+        "--liveness" => trans_ast(&ast).map(|program| {
+            translate::<X86_64Frame>(&program)
+                .into_iter()
+                .filter_map(|frag| match frag {
+                    Frag::Proc { body, mut frame } => {
+                        let (instrs, _) = instructions(&mut frame, body);
+                        Some(format!(
+                            "PROC {}:\n{}",
+                            frame.name(),
+                            liveness::dump(&instrs, X86_64Frame::register_name)
+                        ))
+                    }
+                    Frag::String { .. } => None,
+                })
+                .collect()
+        }),
+        "--asm" => trans_ast(&ast)
+            .map(|program| compile_with(translate::<X86_64Frame>(&program), allocator)),
+        "--mips" => trans_ast(&ast).map(|program| {
+            compile_with(translate::<MipsFrame>(&program), allocator) + MIPS_RUNTIME
+        }),
+        "--mips-sim" => trans_ast(&ast).and_then(|program| {
+            let asm = compile_with(translate::<MipsFrame>(&program), allocator);
+            match mips::run_with(&asm, limits, &mut io::stdin(), &mut io::stdout())? {
+                0 => Ok(String::new()),
+                code => process::exit(code as i32),
+            }
+        }),
+        "--riscv64" => trans_ast(&ast)
+            .map(|program| compile_with(translate::<Riscv64Frame>(&program), allocator)),
+        "--c" => trans_ast(&ast).and_then(|program| match &exe {
+            Some(exe) => {
+                link_c(&compile_c(&program), Path::new(exe), &find_runtime()?)?;
+                Ok(String::new())
+            }
+            None => Ok(compile_c(&program)),
+        }),
+        "--llvm" => trans_ast(&ast).and_then(|program| match &exe {
+            Some(exe) => {
+                link_llvm(&compile_llvm(&program), Path::new(exe), &find_runtime()?)?;
+                Ok(String::new())
+            }
+            None => Ok(compile_llvm(&program)),
+        }),
+        "--interp" => trans_ast(&ast).and_then(|program| {
+            match interp::run_with(&program, limits, &mut io::stdin(), &mut io::stdout())? {
+                0 => Ok(String::new()),
+                code => process::exit(code as i32),
+            }
+        }),
+        "--interp-ir" | "--interp-canon" => trans_ast(&ast).and_then(|program| {
+            let frags = translate::<X86_64Frame>(&program);
+            let canon = emit == "--interp-canon";
+            match ir::run_with(frags, canon, limits, &mut io::stdin(), &mut io::stdout())? {
+                0 => Ok(String::new()),
+                code => process::exit(code as i32),
+            }
+        }),
+        "--bytecode" => trans_ast(&ast).map(|program| disassemble(&compile::compile(&program))),
+        "--vm" => trans_ast(&ast).and_then(|program| {
+            let module = compile::compile(&program);
+            match vm::run_with(&module, limits, &mut io::stdin(), &mut io::stdout())? {
+                0 => Ok(String::new()),
+                code => process::exit(code as i32),
+            }
+        }),
+        "--wat" => trans_ast(&ast).map(|program| to_wat(&compile_wasm(&program))),
+        "--wasm" => trans_ast(&ast).and_then(|program| {
+            let binary = compile_wasm(&program).encode();
+            io::stdout()
+                .write_all(&binary)
+                .map_err(|err| err.to_string())?;
+            Ok(String::new())
+        }),
+        "-o" => trans_ast(&ast).and_then(|program| {
+            let asm = compile_with(translate::<X86_64Frame>(&program), allocator);
+            link(&asm, Path::new(exe.as_deref().unwrap()), &find_runtime()?)?;
+            Ok(String::new())
+        }),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    match output {
+        Ok(output) => print!("{}", output),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,1089 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::lexer::lexer::ValueInfo;
+use crate::parser::ast::*;
+
+/// Index of a node inside an [`Ast`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub(crate) fn new(index: usize) -> NodeId {
+        NodeId(index as u32)
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// One kind per variant of the types in `parser::ast`, named `TypeVariant`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NodeKind {
+    Pending,
+    DecsEmpty,
+    Decs,
+    DecType,
+    DecVar,
+    DecFun,
+    TypeDec,
+    TyId,
+    TyFields,
+    TyArray,
+    TyFieldsEmpty,
+    TyFieldsSome,
+    SomeTyFieldsSome,
+    SomeTyFieldsList,
+    VarDecDefaultInit,
+    VarDecTypeInit,
+    FunDecProc,
+    FunDecFunc,
+    ExpSeqEmpty,
+    ExpSeqSome,
+    SomeExpSeqSome,
+    SomeExpSeqList,
+    ExpMatch,
+    ExpUnmatch,
+    ExpError,
+    MatchIfThenElse,
+    MatchWhile,
+    MatchFor,
+    MatchAssign,
+    MatchSlice,
+    MatchBreak,
+    UnmatchIfThen,
+    UnmatchIfThenElse,
+    UnmatchWhile,
+    UnmatchFor,
+    LValueId,
+    LValueRefer,
+    ReferField,
+    ReferReferField,
+    ReferArray,
+    ReferReferArray,
+    SliceArray,
+    SliceRecord,
+    SliceSheet,
+    SheetOr,
+    SheetPiece,
+    PieceAnd,
+    PieceBit,
+    BitEq,
+    BitNeq,
+    BitLt,
+    BitLe,
+    BitGt,
+    BitGe,
+    BitItem,
+    ItemPlus,
+    ItemMinus,
+    ItemTerm,
+    TermTimes,
+    TermDivide,
+    TermFactor,
+    FactorMMeta,
+    FactorMeta,
+    MetaInt,
+    MetaString,
+    MetaNil,
+    MetaId,
+    MetaRefer,
+    MetaCapSeq,
+    MetaCall,
+    MetaLet,
+    ArgsListEmpty,
+    ArgsListSome,
+    SomeArgsListSome,
+    SomeArgsListList,
+    RecListEmpty,
+    RecListSome,
+    SomeRecListSome,
+    SomeRecListList,
+}
+
+/// The type in `parser::ast` a node stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sort {
+    Decs,
+    Dec,
+    TypeDec,
+    Ty,
+    TyFields,
+    SomeTyFields,
+    VarDec,
+    FunDec,
+    ExpSeq,
+    SomeExpSeq,
+    Exp,
+    Match,
+    Unmatch,
+    LValue,
+    Refer,
+    Slice,
+    Sheet,
+    Piece,
+    Bit,
+    Item,
+    Term,
+    Factor,
+    Meta,
+    ArgsList,
+    SomeArgsList,
+    RecList,
+    SomeRecList,
+}
+
+/// Which [`Leaf`] a kind of node carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeafShape {
+    None,
+    Id,
+    Ids,
+    Int,
+    Binding,
+    TypedBinding,
+}
+
+impl NodeKind {
+    /// The sort of a node of this kind, its leaf, and the sorts of its
+    /// children in order. `None` for [`NodeKind::Pending`].
+    pub fn shape(self) -> Option<(Sort, LeafShape, &'static [Sort])> {
+        use LeafShape as L;
+        use NodeKind as K;
+        use Sort as S;
+        Some(match self {
+            K::Pending => return None,
+            K::DecsEmpty => (S::Decs, L::None, &[]),
+            K::Decs => (S::Decs, L::None, &[S::Dec, S::Decs]),
+            K::DecType => (S::Dec, L::None, &[S::TypeDec]),
+            K::DecVar => (S::Dec, L::None, &[S::VarDec]),
+            K::DecFun => (S::Dec, L::None, &[S::FunDec]),
+            K::TypeDec => (S::TypeDec, L::Id, &[S::Ty]),
+            K::TyId | K::TyArray => (S::Ty, L::Id, &[]),
+            K::TyFields => (S::Ty, L::None, &[S::TyFields]),
+            K::TyFieldsEmpty => (S::TyFields, L::None, &[]),
+            K::TyFieldsSome => (S::TyFields, L::None, &[S::SomeTyFields]),
+            K::SomeTyFieldsSome => (S::SomeTyFields, L::TypedBinding, &[]),
+            K::SomeTyFieldsList => (S::SomeTyFields, L::TypedBinding, &[S::SomeTyFields]),
+            K::VarDecDefaultInit => (S::VarDec, L::Binding, &[S::Exp]),
+            K::VarDecTypeInit => (S::VarDec, L::TypedBinding, &[S::Exp]),
+            K::FunDecProc => (S::FunDec, L::Id, &[S::TyFields, S::Exp]),
+            K::FunDecFunc => (S::FunDec, L::Ids, &[S::TyFields, S::Exp]),
+            K::ExpSeqEmpty => (S::ExpSeq, L::None, &[]),
+            K::ExpSeqSome => (S::ExpSeq, L::None, &[S::SomeExpSeq]),
+            K::SomeExpSeqSome => (S::SomeExpSeq, L::None, &[S::Exp]),
+            K::SomeExpSeqList => (S::SomeExpSeq, L::None, &[S::Exp, S::SomeExpSeq]),
+            K::ExpMatch => (S::Exp, L::None, &[S::Match]),
+            K::ExpUnmatch => (S::Exp, L::None, &[S::Unmatch]),
+            K::ExpError => (S::Exp, L::None, &[]),
+            K::MatchIfThenElse => (S::Match, L::None, &[S::Exp, S::Match, S::Match]),
+            K::MatchWhile => (S::Match, L::None, &[S::Exp, S::Match]),
+            K::MatchFor => (S::Match, L::Binding, &[S::Exp, S::Exp, S::Match]),
+            K::MatchAssign => (S::Match, L::None, &[S::LValue, S::Slice]),
+            K::MatchSlice => (S::Match, L::None, &[S::Slice]),
+            K::MatchBreak => (S::Match, L::None, &[]),
+            K::UnmatchIfThen => (S::Unmatch, L::None, &[S::Exp, S::Exp]),
+            K::UnmatchIfThenElse => (S::Unmatch, L::None, &[S::Exp, S::Match, S::Unmatch]),
+            K::UnmatchWhile => (S::Unmatch, L::None, &[S::Exp, S::Unmatch]),
+            K::UnmatchFor => (S::Unmatch, L::Binding, &[S::Exp, S::Exp, S::Unmatch]),
+            K::LValueId => (S::LValue, L::Id, &[]),
+            K::LValueRefer => (S::LValue, L::None, &[S::Refer]),
+            K::ReferField => (S::Refer, L::Ids, &[]),
+            K::ReferReferField => (S::Refer, L::Id, &[S::Refer]),
+            K::ReferArray => (S::Refer, L::Id, &[S::Exp]),
+            K::ReferReferArray => (S::Refer, L::None, &[S::Refer, S::Exp]),
+            K::SliceArray => (S::Slice, L::Id, &[S::Exp, S::Slice]),
+            K::SliceRecord => (S::Slice, L::Id, &[S::RecList]),
+            K::SliceSheet => (S::Slice, L::None, &[S::Sheet]),
+            K::SheetOr => (S::Sheet, L::None, &[S::Sheet, S::Piece]),
+            K::SheetPiece => (S::Sheet, L::None, &[S::Piece]),
+            K::PieceAnd => (S::Piece, L::None, &[S::Piece, S::Bit]),
+            K::PieceBit => (S::Piece, L::None, &[S::Bit]),
+            K::BitEq | K::BitNeq | K::BitLt | K::BitLe | K::BitGt | K::BitGe => {
+                (S::Bit, L::None, &[S::Item, S::Item])
+            }
+            K::BitItem => (S::Bit, L::None, &[S::Item]),
+            K::ItemPlus | K::ItemMinus => (S::Item, L::None, &[S::Item, S::Term]),
+            K::ItemTerm => (S::Item, L::None, &[S::Term]),
+            K::TermTimes | K::TermDivide => (S::Term, L::None, &[S::Term, S::Factor]),
+            K::TermFactor => (S::Term, L::None, &[S::Factor]),
+            K::FactorMMeta | K::FactorMeta => (S::Factor, L::None, &[S::Meta]),
+            K::MetaInt => (S::Meta, L::Int, &[]),
+            K::MetaString | K::MetaId => (S::Meta, L::Id, &[]),
+            K::MetaNil => (S::Meta, L::None, &[]),
+            K::MetaRefer => (S::Meta, L::None, &[S::Refer]),
+            K::MetaCapSeq => (S::Meta, L::None, &[S::ExpSeq]),
+            K::MetaCall => (S::Meta, L::Id, &[S::ArgsList]),
+            K::MetaLet => (S::Meta, L::None, &[S::Decs, S::ExpSeq]),
+            K::ArgsListEmpty => (S::ArgsList, L::None, &[]),
+            K::ArgsListSome => (S::ArgsList, L::None, &[S::SomeArgsList]),
+            K::SomeArgsListSome => (S::SomeArgsList, L::None, &[S::Exp]),
+            K::SomeArgsListList => (S::SomeArgsList, L::None, &[S::Exp, S::SomeArgsList]),
+            K::RecListEmpty => (S::RecList, L::None, &[]),
+            K::RecListSome => (S::RecList, L::None, &[S::SomeRecList]),
+            K::SomeRecListSome => (S::SomeRecList, L::Id, &[S::Exp]),
+            K::SomeRecListList => (S::SomeRecList, L::Id, &[S::Exp, S::SomeRecList]),
+        })
+    }
+}
+
+/// The tokens a node carries besides its children, in source order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum Leaf {
+    #[default]
+    None,
+    Id(ValueInfo<String>),
+    Ids(ValueInfo<String>, ValueInfo<String>),
+    Int(ValueInfo<u64>),
+    /// A variable, parameter or loop counter, with its escape flag.
+    Binding {
+        id: ValueInfo<String>,
+        type_id: Option<ValueInfo<String>>,
+        escape: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub leaf: Leaf,
+    pub parent: Option<NodeId>,
+    children: (u32, u32),
+}
+
+/// Flat, index-based syntax tree; the parser builds it directly.
+///
+/// Nodes live in one vector and refer to their children by [`NodeId`], so
+/// building, walking, serializing and dropping a tree never recurses, no
+/// matter how long a sequence or how deep a nesting the program has. Nodes
+/// are stored in the order the parser reduces them: children always come
+/// before their parent, and the root is the last node.
+#[derive(Debug, Clone, Default)]
+pub struct Ast {
+    nodes: Vec<Node>,
+    edges: Vec<NodeId>,
+}
+
+enum Pending {
+    Decs(Decs),
+    Dec(Dec),
+    TypeDec(TypeDec),
+    Ty(Ty),
+    TyFields(TyFields),
+    SomeTyFields(SomeTyFields),
+    VarDec(VarDec),
+    FunDec(FunDec),
+    ExpSeq(ExpSeq),
+    SomeExpSeq(SomeExpSeq),
+    Exp(Exp),
+    Match(Match),
+    Unmatch(Unmatch),
+    LValue(LValue),
+    Refer(Refer),
+    Slice(Slice),
+    Sheet(Sheet),
+    Piece(Piece),
+    Bit(Bit),
+    Item(Item),
+    Term(Term),
+    Factor(Factor),
+    Meta(Meta),
+    ArgsList(ArgsList),
+    SomeArgsList(SomeArgsList),
+    RecList(RecList),
+    SomeRecList(SomeRecList),
+}
+
+enum Built {
+    Decs(Box<Decs>),
+    Dec(Box<Dec>),
+    TypeDec(Box<TypeDec>),
+    Ty(Box<Ty>),
+    TyFields(Box<TyFields>),
+    SomeTyFields(Box<SomeTyFields>),
+    VarDec(Box<VarDec>),
+    FunDec(Box<FunDec>),
+    ExpSeq(Box<ExpSeq>),
+    SomeExpSeq(Box<SomeExpSeq>),
+    Exp(Box<Exp>),
+    Match(Box<Match>),
+    Unmatch(Box<Unmatch>),
+    LValue(Box<LValue>),
+    Refer(Box<Refer>),
+    Slice(Box<Slice>),
+    Sheet(Box<Sheet>),
+    Piece(Box<Piece>),
+    Bit(Box<Bit>),
+    Item(Box<Item>),
+    Term(Box<Term>),
+    Factor(Box<Factor>),
+    Meta(Box<Meta>),
+    ArgsList(Box<ArgsList>),
+    SomeArgsList(Box<SomeArgsList>),
+    RecList(Box<RecList>),
+    SomeRecList(Box<SomeRecList>),
+}
+
+macro_rules! take {
+    ($built:expr, $variant:ident) => {
+        match $built {
+            Built::$variant(node) => node,
+            _ => unreachable!(concat!("expected ", stringify!($variant))),
+        }
+    };
+}
+
+impl Ast {
+    /// Adds a node above `children`, which must not have a parent yet.
+    pub fn push(&mut self, kind: NodeKind, leaf: Leaf, children: &[NodeId]) -> NodeId {
+        let id = NodeId(self.nodes.len() as u32);
+        let start = self.edges.len() as u32;
+        for child in children {
+            debug_assert!(self.nodes[child.index()].parent.is_none());
+            self.nodes[child.index()].parent = Some(id);
+        }
+        self.edges.extend_from_slice(children);
+        self.nodes.push(Node {
+            kind,
+            leaf,
+            parent: None,
+            children: (start, children.len() as u32),
+        });
+        id
+    }
+
+    /// Moves a boxed tree into an arena. The boxes are taken apart one at a
+    /// time with an explicit work list, so this is also the safe way to free
+    /// a tree too deep for the recursive `Drop` of `Box<Exp>`.
+    #[allow(clippy::boxed_local)]
+    pub fn from_exp(exp: Box<Exp>) -> Ast {
+        // Lay the tree out top-down, last child first, then reverse it so
+        // children come first in the order the parser would have made them.
+        let mut ast = Ast::default();
+        let mut work = vec![(None, None, Pending::Exp(*exp))];
+        while let Some((parent, edge, pending)) = work.pop() {
+            let id = ast.alloc(parent);
+            if let Some(edge) = edge {
+                ast.edges[edge] = id;
+            }
+            let (kind, leaf, children) = lower(pending);
+            let start = ast.edges.len() as u32;
+            for child in children {
+                work.push((Some(id), Some(ast.edges.len()), child));
+                ast.edges.push(id);
+            }
+            let node = &mut ast.nodes[id.index()];
+            node.kind = kind;
+            node.leaf = leaf;
+            node.children = (start, ast.edges.len() as u32 - start);
+        }
+        let last = ast.nodes.len() as u32 - 1;
+        let flip = |id: NodeId| NodeId(last - id.0);
+        ast.nodes.reverse();
+        for node in &mut ast.nodes {
+            node.parent = node.parent.map(flip);
+        }
+        for edge in &mut ast.edges {
+            *edge = flip(*edge);
+        }
+        ast
+    }
+
+    fn alloc(&mut self, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len() as u32);
+        self.nodes.push(Node {
+            kind: NodeKind::Pending,
+            leaf: Leaf::None,
+            parent,
+            children: (0, 0),
+        });
+        id
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(self.nodes.len() as u32 - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.index()]
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        let (start, len) = self.nodes[id.index()].children;
+        let range: Range<usize> = start as usize..(start + len) as usize;
+        &self.edges[range]
+    }
+
+    /// Depth-first, left-to-right walk starting at `id`.
+    pub fn preorder(&self, id: NodeId) -> Preorder<'_> {
+        Preorder {
+            ast: self,
+            stack: vec![id],
+        }
+    }
+
+    /// Length of the longest root-to-leaf path, counted in nodes.
+    pub fn depth(&self) -> usize {
+        let mut depth = vec![0usize; self.nodes.len()];
+        let mut max = 0;
+        for (index, node) in self.nodes.iter().enumerate().rev() {
+            depth[index] = match node.parent {
+                Some(parent) => depth[parent.index()] + 1,
+                None => 1,
+            };
+            max = max.max(depth[index]);
+        }
+        max
+    }
+
+    /// Rebuilds the boxed tree, bottom-up and without recursion. The result is
+    /// subject to the usual limits of the boxed representation when dropped.
+    pub fn to_exp(&self) -> Box<Exp> {
+        let mut built: Vec<Option<Built>> = Vec::with_capacity(self.nodes.len());
+        built.resize_with(self.nodes.len(), || None);
+        for index in 0..self.nodes.len() {
+            let id = NodeId(index as u32);
+            let mut children = self
+                .children(id)
+                .iter()
+                .map(|child| built[child.index()].take().unwrap());
+            let node = &self.nodes[index];
+            built[index] = Some(raise(node, &mut children));
+        }
+        take!(built.pop().unwrap().unwrap(), Exp)
+    }
+
+    /// Runs `f` on the boxed form of the tree, which is then freed through a
+    /// fresh arena so that dropping it cannot overflow the stack.
+    pub fn with_exp<R>(&self, f: impl FnOnce(&Exp) -> R) -> R {
+        let exp = self.to_exp();
+        let result = f(&exp);
+        Ast::from_exp(exp);
+        result
+    }
+}
+
+pub struct Preorder<'a> {
+    ast: &'a Ast,
+    stack: Vec<NodeId>,
+}
+
+impl<'a> Iterator for Preorder<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.stack.pop()?;
+        self.stack
+            .extend(self.ast.children(id).iter().rev().copied());
+        Some(id)
+    }
+}
+
+fn lower(pending: Pending) -> (NodeKind, Leaf, Vec<Pending>) {
+    use NodeKind as K;
+    use Pending as P;
+    match pending {
+        P::Decs(node) => match node {
+            Decs::Empty => (K::DecsEmpty, Leaf::None, vec![]),
+            Decs::Decs(dec, tail) => (K::Decs, Leaf::None, vec![P::Dec(*dec), P::Decs(*tail)]),
+        },
+        P::Dec(node) => match node {
+            Dec::TypeDec(dec) => (K::DecType, Leaf::None, vec![P::TypeDec(*dec)]),
+            Dec::VarDec(dec) => (K::DecVar, Leaf::None, vec![P::VarDec(*dec)]),
+            Dec::FunDec(dec) => (K::DecFun, Leaf::None, vec![P::FunDec(*dec)]),
+        },
+        P::TypeDec(TypeDec::Dec(id, ty)) => (K::TypeDec, Leaf::Id(id), vec![P::Ty(*ty)]),
+        P::Ty(node) => match node {
+            Ty::Id(id) => (K::TyId, Leaf::Id(id), vec![]),
+            Ty::Fields(fields) => (K::TyFields, Leaf::None, vec![P::TyFields(*fields)]),
+            Ty::Array(id) => (K::TyArray, Leaf::Id(id), vec![]),
+        },
+        P::TyFields(node) => match node {
+            TyFields::Empty => (K::TyFieldsEmpty, Leaf::None, vec![]),
+            TyFields::Some(fields) => (K::TyFieldsSome, Leaf::None, vec![P::SomeTyFields(*fields)]),
+        },
+        P::SomeTyFields(node) => match node {
+            SomeTyFields::Some {
+                id,
+                type_id,
+                escape,
+            } => (
+                K::SomeTyFieldsSome,
+                Leaf::binding(id, Some(type_id), escape),
+                vec![],
+            ),
+            SomeTyFields::List {
+                id,
+                type_id,
+                escape,
+                tail,
+            } => (
+                K::SomeTyFieldsList,
+                Leaf::binding(id, Some(type_id), escape),
+                vec![P::SomeTyFields(*tail)],
+            ),
+        },
+        P::VarDec(node) => match node {
+            VarDec::DefaultInit(id, exp, escape) => (
+                K::VarDecDefaultInit,
+                Leaf::binding(id, None, escape),
+                vec![P::Exp(*exp)],
+            ),
+            VarDec::TypeInit {
+                var_id,
+                type_id,
+                exp,
+                escape,
+            } => (
+                K::VarDecTypeInit,
+                Leaf::binding(var_id, Some(type_id), escape),
+                vec![P::Exp(*exp)],
+            ),
+        },
+        P::FunDec(node) => match node {
+            FunDec::Proc(id, fields, exp) => (
+                K::FunDecProc,
+                Leaf::Id(id),
+                vec![P::TyFields(*fields), P::Exp(*exp)],
+            ),
+            FunDec::Func {
+                id,
+                fields,
+                type_id,
+                exp,
+            } => (
+                K::FunDecFunc,
+                Leaf::Ids(id, type_id),
+                vec![P::TyFields(*fields), P::Exp(*exp)],
+            ),
+        },
+        P::ExpSeq(node) => match node {
+            ExpSeq::Empty => (K::ExpSeqEmpty, Leaf::None, vec![]),
+            ExpSeq::Some(seq) => (K::ExpSeqSome, Leaf::None, vec![P::SomeExpSeq(*seq)]),
+        },
+        P::SomeExpSeq(node) => match node {
+            SomeExpSeq::Some(exp) => (K::SomeExpSeqSome, Leaf::None, vec![P::Exp(*exp)]),
+            SomeExpSeq::List(exp, tail) => (
+                K::SomeExpSeqList,
+                Leaf::None,
+                vec![P::Exp(*exp), P::SomeExpSeq(*tail)],
+            ),
+        },
+        P::Exp(node) => match node {
+            Exp::Match(m) => (K::ExpMatch, Leaf::None, vec![P::Match(*m)]),
+            Exp::Unmatch(u) => (K::ExpUnmatch, Leaf::None, vec![P::Unmatch(*u)]),
+            Exp::Error => (K::ExpError, Leaf::None, vec![]),
+        },
+        P::Match(node) => match node {
+            Match::IfThenElse {
+                if_exp,
+                then_exp,
+                else_exp,
+            } => (
+                K::MatchIfThenElse,
+                Leaf::None,
+                vec![P::Exp(*if_exp), P::Match(*then_exp), P::Match(*else_exp)],
+            ),
+            Match::While { cond, body } => (
+                K::MatchWhile,
+                Leaf::None,
+                vec![P::Exp(*cond), P::Match(*body)],
+            ),
+            Match::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            } => (
+                K::MatchFor,
+                Leaf::binding(id, None, escape),
+                vec![P::Exp(*start), P::Exp(*end), P::Match(*body)],
+            ),
+            Match::Assign(lvalue, slice) => (
+                K::MatchAssign,
+                Leaf::None,
+                vec![P::LValue(*lvalue), P::Slice(*slice)],
+            ),
+            Match::Slice(slice) => (K::MatchSlice, Leaf::None, vec![P::Slice(*slice)]),
+            Match::Break => (K::MatchBreak, Leaf::None, vec![]),
+        },
+        P::Unmatch(node) => match node {
+            Unmatch::IfThen { if_exp, then_exp } => (
+                K::UnmatchIfThen,
+                Leaf::None,
+                vec![P::Exp(*if_exp), P::Exp(*then_exp)],
+            ),
+            Unmatch::IfThenElse(if_exp, then_exp, else_exp) => (
+                K::UnmatchIfThenElse,
+                Leaf::None,
+                vec![P::Exp(*if_exp), P::Match(*then_exp), P::Unmatch(*else_exp)],
+            ),
+            Unmatch::While { cond, body } => (
+                K::UnmatchWhile,
+                Leaf::None,
+                vec![P::Exp(*cond), P::Unmatch(*body)],
+            ),
+            Unmatch::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            } => (
+                K::UnmatchFor,
+                Leaf::binding(id, None, escape),
+                vec![P::Exp(*start), P::Exp(*end), P::Unmatch(*body)],
+            ),
+        },
+        P::LValue(node) => match node {
+            LValue::Id(id) => (K::LValueId, Leaf::Id(id), vec![]),
+            LValue::Refer(refer) => (K::LValueRefer, Leaf::None, vec![P::Refer(*refer)]),
+        },
+        P::Refer(node) => match node {
+            Refer::Field(id, field) => (K::ReferField, Leaf::Ids(id, field), vec![]),
+            Refer::ReferField(refer, field) => {
+                (K::ReferReferField, Leaf::Id(field), vec![P::Refer(*refer)])
+            }
+            Refer::Array(id, index) => (K::ReferArray, Leaf::Id(id), vec![P::Exp(*index)]),
+            Refer::ReferArray(refer, index) => (
+                K::ReferReferArray,
+                Leaf::None,
+                vec![P::Refer(*refer), P::Exp(*index)],
+            ),
+        },
+        P::Slice(node) => match node {
+            Slice::Array { type_id, len, init } => (
+                K::SliceArray,
+                Leaf::Id(type_id),
+                vec![P::Exp(*len), P::Slice(*init)],
+            ),
+            Slice::Record(type_id, rec_list) => (
+                K::SliceRecord,
+                Leaf::Id(type_id),
+                vec![P::RecList(*rec_list)],
+            ),
+            Slice::Sheet(sheet) => (K::SliceSheet, Leaf::None, vec![P::Sheet(*sheet)]),
+        },
+        P::Sheet(node) => match node {
+            Sheet::Or(sheet, piece) => (
+                K::SheetOr,
+                Leaf::None,
+                vec![P::Sheet(*sheet), P::Piece(*piece)],
+            ),
+            Sheet::Piece(piece) => (K::SheetPiece, Leaf::None, vec![P::Piece(*piece)]),
+        },
+        P::Piece(node) => match node {
+            Piece::And(piece, bit) => (
+                K::PieceAnd,
+                Leaf::None,
+                vec![P::Piece(*piece), P::Bit(*bit)],
+            ),
+            Piece::Bit(bit) => (K::PieceBit, Leaf::None, vec![P::Bit(*bit)]),
+        },
+        P::Bit(node) => {
+            let (kind, left, right) = match node {
+                Bit::Eq(left, right) => (K::BitEq, left, right),
+                Bit::Neq(left, right) => (K::BitNeq, left, right),
+                Bit::Lt(left, right) => (K::BitLt, left, right),
+                Bit::Le(left, right) => (K::BitLe, left, right),
+                Bit::Gt(left, right) => (K::BitGt, left, right),
+                Bit::Ge(left, right) => (K::BitGe, left, right),
+                Bit::Item(item) => return (K::BitItem, Leaf::None, vec![P::Item(*item)]),
+            };
+            (kind, Leaf::None, vec![P::Item(*left), P::Item(*right)])
+        }
+        P::Item(node) => match node {
+            Item::Plus(item, term) => (
+                K::ItemPlus,
+                Leaf::None,
+                vec![P::Item(*item), P::Term(*term)],
+            ),
+            Item::Minus(item, term) => (
+                K::ItemMinus,
+                Leaf::None,
+                vec![P::Item(*item), P::Term(*term)],
+            ),
+            Item::Term(term) => (K::ItemTerm, Leaf::None, vec![P::Term(*term)]),
+        },
+        P::Term(node) => match node {
+            Term::Times(term, factor) => (
+                K::TermTimes,
+                Leaf::None,
+                vec![P::Term(*term), P::Factor(*factor)],
+            ),
+            Term::Divide(term, factor) => (
+                K::TermDivide,
+                Leaf::None,
+                vec![P::Term(*term), P::Factor(*factor)],
+            ),
+            Term::Factor(factor) => (K::TermFactor, Leaf::None, vec![P::Factor(*factor)]),
+        },
+        P::Factor(node) => match node {
+            Factor::MMeta(meta) => (K::FactorMMeta, Leaf::None, vec![P::Meta(*meta)]),
+            Factor::Meta(meta) => (K::FactorMeta, Leaf::None, vec![P::Meta(*meta)]),
+        },
+        P::Meta(node) => match node {
+            Meta::Int(i) => (K::MetaInt, Leaf::Int(i), vec![]),
+            Meta::String(s) => (K::MetaString, Leaf::Id(s), vec![]),
+            Meta::Nil => (K::MetaNil, Leaf::None, vec![]),
+            Meta::Id(id) => (K::MetaId, Leaf::Id(id), vec![]),
+            Meta::Refer(refer) => (K::MetaRefer, Leaf::None, vec![P::Refer(*refer)]),
+            Meta::CapSeq(seq) => (K::MetaCapSeq, Leaf::None, vec![P::ExpSeq(*seq)]),
+            Meta::Call(id, args) => (K::MetaCall, Leaf::Id(id), vec![P::ArgsList(*args)]),
+            Meta::Let(decs, seq) => (
+                K::MetaLet,
+                Leaf::None,
+                vec![P::Decs(*decs), P::ExpSeq(*seq)],
+            ),
+        },
+        P::ArgsList(node) => match node {
+            ArgsList::Empty => (K::ArgsListEmpty, Leaf::None, vec![]),
+            ArgsList::Some(args) => (K::ArgsListSome, Leaf::None, vec![P::SomeArgsList(*args)]),
+        },
+        P::SomeArgsList(node) => match node {
+            SomeArgsList::Some(exp) => (K::SomeArgsListSome, Leaf::None, vec![P::Exp(*exp)]),
+            SomeArgsList::List(exp, tail) => (
+                K::SomeArgsListList,
+                Leaf::None,
+                vec![P::Exp(*exp), P::SomeArgsList(*tail)],
+            ),
+        },
+        P::RecList(node) => match node {
+            RecList::Empty => (K::RecListEmpty, Leaf::None, vec![]),
+            RecList::Some(list) => (K::RecListSome, Leaf::None, vec![P::SomeRecList(*list)]),
+        },
+        P::SomeRecList(node) => match node {
+            SomeRecList::Some(id, exp) => (K::SomeRecListSome, Leaf::Id(id), vec![P::Exp(*exp)]),
+            SomeRecList::List(id, exp, tail) => (
+                K::SomeRecListList,
+                Leaf::Id(id),
+                vec![P::Exp(*exp), P::SomeRecList(*tail)],
+            ),
+        },
+    }
+}
+
+impl Leaf {
+    pub fn is_none(&self) -> bool {
+        *self == Leaf::None
+    }
+
+    pub fn shape(&self) -> LeafShape {
+        match self {
+            Leaf::None => LeafShape::None,
+            Leaf::Id(_) => LeafShape::Id,
+            Leaf::Ids(_, _) => LeafShape::Ids,
+            Leaf::Int(_) => LeafShape::Int,
+            Leaf::Binding { type_id: None, .. } => LeafShape::Binding,
+            Leaf::Binding {
+                type_id: Some(_), ..
+            } => LeafShape::TypedBinding,
+        }
+    }
+
+    fn binding(id: ValueInfo<String>, type_id: Option<ValueInfo<String>>, escape: Escape) -> Leaf {
+        Leaf::Binding {
+            id,
+            type_id,
+            escape: escape.get(),
+        }
+    }
+}
+
+fn binding(leaf: &Leaf) -> (ValueInfo<String>, Option<ValueInfo<String>>, Escape) {
+    match leaf {
+        Leaf::Binding {
+            id,
+            type_id,
+            escape,
+        } => (id.clone(), type_id.clone(), Escape::new(*escape)),
+        _ => unreachable!("expected a binding"),
+    }
+}
+
+fn id(leaf: &Leaf) -> ValueInfo<String> {
+    match leaf {
+        Leaf::Id(id) => id.clone(),
+        _ => unreachable!("expected one identifier"),
+    }
+}
+
+fn ids(leaf: &Leaf) -> (ValueInfo<String>, ValueInfo<String>) {
+    match leaf {
+        Leaf::Ids(first, second) => (first.clone(), second.clone()),
+        _ => unreachable!("expected two identifiers"),
+    }
+}
+
+fn raise(node: &Node, children: &mut impl Iterator<Item = Built>) -> Built {
+    use NodeKind as K;
+    let mut next = || children.next().unwrap();
+    match node.kind {
+        K::Pending => unreachable!("arena node was never filled in"),
+        K::DecsEmpty => Built::Decs(Box::new(Decs::Empty)),
+        K::Decs => {
+            let dec = take!(next(), Dec);
+            Built::Decs(Box::new(Decs::Decs(dec, take!(next(), Decs))))
+        }
+        K::DecType => Built::Dec(Box::new(Dec::TypeDec(take!(next(), TypeDec)))),
+        K::DecVar => Built::Dec(Box::new(Dec::VarDec(take!(next(), VarDec)))),
+        K::DecFun => Built::Dec(Box::new(Dec::FunDec(take!(next(), FunDec)))),
+        K::TypeDec => Built::TypeDec(Box::new(TypeDec::Dec(id(&node.leaf), take!(next(), Ty)))),
+        K::TyId => Built::Ty(Box::new(Ty::Id(id(&node.leaf)))),
+        K::TyFields => Built::Ty(Box::new(Ty::Fields(take!(next(), TyFields)))),
+        K::TyArray => Built::Ty(Box::new(Ty::Array(id(&node.leaf)))),
+        K::TyFieldsEmpty => Built::TyFields(Box::new(TyFields::Empty)),
+        K::TyFieldsSome => Built::TyFields(Box::new(TyFields::Some(take!(next(), SomeTyFields)))),
+        K::SomeTyFieldsSome => {
+            let (id, type_id, escape) = binding(&node.leaf);
+            Built::SomeTyFields(Box::new(SomeTyFields::Some {
+                id,
+                type_id: type_id.unwrap(),
+                escape,
+            }))
+        }
+        K::SomeTyFieldsList => {
+            let (id, type_id, escape) = binding(&node.leaf);
+            let tail = take!(next(), SomeTyFields);
+            Built::SomeTyFields(Box::new(SomeTyFields::List {
+                id,
+                type_id: type_id.unwrap(),
+                escape,
+                tail,
+            }))
+        }
+        K::VarDecDefaultInit => {
+            let (id, _, escape) = binding(&node.leaf);
+            Built::VarDec(Box::new(VarDec::DefaultInit(
+                id,
+                take!(next(), Exp),
+                escape,
+            )))
+        }
+        K::VarDecTypeInit => {
+            let (var_id, type_id, escape) = binding(&node.leaf);
+            let exp = take!(next(), Exp);
+            Built::VarDec(Box::new(VarDec::TypeInit {
+                var_id,
+                type_id: type_id.unwrap(),
+                exp,
+                escape,
+            }))
+        }
+        K::FunDecProc => {
+            let fields = take!(next(), TyFields);
+            let exp = take!(next(), Exp);
+            Built::FunDec(Box::new(FunDec::Proc(id(&node.leaf), fields, exp)))
+        }
+        K::FunDecFunc => {
+            let (id, type_id) = ids(&node.leaf);
+            let fields = take!(next(), TyFields);
+            let exp = take!(next(), Exp);
+            Built::FunDec(Box::new(FunDec::Func {
+                id,
+                fields,
+                type_id,
+                exp,
+            }))
+        }
+        K::ExpSeqEmpty => Built::ExpSeq(Box::new(ExpSeq::Empty)),
+        K::ExpSeqSome => Built::ExpSeq(Box::new(ExpSeq::Some(take!(next(), SomeExpSeq)))),
+        K::SomeExpSeqSome => Built::SomeExpSeq(Box::new(SomeExpSeq::Some(take!(next(), Exp)))),
+        K::SomeExpSeqList => {
+            let exp = take!(next(), Exp);
+            Built::SomeExpSeq(Box::new(SomeExpSeq::List(exp, take!(next(), SomeExpSeq))))
+        }
+        K::ExpMatch => Built::Exp(Box::new(Exp::Match(take!(next(), Match)))),
+        K::ExpUnmatch => Built::Exp(Box::new(Exp::Unmatch(take!(next(), Unmatch)))),
+        K::ExpError => Built::Exp(Box::new(Exp::Error)),
+        K::MatchIfThenElse => {
+            let if_exp = take!(next(), Exp);
+            let then_exp = take!(next(), Match);
+            let else_exp = take!(next(), Match);
+            Built::Match(Box::new(Match::IfThenElse {
+                if_exp,
+                then_exp,
+                else_exp,
+            }))
+        }
+        K::MatchWhile => {
+            let cond = take!(next(), Exp);
+            let body = take!(next(), Match);
+            Built::Match(Box::new(Match::While { cond, body }))
+        }
+        K::MatchFor => {
+            let start = take!(next(), Exp);
+            let end = take!(next(), Exp);
+            let body = take!(next(), Match);
+            let (id, _, escape) = binding(&node.leaf);
+            Built::Match(Box::new(Match::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            }))
+        }
+        K::MatchAssign => {
+            let lvalue = take!(next(), LValue);
+            Built::Match(Box::new(Match::Assign(lvalue, take!(next(), Slice))))
+        }
+        K::MatchSlice => Built::Match(Box::new(Match::Slice(take!(next(), Slice)))),
+        K::MatchBreak => Built::Match(Box::new(Match::Break)),
+        K::UnmatchIfThen => {
+            let if_exp = take!(next(), Exp);
+            let then_exp = take!(next(), Exp);
+            Built::Unmatch(Box::new(Unmatch::IfThen { if_exp, then_exp }))
+        }
+        K::UnmatchIfThenElse => {
+            let if_exp = take!(next(), Exp);
+            let then_exp = take!(next(), Match);
+            let else_exp = take!(next(), Unmatch);
+            Built::Unmatch(Box::new(Unmatch::IfThenElse(if_exp, then_exp, else_exp)))
+        }
+        K::UnmatchWhile => {
+            let cond = take!(next(), Exp);
+            let body = take!(next(), Unmatch);
+            Built::Unmatch(Box::new(Unmatch::While { cond, body }))
+        }
+        K::UnmatchFor => {
+            let start = take!(next(), Exp);
+            let end = take!(next(), Exp);
+            let body = take!(next(), Unmatch);
+            let (id, _, escape) = binding(&node.leaf);
+            Built::Unmatch(Box::new(Unmatch::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            }))
+        }
+        K::LValueId => Built::LValue(Box::new(LValue::Id(id(&node.leaf)))),
+        K::LValueRefer => Built::LValue(Box::new(LValue::Refer(take!(next(), Refer)))),
+        K::ReferField => {
+            let (id, field) = ids(&node.leaf);
+            Built::Refer(Box::new(Refer::Field(id, field)))
+        }
+        K::ReferReferField => Built::Refer(Box::new(Refer::ReferField(
+            take!(next(), Refer),
+            id(&node.leaf),
+        ))),
+        K::ReferArray => Built::Refer(Box::new(Refer::Array(id(&node.leaf), take!(next(), Exp)))),
+        K::ReferReferArray => {
+            let refer = take!(next(), Refer);
+            Built::Refer(Box::new(Refer::ReferArray(refer, take!(next(), Exp))))
+        }
+        K::SliceArray => {
+            let len = take!(next(), Exp);
+            let init = take!(next(), Slice);
+            Built::Slice(Box::new(Slice::Array {
+                type_id: id(&node.leaf),
+                len,
+                init,
+            }))
+        }
+        K::SliceRecord => Built::Slice(Box::new(Slice::Record(
+            id(&node.leaf),
+            take!(next(), RecList),
+        ))),
+        K::SliceSheet => Built::Slice(Box::new(Slice::Sheet(take!(next(), Sheet)))),
+        K::SheetOr => {
+            let sheet = take!(next(), Sheet);
+            Built::Sheet(Box::new(Sheet::Or(sheet, take!(next(), Piece))))
+        }
+        K::SheetPiece => Built::Sheet(Box::new(Sheet::Piece(take!(next(), Piece)))),
+        K::PieceAnd => {
+            let piece = take!(next(), Piece);
+            Built::Piece(Box::new(Piece::And(piece, take!(next(), Bit))))
+        }
+        K::PieceBit => Built::Piece(Box::new(Piece::Bit(take!(next(), Bit)))),
+        K::BitEq | K::BitNeq | K::BitLt | K::BitLe | K::BitGt | K::BitGe => {
+            let left = take!(next(), Item);
+            let right = take!(next(), Item);
+            Built::Bit(Box::new(match node.kind {
+                K::BitEq => Bit::Eq(left, right),
+                K::BitNeq => Bit::Neq(left, right),
+                K::BitLt => Bit::Lt(left, right),
+                K::BitLe => Bit::Le(left, right),
+                K::BitGt => Bit::Gt(left, right),
+                _ => Bit::Ge(left, right),
+            }))
+        }
+        K::BitItem => Built::Bit(Box::new(Bit::Item(take!(next(), Item)))),
+        K::ItemPlus => {
+            let item = take!(next(), Item);
+            Built::Item(Box::new(Item::Plus(item, take!(next(), Term))))
+        }
+        K::ItemMinus => {
+            let item = take!(next(), Item);
+            Built::Item(Box::new(Item::Minus(item, take!(next(), Term))))
+        }
+        K::ItemTerm => Built::Item(Box::new(Item::Term(take!(next(), Term)))),
+        K::TermTimes => {
+            let term = take!(next(), Term);
+            Built::Term(Box::new(Term::Times(term, take!(next(), Factor))))
+        }
+        K::TermDivide => {
+            let term = take!(next(), Term);
+            Built::Term(Box::new(Term::Divide(term, take!(next(), Factor))))
+        }
+        K::TermFactor => Built::Term(Box::new(Term::Factor(take!(next(), Factor)))),
+        K::FactorMMeta => Built::Factor(Box::new(Factor::MMeta(take!(next(), Meta)))),
+        K::FactorMeta => Built::Factor(Box::new(Factor::Meta(take!(next(), Meta)))),
+        K::MetaInt => match &node.leaf {
+            Leaf::Int(i) => Built::Meta(Box::new(Meta::Int(i.clone()))),
+            _ => unreachable!("expected an integer literal"),
+        },
+        K::MetaString => Built::Meta(Box::new(Meta::String(id(&node.leaf)))),
+        K::MetaNil => Built::Meta(Box::new(Meta::Nil)),
+        K::MetaId => Built::Meta(Box::new(Meta::Id(id(&node.leaf)))),
+        K::MetaRefer => Built::Meta(Box::new(Meta::Refer(take!(next(), Refer)))),
+        K::MetaCapSeq => Built::Meta(Box::new(Meta::CapSeq(take!(next(), ExpSeq)))),
+        K::MetaCall => Built::Meta(Box::new(Meta::Call(
+            id(&node.leaf),
+            take!(next(), ArgsList),
+        ))),
+        K::MetaLet => {
+            let decs = take!(next(), Decs);
+            Built::Meta(Box::new(Meta::Let(decs, take!(next(), ExpSeq))))
+        }
+        K::ArgsListEmpty => Built::ArgsList(Box::new(ArgsList::Empty)),
+        K::ArgsListSome => Built::ArgsList(Box::new(ArgsList::Some(take!(next(), SomeArgsList)))),
+        K::SomeArgsListSome => {
+            Built::SomeArgsList(Box::new(SomeArgsList::Some(take!(next(), Exp))))
+        }
+        K::SomeArgsListList => {
+            let exp = take!(next(), Exp);
+            Built::SomeArgsList(Box::new(SomeArgsList::List(
+                exp,
+                take!(next(), SomeArgsList),
+            )))
+        }
+        K::RecListEmpty => Built::RecList(Box::new(RecList::Empty)),
+        K::RecListSome => Built::RecList(Box::new(RecList::Some(take!(next(), SomeRecList)))),
+        K::SomeRecListSome => Built::SomeRecList(Box::new(SomeRecList::Some(
+            id(&node.leaf),
+            take!(next(), Exp),
+        ))),
+        K::SomeRecListList => {
+            let exp = take!(next(), Exp);
+            Built::SomeRecList(Box::new(SomeRecList::List(
+                id(&node.leaf),
+                exp,
+                take!(next(), SomeRecList),
+            )))
+        }
+    }
+}
@@ -1,62 +1,72 @@
+use std::cell::Cell;
+
 use serde::{Deserialize, Serialize};
 
 use crate::lexer::lexer::ValueInfo;
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Whether a variable, parameter or loop counter is used from a function
+/// nested inside the one that declares it. The parser starts every binding
+/// out as escaping; `escape::find_escape` narrows it down.
+pub type Escape = Cell<bool>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Decs {
     Empty,
     Decs(Box<Dec>, Box<Decs>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Dec {
     TypeDec(Box<TypeDec>),
     VarDec(Box<VarDec>),
     FunDec(Box<FunDec>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum TypeDec {
     Dec(ValueInfo<String>, Box<Ty>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Ty {
     Id(ValueInfo<String>),
     Fields(Box<TyFields>),
     Array(ValueInfo<String>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum TyFields {
     Empty,
     Some(Box<SomeTyFields>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SomeTyFields {
     Some {
         id: ValueInfo<String>,
         type_id: ValueInfo<String>,
+        escape: Escape,
     },
     List {
         id: ValueInfo<String>,
         type_id: ValueInfo<String>,
+        escape: Escape,
         tail: Box<SomeTyFields>,
     },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum VarDec {
-    DefaultInit(ValueInfo<String>, Box<Exp>),
+    DefaultInit(ValueInfo<String>, Box<Exp>, Escape),
     TypeInit {
         var_id: ValueInfo<String>,
         type_id: ValueInfo<String>,
         exp: Box<Exp>,
+        escape: Escape,
     },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum FunDec {
     Proc(ValueInfo<String>, Box<TyFields>, Box<Exp>),
     Func {
@@ -67,26 +77,26 @@ pub enum FunDec {
     },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ExpSeq {
     Empty,
     Some(Box<SomeExpSeq>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SomeExpSeq {
     Some(Box<Exp>),
     List(Box<Exp>, Box<SomeExpSeq>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Exp {
     Match(Box<Match>),
     Unmatch(Box<Unmatch>),
     Error,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Match {
     IfThenElse {
         if_exp: Box<Exp>,
@@ -99,6 +109,7 @@ pub enum Match {
     },
     For {
         id: ValueInfo<String>,
+        escape: Escape,
         start: Box<Exp>,
         end: Box<Exp>,
         body: Box<Match>,
@@ -108,7 +119,7 @@ pub enum Match {
     Break,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Unmatch {
     IfThen {
         if_exp: Box<Exp>,
@@ -121,19 +132,20 @@ pub enum Unmatch {
     },
     For {
         id: ValueInfo<String>,
+        escape: Escape,
         start: Box<Exp>,
         end: Box<Exp>,
         body: Box<Unmatch>,
     },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum LValue {
     Id(ValueInfo<String>),
     Refer(Box<Refer>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Refer {
     Field(ValueInfo<String>, ValueInfo<String>),
     ReferField(Box<Refer>, ValueInfo<String>),
@@ -141,7 +153,7 @@ pub enum Refer {
     ReferArray(Box<Refer>, Box<Exp>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Slice {
     Array {
         type_id: ValueInfo<String>,
@@ -152,19 +164,19 @@ pub enum Slice {
     Sheet(Box<Sheet>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Sheet {
     Or(Box<Sheet>, Box<Piece>),
     Piece(Box<Piece>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Piece {
     And(Box<Piece>, Box<Bit>),
     Bit(Box<Bit>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Bit {
     Eq(Box<Item>, Box<Item>),
     Neq(Box<Item>, Box<Item>),
@@ -175,27 +187,27 @@ pub enum Bit {
     Item(Box<Item>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Item {
     Plus(Box<Item>, Box<Term>),
     Minus(Box<Item>, Box<Term>),
     Term(Box<Term>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Term {
     Times(Box<Term>, Box<Factor>),
     Divide(Box<Term>, Box<Factor>),
     Factor(Box<Factor>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Factor {
     MMeta(Box<Meta>),
     Meta(Box<Meta>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Meta {
     Int(ValueInfo<u64>),
     String(ValueInfo<String>),
@@ -207,25 +219,25 @@ pub enum Meta {
     Let(Box<Decs>, Box<ExpSeq>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ArgsList {
     Empty,
     Some(Box<SomeArgsList>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SomeArgsList {
     Some(Box<Exp>),
     List(Box<Exp>, Box<SomeArgsList>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RecList {
     Empty,
     Some(Box<SomeRecList>),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SomeRecList {
     Some(ValueInfo<String>, Box<Exp>),
     List(ValueInfo<String>, Box<Exp>, Box<SomeRecList>),
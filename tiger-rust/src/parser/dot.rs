@@ -0,0 +1,142 @@
+use crate::error::error::PosInfo;
+use crate::lexer::lexer::ValueInfo;
+use crate::parser::arena::{Ast, Leaf, NodeKind};
+use crate::parser::ast::Exp;
+
+#[derive(Debug, Clone, Default)]
+pub struct DotOptions {
+    /// Skip the single-child precedence wrappers (`Exp::Match`, `Slice::Sheet`,
+    /// `Sheet::Piece`, ..., `Factor::Meta`) and link their child directly to
+    /// the wrapper's parent.
+    pub collapse_wrappers: bool,
+}
+
+impl Exp {
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&DotOptions::default())
+    }
+
+    /// See [`Ast::to_dot_with`].
+    pub fn to_dot_with(&self, options: &DotOptions) -> String {
+        Ast::from_exp(Box::new(self.clone())).to_dot_with(options)
+    }
+}
+
+impl Ast {
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&DotOptions::default())
+    }
+
+    /// Renders the tree as a Graphviz `digraph`, one node per AST node labelled
+    /// with its kind, the identifiers or literal it carries, and its position.
+    /// Nodes are numbered in preorder, walking the arena without recursion.
+    pub fn to_dot_with(&self, options: &DotOptions) -> String {
+        let mut out = String::from("digraph ast {\n  node [shape=box, fontname=\"monospace\"];\n");
+        // The graph node each arena node is drawn as: its own, or for a
+        // collapsed wrapper the one of its nearest drawn ancestor.
+        let mut drawn: Vec<Option<usize>> = vec![None; self.len()];
+        let mut next_id = 0;
+        for id in self.preorder(self.root()) {
+            let node = self.node(id);
+            let parent = node.parent.and_then(|parent| drawn[parent.index()]);
+            if options.collapse_wrappers && is_wrapper(node.kind) {
+                drawn[id.index()] = parent;
+                continue;
+            }
+            let mut label = kind_name(node.kind);
+            let (text, position) = detail(&node.leaf);
+            if !text.is_empty() {
+                label.push('\n');
+                label.push_str(&text);
+            }
+            if let Some(position) = position {
+                label.push_str(&format!("\n@{}", position));
+            }
+            out.push_str(&format!("  n{} [label=\"{}\"];\n", next_id, escape(&label)));
+            if let Some(parent) = parent {
+                out.push_str(&format!("  n{} -> n{};\n", parent, next_id));
+            }
+            drawn[id.index()] = Some(next_id);
+            next_id += 1;
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn pos(info: &PosInfo) -> String {
+    format!("{}:{}", info.line, info.col)
+}
+
+fn ids(ids: &[&ValueInfo<String>]) -> (String, Option<String>) {
+    let names: Vec<&str> = ids.iter().map(|id| id.v.as_str()).collect();
+    (names.join(" "), ids.first().map(|id| pos(&id.info)))
+}
+
+/// The identifiers or literal a node carries, and where the first starts.
+fn detail(leaf: &Leaf) -> (String, Option<String>) {
+    match leaf {
+        Leaf::None => (String::new(), None),
+        Leaf::Id(id) => ids(&[id]),
+        Leaf::Ids(first, second) => ids(&[first, second]),
+        Leaf::Int(i) => (i.v.to_string(), Some(pos(&i.info))),
+        Leaf::Binding {
+            id,
+            type_id: Some(type_id),
+            ..
+        } => ids(&[id, type_id]),
+        Leaf::Binding { id, .. } => ids(&[id]),
+    }
+}
+
+/// `Type::Variant` for the variant of `parser::ast` a kind stands for, or
+/// just `Type` when the variant is named after its type.
+fn kind_name(kind: NodeKind) -> String {
+    // The kinds of `Dec` drop the `Dec` its variants end in.
+    match kind {
+        NodeKind::DecType => return String::from("Dec::TypeDec"),
+        NodeKind::DecVar => return String::from("Dec::VarDec"),
+        NodeKind::DecFun => return String::from("Dec::FunDec"),
+        _ => (),
+    }
+    let (sort, _, _) = kind.shape().expect("pending node in a finished tree");
+    let (sort, kind) = (format!("{:?}", sort), format!("{:?}", kind));
+    match &kind[sort.len()..] {
+        "" => sort,
+        variant => format!("{}::{}", sort, variant),
+    }
+}
+
+/// Whether `kind` is a single-child precedence or list wrapper that
+/// [`DotOptions::collapse_wrappers`] leaves out.
+fn is_wrapper(kind: NodeKind) -> bool {
+    use NodeKind as K;
+    matches!(
+        kind,
+        K::DecType
+            | K::DecVar
+            | K::DecFun
+            | K::TyFieldsSome
+            | K::ExpSeqSome
+            | K::ExpMatch
+            | K::ExpUnmatch
+            | K::MatchSlice
+            | K::LValueRefer
+            | K::SliceSheet
+            | K::SheetPiece
+            | K::PieceBit
+            | K::BitItem
+            | K::ItemTerm
+            | K::TermFactor
+            | K::FactorMeta
+            | K::MetaRefer
+            | K::ArgsListSome
+            | K::RecListSome
+    )
+}
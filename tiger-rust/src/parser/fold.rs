@@ -0,0 +1,453 @@
+use crate::parser::ast::*;
+
+/// Owning transformation of the syntax tree.
+///
+/// Each `fold_*` method consumes a node and returns its replacement. The
+/// defaults rebuild the node unchanged from its folded children, so a fold
+/// only needs to override the nodes it rewrites.
+pub trait Fold {
+    fn fold_decs(&mut self, node: Decs) -> Decs {
+        walk_decs(self, node)
+    }
+    fn fold_dec(&mut self, node: Dec) -> Dec {
+        walk_dec(self, node)
+    }
+    fn fold_type_dec(&mut self, node: TypeDec) -> TypeDec {
+        walk_type_dec(self, node)
+    }
+    fn fold_ty(&mut self, node: Ty) -> Ty {
+        walk_ty(self, node)
+    }
+    fn fold_ty_fields(&mut self, node: TyFields) -> TyFields {
+        walk_ty_fields(self, node)
+    }
+    fn fold_some_ty_fields(&mut self, node: SomeTyFields) -> SomeTyFields {
+        walk_some_ty_fields(self, node)
+    }
+    fn fold_var_dec(&mut self, node: VarDec) -> VarDec {
+        walk_var_dec(self, node)
+    }
+    fn fold_fun_dec(&mut self, node: FunDec) -> FunDec {
+        walk_fun_dec(self, node)
+    }
+    fn fold_exp_seq(&mut self, node: ExpSeq) -> ExpSeq {
+        walk_exp_seq(self, node)
+    }
+    fn fold_some_exp_seq(&mut self, node: SomeExpSeq) -> SomeExpSeq {
+        walk_some_exp_seq(self, node)
+    }
+    fn fold_exp(&mut self, node: Exp) -> Exp {
+        walk_exp(self, node)
+    }
+    fn fold_match(&mut self, node: Match) -> Match {
+        walk_match(self, node)
+    }
+    fn fold_unmatch(&mut self, node: Unmatch) -> Unmatch {
+        walk_unmatch(self, node)
+    }
+    fn fold_lvalue(&mut self, node: LValue) -> LValue {
+        walk_lvalue(self, node)
+    }
+    fn fold_refer(&mut self, node: Refer) -> Refer {
+        walk_refer(self, node)
+    }
+    fn fold_slice(&mut self, node: Slice) -> Slice {
+        walk_slice(self, node)
+    }
+    fn fold_sheet(&mut self, node: Sheet) -> Sheet {
+        walk_sheet(self, node)
+    }
+    fn fold_piece(&mut self, node: Piece) -> Piece {
+        walk_piece(self, node)
+    }
+    fn fold_bit(&mut self, node: Bit) -> Bit {
+        walk_bit(self, node)
+    }
+    fn fold_item(&mut self, node: Item) -> Item {
+        walk_item(self, node)
+    }
+    fn fold_term(&mut self, node: Term) -> Term {
+        walk_term(self, node)
+    }
+    fn fold_factor(&mut self, node: Factor) -> Factor {
+        walk_factor(self, node)
+    }
+    fn fold_meta(&mut self, node: Meta) -> Meta {
+        walk_meta(self, node)
+    }
+    fn fold_args_list(&mut self, node: ArgsList) -> ArgsList {
+        walk_args_list(self, node)
+    }
+    fn fold_some_args_list(&mut self, node: SomeArgsList) -> SomeArgsList {
+        walk_some_args_list(self, node)
+    }
+    fn fold_rec_list(&mut self, node: RecList) -> RecList {
+        walk_rec_list(self, node)
+    }
+    fn fold_some_rec_list(&mut self, node: SomeRecList) -> SomeRecList {
+        walk_some_rec_list(self, node)
+    }
+}
+
+pub fn walk_decs<F: Fold + ?Sized>(f: &mut F, node: Decs) -> Decs {
+    match node {
+        Decs::Empty => Decs::Empty,
+        Decs::Decs(dec, tail) => {
+            Decs::Decs(Box::new(f.fold_dec(*dec)), Box::new(f.fold_decs(*tail)))
+        }
+    }
+}
+
+pub fn walk_dec<F: Fold + ?Sized>(f: &mut F, node: Dec) -> Dec {
+    match node {
+        Dec::TypeDec(dec) => Dec::TypeDec(Box::new(f.fold_type_dec(*dec))),
+        Dec::VarDec(dec) => Dec::VarDec(Box::new(f.fold_var_dec(*dec))),
+        Dec::FunDec(dec) => Dec::FunDec(Box::new(f.fold_fun_dec(*dec))),
+    }
+}
+
+pub fn walk_type_dec<F: Fold + ?Sized>(f: &mut F, node: TypeDec) -> TypeDec {
+    match node {
+        TypeDec::Dec(id, ty) => TypeDec::Dec(id, Box::new(f.fold_ty(*ty))),
+    }
+}
+
+pub fn walk_ty<F: Fold + ?Sized>(f: &mut F, node: Ty) -> Ty {
+    match node {
+        Ty::Id(id) => Ty::Id(id),
+        Ty::Fields(fields) => Ty::Fields(Box::new(f.fold_ty_fields(*fields))),
+        Ty::Array(id) => Ty::Array(id),
+    }
+}
+
+pub fn walk_ty_fields<F: Fold + ?Sized>(f: &mut F, node: TyFields) -> TyFields {
+    match node {
+        TyFields::Empty => TyFields::Empty,
+        TyFields::Some(fields) => TyFields::Some(Box::new(f.fold_some_ty_fields(*fields))),
+    }
+}
+
+pub fn walk_some_ty_fields<F: Fold + ?Sized>(f: &mut F, node: SomeTyFields) -> SomeTyFields {
+    match node {
+        SomeTyFields::Some {
+            id,
+            type_id,
+            escape,
+        } => SomeTyFields::Some {
+            id,
+            type_id,
+            escape,
+        },
+        SomeTyFields::List {
+            id,
+            type_id,
+            escape,
+            tail,
+        } => SomeTyFields::List {
+            id,
+            type_id,
+            escape,
+            tail: Box::new(f.fold_some_ty_fields(*tail)),
+        },
+    }
+}
+
+pub fn walk_var_dec<F: Fold + ?Sized>(f: &mut F, node: VarDec) -> VarDec {
+    match node {
+        VarDec::DefaultInit(id, exp, escape) => {
+            VarDec::DefaultInit(id, Box::new(f.fold_exp(*exp)), escape)
+        }
+        VarDec::TypeInit {
+            var_id,
+            type_id,
+            exp,
+            escape,
+        } => VarDec::TypeInit {
+            var_id,
+            type_id,
+            exp: Box::new(f.fold_exp(*exp)),
+            escape,
+        },
+    }
+}
+
+pub fn walk_fun_dec<F: Fold + ?Sized>(f: &mut F, node: FunDec) -> FunDec {
+    match node {
+        FunDec::Proc(id, fields, exp) => FunDec::Proc(
+            id,
+            Box::new(f.fold_ty_fields(*fields)),
+            Box::new(f.fold_exp(*exp)),
+        ),
+        FunDec::Func {
+            id,
+            fields,
+            type_id,
+            exp,
+        } => FunDec::Func {
+            id,
+            fields: Box::new(f.fold_ty_fields(*fields)),
+            type_id,
+            exp: Box::new(f.fold_exp(*exp)),
+        },
+    }
+}
+
+pub fn walk_exp_seq<F: Fold + ?Sized>(f: &mut F, node: ExpSeq) -> ExpSeq {
+    match node {
+        ExpSeq::Empty => ExpSeq::Empty,
+        ExpSeq::Some(seq) => ExpSeq::Some(Box::new(f.fold_some_exp_seq(*seq))),
+    }
+}
+
+pub fn walk_some_exp_seq<F: Fold + ?Sized>(f: &mut F, node: SomeExpSeq) -> SomeExpSeq {
+    match node {
+        SomeExpSeq::Some(exp) => SomeExpSeq::Some(Box::new(f.fold_exp(*exp))),
+        SomeExpSeq::List(exp, tail) => SomeExpSeq::List(
+            Box::new(f.fold_exp(*exp)),
+            Box::new(f.fold_some_exp_seq(*tail)),
+        ),
+    }
+}
+
+pub fn walk_exp<F: Fold + ?Sized>(f: &mut F, node: Exp) -> Exp {
+    match node {
+        Exp::Match(m) => Exp::Match(Box::new(f.fold_match(*m))),
+        Exp::Unmatch(u) => Exp::Unmatch(Box::new(f.fold_unmatch(*u))),
+        Exp::Error => Exp::Error,
+    }
+}
+
+pub fn walk_match<F: Fold + ?Sized>(f: &mut F, node: Match) -> Match {
+    match node {
+        Match::IfThenElse {
+            if_exp,
+            then_exp,
+            else_exp,
+        } => Match::IfThenElse {
+            if_exp: Box::new(f.fold_exp(*if_exp)),
+            then_exp: Box::new(f.fold_match(*then_exp)),
+            else_exp: Box::new(f.fold_match(*else_exp)),
+        },
+        Match::While { cond, body } => Match::While {
+            cond: Box::new(f.fold_exp(*cond)),
+            body: Box::new(f.fold_match(*body)),
+        },
+        Match::For {
+            id,
+            escape,
+            start,
+            end,
+            body,
+        } => Match::For {
+            id,
+            escape,
+            start: Box::new(f.fold_exp(*start)),
+            end: Box::new(f.fold_exp(*end)),
+            body: Box::new(f.fold_match(*body)),
+        },
+        Match::Assign(lvalue, slice) => Match::Assign(
+            Box::new(f.fold_lvalue(*lvalue)),
+            Box::new(f.fold_slice(*slice)),
+        ),
+        Match::Slice(slice) => Match::Slice(Box::new(f.fold_slice(*slice))),
+        Match::Break => Match::Break,
+    }
+}
+
+pub fn walk_unmatch<F: Fold + ?Sized>(f: &mut F, node: Unmatch) -> Unmatch {
+    match node {
+        Unmatch::IfThen { if_exp, then_exp } => Unmatch::IfThen {
+            if_exp: Box::new(f.fold_exp(*if_exp)),
+            then_exp: Box::new(f.fold_exp(*then_exp)),
+        },
+        Unmatch::IfThenElse(if_exp, then_exp, else_exp) => Unmatch::IfThenElse(
+            Box::new(f.fold_exp(*if_exp)),
+            Box::new(f.fold_match(*then_exp)),
+            Box::new(f.fold_unmatch(*else_exp)),
+        ),
+        Unmatch::While { cond, body } => Unmatch::While {
+            cond: Box::new(f.fold_exp(*cond)),
+            body: Box::new(f.fold_unmatch(*body)),
+        },
+        Unmatch::For {
+            id,
+            escape,
+            start,
+            end,
+            body,
+        } => Unmatch::For {
+            id,
+            escape,
+            start: Box::new(f.fold_exp(*start)),
+            end: Box::new(f.fold_exp(*end)),
+            body: Box::new(f.fold_unmatch(*body)),
+        },
+    }
+}
+
+pub fn walk_lvalue<F: Fold + ?Sized>(f: &mut F, node: LValue) -> LValue {
+    match node {
+        LValue::Id(id) => LValue::Id(id),
+        LValue::Refer(refer) => LValue::Refer(Box::new(f.fold_refer(*refer))),
+    }
+}
+
+pub fn walk_refer<F: Fold + ?Sized>(f: &mut F, node: Refer) -> Refer {
+    match node {
+        Refer::Field(id, field) => Refer::Field(id, field),
+        Refer::ReferField(refer, field) => Refer::ReferField(Box::new(f.fold_refer(*refer)), field),
+        Refer::Array(id, index) => Refer::Array(id, Box::new(f.fold_exp(*index))),
+        Refer::ReferArray(refer, index) => {
+            Refer::ReferArray(Box::new(f.fold_refer(*refer)), Box::new(f.fold_exp(*index)))
+        }
+    }
+}
+
+pub fn walk_slice<F: Fold + ?Sized>(f: &mut F, node: Slice) -> Slice {
+    match node {
+        Slice::Array { type_id, len, init } => Slice::Array {
+            type_id,
+            len: Box::new(f.fold_exp(*len)),
+            init: Box::new(f.fold_slice(*init)),
+        },
+        Slice::Record(type_id, rec_list) => {
+            Slice::Record(type_id, Box::new(f.fold_rec_list(*rec_list)))
+        }
+        Slice::Sheet(sheet) => Slice::Sheet(Box::new(f.fold_sheet(*sheet))),
+    }
+}
+
+pub fn walk_sheet<F: Fold + ?Sized>(f: &mut F, node: Sheet) -> Sheet {
+    match node {
+        Sheet::Or(sheet, piece) => Sheet::Or(
+            Box::new(f.fold_sheet(*sheet)),
+            Box::new(f.fold_piece(*piece)),
+        ),
+        Sheet::Piece(piece) => Sheet::Piece(Box::new(f.fold_piece(*piece))),
+    }
+}
+
+pub fn walk_piece<F: Fold + ?Sized>(f: &mut F, node: Piece) -> Piece {
+    match node {
+        Piece::And(piece, bit) => {
+            Piece::And(Box::new(f.fold_piece(*piece)), Box::new(f.fold_bit(*bit)))
+        }
+        Piece::Bit(bit) => Piece::Bit(Box::new(f.fold_bit(*bit))),
+    }
+}
+
+pub fn walk_bit<F: Fold + ?Sized>(f: &mut F, node: Bit) -> Bit {
+    let mut fold_items = |left: Box<Item>, right: Box<Item>| {
+        (Box::new(f.fold_item(*left)), Box::new(f.fold_item(*right)))
+    };
+    match node {
+        Bit::Eq(left, right) => {
+            let (left, right) = fold_items(left, right);
+            Bit::Eq(left, right)
+        }
+        Bit::Neq(left, right) => {
+            let (left, right) = fold_items(left, right);
+            Bit::Neq(left, right)
+        }
+        Bit::Lt(left, right) => {
+            let (left, right) = fold_items(left, right);
+            Bit::Lt(left, right)
+        }
+        Bit::Le(left, right) => {
+            let (left, right) = fold_items(left, right);
+            Bit::Le(left, right)
+        }
+        Bit::Gt(left, right) => {
+            let (left, right) = fold_items(left, right);
+            Bit::Gt(left, right)
+        }
+        Bit::Ge(left, right) => {
+            let (left, right) = fold_items(left, right);
+            Bit::Ge(left, right)
+        }
+        Bit::Item(item) => Bit::Item(Box::new(f.fold_item(*item))),
+    }
+}
+
+pub fn walk_item<F: Fold + ?Sized>(f: &mut F, node: Item) -> Item {
+    match node {
+        Item::Plus(item, term) => {
+            Item::Plus(Box::new(f.fold_item(*item)), Box::new(f.fold_term(*term)))
+        }
+        Item::Minus(item, term) => {
+            Item::Minus(Box::new(f.fold_item(*item)), Box::new(f.fold_term(*term)))
+        }
+        Item::Term(term) => Item::Term(Box::new(f.fold_term(*term))),
+    }
+}
+
+pub fn walk_term<F: Fold + ?Sized>(f: &mut F, node: Term) -> Term {
+    match node {
+        Term::Times(term, factor) => Term::Times(
+            Box::new(f.fold_term(*term)),
+            Box::new(f.fold_factor(*factor)),
+        ),
+        Term::Divide(term, factor) => Term::Divide(
+            Box::new(f.fold_term(*term)),
+            Box::new(f.fold_factor(*factor)),
+        ),
+        Term::Factor(factor) => Term::Factor(Box::new(f.fold_factor(*factor))),
+    }
+}
+
+pub fn walk_factor<F: Fold + ?Sized>(f: &mut F, node: Factor) -> Factor {
+    match node {
+        Factor::MMeta(meta) => Factor::MMeta(Box::new(f.fold_meta(*meta))),
+        Factor::Meta(meta) => Factor::Meta(Box::new(f.fold_meta(*meta))),
+    }
+}
+
+pub fn walk_meta<F: Fold + ?Sized>(f: &mut F, node: Meta) -> Meta {
+    match node {
+        Meta::Int(i) => Meta::Int(i),
+        Meta::String(s) => Meta::String(s),
+        Meta::Nil => Meta::Nil,
+        Meta::Id(id) => Meta::Id(id),
+        Meta::Refer(refer) => Meta::Refer(Box::new(f.fold_refer(*refer))),
+        Meta::CapSeq(seq) => Meta::CapSeq(Box::new(f.fold_exp_seq(*seq))),
+        Meta::Call(id, args) => Meta::Call(id, Box::new(f.fold_args_list(*args))),
+        Meta::Let(decs, seq) => {
+            Meta::Let(Box::new(f.fold_decs(*decs)), Box::new(f.fold_exp_seq(*seq)))
+        }
+    }
+}
+
+pub fn walk_args_list<F: Fold + ?Sized>(f: &mut F, node: ArgsList) -> ArgsList {
+    match node {
+        ArgsList::Empty => ArgsList::Empty,
+        ArgsList::Some(args) => ArgsList::Some(Box::new(f.fold_some_args_list(*args))),
+    }
+}
+
+pub fn walk_some_args_list<F: Fold + ?Sized>(f: &mut F, node: SomeArgsList) -> SomeArgsList {
+    match node {
+        SomeArgsList::Some(exp) => SomeArgsList::Some(Box::new(f.fold_exp(*exp))),
+        SomeArgsList::List(exp, tail) => SomeArgsList::List(
+            Box::new(f.fold_exp(*exp)),
+            Box::new(f.fold_some_args_list(*tail)),
+        ),
+    }
+}
+
+pub fn walk_rec_list<F: Fold + ?Sized>(f: &mut F, node: RecList) -> RecList {
+    match node {
+        RecList::Empty => RecList::Empty,
+        RecList::Some(rec_list) => RecList::Some(Box::new(f.fold_some_rec_list(*rec_list))),
+    }
+}
+
+pub fn walk_some_rec_list<F: Fold + ?Sized>(f: &mut F, node: SomeRecList) -> SomeRecList {
+    match node {
+        SomeRecList::Some(id, exp) => SomeRecList::Some(id, Box::new(f.fold_exp(*exp))),
+        SomeRecList::List(id, exp, tail) => SomeRecList::List(
+            id,
+            Box::new(f.fold_exp(*exp)),
+            Box::new(f.fold_some_rec_list(*tail)),
+        ),
+    }
+}
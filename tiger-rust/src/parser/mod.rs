@@ -1,3 +1,8 @@
+pub mod arena;
 pub mod ast;
+pub mod dot;
+pub mod fold;
 pub mod parser;
+pub mod printer;
 pub mod util;
+pub mod visitor;
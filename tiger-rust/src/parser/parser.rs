@@ -2,12 +2,32 @@ use crate::lexer::lexer::Lexer;
 use crate::lexer::lexer::Token;
 use lalrpop_util::ParseError;
 
+use super::arena::Ast;
 use super::ast;
 
-lalrpop_mod!(pub parser,"/parser/tiger.rs");
+lalrpop_mod!(
+    #[allow(
+        clippy::just_underscores_and_digits,
+        clippy::needless_lifetimes,
+        clippy::redundant_field_names,
+        clippy::too_many_arguments
+    )]
+    pub parser,
+    "/parser/tiger.rs"
+);
 
+/// Parses into the boxed tree, rebuilt from the arena [`compile_to_arena`]
+/// produces.
 pub fn compile(input: &str) -> Result<Box<ast::Exp>, ParseError<(), Token, ()>> {
-    parser::ExpParser::new().parse(Lexer::new(input))
+    compile_to_arena(input).map(|ast| ast.to_exp())
+}
+
+/// Parses straight into an [`Ast`] arena, so programs of any size can be
+/// handled without deep recursion.
+pub fn compile_to_arena(input: &str) -> Result<Ast, ParseError<(), Token, ()>> {
+    let mut ast = Ast::default();
+    parser::ExpParser::new().parse(&mut ast, Lexer::new(input))?;
+    Ok(ast)
 }
 
 #[test]
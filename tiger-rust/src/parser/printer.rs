@@ -0,0 +1,630 @@
+use crate::lexer::lexer::{unescape, ValueInfo};
+use crate::parser::arena::Ast;
+use crate::parser::ast::*;
+
+/// Prints the tree in the S-expression layout of Appel's `PrintAbsyn`.
+///
+/// The grammar wrappers (`Sheet`, `Piece`, `Bit`, ...) are flattened into the
+/// abstract syntax they stand for: `&` and `|` become `IfExp`, unary minus
+/// becomes `OpExp(MinusOp, IntExp(0), e)`, and consecutive type or function
+/// declarations are grouped into one `TypeDec[...]` or `FunctionDec[...]`.
+///
+/// Each node expands into the text and child nodes it prints as, pushed on
+/// an explicit stack, so deep trees print without deep recursion.
+struct AbsynPrinter<'a> {
+    out: String,
+    stack: Vec<Task<'a>>,
+}
+
+/// What is left to print: some text, or a node at an indentation.
+enum Task<'a> {
+    Say(&'a str),
+    Text(String),
+    Indent(usize),
+    Exp(&'a Exp, usize),
+    Match(&'a Match, usize),
+    Unmatch(&'a Unmatch, usize),
+    LValue(&'a LValue, usize),
+    Refer(&'a Refer, usize),
+    Slice(&'a Slice, usize),
+    Sheet(&'a Sheet, usize),
+    Piece(&'a Piece, usize),
+    Bit(&'a Bit, usize),
+    Item(&'a Item, usize),
+    Term(&'a Term, usize),
+    Factor(&'a Factor, usize),
+    Meta(&'a Meta, usize),
+}
+
+use Task::{Indent, Say, Text};
+
+enum DecGroup<'a> {
+    Types(Vec<&'a TypeDec>),
+    Funcs(Vec<&'a FunDec>),
+    Var(&'a VarDec),
+}
+
+impl Exp {
+    pub fn to_absyn_string(&self) -> String {
+        let mut printer = AbsynPrinter {
+            out: String::new(),
+            stack: vec![Task::Exp(self, 0)],
+        };
+        while let Some(task) = printer.stack.pop() {
+            printer.run(task);
+        }
+        printer.out.push('\n');
+        printer.out
+    }
+}
+
+impl Ast {
+    pub fn to_absyn_string(&self) -> String {
+        self.with_exp(Exp::to_absyn_string)
+    }
+}
+
+impl<'a> AbsynPrinter<'a> {
+    fn run(&mut self, task: Task<'a>) {
+        let tasks = match task {
+            Say(s) => return self.out.push_str(s),
+            Text(s) => return self.out.push_str(&s),
+            Indent(d) => return self.out.extend(std::iter::repeat_n(' ', d)),
+            Task::Exp(node, d) => exp(node, d),
+            Task::Match(node, d) => matched(node, d),
+            Task::Unmatch(node, d) => unmatched(node, d),
+            Task::LValue(node, d) => lvalue(node, d),
+            Task::Refer(node, d) => refer(node, d),
+            Task::Slice(node, d) => slice(node, d),
+            Task::Sheet(node, d) => sheet(node, d),
+            Task::Piece(node, d) => piece(node, d),
+            Task::Bit(node, d) => bit(node, d),
+            Task::Item(node, d) => item(node, d),
+            Task::Term(node, d) => term(node, d),
+            Task::Factor(node, d) => factor(node, d),
+            Task::Meta(node, d) => meta(node, d),
+        };
+        self.stack.extend(tasks.into_iter().rev());
+    }
+}
+
+/// The items of a list, each on its own line after a comma.
+fn dolist<'a>(items: impl IntoIterator<Item = Vec<Task<'a>>>) -> Vec<Task<'a>> {
+    let mut tasks = Vec::new();
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            tasks.push(Say(","));
+        }
+        tasks.push(Say("\n"));
+        tasks.extend(item);
+    }
+    tasks
+}
+
+fn simple_var(id: &ValueInfo<String>, d: usize) -> Vec<Task<'_>> {
+    vec![Indent(d), Say("SimpleVar("), Say(&id.v), Say(")")]
+}
+
+fn field_var<'a>(var: Vec<Task<'a>>, field: &'a str, d: usize) -> Vec<Task<'a>> {
+    let mut tasks = vec![Indent(d), Say("FieldVar(\n")];
+    tasks.extend(var);
+    tasks.extend([Say(",\n"), Indent(d + 1), Say(field), Say(")")]);
+    tasks
+}
+
+fn subscript_var<'a>(var: Vec<Task<'a>>, index: &'a Exp, d: usize) -> Vec<Task<'a>> {
+    let mut tasks = vec![Indent(d), Say("SubscriptVar(\n")];
+    tasks.extend(var);
+    tasks.extend([Say(",\n"), Task::Exp(index, d + 1), Say(")")]);
+    tasks
+}
+
+fn lvalue(node: &LValue, d: usize) -> Vec<Task<'_>> {
+    match node {
+        LValue::Id(id) => simple_var(id, d),
+        LValue::Refer(refer) => vec![Task::Refer(refer, d)],
+    }
+}
+
+fn refer(node: &Refer, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Refer::Field(id, field) => field_var(simple_var(id, d + 1), &field.v, d),
+        Refer::ReferField(refer, field) => field_var(vec![Task::Refer(refer, d + 1)], &field.v, d),
+        Refer::Array(id, index) => subscript_var(simple_var(id, d + 1), index, d),
+        Refer::ReferArray(refer, index) => subscript_var(vec![Task::Refer(refer, d + 1)], index, d),
+    }
+}
+
+fn var_exp(var: Vec<Task<'_>>, d: usize) -> Vec<Task<'_>> {
+    let mut tasks = vec![Indent(d), Say("VarExp(\n")];
+    tasks.extend(var);
+    tasks.push(Say(")"));
+    tasks
+}
+
+fn int_exp<'a>(i: u64, d: usize) -> Vec<Task<'a>> {
+    vec![Indent(d), Say("IntExp("), Text(i.to_string()), Say(")")]
+}
+
+fn op_exp<'a>(oper: &'a str, left: Vec<Task<'a>>, right: Vec<Task<'a>>, d: usize) -> Vec<Task<'a>> {
+    let mut tasks = vec![Indent(d), Say("OpExp("), Say(oper), Say(",\n")];
+    tasks.extend(left);
+    tasks.push(Say(",\n"));
+    tasks.extend(right);
+    tasks.push(Say(")"));
+    tasks
+}
+
+fn if_exp<'a>(
+    test: Vec<Task<'a>>,
+    then: Vec<Task<'a>>,
+    else_: Option<Vec<Task<'a>>>,
+    d: usize,
+) -> Vec<Task<'a>> {
+    let mut tasks = vec![Indent(d), Say("IfExp(\n")];
+    tasks.extend(test);
+    tasks.push(Say(",\n"));
+    tasks.extend(then);
+    if let Some(else_) = else_ {
+        tasks.push(Say(",\n"));
+        tasks.extend(else_);
+    }
+    tasks.push(Say(")"));
+    tasks
+}
+
+fn while_exp<'a>(test: &'a Exp, body: Task<'a>, d: usize) -> Vec<Task<'a>> {
+    vec![
+        Indent(d),
+        Say("WhileExp(\n"),
+        Task::Exp(test, d + 1),
+        Say(",\n"),
+        body,
+        Say(")"),
+    ]
+}
+
+fn for_exp<'a>(
+    id: &'a ValueInfo<String>,
+    escape: &Escape,
+    lo: &'a Exp,
+    hi: &'a Exp,
+    body: Task<'a>,
+    d: usize,
+) -> Vec<Task<'a>> {
+    // PrintAbsyn writes the loop variable and its escape flag unindented
+    // and without a separator; keep that so reference outputs still diff.
+    vec![
+        Indent(d),
+        Say("ForExp(\n"),
+        Say(&id.v),
+        Text(escape.get().to_string()),
+        Say(",\n"),
+        Task::Exp(lo, d + 1),
+        Say(",\n"),
+        Task::Exp(hi, d + 1),
+        Say(",\n"),
+        body,
+        Say(")"),
+    ]
+}
+
+fn seq_exp(node: &ExpSeq, d: usize) -> Vec<Task<'_>> {
+    let mut exps = Vec::new();
+    if let ExpSeq::Some(seq) = node {
+        let mut seq: &SomeExpSeq = seq;
+        loop {
+            match seq {
+                SomeExpSeq::Some(exp) => {
+                    exps.push(exp.as_ref());
+                    break;
+                }
+                SomeExpSeq::List(exp, tail) => {
+                    exps.push(exp.as_ref());
+                    seq = tail;
+                }
+            }
+        }
+    }
+    let mut tasks = vec![Indent(d), Say("SeqExp[")];
+    tasks.extend(dolist(
+        exps.into_iter().map(|exp| vec![Task::Exp(exp, d + 1)]),
+    ));
+    tasks.push(Say("]"));
+    tasks
+}
+
+fn exp(node: &Exp, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Exp::Match(m) => vec![Task::Match(m, d)],
+        Exp::Unmatch(u) => vec![Task::Unmatch(u, d)],
+        Exp::Error => vec![Indent(d), Say("ErrorExp")],
+    }
+}
+
+fn matched(node: &Match, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Match::IfThenElse {
+            if_exp: test,
+            then_exp,
+            else_exp,
+        } => if_exp(
+            vec![Task::Exp(test, d + 1)],
+            vec![Task::Match(then_exp, d + 1)],
+            Some(vec![Task::Match(else_exp, d + 1)]),
+            d,
+        ),
+        Match::While { cond, body } => while_exp(cond, Task::Match(body, d + 1), d),
+        Match::For {
+            id,
+            escape,
+            start,
+            end,
+            body,
+        } => for_exp(id, escape, start, end, Task::Match(body, d + 1), d),
+        Match::Assign(lvalue, slice) => vec![
+            Indent(d),
+            Say("AssignExp(\n"),
+            Task::LValue(lvalue, d + 1),
+            Say(",\n"),
+            Task::Slice(slice, d + 1),
+            Say(")"),
+        ],
+        Match::Slice(slice) => vec![Task::Slice(slice, d)],
+        Match::Break => vec![Indent(d), Say("BreakExp")],
+    }
+}
+
+fn unmatched(node: &Unmatch, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Unmatch::IfThen {
+            if_exp: test,
+            then_exp,
+        } => if_exp(
+            vec![Task::Exp(test, d + 1)],
+            vec![Task::Exp(then_exp, d + 1)],
+            None,
+            d,
+        ),
+        Unmatch::IfThenElse(test, then_exp, else_exp) => if_exp(
+            vec![Task::Exp(test, d + 1)],
+            vec![Task::Match(then_exp, d + 1)],
+            Some(vec![Task::Unmatch(else_exp, d + 1)]),
+            d,
+        ),
+        Unmatch::While { cond, body } => while_exp(cond, Task::Unmatch(body, d + 1), d),
+        Unmatch::For {
+            id,
+            escape,
+            start,
+            end,
+            body,
+        } => for_exp(id, escape, start, end, Task::Unmatch(body, d + 1), d),
+    }
+}
+
+fn slice(node: &Slice, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Slice::Array { type_id, len, init } => vec![
+            Indent(d),
+            Say("ArrayExp("),
+            Say(&type_id.v),
+            Say(",\n"),
+            Task::Exp(len, d + 1),
+            Say(",\n"),
+            Task::Slice(init, d + 1),
+            Say(")"),
+        ],
+        Slice::Record(type_id, rec_list) => {
+            let mut fields = Vec::new();
+            if let RecList::Some(list) = rec_list.as_ref() {
+                let mut list: &SomeRecList = list;
+                loop {
+                    match list {
+                        SomeRecList::Some(id, exp) => {
+                            fields.push((id, exp.as_ref()));
+                            break;
+                        }
+                        SomeRecList::List(id, exp, tail) => {
+                            fields.push((id, exp.as_ref()));
+                            list = tail;
+                        }
+                    }
+                }
+            }
+            let mut tasks = vec![Indent(d), Say("RecordExp("), Say(&type_id.v), Say(",[\n")];
+            tasks.extend(dolist(fields.into_iter().map(|(id, exp)| {
+                vec![
+                    Indent(d + 1),
+                    Say("("),
+                    Say(&id.v),
+                    Say(",\n"),
+                    Task::Exp(exp, d + 2),
+                    Say(")"),
+                ]
+            })));
+            tasks.push(Say("])"));
+            tasks
+        }
+        Slice::Sheet(sheet) => vec![Task::Sheet(sheet, d)],
+    }
+}
+
+fn sheet(node: &Sheet, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Sheet::Or(sheet, piece) => if_exp(
+            vec![Task::Sheet(sheet, d + 1)],
+            int_exp(1, d + 1),
+            Some(vec![Task::Piece(piece, d + 1)]),
+            d,
+        ),
+        Sheet::Piece(piece) => vec![Task::Piece(piece, d)],
+    }
+}
+
+fn piece(node: &Piece, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Piece::And(piece, bit) => if_exp(
+            vec![Task::Piece(piece, d + 1)],
+            vec![Task::Bit(bit, d + 1)],
+            Some(int_exp(0, d + 1)),
+            d,
+        ),
+        Piece::Bit(bit) => vec![Task::Bit(bit, d)],
+    }
+}
+
+fn bit(node: &Bit, d: usize) -> Vec<Task<'_>> {
+    let (oper, left, right) = match node {
+        Bit::Eq(left, right) => ("EqOp", left, right),
+        Bit::Neq(left, right) => ("NeqOp", left, right),
+        Bit::Lt(left, right) => ("LtOp", left, right),
+        Bit::Le(left, right) => ("LeOp", left, right),
+        Bit::Gt(left, right) => ("GtOp", left, right),
+        Bit::Ge(left, right) => ("GeOp", left, right),
+        Bit::Item(item) => return vec![Task::Item(item, d)],
+    };
+    op_exp(
+        oper,
+        vec![Task::Item(left, d + 1)],
+        vec![Task::Item(right, d + 1)],
+        d,
+    )
+}
+
+fn item(node: &Item, d: usize) -> Vec<Task<'_>> {
+    let (oper, left, right) = match node {
+        Item::Plus(left, right) => ("PlusOp", left, right),
+        Item::Minus(left, right) => ("MinusOp", left, right),
+        Item::Term(term) => return vec![Task::Term(term, d)],
+    };
+    op_exp(
+        oper,
+        vec![Task::Item(left, d + 1)],
+        vec![Task::Term(right, d + 1)],
+        d,
+    )
+}
+
+fn term(node: &Term, d: usize) -> Vec<Task<'_>> {
+    let (oper, left, right) = match node {
+        Term::Times(left, right) => ("TimesOp", left, right),
+        Term::Divide(left, right) => ("DivideOp", left, right),
+        Term::Factor(factor) => return vec![Task::Factor(factor, d)],
+    };
+    op_exp(
+        oper,
+        vec![Task::Term(left, d + 1)],
+        vec![Task::Factor(right, d + 1)],
+        d,
+    )
+}
+
+fn factor(node: &Factor, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Factor::MMeta(meta) => op_exp(
+            "MinusOp",
+            int_exp(0, d + 1),
+            vec![Task::Meta(meta, d + 1)],
+            d,
+        ),
+        Factor::Meta(meta) => vec![Task::Meta(meta, d)],
+    }
+}
+
+fn meta(node: &Meta, d: usize) -> Vec<Task<'_>> {
+    match node {
+        Meta::Int(i) => int_exp(i.v, d),
+        Meta::String(s) => vec![
+            Indent(d),
+            Say("StringExp(\""),
+            Text(unescape(&s.v)),
+            Say("\")"),
+        ],
+        Meta::Nil => vec![Indent(d), Say("NilExp")],
+        Meta::Id(id) => var_exp(simple_var(id, d + 1), d),
+        Meta::Refer(refer) => var_exp(vec![Task::Refer(refer, d + 1)], d),
+        Meta::CapSeq(seq) => seq_exp(seq, d),
+        Meta::Call(id, args) => {
+            let mut exps = Vec::new();
+            if let ArgsList::Some(list) = args.as_ref() {
+                let mut list: &SomeArgsList = list;
+                loop {
+                    match list {
+                        SomeArgsList::Some(exp) => {
+                            exps.push(exp.as_ref());
+                            break;
+                        }
+                        SomeArgsList::List(exp, tail) => {
+                            exps.push(exp.as_ref());
+                            list = tail;
+                        }
+                    }
+                }
+            }
+            let mut tasks = vec![Indent(d), Say("CallExp("), Say(&id.v), Say(",[")];
+            tasks.extend(dolist(
+                exps.into_iter().map(|exp| vec![Task::Exp(exp, d + 1)]),
+            ));
+            tasks.push(Say("])"));
+            tasks
+        }
+        Meta::Let(decs, seq) => {
+            let mut tasks = vec![Indent(d), Say("LetExp([")];
+            tasks.extend(dolist(
+                group_decs(decs).into_iter().map(|group| dec(group, d + 1)),
+            ));
+            tasks.push(Say("],\n"));
+            tasks.extend(seq_exp(seq, d + 1));
+            tasks.push(Say(")"));
+            tasks
+        }
+    }
+}
+
+fn result_type(type_id: Option<&ValueInfo<String>>) -> Vec<Task<'_>> {
+    match type_id {
+        Some(type_id) => vec![Say("SOME("), Say(&type_id.v), Say(")")],
+        None => vec![Say("NONE")],
+    }
+}
+
+fn dec(node: DecGroup<'_>, d: usize) -> Vec<Task<'_>> {
+    match node {
+        DecGroup::Types(decs) => {
+            let mut tasks = vec![Indent(d), Say("TypeDec[")];
+            tasks.extend(dolist(decs.into_iter().map(|TypeDec::Dec(id, ty)| {
+                let mut tasks = vec![Indent(d + 1), Say("("), Say(&id.v), Say(",\n")];
+                tasks.extend(self::ty(ty, d + 2));
+                tasks.push(Say(")"));
+                tasks
+            })));
+            tasks.push(Say("]"));
+            tasks
+        }
+        DecGroup::Funcs(decs) => {
+            let mut tasks = vec![Indent(d), Say("FunctionDec[")];
+            tasks.extend(dolist(decs.into_iter().map(|dec| fun_dec(dec, d + 1))));
+            tasks.push(Say("]"));
+            tasks
+        }
+        DecGroup::Var(dec) => {
+            let (id, escape, result, init) = match dec {
+                VarDec::DefaultInit(id, exp, escape) => (id, escape, None, exp),
+                VarDec::TypeInit {
+                    var_id,
+                    type_id,
+                    exp,
+                    escape,
+                } => (var_id, escape, Some(type_id), exp),
+            };
+            let mut tasks = vec![
+                Indent(d),
+                Say("VarDec("),
+                Say(&id.v),
+                Say(","),
+                Text(escape.get().to_string()),
+                Say(","),
+            ];
+            tasks.extend(result_type(result));
+            tasks.extend([Say(",\n"), Task::Exp(init, d + 1), Say(")")]);
+            tasks
+        }
+    }
+}
+
+fn fun_dec(node: &FunDec, d: usize) -> Vec<Task<'_>> {
+    let (id, fields, result, body) = match node {
+        FunDec::Proc(id, fields, body) => (id, fields, None, body),
+        FunDec::Func {
+            id,
+            fields,
+            type_id,
+            exp,
+        } => (id, fields, Some(type_id), exp),
+    };
+    let mut tasks = vec![Indent(d), Say("("), Say(&id.v), Say(",[")];
+    tasks.extend(dolist(
+        ty_fields(fields).into_iter().map(|f| field(f, d + 1)),
+    ));
+    tasks.push(Say("],\n"));
+    tasks.extend(result_type(result));
+    tasks.extend([Say(",\n"), Task::Exp(body, d + 1), Say(")")]);
+    tasks
+}
+
+fn field((id, escape, type_id): Field<'_>, d: usize) -> Vec<Task<'_>> {
+    vec![
+        Indent(d),
+        Say("("),
+        Say(&id.v),
+        Say(","),
+        Text(escape.get().to_string()),
+        Say(","),
+        Say(&type_id.v),
+        Say(")"),
+    ]
+}
+
+fn ty(node: &Ty, d: usize) -> Vec<Task<'_>> {
+    let mut tasks = vec![Indent(d)];
+    match node {
+        Ty::Id(id) => tasks.extend([Say("NameTy("), Say(&id.v), Say(")")]),
+        Ty::Fields(fields) => {
+            tasks.push(Say("RecordTy["));
+            tasks.extend(dolist(
+                ty_fields(fields).into_iter().map(|f| field(f, d + 1)),
+            ));
+            tasks.push(Say("]"));
+        }
+        Ty::Array(id) => tasks.extend([Say("ArrayTy("), Say(&id.v), Say(")")]),
+    }
+    tasks
+}
+
+type Field<'a> = (&'a ValueInfo<String>, &'a Escape, &'a ValueInfo<String>);
+
+fn ty_fields(node: &TyFields) -> Vec<Field<'_>> {
+    let mut fields = Vec::new();
+    if let TyFields::Some(some) = node {
+        let mut some: &SomeTyFields = some;
+        loop {
+            match some {
+                SomeTyFields::Some {
+                    id,
+                    type_id,
+                    escape,
+                } => {
+                    fields.push((id, escape, type_id));
+                    break;
+                }
+                SomeTyFields::List {
+                    id,
+                    type_id,
+                    escape,
+                    tail,
+                } => {
+                    fields.push((id, escape, type_id));
+                    some = tail;
+                }
+            }
+        }
+    }
+    fields
+}
+
+fn group_decs(node: &Decs) -> Vec<DecGroup<'_>> {
+    let mut groups = Vec::new();
+    let mut node = node;
+    while let Decs::Decs(dec, tail) = node {
+        match (dec.as_ref(), groups.last_mut()) {
+            (Dec::TypeDec(dec), Some(DecGroup::Types(decs))) => decs.push(dec.as_ref()),
+            (Dec::TypeDec(dec), _) => groups.push(DecGroup::Types(vec![dec.as_ref()])),
+            (Dec::FunDec(dec), Some(DecGroup::Funcs(decs))) => decs.push(dec.as_ref()),
+            (Dec::FunDec(dec), _) => groups.push(DecGroup::Funcs(vec![dec.as_ref()])),
+            (Dec::VarDec(dec), _) => groups.push(DecGroup::Var(dec.as_ref())),
+        }
+        node = tail;
+    }
+    groups
+}
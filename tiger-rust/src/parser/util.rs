@@ -1,15 +1,304 @@
+use serde::{Deserialize, Serialize};
+
+use crate::lexer::lexer::ValueInfo;
+use crate::parser::arena::{Ast, Leaf, NodeId, NodeKind, Sort};
 use crate::parser::ast::Exp;
 
+/// Version of the serialized AST layout. Bump it whenever a node kind in
+/// `parser::arena` changes shape so stale dumps are rejected instead of
+/// silently misread.
+pub const AST_SCHEMA_VERSION: u32 = 3;
+
+/// Deepest nesting of arrays and objects accepted in a JSON dump. Dumps are
+/// a flat list of nodes and never nest more than a few levels, so anything
+/// deeper is rejected up front rather than parsed recursively.
+pub const MAX_DUMP_DEPTH: usize = 64;
+
+/// One arena node as it appears in a dump; `children` are indices of
+/// earlier nodes.
+#[derive(Serialize)]
+struct DumpNodeRef<'a> {
+    kind: NodeKind,
+    #[serde(skip_serializing_if = "Leaf::is_none")]
+    leaf: &'a Leaf,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    children: &'a [NodeId],
+}
+
+#[derive(Deserialize)]
+struct DumpNode {
+    kind: NodeKind,
+    #[serde(default)]
+    leaf: Leaf,
+    #[serde(default)]
+    children: Vec<NodeId>,
+}
+
+#[derive(Serialize)]
+struct AstDocumentRef<'a> {
+    version: u32,
+    nodes: Vec<DumpNodeRef<'a>>,
+}
+
+#[derive(Deserialize)]
+struct AstDocument {
+    nodes: Vec<DumpNode>,
+}
+
+#[derive(Deserialize)]
+struct AstHeader {
+    version: u32,
+}
+
 impl Exp {
+    /// The tree as an arena dump; see [`Ast::to_json_string`].
+    pub fn to_json_string(&self) -> Result<String, String> {
+        Ast::from_exp(Box::new(self.clone())).to_json_string()
+    }
+
+    /// The tree as an arena dump; see [`Ast::to_yaml_string`].
+    pub fn to_yaml_string(&self) -> Result<String, String> {
+        Ast::from_exp(Box::new(self.clone())).to_yaml_string()
+    }
+
+    /// Reads a tree written by [`Exp::to_json_string`]; see [`Ast::from_json_str`].
+    pub fn from_json_str(input: &str) -> Result<Box<Exp>, String> {
+        Ast::from_json_str(input).map(|ast| ast.to_exp())
+    }
+
+    /// Reads a tree written by [`Exp::to_yaml_string`]; see [`Ast::from_yaml_str`].
+    pub fn from_yaml_str(input: &str) -> Result<Box<Exp>, String> {
+        Ast::from_yaml_str(input).map(|ast| ast.to_exp())
+    }
+}
+
+impl Ast {
     pub fn to_json_string(&self) -> Result<String, String> {
-        serde_json::to_string(self).map_err(|err| err.to_string())
+        serde_json::to_string(&self.document()).map_err(|err| err.to_string())
     }
 
     pub fn to_yaml_string(&self) -> Result<String, String> {
-        let yaml_data = serde_json::from_str::<serde_yaml::Value>(
-            &serde_json::to_string(self).map_err(|err| err.to_string())?,
-        )
-        .map_err(|err| err.to_string())?;
+        let yaml_data = serde_json::to_value(self.document()).map_err(|err| err.to_string())?;
         serde_yaml::to_string(&yaml_data).map_err(|err| err.to_string())
     }
+
+    /// Reads a tree written by [`Ast::to_json_string`], checking the schema
+    /// version and that the tree could have come out of the parser.
+    pub fn from_json_str(input: &str) -> Result<Ast, String> {
+        let depth = nesting_depth(input);
+        if depth > MAX_DUMP_DEPTH {
+            return Err(format!(
+                "AST dump too deep: {} levels of nesting, at most {} are supported",
+                depth, MAX_DUMP_DEPTH
+            ));
+        }
+        let value =
+            serde_json::from_str::<serde_json::Value>(input).map_err(|err| err.to_string())?;
+        Self::from_value(value)
+    }
+
+    /// Reads a tree written by [`Ast::to_yaml_string`]; see [`Ast::from_json_str`].
+    pub fn from_yaml_str(input: &str) -> Result<Ast, String> {
+        let value =
+            serde_yaml::from_str::<serde_json::Value>(input).map_err(|err| err.to_string())?;
+        Self::from_value(value)
+    }
+
+    fn document(&self) -> AstDocumentRef<'_> {
+        let nodes = (0..self.len())
+            .map(|index| {
+                let id = NodeId::new(index);
+                let node = self.node(id);
+                DumpNodeRef {
+                    kind: node.kind,
+                    leaf: &node.leaf,
+                    children: self.children(id),
+                }
+            })
+            .collect();
+        AstDocumentRef {
+            version: AST_SCHEMA_VERSION,
+            nodes,
+        }
+    }
+
+    fn from_value(value: serde_json::Value) -> Result<Ast, String> {
+        let header = AstHeader::deserialize(&value)
+            .map_err(|err| format!("missing AST schema header: {}", err))?;
+        if header.version != AST_SCHEMA_VERSION {
+            return Err(format!(
+                "AST schema version {} is not supported (expected {})",
+                header.version, AST_SCHEMA_VERSION
+            ));
+        }
+        let document =
+            serde_json::from_value::<AstDocument>(value).map_err(|err| err.to_string())?;
+        let ast = Self::assemble(document.nodes)?;
+        ast.validate()?;
+        Ok(ast)
+    }
+
+    /// Rebuilds the arena from dumped nodes, checking that every node has
+    /// the children and leaf its kind calls for and that they form one tree.
+    fn assemble(nodes: Vec<DumpNode>) -> Result<Ast, String> {
+        let mut ast = Ast::default();
+        let mut sorts = Vec::with_capacity(nodes.len());
+        for (index, node) in nodes.into_iter().enumerate() {
+            let Some((sort, leaf, children)) = node.kind.shape() else {
+                return Err(format!(
+                    "node {}: {:?} is not a node kind",
+                    index, node.kind
+                ));
+            };
+            if node.leaf.shape() != leaf {
+                return Err(format!(
+                    "node {}: {:?} needs leaf {:?}, found {:?}",
+                    index,
+                    node.kind,
+                    leaf,
+                    node.leaf.shape()
+                ));
+            }
+            if node.children.len() != children.len() {
+                return Err(format!(
+                    "node {}: {:?} has {} children, expected {}",
+                    index,
+                    node.kind,
+                    node.children.len(),
+                    children.len()
+                ));
+            }
+            for (child, expected) in node.children.iter().zip(children) {
+                if child.index() >= index {
+                    return Err(format!(
+                        "node {}: child {} does not come before its parent",
+                        index,
+                        child.index()
+                    ));
+                }
+                if ast.node(*child).parent.is_some() {
+                    return Err(format!(
+                        "node {}: child {} already has a parent",
+                        index,
+                        child.index()
+                    ));
+                }
+                if sorts[child.index()] != *expected {
+                    return Err(format!(
+                        "node {}: child {} is a {:?}, expected {:?}",
+                        index,
+                        child.index(),
+                        sorts[child.index()],
+                        expected
+                    ));
+                }
+            }
+            ast.push(node.kind, node.leaf, &node.children);
+            sorts.push(sort);
+        }
+        match sorts.last() {
+            None => return Err(String::from("AST dump has no nodes")),
+            Some(Sort::Exp) => (),
+            Some(sort) => return Err(format!("root node is a {:?}, expected Exp", sort)),
+        }
+        let root = ast.root();
+        if let Some(orphan) = (0..ast.len())
+            .map(NodeId::new)
+            .find(|&id| id != root && ast.node(id).parent.is_none())
+        {
+            return Err(format!("node {} has no parent", orphan.index()));
+        }
+        Ok(ast)
+    }
+
+    /// Checks the invariants the parser guarantees but deserialization does
+    /// not: no error nodes, well-formed identifiers and string literals, and
+    /// 1-based positions.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut validator = Validator { errors: Vec::new() };
+        for id in self.preorder(self.root()) {
+            let node = self.node(id);
+            match (node.kind, &node.leaf) {
+                (NodeKind::ExpError, _) => validator.errors.push(String::from("error node in AST")),
+                (NodeKind::MetaInt, Leaf::Int(i)) => validator.position(i, "integer literal"),
+                (NodeKind::MetaString, Leaf::Id(s)) => validator.string(s),
+                (_, Leaf::Id(id)) => validator.id(id),
+                (_, Leaf::Ids(first, second)) => {
+                    validator.id(first);
+                    validator.id(second);
+                }
+                (_, Leaf::Binding { id, type_id, .. }) => {
+                    validator.id(id);
+                    if let Some(type_id) = type_id {
+                        validator.id(type_id);
+                    }
+                }
+                _ => (),
+            }
+        }
+        match validator.errors.first() {
+            None => Ok(()),
+            Some(_) => Err(validator.errors.join("\n")),
+        }
+    }
+}
+
+/// The deepest nesting of arrays and objects in the JSON text `input`,
+/// found without recursing.
+fn nesting_depth(input: &str) -> usize {
+    let (mut depth, mut max) = (0usize, 0);
+    let (mut in_string, mut escaped) = (false, false);
+    for byte in input.bytes() {
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' if in_string => escaped = true,
+            b'"' => in_string = !in_string,
+            _ if in_string => (),
+            b'[' | b'{' => {
+                depth += 1;
+                max = max.max(depth);
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+    max
+}
+
+struct Validator {
+    errors: Vec<String>,
+}
+
+impl Validator {
+    fn position<V>(&mut self, value: &ValueInfo<V>, what: &str) {
+        if value.info.line == 0 || value.info.col == 0 {
+            self.errors.push(format!(
+                "{} has invalid position {}.{}",
+                what, value.info.line, value.info.col
+            ));
+        }
+    }
+
+    fn id(&mut self, id: &ValueInfo<String>) {
+        let mut chars = id.v.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            self.errors.push(format!(
+                "{}.{}: invalid identifier {:?}",
+                id.info.line, id.info.col, id.v
+            ));
+        }
+        self.position(id, "identifier");
+    }
+
+    fn string(&mut self, s: &ValueInfo<String>) {
+        if s.v.len() < 2 || !s.v.starts_with('"') || !s.v.ends_with('"') {
+            self.errors.push(format!(
+                "{}.{}: string literal {:?} is not quoted",
+                s.info.line, s.info.col, s.v
+            ));
+        }
+        self.position(s, "string literal");
+    }
 }
@@ -0,0 +1,743 @@
+use crate::parser::ast::*;
+
+/// Read-only traversal over the syntax tree.
+///
+/// Every `visit_*` method defaults to the matching `walk_*` function, which
+/// recurses into the children of the node. Override only the nodes you care
+/// about and call the `walk_*` function to keep descending.
+pub trait Visitor<'ast> {
+    fn visit_decs(&mut self, node: &'ast Decs) {
+        walk_decs(self, node)
+    }
+    fn visit_dec(&mut self, node: &'ast Dec) {
+        walk_dec(self, node)
+    }
+    fn visit_type_dec(&mut self, node: &'ast TypeDec) {
+        walk_type_dec(self, node)
+    }
+    fn visit_ty(&mut self, node: &'ast Ty) {
+        walk_ty(self, node)
+    }
+    fn visit_ty_fields(&mut self, node: &'ast TyFields) {
+        walk_ty_fields(self, node)
+    }
+    fn visit_some_ty_fields(&mut self, node: &'ast SomeTyFields) {
+        walk_some_ty_fields(self, node)
+    }
+    fn visit_var_dec(&mut self, node: &'ast VarDec) {
+        walk_var_dec(self, node)
+    }
+    fn visit_fun_dec(&mut self, node: &'ast FunDec) {
+        walk_fun_dec(self, node)
+    }
+    fn visit_exp_seq(&mut self, node: &'ast ExpSeq) {
+        walk_exp_seq(self, node)
+    }
+    fn visit_some_exp_seq(&mut self, node: &'ast SomeExpSeq) {
+        walk_some_exp_seq(self, node)
+    }
+    fn visit_exp(&mut self, node: &'ast Exp) {
+        walk_exp(self, node)
+    }
+    fn visit_match(&mut self, node: &'ast Match) {
+        walk_match(self, node)
+    }
+    fn visit_unmatch(&mut self, node: &'ast Unmatch) {
+        walk_unmatch(self, node)
+    }
+    fn visit_lvalue(&mut self, node: &'ast LValue) {
+        walk_lvalue(self, node)
+    }
+    fn visit_refer(&mut self, node: &'ast Refer) {
+        walk_refer(self, node)
+    }
+    fn visit_slice(&mut self, node: &'ast Slice) {
+        walk_slice(self, node)
+    }
+    fn visit_sheet(&mut self, node: &'ast Sheet) {
+        walk_sheet(self, node)
+    }
+    fn visit_piece(&mut self, node: &'ast Piece) {
+        walk_piece(self, node)
+    }
+    fn visit_bit(&mut self, node: &'ast Bit) {
+        walk_bit(self, node)
+    }
+    fn visit_item(&mut self, node: &'ast Item) {
+        walk_item(self, node)
+    }
+    fn visit_term(&mut self, node: &'ast Term) {
+        walk_term(self, node)
+    }
+    fn visit_factor(&mut self, node: &'ast Factor) {
+        walk_factor(self, node)
+    }
+    fn visit_meta(&mut self, node: &'ast Meta) {
+        walk_meta(self, node)
+    }
+    fn visit_args_list(&mut self, node: &'ast ArgsList) {
+        walk_args_list(self, node)
+    }
+    fn visit_some_args_list(&mut self, node: &'ast SomeArgsList) {
+        walk_some_args_list(self, node)
+    }
+    fn visit_rec_list(&mut self, node: &'ast RecList) {
+        walk_rec_list(self, node)
+    }
+    fn visit_some_rec_list(&mut self, node: &'ast SomeRecList) {
+        walk_some_rec_list(self, node)
+    }
+}
+
+pub fn walk_decs<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Decs) {
+    match node {
+        Decs::Empty => (),
+        Decs::Decs(dec, tail) => {
+            v.visit_dec(dec);
+            v.visit_decs(tail);
+        }
+    }
+}
+
+pub fn walk_dec<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Dec) {
+    match node {
+        Dec::TypeDec(dec) => v.visit_type_dec(dec),
+        Dec::VarDec(dec) => v.visit_var_dec(dec),
+        Dec::FunDec(dec) => v.visit_fun_dec(dec),
+    }
+}
+
+pub fn walk_type_dec<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast TypeDec) {
+    match node {
+        TypeDec::Dec(_, ty) => v.visit_ty(ty),
+    }
+}
+
+pub fn walk_ty<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Ty) {
+    match node {
+        Ty::Id(_) | Ty::Array(_) => (),
+        Ty::Fields(fields) => v.visit_ty_fields(fields),
+    }
+}
+
+pub fn walk_ty_fields<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast TyFields) {
+    match node {
+        TyFields::Empty => (),
+        TyFields::Some(fields) => v.visit_some_ty_fields(fields),
+    }
+}
+
+pub fn walk_some_ty_fields<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast SomeTyFields) {
+    match node {
+        SomeTyFields::Some { .. } => (),
+        SomeTyFields::List { tail, .. } => v.visit_some_ty_fields(tail),
+    }
+}
+
+pub fn walk_var_dec<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast VarDec) {
+    match node {
+        VarDec::DefaultInit(_, exp, _) => v.visit_exp(exp),
+        VarDec::TypeInit { exp, .. } => v.visit_exp(exp),
+    }
+}
+
+pub fn walk_fun_dec<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast FunDec) {
+    match node {
+        FunDec::Proc(_, fields, exp) => {
+            v.visit_ty_fields(fields);
+            v.visit_exp(exp);
+        }
+        FunDec::Func { fields, exp, .. } => {
+            v.visit_ty_fields(fields);
+            v.visit_exp(exp);
+        }
+    }
+}
+
+pub fn walk_exp_seq<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast ExpSeq) {
+    match node {
+        ExpSeq::Empty => (),
+        ExpSeq::Some(seq) => v.visit_some_exp_seq(seq),
+    }
+}
+
+pub fn walk_some_exp_seq<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast SomeExpSeq) {
+    match node {
+        SomeExpSeq::Some(exp) => v.visit_exp(exp),
+        SomeExpSeq::List(exp, tail) => {
+            v.visit_exp(exp);
+            v.visit_some_exp_seq(tail);
+        }
+    }
+}
+
+pub fn walk_exp<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Exp) {
+    match node {
+        Exp::Match(m) => v.visit_match(m),
+        Exp::Unmatch(u) => v.visit_unmatch(u),
+        Exp::Error => (),
+    }
+}
+
+pub fn walk_match<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Match) {
+    match node {
+        Match::IfThenElse {
+            if_exp,
+            then_exp,
+            else_exp,
+        } => {
+            v.visit_exp(if_exp);
+            v.visit_match(then_exp);
+            v.visit_match(else_exp);
+        }
+        Match::While { cond, body } => {
+            v.visit_exp(cond);
+            v.visit_match(body);
+        }
+        Match::For {
+            start, end, body, ..
+        } => {
+            v.visit_exp(start);
+            v.visit_exp(end);
+            v.visit_match(body);
+        }
+        Match::Assign(lvalue, slice) => {
+            v.visit_lvalue(lvalue);
+            v.visit_slice(slice);
+        }
+        Match::Slice(slice) => v.visit_slice(slice),
+        Match::Break => (),
+    }
+}
+
+pub fn walk_unmatch<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Unmatch) {
+    match node {
+        Unmatch::IfThen { if_exp, then_exp } => {
+            v.visit_exp(if_exp);
+            v.visit_exp(then_exp);
+        }
+        Unmatch::IfThenElse(if_exp, then_exp, else_exp) => {
+            v.visit_exp(if_exp);
+            v.visit_match(then_exp);
+            v.visit_unmatch(else_exp);
+        }
+        Unmatch::While { cond, body } => {
+            v.visit_exp(cond);
+            v.visit_unmatch(body);
+        }
+        Unmatch::For {
+            start, end, body, ..
+        } => {
+            v.visit_exp(start);
+            v.visit_exp(end);
+            v.visit_unmatch(body);
+        }
+    }
+}
+
+pub fn walk_lvalue<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast LValue) {
+    match node {
+        LValue::Id(_) => (),
+        LValue::Refer(refer) => v.visit_refer(refer),
+    }
+}
+
+pub fn walk_refer<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Refer) {
+    match node {
+        Refer::Field(_, _) => (),
+        Refer::ReferField(refer, _) => v.visit_refer(refer),
+        Refer::Array(_, index) => v.visit_exp(index),
+        Refer::ReferArray(refer, index) => {
+            v.visit_refer(refer);
+            v.visit_exp(index);
+        }
+    }
+}
+
+pub fn walk_slice<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Slice) {
+    match node {
+        Slice::Array { len, init, .. } => {
+            v.visit_exp(len);
+            v.visit_slice(init);
+        }
+        Slice::Record(_, rec_list) => v.visit_rec_list(rec_list),
+        Slice::Sheet(sheet) => v.visit_sheet(sheet),
+    }
+}
+
+pub fn walk_sheet<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Sheet) {
+    match node {
+        Sheet::Or(sheet, piece) => {
+            v.visit_sheet(sheet);
+            v.visit_piece(piece);
+        }
+        Sheet::Piece(piece) => v.visit_piece(piece),
+    }
+}
+
+pub fn walk_piece<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Piece) {
+    match node {
+        Piece::And(piece, bit) => {
+            v.visit_piece(piece);
+            v.visit_bit(bit);
+        }
+        Piece::Bit(bit) => v.visit_bit(bit),
+    }
+}
+
+pub fn walk_bit<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Bit) {
+    match node {
+        Bit::Eq(left, right)
+        | Bit::Neq(left, right)
+        | Bit::Lt(left, right)
+        | Bit::Le(left, right)
+        | Bit::Gt(left, right)
+        | Bit::Ge(left, right) => {
+            v.visit_item(left);
+            v.visit_item(right);
+        }
+        Bit::Item(item) => v.visit_item(item),
+    }
+}
+
+pub fn walk_item<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Item) {
+    match node {
+        Item::Plus(item, term) | Item::Minus(item, term) => {
+            v.visit_item(item);
+            v.visit_term(term);
+        }
+        Item::Term(term) => v.visit_term(term),
+    }
+}
+
+pub fn walk_term<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Term) {
+    match node {
+        Term::Times(term, factor) | Term::Divide(term, factor) => {
+            v.visit_term(term);
+            v.visit_factor(factor);
+        }
+        Term::Factor(factor) => v.visit_factor(factor),
+    }
+}
+
+pub fn walk_factor<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Factor) {
+    match node {
+        Factor::MMeta(meta) | Factor::Meta(meta) => v.visit_meta(meta),
+    }
+}
+
+pub fn walk_meta<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast Meta) {
+    match node {
+        Meta::Int(_) | Meta::String(_) | Meta::Nil | Meta::Id(_) => (),
+        Meta::Refer(refer) => v.visit_refer(refer),
+        Meta::CapSeq(seq) => v.visit_exp_seq(seq),
+        Meta::Call(_, args) => v.visit_args_list(args),
+        Meta::Let(decs, seq) => {
+            v.visit_decs(decs);
+            v.visit_exp_seq(seq);
+        }
+    }
+}
+
+pub fn walk_args_list<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast ArgsList) {
+    match node {
+        ArgsList::Empty => (),
+        ArgsList::Some(args) => v.visit_some_args_list(args),
+    }
+}
+
+pub fn walk_some_args_list<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast SomeArgsList) {
+    match node {
+        SomeArgsList::Some(exp) => v.visit_exp(exp),
+        SomeArgsList::List(exp, tail) => {
+            v.visit_exp(exp);
+            v.visit_some_args_list(tail);
+        }
+    }
+}
+
+pub fn walk_rec_list<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast RecList) {
+    match node {
+        RecList::Empty => (),
+        RecList::Some(rec_list) => v.visit_some_rec_list(rec_list),
+    }
+}
+
+pub fn walk_some_rec_list<'ast, V: Visitor<'ast> + ?Sized>(v: &mut V, node: &'ast SomeRecList) {
+    match node {
+        SomeRecList::Some(_, exp) => v.visit_exp(exp),
+        SomeRecList::List(_, exp, tail) => {
+            v.visit_exp(exp);
+            v.visit_some_rec_list(tail);
+        }
+    }
+}
+
+/// Mutable counterpart of [`Visitor`], for in-place rewrites of the tree.
+pub trait VisitorMut {
+    fn visit_decs_mut(&mut self, node: &mut Decs) {
+        walk_decs_mut(self, node)
+    }
+    fn visit_dec_mut(&mut self, node: &mut Dec) {
+        walk_dec_mut(self, node)
+    }
+    fn visit_type_dec_mut(&mut self, node: &mut TypeDec) {
+        walk_type_dec_mut(self, node)
+    }
+    fn visit_ty_mut(&mut self, node: &mut Ty) {
+        walk_ty_mut(self, node)
+    }
+    fn visit_ty_fields_mut(&mut self, node: &mut TyFields) {
+        walk_ty_fields_mut(self, node)
+    }
+    fn visit_some_ty_fields_mut(&mut self, node: &mut SomeTyFields) {
+        walk_some_ty_fields_mut(self, node)
+    }
+    fn visit_var_dec_mut(&mut self, node: &mut VarDec) {
+        walk_var_dec_mut(self, node)
+    }
+    fn visit_fun_dec_mut(&mut self, node: &mut FunDec) {
+        walk_fun_dec_mut(self, node)
+    }
+    fn visit_exp_seq_mut(&mut self, node: &mut ExpSeq) {
+        walk_exp_seq_mut(self, node)
+    }
+    fn visit_some_exp_seq_mut(&mut self, node: &mut SomeExpSeq) {
+        walk_some_exp_seq_mut(self, node)
+    }
+    fn visit_exp_mut(&mut self, node: &mut Exp) {
+        walk_exp_mut(self, node)
+    }
+    fn visit_match_mut(&mut self, node: &mut Match) {
+        walk_match_mut(self, node)
+    }
+    fn visit_unmatch_mut(&mut self, node: &mut Unmatch) {
+        walk_unmatch_mut(self, node)
+    }
+    fn visit_lvalue_mut(&mut self, node: &mut LValue) {
+        walk_lvalue_mut(self, node)
+    }
+    fn visit_refer_mut(&mut self, node: &mut Refer) {
+        walk_refer_mut(self, node)
+    }
+    fn visit_slice_mut(&mut self, node: &mut Slice) {
+        walk_slice_mut(self, node)
+    }
+    fn visit_sheet_mut(&mut self, node: &mut Sheet) {
+        walk_sheet_mut(self, node)
+    }
+    fn visit_piece_mut(&mut self, node: &mut Piece) {
+        walk_piece_mut(self, node)
+    }
+    fn visit_bit_mut(&mut self, node: &mut Bit) {
+        walk_bit_mut(self, node)
+    }
+    fn visit_item_mut(&mut self, node: &mut Item) {
+        walk_item_mut(self, node)
+    }
+    fn visit_term_mut(&mut self, node: &mut Term) {
+        walk_term_mut(self, node)
+    }
+    fn visit_factor_mut(&mut self, node: &mut Factor) {
+        walk_factor_mut(self, node)
+    }
+    fn visit_meta_mut(&mut self, node: &mut Meta) {
+        walk_meta_mut(self, node)
+    }
+    fn visit_args_list_mut(&mut self, node: &mut ArgsList) {
+        walk_args_list_mut(self, node)
+    }
+    fn visit_some_args_list_mut(&mut self, node: &mut SomeArgsList) {
+        walk_some_args_list_mut(self, node)
+    }
+    fn visit_rec_list_mut(&mut self, node: &mut RecList) {
+        walk_rec_list_mut(self, node)
+    }
+    fn visit_some_rec_list_mut(&mut self, node: &mut SomeRecList) {
+        walk_some_rec_list_mut(self, node)
+    }
+}
+
+pub fn walk_decs_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Decs) {
+    match node {
+        Decs::Empty => (),
+        Decs::Decs(dec, tail) => {
+            v.visit_dec_mut(dec);
+            v.visit_decs_mut(tail);
+        }
+    }
+}
+
+pub fn walk_dec_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Dec) {
+    match node {
+        Dec::TypeDec(dec) => v.visit_type_dec_mut(dec),
+        Dec::VarDec(dec) => v.visit_var_dec_mut(dec),
+        Dec::FunDec(dec) => v.visit_fun_dec_mut(dec),
+    }
+}
+
+pub fn walk_type_dec_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut TypeDec) {
+    match node {
+        TypeDec::Dec(_, ty) => v.visit_ty_mut(ty),
+    }
+}
+
+pub fn walk_ty_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Ty) {
+    match node {
+        Ty::Id(_) | Ty::Array(_) => (),
+        Ty::Fields(fields) => v.visit_ty_fields_mut(fields),
+    }
+}
+
+pub fn walk_ty_fields_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut TyFields) {
+    match node {
+        TyFields::Empty => (),
+        TyFields::Some(fields) => v.visit_some_ty_fields_mut(fields),
+    }
+}
+
+pub fn walk_some_ty_fields_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut SomeTyFields) {
+    match node {
+        SomeTyFields::Some { .. } => (),
+        SomeTyFields::List { tail, .. } => v.visit_some_ty_fields_mut(tail),
+    }
+}
+
+pub fn walk_var_dec_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut VarDec) {
+    match node {
+        VarDec::DefaultInit(_, exp, _) => v.visit_exp_mut(exp),
+        VarDec::TypeInit { exp, .. } => v.visit_exp_mut(exp),
+    }
+}
+
+pub fn walk_fun_dec_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut FunDec) {
+    match node {
+        FunDec::Proc(_, fields, exp) => {
+            v.visit_ty_fields_mut(fields);
+            v.visit_exp_mut(exp);
+        }
+        FunDec::Func { fields, exp, .. } => {
+            v.visit_ty_fields_mut(fields);
+            v.visit_exp_mut(exp);
+        }
+    }
+}
+
+pub fn walk_exp_seq_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut ExpSeq) {
+    match node {
+        ExpSeq::Empty => (),
+        ExpSeq::Some(seq) => v.visit_some_exp_seq_mut(seq),
+    }
+}
+
+pub fn walk_some_exp_seq_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut SomeExpSeq) {
+    match node {
+        SomeExpSeq::Some(exp) => v.visit_exp_mut(exp),
+        SomeExpSeq::List(exp, tail) => {
+            v.visit_exp_mut(exp);
+            v.visit_some_exp_seq_mut(tail);
+        }
+    }
+}
+
+pub fn walk_exp_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Exp) {
+    match node {
+        Exp::Match(m) => v.visit_match_mut(m),
+        Exp::Unmatch(u) => v.visit_unmatch_mut(u),
+        Exp::Error => (),
+    }
+}
+
+pub fn walk_match_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Match) {
+    match node {
+        Match::IfThenElse {
+            if_exp,
+            then_exp,
+            else_exp,
+        } => {
+            v.visit_exp_mut(if_exp);
+            v.visit_match_mut(then_exp);
+            v.visit_match_mut(else_exp);
+        }
+        Match::While { cond, body } => {
+            v.visit_exp_mut(cond);
+            v.visit_match_mut(body);
+        }
+        Match::For {
+            start, end, body, ..
+        } => {
+            v.visit_exp_mut(start);
+            v.visit_exp_mut(end);
+            v.visit_match_mut(body);
+        }
+        Match::Assign(lvalue, slice) => {
+            v.visit_lvalue_mut(lvalue);
+            v.visit_slice_mut(slice);
+        }
+        Match::Slice(slice) => v.visit_slice_mut(slice),
+        Match::Break => (),
+    }
+}
+
+pub fn walk_unmatch_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Unmatch) {
+    match node {
+        Unmatch::IfThen { if_exp, then_exp } => {
+            v.visit_exp_mut(if_exp);
+            v.visit_exp_mut(then_exp);
+        }
+        Unmatch::IfThenElse(if_exp, then_exp, else_exp) => {
+            v.visit_exp_mut(if_exp);
+            v.visit_match_mut(then_exp);
+            v.visit_unmatch_mut(else_exp);
+        }
+        Unmatch::While { cond, body } => {
+            v.visit_exp_mut(cond);
+            v.visit_unmatch_mut(body);
+        }
+        Unmatch::For {
+            start, end, body, ..
+        } => {
+            v.visit_exp_mut(start);
+            v.visit_exp_mut(end);
+            v.visit_unmatch_mut(body);
+        }
+    }
+}
+
+pub fn walk_lvalue_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut LValue) {
+    match node {
+        LValue::Id(_) => (),
+        LValue::Refer(refer) => v.visit_refer_mut(refer),
+    }
+}
+
+pub fn walk_refer_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Refer) {
+    match node {
+        Refer::Field(_, _) => (),
+        Refer::ReferField(refer, _) => v.visit_refer_mut(refer),
+        Refer::Array(_, index) => v.visit_exp_mut(index),
+        Refer::ReferArray(refer, index) => {
+            v.visit_refer_mut(refer);
+            v.visit_exp_mut(index);
+        }
+    }
+}
+
+pub fn walk_slice_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Slice) {
+    match node {
+        Slice::Array { len, init, .. } => {
+            v.visit_exp_mut(len);
+            v.visit_slice_mut(init);
+        }
+        Slice::Record(_, rec_list) => v.visit_rec_list_mut(rec_list),
+        Slice::Sheet(sheet) => v.visit_sheet_mut(sheet),
+    }
+}
+
+pub fn walk_sheet_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Sheet) {
+    match node {
+        Sheet::Or(sheet, piece) => {
+            v.visit_sheet_mut(sheet);
+            v.visit_piece_mut(piece);
+        }
+        Sheet::Piece(piece) => v.visit_piece_mut(piece),
+    }
+}
+
+pub fn walk_piece_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Piece) {
+    match node {
+        Piece::And(piece, bit) => {
+            v.visit_piece_mut(piece);
+            v.visit_bit_mut(bit);
+        }
+        Piece::Bit(bit) => v.visit_bit_mut(bit),
+    }
+}
+
+pub fn walk_bit_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Bit) {
+    match node {
+        Bit::Eq(left, right)
+        | Bit::Neq(left, right)
+        | Bit::Lt(left, right)
+        | Bit::Le(left, right)
+        | Bit::Gt(left, right)
+        | Bit::Ge(left, right) => {
+            v.visit_item_mut(left);
+            v.visit_item_mut(right);
+        }
+        Bit::Item(item) => v.visit_item_mut(item),
+    }
+}
+
+pub fn walk_item_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Item) {
+    match node {
+        Item::Plus(item, term) | Item::Minus(item, term) => {
+            v.visit_item_mut(item);
+            v.visit_term_mut(term);
+        }
+        Item::Term(term) => v.visit_term_mut(term),
+    }
+}
+
+pub fn walk_term_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Term) {
+    match node {
+        Term::Times(term, factor) | Term::Divide(term, factor) => {
+            v.visit_term_mut(term);
+            v.visit_factor_mut(factor);
+        }
+        Term::Factor(factor) => v.visit_factor_mut(factor),
+    }
+}
+
+pub fn walk_factor_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Factor) {
+    match node {
+        Factor::MMeta(meta) | Factor::Meta(meta) => v.visit_meta_mut(meta),
+    }
+}
+
+pub fn walk_meta_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut Meta) {
+    match node {
+        Meta::Int(_) | Meta::String(_) | Meta::Nil | Meta::Id(_) => (),
+        Meta::Refer(refer) => v.visit_refer_mut(refer),
+        Meta::CapSeq(seq) => v.visit_exp_seq_mut(seq),
+        Meta::Call(_, args) => v.visit_args_list_mut(args),
+        Meta::Let(decs, seq) => {
+            v.visit_decs_mut(decs);
+            v.visit_exp_seq_mut(seq);
+        }
+    }
+}
+
+pub fn walk_args_list_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut ArgsList) {
+    match node {
+        ArgsList::Empty => (),
+        ArgsList::Some(args) => v.visit_some_args_list_mut(args),
+    }
+}
+
+pub fn walk_some_args_list_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut SomeArgsList) {
+    match node {
+        SomeArgsList::Some(exp) => v.visit_exp_mut(exp),
+        SomeArgsList::List(exp, tail) => {
+            v.visit_exp_mut(exp);
+            v.visit_some_args_list_mut(tail);
+        }
+    }
+}
+
+pub fn walk_rec_list_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut RecList) {
+    match node {
+        RecList::Empty => (),
+        RecList::Some(rec_list) => v.visit_some_rec_list_mut(rec_list),
+    }
+}
+
+pub fn walk_some_rec_list_mut<V: VisitorMut + ?Sized>(v: &mut V, node: &mut SomeRecList) {
+    match node {
+        SomeRecList::Some(_, exp) => v.visit_exp_mut(exp),
+        SomeRecList::List(_, exp, tail) => {
+            v.visit_exp_mut(exp);
+            v.visit_some_rec_list_mut(tail);
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::liveness::liveness::InterferenceGraph;
+use crate::regalloc::regalloc::Allocation;
+use crate::temp::temp::Temp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Node {
+    Precolored,
+    Initial,
+    Simplify,
+    Freeze,
+    Spill,
+    Spilled,
+    Coalesced,
+    Colored,
+    OnStack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Move {
+    Coalesced,
+    Constrained,
+    Frozen,
+    Worklist,
+    Active,
+}
+
+/// Iterated register coalescing (George and Appel) over one interference
+/// graph. Returns the colors found and the temps that must be spilled.
+///
+/// `registers` are the colors; precolored temps keep their own register.
+/// `cost` estimates what spilling a temp would cost, and temps it prices at
+/// `f64::INFINITY` are spilled only when nothing else is left.
+pub fn color(
+    graph: &InterferenceGraph,
+    registers: &[Temp],
+    cost: impl Fn(Temp) -> f64,
+) -> (Allocation, Vec<Temp>) {
+    let mut color = Color::new(graph, registers);
+    color.make_worklist();
+    loop {
+        if let Some(n) = color.simplify_worklist.pop_first() {
+            color.simplify(n);
+        } else if let Some(m) = color.worklist_moves.pop_first() {
+            color.coalesce(m);
+        } else if let Some(n) = color.freeze_worklist.pop_first() {
+            color.freeze(n);
+        } else if !color.spill_worklist.is_empty() {
+            color.select_spill(&cost);
+        } else {
+            break;
+        }
+    }
+    color.assign_colors();
+    let allocation = (0..color.temps.len())
+        .filter(|n| color.state[*n] != Node::Precolored)
+        .filter_map(|n| color.color[n].map(|register| (color.temps[n], register)))
+        .collect();
+    let spilled = color.spilled.iter().map(|n| color.temps[*n]).collect();
+    (allocation, spilled)
+}
+
+struct Color<'a> {
+    registers: &'a [Temp],
+    k: usize,
+    temps: Vec<Temp>,
+    state: Vec<Node>,
+    adj_set: HashSet<(usize, usize)>,
+    adj_list: Vec<Vec<usize>>,
+    degree: Vec<usize>,
+    alias: Vec<usize>,
+    color: Vec<Option<Temp>>,
+    moves: Vec<(usize, usize)>,
+    move_state: Vec<Move>,
+    move_list: Vec<Vec<usize>>,
+    simplify_worklist: BTreeSet<usize>,
+    freeze_worklist: BTreeSet<usize>,
+    spill_worklist: BTreeSet<usize>,
+    worklist_moves: BTreeSet<usize>,
+    select_stack: Vec<usize>,
+    coalesced: Vec<usize>,
+    spilled: Vec<usize>,
+}
+
+impl<'a> Color<'a> {
+    fn new(graph: &InterferenceGraph, registers: &'a [Temp]) -> Color<'a> {
+        let temps = graph.temps().to_vec();
+        let index: HashMap<Temp, usize> = temps
+            .iter()
+            .enumerate()
+            .map(|(index, temp)| (*temp, index))
+            .collect();
+        let n = temps.len();
+        let mut color = Color {
+            registers,
+            k: registers.len(),
+            state: temps
+                .iter()
+                .map(|temp| match temp.is_precolored() {
+                    true => Node::Precolored,
+                    false => Node::Initial,
+                })
+                .collect(),
+            color: temps
+                .iter()
+                .map(|temp| temp.is_precolored().then_some(*temp))
+                .collect(),
+            temps,
+            adj_set: HashSet::new(),
+            adj_list: vec![Vec::new(); n],
+            degree: vec![0; n],
+            alias: (0..n).collect(),
+            moves: Vec::new(),
+            move_state: Vec::new(),
+            move_list: vec![Vec::new(); n],
+            simplify_worklist: BTreeSet::new(),
+            freeze_worklist: BTreeSet::new(),
+            spill_worklist: BTreeSet::new(),
+            worklist_moves: BTreeSet::new(),
+            select_stack: Vec::new(),
+            coalesced: Vec::new(),
+            spilled: Vec::new(),
+        };
+        for u in 0..n {
+            if color.precolored(u) {
+                // Precolored nodes never leave the graph.
+                color.degree[u] = usize::MAX / 2;
+            }
+        }
+        for u in 0..n {
+            for temp in graph.adjacent(color.temps[u]) {
+                color.add_edge(u, index[&temp]);
+            }
+        }
+        for (dst, src) in graph.moves() {
+            let m = color.moves.len();
+            let (dst, src) = (index[dst], index[src]);
+            color.moves.push((dst, src));
+            color.move_state.push(Move::Worklist);
+            color.worklist_moves.insert(m);
+            color.move_list[dst].push(m);
+            color.move_list[src].push(m);
+        }
+        color
+    }
+
+    fn precolored(&self, n: usize) -> bool {
+        self.state[n] == Node::Precolored
+    }
+
+    fn add_edge(&mut self, u: usize, v: usize) {
+        if u == v || self.adj_set.contains(&(u, v)) {
+            return;
+        }
+        self.adj_set.insert((u, v));
+        self.adj_set.insert((v, u));
+        if !self.precolored(u) {
+            self.adj_list[u].push(v);
+            self.degree[u] += 1;
+        }
+        if !self.precolored(v) {
+            self.adj_list[v].push(u);
+            self.degree[v] += 1;
+        }
+    }
+
+    fn make_worklist(&mut self) {
+        for n in 0..self.temps.len() {
+            if self.state[n] != Node::Initial {
+                continue;
+            }
+            if self.degree[n] >= self.k {
+                self.set_state(n, Node::Spill);
+            } else if self.move_related(n) {
+                self.set_state(n, Node::Freeze);
+            } else {
+                self.set_state(n, Node::Simplify);
+            }
+        }
+    }
+
+    /// Moves `n` to the work list or set that `state` stands for.
+    fn set_state(&mut self, n: usize, state: Node) {
+        match self.state[n] {
+            Node::Simplify => self.simplify_worklist.remove(&n),
+            Node::Freeze => self.freeze_worklist.remove(&n),
+            Node::Spill => self.spill_worklist.remove(&n),
+            _ => false,
+        };
+        match state {
+            Node::Simplify => self.simplify_worklist.insert(n),
+            Node::Freeze => self.freeze_worklist.insert(n),
+            Node::Spill => self.spill_worklist.insert(n),
+            _ => false,
+        };
+        self.state[n] = state;
+    }
+
+    fn adjacent(&self, n: usize) -> Vec<usize> {
+        self.adj_list[n]
+            .iter()
+            .copied()
+            .filter(|m| !matches!(self.state[*m], Node::OnStack | Node::Coalesced))
+            .collect()
+    }
+
+    fn node_moves(&self, n: usize) -> Vec<usize> {
+        self.move_list[n]
+            .iter()
+            .copied()
+            .filter(|m| matches!(self.move_state[*m], Move::Active | Move::Worklist))
+            .collect()
+    }
+
+    fn move_related(&self, n: usize) -> bool {
+        !self.node_moves(n).is_empty()
+    }
+
+    fn simplify(&mut self, n: usize) {
+        self.state[n] = Node::OnStack;
+        self.select_stack.push(n);
+        for m in self.adjacent(n) {
+            self.decrement_degree(m);
+        }
+    }
+
+    fn decrement_degree(&mut self, m: usize) {
+        if self.precolored(m) {
+            return;
+        }
+        let d = self.degree[m];
+        self.degree[m] = d - 1;
+        if d == self.k {
+            let mut nodes = self.adjacent(m);
+            nodes.push(m);
+            self.enable_moves(&nodes);
+            if self.state[m] == Node::Spill {
+                let state = match self.move_related(m) {
+                    true => Node::Freeze,
+                    false => Node::Simplify,
+                };
+                self.set_state(m, state);
+            }
+        }
+    }
+
+    fn enable_moves(&mut self, nodes: &[usize]) {
+        for n in nodes {
+            for m in self.node_moves(*n) {
+                if self.move_state[m] == Move::Active {
+                    self.move_state[m] = Move::Worklist;
+                    self.worklist_moves.insert(m);
+                }
+            }
+        }
+    }
+
+    fn add_worklist(&mut self, u: usize) {
+        if !self.precolored(u) && !self.move_related(u) && self.degree[u] < self.k {
+            self.set_state(u, Node::Simplify);
+        }
+    }
+
+    /// George's test: `t` cannot stop `u` from getting a register.
+    fn ok(&self, t: usize, r: usize) -> bool {
+        self.degree[t] < self.k || self.precolored(t) || self.adj_set.contains(&(t, r))
+    }
+
+    /// Briggs's test: the merged node has fewer than K significant
+    /// neighbours.
+    fn conservative(&self, nodes: &BTreeSet<usize>) -> bool {
+        nodes.iter().filter(|n| self.degree[**n] >= self.k).count() < self.k
+    }
+
+    fn get_alias(&self, mut n: usize) -> usize {
+        while self.state[n] == Node::Coalesced {
+            n = self.alias[n];
+        }
+        n
+    }
+
+    fn coalesce(&mut self, m: usize) {
+        let (x, y) = self.moves[m];
+        let (x, y) = (self.get_alias(x), self.get_alias(y));
+        let (u, v) = if self.precolored(y) { (y, x) } else { (x, y) };
+        if u == v {
+            self.move_state[m] = Move::Coalesced;
+            self.add_worklist(u);
+        } else if self.precolored(v) || self.adj_set.contains(&(u, v)) {
+            self.move_state[m] = Move::Constrained;
+            self.add_worklist(u);
+            self.add_worklist(v);
+        } else if (self.precolored(u) && self.adjacent(v).iter().all(|t| self.ok(*t, u)))
+            || (!self.precolored(u)
+                && self.conservative(
+                    &self
+                        .adjacent(u)
+                        .into_iter()
+                        .chain(self.adjacent(v))
+                        .collect(),
+                ))
+        {
+            self.move_state[m] = Move::Coalesced;
+            self.combine(u, v);
+            self.add_worklist(u);
+        } else {
+            self.move_state[m] = Move::Active;
+        }
+    }
+
+    fn combine(&mut self, u: usize, v: usize) {
+        self.set_state(v, Node::Coalesced);
+        self.coalesced.push(v);
+        self.alias[v] = u;
+        let moves = self.move_list[v].clone();
+        self.move_list[u].extend(moves);
+        self.enable_moves(&[v]);
+        for t in self.adjacent(v) {
+            self.add_edge(t, u);
+            self.decrement_degree(t);
+        }
+        if self.degree[u] >= self.k && self.state[u] == Node::Freeze {
+            self.set_state(u, Node::Spill);
+        }
+    }
+
+    fn freeze(&mut self, u: usize) {
+        self.state[u] = Node::Initial;
+        self.set_state(u, Node::Simplify);
+        self.freeze_moves(u);
+    }
+
+    fn freeze_moves(&mut self, u: usize) {
+        for m in self.node_moves(u) {
+            let (x, y) = self.moves[m];
+            let v = if self.get_alias(y) == self.get_alias(u) {
+                self.get_alias(x)
+            } else {
+                self.get_alias(y)
+            };
+            self.move_state[m] = Move::Frozen;
+            if self.state[v] == Node::Freeze && !self.move_related(v) && self.degree[v] < self.k {
+                self.set_state(v, Node::Simplify);
+            }
+        }
+    }
+
+    fn select_spill(&mut self, cost: &impl Fn(Temp) -> f64) {
+        let m = *self
+            .spill_worklist
+            .iter()
+            .min_by(|a, b| {
+                let a = cost(self.temps[**a]) / self.degree[**a] as f64;
+                let b = cost(self.temps[**b]) / self.degree[**b] as f64;
+                a.total_cmp(&b)
+            })
+            .unwrap();
+        self.set_state(m, Node::Simplify);
+        self.freeze_moves(m);
+    }
+
+    fn assign_colors(&mut self) {
+        while let Some(n) = self.select_stack.pop() {
+            let taken: HashSet<Temp> = self.adj_list[n]
+                .iter()
+                .map(|w| self.get_alias(*w))
+                .filter(|w| matches!(self.state[*w], Node::Colored | Node::Precolored))
+                .filter_map(|w| self.color[w])
+                .collect();
+            match self.registers.iter().find(|r| !taken.contains(r)) {
+                Some(register) => {
+                    self.state[n] = Node::Colored;
+                    self.color[n] = Some(*register);
+                }
+                None => {
+                    self.state[n] = Node::Spilled;
+                    self.spilled.push(n);
+                }
+            }
+        }
+        for n in self.coalesced.clone() {
+            self.color[n] = self.color[self.get_alias(n)];
+        }
+    }
+}
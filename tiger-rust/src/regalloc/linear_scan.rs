@@ -0,0 +1,145 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::flowgraph::flowgraph::FlowGraph;
+use crate::liveness::liveness::Liveness;
+use crate::regalloc::regalloc::Allocation;
+use crate::temp::temp::Temp;
+
+/// The stretch over which a temp must keep its register. Instruction `n`
+/// reads its uses at point `2n` and writes its defs at `2n + 1`, so a temp
+/// that dies at `n` can hand its register to one defined there.
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    temp: Temp,
+    start: usize,
+    end: usize,
+}
+
+/// Linear-scan allocation (Poletto and Sarkar) over the numbered
+/// instructions of `flow`. Faster than coloring but blind to holes in live
+/// ranges, so it tends to spill more. Returns the registers given and the
+/// temps that must be spilled; temps in `no_spill` are never chosen.
+pub fn linear_scan(
+    flow: &FlowGraph,
+    liveness: &Liveness,
+    registers: &[Temp],
+    no_spill: &HashSet<Temp>,
+) -> (Allocation, Vec<Temp>) {
+    let intervals = intervals(flow, liveness);
+    let blocked = precolored_conflicts(flow, liveness);
+    let hints = move_hints(flow);
+    let no_conflicts = HashSet::new();
+    let mut allocation = Allocation::new();
+    let mut spilled = Vec::new();
+    // Active intervals by end, each holding a register.
+    let mut active: BTreeSet<(usize, Temp)> = BTreeSet::new();
+    let mut holder: HashMap<Temp, Temp> = HashMap::new();
+    for interval in intervals {
+        while let Some(&(end, temp)) = active.first() {
+            if end >= interval.start {
+                break;
+            }
+            active.pop_first();
+            holder.remove(&allocation[&temp]);
+        }
+        let blocked = blocked.get(&interval.temp).unwrap_or(&no_conflicts);
+        let free = |register: &Temp| !holder.contains_key(register) && !blocked.contains(register);
+        let hinted = hints.get(&interval.temp).and_then(|partners| {
+            partners
+                .iter()
+                .filter_map(|partner| match partner.is_precolored() {
+                    true => Some(*partner),
+                    false => allocation.get(partner).copied(),
+                })
+                .find(|register| registers.contains(register) && free(register))
+        });
+        if let Some(register) = hinted.or_else(|| registers.iter().copied().find(free)) {
+            allocation.insert(interval.temp, register);
+            holder.insert(register, interval.temp);
+            active.insert((interval.end, interval.temp));
+            continue;
+        }
+        // Spill whichever interval ends last, if handing its register over
+        // is allowed.
+        let victim = active
+            .iter()
+            .rev()
+            .find(|(_, temp)| !no_spill.contains(temp) && !blocked.contains(&allocation[temp]))
+            .copied();
+        match victim {
+            Some((end, temp)) if no_spill.contains(&interval.temp) || end > interval.end => {
+                let register = allocation.remove(&temp).unwrap();
+                active.remove(&(end, temp));
+                spilled.push(temp);
+                allocation.insert(interval.temp, register);
+                holder.insert(register, interval.temp);
+                active.insert((interval.end, interval.temp));
+            }
+            _ => spilled.push(interval.temp),
+        }
+    }
+    (allocation, spilled)
+}
+
+fn intervals(flow: &FlowGraph, liveness: &Liveness) -> Vec<Interval> {
+    let mut ranges: HashMap<Temp, (usize, usize)> = HashMap::new();
+    let mut extend = |temp: Temp, point: usize| {
+        if temp.is_precolored() {
+            return;
+        }
+        let range = ranges.entry(temp).or_insert((point, point));
+        range.0 = range.0.min(point);
+        range.1 = range.1.max(point);
+    };
+    for (node, flow_node) in flow.nodes.iter().enumerate() {
+        for temp in &flow_node.uses {
+            extend(*temp, 2 * node);
+        }
+        for temp in flow_node.def.iter().copied().chain(liveness.live_out(node)) {
+            extend(temp, 2 * node + 1);
+        }
+    }
+    let mut intervals: Vec<Interval> = ranges
+        .into_iter()
+        .map(|(temp, (start, end))| Interval { temp, start, end })
+        .collect();
+    intervals.sort_by_key(|interval| (interval.start, interval.temp));
+    intervals
+}
+
+/// For each temp, the machine registers it interferes with.
+fn precolored_conflicts(flow: &FlowGraph, liveness: &Liveness) -> HashMap<Temp, HashSet<Temp>> {
+    let mut blocked: HashMap<Temp, HashSet<Temp>> = HashMap::new();
+    for (node, flow_node) in flow.nodes.iter().enumerate() {
+        let src = match (flow_node.is_move, flow_node.uses.as_slice()) {
+            (true, [src]) => Some(*src),
+            _ => None,
+        };
+        let live_out = liveness.live_out(node);
+        for def in &flow_node.def {
+            for live in &live_out {
+                if live == def || Some(*live) == src {
+                    continue;
+                }
+                match (def.is_precolored(), live.is_precolored()) {
+                    (false, true) => blocked.entry(*def).or_default().insert(*live),
+                    (true, false) => blocked.entry(*live).or_default().insert(*def),
+                    _ => false,
+                };
+            }
+        }
+    }
+    blocked
+}
+
+/// For each temp, the temps it is copied to or from.
+fn move_hints(flow: &FlowGraph) -> HashMap<Temp, Vec<Temp>> {
+    let mut hints: HashMap<Temp, Vec<Temp>> = HashMap::new();
+    for flow_node in flow.nodes.iter().filter(|node| node.is_move) {
+        if let ([dst], [src]) = (flow_node.def.as_slice(), flow_node.uses.as_slice()) {
+            hints.entry(*dst).or_default().push(*src);
+            hints.entry(*src).or_default().push(*dst);
+        }
+    }
+    hints
+}
@@ -0,0 +1,3 @@
+pub mod color;
+pub mod linear_scan;
+pub mod regalloc;
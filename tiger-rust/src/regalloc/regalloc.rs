@@ -0,0 +1,163 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::assem::assem::Instr;
+use crate::codegen::codegen::{alloc_slot, Codegen};
+use crate::flowgraph::flowgraph::FlowGraph;
+use crate::liveness::liveness::{InterferenceGraph, Liveness};
+use crate::regalloc::color::color;
+use crate::regalloc::linear_scan::linear_scan;
+use crate::temp::temp::Temp;
+
+/// The register given to each temp; precolored temps are left out.
+pub type Allocation = HashMap<Temp, Temp>;
+
+/// Gives each temp in `spilled` a stack slot and rewrites `instrs` so that
+/// every use loads into, and every definition stores from, a fresh temp
+/// that lives only across that one instruction. Returns the fresh temps of
+/// each original instruction next to the rewritten code.
+pub fn rewrite<F: Codegen>(
+    frame: &mut F,
+    instrs: Vec<Instr>,
+    spilled: &BTreeSet<Temp>,
+) -> (Vec<Instr>, Vec<Vec<Temp>>) {
+    let slots: HashMap<Temp, i64> = spilled
+        .iter()
+        .map(|temp| (*temp, alloc_slot(frame)))
+        .collect();
+    let mut out = Vec::with_capacity(instrs.len());
+    let mut fresh = Vec::with_capacity(instrs.len());
+    for instr in instrs {
+        let mut renamed: Vec<(Temp, Temp)> = Vec::new();
+        let mut rename = |temp: Temp| {
+            if !slots.contains_key(&temp) {
+                return temp;
+            }
+            match renamed.iter().find(|(old, _)| *old == temp) {
+                Some((_, new)) => *new,
+                None => {
+                    let new = Temp::new();
+                    renamed.push((temp, new));
+                    new
+                }
+            }
+        };
+        let uses: Vec<Temp> = instr.uses().iter().map(|temp| rename(*temp)).collect();
+        let defs: Vec<Temp> = instr.defs().iter().map(|temp| rename(*temp)).collect();
+        let old_uses: Vec<Temp> = instr.uses().to_vec();
+        let old_defs: Vec<Temp> = instr.defs().to_vec();
+        let instr = instr.map_temps(&mut rename);
+        let mut loaded = Vec::new();
+        for (old, new) in old_uses.iter().zip(&uses) {
+            if slots.contains_key(old) && !loaded.contains(new) {
+                out.push(F::load(*new, slots[old]));
+                loaded.push(*new);
+            }
+        }
+        out.push(instr);
+        let mut stored = Vec::new();
+        for (old, new) in old_defs.iter().zip(&defs) {
+            if slots.contains_key(old) && !stored.contains(new) {
+                out.push(F::store(*new, slots[old]));
+                stored.push(*new);
+            }
+        }
+        fresh.push(renamed.into_iter().map(|(_, new)| new).collect());
+    }
+    (out, fresh)
+}
+
+/// The outcome of allocating one function.
+pub struct Allocated {
+    pub instrs: Vec<Instr>,
+    pub allocation: Allocation,
+    /// Temps that ended up in stack slots.
+    pub spilled: Vec<Temp>,
+}
+
+/// How registers are assigned to temps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Allocator {
+    /// Iterated register coalescing: fewer spills and moves.
+    #[default]
+    Coloring,
+    /// Linear scan: much faster on large functions.
+    LinearScan,
+}
+
+/// Assigns registers to the temps of `instrs`, spilling and rewriting until
+/// every temp gets one.
+pub fn allocate<F: Codegen>(
+    frame: &mut F,
+    mut instrs: Vec<Instr>,
+    allocator: Allocator,
+) -> Allocated {
+    let registers = F::registers();
+    let mut spill_temps: HashSet<Temp> = HashSet::new();
+    let mut spilled = Vec::new();
+    loop {
+        let flow = FlowGraph::new(&instrs);
+        let liveness = Liveness::new(&flow);
+        let (allocation, spills) = match allocator {
+            Allocator::Coloring => {
+                let graph = InterferenceGraph::new(&flow, &liveness);
+                let uses = use_counts(&instrs);
+                color(&graph, &registers, |temp| {
+                    match spill_temps.contains(&temp) {
+                        true => f64::INFINITY,
+                        false => uses.get(&temp).copied().unwrap_or(0) as f64,
+                    }
+                })
+            }
+            Allocator::LinearScan => linear_scan(&flow, &liveness, &registers, &spill_temps),
+        };
+        if spills.is_empty() {
+            return Allocated {
+                instrs,
+                allocation,
+                spilled,
+            };
+        }
+        let spills: BTreeSet<Temp> = spills.into_iter().collect();
+        let (rewritten, fresh) = rewrite(frame, instrs, &spills);
+        instrs = rewritten;
+        spill_temps.extend(fresh.into_iter().flatten());
+        spilled.extend(spills);
+    }
+}
+
+/// How many times each temp is used or defined.
+pub fn use_counts(instrs: &[Instr]) -> HashMap<Temp, usize> {
+    let mut counts = HashMap::new();
+    for instr in instrs {
+        for temp in instr.uses().iter().chain(instr.defs()) {
+            *counts.entry(*temp).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Checks that `allocation` gives every temp of `instrs` a register and never
+/// gives one register to two temps that interfere.
+pub fn check<F: Codegen>(instrs: &[Instr], allocation: &Allocation) -> Result<(), String> {
+    let flow = FlowGraph::new(instrs);
+    let graph = InterferenceGraph::new(&flow, &Liveness::new(&flow));
+    let register = |temp: Temp| match temp.is_precolored() {
+        true => Some(temp),
+        false => allocation.get(&temp).copied(),
+    };
+    for temp in graph.temps() {
+        let color = register(*temp)
+            .ok_or_else(|| format!("{} has no register", F::register_name(*temp)))?;
+        for other in graph.adjacent(*temp) {
+            if register(other) == Some(color) {
+                return Err(format!(
+                    "{} and {} interfere but share {}",
+                    F::register_name(*temp),
+                    F::register_name(other),
+                    F::register_name(color)
+                ));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,3 @@
+pub mod semant;
+pub mod tast;
+pub mod types;
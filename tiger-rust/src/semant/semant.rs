@@ -0,0 +1,994 @@
+use std::collections::HashSet;
+
+use crate::error::error::PosInfo;
+use crate::escape::escape::find_escape;
+use crate::lexer::lexer::{unescape, ValueInfo};
+use crate::parser::arena::Ast;
+use crate::parser::ast::*;
+use crate::semant::tast::*;
+use crate::semant::types::{Type, TypeDef, Types};
+use crate::temp::temp::Label;
+
+/// Runtime functions every program can call, as (name, parameters, result).
+const BUILTINS: [(&str, &[Type], Type); 10] = [
+    ("print", &[Type::String], Type::Unit),
+    ("flush", &[], Type::Unit),
+    ("getchar", &[], Type::String),
+    ("ord", &[Type::String], Type::Int),
+    ("chr", &[Type::Int], Type::String),
+    ("size", &[Type::String], Type::Int),
+    (
+        "substring",
+        &[Type::String, Type::Int, Type::Int],
+        Type::String,
+    ),
+    ("concat", &[Type::String, Type::String], Type::String),
+    ("not", &[Type::Int], Type::Int),
+    ("exit", &[Type::Int], Type::Unit),
+];
+
+/// [`trans_prog`] on a tree held in an [`Ast`] arena.
+pub fn trans_ast(ast: &Ast) -> Result<Program, String> {
+    ast.with_exp(trans_prog)
+}
+
+/// Type-checks a parsed program and resolves every name in it.
+///
+/// Runs [`find_escape`] first, so the escape flags in the result are final.
+/// Stops at the first error, reported as `line.col: message`.
+pub fn trans_prog(exp: &Exp) -> Result<Program, String> {
+    find_escape(exp);
+    let mut semant = Semant {
+        types: Types::default(),
+        vars: Vec::new(),
+        funcs: Vec::new(),
+        readonly: HashSet::new(),
+        venv: Vec::new(),
+        tenv: vec![
+            (String::from("int"), Type::Int),
+            (String::from("string"), Type::String),
+        ],
+        fun: FunId(0),
+        loops: 0,
+        pos: PosInfo { line: 1, col: 1 },
+    };
+    for (name, params, result) in BUILTINS {
+        semant.funcs.push(FunInfo {
+            name: String::from(name),
+            label: Label::named(name),
+            params: Vec::new(),
+            param_types: params.to_vec(),
+            result,
+            parent: None,
+            kind: FunKind::Builtin,
+            pos: semant.pos.clone(),
+        });
+        let id = FunId(semant.funcs.len() - 1);
+        semant.venv.push((String::from(name), Entry::Fun(id)));
+    }
+    let main = FunId(semant.funcs.len());
+    semant.funcs.push(FunInfo {
+        name: String::from("tigermain"),
+        label: Label::named("tigermain"),
+        params: Vec::new(),
+        param_types: Vec::new(),
+        result: Type::Unit,
+        parent: None,
+        kind: FunKind::Builtin,
+        pos: semant.pos.clone(),
+    });
+    semant.fun = main;
+    let body = semant.exp(exp)?;
+    semant.funcs[main.0].result = body.ty;
+    semant.funcs[main.0].kind = FunKind::User { body };
+    Ok(Program {
+        types: semant.types,
+        vars: semant.vars,
+        funcs: semant.funcs,
+        main,
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Entry {
+    Var(VarId),
+    Fun(FunId),
+}
+
+struct Semant {
+    types: Types,
+    vars: Vec<VarInfo>,
+    funcs: Vec<FunInfo>,
+    /// `for` counters, which the loop body may not assign.
+    readonly: HashSet<VarId>,
+    venv: Vec<(String, Entry)>,
+    tenv: Vec<(String, Type)>,
+    fun: FunId,
+    /// Number of loops around the current expression within its function.
+    loops: usize,
+    /// Position of the last token seen, for nodes that carry none.
+    pos: PosInfo,
+}
+
+fn error<T>(pos: &PosInfo, msg: String) -> Result<T, String> {
+    Err(format!("{}.{}: {}", pos.line, pos.col, msg))
+}
+
+impl Semant {
+    fn at<V>(&mut self, token: &ValueInfo<V>) -> PosInfo {
+        self.pos = token.info.clone();
+        self.pos.clone()
+    }
+
+    fn texp(&self, kind: TExpKind, ty: Type, pos: PosInfo) -> TExp {
+        TExp { kind, ty, pos }
+    }
+
+    fn show(&self, ty: Type) -> String {
+        self.types.display(ty).to_string()
+    }
+
+    fn lookup_type(&mut self, id: &ValueInfo<String>) -> Result<Type, String> {
+        let pos = self.at(id);
+        match self.tenv.iter().rev().find(|(name, _)| *name == id.v) {
+            Some((_, ty)) => Ok(*ty),
+            None => error(&pos, format!("undefined type {}", id.v)),
+        }
+    }
+
+    fn lookup_value(&mut self, id: &ValueInfo<String>) -> Result<Entry, String> {
+        let pos = self.at(id);
+        match self.venv.iter().rev().find(|(name, _)| *name == id.v) {
+            Some((_, entry)) => Ok(*entry),
+            None => error(&pos, format!("undefined variable {}", id.v)),
+        }
+    }
+
+    fn new_var(&mut self, id: &ValueInfo<String>, ty: Type, escape: &Escape) -> VarId {
+        self.vars.push(VarInfo {
+            name: id.v.clone(),
+            ty,
+            escape: escape.get(),
+            fun: self.fun,
+            pos: id.info.clone(),
+        });
+        let var = VarId(self.vars.len() - 1);
+        self.venv.push((id.v.clone(), Entry::Var(var)));
+        var
+    }
+
+    fn expect(&self, exp: &TExp, ty: Type, what: &str) -> Result<(), String> {
+        if self.types.assignable(exp.ty, ty) {
+            Ok(())
+        } else {
+            error(
+                &exp.pos,
+                format!(
+                    "{}: expected {}, found {}",
+                    what,
+                    self.show(ty),
+                    self.show(exp.ty)
+                ),
+            )
+        }
+    }
+
+    fn exp(&mut self, node: &Exp) -> Result<TExp, String> {
+        match node {
+            Exp::Match(m) => self.matched(m),
+            Exp::Unmatch(u) => self.unmatched(u),
+            Exp::Error => error(&self.pos, String::from("syntax error")),
+        }
+    }
+
+    fn matched(&mut self, node: &Match) -> Result<TExp, String> {
+        match node {
+            Match::IfThenElse {
+                if_exp,
+                then_exp,
+                else_exp,
+            } => {
+                let test = self.exp(if_exp)?;
+                let then = self.matched(then_exp)?;
+                let other = self.matched(else_exp)?;
+                self.if_exp(test, then, Some(other))
+            }
+            Match::While { cond, body } => {
+                let test = self.exp(cond)?;
+                self.loops += 1;
+                let body = self.matched(body);
+                self.loops -= 1;
+                self.while_exp(test, body?)
+            }
+            Match::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            } => self.for_exp(id, escape, start, end, |s| s.matched(body)),
+            Match::Assign(lvalue, slice) => {
+                let var = self.lvalue(lvalue)?;
+                if let TVarKind::Simple(id) = var.kind {
+                    if self.readonly.contains(&id) {
+                        return error(
+                            &var.pos,
+                            format!("cannot assign to loop variable {}", self.vars[id.0].name),
+                        );
+                    }
+                }
+                let value = self.slice(slice)?;
+                self.expect(&value, var.ty, "type mismatch in assignment")?;
+                let pos = var.pos.clone();
+                Ok(self.texp(TExpKind::Assign(var, Box::new(value)), Type::Unit, pos))
+            }
+            Match::Slice(slice) => self.slice(slice),
+            Match::Break => {
+                if self.loops == 0 {
+                    return error(&self.pos, String::from("break outside of a loop"));
+                }
+                Ok(self.texp(TExpKind::Break, Type::Unit, self.pos.clone()))
+            }
+        }
+    }
+
+    fn unmatched(&mut self, node: &Unmatch) -> Result<TExp, String> {
+        match node {
+            Unmatch::IfThen { if_exp, then_exp } => {
+                let test = self.exp(if_exp)?;
+                let then = self.exp(then_exp)?;
+                self.if_exp(test, then, None)
+            }
+            Unmatch::IfThenElse(if_exp, then_exp, else_exp) => {
+                let test = self.exp(if_exp)?;
+                let then = self.matched(then_exp)?;
+                let other = self.unmatched(else_exp)?;
+                self.if_exp(test, then, Some(other))
+            }
+            Unmatch::While { cond, body } => {
+                let test = self.exp(cond)?;
+                self.loops += 1;
+                let body = self.unmatched(body);
+                self.loops -= 1;
+                self.while_exp(test, body?)
+            }
+            Unmatch::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            } => self.for_exp(id, escape, start, end, |s| s.unmatched(body)),
+        }
+    }
+
+    fn if_exp(&mut self, test: TExp, then: TExp, other: Option<TExp>) -> Result<TExp, String> {
+        self.expect(&test, Type::Int, "if condition")?;
+        let pos = test.pos.clone();
+        let ty = match &other {
+            None => {
+                self.expect(
+                    &then,
+                    Type::Unit,
+                    "if-then without else must not return a value",
+                )?;
+                Type::Unit
+            }
+            Some(other) => {
+                if self.types.assignable(then.ty, other.ty) {
+                    other.ty
+                } else if self.types.assignable(other.ty, then.ty) {
+                    then.ty
+                } else {
+                    return error(
+                        &other.pos,
+                        format!(
+                            "types of then and else differ: {} and {}",
+                            self.show(then.ty),
+                            self.show(other.ty)
+                        ),
+                    );
+                }
+            }
+        };
+        Ok(self.texp(
+            TExpKind::If(Box::new(test), Box::new(then), other.map(Box::new)),
+            ty,
+            pos,
+        ))
+    }
+
+    fn while_exp(&mut self, test: TExp, body: TExp) -> Result<TExp, String> {
+        self.expect(&test, Type::Int, "while condition")?;
+        self.expect(&body, Type::Unit, "body of while must not return a value")?;
+        let pos = test.pos.clone();
+        Ok(self.texp(
+            TExpKind::While(Box::new(test), Box::new(body)),
+            Type::Unit,
+            pos,
+        ))
+    }
+
+    fn for_exp(
+        &mut self,
+        id: &ValueInfo<String>,
+        escape: &Escape,
+        start: &Exp,
+        end: &Exp,
+        body: impl FnOnce(&mut Self) -> Result<TExp, String>,
+    ) -> Result<TExp, String> {
+        let pos = self.at(id);
+        let lo = self.exp(start)?;
+        self.expect(&lo, Type::Int, "for loop lower bound")?;
+        let hi = self.exp(end)?;
+        self.expect(&hi, Type::Int, "for loop upper bound")?;
+        let scope = self.venv.len();
+        let var = self.new_var(id, Type::Int, escape);
+        self.readonly.insert(var);
+        self.loops += 1;
+        let body = body(self);
+        self.loops -= 1;
+        self.venv.truncate(scope);
+        let body = body?;
+        self.expect(&body, Type::Unit, "body of for must not return a value")?;
+        Ok(self.texp(
+            TExpKind::For(var, Box::new(lo), Box::new(hi), Box::new(body)),
+            Type::Unit,
+            pos,
+        ))
+    }
+
+    fn lvalue(&mut self, node: &LValue) -> Result<TVar, String> {
+        match node {
+            LValue::Id(id) => self.simple_var(id),
+            LValue::Refer(refer) => self.refer(refer),
+        }
+    }
+
+    fn simple_var(&mut self, id: &ValueInfo<String>) -> Result<TVar, String> {
+        let pos = self.at(id);
+        match self.lookup_value(id)? {
+            Entry::Var(var) => Ok(TVar {
+                kind: TVarKind::Simple(var),
+                ty: self.vars[var.0].ty,
+                pos,
+            }),
+            Entry::Fun(_) => error(&pos, format!("{} is a function, not a variable", id.v)),
+        }
+    }
+
+    fn var_exp(&self, var: TVar) -> TExp {
+        let (ty, pos) = (var.ty, var.pos.clone());
+        self.texp(TExpKind::Var(var), ty, pos)
+    }
+
+    fn field(&mut self, base: TVar, field: &ValueInfo<String>) -> Result<TVar, String> {
+        let pos = self.at(field);
+        let fields = match self.types.record_fields(base.ty) {
+            Some(fields) => fields,
+            None => return error(&pos, format!("{} is not a record type", self.show(base.ty))),
+        };
+        match fields.iter().position(|(name, _)| *name == field.v) {
+            Some(index) => {
+                let ty = fields[index].1;
+                Ok(TVar {
+                    kind: TVarKind::Field(Box::new(self.var_exp(base)), index),
+                    ty,
+                    pos,
+                })
+            }
+            None => error(
+                &pos,
+                format!(
+                    "field {} not in record type {}",
+                    field.v,
+                    self.show(base.ty)
+                ),
+            ),
+        }
+    }
+
+    fn subscript(&mut self, base: TVar, index: &Exp) -> Result<TVar, String> {
+        let pos = base.pos.clone();
+        let elem = match self.types.array_elem(base.ty) {
+            Some(elem) => elem,
+            None => return error(&pos, format!("{} is not an array type", self.show(base.ty))),
+        };
+        let index = self.exp(index)?;
+        self.expect(&index, Type::Int, "array index")?;
+        Ok(TVar {
+            kind: TVarKind::Subscript(Box::new(self.var_exp(base)), Box::new(index)),
+            ty: elem,
+            pos,
+        })
+    }
+
+    fn refer(&mut self, node: &Refer) -> Result<TVar, String> {
+        match node {
+            Refer::Field(id, field) => {
+                let base = self.simple_var(id)?;
+                self.field(base, field)
+            }
+            Refer::ReferField(refer, field) => {
+                let base = self.refer(refer)?;
+                self.field(base, field)
+            }
+            Refer::Array(id, index) => {
+                let base = self.simple_var(id)?;
+                self.subscript(base, index)
+            }
+            Refer::ReferArray(refer, index) => {
+                let base = self.refer(refer)?;
+                self.subscript(base, index)
+            }
+        }
+    }
+
+    fn slice(&mut self, node: &Slice) -> Result<TExp, String> {
+        match node {
+            Slice::Array { type_id, len, init } => {
+                let pos = self.at(type_id);
+                let ty = self.lookup_type(type_id)?;
+                let (id, elem) = match (self.types.actual(ty), self.types.array_elem(ty)) {
+                    (Type::Array(id), Some(elem)) => (id, elem),
+                    _ => return error(&pos, format!("{} is not an array type", type_id.v)),
+                };
+                let size = self.exp(len)?;
+                self.expect(&size, Type::Int, "array size")?;
+                let init = self.slice(init)?;
+                self.expect(&init, elem, "array initializer")?;
+                Ok(self.texp(
+                    TExpKind::Array(id, Box::new(size), Box::new(init)),
+                    Type::Array(id),
+                    pos,
+                ))
+            }
+            Slice::Record(type_id, rec_list) => {
+                let pos = self.at(type_id);
+                let ty = self.lookup_type(type_id)?;
+                let (id, fields) = match (self.types.actual(ty), self.types.record_fields(ty)) {
+                    (Type::Record(id), Some(fields)) => (id, fields.to_vec()),
+                    _ => return error(&pos, format!("{} is not a record type", type_id.v)),
+                };
+                let mut inits = Vec::new();
+                let mut next = match rec_list.as_ref() {
+                    RecList::Empty => None,
+                    RecList::Some(list) => Some(list.as_ref()),
+                };
+                while let Some(list) = next {
+                    let (name, exp, tail) = match list {
+                        SomeRecList::Some(name, exp) => (name, exp, None),
+                        SomeRecList::List(name, exp, tail) => (name, exp, Some(tail.as_ref())),
+                    };
+                    let field_pos = self.at(name);
+                    let value = self.exp(exp)?;
+                    match fields.get(inits.len()) {
+                        Some((field, ty)) if *field == name.v => {
+                            self.expect(&value, *ty, &format!("field {}", field))?
+                        }
+                        _ => {
+                            return error(
+                                &field_pos,
+                                format!("unexpected field {} for record {}", name.v, type_id.v),
+                            )
+                        }
+                    }
+                    inits.push(value);
+                    next = tail;
+                }
+                if inits.len() != fields.len() {
+                    return error(
+                        &pos,
+                        format!(
+                            "record {} needs {} fields, got {}",
+                            type_id.v,
+                            fields.len(),
+                            inits.len()
+                        ),
+                    );
+                }
+                Ok(self.texp(TExpKind::Record(id, inits), Type::Record(id), pos))
+            }
+            Slice::Sheet(sheet) => self.sheet(sheet),
+        }
+    }
+
+    fn logic(
+        &mut self,
+        left: TExp,
+        right: TExp,
+        kind: fn(Box<TExp>, Box<TExp>) -> TExpKind,
+    ) -> Result<TExp, String> {
+        self.expect(&left, Type::Int, "logical operand")?;
+        self.expect(&right, Type::Int, "logical operand")?;
+        let pos = left.pos.clone();
+        Ok(self.texp(kind(Box::new(left), Box::new(right)), Type::Int, pos))
+    }
+
+    // The binary operators are left-associative, so a chain of them nests
+    // down its left operands. `sheet`, `piece`, `item` and `term` walk that
+    // spine in a loop rather than one call per operator.
+
+    fn sheet(&mut self, mut node: &Sheet) -> Result<TExp, String> {
+        let mut rights = Vec::new();
+        let first = loop {
+            match node {
+                Sheet::Or(sheet, piece) => {
+                    rights.push(piece);
+                    node = sheet;
+                }
+                Sheet::Piece(piece) => break piece,
+            }
+        };
+        let mut left = self.piece(first)?;
+        while let Some(piece) = rights.pop() {
+            let right = self.piece(piece)?;
+            left = self.logic(left, right, TExpKind::Or)?;
+        }
+        Ok(left)
+    }
+
+    fn piece(&mut self, mut node: &Piece) -> Result<TExp, String> {
+        let mut rights = Vec::new();
+        let first = loop {
+            match node {
+                Piece::And(piece, bit) => {
+                    rights.push(bit);
+                    node = piece;
+                }
+                Piece::Bit(bit) => break bit,
+            }
+        };
+        let mut left = self.bit(first)?;
+        while let Some(bit) = rights.pop() {
+            let right = self.bit(bit)?;
+            left = self.logic(left, right, TExpKind::And)?;
+        }
+        Ok(left)
+    }
+
+    fn bit(&mut self, node: &Bit) -> Result<TExp, String> {
+        let (oper, left, right) = match node {
+            Bit::Eq(left, right) => (Oper::Eq, left, right),
+            Bit::Neq(left, right) => (Oper::Neq, left, right),
+            Bit::Lt(left, right) => (Oper::Lt, left, right),
+            Bit::Le(left, right) => (Oper::Le, left, right),
+            Bit::Gt(left, right) => (Oper::Gt, left, right),
+            Bit::Ge(left, right) => (Oper::Ge, left, right),
+            Bit::Item(item) => return self.item(item),
+        };
+        let left = self.item(left)?;
+        let right = self.item(right)?;
+        let (lt, rt) = (self.types.actual(left.ty), self.types.actual(right.ty));
+        let ok = match oper {
+            Oper::Eq | Oper::Neq => match (lt, rt) {
+                (Type::Nil, Type::Record(_)) | (Type::Record(_), Type::Nil) => true,
+                (Type::Int, Type::Int) | (Type::String, Type::String) => true,
+                (Type::Record(a), Type::Record(b)) | (Type::Array(a), Type::Array(b)) => a == b,
+                _ => false,
+            },
+            _ => matches!(
+                (lt, rt),
+                (Type::Int, Type::Int) | (Type::String, Type::String)
+            ),
+        };
+        if !ok {
+            return error(
+                &right.pos,
+                format!(
+                    "cannot compare {} with {}",
+                    self.show(left.ty),
+                    self.show(right.ty)
+                ),
+            );
+        }
+        let pos = left.pos.clone();
+        // Keep the non-nil side on the left so the operand type is known.
+        let (left, right) = if lt == Type::Nil {
+            (right, left)
+        } else {
+            (left, right)
+        };
+        Ok(self.texp(
+            TExpKind::Op(oper, Box::new(left), Box::new(right)),
+            Type::Int,
+            pos,
+        ))
+    }
+
+    fn arith(&mut self, oper: Oper, left: TExp, right: TExp) -> Result<TExp, String> {
+        self.expect(&left, Type::Int, "integer required")?;
+        self.expect(&right, Type::Int, "integer required")?;
+        let pos = left.pos.clone();
+        Ok(self.texp(
+            TExpKind::Op(oper, Box::new(left), Box::new(right)),
+            Type::Int,
+            pos,
+        ))
+    }
+
+    fn item(&mut self, mut node: &Item) -> Result<TExp, String> {
+        let mut rights = Vec::new();
+        let first = loop {
+            let (oper, item, term) = match node {
+                Item::Plus(item, term) => (Oper::Plus, item, term),
+                Item::Minus(item, term) => (Oper::Minus, item, term),
+                Item::Term(term) => break term,
+            };
+            rights.push((oper, term));
+            node = item;
+        };
+        let mut left = self.term(first)?;
+        while let Some((oper, term)) = rights.pop() {
+            let right = self.term(term)?;
+            left = self.arith(oper, left, right)?;
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self, mut node: &Term) -> Result<TExp, String> {
+        let mut rights = Vec::new();
+        let first = loop {
+            let (oper, term, factor) = match node {
+                Term::Times(term, factor) => (Oper::Times, term, factor),
+                Term::Divide(term, factor) => (Oper::Divide, term, factor),
+                Term::Factor(factor) => break factor,
+            };
+            rights.push((oper, factor));
+            node = term;
+        };
+        let mut left = self.factor(first)?;
+        while let Some((oper, factor)) = rights.pop() {
+            let right = self.factor(factor)?;
+            left = self.arith(oper, left, right)?;
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self, node: &Factor) -> Result<TExp, String> {
+        match node {
+            Factor::MMeta(meta) => {
+                let exp = self.meta(meta)?;
+                let zero = self.texp(TExpKind::Int(0), Type::Int, exp.pos.clone());
+                self.arith(Oper::Minus, zero, exp)
+            }
+            Factor::Meta(meta) => self.meta(meta),
+        }
+    }
+
+    fn meta(&mut self, node: &Meta) -> Result<TExp, String> {
+        match node {
+            Meta::Int(i) => {
+                let pos = self.at(i);
+                Ok(self.texp(TExpKind::Int(i.v as i64), Type::Int, pos))
+            }
+            Meta::String(s) => {
+                let pos = self.at(s);
+                Ok(self.texp(TExpKind::String(unescape(&s.v)), Type::String, pos))
+            }
+            Meta::Nil => Ok(self.texp(TExpKind::Nil, Type::Nil, self.pos.clone())),
+            Meta::Id(id) => {
+                let var = self.simple_var(id)?;
+                Ok(self.var_exp(var))
+            }
+            Meta::Refer(refer) => {
+                let var = self.refer(refer)?;
+                Ok(self.var_exp(var))
+            }
+            Meta::CapSeq(seq) => self.exp_seq(seq),
+            Meta::Call(id, args) => self.call(id, args),
+            Meta::Let(decs, seq) => {
+                let pos = self.pos.clone();
+                let (venv, tenv) = (self.venv.len(), self.tenv.len());
+                let result = self.decs(decs).and_then(|decs| {
+                    let body = self.exp_seq(seq)?;
+                    let ty = body.ty;
+                    Ok(self.texp(TExpKind::Let(decs, Box::new(body)), ty, pos))
+                });
+                self.venv.truncate(venv);
+                self.tenv.truncate(tenv);
+                result
+            }
+        }
+    }
+
+    fn exp_seq(&mut self, node: &ExpSeq) -> Result<TExp, String> {
+        let pos = self.pos.clone();
+        let mut exps = Vec::new();
+        let mut next = match node {
+            ExpSeq::Empty => None,
+            ExpSeq::Some(seq) => Some(seq.as_ref()),
+        };
+        while let Some(seq) = next {
+            let (exp, tail) = match seq {
+                SomeExpSeq::Some(exp) => (exp, None),
+                SomeExpSeq::List(exp, tail) => (exp, Some(tail.as_ref())),
+            };
+            exps.push(self.exp(exp)?);
+            next = tail;
+        }
+        let ty = exps.last().map_or(Type::Unit, |exp| exp.ty);
+        let pos = exps.first().map_or(pos, |exp| exp.pos.clone());
+        Ok(self.texp(TExpKind::Seq(exps), ty, pos))
+    }
+
+    fn call(&mut self, id: &ValueInfo<String>, args: &ArgsList) -> Result<TExp, String> {
+        let pos = self.at(id);
+        let fun = match self.lookup_value(id) {
+            Ok(Entry::Fun(fun)) => fun,
+            Ok(Entry::Var(_)) => return error(&pos, format!("{} is not a function", id.v)),
+            Err(_) => return error(&pos, format!("undefined function {}", id.v)),
+        };
+        let mut values = Vec::new();
+        let mut next = match args {
+            ArgsList::Empty => None,
+            ArgsList::Some(list) => Some(list.as_ref()),
+        };
+        while let Some(list) = next {
+            let (exp, tail) = match list {
+                SomeArgsList::Some(exp) => (exp, None),
+                SomeArgsList::List(exp, tail) => (exp, Some(tail.as_ref())),
+            };
+            values.push(self.exp(exp)?);
+            next = tail;
+        }
+        let params = self.funcs[fun.0].param_types.clone();
+        if params.len() != values.len() {
+            return error(
+                &pos,
+                format!(
+                    "{} expects {} arguments, got {}",
+                    id.v,
+                    params.len(),
+                    values.len()
+                ),
+            );
+        }
+        for (value, ty) in values.iter().zip(params) {
+            self.expect(value, ty, &format!("argument of {}", id.v))?;
+        }
+        let result = self.funcs[fun.0].result;
+        Ok(self.texp(TExpKind::Call(fun, values), result, pos))
+    }
+
+    fn decs(&mut self, node: &Decs) -> Result<Vec<TDec>, String> {
+        let mut decs = Vec::new();
+        let mut types = Vec::new();
+        let mut funcs = Vec::new();
+        let mut node = node;
+        loop {
+            let dec = match node {
+                Decs::Decs(dec, tail) => {
+                    node = tail;
+                    Some(dec.as_ref())
+                }
+                Decs::Empty => None,
+            };
+            if !matches!(dec, Some(Dec::TypeDec(_))) && !types.is_empty() {
+                self.type_decs(&std::mem::take(&mut types))?;
+            }
+            if !matches!(dec, Some(Dec::FunDec(_))) && !funcs.is_empty() {
+                decs.push(self.fun_decs(&std::mem::take(&mut funcs))?);
+            }
+            match dec {
+                Some(Dec::TypeDec(dec)) => types.push(dec.as_ref()),
+                Some(Dec::FunDec(dec)) => funcs.push(dec.as_ref()),
+                Some(Dec::VarDec(dec)) => decs.push(self.var_dec(dec)?),
+                None => return Ok(decs),
+            }
+        }
+    }
+
+    fn type_decs(&mut self, decs: &[&TypeDec]) -> Result<(), String> {
+        let mut names = HashSet::new();
+        let mut ids = Vec::new();
+        for TypeDec::Dec(id, _) in decs.iter().copied() {
+            let pos = self.at(id);
+            if !names.insert(id.v.as_str()) {
+                return error(&pos, format!("type {} declared twice in one batch", id.v));
+            }
+            let ty = self.types.add(TypeDef::Name {
+                name: id.v.clone(),
+                ty: None,
+            });
+            self.tenv.push((id.v.clone(), Type::Name(ty)));
+            ids.push(ty);
+        }
+        for (TypeDec::Dec(id, ty), name) in decs.iter().copied().zip(&ids) {
+            let resolved = match ty.as_ref() {
+                Ty::Id(other) => self.lookup_type(other)?,
+                Ty::Array(elem) => {
+                    let elem = self.lookup_type(elem)?;
+                    Type::Array(self.types.add(TypeDef::Array {
+                        name: id.v.clone(),
+                        elem,
+                    }))
+                }
+                Ty::Fields(fields) => {
+                    let mut field_names = HashSet::new();
+                    let mut record = Vec::new();
+                    for (field, type_id, _) in ty_fields(fields) {
+                        if !field_names.insert(field.v.as_str()) {
+                            return error(
+                                &field.info,
+                                format!("field {} declared twice in record {}", field.v, id.v),
+                            );
+                        }
+                        record.push((field.v.clone(), self.lookup_type(type_id)?));
+                    }
+                    Type::Record(self.types.add(TypeDef::Record {
+                        name: id.v.clone(),
+                        fields: record,
+                    }))
+                }
+            };
+            if let TypeDef::Name { ty, .. } = self.types.def_mut(*name) {
+                *ty = Some(resolved);
+            }
+        }
+        for (TypeDec::Dec(id, _), name) in decs.iter().copied().zip(&ids) {
+            let mut ty = Type::Name(*name);
+            for _ in 0..=ids.len() {
+                ty = match ty {
+                    Type::Name(next) => match self.types.def(next) {
+                        TypeDef::Name { ty: Some(ty), .. } => *ty,
+                        _ => unreachable!("type name bound above"),
+                    },
+                    _ => break,
+                };
+            }
+            if let Type::Name(_) = ty {
+                return error(
+                    &id.info,
+                    format!("illegal cycle in type declaration {}", id.v),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn fun_decs(&mut self, decs: &[&FunDec]) -> Result<TDec, String> {
+        let mut names = HashSet::new();
+        let mut ids = Vec::new();
+        for dec in decs.iter().copied() {
+            let (id, fields, result) = match dec {
+                FunDec::Proc(id, fields, _) => (id, fields, None),
+                FunDec::Func {
+                    id,
+                    fields,
+                    type_id,
+                    ..
+                } => (id, fields, Some(type_id)),
+            };
+            let pos = self.at(id);
+            if !names.insert(id.v.as_str()) {
+                return error(
+                    &pos,
+                    format!("function {} declared twice in one batch", id.v),
+                );
+            }
+            let mut param_names = HashSet::new();
+            let mut param_types = Vec::new();
+            for (param, type_id, _) in ty_fields(fields) {
+                if !param_names.insert(param.v.as_str()) {
+                    return error(
+                        &param.info,
+                        format!("parameter {} declared twice in function {}", param.v, id.v),
+                    );
+                }
+                param_types.push(self.lookup_type(type_id)?);
+            }
+            let result = match result {
+                Some(type_id) => self.lookup_type(type_id)?,
+                None => Type::Unit,
+            };
+            self.funcs.push(FunInfo {
+                name: id.v.clone(),
+                label: Label::unique(&id.v),
+                params: Vec::new(),
+                param_types,
+                result,
+                parent: Some(self.fun),
+                kind: FunKind::Builtin,
+                pos,
+            });
+            let fun = FunId(self.funcs.len() - 1);
+            self.venv.push((id.v.clone(), Entry::Fun(fun)));
+            ids.push(fun);
+        }
+        for (dec, fun) in decs.iter().copied().zip(&ids) {
+            let (fields, body) = match dec {
+                FunDec::Proc(_, fields, body) => (fields, body),
+                FunDec::Func { fields, exp, .. } => (fields, exp),
+            };
+            let scope = self.venv.len();
+            let (outer, loops) = (self.fun, self.loops);
+            self.fun = *fun;
+            self.loops = 0;
+            let mut params = Vec::new();
+            for ((id, _, escape), ty) in ty_fields(fields)
+                .into_iter()
+                .zip(self.funcs[fun.0].param_types.clone())
+            {
+                params.push(self.new_var(id, ty, escape));
+            }
+            let body = self.exp(body);
+            self.fun = outer;
+            self.loops = loops;
+            self.venv.truncate(scope);
+            let body = body?;
+            let result = self.funcs[fun.0].result;
+            if result == Type::Unit {
+                self.expect(&body, Type::Unit, "procedure must not return a value")?;
+            } else {
+                self.expect(&body, result, "function result")?;
+            }
+            self.funcs[fun.0].params = params;
+            self.funcs[fun.0].kind = FunKind::User { body };
+        }
+        Ok(TDec::Funcs(ids))
+    }
+
+    fn var_dec(&mut self, node: &VarDec) -> Result<TDec, String> {
+        let (id, type_id, exp, escape) = match node {
+            VarDec::DefaultInit(id, exp, escape) => (id, None, exp, escape),
+            VarDec::TypeInit {
+                var_id,
+                type_id,
+                exp,
+                escape,
+            } => (var_id, Some(type_id), exp, escape),
+        };
+        let pos = self.at(id);
+        let init = self.exp(exp)?;
+        let ty = match type_id {
+            Some(type_id) => {
+                let ty = self.lookup_type(type_id)?;
+                self.expect(&init, ty, &format!("initializer of {}", id.v))?;
+                ty
+            }
+            None => {
+                if init.ty == Type::Nil {
+                    return error(
+                        &pos,
+                        format!("nil initializer of {} needs a record type", id.v),
+                    );
+                }
+                init.ty
+            }
+        };
+        let var = self.new_var(id, ty, escape);
+        Ok(TDec::Var(var, init))
+    }
+}
+
+/// Names, type names and escape flags of a field list, in order.
+fn ty_fields(node: &TyFields) -> Vec<(&ValueInfo<String>, &ValueInfo<String>, &Escape)> {
+    let mut fields = Vec::new();
+    let mut next = match node {
+        TyFields::Empty => None,
+        TyFields::Some(some) => Some(some.as_ref()),
+    };
+    while let Some(some) = next {
+        next = match some {
+            SomeTyFields::Some {
+                id,
+                type_id,
+                escape,
+            } => {
+                fields.push((id, type_id, escape));
+                None
+            }
+            SomeTyFields::List {
+                id,
+                type_id,
+                escape,
+                tail,
+            } => {
+                fields.push((id, type_id, escape));
+                Some(tail.as_ref())
+            }
+        };
+    }
+    fields
+}
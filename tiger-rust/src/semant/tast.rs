@@ -0,0 +1,173 @@
+use crate::error::error::PosInfo;
+use crate::semant::types::{Type, TypeId, Types};
+use crate::temp::temp::Label;
+
+/// Index of a variable, parameter or loop counter in [`Program::vars`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VarId(pub usize);
+
+/// Index of a function in [`Program::funcs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FunId(pub usize);
+
+#[derive(Debug, Clone)]
+pub struct VarInfo {
+    pub name: String,
+    pub ty: Type,
+    pub escape: bool,
+    /// The function whose frame holds the variable.
+    pub fun: FunId,
+    pub pos: PosInfo,
+}
+
+#[derive(Debug, Clone)]
+pub enum FunKind {
+    /// Provided by the runtime under `label`; takes no static link.
+    Builtin,
+    User {
+        body: TExp,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct FunInfo {
+    pub name: String,
+    pub label: Label,
+    pub params: Vec<VarId>,
+    pub param_types: Vec<Type>,
+    pub result: Type,
+    /// The lexically enclosing function; `None` for builtins and the main
+    /// program.
+    pub parent: Option<FunId>,
+    pub kind: FunKind,
+    pub pos: PosInfo,
+}
+
+/// A type-checked program, with the grammar wrappers of `parser::ast`
+/// flattened away and every name resolved.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub types: Types,
+    pub vars: Vec<VarInfo>,
+    pub funcs: Vec<FunInfo>,
+    /// The body of the program, wrapped as a function without parameters.
+    pub main: FunId,
+}
+
+impl Program {
+    pub fn var(&self, id: VarId) -> &VarInfo {
+        &self.vars[id.0]
+    }
+
+    pub fn func(&self, id: FunId) -> &FunInfo {
+        &self.funcs[id.0]
+    }
+
+    /// Number of functions between `fun` and the outermost level, the main
+    /// program being at depth 0.
+    pub fn depth(&self, fun: FunId) -> usize {
+        let mut depth = 0;
+        let mut fun = fun;
+        while let Some(parent) = self.func(fun).parent {
+            depth += 1;
+            fun = parent;
+        }
+        depth
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Oper {
+    Plus,
+    Minus,
+    Times,
+    Divide,
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+pub struct TExp {
+    pub kind: TExpKind,
+    pub ty: Type,
+    pub pos: PosInfo,
+}
+
+impl Drop for TExp {
+    /// Operator chains nest one level per operand, so their operands are
+    /// unlinked and dropped from a work list rather than recursively.
+    fn drop(&mut self) {
+        let mut work = Vec::new();
+        operands(&mut self.kind, &mut work);
+        while let Some(mut exp) = work.pop() {
+            operands(&mut exp.kind, &mut work);
+        }
+    }
+}
+
+fn operands(kind: &mut TExpKind, work: &mut Vec<TExp>) {
+    if let TExpKind::Op(..) | TExpKind::And(..) | TExpKind::Or(..) = kind {
+        if let TExpKind::Op(_, left, right)
+        | TExpKind::And(left, right)
+        | TExpKind::Or(left, right) = std::mem::replace(kind, TExpKind::Nil)
+        {
+            work.push(*left);
+            work.push(*right);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TExpKind {
+    Int(i64),
+    /// The decoded contents of a string literal.
+    String(String),
+    Nil,
+    Var(TVar),
+    Call(FunId, Vec<TExp>),
+    /// Operands of comparisons may be ints, strings, records or arrays; the
+    /// type of `left` tells which.
+    Op(Oper, Box<TExp>, Box<TExp>),
+    /// Short-circuit `&`, from `ast::Piece::And`.
+    And(Box<TExp>, Box<TExp>),
+    /// Short-circuit `|`, from `ast::Sheet::Or`.
+    Or(Box<TExp>, Box<TExp>),
+    /// Field values in the order of the record type.
+    Record(TypeId, Vec<TExp>),
+    Array(TypeId, Box<TExp>, Box<TExp>),
+    /// Evaluates every expression and yields the last; empty is `()`.
+    Seq(Vec<TExp>),
+    Assign(TVar, Box<TExp>),
+    If(Box<TExp>, Box<TExp>, Option<Box<TExp>>),
+    While(Box<TExp>, Box<TExp>),
+    For(VarId, Box<TExp>, Box<TExp>, Box<TExp>),
+    Break,
+    Let(Vec<TDec>, Box<TExp>),
+}
+
+#[derive(Debug, Clone)]
+pub struct TVar {
+    pub kind: TVarKind,
+    pub ty: Type,
+    pub pos: PosInfo,
+}
+
+#[derive(Debug, Clone)]
+pub enum TVarKind {
+    Simple(VarId),
+    /// Record value and the index of the field in its type.
+    Field(Box<TExp>, usize),
+    Subscript(Box<TExp>, Box<TExp>),
+}
+
+#[derive(Debug, Clone)]
+pub enum TDec {
+    Var(VarId, TExp),
+    /// A batch of mutually recursive functions; bodies live in
+    /// [`Program::funcs`].
+    Funcs(Vec<FunId>),
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+
+/// Index of a record or array type, or of a type name, in [`Types`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TypeId(usize);
+
+/// A Tiger type. Records and arrays compare by identity, so two structurally
+/// equal declarations still give two different types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Type {
+    Int,
+    String,
+    Nil,
+    Unit,
+    Record(TypeId),
+    Array(TypeId),
+    /// A declared name, resolved through [`Types::actual`].
+    Name(TypeId),
+}
+
+#[derive(Debug, Clone)]
+pub enum TypeDef {
+    Record {
+        name: String,
+        fields: Vec<(String, Type)>,
+    },
+    Array {
+        name: String,
+        elem: Type,
+    },
+    Name {
+        name: String,
+        ty: Option<Type>,
+    },
+}
+
+/// Every record, array and named type declared by a program.
+#[derive(Debug, Clone, Default)]
+pub struct Types {
+    defs: Vec<TypeDef>,
+}
+
+impl Types {
+    pub fn add(&mut self, def: TypeDef) -> TypeId {
+        self.defs.push(def);
+        TypeId(self.defs.len() - 1)
+    }
+
+    pub fn def(&self, id: TypeId) -> &TypeDef {
+        &self.defs[id.0]
+    }
+
+    pub fn def_mut(&mut self, id: TypeId) -> &mut TypeDef {
+        &mut self.defs[id.0]
+    }
+
+    /// Follows type names to the type they stand for. Names must have been
+    /// bound and checked for cycles.
+    pub fn actual(&self, ty: Type) -> Type {
+        let mut ty = ty;
+        while let Type::Name(id) = ty {
+            match &self.defs[id.0] {
+                TypeDef::Name { ty: Some(next), .. } => ty = *next,
+                _ => unreachable!("unresolved type name"),
+            }
+        }
+        ty
+    }
+
+    pub fn record_fields(&self, ty: Type) -> Option<&[(String, Type)]> {
+        match self.actual(ty) {
+            Type::Record(id) => match &self.defs[id.0] {
+                TypeDef::Record { fields, .. } => Some(fields),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn array_elem(&self, ty: Type) -> Option<Type> {
+        match self.actual(ty) {
+            Type::Array(id) => match &self.defs[id.0] {
+                TypeDef::Array { elem, .. } => Some(*elem),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether a value of type `from` may be stored where `to` is expected.
+    pub fn assignable(&self, from: Type, to: Type) -> bool {
+        let (from, to) = (self.actual(from), self.actual(to));
+        from == to || (from == Type::Nil && matches!(to, Type::Record(_)))
+    }
+
+    /// Whether the value is a heap pointer the collector has to trace.
+    pub fn is_pointer(&self, ty: Type) -> bool {
+        matches!(
+            self.actual(ty),
+            Type::String | Type::Record(_) | Type::Array(_) | Type::Nil
+        )
+    }
+
+    pub fn display(&self, ty: Type) -> TypeDisplay<'_> {
+        TypeDisplay { types: self, ty }
+    }
+}
+
+pub struct TypeDisplay<'a> {
+    types: &'a Types,
+    ty: Type,
+}
+
+impl fmt::Display for TypeDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ty {
+            Type::Int => f.write_str("int"),
+            Type::String => f.write_str("string"),
+            Type::Nil => f.write_str("nil"),
+            Type::Unit => f.write_str("unit"),
+            Type::Record(id) | Type::Array(id) | Type::Name(id) => match self.types.def(id) {
+                TypeDef::Record { name, .. }
+                | TypeDef::Array { name, .. }
+                | TypeDef::Name { name, .. } => f.write_str(name),
+            },
+        }
+    }
+}
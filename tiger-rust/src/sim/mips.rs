@@ -0,0 +1,939 @@
+//! A simulator for the assembly of the MIPS backend, so that its output runs
+//! without SPIM. It assembles the text itself, then runs it with SPIM's
+//! memory layout and system calls, and without branch delay slots. The
+//! runtime functions are built in: a call of `tig_print` or any of the
+//! others the text does not define runs it here. Integers are 32 bits wide,
+//! and the heap is never collected.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::error::error::{pack_pos, PosInfo};
+use crate::frame::mips::{MipsFrame, REGISTER_NAMES};
+use crate::interp::interp::backtrace;
+use crate::interp::limits::{Limits, Meter};
+use crate::interp::runtime::{at, labels, Runtime};
+use crate::temp::temp::Label;
+
+pub const TEXT_BASE: u32 = 0x0040_0000;
+pub const DATA_BASE: u32 = 0x1001_0000;
+/// Address just above the stack.
+pub const STACK_TOP: u32 = 0x8000_0000;
+const STACK_BYTES: u32 = 8 << 20;
+/// Where the built-in runtime functions are called, below the text.
+const RUNTIME_BASE: u32 = 0x0030_0000;
+
+const V0: usize = 2;
+const A0: usize = 4;
+const SP: usize = 29;
+const RA: usize = 31;
+
+type Reg = usize;
+
+/// The second operand of an instruction that takes a register or an
+/// immediate there.
+#[derive(Debug, Clone, Copy)]
+enum Src {
+    Reg(Reg),
+    Imm(i32),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Alu {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    And,
+    Or,
+    Xor,
+    Nor,
+    Sll,
+    Srl,
+    Sra,
+    Slt,
+    Sltu,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Cond {
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Ltu,
+    Gtu,
+    Leu,
+    Geu,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Width {
+    Word,
+    Byte,
+    ByteUnsigned,
+}
+
+/// One instruction, pseudo-instructions included, with its labels
+/// resolved to addresses.
+#[derive(Debug, Clone, Copy)]
+enum Inst {
+    Alu(Alu, Reg, Reg, Src),
+    Li(Reg, i32),
+    Load(Width, Reg, i32, Reg),
+    Store(Width, Reg, i32, Reg),
+    Mult(Reg, Reg),
+    Div(Reg, Reg),
+    Mflo(Reg),
+    Mfhi(Reg),
+    Branch(Cond, Reg, Src, u32),
+    Jump(u32),
+    Jal(u32),
+    Jr(Reg),
+    Jalr(Reg),
+    Syscall,
+    Nop,
+}
+
+/// An assembled program.
+pub struct Program {
+    text: Vec<Inst>,
+    data: Vec<u8>,
+    entry: u32,
+    /// The source name of each text label, by address, for backtraces.
+    names: HashMap<u32, String>,
+    /// The address after each source position comment in the text, and the
+    /// position, packed as the compiler packs it, in address order.
+    positions: Vec<(u32, i64)>,
+    runtime: HashMap<u32, Runtime>,
+}
+
+/// An instruction as written, before its labels are resolved.
+struct Line<'a> {
+    number: usize,
+    mnemonic: &'a str,
+    operands: Vec<&'a str>,
+}
+
+/// Assembles the SPIM-style text `asm`.
+pub fn assemble(asm: &str) -> Result<Program, String> {
+    let mut lines = Vec::new();
+    let mut data = Vec::new();
+    let mut labels: HashMap<&str, u32> = HashMap::new();
+    let mut names = HashMap::new();
+    let mut positions = Vec::new();
+    // Words of the data holding the address of a label.
+    let mut fixups = Vec::new();
+    let mut in_text = true;
+    for (index, line) in asm.lines().enumerate() {
+        let number = index + 1;
+        let error = |msg: String| format!("line {}: {}", number, msg);
+        if let Some(pos) = position_comment(line).filter(|_| in_text) {
+            positions.push((TEXT_BASE + 4 * lines.len() as u32, pos));
+            continue;
+        }
+        let mut line = strip_comment(line).trim();
+        while let Some((label, rest)) = split_label(line) {
+            let address = match in_text {
+                true => TEXT_BASE + 4 * lines.len() as u32,
+                false => DATA_BASE + data.len() as u32,
+            };
+            if labels.insert(label, address).is_some() {
+                return Err(error(format!("label {} defined twice", label)));
+            }
+            if in_text {
+                names
+                    .entry(address)
+                    .or_insert_with(|| Label::named(label).source_name().to_string());
+            }
+            line = rest.trim();
+        }
+        if line.is_empty() {
+            continue;
+        }
+        let (head, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(head, rest)| (head, rest.trim()));
+        let operands = || -> Vec<&str> {
+            match rest {
+                "" => Vec::new(),
+                rest => rest.split(',').map(str::trim).collect(),
+            }
+        };
+        match head {
+            ".text" => in_text = true,
+            ".data" => in_text = false,
+            ".globl" | ".global" => (),
+            _ if in_text && head.starts_with('.') => {
+                return Err(error(format!("{} in the text segment", head)));
+            }
+            ".align" => {
+                let align = 1 << number_operand(rest).map_err(error)?;
+                data.resize(data.len().next_multiple_of(align), 0);
+            }
+            ".space" => {
+                let bytes = number_operand(rest).map_err(error)?;
+                data.resize(data.len() + bytes as usize, 0);
+            }
+            ".word" => {
+                for operand in operands() {
+                    match immediate(operand) {
+                        Ok(value) => data.extend_from_slice(&value.to_le_bytes()),
+                        Err(_) => {
+                            fixups.push((data.len(), operand, number));
+                            data.extend_from_slice(&[0; 4]);
+                        }
+                    }
+                }
+            }
+            ".byte" => {
+                for operand in operands() {
+                    data.push(immediate(operand).map_err(error)? as u8);
+                }
+            }
+            ".ascii" | ".asciiz" => {
+                data.extend(string_operand(rest).map_err(error)?);
+                if head == ".asciiz" {
+                    data.push(0);
+                }
+            }
+            _ if head.starts_with('.') => {
+                return Err(error(format!("unknown directive {}", head)));
+            }
+            _ if !in_text => return Err(error(format!("{} in the data segment", head))),
+            mnemonic => lines.push(Line {
+                number,
+                mnemonic,
+                operands: operands(),
+            }),
+        }
+    }
+
+    let runtime_labels = runtime_labels();
+    let resolve = |label: &str| -> Result<u32, String> {
+        match labels.get(label) {
+            Some(address) => Ok(*address),
+            None => runtime_labels
+                .get(label)
+                .map(|(address, _)| *address)
+                .ok_or_else(|| format!("undefined label {}", label)),
+        }
+    };
+    for (offset, label, number) in fixups {
+        let address = resolve(label).map_err(|err| format!("line {}: {}", number, err))?;
+        data[offset..offset + 4].copy_from_slice(&address.to_le_bytes());
+    }
+    let text = lines
+        .iter()
+        .map(|line| {
+            instruction(line, &resolve).map_err(|err| format!("line {}: {}", line.number, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let entry = *labels.get("main").ok_or("no main label")?;
+    Ok(Program {
+        text,
+        data,
+        entry,
+        names,
+        positions,
+        runtime: runtime_labels.into_values().collect(),
+    })
+}
+
+/// The source position in a line that is only a comment such as `# 3.14`,
+/// as the compiler writes them, packed.
+fn position_comment(line: &str) -> Option<i64> {
+    let (line, col) = line.trim().strip_prefix('#')?.trim().split_once('.')?;
+    let (line, col) = (line.parse().ok()?, col.parse().ok()?);
+    Some(pack_pos(&PosInfo { line, col }))
+}
+
+/// The runtime functions the text may call without defining, by label,
+/// with the address each is called at.
+fn runtime_labels() -> HashMap<String, (u32, Runtime)> {
+    let mut runtime: Vec<(Label, Runtime)> = labels::<MipsFrame>().into_iter().collect();
+    runtime.sort_by(|(a, _), (b, _)| a.cmp(b));
+    runtime
+        .into_iter()
+        .enumerate()
+        .map(|(index, (label, runtime))| {
+            let address = RUNTIME_BASE + 4 * index as u32;
+            (label.name().to_string(), (address, runtime))
+        })
+        .collect()
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => (),
+        }
+    }
+    line
+}
+
+/// The label that starts `line`, and the rest of the line after it.
+fn split_label(line: &str) -> Option<(&str, &str)> {
+    let (label, rest) = line.split_once(':')?;
+    let valid = !label.is_empty()
+        && label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$');
+    valid.then_some((label, rest))
+}
+
+fn immediate(operand: &str) -> Result<i32, String> {
+    let (negative, digits) = match operand.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, operand),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => digits.parse::<i64>(),
+    }
+    .map_err(|_| format!("bad immediate {}", operand))?;
+    let value = if negative { -value } else { value };
+    match i32::try_from(value) {
+        Ok(value) => Ok(value),
+        Err(_) => u32::try_from(value)
+            .map(|value| value as i32)
+            .map_err(|_| format!("immediate {} out of range", operand)),
+    }
+}
+
+fn number_operand(operand: &str) -> Result<u32, String> {
+    immediate(operand)
+        .and_then(|value| u32::try_from(value).map_err(|_| format!("bad count {}", operand)))
+}
+
+/// The bytes of a quoted string, with SPIM's escapes.
+fn string_operand(operand: &str) -> Result<Vec<u8>, String> {
+    let inner = operand
+        .strip_prefix('"')
+        .and_then(|operand| operand.strip_suffix('"'))
+        .ok_or_else(|| format!("bad string {}", operand))?;
+    let mut bytes = Vec::new();
+    let mut chars = inner.bytes();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        bytes.push(match chars.next() {
+            Some(b'n') => b'\n',
+            Some(b't') => b'\t',
+            Some(b'0') => 0,
+            Some(byte @ (b'"' | b'\\')) => byte,
+            _ => return Err(format!("bad escape in {}", operand)),
+        });
+    }
+    Ok(bytes)
+}
+
+fn register(operand: &str) -> Result<Reg, String> {
+    let name = operand
+        .strip_prefix('$')
+        .ok_or_else(|| format!("expected a register, found {}", operand))?;
+    match name.parse::<usize>() {
+        Ok(number) if number < 32 => Ok(number),
+        _ => REGISTER_NAMES
+            .iter()
+            .position(|register| *register == name)
+            .ok_or_else(|| format!("unknown register {}", operand)),
+    }
+}
+
+fn src(operand: &str) -> Result<Src, String> {
+    match operand.starts_with('$') {
+        true => register(operand).map(Src::Reg),
+        false => immediate(operand).map(Src::Imm),
+    }
+}
+
+/// An `offset(base)` operand.
+fn memory(operand: &str) -> Result<(i32, Reg), String> {
+    let (offset, base) = operand
+        .strip_suffix(')')
+        .and_then(|operand| operand.split_once('('))
+        .ok_or_else(|| format!("bad address {}", operand))?;
+    let offset = match offset.trim() {
+        "" => 0,
+        offset => immediate(offset)?,
+    };
+    Ok((offset, register(base.trim())?))
+}
+
+fn instruction(line: &Line, resolve: &dyn Fn(&str) -> Result<u32, String>) -> Result<Inst, String> {
+    let ops = &line.operands;
+    let arity = |n: usize| match ops.len() == n {
+        true => Ok(()),
+        false => Err(format!(
+            "{} takes {} operands, not {}",
+            line.mnemonic,
+            n,
+            ops.len()
+        )),
+    };
+    let alu = |alu: Alu| -> Result<Inst, String> {
+        arity(3)?;
+        Ok(Inst::Alu(
+            alu,
+            register(ops[0])?,
+            register(ops[1])?,
+            src(ops[2])?,
+        ))
+    };
+    let branch = |cond: Cond| -> Result<Inst, String> {
+        arity(3)?;
+        Ok(Inst::Branch(
+            cond,
+            register(ops[0])?,
+            src(ops[1])?,
+            resolve(ops[2])?,
+        ))
+    };
+    let branch_zero = |cond: Cond| -> Result<Inst, String> {
+        arity(2)?;
+        Ok(Inst::Branch(
+            cond,
+            register(ops[0])?,
+            Src::Imm(0),
+            resolve(ops[1])?,
+        ))
+    };
+    let load = |width: Width| -> Result<Inst, String> {
+        arity(2)?;
+        let (offset, base) = memory(ops[1])?;
+        Ok(Inst::Load(width, register(ops[0])?, offset, base))
+    };
+    let store = |width: Width| -> Result<Inst, String> {
+        arity(2)?;
+        let (offset, base) = memory(ops[1])?;
+        Ok(Inst::Store(width, register(ops[0])?, offset, base))
+    };
+    let two = || -> Result<(Reg, Reg), String> {
+        arity(2)?;
+        Ok((register(ops[0])?, register(ops[1])?))
+    };
+    match line.mnemonic {
+        "addu" | "add" | "addiu" | "addi" => alu(Alu::Add),
+        "subu" | "sub" => alu(Alu::Sub),
+        "mul" => alu(Alu::Mul),
+        "div" if ops.len() == 2 => two().map(|(a, b)| Inst::Div(a, b)),
+        "div" => alu(Alu::Div),
+        "rem" => alu(Alu::Rem),
+        "and" | "andi" => alu(Alu::And),
+        "or" | "ori" => alu(Alu::Or),
+        "xor" | "xori" => alu(Alu::Xor),
+        "nor" => alu(Alu::Nor),
+        "sll" | "sllv" => alu(Alu::Sll),
+        "srl" | "srlv" => alu(Alu::Srl),
+        "sra" | "srav" => alu(Alu::Sra),
+        "slt" | "slti" => alu(Alu::Slt),
+        "sltu" | "sltiu" => alu(Alu::Sltu),
+        "move" => two().map(|(d, s)| Inst::Alu(Alu::Add, d, s, Src::Reg(0))),
+        "neg" | "negu" => two().map(|(d, s)| Inst::Alu(Alu::Sub, d, 0, Src::Reg(s))),
+        "not" => two().map(|(d, s)| Inst::Alu(Alu::Nor, d, s, Src::Reg(0))),
+        "li" => {
+            arity(2)?;
+            Ok(Inst::Li(register(ops[0])?, immediate(ops[1])?))
+        }
+        "la" => {
+            arity(2)?;
+            Ok(Inst::Li(register(ops[0])?, resolve(ops[1])? as i32))
+        }
+        "lui" => {
+            arity(2)?;
+            Ok(Inst::Li(register(ops[0])?, immediate(ops[1])? << 16))
+        }
+        "lw" => load(Width::Word),
+        "lb" => load(Width::Byte),
+        "lbu" => load(Width::ByteUnsigned),
+        "sw" => store(Width::Word),
+        "sb" => store(Width::Byte),
+        "mult" => two().map(|(a, b)| Inst::Mult(a, b)),
+        "mflo" => {
+            arity(1)?;
+            Ok(Inst::Mflo(register(ops[0])?))
+        }
+        "mfhi" => {
+            arity(1)?;
+            Ok(Inst::Mfhi(register(ops[0])?))
+        }
+        "beq" => branch(Cond::Eq),
+        "bne" => branch(Cond::Ne),
+        "blt" => branch(Cond::Lt),
+        "bgt" => branch(Cond::Gt),
+        "ble" => branch(Cond::Le),
+        "bge" => branch(Cond::Ge),
+        "bltu" => branch(Cond::Ltu),
+        "bgtu" => branch(Cond::Gtu),
+        "bleu" => branch(Cond::Leu),
+        "bgeu" => branch(Cond::Geu),
+        "beqz" => branch_zero(Cond::Eq),
+        "bnez" => branch_zero(Cond::Ne),
+        "bltz" => branch_zero(Cond::Lt),
+        "bgtz" => branch_zero(Cond::Gt),
+        "blez" => branch_zero(Cond::Le),
+        "bgez" => branch_zero(Cond::Ge),
+        "b" | "j" => {
+            arity(1)?;
+            Ok(Inst::Jump(resolve(ops[0])?))
+        }
+        "jal" => {
+            arity(1)?;
+            Ok(Inst::Jal(resolve(ops[0])?))
+        }
+        "jr" => {
+            arity(1)?;
+            Ok(Inst::Jr(register(ops[0])?))
+        }
+        "jalr" => {
+            arity(1)?;
+            Ok(Inst::Jalr(register(ops[0])?))
+        }
+        "syscall" => arity(0).map(|()| Inst::Syscall),
+        "nop" => arity(0).map(|()| Inst::Nop),
+        mnemonic => Err(format!("unknown instruction {}", mnemonic)),
+    }
+}
+
+struct Machine<'a> {
+    program: &'a Program,
+    regs: [i32; 32],
+    lo: i32,
+    hi: i32,
+    pc: u32,
+    data: Vec<u8>,
+    stack: Vec<u8>,
+    /// The entry address of each active call, innermost last.
+    calls: Vec<u32>,
+    meter: Meter,
+    input: &'a mut dyn Read,
+    output: &'a mut dyn Write,
+}
+
+/// Assembles and runs `asm`. Returns the status passed to `exit`, or 0.
+pub fn run(asm: &str, input: &mut dyn Read, output: &mut dyn Write) -> Result<i64, String> {
+    run_with(asm, Limits::default(), input, output)
+}
+
+/// Runs `asm` as [`run`] does, failing once it exceeds `limits`. Calls of
+/// text labels count as function activations.
+pub fn run_with(
+    asm: &str,
+    limits: Limits,
+    input: &mut dyn Read,
+    output: &mut dyn Write,
+) -> Result<i64, String> {
+    let program = assemble(asm)?;
+    let mut machine = Machine {
+        program: &program,
+        regs: [0; 32],
+        lo: 0,
+        hi: 0,
+        pc: program.entry,
+        data: program.data.clone(),
+        stack: vec![0; STACK_BYTES as usize],
+        calls: Vec::new(),
+        meter: Meter::new(limits),
+        input,
+        output,
+    };
+    machine.regs[SP] = STACK_TOP as i32;
+    let status = machine.execute().map_err(|err| {
+        machine.calls.iter().rev().fold(err, |err, entry| {
+            let name = program.names.get(entry).map_or("?", String::as_str);
+            backtrace(err, name)
+        })
+    });
+    machine.output.flush().map_err(|err| err.to_string())?;
+    status
+}
+
+fn io_error(err: std::io::Error) -> String {
+    err.to_string()
+}
+
+impl Machine<'_> {
+    /// Runs until the program exits or returns from `main`.
+    fn execute(&mut self) -> Result<i64, String> {
+        loop {
+            if self.pc < TEXT_BASE {
+                if self.pc == 0 {
+                    return Ok(0);
+                }
+                let runtime = *self
+                    .program
+                    .runtime
+                    .get(&self.pc)
+                    .ok_or_else(|| format!("jump to bad address {:#x}", self.pc))?;
+                if let Some(status) = self.runtime(runtime)? {
+                    return Ok(status);
+                }
+                self.pc = self.regs[RA] as u32;
+                continue;
+            }
+            let inst = *self
+                .program
+                .text
+                .get(((self.pc - TEXT_BASE) / 4) as usize)
+                .ok_or_else(|| format!("jump to bad address {:#x}", self.pc))?;
+            self.pc += 4;
+            self.meter.step().map_err(|err| self.limit(err))?;
+            match inst {
+                Inst::Alu(alu, d, s, t) => {
+                    let (a, b) = (self.regs[s], self.operand(t));
+                    let value = match alu {
+                        Alu::Add => a.wrapping_add(b),
+                        Alu::Sub => a.wrapping_sub(b),
+                        Alu::Mul => a.wrapping_mul(b),
+                        Alu::Div | Alu::Rem if b == 0 => {
+                            return Err(String::from("division by zero"))
+                        }
+                        Alu::Div => a.wrapping_div(b),
+                        Alu::Rem => a.wrapping_rem(b),
+                        Alu::And => a & b,
+                        Alu::Or => a | b,
+                        Alu::Xor => a ^ b,
+                        Alu::Nor => !(a | b),
+                        Alu::Sll => a.wrapping_shl(b as u32 & 31),
+                        Alu::Srl => ((a as u32) >> (b as u32 & 31)) as i32,
+                        Alu::Sra => a >> (b as u32 & 31),
+                        Alu::Slt => (a < b) as i32,
+                        Alu::Sltu => ((a as u32) < (b as u32)) as i32,
+                    };
+                    self.set(d, value);
+                }
+                Inst::Li(d, value) => self.set(d, value),
+                Inst::Load(width, d, offset, base) => {
+                    let address = self.regs[base].wrapping_add(offset) as u32;
+                    let value = match width {
+                        Width::Word => self.load(address)?,
+                        Width::Byte => self.bytes(address, 1)?[0] as i8 as i32,
+                        Width::ByteUnsigned => self.bytes(address, 1)?[0] as i32,
+                    };
+                    self.set(d, value);
+                }
+                Inst::Store(width, s, offset, base) => {
+                    let address = self.regs[base].wrapping_add(offset) as u32;
+                    let value = self.regs[s];
+                    match width {
+                        Width::Word => self.store(address, value)?,
+                        Width::Byte | Width::ByteUnsigned => {
+                            self.bytes(address, 1)?[0] = value as u8
+                        }
+                    }
+                }
+                Inst::Mult(a, b) => {
+                    let product = self.regs[a] as i64 * self.regs[b] as i64;
+                    self.lo = product as i32;
+                    self.hi = (product >> 32) as i32;
+                }
+                Inst::Div(a, b) => {
+                    let (a, b) = (self.regs[a], self.regs[b]);
+                    if b == 0 {
+                        return Err(String::from("division by zero"));
+                    }
+                    self.lo = a.wrapping_div(b);
+                    self.hi = a.wrapping_rem(b);
+                }
+                Inst::Mflo(d) => self.set(d, self.lo),
+                Inst::Mfhi(d) => self.set(d, self.hi),
+                Inst::Branch(cond, s, t, target) => {
+                    let (a, b) = (self.regs[s], self.operand(t));
+                    let (ua, ub) = (a as u32, b as u32);
+                    let taken = match cond {
+                        Cond::Eq => a == b,
+                        Cond::Ne => a != b,
+                        Cond::Lt => a < b,
+                        Cond::Gt => a > b,
+                        Cond::Le => a <= b,
+                        Cond::Ge => a >= b,
+                        Cond::Ltu => ua < ub,
+                        Cond::Gtu => ua > ub,
+                        Cond::Leu => ua <= ub,
+                        Cond::Geu => ua >= ub,
+                    };
+                    if taken {
+                        self.pc = target;
+                    }
+                }
+                Inst::Jump(target) => self.pc = target,
+                Inst::Jal(target) => self.call(target)?,
+                Inst::Jalr(s) => self.call(self.regs[s] as u32)?,
+                Inst::Jr(s) => {
+                    if s == RA && self.calls.pop().is_some() {
+                        self.meter.leave();
+                    }
+                    self.pc = self.regs[s] as u32;
+                }
+                Inst::Syscall => {
+                    if let Some(status) = self.syscall()? {
+                        return Ok(status);
+                    }
+                }
+                Inst::Nop => (),
+            }
+        }
+    }
+
+    fn operand(&self, src: Src) -> i32 {
+        match src {
+            Src::Reg(r) => self.regs[r],
+            Src::Imm(value) => value,
+        }
+    }
+
+    fn set(&mut self, d: Reg, value: i32) {
+        if d != 0 {
+            self.regs[d] = value;
+        }
+    }
+
+    fn call(&mut self, target: u32) -> Result<(), String> {
+        self.regs[RA] = self.pc as i32;
+        if target >= TEXT_BASE {
+            self.meter.enter().map_err(|err| self.limit(err))?;
+            self.calls.push(target);
+        }
+        self.pc = target;
+        Ok(())
+    }
+
+    /// `err`, a limit exceeded, at the source position marked last before
+    /// the instruction just run, or before the call of the runtime routine
+    /// running.
+    fn limit(&self, err: String) -> String {
+        let pc = match self.pc < TEXT_BASE {
+            true => self.regs[RA] as u32,
+            false => self.pc,
+        };
+        let positions = &self.program.positions;
+        let index = positions.partition_point(|(address, _)| *address < pc);
+        match index.checked_sub(1) {
+            Some(index) => at(positions[index].1, &err),
+            None => err,
+        }
+    }
+
+    fn bytes(&mut self, address: u32, len: u32) -> Result<&mut [u8], String> {
+        let bad = || format!("bad address {:#x}", address);
+        let end = address.checked_add(len).ok_or_else(bad)?;
+        let stack_base = STACK_TOP - STACK_BYTES;
+        if address >= stack_base && end <= STACK_TOP {
+            let start = (address - stack_base) as usize;
+            return Ok(&mut self.stack[start..start + len as usize]);
+        }
+        if address >= DATA_BASE && ((end - DATA_BASE) as usize) <= self.data.len() {
+            let start = (address - DATA_BASE) as usize;
+            return Ok(&mut self.data[start..start + len as usize]);
+        }
+        Err(bad())
+    }
+
+    fn load(&mut self, address: u32) -> Result<i32, String> {
+        if !address.is_multiple_of(4) {
+            return Err(format!("unaligned address {:#x}", address));
+        }
+        let bytes = self.bytes(address, 4)?;
+        Ok(i32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn store(&mut self, address: u32, value: i32) -> Result<(), String> {
+        if !address.is_multiple_of(4) {
+            return Err(format!("unaligned address {:#x}", address));
+        }
+        self.bytes(address, 4)?
+            .copy_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    /// `bytes` of fresh zeroed heap, word aligned.
+    fn alloc(&mut self, bytes: usize) -> Result<u32, String> {
+        self.meter.alloc(bytes).map_err(|err| self.limit(err))?;
+        let start = self.data.len().next_multiple_of(4);
+        let end = start
+            .checked_add(bytes.next_multiple_of(4))
+            .filter(|end| *end <= (STACK_TOP - STACK_BYTES - DATA_BASE) as usize)
+            .ok_or("out of memory")?;
+        self.data.resize(end, 0);
+        Ok(DATA_BASE + start as u32)
+    }
+
+    fn string(&mut self, s: u32) -> Result<Vec<u8>, String> {
+        let len = self.load(s)?;
+        let len = u32::try_from(len).map_err(|_| format!("bad string at {:#x}", s))?;
+        Ok(self.bytes(s + 4, len)?.to_vec())
+    }
+
+    fn alloc_string(&mut self, bytes: &[u8]) -> Result<i32, String> {
+        let s = self.alloc(4 + bytes.len())?;
+        self.store(s, bytes.len() as i32)?;
+        self.bytes(s + 4, bytes.len() as u32)?
+            .copy_from_slice(bytes);
+        Ok(s as i32)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.meter
+            .output(bytes.len())
+            .map_err(|err| self.limit(err))?;
+        self.output.write_all(bytes).map_err(io_error)
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>, String> {
+        let mut byte = [0u8];
+        match self.input.read(&mut byte).map_err(io_error)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Carries out the system call in `$v0`, returning the exit status if
+    /// it ends the program.
+    fn syscall(&mut self) -> Result<Option<i64>, String> {
+        let a0 = self.regs[A0];
+        match self.regs[V0] {
+            1 => self.write(a0.to_string().as_bytes())?,
+            4 => {
+                let mut bytes = Vec::new();
+                let mut address = a0 as u32;
+                loop {
+                    match self.bytes(address, 1)?[0] {
+                        0 => break,
+                        byte => bytes.push(byte),
+                    }
+                    address += 1;
+                }
+                self.write(&bytes)?;
+            }
+            5 => {
+                let mut line = Vec::new();
+                while let Some(byte) = self.read_byte()? {
+                    if byte == b'\n' {
+                        break;
+                    }
+                    line.push(byte);
+                }
+                let line = String::from_utf8_lossy(&line);
+                self.regs[V0] = line.trim().parse().unwrap_or(0);
+            }
+            8 => {
+                let (buffer, len) = (a0 as u32, self.regs[A0 + 1]);
+                let mut bytes = Vec::new();
+                while (bytes.len() as i32) < len - 1 {
+                    match self.read_byte()? {
+                        Some(byte) => {
+                            bytes.push(byte);
+                            if byte == b'\n' {
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                bytes.push(0);
+                self.bytes(buffer, bytes.len() as u32)?
+                    .copy_from_slice(&bytes);
+            }
+            9 => {
+                let bytes = usize::try_from(a0).map_err(|_| "negative sbrk")?;
+                self.regs[V0] = self.alloc(bytes)? as i32;
+            }
+            10 => return Ok(Some(0)),
+            11 => self.write(&[a0 as u8])?,
+            12 => self.regs[V0] = self.read_byte()?.map_or(-1, |byte| byte as i32),
+            17 => return Ok(Some(a0 as i64)),
+            code => return Err(format!("unknown syscall {}", code)),
+        }
+        Ok(None)
+    }
+
+    /// Carries out a call of the runtime, returning the exit status if it
+    /// ends the program.
+    fn runtime(&mut self, runtime: Runtime) -> Result<Option<i64>, String> {
+        let [a0, a1, a2, a3] = [
+            self.regs[A0],
+            self.regs[A0 + 1],
+            self.regs[A0 + 2],
+            self.regs[A0 + 3],
+        ];
+        let pos = |pos: i32| pos as i64;
+        let result = match runtime {
+            Runtime::Print => {
+                let bytes = self.string(a0 as u32)?;
+                self.write(&bytes)?;
+                0
+            }
+            Runtime::Flush => {
+                self.output.flush().map_err(io_error)?;
+                0
+            }
+            Runtime::Getchar => match self.read_byte()? {
+                Some(byte) => self.alloc_string(&[byte])?,
+                None => self.alloc_string(&[])?,
+            },
+            Runtime::Ord => self.string(a0 as u32)?.first().map_or(-1, |c| *c as i32),
+            Runtime::Chr => match u8::try_from(a0) {
+                Ok(c) => self.alloc_string(&[c])?,
+                Err(_) => return Err(at(pos(a1), &format!("chr({}) out of range", a0))),
+            },
+            Runtime::Size => self.load(a0 as u32)?,
+            Runtime::Substring => {
+                let bytes = self.string(a0 as u32)?;
+                let (first, n) = (a1 as i64, a2 as i64);
+                if first < 0 || n < 0 || first + n > bytes.len() as i64 {
+                    let msg = format!(
+                        "substring({:?}, {}, {}) out of range",
+                        String::from_utf8_lossy(&bytes),
+                        first,
+                        n
+                    );
+                    return Err(at(pos(a3), &msg));
+                }
+                self.alloc_string(&bytes[first as usize..(first + n) as usize])?
+            }
+            Runtime::Concat => {
+                let bytes = [self.string(a0 as u32)?, self.string(a1 as u32)?].concat();
+                self.alloc_string(&bytes)?
+            }
+            Runtime::Not => (a0 == 0) as i32,
+            Runtime::Exit => return Ok(Some(a0 as i64)),
+            Runtime::StringEqual => (self.string(a0 as u32)? == self.string(a1 as u32)?) as i32,
+            Runtime::StringCompare => self.string(a0 as u32)?.cmp(&self.string(a1 as u32)?) as i32,
+            Runtime::InitArray => {
+                if a0 < 0 {
+                    return Err(at(pos(a3), &format!("array size {} is negative", a0)));
+                }
+                let a = self.alloc(4 * (1 + a0 as usize))?;
+                self.store(a, a0)?;
+                for i in 1..=a0 as u32 {
+                    self.store(a + 4 * i, a1)?;
+                }
+                a as i32
+            }
+            Runtime::AllocRecord => {
+                let bytes = usize::try_from(a0).map_err(|_| "negative record size")?;
+                self.alloc(bytes)? as i32
+            }
+            Runtime::NilRecord => return Err(at(pos(a0), "nil record dereferenced")),
+            Runtime::IndexOutOfRange => {
+                let msg = format!("index {} out of range for array of size {}", a0, a1);
+                return Err(at(pos(a2), &msg));
+            }
+            Runtime::DivisionByZero => return Err(at(pos(a0), "division by zero")),
+        };
+        self.regs[V0] = result;
+        Ok(None)
+    }
+}
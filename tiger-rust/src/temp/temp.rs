@@ -0,0 +1,109 @@
+use std::cell::Cell;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Temps below this number are reserved for machine registers, so each
+/// frame can name its registers with [`Temp::precolored`].
+pub const FIRST_TEMP: u32 = 100;
+
+thread_local! {
+    static NEXT_TEMP: Cell<u32> = const { Cell::new(FIRST_TEMP) };
+    static NEXT_LABEL: Cell<u32> = const { Cell::new(0) };
+}
+
+/// An abstract register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Temp(u32);
+
+impl Temp {
+    pub fn new() -> Temp {
+        NEXT_TEMP.with(|next| {
+            let temp = next.get();
+            next.set(temp + 1);
+            Temp(temp)
+        })
+    }
+
+    pub const fn precolored(register: u32) -> Temp {
+        assert!(register < FIRST_TEMP);
+        Temp(register)
+    }
+
+    pub fn index(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_precolored(self) -> bool {
+        self.0 < FIRST_TEMP
+    }
+}
+
+impl Default for Temp {
+    fn default() -> Self {
+        Temp::new()
+    }
+}
+
+impl fmt::Display for Temp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "t{}", self.0)
+    }
+}
+
+/// A symbolic machine-language address.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Label(String);
+
+impl Label {
+    pub fn new() -> Label {
+        NEXT_LABEL.with(|next| {
+            let label = next.get();
+            next.set(label + 1);
+            Label(format!("L{}", label))
+        })
+    }
+
+    /// A fresh label that still shows where it came from, for functions
+    /// whose source names may repeat or clash with the runtime.
+    pub fn unique(name: &str) -> Label {
+        let Label(label) = Label::new();
+        Label(format!("{}_{}", name, label))
+    }
+
+    pub fn named(name: &str) -> Label {
+        Label(String::from(name))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    /// The name a label made by `unique` came from, or the whole name of
+    /// any other label.
+    pub fn source_name(&self) -> &str {
+        match self.0.rsplit_once("_L") {
+            Some((name, n)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => name,
+            _ => &self.0,
+        }
+    }
+}
+
+impl Default for Label {
+    fn default() -> Self {
+        Label::new()
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Restarts temp and label numbering on this thread, so compiling the same
+/// program twice gives the same output.
+pub fn reset() {
+    NEXT_TEMP.with(|next| next.set(FIRST_TEMP));
+    NEXT_LABEL.with(|next| next.set(0));
+}
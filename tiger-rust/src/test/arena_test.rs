@@ -0,0 +1,124 @@
+use std::fs::{self, metadata};
+
+use super::util::all_path;
+use crate::frame::x86_64::X86_64Frame;
+use crate::parser::arena::{Ast, Leaf, NodeKind};
+use crate::parser::parser::compile_to_arena;
+use crate::semant::semant::trans_ast;
+use crate::translate::translate::translate;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+#[test]
+fn test_arena_round_trip() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        let parsed = match compile_to_arena(&input) {
+            Ok(ast) => ast,
+            Err(_) => continue,
+        };
+        println!("{}", input_path);
+        let exp = parsed.to_exp();
+        let json = serde_json::to_string(&exp).unwrap();
+        let moved = Ast::from_exp(exp);
+        for ast in [&parsed, &moved] {
+            assert_eq!(ast.preorder(ast.root()).count(), ast.len());
+            assert_eq!(ast.node(ast.root()).parent, None);
+            for id in ast.preorder(ast.root()) {
+                assert_ne!(ast.node(id).kind, NodeKind::Pending);
+                for child in ast.children(id) {
+                    assert!(child < &id);
+                    assert_eq!(ast.node(*child).parent, Some(id));
+                }
+            }
+        }
+        assert_eq!(serde_json::to_string(&moved.to_exp()).unwrap(), json);
+    }
+}
+
+#[test]
+fn test_arena_preorder() {
+    let ast = compile_to_arena("f(1, x)").unwrap();
+    let leaves: Vec<&Leaf> = ast
+        .preorder(ast.root())
+        .map(|id| &ast.node(id).leaf)
+        .filter(|leaf| **leaf != Leaf::None)
+        .collect();
+    let names: Vec<String> = leaves
+        .iter()
+        .map(|leaf| match leaf {
+            Leaf::Id(id) => id.v.clone(),
+            Leaf::Int(i) => i.v.to_string(),
+            _ => unreachable!(),
+        })
+        .collect();
+    assert_eq!(names, vec!["f", "1", "x"]);
+}
+
+// Each of these trees is far deeper than the default test thread stack could
+// handle recursively; they must be parsed, walked, serialized and freed
+// through the arena alone.
+
+#[test]
+fn test_arena_long_sequence() {
+    let count = 100_000;
+    let input = format!(
+        "let var a := 0 in {} end",
+        vec!["a := a + 1"; count].join(";")
+    );
+    let ast = compile_to_arena(&input).unwrap();
+    let assigns = ast
+        .preorder(ast.root())
+        .filter(|id| ast.node(*id).kind == NodeKind::MatchAssign)
+        .count();
+    assert_eq!(assigns, count);
+    assert!(ast.depth() > count);
+    let back = Ast::from_json_str(&ast.to_json_string().unwrap()).unwrap();
+    assert_eq!(back.len(), ast.len());
+    let absyn = back.to_absyn_string();
+    assert_eq!(absyn.matches("AssignExp(").count(), count);
+    assert!(trans_ast(&back).is_ok());
+}
+
+#[test]
+fn test_arena_long_operator_chain() {
+    let count = 100_000;
+    let input = vec!["1"; count].join("+");
+    let ast = compile_to_arena(&input).unwrap();
+    let ints = ast
+        .preorder(ast.root())
+        .filter(|id| ast.node(*id).kind == NodeKind::MetaInt)
+        .count();
+    assert_eq!(ints, count);
+    let dot = ast.to_dot();
+    assert_eq!(dot.matches("label=\"Meta::Int\\n1\\n@").count(), count);
+    let program = trans_ast(&ast).unwrap();
+    assert_eq!(translate::<X86_64Frame>(&program).len(), 1);
+
+    // The absyn layout indents each operand by its depth, so its output
+    // grows with the square of the chain; a shorter one keeps it small.
+    let count = 5_000;
+    let ast = compile_to_arena(&vec!["1"; count].join("+")).unwrap();
+    let absyn = ast.to_absyn_string();
+    assert_eq!(absyn.matches("OpExp(PlusOp,").count(), count - 1);
+    assert_eq!(absyn.matches("IntExp(1)").count(), count);
+}
+
+#[test]
+fn test_arena_deep_nesting() {
+    let depth = 20_000;
+    let input = format!("{}x{}", "(".repeat(depth), ")".repeat(depth));
+    let ast = compile_to_arena(&input).unwrap();
+    let seqs = ast
+        .preorder(ast.root())
+        .filter(|id| ast.node(*id).kind == NodeKind::MetaCapSeq)
+        .count();
+    assert_eq!(seqs, depth);
+    assert!(ast.depth() > depth * 10);
+    let dot = ast.to_dot();
+    assert_eq!(dot.matches("label=\"Meta::CapSeq\"").count(), depth);
+}
@@ -0,0 +1,279 @@
+use std::fs;
+use std::process::Output;
+
+use super::c_test;
+use super::codegen_test::run;
+use super::llvm_test;
+use super::rv64im;
+use super::util::{NON_TERMINATING, TEST_DATA_PATH};
+use super::wasm_host;
+use crate::bytecode::{compile::compile, vm};
+use crate::codegen::codegen::compile_with;
+use crate::frame::mips::MipsFrame;
+use crate::frame::riscv64::Riscv64Frame;
+use crate::frame::x86_64::X86_64Frame;
+use crate::interp::interp::{self, BACKTRACE_ENDS};
+use crate::interp::ir;
+use crate::parser::parser::compile as parse;
+use crate::regalloc::regalloc::Allocator;
+use crate::semant::semant::trans_prog;
+use crate::semant::tast::Program;
+use crate::sim::mips;
+use crate::temp::temp::Label;
+use crate::translate::translate::translate;
+use crate::wasm::compile::compile as compile_wasm;
+
+/// Programs that fail at run time, and the error each reports.
+const CASES: [(&str, &str); 10] = [
+    (
+        "let type r = {f: int} function get(x: r): int = x.f in get(nil) end",
+        "1.49: nil record dereferenced\n  in get\n  in tigermain",
+    ),
+    (
+        "let type r = {f: int} var x: r := nil in x.f := 1 end",
+        "1.42: nil record dereferenced\n  in tigermain",
+    ),
+    (
+        "let type a = array of int \
+           function g(x: a, i: int): int = x[i] \
+           function f(x: a): int = g(x, 3) \
+         in f(a [3] of 0) end",
+        "1.61: index 3 out of range for array of size 3\n  in g\n  in f\n  in tigermain",
+    ),
+    (
+        "let type a = array of int var x := a [2] of 0 in x[-1] := 5 end",
+        "1.53: index -1 out of range for array of size 2\n  in tigermain",
+    ),
+    (
+        "let function f(n: int): int = let function g(): int = 10 / n in g() end in f(0) end",
+        "1.55: division by zero\n  in g\n  in f\n  in tigermain",
+    ),
+    (
+        "let function f(n: int): string = if n = 0 then chr(-1) else f(n - 1) in print(f(2)) end",
+        "1.48: chr(-1) out of range\n  in f (x3)\n  in tigermain",
+    ),
+    (
+        "let function f(n: int): string = if n = 0 then chr(-1) else f(n - 1) in print(f(1000)) end",
+        "1.48: chr(-1) out of range\n  in f (x1001)\n  in tigermain",
+    ),
+    (
+        "let function f(s: string): string = substring(s, 1, 5) in print(f(\"abc\")) end",
+        "1.37: substring(\"abc\", 1, 5) out of range\n  in f\n  in tigermain",
+    ),
+    (
+        "let type a = array of int function f(n: int): a = a [n] of 0 in f(-2) end",
+        "1.51: array size -2 is negative\n  in f\n  in tigermain",
+    ),
+    (
+        "let function f(n: int): int = if n = 0 then 1 / n else g(n - 1) \
+           function g(n: int): int = f(n) \
+         in f(30) end",
+        concat!(
+            "1.45: division by zero",
+            "\n  in f\n  in g\n  in f\n  in g\n  in f\n  in g\n  in f\n  in g\n  in f\n  in g",
+            "\n  ... 42 more",
+            "\n  in f\n  in g\n  in f\n  in g\n  in f\n  in g\n  in f\n  in g\n  in f",
+            "\n  in tigermain",
+        ),
+    ),
+];
+
+#[test]
+fn test_backtrace_source_name() {
+    assert_eq!(Label::unique("f").source_name(), "f");
+    assert_eq!(Label::unique("a_L1").source_name(), "a_L1");
+    assert_eq!(Label::named("tigermain").source_name(), "tigermain");
+    assert_eq!(Label::named("f_L").source_name(), "f_L");
+}
+
+/// Runs the program `input`, which `name` tells apart from the others run
+/// at once, returning its exit status, or what it reports on failing.
+type Runner = fn(name: &str, input: &str) -> Result<i64, String>;
+
+fn program(input: &str) -> Program {
+    trans_prog(&parse(input).unwrap()).unwrap()
+}
+
+/// The status of an executable that ran, failing when it exits with 1.
+fn exit(output: Output) -> Result<i64, String> {
+    match output.status.code() {
+        Some(1) => Err(String::from_utf8(output.stderr).unwrap()),
+        code => Ok(code.unwrap_or(-1) as i64),
+    }
+}
+
+/// `status` as the executables report it, errors ending their line.
+fn reported(status: Result<i64, String>) -> Result<i64, String> {
+    status.map_err(|err| err + "\n")
+}
+
+fn run_interp(_: &str, input: &str) -> Result<i64, String> {
+    reported(interp::run(
+        &program(input),
+        &mut "".as_bytes(),
+        &mut Vec::new(),
+    ))
+}
+
+fn run_ir(_: &str, input: &str) -> Result<i64, String> {
+    let frags = translate::<X86_64Frame>(&program(input));
+    reported(ir::run(frags, false, &mut "".as_bytes(), &mut Vec::new()))
+}
+
+fn run_canon(_: &str, input: &str) -> Result<i64, String> {
+    let frags = translate::<X86_64Frame>(&program(input));
+    reported(ir::run(frags, true, &mut "".as_bytes(), &mut Vec::new()))
+}
+
+fn run_vm(_: &str, input: &str) -> Result<i64, String> {
+    let module = compile(&program(input));
+    reported(vm::run(&module, &mut "".as_bytes(), &mut Vec::new()))
+}
+
+fn run_native(name: &str, input: &str) -> Result<i64, String> {
+    exit(run(name, input, ""))
+}
+
+fn run_c(name: &str, input: &str) -> Result<i64, String> {
+    exit(c_test::run(name, input, ""))
+}
+
+fn run_llvm(name: &str, input: &str) -> Result<i64, String> {
+    exit(llvm_test::run(name, input, ""))
+}
+
+fn run_mips(_: &str, input: &str) -> Result<i64, String> {
+    let asm = compile_with(
+        translate::<MipsFrame>(&program(input)),
+        Allocator::default(),
+    );
+    reported(mips::run(&asm, &mut "".as_bytes(), &mut Vec::new()))
+}
+
+fn run_riscv64(_: &str, input: &str) -> Result<i64, String> {
+    let asm = compile_with(
+        translate::<Riscv64Frame>(&program(input)),
+        Allocator::default(),
+    );
+    reported(rv64im::run(&asm, &mut "".as_bytes(), &mut Vec::new()))
+}
+
+fn run_wasm(_: &str, input: &str) -> Result<i64, String> {
+    let binary = compile_wasm(&program(input)).encode();
+    reported(wasm_host::run(&binary, &mut "".as_bytes(), &mut Vec::new()))
+}
+
+/// Checks that `runner` reports each of [`CASES`] as its error.
+fn assert_backtraces(runner: Runner) {
+    for (i, (input, err)) in CASES.iter().enumerate() {
+        let status = runner(&format!("backtrace{}", i), input);
+        assert_eq!(status, Err(format!("{}\n", err)), "{}", input);
+    }
+}
+
+#[test]
+fn test_backtrace_engines() {
+    for runner in [run_interp, run_ir, run_canon, run_vm] {
+        assert_backtraces(runner);
+    }
+}
+
+#[test]
+fn test_backtrace_native() {
+    assert_backtraces(run_native);
+}
+
+#[test]
+fn test_backtrace_c() {
+    assert_backtraces(run_c);
+}
+
+#[test]
+fn test_backtrace_llvm() {
+    if llvm_test::has_clang() {
+        assert_backtraces(run_llvm);
+    }
+}
+
+#[test]
+fn test_backtrace_mips() {
+    assert_backtraces(run_mips);
+}
+
+#[test]
+fn test_backtrace_riscv64() {
+    assert_backtraces(run_riscv64);
+}
+
+#[test]
+fn test_backtrace_wasm() {
+    assert_backtraces(run_wasm);
+}
+
+#[test]
+fn test_backtrace_wide_column() {
+    // Packed positions keep 16 bits of column, so every engine reports an
+    // error further along the line at the last column they hold.
+    let input = format!("let var zero := 0 in {}1 / zero end", " ".repeat(70000));
+    let err = "1.65535: division by zero\n  in tigermain\n";
+    let mut runners: Vec<Runner> = vec![
+        run_interp,
+        run_ir,
+        run_canon,
+        run_vm,
+        run_native,
+        run_c,
+        run_mips,
+        run_riscv64,
+        run_wasm,
+    ];
+    if llvm_test::has_clang() {
+        runners.push(run_llvm);
+    }
+    for runner in runners {
+        assert_eq!(runner("backtrace_wide", &input), Err(String::from(err)));
+    }
+}
+
+#[test]
+fn test_backtrace_deep_recursion() {
+    // Recursing through two functions in turn until the depth limit, these
+    // would take a line per activation without the fold in the middle.
+    for file_name in NON_TERMINATING {
+        let input = fs::read_to_string(format!("{}{}", TEST_DATA_PATH, file_name)).unwrap();
+        for runner in [run_interp, run_ir, run_canon, run_vm, run_mips] {
+            let err = runner(file_name, &input).unwrap_err();
+            let lines: Vec<_> = err.lines().collect();
+            assert_eq!(
+                lines.len(),
+                2 * BACKTRACE_ENDS + 2,
+                "{}: {}",
+                file_name,
+                err
+            );
+            assert!(lines[BACKTRACE_ENDS + 1].starts_with("  ... "), "{}", err);
+            assert_eq!(lines.last(), Some(&"  in tigermain"), "{}", err);
+        }
+    }
+}
+
+#[test]
+fn test_backtrace_stack_overflow() {
+    // Native code recurses until the stack runs out instead.
+    let mut runners: Vec<Runner> = vec![run_native, run_c];
+    if llvm_test::has_clang() {
+        runners.push(run_llvm);
+    }
+    for file_name in NON_TERMINATING {
+        let input = fs::read_to_string(format!("{}{}", TEST_DATA_PATH, file_name)).unwrap();
+        for runner in &runners {
+            let status = runner(file_name, &input);
+            assert_eq!(
+                status,
+                Err(String::from("stack overflow\n")),
+                "{}",
+                file_name
+            );
+        }
+    }
+}
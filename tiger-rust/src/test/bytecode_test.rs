@@ -0,0 +1,157 @@
+use std::fs::{self, metadata};
+
+use super::util::{all_path, NON_TERMINATING};
+
+use crate::bytecode::bytecode::{Module, Op};
+use crate::bytecode::compile::compile;
+use crate::bytecode::disasm::disassemble;
+use crate::bytecode::vm::{self, Vm};
+use crate::interp::interp;
+use crate::parser::parser::compile as parse;
+use crate::semant::semant::trans_prog;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+fn module(input: &str) -> Module {
+    compile(&trans_prog(&parse(input).unwrap()).unwrap())
+}
+
+/// Runs `input` on the VM, returning its exit status and output.
+fn run(input: &str, stdin: &str) -> Result<(i64, String), String> {
+    let mut output = Vec::new();
+    let status = vm::run(&module(input), &mut stdin.as_bytes(), &mut output)?;
+    Ok((status, String::from_utf8(output).unwrap()))
+}
+
+#[test]
+fn test_bytecode_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let (status, output) = run(&input, "").unwrap();
+    assert_eq!(status, 0);
+    assert_eq!(output.lines().count(), 92 * 9);
+    assert!(output.starts_with(" O . . . . . . .\n . . . . O . . .\n"));
+}
+
+#[test]
+fn test_bytecode_merge() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    assert_eq!(
+        run(&input, "1 4 9 ;\n2 3 10 11 ;\n"),
+        Ok((0, String::from("1 2 3 4 9 10 11 \n")))
+    );
+}
+
+#[test]
+fn test_bytecode_matches_interp() {
+    let mut inputs = vec![
+        // A break that leaves operands on the stack, and one out of a for.
+        String::from(
+            "let var n := 0 var s := \"\" \
+             in while 1 do n := n + (if n > 3 then break; 2); \
+                for i := 1 to 10 do (s := concat(s, chr(ord(\"a\") + i)); if i = 3 then break); \
+                print(concat(s, chr(ord(\"0\") + n))) \
+             end",
+        ),
+        String::from("(print(\"x\"); exit(7); print(\"y\"))"),
+        String::from("let type r = {f: int} var x: r := nil in print(\"a\"); x.f := 1 end"),
+        String::from("let type a = array of int var x := a [2] of 0 in x[-1] end"),
+        String::from("substring(\"abc\", 1, 5)"),
+        String::from("if 1 | 1 / 0 then print(chr(48 + 2 / (1 & 0)))"),
+    ];
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+        if metadata(&input_path).unwrap().is_dir() || NON_TERMINATING.contains(&file_name) {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path).unwrap();
+        if matches!(parse(&input).map(|exp| trans_prog(&exp)), Ok(Ok(_))) {
+            inputs.push(input);
+        }
+    }
+    let stdin = "5 3 1 ;\n4 2 ;\n";
+    for input in inputs {
+        let program = trans_prog(&parse(&input).unwrap()).unwrap();
+        let mut expected = Vec::new();
+        let status = interp::run(&program, &mut stdin.as_bytes(), &mut expected);
+        let expected = status.map(|status| (status, String::from_utf8(expected).unwrap()));
+        assert_eq!(run(&input, stdin), expected, "{}", input);
+    }
+}
+
+#[test]
+fn test_bytecode_disassemble() {
+    let module = module(
+        "let function f(x: int): int = let function g(): int = x * 2 in g() end \
+         in print(chr(f(33))) end",
+    );
+    assert_eq!(
+        disassemble(&module),
+        "constants:\n\
+         \x20    0  33\n\
+         \x20    1  2\n\
+         function 0 tigermain (params 0, locals 0):\n\
+         \x20     0  1.87     const         0  ; 33\n\
+         \x20     3  1.85     call          1 0  ; f\n\
+         \x20     7  1.81     builtin       4  ; chr\n\
+         \x20     9  1.75     builtin       0  ; print\n\
+         \x20    11  1.1      return\n\
+         function 1 f (params 1, locals 1, returns):\n\
+         \x20     0  1.64     call          2 0  ; g\n\
+         \x20     4  1.25     return\n\
+         function 2 g (params 0, locals 0, returns):\n\
+         \x20     0  1.55     load_outer    1 0\n\
+         \x20     4  1.59     const         1  ; 2\n\
+         \x20     7  1.55     mul\n\
+         \x20     8           return\n"
+    );
+}
+
+#[test]
+fn test_bytecode_encode() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let module = module(&input);
+    let bytes = module.encode();
+    assert_eq!(Module::decode(&bytes), Ok(module.clone()));
+
+    assert_eq!(
+        Module::decode(b"TIGC\x01"),
+        Err(String::from("not a Tiger bytecode module"))
+    );
+    assert_eq!(
+        Module::decode(&bytes[..bytes.len() - 1]),
+        Err(String::from("truncated module"))
+    );
+    // Point the first jump of the program into the middle of an instruction.
+    let mut bad = module;
+    let code = &mut bad.funcs[1].code;
+    let jump = code
+        .iter()
+        .position(|op| *op == Op::JumpIfFalse as u8)
+        .unwrap();
+    code[jump + 1] += 1;
+    assert!(Module::decode(&bad.encode())
+        .unwrap_err()
+        .ends_with("operand out of range"));
+}
+
+#[test]
+fn test_bytecode_gc() {
+    // Each iteration drops a list of 100 records and a few strings.
+    let input = "let type list = {hd: string, tl: list} \
+                   var keep: list := nil \
+                   function build(n: int): list = \
+                     let var l: list := nil \
+                     in for i := 1 to n do l := list {hd = concat(\"x\", chr(i)), tl = l}; l end \
+                 in for i := 1 to 1000 do (let var l := build(100) in if i = 500 then keep := l end); \
+                    print(keep.tl.hd) \
+                 end";
+    let module = module(input);
+    let mut output = Vec::new();
+    let mut input = "".as_bytes();
+    let mut vm = Vm::new(&module, &mut input, &mut output);
+    assert_eq!(vm.run(), Ok(0));
+    assert!(vm.collections() > 10);
+    assert!(vm.heap_size() < 20000, "{}", vm.heap_size());
+    drop(vm);
+    assert_eq!(output, b"xc");
+}
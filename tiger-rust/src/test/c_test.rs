@@ -0,0 +1,106 @@
+use std::fs::{self, metadata};
+use std::process::Output;
+
+use super::util::{all_path, run_exe, runtime, scratch_path, stdout, NON_TERMINATING, TORTURE};
+
+use crate::c::compile::compile;
+use crate::codegen::link::link_c;
+use crate::interp::interp;
+use crate::parser::parser::compile as parse;
+use crate::semant::semant::trans_prog;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+/// Compiles `input` through C and runs it on `stdin` with the environment
+/// variables `vars`.
+pub fn run_env(name: &str, input: &str, stdin: &str, vars: &[(&str, &str)]) -> Output {
+    let program = trans_prog(&parse(input).unwrap()).unwrap();
+    let exe_path = scratch_path(name);
+    link_c(&compile(&program), &exe_path, runtime())
+        .unwrap_or_else(|err| panic!("{}: {}", name, err));
+    run_exe(&exe_path, stdin, vars)
+}
+
+pub fn run(name: &str, input: &str, stdin: &str) -> Output {
+    run_env(name, input, stdin, &[])
+}
+
+#[test]
+fn test_c_matches_interp() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+        if NON_TERMINATING.contains(&file_name) {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path).unwrap();
+        let Ok(Ok(program)) = parse(&input).map(|exp| trans_prog(&exp)) else {
+            continue;
+        };
+        let mut expected = Vec::new();
+        let status = interp::run(&program, &mut "".as_bytes(), &mut expected);
+        let output = run(file_name, &input, "");
+        match status {
+            Ok(status) => {
+                assert_eq!(output.status.code(), Some(status as i32), "{}", file_name);
+                assert_eq!(output.stdout, expected, "{}", file_name);
+            }
+            Err(err) => {
+                assert_eq!(output.status.code(), Some(1), "{}", file_name);
+                let stderr = String::from_utf8(output.stderr).unwrap();
+                assert_eq!(stderr, format!("{}\n", err), "{}", file_name);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_c_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let output = stdout(run("queens", &input, ""));
+    assert_eq!(output.lines().count(), 92 * 9);
+    assert!(output.starts_with(" O . . . . . . .\n . . . . O . . .\n"));
+}
+
+#[test]
+fn test_c_merge() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    let output = stdout(run("merge", &input, "1 4 9 ;\n2 3 10 11 ;\n"));
+    assert_eq!(output, "1 2 3 4 9 10 11 \n");
+}
+
+#[test]
+fn test_c_calls() {
+    // Static links two levels up, and an exit status.
+    let input = "let var base := 100 \
+                   function many(a: int, b: int, c: int, d: int, e: int, \
+                                 f: int, g: int, h: int, i: int, j: int): int = \
+                     let function inner(x: int): int = x + base + j \
+                     in inner(a + b * 2 + c * 3 + d * 4 + e * 5 + f * 6 + g * 7 \
+                              + h * 8 + i * 9 + j * 10) end \
+                 in exit(many(1, 2, 3, 4, 5, 6, 7, 8, 9, 10) - 300) end";
+    assert_eq!(run("calls", input, "").status.code(), Some(195));
+}
+
+#[test]
+fn test_c_every_allocation() {
+    let input = "let type list = {hd: int, tl: list} \
+                   type lists = array of list \
+                   function range(lo: int, hi: int): list = \
+                     if lo > hi then nil else list {hd = lo, tl = range(lo + 1, hi)} \
+                   function sum(l: list): int = if l = nil then 0 else l.hd + sum(l.tl) \
+                   var a := lists [3] of range(1, 2) \
+                   var s := concat(\"n\", concat(\"=\", chr(48 + sum(a[1])))) \
+                 in a[2] := list {hd = sum(range(1, 4)), tl = range(1, 3)}; \
+                    print(s); print(chr(48 + sum(a[0]) + a[2].hd)); \
+                    print(chr(48 + sum(a[2]) - 10)) \
+                 end";
+    assert_eq!(stdout(run_env("torture", input, "", &TORTURE)), "n=3=6");
+    let queens = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    assert_eq!(
+        stdout(run_env("queens-torture", &queens, "", &TORTURE)),
+        stdout(run("queens-plain", &queens, ""))
+    );
+}
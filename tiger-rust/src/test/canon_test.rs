@@ -0,0 +1,250 @@
+use std::collections::HashSet;
+use std::fs::{self, metadata};
+
+use super::util::all_path;
+
+use crate::canon::canon::{basic_blocks, canonicalize, linearize, trace_schedule};
+use crate::frame::x86_64::X86_64Frame;
+use crate::parser::parser::compile;
+use crate::semant::semant::trans_prog;
+use crate::temp::temp::{Label, Temp};
+use crate::translate::translate::{translate, Frag};
+use crate::tree::tree::{seq, BinOp, Exp, RelOp, Stm};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+/// Bodies of every procedure of every testcase that type checks.
+fn all_bodies() -> Vec<(String, Stm)> {
+    let mut bodies = Vec::new();
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        let program = match compile(&input).map(|exp| trans_prog(&exp)) {
+            Ok(Ok(program)) => program,
+            _ => continue,
+        };
+        for frag in translate::<X86_64Frame>(&program) {
+            if let Frag::Proc { body, .. } = frag {
+                bodies.push((input_path.clone(), body));
+            }
+        }
+    }
+    bodies
+}
+
+/// Checks that `exp` has no `ESeq` and, unless `call_allowed`, no `Call`.
+fn check_exp(exp: &Exp, call_allowed: bool) -> Result<(), String> {
+    match exp {
+        Exp::ESeq(..) => Err(String::from("ESeq left")),
+        Exp::Call(f, args) if call_allowed => {
+            check_exp(f, false)?;
+            args.iter().try_for_each(|arg| check_exp(arg, false))
+        }
+        Exp::Call(..) => Err(String::from("nested Call")),
+        Exp::BinOp(_, a, b) => {
+            check_exp(a, false)?;
+            check_exp(b, false)
+        }
+        Exp::Mem(a) => check_exp(a, false),
+        Exp::Const(_) | Exp::Name(_) | Exp::Temp(_) => Ok(()),
+    }
+}
+
+fn check_stm(stm: &Stm) -> Result<(), String> {
+    match stm {
+        Stm::Seq(..) => Err(String::from("Seq left")),
+        Stm::Move(dst, src) => {
+            check_exp(dst, false)?;
+            check_exp(src, matches!(dst.as_ref(), Exp::Temp(_)))
+        }
+        Stm::Exp(exp) => check_exp(exp, true),
+        Stm::Jump(exp, _) => check_exp(exp, false),
+        Stm::CJump(_, a, b, _, _) => {
+            check_exp(a, false)?;
+            check_exp(b, false)
+        }
+        Stm::Label(_) | Stm::Pos(_) => Ok(()),
+    }
+}
+
+fn check_linear(stms: &[Stm]) -> Result<(), String> {
+    stms.iter().try_for_each(check_stm)
+}
+
+fn check_blocks(blocks: &[Vec<Stm>]) -> Result<(), String> {
+    for block in blocks {
+        if !matches!(block.first(), Some(Stm::Label(_))) {
+            return Err(String::from("block does not start with a label"));
+        }
+        if !matches!(block.last(), Some(Stm::Jump(..) | Stm::CJump(..))) {
+            return Err(String::from("block does not end with a jump"));
+        }
+        let inner = &block[1..block.len() - 1];
+        if inner
+            .iter()
+            .any(|stm| matches!(stm, Stm::Label(_) | Stm::Jump(..) | Stm::CJump(..)))
+        {
+            return Err(String::from("label or jump inside a block"));
+        }
+    }
+    Ok(())
+}
+
+fn check_trace(stms: &[Stm]) -> Result<(), String> {
+    check_linear(stms)?;
+    let mut defined = HashSet::new();
+    for stm in stms {
+        if let Stm::Label(label) = stm {
+            if !defined.insert(label.clone()) {
+                return Err(format!("label {} defined twice", label.name()));
+            }
+        }
+    }
+    for (index, stm) in stms.iter().enumerate() {
+        let targets: Vec<&Label> = match stm {
+            Stm::CJump(_, _, _, t, f) => {
+                match stms.get(index + 1) {
+                    Some(Stm::Label(next)) if next == f => (),
+                    _ => return Err(format!("CJump not followed by {}", f.name())),
+                }
+                vec![t, f]
+            }
+            Stm::Jump(_, labels) => labels.iter().collect(),
+            _ => continue,
+        };
+        if let Some(label) = targets.iter().find(|label| !defined.contains(**label)) {
+            return Err(format!("jump to undefined label {}", label.name()));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_canon_all() {
+    for (path, body) in all_bodies() {
+        let stms = linearize(body.clone());
+        check_linear(&stms).unwrap_or_else(|err| panic!("{}: {}", path, err));
+        let (blocks, done) = basic_blocks(stms);
+        check_blocks(&blocks).unwrap_or_else(|err| panic!("{}: {}", path, err));
+        let stms = trace_schedule(blocks, done.clone());
+        check_trace(&stms).unwrap_or_else(|err| panic!("{}: {}", path, err));
+        assert_eq!(stms.last(), Some(&Stm::Label(done)), "{}", path);
+        assert_eq!(canonicalize(body).len(), stms.len(), "{}", path);
+    }
+}
+
+fn call(name: &str, args: Vec<Exp>) -> Exp {
+    Exp::Call(Box::new(Exp::Name(Label::named(name))), args)
+}
+
+fn eseq(stm: Stm, exp: Exp) -> Exp {
+    Exp::ESeq(Box::new(stm), Box::new(exp))
+}
+
+fn mv(dst: Exp, src: Exp) -> Stm {
+    Stm::Move(Box::new(dst), Box::new(src))
+}
+
+#[test]
+fn test_linearize_order() {
+    let a = Temp::new();
+    let b = Temp::new();
+    // a + (a := 1; b): the read of `a` must happen before the assignment.
+    let stm = mv(
+        Exp::Temp(b),
+        Exp::BinOp(
+            BinOp::Plus,
+            Box::new(Exp::Temp(a)),
+            Box::new(eseq(mv(Exp::Temp(a), Exp::Const(1)), Exp::Temp(b))),
+        ),
+    );
+    let stms = linearize(stm);
+    check_linear(&stms).unwrap();
+    assert_eq!(stms.len(), 3);
+    let saved = match &stms[0] {
+        Stm::Move(dst, src) if src.as_ref() == &Exp::Temp(a) => match dst.as_ref() {
+            Exp::Temp(t) => *t,
+            _ => unreachable!(),
+        },
+        stm => panic!("{:?}", stm),
+    };
+    assert_eq!(stms[1], mv(Exp::Temp(a), Exp::Const(1)));
+    assert_eq!(
+        stms[2],
+        mv(
+            Exp::Temp(b),
+            Exp::BinOp(
+                BinOp::Plus,
+                Box::new(Exp::Temp(saved)),
+                Box::new(Exp::Temp(b))
+            )
+        )
+    );
+}
+
+#[test]
+fn test_linearize_long_sequence() {
+    // One `Seq` per statement, far deeper than canon could recurse.
+    let count = 100_000;
+    let t = Temp::new();
+    let body = seq((0..count)
+        .map(|i| mv(Exp::Temp(t), Exp::Const(i)))
+        .collect());
+    let stm = Stm::Exp(Box::new(eseq(body, Exp::Temp(t))));
+    let stms = linearize(stm);
+    check_linear(&stms).unwrap();
+    assert_eq!(stms.len(), count as usize + 1);
+    for (i, stm) in stms.iter().take(count as usize).enumerate() {
+        assert_eq!(*stm, mv(Exp::Temp(t), Exp::Const(i as i64)));
+    }
+}
+
+#[test]
+fn test_linearize_calls() {
+    // f(g(1), h(2)) in a memory store target: both inner calls are lifted.
+    let stm = mv(
+        Exp::Mem(Box::new(call("f", vec![Exp::Const(0)]))),
+        call("g", vec![call("h", vec![Exp::Const(1)]), Exp::Const(2)]),
+    );
+    let stms = linearize(stm);
+    check_linear(&stms).unwrap();
+    let names: Vec<&str> = stms
+        .iter()
+        .filter_map(|stm| match stm {
+            Stm::Move(_, src) => match src.as_ref() {
+                Exp::Call(f, _) => match f.as_ref() {
+                    Exp::Name(label) => Some(label.name()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["f", "h", "g"]);
+}
+
+#[test]
+fn test_trace_negates_cjump() {
+    let t = Label::new();
+    let f = Label::new();
+    let stms = vec![
+        Stm::CJump(
+            RelOp::Lt,
+            Box::new(Exp::Const(1)),
+            Box::new(Exp::Const(2)),
+            t.clone(),
+            f.clone(),
+        ),
+        Stm::Label(t.clone()),
+        Stm::Exp(Box::new(call("print", vec![]))),
+        Stm::Label(f.clone()),
+    ];
+    let (blocks, done) = basic_blocks(stms);
+    assert_eq!(blocks.len(), 3);
+    let stms = trace_schedule(blocks, done);
+    check_trace(&stms).unwrap();
+}
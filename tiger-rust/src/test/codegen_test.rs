@@ -0,0 +1,214 @@
+use std::fs::{self, metadata};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use super::util::{all_path, run_exe, runtime, scratch_path, stdout, NON_TERMINATING};
+
+use crate::assem::assem::Instr;
+use crate::c::compile::compile as compile_c;
+use crate::codegen::codegen::compile_with;
+use crate::codegen::link::{link, link_c};
+use crate::frame::x86_64::{X86_64Frame, RAX, RDI};
+use crate::parser::parser::compile as parse;
+use crate::regalloc::regalloc::Allocator;
+use crate::semant::semant::trans_prog;
+use crate::temp::temp::{Label, Temp};
+use crate::translate::translate::translate;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+/// Compiles `input` to an executable named after `name`.
+pub fn build(name: &str, input: &str) -> PathBuf {
+    build_with(name, input, Allocator::default())
+}
+
+pub fn build_with(name: &str, input: &str, allocator: Allocator) -> PathBuf {
+    let program = trans_prog(&parse(input).unwrap()).unwrap();
+    let asm = compile_with(translate::<X86_64Frame>(&program), allocator);
+    let exe_path = scratch_path(name);
+    link(&asm, &exe_path, runtime()).unwrap_or_else(|err| panic!("{}: {}", name, err));
+    exe_path
+}
+
+pub fn run(name: &str, input: &str, stdin: &str) -> Output {
+    run_with(name, input, stdin, Allocator::default())
+}
+
+pub fn run_with(name: &str, input: &str, stdin: &str, allocator: Allocator) -> Output {
+    run_exe(&build_with(name, input, allocator), stdin, &[])
+}
+
+pub fn run_stdout(name: &str, input: &str) -> String {
+    run_stdout_with(name, input, Allocator::default())
+}
+
+pub fn run_stdout_with(name: &str, input: &str, allocator: Allocator) -> String {
+    stdout(run_with(name, input, "", allocator))
+}
+
+const PRINTINT: &str = "function printint(i: int) = \
+     let function f(i: int) = \
+       if i > 0 then (f(i / 10); print(chr(i - i / 10 * 10 + ord(\"0\")))) \
+     in if i < 0 then (print(\"-\"); f(-i)) else if i > 0 then f(i) else print(\"0\") end";
+
+#[test]
+fn test_format() {
+    let instr = Instr::Oper {
+        assem: String::from("\tmovq `s0, 8(`s1)\n\tjmp `j0"),
+        dst: vec![],
+        src: vec![RAX, RDI],
+        jump: Some(vec![Label::named("done")]),
+    };
+    let name = |temp: Temp| format!("%{}", temp);
+    assert_eq!(instr.format(name), "\tmovq %t0, 8(%t5)\n\tjmp done");
+    let copy = Instr::Move {
+        assem: String::from("\tmovq `s0, `d0"),
+        dst: RAX,
+        src: RAX,
+    };
+    assert!(copy.is_redundant_move(name));
+}
+
+#[test]
+fn test_link_leaves_other_files_alone() {
+    let dir = scratch_path("link-dir");
+    fs::create_dir_all(&dir).unwrap();
+    let (asm, c) = (dir.join("prog.s"), dir.join("prog.c"));
+    fs::write(&asm, "mine").unwrap();
+    fs::write(&c, "mine").unwrap();
+    let program = trans_prog(&parse("print(\"ok\")").unwrap()).unwrap();
+    let x86 = compile_with(translate::<X86_64Frame>(&program), Allocator::default());
+    link(&x86, &dir.join("prog"), runtime()).unwrap();
+    link_c(&compile_c(&program), &dir.join("prog.exe"), runtime()).unwrap();
+    assert_eq!(fs::read_to_string(&asm).unwrap(), "mine");
+    assert_eq!(fs::read_to_string(&c).unwrap(), "mine");
+    let output = Command::new(dir.join("prog")).output().unwrap();
+    assert_eq!(output.stdout, b"ok");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_codegen_arithmetic() {
+    let input = format!(
+        "let {} \
+           function fact(n: int): int = if n = 0 then 1 else n * fact(n - 1) \
+         in printint(fact(10)); print(\" \"); \
+            printint(-7 / 2); print(\" \"); \
+            printint(4000000000 * 3 - 12); print(\" \"); \
+            printint(1 + (2 * 3) - 4 / (1 + 1)) \
+         end",
+        PRINTINT
+    );
+    assert_eq!(run_stdout("arithmetic", &input), "3628800 -3 11999999988 5");
+}
+
+#[test]
+fn test_codegen_divide_overflow() {
+    // The least integer divided by -1 wraps around, as in the interpreter,
+    // whether the -1 is a constant or not.
+    let input = "let var min := 0 - 9223372036854775807 - 1 \
+                   var minus := -1 \
+                   function check(i: int) = print(if i = min then \"min \" else \"bad \") \
+                 in check(min / minus); check(min / -1); \
+                    print(if 7 / minus = -7 then \"ok\" else \"bad\") end";
+    assert_eq!(run_stdout("divide-overflow", input), "min min ok");
+}
+
+#[test]
+fn test_codegen_calls() {
+    // More arguments than registers, and static links two levels up.
+    let input = format!(
+        "let {} \
+           var base := 100 \
+           function many(a: int, b: int, c: int, d: int, e: int, f: int, g: int, h: int): int = \
+             let function inner(x: int): int = x + base + h \
+             in inner(a + b * 2 + c * 3 + d * 4 + e * 5 + f * 6 + g * 7 + h * 8) end \
+         in printint(many(1, 2, 3, 4, 5, 6, 7, 8)) end",
+        PRINTINT
+    );
+    assert_eq!(run_stdout("calls", &input), "312");
+}
+
+#[test]
+fn test_codegen_data() {
+    let input = format!(
+        "let {} \
+           type list = {{hd: int, tl: list}} \
+           type arr = array of int \
+           var l := list {{hd = 1, tl = list {{hd = 2, tl = nil}}}} \
+           var a := arr [5] of 7 \
+           var sum := 0 \
+         in a[2] := 10; \
+            for i := 0 to 4 do sum := sum + a[i]; \
+            printint(l.tl.hd); print(\" \"); printint(sum); print(\" \"); \
+            if l.tl.tl = nil then print(\"nil\") \
+         end",
+        PRINTINT
+    );
+    assert_eq!(run_stdout("data", &input), "2 38 nil");
+}
+
+#[test]
+fn test_codegen_strings() {
+    let input = "(if \"abc\" < \"abd\" then print(\"lt \"); \
+                  if \"abc\" = \"abc\" then print(\"eq \"); \
+                  if \"abc\" <> \"ab\" then print(\"ne \"); \
+                  print(concat(\"foo\", substring(\"xbarx\", 1, 3))); \
+                  print(\"\\t\\\"q\\\"\\n\"))";
+    assert_eq!(run_stdout("strings", input), "lt eq ne foobar\t\"q\"\n");
+}
+
+#[test]
+fn test_codegen_loops() {
+    let input = format!(
+        "let {} var n := 0 \
+         in while 1 do (n := n + 1; if n = 10 then break); \
+            for i := 1 to 100 do (if i > 5 then break; n := n + i); \
+            printint(n) \
+         end",
+        PRINTINT
+    );
+    assert_eq!(run_stdout("loops", &input), "25");
+}
+
+#[test]
+fn test_codegen_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let output = run_stdout("queens", &input);
+    assert_eq!(output.lines().count(), 92 * 9);
+    assert!(output.starts_with(" O . . . . . . .\n . . . . O . . .\n"));
+}
+
+#[test]
+fn test_codegen_merge() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    let output = run("merge", &input, "1 4 9 ;\n2 3 10 11 ;\n");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1 2 3 4 9 10 11 \n"
+    );
+}
+
+#[test]
+fn test_codegen_all() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        match parse(&input).map(|exp| trans_prog(&exp)) {
+            Ok(Ok(_)) => (),
+            _ => continue,
+        };
+        let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+        println!("{}", file_name);
+        let name = file_name.trim_end_matches(".tig");
+        if NON_TERMINATING.contains(&file_name) {
+            fs::remove_file(build(name, &input)).unwrap();
+        } else {
+            assert!(run(name, &input, "").status.success(), "{}", file_name);
+        }
+    }
+}
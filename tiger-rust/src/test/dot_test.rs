@@ -0,0 +1,37 @@
+use crate::parser::dot::DotOptions;
+use crate::parser::parser::compile;
+
+fn count(dot: &str, pattern: &str) -> usize {
+    dot.matches(pattern).count()
+}
+
+#[test]
+fn test_dot() {
+    let exp = compile("if a then x := \"s\\\"q\" else y[1] := 2").unwrap();
+    let dot = exp.to_dot();
+    assert!(dot.starts_with("digraph ast {\n"));
+    assert!(dot.ends_with("}\n"));
+    let nodes = count(&dot, "[label=");
+    assert_eq!(count(&dot, " -> "), nodes - 1);
+    assert!(dot.contains("label=\"Match::IfThenElse\"]"));
+    assert!(dot.contains("label=\"Meta::String\\n\\\"s\\\\\\\"q\\\"\\n@"));
+    assert!(dot.contains("label=\"Refer::Array\\ny\\n@1:28\"]"));
+    assert!(dot.contains("Slice::Sheet"));
+
+    let collapsed = exp.to_dot_with(&DotOptions {
+        collapse_wrappers: true,
+    });
+    let collapsed_nodes = count(&collapsed, "[label=");
+    assert!(collapsed_nodes < nodes);
+    assert_eq!(count(&collapsed, " -> "), collapsed_nodes - 1);
+    for wrapper in [
+        "Slice::Sheet",
+        "Sheet::Piece",
+        "Piece::Bit",
+        "Item::Term",
+        "Factor::Meta",
+    ] {
+        assert!(!collapsed.contains(wrapper));
+    }
+    assert!(collapsed.contains("Meta::Int\\n2"));
+}
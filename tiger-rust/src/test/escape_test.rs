@@ -0,0 +1,95 @@
+use std::fs::{self, metadata};
+
+use super::util::all_path;
+use crate::escape::escape::find_escape;
+use crate::parser::parser::compile;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+fn absyn_after_escape(input: &str) -> String {
+    let exp = compile(input).unwrap();
+    find_escape(&exp);
+    exp.to_absyn_string()
+}
+
+#[test]
+fn test_escape_var() {
+    let absyn =
+        absyn_after_escape("let var a := 1 var b := 2 function f() : int = a in b + f() end");
+    assert!(absyn.contains("VarDec(a,true,"));
+    assert!(absyn.contains("VarDec(b,false,"));
+}
+
+#[test]
+fn test_escape_param() {
+    let absyn = absyn_after_escape(
+        "let function f(x: int, y: int) : int = \
+         let function g() : int = x in g() + y end \
+         in f(1, 2) end",
+    );
+    assert!(absyn.contains("(x,true,int)"));
+    assert!(absyn.contains("(y,false,int)"));
+}
+
+#[test]
+fn test_escape_for() {
+    let absyn =
+        absyn_after_escape("for i := 0 to 10 do let function g() = print(chr(i)) in g() end");
+    assert!(absyn.contains("ForExp(\nitrue,"));
+    let absyn = absyn_after_escape("for i := 0 to 10 do print(chr(i))");
+    assert!(absyn.contains("ForExp(\nifalse,"));
+}
+
+#[test]
+fn test_escape_shadowing() {
+    let absyn = absyn_after_escape(
+        "let var a := 1 \
+         function f(a: int) : int = let function g() : int = a in g() end \
+         in f(a) end",
+    );
+    assert!(absyn.contains("VarDec(a,false,"));
+    assert!(absyn.contains("(a,true,int)"));
+    let absyn = absyn_after_escape("let var a := 1 in let var b := a in b end end");
+    assert!(absyn.contains("VarDec(a,false,"));
+    assert!(absyn.contains("VarDec(b,false,"));
+}
+
+#[test]
+fn test_escape_record_fields_untouched() {
+    let absyn = absyn_after_escape("let type r = {x: int} var v := r{x = 1} in v.x end");
+    assert!(absyn.contains("(x,true,int)"));
+    assert!(absyn.contains("VarDec(v,false,"));
+}
+
+#[test]
+fn test_escape_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let absyn = absyn_after_escape(&input);
+    for var in ["N", "row", "col", "diag1", "diag2"] {
+        assert!(absyn.contains(&format!("VarDec({},true,", var)));
+    }
+    assert!(absyn.contains("(c,false,int)"));
+    for var in ["i", "j", "r"] {
+        assert!(absyn.contains(&format!("ForExp(\n{}false,", var)));
+    }
+}
+
+#[test]
+fn test_escape_rerun() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        let exp = match compile(&input) {
+            Ok(exp) => exp,
+            Err(_) => continue,
+        };
+        println!("{}", input_path);
+        find_escape(&exp);
+        let once = exp.to_absyn_string();
+        find_escape(&exp);
+        assert_eq!(exp.to_absyn_string(), once);
+    }
+}
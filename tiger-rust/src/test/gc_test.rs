@@ -0,0 +1,129 @@
+use std::fs;
+use std::process::Output;
+
+use super::codegen_test::build;
+use super::util::{run_exe, TORTURE};
+
+use crate::codegen::codegen::instructions;
+use crate::frame::x86_64::X86_64Frame;
+use crate::gc::gc::FrameMap;
+use crate::parser::parser::compile as parse;
+use crate::semant::semant::trans_prog;
+use crate::translate::translate::{translate, Frag};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+const LIST: &str = "type list = {hd: int, tl: list} \
+     function cons(hd: int, tl: list): list = list {hd = hd, tl = tl} \
+     function range(lo: int, hi: int): list = \
+       if lo > hi then nil else cons(lo, range(lo + 1, hi)) \
+     function sum(l: list): int = if l = nil then 0 else l.hd + sum(l.tl)";
+
+/// The frame maps of every function of `input`, by source name.
+fn frame_maps(input: &str) -> Vec<(String, FrameMap)> {
+    let program = trans_prog(&parse(input).unwrap()).unwrap();
+    translate::<X86_64Frame>(&program)
+        .into_iter()
+        .filter_map(|frag| match frag {
+            Frag::Proc { body, mut frame } => {
+                let (_, map) = instructions(&mut frame, body);
+                Some((map.name.clone(), map))
+            }
+            Frag::String { .. } => None,
+        })
+        .collect()
+}
+
+fn run_env(name: &str, input: &str, vars: &[(&str, &str)]) -> Output {
+    run_exe(&build(name, input), "", vars)
+}
+
+#[test]
+fn test_gc_frame_maps() {
+    let input = format!(
+        "let {} function f() = () \
+           var l := list {{hd = 1, tl = nil}} \
+         in f(); l.hd + sum(l) end",
+        LIST
+    );
+    let maps = frame_maps(&input);
+    let (_, main) = maps.iter().find(|(name, _)| name == "tigermain").unwrap();
+    // Only `l` lives across a call: not while the record is allocated, not
+    // across the report of a nil `l`, and not past the last call, which
+    // takes it as an argument.
+    assert_eq!(main.slots.len(), 1);
+    let live: Vec<&[usize]> = main.sites.iter().map(|site| &site.live[..]).collect();
+    assert_eq!(live, vec![&[][..], &[0], &[], &[]]);
+    // `cons` needs `tl` after allocating; `sum` reads `l` before its call.
+    let (_, cons) = maps.iter().find(|(name, _)| name == "cons").unwrap();
+    assert_eq!(cons.slots.len(), 1);
+    assert_eq!(cons.sites[0].live, vec![0]);
+    let (_, sum) = maps.iter().find(|(name, _)| name == "sum").unwrap();
+    assert!(sum.slots.is_empty());
+    // The nil checks of `l.hd` and `l.tl`, and the recursive call.
+    assert_eq!(sum.sites.len(), 3);
+}
+
+#[test]
+fn test_gc_stress() {
+    // Allocates over 25MB through a 64KB heap, keeping a little alive.
+    let input = format!(
+        "let {} \
+           type table = array of list \
+           function label(n: int): string = \
+             if n = 0 then \"\" else concat(label(n - 1), chr(ord(\"a\") + n - 1)) \
+           var keep := range(1, 100) \
+           var lists := table [10] of nil \
+           var total := 0 \
+           var s := \"\" \
+         in for i := 1 to 20000 do ( \
+              lists[i - i / 10 * 10] := range(1, 50); \
+              total := total + sum(lists[3]) + sum(keep); \
+              s := concat(label(5), substring(\"xyz\", 0, 2))); \
+            print(if total = 126497450 & sum(keep) = 5050 then s else \"wrong\") \
+         end",
+        LIST
+    );
+    let output = run_env(
+        "gc-stress",
+        &input,
+        &[("TIGER_HEAP", "65536"), ("TIGER_GC_STATS", "1")],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "abcdexy");
+    let stats = String::from_utf8(output.stderr).unwrap();
+    let words: Vec<&str> = stats.split_whitespace().collect();
+    let collections: u64 = words[1].parse().unwrap();
+    let allocated: u64 = words[3].parse().unwrap();
+    assert!(collections > 100, "{}", stats);
+    assert!(allocated > 65536 * 100, "{}", stats);
+    assert!(stats.ends_with("65536 byte heap\n"), "{}", stats);
+}
+
+#[test]
+fn test_gc_every_allocation() {
+    // Collecting before every allocation moves every object as often as
+    // possible, so a root missing from a frame map shows up as garbage.
+    let input = format!(
+        "let {} \
+           type pair = {{l: list, s: string}} \
+           type lists = array of list \
+           function twice(l: list): pair = \
+             pair {{l = cons(sum(l), range(1, 3)), s = concat(\"n\", concat(\"=\", chr(48 + sum(l))))}} \
+           var a := lists [3] of range(1, 2) \
+           var p := twice(a[1]) \
+         in a[2] := cons(sum(range(1, 4)), p.l); \
+            print(p.s); print(chr(48 + sum(a[0]) + a[2].hd)); \
+            print(chr(48 + sum(a[2]) - 10)) \
+         end",
+        LIST
+    );
+    let output = run_env("gc-torture", &input, &TORTURE);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "n=3=9");
+    let queens = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let plain = run_env("gc-queens", &queens, &[]);
+    let tortured = run_env("gc-queens-torture", &queens, &TORTURE);
+    assert!(tortured.status.success());
+    assert_eq!(plain.stdout, tortured.stdout);
+}
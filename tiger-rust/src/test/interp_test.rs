@@ -0,0 +1,171 @@
+use std::fs::{self, metadata};
+
+use super::codegen_test::run;
+use super::util::{all_path, NON_TERMINATING};
+
+use crate::interp::interp;
+use crate::parser::parser::compile as parse;
+use crate::semant::semant::trans_prog;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+/// Interprets `input` on `stdin`, returning its exit status and output.
+fn interp(input: &str, stdin: &str) -> Result<(i64, String), String> {
+    let program = trans_prog(&parse(input).unwrap())?;
+    let mut output = Vec::new();
+    let status = interp::run(&program, &mut stdin.as_bytes(), &mut output)?;
+    Ok((status, String::from_utf8(output).unwrap()))
+}
+
+fn interp_stdout(input: &str) -> String {
+    let (status, output) = interp(input, "").unwrap();
+    assert_eq!(status, 0);
+    output
+}
+
+#[test]
+fn test_interp_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let output = interp_stdout(&input);
+    assert_eq!(output.lines().count(), 92 * 9);
+    assert!(output.starts_with(" O . . . . . . .\n . . . . O . . .\n"));
+}
+
+#[test]
+fn test_interp_merge() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    assert_eq!(
+        interp(&input, "1 4 9 ;\n2 3 10 11 ;\n"),
+        Ok((0, String::from("1 2 3 4 9 10 11 \n")))
+    );
+}
+
+#[test]
+fn test_interp_builtins() {
+    let input = "(print(concat(\"ab\", substring(\"xcdx\", 1, 2))); \
+                  print(chr(ord(\"A\") + size(\"abc\"))); \
+                  if not(0) & ord(\"\") = -1 then print(\"!\"); \
+                  print(getchar()); print(getchar()); print(getchar()); \
+                  flush(); exit(3); print(\"unreachable\"))";
+    assert_eq!(interp(input, "xy"), Ok((3, String::from("abcdD!xy"))));
+}
+
+#[test]
+fn test_interp_closures() {
+    // Each level of `walk` sees its own `depth`, and `bump` the counter two
+    // levels up.
+    let input = "let var count := 0 \
+                   function walk(depth: int): string = \
+                     let function bump() = count := count + depth \
+                         function deeper(): string = \
+                           (bump(); if depth = 3 then chr(ord(\"0\") + count) \
+                            else concat(chr(ord(\"0\") + depth), walk(depth + 1))) \
+                     in deeper() end \
+                 in print(walk(1)) end";
+    assert_eq!(interp_stdout(input), "126");
+}
+
+#[test]
+fn test_interp_records_and_arrays() {
+    let input = "let type point = {x: int, y: int} \
+                   type points = array of point \
+                   var p := point {x = 1, y = 2} \
+                   var q := point {x = 1, y = 2} \
+                   var ps := points [3] of p \
+                 in ps[1].x := 7; \
+                    if p.x = 7 then print(\"shared \"); \
+                    if p <> q then print(\"distinct \"); \
+                    if ps[2] = p then print(\"same \"); \
+                    ps[0] := nil; if ps[0] = nil then print(\"nil\") \
+                 end";
+    assert_eq!(interp_stdout(input), "shared distinct same nil");
+}
+
+#[test]
+fn test_interp_loops() {
+    let input = "let var n := 0 \
+                 in while 1 do (n := n + 1; if n = 10 then break); \
+                    for i := 1 to 100 do (if i > 5 then break; n := n + i); \
+                    for i := 3 to 1 do n := 0; \
+                    print(chr(n + 40)) \
+                 end";
+    assert_eq!(interp_stdout(input), "A");
+}
+
+#[test]
+fn test_interp_assign_checks_after_value() {
+    // The record or array of an assignment is checked only once the value
+    // is computed, as compiled code does, so the value's output shows.
+    let decs = "let type r = {f: int} type a = array of int \
+                var x: r := nil var y := a [1] of 0 in ";
+    let cases = [
+        (
+            "x.f := (print(\"v\"); 1) end",
+            "1.84: nil record dereferenced",
+        ),
+        (
+            "y[1] := (print(\"v\"); 1) end",
+            "1.86: index 1 out of range for array of size 1",
+        ),
+    ];
+    for (assign, err) in cases {
+        let input = format!("{}{}", decs, assign);
+        let program = trans_prog(&parse(&input).unwrap()).unwrap();
+        let mut output = Vec::new();
+        let status = interp::run(&program, &mut "".as_bytes(), &mut output);
+        assert_eq!(output, b"v", "{}", input);
+        assert_eq!(status.unwrap_err().lines().next(), Some(err), "{}", input);
+    }
+}
+
+#[test]
+fn test_interp_errors() {
+    let cases = [
+        (
+            "let type r = {f: int} var x: r := nil in x.f end",
+            "1.42: nil record dereferenced",
+        ),
+        (
+            "let type a = array of int var x := a [2] of 0 in x[2] end",
+            "1.52: index 2 out of range for array of size 2",
+        ),
+        ("1 / (2 - 2)", "1.1: division by zero"),
+        ("chr(256)", "1.1: chr(256) out of range"),
+        (
+            "substring(\"abc\", 2, 2)",
+            "1.1: substring(\"abc\", 2, 2) out of range",
+        ),
+        (
+            "let type a = array of int in a [-1] of 0 end",
+            "1.30: array size -1 is negative",
+        ),
+    ];
+    for (input, err) in cases {
+        let err = format!("{}\n  in tigermain", err);
+        assert_eq!(interp(input, ""), Err(err), "{}", input);
+    }
+}
+
+#[test]
+fn test_interp_matches_codegen() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+        let input = fs::read_to_string(&input_path).unwrap();
+        if NON_TERMINATING.contains(&file_name)
+            || !matches!(parse(&input).map(|exp| trans_prog(&exp)), Ok(Ok(_)))
+        {
+            continue;
+        }
+        let stdin = "5 3 1 ;\n4 2 ;\n";
+        let name = format!("interp-{}", file_name.trim_end_matches(".tig"));
+        let output = run(&name, &input, stdin);
+        let expected = (
+            output.status.code().unwrap() as i64,
+            String::from_utf8(output.stdout).unwrap(),
+        );
+        assert_eq!(interp(&input, stdin), Ok(expected), "{}", file_name);
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs::{self, metadata};
+
+use super::util::{all_path, NON_TERMINATING};
+
+use crate::frame::frame::Frame;
+use crate::frame::x86_64::X86_64Frame;
+use crate::interp::{interp, ir};
+use crate::parser::parser::compile as parse;
+use crate::semant::semant::trans_prog;
+use crate::temp::temp::{Label, Temp};
+use crate::translate::translate::{translate, Frag};
+use crate::tree::tree::{Exp, Stm};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+/// Runs the IR of `input` on `stdin`, returning its exit status and output.
+fn run(input: &str, canon: bool, stdin: &str) -> Result<(i64, String), String> {
+    let program = trans_prog(&parse(input).unwrap()).unwrap();
+    let frags = translate::<X86_64Frame>(&program);
+    run_frags(frags, canon, stdin)
+}
+
+fn run_frags(
+    frags: Vec<Frag<X86_64Frame>>,
+    canon: bool,
+    stdin: &str,
+) -> Result<(i64, String), String> {
+    let mut output = Vec::new();
+    let status = ir::run(frags, canon, &mut stdin.as_bytes(), &mut output)?;
+    Ok((status, String::from_utf8(output).unwrap()))
+}
+
+#[test]
+fn test_ir_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    for canon in [false, true] {
+        let (status, output) = run(&input, canon, "").unwrap();
+        assert_eq!(status, 0);
+        assert_eq!(output.lines().count(), 92 * 9);
+        assert!(output.starts_with(" O . . . . . . .\n . . . . O . . .\n"));
+    }
+}
+
+#[test]
+fn test_ir_merge() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    for canon in [false, true] {
+        assert_eq!(
+            run(&input, canon, "1 4 9 ;\n2 3 10 11 ;\n"),
+            Ok((0, String::from("1 2 3 4 9 10 11 \n")))
+        );
+    }
+}
+
+#[test]
+fn test_ir_matches_interp() {
+    let mut inputs = vec![
+        // A break out of the middle of an expression.
+        String::from(
+            "let var n := 0 \
+             in while 1 do n := n + (if n > 3 then break; 2); \
+                print(chr(ord(\"0\") + n)) \
+             end",
+        ),
+        String::from(
+            "let type a = array of string var x := a [3] of \"ab\" \
+             in x[1] := concat(x[0], substring(\"xyz\", 1, 2)); \
+                if x[1] > x[2] & size(x[1]) = 4 then print(x[1]); exit(3) \
+             end",
+        ),
+    ];
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+        if metadata(&input_path).unwrap().is_dir() || NON_TERMINATING.contains(&file_name) {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path).unwrap();
+        if matches!(parse(&input).map(|exp| trans_prog(&exp)), Ok(Ok(_))) {
+            inputs.push(input);
+        }
+    }
+    let stdin = "5 3 1 ;\n4 2 ;\n";
+    for input in inputs {
+        let program = trans_prog(&parse(&input).unwrap()).unwrap();
+        let mut output = Vec::new();
+        let status = interp::run(&program, &mut stdin.as_bytes(), &mut output).unwrap();
+        let expected = Ok((status, String::from_utf8(output).unwrap()));
+        assert_eq!(run(&input, false, stdin), expected, "{}", input);
+        assert_eq!(run(&input, true, stdin), expected, "{}", input);
+    }
+}
+
+#[test]
+fn test_ir_errors() {
+    let nil = "let type r = {f: int} var x: r := nil in x.f end";
+    assert_eq!(
+        run(nil, true, ""),
+        Err(String::from(
+            "1.42: nil record dereferenced\n  in tigermain"
+        ))
+    );
+    assert_eq!(
+        run("1 / (2 - 2)", false, ""),
+        Err(String::from("1.1: division by zero\n  in tigermain"))
+    );
+
+    // Translation bugs show up as reads of unset temps and stray jumps.
+    let main = |body: Stm| Frag::Proc {
+        body,
+        frame: X86_64Frame::new(Label::named("tigermain"), &[true]),
+    };
+    let unset = Temp::new();
+    let read = Stm::Move(
+        Box::new(Exp::Temp(X86_64Frame::rv())),
+        Box::new(Exp::Temp(unset)),
+    );
+    assert_eq!(
+        run_frags(vec![main(read)], false, ""),
+        Err(format!(
+            "temp {} read before it is set\n  in tigermain",
+            unset
+        ))
+    );
+    let jump = Stm::Jump(
+        Box::new(Exp::Name(Label::named("nowhere"))),
+        vec![Label::named("nowhere")],
+    );
+    assert_eq!(
+        run_frags(vec![main(jump)], false, ""),
+        Err(String::from(
+            "jump to unknown label nowhere\n  in tigermain"
+        ))
+    );
+}
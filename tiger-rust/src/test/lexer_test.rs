@@ -1,7 +1,8 @@
 use std::fs::{self, metadata};
 
 use super::util::all_path;
-use crate::lexer::lexer::tokenize;
+use crate::lexer::lexer::{tokenize, unescape, Token};
+use crate::parser::parser::compile;
 
 const TEST_DATA_PATH: &str = "./src/test/testcases";
 
@@ -21,3 +22,30 @@ fn test_lexer() {
         }
     }
 }
+
+#[test]
+fn test_lexer_continued_string() {
+    // `\f___f\` drops whitespace, line breaks included, and may appear more
+    // than once in a literal.
+    let input = "\"ab\\ \n\t\x0c\r\n  \\cd\\\n\\\\\\\"";
+    let strings: Vec<_> = tokenize(input)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::String(s) => Some(s.v),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(strings, ["\"abcd\\\\\""]);
+    assert_eq!(unescape(&strings[0]), "abcd\\");
+    assert_eq!(unescape("\"a\\ \n \\b\\n\""), "ab\n");
+}
+
+#[test]
+fn test_lexer_int_out_of_range() {
+    let input = "print(chr(99999999999999999999))";
+    let tokens = tokenize(input);
+    assert!(!tokens.iter().any(|token| matches!(token, Token::Int(_))));
+    assert!(compile(input).is_err());
+    let max = format!("{}", u64::MAX);
+    assert!(matches!(&tokenize(&max)[..], [Token::Int(i)] if i.v == u64::MAX));
+}
@@ -0,0 +1,212 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::bytecode::{compile::compile, vm};
+use crate::codegen::codegen::compile_with;
+use crate::frame::mips::MipsFrame;
+use crate::frame::x86_64::X86_64Frame;
+use crate::interp::limits::{Limits, DEFAULT_DEPTH};
+use crate::interp::{interp, ir};
+use crate::parser::parser::compile as parse;
+use crate::regalloc::regalloc::Allocator;
+use crate::semant::semant::trans_prog;
+use crate::sim::mips;
+use crate::translate::translate::translate;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+const SPIN: &str = "while 1 do print(\"x\")";
+
+type Engine<'a> = &'a dyn Fn(&mut Vec<u8>) -> Result<i64, String>;
+
+/// Runs `input` under `limits` on the interpreter, the IR before and after
+/// canon, the VM and the MIPS simulator, returning the status or error and the output of each.
+fn run_all(input: &str, limits: &Limits) -> Vec<(Result<i64, String>, String)> {
+    let program = trans_prog(&parse(input).unwrap()).unwrap();
+    let asm = compile_with(translate::<MipsFrame>(&program), Allocator::default());
+    let engines: [Engine; 5] = [
+        &|output| interp::run_with(&program, limits.clone(), &mut "".as_bytes(), output),
+        &|output| {
+            let frags = translate::<X86_64Frame>(&program);
+            ir::run_with(frags, false, limits.clone(), &mut "".as_bytes(), output)
+        },
+        &|output| {
+            let frags = translate::<X86_64Frame>(&program);
+            ir::run_with(frags, true, limits.clone(), &mut "".as_bytes(), output)
+        },
+        &|output| {
+            vm::run_with(
+                &compile(&program),
+                limits.clone(),
+                &mut "".as_bytes(),
+                output,
+            )
+        },
+        &|output| mips::run_with(&asm, limits.clone(), &mut "".as_bytes(), output),
+    ];
+    engines
+        .iter()
+        .map(|engine| {
+            let mut output = Vec::new();
+            let status = engine(&mut output);
+            (status, String::from_utf8(output).unwrap())
+        })
+        .collect()
+}
+
+/// Checks that every engine fails with `msg` at a source position, and
+/// unwinds out of the main program.
+fn assert_fails(input: &str, limits: &Limits, msg: &str) {
+    for (status, _) in run_all(input, limits) {
+        let err = status.unwrap_err();
+        let first = err.lines().next().unwrap();
+        assert!(first.ends_with(&format!(": {}", msg)), "{}", err);
+        assert!(err.ends_with("\n  in tigermain"), "{}", err);
+    }
+}
+
+#[test]
+fn test_limits_steps() {
+    let limits = Limits {
+        steps: Some(1000),
+        ..Limits::default()
+    };
+    assert_fails(SPIN, &limits, "step limit of 1000 exceeded");
+}
+
+#[test]
+fn test_limits_depth() {
+    let limits = Limits {
+        depth: Some(50),
+        ..Limits::default()
+    };
+    let input = |n: i64| {
+        format!(
+            "let function f(n: int): int = if n = 0 then 0 else 1 + f(n - 1) in f({}) end",
+            n
+        )
+    };
+    // The main program and 49 calls of f.
+    for (status, _) in run_all(&input(48), &limits) {
+        assert_eq!(status, Ok(0));
+    }
+    assert_fails(&input(49), &limits, "recursion depth limit of 50 exceeded");
+    assert_fails(
+        "let function f(n: int): int = f(n + 1) in f(0) end",
+        &limits,
+        "recursion depth limit of 50 exceeded",
+    );
+}
+
+#[test]
+fn test_limits_default_depth() {
+    let input = "let function f(n: int): int = if n = 0 then 0 else 1 + f(n - 1) \
+                 in print(if f(20000) = 20000 then \"ok\" else \"bad\") end";
+    for (status, output) in run_all(input, &Limits::default()) {
+        assert_eq!((status, output.as_str()), (Ok(0), "ok"));
+    }
+    // Recursion with no end stops at the limit rather than overflowing the
+    // host stack.
+    assert_fails(
+        "let function f(n: int): int = f(n + 1) in f(0) end",
+        &Limits::default(),
+        &format!("recursion depth limit of {} exceeded", DEFAULT_DEPTH),
+    );
+}
+
+/// Kilobytes of address space [`test_limits_address_space`] runs in.
+const ADDRESS_SPACE: usize = 400_000;
+
+#[test]
+fn test_limits_address_space() {
+    // A stack reserved up front for the default depth would not fit, so
+    // the engines have to grow theirs as they go. The test runs itself
+    // again under `ulimit -v` for this part.
+    if env::var_os("TIGER_ADDRESS_SPACE").is_some() {
+        let input = "let function f(n: int): int = if n = 0 then 0 else 1 + f(n - 1) \
+                     in print(\"hi\"); f(1000) end";
+        for (status, output) in run_all(input, &Limits::default()) {
+            assert_eq!((status, output.as_str()), (Ok(0), "hi"));
+        }
+        return;
+    }
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(format!(
+            "ulimit -v {} && exec \"$0\" --exact test::limits_test::test_limits_address_space",
+            ADDRESS_SPACE
+        ))
+        .arg(env::current_exe().unwrap())
+        .env("TIGER_ADDRESS_SPACE", "1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("1 passed"), "{}", stdout);
+}
+
+#[test]
+fn test_limits_heap() {
+    let limits = Limits {
+        heap: Some(100_000),
+        ..Limits::default()
+    };
+    assert_fails(
+        "let type a = array of int var x := a [100000] of 0 in x[99] end",
+        &limits,
+        "heap limit of 100000 bytes exceeded",
+    );
+
+    // Garbage does not count against the VM's heap once collected.
+    let input = "let type list = {hd: int, tl: list} \
+                   function build(n: int): list = \
+                     let var l: list := nil in for i := 1 to n do l := list {hd = i, tl = l}; l end \
+                 in for i := 1 to 100 do (build(1000); ()) end";
+    let module = compile(&trans_prog(&parse(input).unwrap()).unwrap());
+    let status = vm::run_with(&module, limits, &mut "".as_bytes(), &mut Vec::new());
+    assert_eq!(status, Ok(0));
+}
+
+#[test]
+fn test_limits_output() {
+    let limits = Limits {
+        output: Some(5),
+        ..Limits::default()
+    };
+    for (status, output) in run_all(SPIN, &limits) {
+        let err = status.unwrap_err();
+        assert!(err
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with(": output limit of 5 bytes exceeded"));
+        assert_eq!(output, "xxxxx");
+    }
+}
+
+#[test]
+fn test_limits_time() {
+    let limits = Limits {
+        time: Some(Duration::from_millis(50)),
+        ..Limits::default()
+    };
+    assert_fails("while 1 do ()", &limits, "time limit of 50ms exceeded");
+}
+
+#[test]
+fn test_limits_within() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let limits = Limits {
+        steps: Some(100_000_000),
+        depth: Some(100),
+        heap: Some(1 << 20),
+        output: Some(1 << 20),
+        time: Some(Duration::from_secs(60)),
+    };
+    for (status, output) in run_all(&input, &limits) {
+        assert_eq!(status, Ok(0));
+        assert_eq!(output.lines().count(), 92 * 9);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::fs::{self, metadata};
+
+use super::util::all_path;
+
+use crate::assem::assem::Instr;
+use crate::codegen::codegen::{instructions, Codegen};
+use crate::flowgraph::flowgraph::FlowGraph;
+use crate::frame::x86_64::X86_64Frame;
+use crate::liveness::liveness::{dump, InterferenceGraph, Liveness};
+use crate::parser::parser::compile;
+use crate::semant::semant::trans_prog;
+use crate::temp::temp::{Label, Temp};
+use crate::translate::translate::{translate, Frag};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+fn set(temps: &[Temp]) -> BTreeSet<Temp> {
+    temps.iter().copied().collect()
+}
+
+/// The loop of Appel's figure 10.1: a := 0; L1: b := a + 1; c := c + b;
+/// a := b * 2; if a < N goto L1; return c.
+fn loop_program() -> (Vec<Instr>, [Temp; 3]) {
+    let (a, b, c) = (Temp::new(), Temp::new(), Temp::new());
+    let (l1, l2) = (Label::new(), Label::new());
+    let instrs = vec![
+        Instr::oper("\tmovq $0, `d0", vec![a], vec![]),
+        Instr::Label {
+            assem: format!("{}:", l1),
+            label: l1.clone(),
+        },
+        Instr::oper("\tleaq 1(`s0), `d0", vec![b], vec![a]),
+        Instr::oper("\taddq `s1, `d0", vec![c], vec![c, b]),
+        Instr::oper("\tleaq (`s0,`s0), `d0", vec![a], vec![b]),
+        Instr::Oper {
+            assem: String::from("\tcmpq $10, `s0\n\tjl `j0"),
+            dst: vec![],
+            src: vec![a],
+            jump: Some(vec![l1, l2.clone()]),
+        },
+        Instr::Label {
+            assem: format!("{}:", l2),
+            label: l2,
+        },
+        Instr::Oper {
+            assem: String::new(),
+            dst: vec![],
+            src: vec![c],
+            jump: Some(vec![]),
+        },
+    ];
+    (instrs, [a, b, c])
+}
+
+#[test]
+fn test_flowgraph() {
+    let (instrs, [a, b, c]) = loop_program();
+    let flow = FlowGraph::new(&instrs);
+    assert_eq!(flow.len(), 8);
+    assert_eq!(flow.nodes[5].succ, vec![1, 6]);
+    assert_eq!(flow.nodes[1].pred, vec![0, 5]);
+    assert!(flow.nodes[7].succ.is_empty());
+    assert_eq!(flow.nodes[3].def, vec![c]);
+    assert_eq!(flow.nodes[3].uses, vec![c, b]);
+    assert_eq!(flow.nodes[2].uses, vec![a]);
+    assert!(flow.nodes.iter().all(|node| !node.is_move));
+}
+
+#[test]
+fn test_liveness_loop() {
+    let (instrs, [a, b, c]) = loop_program();
+    let flow = FlowGraph::new(&instrs);
+    let liveness = Liveness::new(&flow);
+    assert_eq!(set(&liveness.live_in(0)), set(&[c]));
+    assert_eq!(set(&liveness.live_out(0)), set(&[a, c]));
+    assert_eq!(set(&liveness.live_out(2)), set(&[b, c]));
+    assert_eq!(set(&liveness.live_out(3)), set(&[b, c]));
+    assert_eq!(set(&liveness.live_out(4)), set(&[a, c]));
+    assert_eq!(set(&liveness.live_out(5)), set(&[a, c]));
+    assert_eq!(set(&liveness.live_out(6)), set(&[c]));
+    assert!(liveness.live_out(7).is_empty());
+    let graph = InterferenceGraph::new(&flow, &liveness);
+    assert!(graph.interferes(a, c) && graph.interferes(c, a));
+    assert!(graph.interferes(b, c));
+    assert!(!graph.interferes(a, b));
+    assert_eq!(graph.edge_count(), 2);
+}
+
+#[test]
+fn test_interference_moves() {
+    let (s, t, u) = (Temp::new(), Temp::new(), Temp::new());
+    let instrs = vec![
+        Instr::oper("\tmovq $1, `d0", vec![s], vec![]),
+        Instr::oper("\tmovq $2, `d0", vec![u], vec![]),
+        Instr::Move {
+            assem: String::from("\tmovq `s0, `d0"),
+            dst: t,
+            src: s,
+        },
+        Instr::oper("", vec![], vec![s, t, u]),
+    ];
+    let flow = FlowGraph::new(&instrs);
+    assert!(flow.nodes[2].is_move);
+    let graph = InterferenceGraph::new(&flow, &Liveness::new(&flow));
+    assert!(!graph.interferes(s, t));
+    assert!(graph.interferes(t, u));
+    assert!(graph.interferes(s, u));
+    assert_eq!(graph.moves(), &[(t, s)]);
+}
+
+#[test]
+fn test_liveness_all() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        let program = match compile(&input).map(|exp| trans_prog(&exp)) {
+            Ok(Ok(program)) => program,
+            _ => continue,
+        };
+        println!("{}", input_path);
+        for frag in translate::<X86_64Frame>(&program) {
+            let Frag::Proc { body, mut frame } = frag else {
+                continue;
+            };
+            let (instrs, _) = instructions(&mut frame, body);
+            let flow = FlowGraph::new(&instrs);
+            let liveness = Liveness::new(&flow);
+            for (index, node) in flow.nodes.iter().enumerate() {
+                for succ in &node.succ {
+                    assert!(flow.nodes[*succ].pred.contains(&index));
+                }
+                // The dataflow equations hold at the fixed point.
+                let out: BTreeSet<Temp> = node
+                    .succ
+                    .iter()
+                    .flat_map(|succ| liveness.live_in(*succ))
+                    .collect();
+                assert_eq!(set(&liveness.live_out(index)), out);
+                let mut live_in: BTreeSet<Temp> =
+                    out.difference(&set(&node.def)).copied().collect();
+                live_in.extend(&node.uses);
+                assert_eq!(set(&liveness.live_in(index)), live_in);
+            }
+            // Nothing but the sink's registers is live at the end, and no
+            // temp is live on entry.
+            assert!(liveness.live_in(0).iter().all(|temp| temp.is_precolored()));
+            let graph = InterferenceGraph::new(&flow, &liveness);
+            for temp in graph.temps() {
+                for other in graph.adjacent(*temp) {
+                    assert_ne!(*temp, other);
+                    assert!(graph.interferes(other, *temp));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_liveness_dump() {
+    let (instrs, [a, _, c]) = loop_program();
+    let text = dump(&instrs, X86_64Frame::register_name);
+    assert!(text.starts_with("instructions:\n"));
+    assert!(text.contains(&format!("out: %{} %{}\n", a, c)));
+    assert!(text.contains(&format!("interference:\n  %{}: %{}\n", a, c)));
+    assert!(text.ends_with("moves:\n"));
+}
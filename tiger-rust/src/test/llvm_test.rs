@@ -0,0 +1,167 @@
+use std::fs;
+use std::process::{Command, Output};
+
+use super::util::{run_exe, runtime, scratch_path, stdout, testcases, NON_TERMINATING, TORTURE};
+
+use crate::codegen::link::{link_llvm, needs_opaque_pointers_flag, parse_llvm_version};
+use crate::interp::interp;
+use crate::llvm::compile::compile;
+use crate::parser::parser::compile as parse;
+use crate::semant::semant::trans_prog;
+use crate::temp::temp;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+const LL_PATH: &str = "./src/test/testcases/llvm/";
+
+fn compile_llvm(input: &str) -> String {
+    compile(&trans_prog(&parse(input).unwrap()).unwrap())
+}
+
+/// Whether `clang` is installed to build the modules; the tests that run
+/// them pass trivially without it.
+pub fn has_clang() -> bool {
+    Command::new("clang")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// The arguments `llvm-as` needs to read the modules, if it is installed.
+fn llvm_as_args() -> Option<Vec<&'static str>> {
+    Some(match needs_opaque_pointers_flag("llvm-as")? {
+        true => vec!["-opaque-pointers"],
+        false => vec![],
+    })
+}
+
+/// Compiles `input` through LLVM and runs it on `stdin` with the
+/// environment variables `vars`.
+pub fn run_env(name: &str, input: &str, stdin: &str, vars: &[(&str, &str)]) -> Output {
+    let exe_path = scratch_path(name);
+    link_llvm(&compile_llvm(input), &exe_path, runtime())
+        .unwrap_or_else(|err| panic!("{}: {}", name, err));
+    run_exe(&exe_path, stdin, vars)
+}
+
+pub fn run(name: &str, input: &str, stdin: &str) -> Output {
+    run_env(name, input, stdin, &[])
+}
+
+#[test]
+fn test_llvm_reference() {
+    for (file_name, input) in testcases() {
+        let reference_path = format!("{}{}", LL_PATH, file_name.replace(".tig", ".ll"));
+        let expected = fs::read_to_string(&reference_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", reference_path));
+        println!("{}", file_name);
+        temp::reset();
+        assert_eq!(compile_llvm(&input), expected);
+    }
+}
+
+#[test]
+fn test_llvm_version() {
+    let versions = [
+        (
+            "Ubuntu clang version 14.0.0-1ubuntu1.1\nTarget: x86_64\n",
+            Some(14),
+        ),
+        (
+            "LLVM (http://llvm.org/):\n  LLVM version 17.0.6\n",
+            Some(17),
+        ),
+        ("clang version 18.1.8 (Fedora 18.1.8-1.fc40)\n", Some(18)),
+        ("cc (GCC) 13.2.0\n", None),
+    ];
+    for (text, major) in versions {
+        assert_eq!(parse_llvm_version(text), major, "{}", text);
+    }
+}
+
+#[test]
+fn test_llvm_assembles() {
+    let Some(args) = llvm_as_args() else {
+        return;
+    };
+    for (file_name, _) in testcases() {
+        let reference_path = format!("{}{}", LL_PATH, file_name.replace(".tig", ".ll"));
+        let output = Command::new("llvm-as")
+            .args(&args)
+            .arg(&reference_path)
+            .args(["-o", "/dev/null"])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}: {}",
+            reference_path,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
+
+#[test]
+fn test_llvm_frames() {
+    // Only `n`, which `g` reaches through its static link, is in the frame
+    // of `f`. The list `l` is a root, and `i` has an `alloca` of its own.
+    let input = "let type list = {hd: int, tl: list} \
+                   function f(n: int, l: list): int = \
+                     let var i := 0 function g(): int = n in i := g(); i + l.hd end \
+                 in f(1, list {hd = 2, tl = nil}) end";
+    temp::reset();
+    let ll = compile_llvm(input);
+    assert!(ll.contains("%f_L0.frame = type { %tig_frame, ptr, i64 }\n"));
+    assert!(ll.contains("%f_L0.roots = type { %tig_frame, i64, i64 }\n"));
+    assert!(ll.contains("  %i_2.addr = alloca i64\n"));
+    assert!(ll.contains("%g_L1.frame = type { %tig_frame, ptr }\n"));
+    assert!(!ll.contains("%g_L1.roots"));
+}
+
+#[test]
+fn test_llvm_matches_interp() {
+    if !has_clang() {
+        return;
+    }
+    for (file_name, input) in testcases() {
+        if NON_TERMINATING.contains(&file_name.as_str()) {
+            continue;
+        }
+        let program = trans_prog(&parse(&input).unwrap()).unwrap();
+        let mut expected = Vec::new();
+        let status = interp::run(&program, &mut "".as_bytes(), &mut expected);
+        let output = run(&file_name, &input, "");
+        match status {
+            Ok(status) => {
+                assert_eq!(output.status.code(), Some(status as i32), "{}", file_name);
+                assert_eq!(output.stdout, expected, "{}", file_name);
+            }
+            Err(err) => {
+                assert_eq!(output.status.code(), Some(1), "{}", file_name);
+                let stderr = String::from_utf8(output.stderr).unwrap();
+                assert_eq!(stderr, format!("{}\n", err), "{}", file_name);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_llvm_merge() {
+    if !has_clang() {
+        return;
+    }
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    let output = stdout(run("merge", &input, "1 4 9 ;\n2 3 10 11 ;\n"));
+    assert_eq!(output, "1 2 3 4 9 10 11 \n");
+}
+
+#[test]
+fn test_llvm_every_allocation() {
+    if !has_clang() {
+        return;
+    }
+    let queens = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    assert_eq!(
+        stdout(run_env("queens-torture", &queens, "", &TORTURE)),
+        stdout(run("queens-plain", &queens, ""))
+    );
+}
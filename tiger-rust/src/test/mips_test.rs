@@ -0,0 +1,257 @@
+use std::fs::{self, metadata};
+
+use super::util::{all_path, NON_TERMINATING, WORD_SIZE_DEPENDENT};
+
+use crate::codegen::codegen::{compile_with, instructions};
+use crate::codegen::mips::RUNTIME;
+use crate::frame::frame::Frame;
+use crate::frame::mips::MipsFrame;
+use crate::interp::interp;
+use crate::parser::parser::compile as parse;
+use crate::regalloc::regalloc::{allocate, check, Allocator};
+use crate::semant::semant::trans_prog;
+use crate::sim::mips;
+use crate::translate::translate::{translate, Frag};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+fn compile_mips(input: &str, allocator: Allocator) -> String {
+    let program = trans_prog(&parse(input).unwrap()).unwrap();
+    compile_with(translate::<MipsFrame>(&program), allocator)
+}
+
+/// Runs the assembly `asm` on `stdin`, returning its exit status and output.
+fn sim(asm: &str, stdin: &str) -> Result<(i64, String), String> {
+    let mut output = Vec::new();
+    let status = mips::run(asm, &mut stdin.as_bytes(), &mut output)?;
+    Ok((status, String::from_utf8(output).unwrap()))
+}
+
+fn sim_stdout(input: &str) -> String {
+    let (status, output) = sim(&compile_mips(input, Allocator::default()), "").unwrap();
+    assert_eq!(status, 0);
+    output
+}
+
+#[test]
+fn test_mips_asm() {
+    let asm = compile_mips(
+        "let function f(x: int): int = x + 1 in print(\"hi\\n\"); f(2) end",
+        Allocator::default(),
+    );
+    assert!(asm.starts_with("\t.text\n\t.globl main\nmain:\n"));
+    assert!(asm.contains("\tjal tig_print\n"));
+    assert!(asm.contains("\t.data\n"));
+    assert!(asm.contains("\t.ascii \"hi\\n\"\n"));
+}
+
+#[test]
+fn test_mips_programs() {
+    let input = "let type list = {hd: int, tl: list} \
+                   type arr = array of int \
+                   var l := list {hd = 1, tl = list {hd = 2, tl = nil}} \
+                   var a := arr [5] of 7 \
+                   var sum := 0 \
+                   function many(a: int, b: int, c: int, d: int, e: int, f: int): int = \
+                     let function inner(x: int): int = x + f \
+                     in inner(a + b * 2 + c * 3 + d * 4 + e * 5) end \
+                 in a[2] := 10; \
+                    for i := 0 to 4 do sum := sum + a[i]; \
+                    print(chr(ord(\"0\") + l.tl.hd)); \
+                    print(chr(ord(\"0\") + sum - 30)); \
+                    print(chr(many(1, 2, 3, 4, 5, 6) - 55 + ord(\"a\"))); \
+                    if \"abc\" < \"abd\" then print(\" lt\"); \
+                    print(concat(\" foo\", substring(\"xbarx\", 1, 3))); \
+                    print(chr(-7 / 2 + ord(\"5\"))) \
+                 end";
+    assert_eq!(sim_stdout(input), "28g lt foobar2");
+}
+
+#[test]
+fn test_mips_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let output = sim_stdout(&input);
+    assert_eq!(output.lines().count(), 92 * 9);
+    assert!(output.starts_with(" O . . . . . . .\n . . . . O . . .\n"));
+}
+
+#[test]
+fn test_mips_merge() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    let asm = compile_mips(&input, Allocator::LinearScan);
+    assert_eq!(
+        sim(&asm, "1 4 9 ;\n2 3 10 11 ;\n"),
+        Ok((0, String::from("1 2 3 4 9 10 11 \n")))
+    );
+}
+
+#[test]
+fn test_mips_matches_interp() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path).unwrap();
+        let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+        let program = match parse(&input).map(|exp| trans_prog(&exp)) {
+            Ok(Ok(program)) => program,
+            _ => continue,
+        };
+        for allocator in [Allocator::Coloring, Allocator::LinearScan] {
+            let asm = compile_with(translate::<MipsFrame>(&program), allocator);
+            if NON_TERMINATING.contains(&file_name) || WORD_SIZE_DEPENDENT.contains(&file_name) {
+                assert!(mips::assemble(&asm).is_ok(), "{}", file_name);
+                continue;
+            }
+            let mut expected = Vec::new();
+            let status = interp::run(&program, &mut "".as_bytes(), &mut expected);
+            let expected = status.map(|status| (status, String::from_utf8(expected).unwrap()));
+            assert_eq!(sim(&asm, ""), expected, "{}", file_name);
+        }
+    }
+}
+
+/// Checks that `input` on `stdin` runs with the runtime functions of
+/// [`RUNTIME`] as it does in the interpreter, errors going to the output.
+fn assert_runtime_matches(name: &str, input: &str, stdin: &str) {
+    let program = trans_prog(&parse(input).unwrap()).unwrap();
+    let asm = compile_with(translate::<MipsFrame>(&program), Allocator::default()) + RUNTIME;
+    let mut expected = Vec::new();
+    let status = interp::run(&program, &mut stdin.as_bytes(), &mut expected);
+    let mut expected = String::from_utf8(expected).unwrap();
+    let status = match status {
+        Ok(status) => status,
+        Err(err) => {
+            expected.push_str(err.lines().next().unwrap());
+            expected.push('\n');
+            1
+        }
+    };
+    assert_eq!(sim(&asm, stdin), Ok((status, expected)), "{}", name);
+}
+
+#[test]
+fn test_mips_runtime() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    assert_runtime_matches("merge", &input, "1 4 9 ;\n2 3 10 11 ;\n");
+    let strings = "let var a := \"abc\" var s := getchar() \
+                   in print(if a < \"abd\" then \"lt \" else \"ge \"); \
+                      print(if \"b\" > a then \"gt \" else \"le \"); \
+                      print(if \"ab\" < a then \"prefix \" else \"ge \"); \
+                      print(if a = \"abc\" then \"eq \" else \"ne \"); \
+                      print(concat(s, substring(\"xbarx\", 1, 3))); \
+                      print(chr(ord(\"A\") + size(a) + not(0))); \
+                      print(getchar()); print(getchar()); \
+                      if ord(getchar()) = -1 then print(\" eof\"); \
+                      exit(3) end";
+    assert_runtime_matches("strings", strings, "Zq");
+    let errors = [
+        "print(chr(256))",
+        "print(substring(\"abc\", 2, 2))",
+        "let type a = array of int in a [-1] of 0 end",
+        "let type a = array of int var x := a [3] of 0 in x[3] end",
+        "let type r = {f: int} var x: r := nil in x.f end",
+        "let var zero := 0 in 1 / zero end",
+    ];
+    for input in errors {
+        assert_runtime_matches(input, &format!("(print(\"x\"); {})", input), "");
+    }
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path).unwrap();
+        let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+        if NON_TERMINATING.contains(&file_name) || WORD_SIZE_DEPENDENT.contains(&file_name) {
+            continue;
+        }
+        match parse(&input).map(|exp| trans_prog(&exp)) {
+            Ok(Ok(_)) => (),
+            _ => continue,
+        };
+        assert_runtime_matches(file_name, &input, "");
+    }
+}
+
+#[test]
+fn test_mips_word_size() {
+    let input = fs::read_to_string(format!("{}wordsize.tig", TEST_DATA_PATH)).unwrap();
+    assert_eq!(sim_stdout(&input), "32\n");
+    let program = trans_prog(&parse(&input).unwrap()).unwrap();
+    let mut output = Vec::new();
+    assert_eq!(
+        interp::run(&program, &mut "".as_bytes(), &mut output),
+        Ok(0)
+    );
+    assert_eq!(output, b"64\n");
+}
+
+#[test]
+fn test_mips_regalloc() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let program = trans_prog(&parse(&input).unwrap()).unwrap();
+    for allocator in [Allocator::Coloring, Allocator::LinearScan] {
+        for frag in translate::<MipsFrame>(&program) {
+            let Frag::Proc { body, mut frame } = frag else {
+                continue;
+            };
+            let (instrs, _) = instructions(&mut frame, body);
+            let allocated = allocate(&mut frame, instrs, allocator);
+            check::<MipsFrame>(&allocated.instrs, &allocated.allocation)
+                .unwrap_or_else(|err| panic!("{}: {}", frame.name(), err));
+        }
+    }
+}
+
+#[test]
+fn test_mips_syscalls() {
+    let asm = "\t.data
+prompt:\t.asciiz \"n? \"   # a comment
+\t.text
+\t.globl main
+main:
+\tla $a0, prompt
+\tli $v0, 4
+\tsyscall
+\tli $v0, 5
+\tsyscall
+\tmove $t0, $v0
+\tli $t1, 0
+loop:\tblez $t0, done
+\taddu $t1, $t1, $t0
+\taddiu $t0, $t0, -1
+\tb loop
+done:\tmove $a0, $t1
+\tli $v0, 1
+\tsyscall
+\tli $a0, 10
+\tli $v0, 11
+\tsyscall
+\tli $a0, 7
+\tli $v0, 17
+\tsyscall
+";
+    assert_eq!(sim(asm, "10\n"), Ok((7, String::from("n? 55\n"))));
+}
+
+#[test]
+fn test_mips_assembly_errors() {
+    let cases = [
+        ("main:\n\tfrob $t0\n", "line 2: unknown instruction frob"),
+        ("main:\n\tj nowhere\n", "line 2: undefined label nowhere"),
+        ("main:\n\tmove $q1, $t0\n", "line 2: unknown register $q1"),
+        (
+            "main:\n\taddu $t0, $t1\n",
+            "line 2: addu takes 3 operands, not 2",
+        ),
+        ("main:\nmain:\n", "line 2: label main defined twice"),
+        ("\tnop\n", "no main label"),
+    ];
+    for (asm, err) in cases {
+        assert_eq!(sim(asm, "").map(|_| ()), Err(String::from(err)), "{}", asm);
+    }
+    assert_eq!(
+        sim("main:\n\tlw $t0, 0($zero)\n", ""),
+        Err(String::from("bad address 0x0"))
+    );
+}
@@ -1,5 +1,57 @@
 #[cfg(test)]
+pub mod arena_test;
+#[cfg(test)]
+pub mod backtrace_test;
+#[cfg(test)]
+pub mod bytecode_test;
+#[cfg(test)]
+pub mod c_test;
+#[cfg(test)]
+pub mod canon_test;
+#[cfg(test)]
+pub mod codegen_test;
+#[cfg(test)]
+pub mod dot_test;
+#[cfg(test)]
+pub mod escape_test;
+#[cfg(test)]
+pub mod gc_test;
+#[cfg(test)]
+pub mod interp_test;
+#[cfg(test)]
+pub mod ir_test;
+#[cfg(test)]
 pub mod lexer_test;
 #[cfg(test)]
+pub mod limits_test;
+#[cfg(test)]
+pub mod liveness_test;
+#[cfg(test)]
+pub mod llvm_test;
+#[cfg(test)]
+pub mod mips_test;
+#[cfg(test)]
 pub mod parser_test;
+#[cfg(test)]
+pub mod printer_test;
+#[cfg(test)]
+pub mod regalloc_test;
+#[cfg(test)]
+pub mod riscv64_test;
+#[cfg(test)]
+pub mod runtime_test;
+#[cfg(test)]
+pub mod rv64im;
+#[cfg(test)]
+pub mod semant_test;
+#[cfg(test)]
+pub mod serialize_test;
+#[cfg(test)]
+pub mod translate_test;
 pub mod util;
+#[cfg(test)]
+pub mod visitor_test;
+#[cfg(test)]
+pub mod wasm_host;
+#[cfg(test)]
+pub mod wasm_test;
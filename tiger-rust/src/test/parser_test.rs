@@ -4,6 +4,7 @@ use std::{
 };
 
 use super::util::all_path;
+use crate::error::error::{get_position_info, parse_line_pos, reset_line_pos};
 use crate::parser::parser::compile;
 
 const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
@@ -54,3 +55,33 @@ fn test_my_data() {
 
     println!("{}", result.unwrap().to_yaml_string().unwrap());
 }
+
+#[test]
+fn test_positions_are_one_based() {
+    reset_line_pos();
+    parse_line_pos("ab\ncd\r\nef");
+    let at = |pos| {
+        let info = get_position_info(pos);
+        (info.line, info.col)
+    };
+    assert_eq!(at(0), (1, 1));
+    assert_eq!(at(1), (1, 2));
+    assert_eq!(at(3), (2, 1));
+    assert_eq!(at(4), (2, 2));
+    assert_eq!(at(7), (3, 1));
+    assert_eq!(at(8), (3, 2));
+}
+
+#[test]
+fn test_positions_are_per_thread() {
+    reset_line_pos();
+    parse_line_pos("a\nb");
+    std::thread::spawn(|| {
+        reset_line_pos();
+        parse_line_pos("\n\n\nc");
+        assert_eq!(get_position_info(3).line, 4);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(get_position_info(2).line, 2);
+}
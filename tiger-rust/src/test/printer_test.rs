@@ -0,0 +1,108 @@
+use std::fs::{self, metadata};
+
+use super::util::all_path;
+use crate::parser::parser::compile;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+const ABSYN_PATH: &str = "./src/test/testcases/absyn/";
+
+#[test]
+fn test_absyn_reference() {
+    for reference_path in all_path(ABSYN_PATH).unwrap() {
+        if metadata(&reference_path).unwrap().is_dir() {
+            continue;
+        }
+        let name = reference_path
+            .strip_prefix(ABSYN_PATH)
+            .unwrap()
+            .strip_suffix(".absyn")
+            .unwrap();
+        let input_path = format!("{}{}.tig", TEST_DATA_PATH, name);
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        let expected = fs::read_to_string(&reference_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", reference_path));
+        println!("{}", input_path);
+        assert_eq!(compile(&input).unwrap().to_absyn_string(), expected);
+    }
+}
+
+#[test]
+fn test_absyn_by_hand() {
+    // Written out by hand from the rules of Appel's prabsyn.sml, quirks
+    // included, rather than generated by the printer.
+    let absyn = compile("let function f(a: int): int = a in f(1) end")
+        .unwrap()
+        .to_absyn_string();
+    let expected = "\
+LetExp([
+ FunctionDec[
+  (f,[
+   (a,true,int)],
+SOME(int),
+   VarExp(
+    SimpleVar(a)))]],
+ SeqExp[
+  CallExp(f,[
+   IntExp(1)])])
+";
+    assert_eq!(absyn, expected);
+
+    let input = "(a.b := \"s\"; a[0] := nil; for i := 0 to 1 do break; \
+                 while 1 do (); r {x = 2})";
+    let expected = "\
+SeqExp[
+ AssignExp(
+  FieldVar(
+   SimpleVar(a),
+   b),
+  StringExp(\"s\")),
+ AssignExp(
+  SubscriptVar(
+   SimpleVar(a),
+   IntExp(0)),
+  NilExp),
+ ForExp(
+itrue,
+  IntExp(0),
+  IntExp(1),
+  BreakExp),
+ WhileExp(
+  IntExp(1),
+  SeqExp[]),
+ RecordExp(r,[
+
+  (x,
+   IntExp(2))])]
+";
+    assert_eq!(compile(input).unwrap().to_absyn_string(), expected);
+}
+
+#[test]
+fn test_absyn_desugar() {
+    let absyn = compile("a & b | -c").unwrap().to_absyn_string();
+    let expected = "\
+IfExp(
+ IfExp(
+  VarExp(
+   SimpleVar(a)),
+  VarExp(
+   SimpleVar(b)),
+  IntExp(0)),
+ IntExp(1),
+ OpExp(MinusOp,
+  IntExp(0),
+  VarExp(
+   SimpleVar(c))))
+";
+    assert_eq!(absyn, expected);
+
+    let absyn = compile("let type a = int var b := 1 function f() = () function g() = () in end")
+        .unwrap()
+        .to_absyn_string();
+    assert!(absyn.starts_with("LetExp([\n TypeDec[\n  (a,\n   NameTy(int))],\n VarDec(b,"));
+    assert!(absyn.contains(" FunctionDec[\n  (f,[],\nNONE,\n   SeqExp[]),\n  (g,[],"));
+
+    let absyn = compile("print(\"a\\tb\\\"\")").unwrap().to_absyn_string();
+    assert_eq!(absyn, "CallExp(print,[\n StringExp(\"a\tb\"\")])\n");
+}
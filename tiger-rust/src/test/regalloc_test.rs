@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::fs::{self, metadata};
+
+use super::codegen_test::{run_stdout_with, run_with};
+use super::util::all_path;
+
+use crate::assem::assem::Instr;
+use crate::codegen::codegen::{instructions, Codegen};
+use crate::flowgraph::flowgraph::FlowGraph;
+use crate::frame::frame::Frame;
+use crate::frame::x86_64::{X86_64Frame, RAX, RBX, RCX};
+use crate::liveness::liveness::{InterferenceGraph, Liveness};
+use crate::parser::parser::compile;
+use crate::regalloc::color::color;
+use crate::regalloc::linear_scan::linear_scan;
+use crate::regalloc::regalloc::{allocate, check, Allocated, Allocation, Allocator};
+use crate::semant::semant::trans_prog;
+use crate::temp::temp::Temp;
+use crate::translate::translate::{translate, Frag};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+/// Allocates every function of `input`, checking each allocation.
+fn allocate_all(input: &str, allocator: Allocator) -> Vec<(String, Allocated)> {
+    let program = trans_prog(&compile(input).unwrap()).unwrap();
+    let mut procs = Vec::new();
+    for frag in translate::<X86_64Frame>(&program) {
+        let Frag::Proc { body, mut frame } = frag else {
+            continue;
+        };
+        let (instrs, _) = instructions(&mut frame, body);
+        let allocated = allocate(&mut frame, instrs, allocator);
+        let name = frame.name().to_string();
+        check::<X86_64Frame>(&allocated.instrs, &allocated.allocation)
+            .unwrap_or_else(|err| panic!("{}: {}", name, err));
+        procs.push((name, allocated));
+    }
+    procs
+}
+
+/// Three temps that are all live at once, and a copy of one of them.
+fn triangle() -> (Vec<Instr>, [Temp; 4]) {
+    let (a, b, c, d) = (Temp::new(), Temp::new(), Temp::new(), Temp::new());
+    let instrs = vec![
+        Instr::oper("\tmovq $1, `d0", vec![a], vec![]),
+        Instr::oper("\tmovq $2, `d0", vec![b], vec![]),
+        Instr::oper("\tmovq $3, `d0", vec![c], vec![]),
+        Instr::Move {
+            assem: String::from("\tmovq `s0, `d0"),
+            dst: d,
+            src: a,
+        },
+        Instr::oper("", vec![], vec![d, b, c]),
+    ];
+    (instrs, [a, b, c, d])
+}
+
+#[test]
+fn test_color_coalesces() {
+    let (instrs, [a, b, c, d]) = triangle();
+    let flow = FlowGraph::new(&instrs);
+    let graph = InterferenceGraph::new(&flow, &Liveness::new(&flow));
+    let (allocation, spilled) = color(&graph, &[RAX, RBX, RCX], |_| 1.0);
+    assert!(spilled.is_empty());
+    assert_eq!(allocation[&a], allocation[&d]);
+    assert_ne!(allocation[&a], allocation[&b]);
+    assert_ne!(allocation[&b], allocation[&c]);
+    assert_ne!(allocation[&a], allocation[&c]);
+    check::<X86_64Frame>(&instrs, &allocation).unwrap();
+}
+
+#[test]
+fn test_color_spills() {
+    let (instrs, [_, b, _, _]) = triangle();
+    let flow = FlowGraph::new(&instrs);
+    let graph = InterferenceGraph::new(&flow, &Liveness::new(&flow));
+    let cost = |temp: Temp| if temp == b { 0.5 } else { 2.0 };
+    let (_, spilled) = color(&graph, &[RAX, RBX], cost);
+    assert_eq!(spilled, vec![b]);
+}
+
+#[test]
+fn test_check_rejects_conflicts() {
+    let (instrs, [a, b, c, d]) = triangle();
+    let allocation: Allocation = [(a, RAX), (b, RBX), (c, RAX), (d, RAX)]
+        .into_iter()
+        .collect();
+    let err = check::<X86_64Frame>(&instrs, &allocation).unwrap_err();
+    assert!(err.ends_with("interfere but share %rax"), "{}", err);
+    let allocation: Allocation = [(a, RAX), (b, RBX)].into_iter().collect();
+    assert!(check::<X86_64Frame>(&instrs, &allocation)
+        .unwrap_err()
+        .ends_with("has no register"));
+}
+
+#[test]
+fn test_regalloc_all() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        match compile(&input).map(|exp| trans_prog(&exp)) {
+            Ok(Ok(_)) => (),
+            _ => continue,
+        };
+        println!("{}", input_path);
+        let procs = allocate_all(&input, Allocator::Coloring)
+            .into_iter()
+            .chain(allocate_all(&input, Allocator::LinearScan));
+        for (name, allocated) in procs {
+            // Temps only get registers the frame hands out, or the one
+            // they were coalesced with.
+            let registers = X86_64Frame::registers();
+            for temp in allocated.allocation.values() {
+                assert!(registers.contains(temp) || temp.is_precolored(), "{}", name);
+            }
+        }
+    }
+}
+
+/// Twenty values, all still needed after a run of calls: more than the
+/// callee-save registers can hold.
+fn pressure_program() -> String {
+    let vars: Vec<String> = (1..=20).map(|i| format!("v{}", i)).collect();
+    let decs: String = vars
+        .iter()
+        .enumerate()
+        .map(|(i, v)| format!("var {} := id({}) ", v, i + 1))
+        .collect();
+    format!(
+        "let function id(x: int): int = x \
+           function printint(i: int) = \
+             if i > 0 then (printint(i / 10); print(chr(i - i / 10 * 10 + ord(\"0\")))) \
+           {} \
+         in printint({}); print(\" \"); printint({}) end",
+        decs,
+        vars.join(" + "),
+        vars.join(" * id(1) + ")
+    )
+}
+
+#[test]
+fn test_regalloc_spills() {
+    let input = pressure_program();
+    for (allocator, name) in [
+        (Allocator::Coloring, "pressure-coloring"),
+        (Allocator::LinearScan, "pressure-linear"),
+    ] {
+        let procs = allocate_all(&input, allocator);
+        let (_, main) = procs.iter().find(|(name, _)| name == "tigermain").unwrap();
+        assert!(!main.spilled.is_empty());
+        assert_eq!(run_stdout_with(name, &input, allocator), "210 210");
+    }
+}
+
+#[test]
+fn test_regalloc_coalesces() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    for (name, allocated) in allocate_all(&input, Allocator::Coloring) {
+        let register =
+            |temp: Temp| -> Temp { allocated.allocation.get(&temp).copied().unwrap_or(temp) };
+        let moves = allocated
+            .instrs
+            .iter()
+            .filter(|instr| matches!(instr, Instr::Move { .. }))
+            .count();
+        let kept = allocated
+            .instrs
+            .iter()
+            .filter(|instr| {
+                matches!(instr, Instr::Move { dst, src, .. } if register(*dst) != register(*src))
+            })
+            .count();
+        assert!(
+            kept * 2 < moves,
+            "{}: {} of {} moves kept",
+            name,
+            kept,
+            moves
+        );
+    }
+}
+
+#[test]
+fn test_linear_scan_programs() {
+    let queens = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let output = run_stdout_with("queens-linear", &queens, Allocator::LinearScan);
+    assert_eq!(output.lines().count(), 92 * 9);
+    let merge = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    let output = run_with(
+        "merge-linear",
+        &merge,
+        "1 4 9 ;\n2 3 10 11 ;\n",
+        Allocator::LinearScan,
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1 2 3 4 9 10 11 \n"
+    );
+}
+
+#[test]
+fn test_linear_scan_hints() {
+    // A copy whose source dies at the copy needs no register of its own.
+    let (instrs, [a, _, _, d]) = triangle();
+    let flow = FlowGraph::new(&instrs);
+    let liveness = Liveness::new(&flow);
+    let (allocation, spilled) = linear_scan(&flow, &liveness, &[RAX, RBX, RCX], &HashSet::new());
+    assert!(spilled.is_empty());
+    assert_eq!(allocation[&a], allocation[&d]);
+    check::<X86_64Frame>(&instrs, &allocation).unwrap();
+    let (_, spilled) = linear_scan(&flow, &liveness, &[RAX, RBX], &HashSet::new());
+    assert_eq!(spilled.len(), 1);
+}
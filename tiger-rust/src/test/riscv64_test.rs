@@ -0,0 +1,109 @@
+use std::fs;
+
+use super::rv64im::run;
+use super::util::{testcases, NON_TERMINATING};
+
+use crate::codegen::codegen::{compile_with, instructions};
+use crate::frame::frame::Frame;
+use crate::frame::riscv64::Riscv64Frame;
+use crate::interp::interp;
+use crate::parser::parser::compile as parse;
+use crate::regalloc::regalloc::{allocate, check, Allocator};
+use crate::semant::semant::trans_prog;
+use crate::temp::temp;
+use crate::translate::translate::{translate, Frag};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+const ASM_PATH: &str = "./src/test/testcases/riscv64/";
+
+fn compile_riscv64(input: &str, allocator: Allocator) -> String {
+    let program = trans_prog(&parse(input).unwrap()).unwrap();
+    compile_with(translate::<Riscv64Frame>(&program), allocator)
+}
+
+/// Runs the assembly `asm` on `stdin`, returning its exit status and output.
+fn emulate(asm: &str, stdin: &str) -> Result<(i64, String), String> {
+    let mut output = Vec::new();
+    let status = run(asm, &mut stdin.as_bytes(), &mut output)?;
+    Ok((status, String::from_utf8(output).unwrap()))
+}
+
+#[test]
+fn test_riscv64_reference() {
+    for (file_name, input) in testcases() {
+        let reference_path = format!("{}{}", ASM_PATH, file_name.replace(".tig", ".s"));
+        let expected = fs::read_to_string(&reference_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", reference_path));
+        println!("{}", file_name);
+        temp::reset();
+        assert_eq!(compile_riscv64(&input, Allocator::default()), expected);
+    }
+}
+
+#[test]
+fn test_riscv64_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let (status, output) = emulate(&compile_riscv64(&input, Allocator::default()), "").unwrap();
+    assert_eq!(status, 0);
+    assert_eq!(output.lines().count(), 92 * 9);
+    assert!(output.starts_with(" O . . . . . . .\n . . . . O . . .\n"));
+}
+
+#[test]
+fn test_riscv64_merge() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    let asm = compile_riscv64(&input, Allocator::LinearScan);
+    assert_eq!(
+        emulate(&asm, "1 4 9 ;\n2 3 10 11 ;\n"),
+        Ok((0, String::from("1 2 3 4 9 10 11 \n")))
+    );
+}
+
+#[test]
+fn test_riscv64_calls() {
+    // More arguments than registers, and static links two levels up.
+    let input = "let var base := 100 \
+                   function many(a: int, b: int, c: int, d: int, e: int, \
+                                 f: int, g: int, h: int, i: int, j: int): int = \
+                     let function inner(x: int): int = x + base + j \
+                     in inner(a + b * 2 + c * 3 + d * 4 + e * 5 + f * 6 + g * 7 \
+                              + h * 8 + i * 9 + j * 10) end \
+                 in exit(many(1, 2, 3, 4, 5, 6, 7, 8, 9, 10) - 300) end";
+    let asm = compile_riscv64(input, Allocator::default());
+    assert_eq!(emulate(&asm, ""), Ok((195, String::new())));
+}
+
+#[test]
+fn test_riscv64_matches_interp() {
+    for (file_name, input) in testcases() {
+        if NON_TERMINATING.contains(&file_name.as_str()) {
+            continue;
+        }
+        let program = trans_prog(&parse(&input).unwrap()).unwrap();
+        let mut expected = Vec::new();
+        let status = interp::run(&program, &mut "".as_bytes(), &mut expected);
+        let expected = status.map(|status| (status, String::from_utf8(expected).unwrap()));
+        for allocator in [Allocator::Coloring, Allocator::LinearScan] {
+            let asm = compile_with(translate::<Riscv64Frame>(&program), allocator);
+            assert_eq!(emulate(&asm, ""), expected, "{}", file_name);
+        }
+    }
+}
+
+#[test]
+fn test_riscv64_regalloc() {
+    for (file_name, input) in testcases() {
+        let program = trans_prog(&parse(&input).unwrap()).unwrap();
+        for allocator in [Allocator::Coloring, Allocator::LinearScan] {
+            for frag in translate::<Riscv64Frame>(&program) {
+                let Frag::Proc { body, mut frame } = frag else {
+                    continue;
+                };
+                let (instrs, _) = instructions(&mut frame, body);
+                let allocated = allocate(&mut frame, instrs, allocator);
+                check::<Riscv64Frame>(&allocated.instrs, &allocated.allocation)
+                    .unwrap_or_else(|err| panic!("{} {}: {}", file_name, frame.name(), err));
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::codegen_test::{run, run_stdout};
+
+#[test]
+fn test_runtime_builtins() {
+    let input = "let var s := \"hello\" \
+                 in print(chr(ord(\"a\") + 1)); \
+                    print(if size(s) = 5 then \"5\" else \"?\"); \
+                    print(if size(\"\") = 0 then \"0\" else \"?\"); \
+                    print(substring(s, 1, 3)); \
+                    print(concat(\"\", concat(s, \"\"))); \
+                    print(if not(0) & not(not(7)) then \"t\" else \"f\"); \
+                    print(if ord(\"\") = -1 then \"e\" else \"?\"); \
+                    flush() \
+                 end";
+    assert_eq!(run_stdout("builtins", input), "b50ellhellote");
+}
+
+#[test]
+fn test_runtime_getchar() {
+    let input = "let var c := getchar() \
+                 in while c <> \"\" do (print(concat(c, c)); c := getchar()) end";
+    let output = run("getchar", input, "ab\n");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "aabb\n\n");
+}
+
+#[test]
+fn test_runtime_exit() {
+    let output = run(
+        "exit",
+        "(print(\"bye\"); exit(3); print(\"unreachable\"))",
+        "",
+    );
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "bye");
+}
+
+#[test]
+fn test_runtime_errors() {
+    let output = run("substring", "print(substring(\"abc\", 2, 5))", "");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!String::from_utf8(output.stderr).unwrap().is_empty());
+    let output = run("chr", "print(chr(300))", "");
+    assert_eq!(output.status.code(), Some(1));
+}
@@ -0,0 +1,665 @@
+//! A small RV64IM emulator for the tests of the RISC-V backend. It assembles
+//! the GNU assembler text the backend emits, then runs it from `tigermain`
+//! with the runtime functions built in, as the MIPS simulator does. Each
+//! line holds one instruction, pseudo-instructions included, and runs as
+//! the instructions it stands for would.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use crate::frame::riscv64::{Riscv64Frame, REGISTER_NAMES};
+use crate::interp::interp::backtrace;
+use crate::interp::runtime::{at, labels, Runtime};
+use crate::temp::temp::Label;
+
+const RUNTIME_BASE: u64 = 0x1000;
+const TEXT_BASE: u64 = 0x1_0000;
+const DATA_BASE: u64 = 0x1000_0000;
+const STACK_TOP: u64 = 0x8000_0000;
+const STACK_BYTES: u64 = 8 << 20;
+
+const RA: usize = 1;
+const SP: usize = 2;
+const A0: usize = 10;
+
+type Reg = usize;
+
+#[derive(Debug, Clone, Copy)]
+enum Src {
+    Reg(Reg),
+    Imm(i64),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Alu {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    And,
+    Or,
+    Xor,
+    Sll,
+    Srl,
+    Sra,
+    Slt,
+    Sltu,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Cond {
+    Eq,
+    Ne,
+    Lt,
+    Ge,
+    Ltu,
+    Geu,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Inst {
+    Alu(Alu, Reg, Reg, Src),
+    Li(Reg, i64),
+    Ld(Reg, i64, Reg),
+    Sd(Reg, i64, Reg),
+    Lbu(Reg, i64, Reg),
+    Sb(Reg, i64, Reg),
+    Branch(Cond, Reg, Reg, u64),
+    Jal(Reg, u64),
+    Jalr(Reg, Reg, i64),
+}
+
+struct Program {
+    text: Vec<Inst>,
+    data: Vec<u8>,
+    entry: u64,
+    names: HashMap<u64, String>,
+    runtime: HashMap<u64, Runtime>,
+}
+
+fn assemble(asm: &str) -> Result<Program, String> {
+    let mut lines = Vec::new();
+    let mut data = Vec::new();
+    let mut symbols: HashMap<&str, u64> = HashMap::new();
+    let mut fixups = Vec::new();
+    let mut in_text = true;
+    for (index, line) in asm.lines().enumerate() {
+        let number = index + 1;
+        let error = |msg: String| format!("line {}: {}", number, msg);
+        let mut line = strip_comment(line).trim();
+        while let Some((label, rest)) = split_label(line) {
+            let address = match in_text {
+                true => TEXT_BASE + 4 * lines.len() as u64,
+                false => DATA_BASE + data.len() as u64,
+            };
+            if symbols.insert(label, address).is_some() {
+                return Err(error(format!("label {} defined twice", label)));
+            }
+            line = rest.trim();
+        }
+        if line.is_empty() {
+            continue;
+        }
+        let (head, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(head, rest)| (head, rest.trim()));
+        let operands: Vec<&str> = match rest {
+            "" => Vec::new(),
+            rest => rest.split(',').map(str::trim).collect(),
+        };
+        match head {
+            ".text" => in_text = true,
+            ".section" => in_text = operands.first() == Some(&".text"),
+            ".globl" | ".type" | ".size" => (),
+            ".p2align" => {
+                let align = 1 << immediate(rest).map_err(error)?;
+                data.resize(data.len().next_multiple_of(align), 0);
+            }
+            ".zero" => data.resize(data.len() + immediate(rest).map_err(error)? as usize, 0),
+            ".quad" => {
+                for operand in operands {
+                    match immediate(operand) {
+                        Ok(value) => data.extend_from_slice(&value.to_le_bytes()),
+                        Err(_) => {
+                            fixups.push((data.len(), operand, number));
+                            data.extend_from_slice(&[0; 8]);
+                        }
+                    }
+                }
+            }
+            ".byte" => {
+                for operand in operands {
+                    data.push(immediate(operand).map_err(error)? as u8);
+                }
+            }
+            ".ascii" => data.extend(string_operand(rest).map_err(error)?),
+            _ if head.starts_with('.') => {
+                return Err(error(format!("unknown directive {}", head)));
+            }
+            _ if !in_text => return Err(error(format!("{} outside the text", head))),
+            mnemonic => lines.push((number, mnemonic, operands)),
+        }
+    }
+
+    let mut runtime_labels: Vec<(Label, Runtime)> = labels::<Riscv64Frame>().into_iter().collect();
+    runtime_labels.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let runtime_labels: HashMap<String, u64> = runtime_labels
+        .iter()
+        .enumerate()
+        .map(|(index, (label, _))| (label.to_string(), RUNTIME_BASE + 4 * index as u64))
+        .collect();
+    let resolve = |label: &str| -> Result<u64, String> {
+        symbols
+            .get(label)
+            .or_else(|| runtime_labels.get(label))
+            .copied()
+            .ok_or_else(|| format!("undefined label {}", label))
+    };
+    for (offset, label, number) in fixups {
+        let address = resolve(label).map_err(|err| format!("line {}: {}", number, err))?;
+        data[offset..offset + 8].copy_from_slice(&address.to_le_bytes());
+    }
+    let text = lines
+        .iter()
+        .map(|(number, mnemonic, operands)| {
+            instruction(mnemonic, operands, &resolve)
+                .map_err(|err| format!("line {}: {}", number, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let names = symbols
+        .iter()
+        .filter(|(_, address)| **address < DATA_BASE)
+        .map(|(label, address)| (*address, Label::named(label).source_name().to_string()))
+        .collect();
+    let runtime = labels::<Riscv64Frame>()
+        .into_iter()
+        .map(|(label, runtime)| (runtime_labels[label.name()], runtime))
+        .collect();
+    Ok(Program {
+        text,
+        data,
+        entry: *symbols.get("tigermain").ok_or("no tigermain label")?,
+        names,
+        runtime,
+    })
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn split_label(line: &str) -> Option<(&str, &str)> {
+    let (label, rest) = line.split_once(':')?;
+    let valid = !label.is_empty()
+        && label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    valid.then_some((label, rest))
+}
+
+fn immediate(operand: &str) -> Result<i64, String> {
+    let (negative, digits) = match operand.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, operand),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).map(|value| value as i64),
+        None => digits.parse::<i64>(),
+    }
+    .map_err(|_| format!("bad immediate {}", operand))?;
+    Ok(if negative {
+        value.wrapping_neg()
+    } else {
+        value
+    })
+}
+
+/// The bytes of a quoted string, with the escapes the backend writes.
+fn string_operand(operand: &str) -> Result<Vec<u8>, String> {
+    let inner = operand
+        .strip_prefix('"')
+        .and_then(|operand| operand.strip_suffix('"'))
+        .ok_or_else(|| format!("bad string {}", operand))?;
+    let mut bytes = Vec::new();
+    let mut chars = inner.bytes().peekable();
+    while let Some(byte) = chars.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match chars.next() {
+            Some(byte @ (b'"' | b'\\')) => bytes.push(byte),
+            Some(digit @ b'0'..=b'7') => {
+                let mut value = (digit - b'0') as u32;
+                for _ in 0..2 {
+                    match chars.peek() {
+                        Some(digit @ b'0'..=b'7') => {
+                            value = value * 8 + (digit - b'0') as u32;
+                            chars.next();
+                        }
+                        _ => break,
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            _ => return Err(format!("bad escape in {}", operand)),
+        }
+    }
+    Ok(bytes)
+}
+
+fn register(operand: &str) -> Result<Reg, String> {
+    match operand {
+        "fp" => Ok(8),
+        _ => match operand.strip_prefix('x').map(str::parse::<usize>) {
+            Some(Ok(number)) if number < 32 => Ok(number),
+            _ => REGISTER_NAMES
+                .iter()
+                .position(|name| *name == operand)
+                .ok_or_else(|| format!("unknown register {}", operand)),
+        },
+    }
+}
+
+fn memory(operand: &str) -> Result<(i64, Reg), String> {
+    let (offset, base) = operand
+        .strip_suffix(')')
+        .and_then(|operand| operand.split_once('('))
+        .ok_or_else(|| format!("bad address {}", operand))?;
+    let offset = match offset.trim() {
+        "" => 0,
+        offset => immediate(offset)?,
+    };
+    Ok((offset, register(base.trim())?))
+}
+
+fn instruction(
+    mnemonic: &str,
+    ops: &[&str],
+    resolve: &dyn Fn(&str) -> Result<u64, String>,
+) -> Result<Inst, String> {
+    let arity = |n: usize| match ops.len() == n {
+        true => Ok(()),
+        false => Err(format!(
+            "{} takes {} operands, not {}",
+            mnemonic,
+            n,
+            ops.len()
+        )),
+    };
+    let alu = |alu: Alu, immediate_form: bool| -> Result<Inst, String> {
+        arity(3)?;
+        let src = match immediate_form {
+            true => Src::Imm(immediate(ops[2])?),
+            false => Src::Reg(register(ops[2])?),
+        };
+        Ok(Inst::Alu(alu, register(ops[0])?, register(ops[1])?, src))
+    };
+    let branch = |cond: Cond, swap: bool| -> Result<Inst, String> {
+        arity(3)?;
+        let (a, b) = (register(ops[0])?, register(ops[1])?);
+        let (a, b) = if swap { (b, a) } else { (a, b) };
+        Ok(Inst::Branch(cond, a, b, resolve(ops[2])?))
+    };
+    let memory_op = |make: fn(Reg, i64, Reg) -> Inst| -> Result<Inst, String> {
+        arity(2)?;
+        let (offset, base) = memory(ops[1])?;
+        Ok(make(register(ops[0])?, offset, base))
+    };
+    match mnemonic {
+        "add" => alu(Alu::Add, false),
+        "addi" => alu(Alu::Add, true),
+        "sub" => alu(Alu::Sub, false),
+        "mul" => alu(Alu::Mul, false),
+        "div" => alu(Alu::Div, false),
+        "rem" => alu(Alu::Rem, false),
+        "and" => alu(Alu::And, false),
+        "andi" => alu(Alu::And, true),
+        "or" => alu(Alu::Or, false),
+        "ori" => alu(Alu::Or, true),
+        "xor" => alu(Alu::Xor, false),
+        "xori" => alu(Alu::Xor, true),
+        "sll" => alu(Alu::Sll, false),
+        "slli" => alu(Alu::Sll, true),
+        "srl" => alu(Alu::Srl, false),
+        "srli" => alu(Alu::Srl, true),
+        "sra" => alu(Alu::Sra, false),
+        "srai" => alu(Alu::Sra, true),
+        "slt" => alu(Alu::Slt, false),
+        "slti" => alu(Alu::Slt, true),
+        "sltu" => alu(Alu::Sltu, false),
+        "sltiu" => alu(Alu::Sltu, true),
+        "mv" => {
+            arity(2)?;
+            Ok(Inst::Alu(
+                Alu::Add,
+                register(ops[0])?,
+                register(ops[1])?,
+                Src::Imm(0),
+            ))
+        }
+        "li" => {
+            arity(2)?;
+            Ok(Inst::Li(register(ops[0])?, immediate(ops[1])?))
+        }
+        "la" => {
+            arity(2)?;
+            Ok(Inst::Li(register(ops[0])?, resolve(ops[1])? as i64))
+        }
+        "ld" => memory_op(Inst::Ld),
+        "sd" => memory_op(Inst::Sd),
+        "lbu" => memory_op(Inst::Lbu),
+        "sb" => memory_op(Inst::Sb),
+        "beq" => branch(Cond::Eq, false),
+        "bne" => branch(Cond::Ne, false),
+        "blt" => branch(Cond::Lt, false),
+        "bge" => branch(Cond::Ge, false),
+        "bltu" => branch(Cond::Ltu, false),
+        "bgeu" => branch(Cond::Geu, false),
+        "bgt" => branch(Cond::Lt, true),
+        "ble" => branch(Cond::Ge, true),
+        "bgtu" => branch(Cond::Ltu, true),
+        "bleu" => branch(Cond::Geu, true),
+        "j" => {
+            arity(1)?;
+            Ok(Inst::Jal(0, resolve(ops[0])?))
+        }
+        "call" => {
+            arity(1)?;
+            Ok(Inst::Jal(RA, resolve(ops[0])?))
+        }
+        "jr" => {
+            arity(1)?;
+            Ok(Inst::Jalr(0, register(ops[0])?, 0))
+        }
+        "jalr" => {
+            arity(1)?;
+            Ok(Inst::Jalr(RA, register(ops[0])?, 0))
+        }
+        "ret" => {
+            arity(0)?;
+            Ok(Inst::Jalr(0, RA, 0))
+        }
+        mnemonic => Err(format!("unknown instruction {}", mnemonic)),
+    }
+}
+
+struct Machine<'a> {
+    program: &'a Program,
+    regs: [i64; 32],
+    pc: u64,
+    data: Vec<u8>,
+    stack: Vec<u8>,
+    /// The entry address of each active call, innermost last.
+    calls: Vec<u64>,
+    input: &'a mut dyn Read,
+    output: &'a mut dyn Write,
+}
+
+/// Assembles and runs `asm`, returning the status passed to `exit` or 0,
+/// or the runtime error with its backtrace.
+pub fn run(asm: &str, input: &mut dyn Read, output: &mut dyn Write) -> Result<i64, String> {
+    let program = assemble(asm)?;
+    let mut machine = Machine {
+        program: &program,
+        regs: [0; 32],
+        pc: program.entry,
+        data: program.data.clone(),
+        stack: vec![0; STACK_BYTES as usize],
+        calls: vec![program.entry],
+        input,
+        output,
+    };
+    machine.regs[SP] = STACK_TOP as i64;
+    let status = machine.execute().map_err(|err| {
+        machine.calls.iter().rev().fold(err, |err, entry| {
+            backtrace(err, program.names.get(entry).map_or("?", String::as_str))
+        })
+    });
+    machine.output.flush().map_err(|err| err.to_string())?;
+    status
+}
+
+impl Machine<'_> {
+    fn execute(&mut self) -> Result<i64, String> {
+        loop {
+            if self.pc < TEXT_BASE {
+                if self.pc == 0 {
+                    return Ok(0);
+                }
+                let runtime = *self
+                    .program
+                    .runtime
+                    .get(&self.pc)
+                    .ok_or_else(|| format!("jump to bad address {:#x}", self.pc))?;
+                if let Some(status) = self.runtime(runtime)? {
+                    return Ok(status);
+                }
+                self.pc = self.regs[RA] as u64;
+                continue;
+            }
+            let inst = *self
+                .program
+                .text
+                .get(((self.pc - TEXT_BASE) / 4) as usize)
+                .ok_or_else(|| format!("jump to bad address {:#x}", self.pc))?;
+            self.pc += 4;
+            match inst {
+                Inst::Alu(alu, d, s, t) => {
+                    let a = self.regs[s];
+                    let b = match t {
+                        Src::Reg(r) => self.regs[r],
+                        Src::Imm(value) => value,
+                    };
+                    let value = match alu {
+                        Alu::Add => a.wrapping_add(b),
+                        Alu::Sub => a.wrapping_sub(b),
+                        Alu::Mul => a.wrapping_mul(b),
+                        // RISC-V does not trap on division by zero.
+                        Alu::Div if b == 0 => -1,
+                        Alu::Div => a.wrapping_div(b),
+                        Alu::Rem if b == 0 => a,
+                        Alu::Rem => a.wrapping_rem(b),
+                        Alu::And => a & b,
+                        Alu::Or => a | b,
+                        Alu::Xor => a ^ b,
+                        Alu::Sll => a.wrapping_shl(b as u32 & 63),
+                        Alu::Srl => ((a as u64) >> (b as u32 & 63)) as i64,
+                        Alu::Sra => a >> (b as u32 & 63),
+                        Alu::Slt => (a < b) as i64,
+                        Alu::Sltu => ((a as u64) < (b as u64)) as i64,
+                    };
+                    self.set(d, value);
+                }
+                Inst::Li(d, value) => self.set(d, value),
+                Inst::Ld(d, offset, base) => {
+                    let value = self.load(self.regs[base].wrapping_add(offset) as u64)?;
+                    self.set(d, value);
+                }
+                Inst::Sd(s, offset, base) => {
+                    self.store(self.regs[base].wrapping_add(offset) as u64, self.regs[s])?
+                }
+                Inst::Lbu(d, offset, base) => {
+                    let address = self.regs[base].wrapping_add(offset) as u64;
+                    let value = self.bytes(address, 1)?[0] as i64;
+                    self.set(d, value);
+                }
+                Inst::Sb(s, offset, base) => {
+                    let address = self.regs[base].wrapping_add(offset) as u64;
+                    self.bytes(address, 1)?[0] = self.regs[s] as u8;
+                }
+                Inst::Branch(cond, s, t, target) => {
+                    let (a, b) = (self.regs[s], self.regs[t]);
+                    let taken = match cond {
+                        Cond::Eq => a == b,
+                        Cond::Ne => a != b,
+                        Cond::Lt => a < b,
+                        Cond::Ge => a >= b,
+                        Cond::Ltu => (a as u64) < (b as u64),
+                        Cond::Geu => (a as u64) >= (b as u64),
+                    };
+                    if taken {
+                        self.pc = target;
+                    }
+                }
+                Inst::Jal(d, target) => self.jump(d, target),
+                Inst::Jalr(d, s, offset) => {
+                    let target = self.regs[s].wrapping_add(offset) as u64;
+                    if d == 0 && s == RA {
+                        self.calls.pop();
+                    }
+                    self.jump(d, target)
+                }
+            }
+        }
+    }
+
+    fn set(&mut self, d: Reg, value: i64) {
+        if d != 0 {
+            self.regs[d] = value;
+        }
+    }
+
+    fn jump(&mut self, d: Reg, target: u64) {
+        self.set(d, self.pc as i64);
+        if d == RA && target >= TEXT_BASE {
+            self.calls.push(target);
+        }
+        self.pc = target;
+    }
+
+    fn bytes(&mut self, address: u64, len: u64) -> Result<&mut [u8], String> {
+        let bad = || format!("bad address {:#x}", address);
+        let end = address.checked_add(len).ok_or_else(bad)?;
+        if address >= STACK_TOP - STACK_BYTES && end <= STACK_TOP {
+            let start = (address - (STACK_TOP - STACK_BYTES)) as usize;
+            return Ok(&mut self.stack[start..start + len as usize]);
+        }
+        if address >= DATA_BASE && end - DATA_BASE <= self.data.len() as u64 {
+            let start = (address - DATA_BASE) as usize;
+            return Ok(&mut self.data[start..start + len as usize]);
+        }
+        Err(bad())
+    }
+
+    fn load(&mut self, address: u64) -> Result<i64, String> {
+        let bytes = self.bytes(address, 8)?;
+        Ok(i64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn store(&mut self, address: u64, value: i64) -> Result<(), String> {
+        self.bytes(address, 8)?
+            .copy_from_slice(&value.to_le_bytes());
+        Ok(())
+    }
+
+    fn alloc(&mut self, bytes: usize) -> Result<u64, String> {
+        let start = self.data.len().next_multiple_of(8);
+        let end = start
+            .checked_add(bytes.next_multiple_of(8))
+            .filter(|end| (*end as u64) < STACK_TOP - STACK_BYTES - DATA_BASE)
+            .ok_or("out of memory")?;
+        self.data.resize(end, 0);
+        Ok(DATA_BASE + start as u64)
+    }
+
+    fn string(&mut self, s: u64) -> Result<Vec<u8>, String> {
+        let len = u64::try_from(self.load(s)?).map_err(|_| format!("bad string at {:#x}", s))?;
+        Ok(self.bytes(s + 8, len)?.to_vec())
+    }
+
+    fn alloc_string(&mut self, bytes: &[u8]) -> Result<i64, String> {
+        let s = self.alloc(8 + bytes.len())?;
+        self.store(s, bytes.len() as i64)?;
+        self.bytes(s + 8, bytes.len() as u64)?
+            .copy_from_slice(bytes);
+        Ok(s as i64)
+    }
+
+    /// Carries out a call of the runtime, returning the exit status if it
+    /// ends the program.
+    fn runtime(&mut self, runtime: Runtime) -> Result<Option<i64>, String> {
+        let [a0, a1, a2, a3] = [
+            self.regs[A0],
+            self.regs[A0 + 1],
+            self.regs[A0 + 2],
+            self.regs[A0 + 3],
+        ];
+        let io_error = |err: std::io::Error| err.to_string();
+        let result = match runtime {
+            Runtime::Print => {
+                let bytes = self.string(a0 as u64)?;
+                self.output.write_all(&bytes).map_err(io_error)?;
+                0
+            }
+            Runtime::Flush => {
+                self.output.flush().map_err(io_error)?;
+                0
+            }
+            Runtime::Getchar => {
+                let mut byte = [0u8];
+                let n = self.input.read(&mut byte).map_err(io_error)?;
+                self.alloc_string(&byte[..n])?
+            }
+            Runtime::Ord => self.string(a0 as u64)?.first().map_or(-1, |c| *c as i64),
+            Runtime::Chr => match u8::try_from(a0) {
+                Ok(c) => self.alloc_string(&[c])?,
+                Err(_) => return Err(at(a1, &format!("chr({}) out of range", a0))),
+            },
+            Runtime::Size => self.load(a0 as u64)?,
+            Runtime::Substring => {
+                let bytes = self.string(a0 as u64)?;
+                if a1 < 0 || a2 < 0 || a1.saturating_add(a2) > bytes.len() as i64 {
+                    let msg = format!(
+                        "substring({:?}, {}, {}) out of range",
+                        String::from_utf8_lossy(&bytes),
+                        a1,
+                        a2
+                    );
+                    return Err(at(a3, &msg));
+                }
+                self.alloc_string(&bytes[a1 as usize..(a1 + a2) as usize])?
+            }
+            Runtime::Concat => {
+                let bytes = [self.string(a0 as u64)?, self.string(a1 as u64)?].concat();
+                self.alloc_string(&bytes)?
+            }
+            Runtime::Not => (a0 == 0) as i64,
+            Runtime::Exit => return Ok(Some(a0)),
+            Runtime::StringEqual => (self.string(a0 as u64)? == self.string(a1 as u64)?) as i64,
+            Runtime::StringCompare => self.string(a0 as u64)?.cmp(&self.string(a1 as u64)?) as i64,
+            Runtime::InitArray => {
+                if a0 < 0 {
+                    return Err(at(a3, &format!("array size {} is negative", a0)));
+                }
+                let a = self.alloc(8 * (a0 as usize).saturating_add(1))?;
+                self.store(a, a0)?;
+                for i in 1..=a0 as u64 {
+                    self.store(a + 8 * i, a1)?;
+                }
+                a as i64
+            }
+            Runtime::AllocRecord => self.alloc(a0 as usize)? as i64,
+            Runtime::NilRecord => return Err(at(a0, "nil record dereferenced")),
+            Runtime::IndexOutOfRange => {
+                let msg = format!("index {} out of range for array of size {}", a0, a1);
+                return Err(at(a2, &msg));
+            }
+            Runtime::DivisionByZero => return Err(at(a0, "division by zero")),
+        };
+        self.regs[A0] = result;
+        Ok(None)
+    }
+}
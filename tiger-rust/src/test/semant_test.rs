@@ -0,0 +1,96 @@
+use std::{
+    collections::HashSet,
+    fs::{self, metadata},
+};
+
+use super::util::all_path;
+use crate::parser::parser::compile;
+use crate::semant::semant::trans_prog;
+use crate::semant::tast::{FunKind, TExpKind};
+use crate::semant::types::Type;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+fn build_err_case_set() -> HashSet<String> {
+    let case_path = "./src/test/testcases/semant_error_case.json";
+    let case_json = fs::read_to_string(case_path)
+        .unwrap_or_else(|_| panic!("Something went wrong reading {}", case_path));
+    serde_json::from_str::<Vec<String>>(&case_json)
+        .unwrap()
+        .into_iter()
+        .collect()
+}
+
+#[test]
+fn test_semant() {
+    let err_cases = build_err_case_set();
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        let exp = match compile(&input) {
+            Ok(exp) => exp,
+            Err(_) => continue,
+        };
+        let result = trans_prog(&exp);
+        println!("{} {:?}", input_path, result.as_ref().err());
+        let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+        assert_eq!(result.is_err(), err_cases.contains(file_name));
+    }
+}
+
+fn check_error(input: &str, expected: &str) {
+    let err = trans_prog(&compile(input).unwrap()).unwrap_err();
+    assert_eq!(err, expected);
+}
+
+#[test]
+fn test_semant_errors() {
+    check_error("3 > \"df\"", "1.5: cannot compare int with string");
+    // `break` carries no position of its own in the syntax tree.
+    let err = trans_prog(&compile("(while 1 do break; break)").unwrap()).unwrap_err();
+    assert!(err.ends_with("break outside of a loop"));
+    check_error(
+        "for i := 0 to 3 do i := 1",
+        "1.20: cannot assign to loop variable i",
+    );
+    check_error(
+        "let type a = {x: int} in a{y = 1} end",
+        "1.28: unexpected field y for record a",
+    );
+    check_error(
+        "let type a = b type b = a in 0 end",
+        "1.10: illegal cycle in type declaration a",
+    );
+    check_error(
+        "let type r = {a: int, a: string} in 0 end",
+        "1.23: field a declared twice in record r",
+    );
+    check_error(
+        "let function f(a: int, a: int): int = a in f(1, 2) end",
+        "1.24: parameter a declared twice in function f",
+    );
+}
+
+#[test]
+fn test_semant_program() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let program = trans_prog(&compile(&input).unwrap()).unwrap();
+    let main = program.func(program.main);
+    assert_eq!(main.result, Type::Unit);
+    let body = match &main.kind {
+        FunKind::User { body } => body,
+        FunKind::Builtin => panic!("main program has no body"),
+    };
+    assert!(matches!(body.kind, TExpKind::Let(_, _)));
+    let try_fun = program.funcs.iter().find(|f| f.name == "try").unwrap();
+    assert_eq!(try_fun.parent, Some(program.main));
+    assert_eq!(program.depth(program.main), 0);
+    let c = program.var(try_fun.params[0]);
+    assert_eq!((c.name.as_str(), c.ty, c.escape), ("c", Type::Int, false));
+    let row = program.vars.iter().find(|v| v.name == "row").unwrap();
+    assert!(row.escape);
+    assert!(program.types.is_pointer(row.ty));
+}
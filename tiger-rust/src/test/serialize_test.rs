@@ -0,0 +1,139 @@
+use std::fs::{self, metadata};
+
+use super::util::all_path;
+use crate::parser::arena::Ast;
+use crate::parser::ast::Exp;
+use crate::parser::parser::compile_to_arena;
+use crate::parser::util::{AST_SCHEMA_VERSION, MAX_DUMP_DEPTH};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+#[test]
+fn test_round_trip() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        let ast = match compile_to_arena(&input) {
+            Ok(ast) => ast,
+            Err(_) => continue,
+        };
+        println!("{}", input_path);
+        let absyn = ast.to_absyn_string();
+        let json = ast.to_json_string().unwrap();
+        let from_json = Ast::from_json_str(&json).unwrap();
+        assert_eq!(from_json.to_absyn_string(), absyn);
+        assert_eq!(from_json.to_json_string().unwrap(), json);
+        let from_yaml = Ast::from_yaml_str(&ast.to_yaml_string().unwrap()).unwrap();
+        assert_eq!(from_yaml.to_json_string().unwrap(), json);
+        let exp = Exp::from_json_str(&json).unwrap();
+        assert_eq!(exp.to_json_string().unwrap(), json);
+        let exp = Exp::from_yaml_str(&exp.to_yaml_string().unwrap()).unwrap();
+        assert_eq!(exp.to_json_string().unwrap(), json);
+    }
+}
+
+#[test]
+fn test_too_deep_json() {
+    let json = format!(
+        "{{\"version\":{},\"nodes\":{}{}}}",
+        AST_SCHEMA_VERSION,
+        "[".repeat(500_000),
+        "]".repeat(500_000)
+    );
+    assert_eq!(
+        Ast::from_json_str(&json).map(|_| ()),
+        Err(format!(
+            "AST dump too deep: 500001 levels of nesting, at most {} are supported",
+            MAX_DUMP_DEPTH
+        ))
+    );
+    // Brackets inside string literals are not nesting.
+    let input = format!("print(\"{}\\\"\")", "[{".repeat(MAX_DUMP_DEPTH));
+    let json = compile_to_arena(&input).unwrap().to_json_string().unwrap();
+    assert!(Ast::from_json_str(&json).is_ok());
+}
+
+#[test]
+fn test_schema_version() {
+    let json = compile_to_arena("a + 1").unwrap().to_json_string().unwrap();
+    let header = format!("{{\"version\":{},", AST_SCHEMA_VERSION);
+    assert!(json.starts_with(&header));
+
+    let stale = json.replacen(&header, "{\"version\":2,", 1);
+    let err = Ast::from_json_str(&stale).unwrap_err();
+    assert!(err.contains("version 2 is not supported"));
+
+    let bare = json
+        .strip_prefix(&header)
+        .map(|rest| format!("{{{}", rest))
+        .unwrap();
+    assert!(Ast::from_json_str(&bare)
+        .unwrap_err()
+        .contains("missing AST schema header"));
+    assert!(Ast::from_yaml_str("nodes: []\n").is_err());
+}
+
+#[test]
+fn test_validation() {
+    let json = compile_to_arena("a + \"s\"")
+        .unwrap()
+        .to_json_string()
+        .unwrap();
+
+    let bad_id = json.replace("\"v\":\"a\"", "\"v\":\"1a\"");
+    assert!(Ast::from_json_str(&bad_id)
+        .unwrap_err()
+        .contains("invalid identifier \"1a\""));
+
+    let bad_string = json.replace("\"v\":\"\\\"s\\\"\"", "\"v\":\"s\"");
+    assert!(Ast::from_json_str(&bad_string)
+        .unwrap_err()
+        .contains("is not quoted"));
+
+    let bad_pos = json.replacen("\"line\":1", "\"line\":0", 1);
+    assert!(Ast::from_json_str(&bad_pos)
+        .unwrap_err()
+        .contains("invalid position 0."));
+
+    let load = |nodes: &str| {
+        let json = format!(
+            "{{\"version\":{},\"nodes\":[{}]}}",
+            AST_SCHEMA_VERSION, nodes
+        );
+        Ast::from_json_str(&json).map(|_| ())
+    };
+    assert_eq!(
+        load("{\"kind\":\"ExpError\"}"),
+        Err(String::from("error node in AST"))
+    );
+    assert_eq!(load(""), Err(String::from("AST dump has no nodes")));
+    assert_eq!(
+        load("{\"kind\":\"MetaId\"}"),
+        Err(String::from("node 0: MetaId needs leaf Id, found None"))
+    );
+    assert_eq!(
+        load("{\"kind\":\"MatchBreak\"},{\"kind\":\"ExpMatch\",\"children\":[0,0]}"),
+        Err(String::from("node 1: ExpMatch has 2 children, expected 1"))
+    );
+    assert_eq!(
+        load("{\"kind\":\"ExpMatch\",\"children\":[0]}"),
+        Err(String::from(
+            "node 0: child 0 does not come before its parent"
+        ))
+    );
+    assert_eq!(
+        load("{\"kind\":\"MetaNil\"},{\"kind\":\"ExpMatch\",\"children\":[0]}"),
+        Err(String::from("node 1: child 0 is a Meta, expected Match"))
+    );
+    assert_eq!(
+        load("{\"kind\":\"MatchBreak\"},{\"kind\":\"MatchBreak\"},{\"kind\":\"ExpMatch\",\"children\":[1]}"),
+        Err(String::from("node 0 has no parent"))
+    );
+    assert_eq!(
+        load("{\"kind\":\"MatchBreak\"}"),
+        Err(String::from("root node is a Match, expected Exp"))
+    );
+}
@@ -0,0 +1,926 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str1 = private unnamed_addr constant { i64, [2 x i8] } { i64 2, [2 x i8] c"np" }, align 8
+@str2 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"ppppppppp" }, align 8
+@str3 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"0" }, align 8
+@str4 = private unnamed_addr constant { i64, [7 x i8] } { i64 7, [7 x i8] c"readint" }, align 8
+@str5 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str6 = private unnamed_addr constant { i64, [6 x i8] } { i64 6, [6 x i8] c"pppppp" }, align 8
+@str7 = private unnamed_addr constant { i64, [8 x i8] } { i64 8, [8 x i8] c"readlist" }, align 8
+@str8 = private unnamed_addr constant { i64, [7 x i8] } { i64 7, [7 x i8] c"ppppppp" }, align 8
+@str9 = private unnamed_addr constant { i64, [5 x i8] } { i64 5, [5 x i8] c"merge" }, align 8
+@str10 = private unnamed_addr constant { i64, [23 x i8] } { i64 23, [23 x i8] c"ppppppppppppppppppppppp" }, align 8
+@str11 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"-" }, align 8
+@str12 = private unnamed_addr constant { i64, [8 x i8] } { i64 8, [8 x i8] c"printint" }, align 8
+@str13 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"\0A" }, align 8
+@str14 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c" " }, align 8
+@str15 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"printlist" }, align 8
+@str16 = private unnamed_addr constant { i64, [5 x i8] } { i64 5, [5 x i8] c"ppppp" }, align 8
+@str17 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"9" }, align 8
+@str18 = private unnamed_addr constant { i64, [7 x i8] } { i64 7, [7 x i8] c"isdigit" }, align 8
+@str19 = private unnamed_addr constant { i64, [3 x i8] } { i64 3, [3 x i8] c"ppp" }, align 8
+@str20 = private unnamed_addr constant { i64, [6 x i8] } { i64 6, [6 x i8] c"skipto" }, align 8
+@str21 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"f" }, align 8
+@str22 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"p" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr, i64 }
+%tigermain.roots = type { %tig_frame, i64, i64, i64, i64, i64, i64, i64, i64, i64 }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %r = alloca %tigermain.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str0 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.descriptor
+  store %tigermain.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str2 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  %v1 = call i64 @tig_getchar()
+  %v2 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  store i64 %v1, ptr %v2
+  %v3 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  %v4 = load i64, ptr %v3
+  %v5 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  store i64 %v4, ptr %v5
+  %v6 = call i64 @readlist_L3(ptr %f)
+  %v7 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 4
+  store i64 %v6, ptr %v7
+  %v8 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 4
+  %v9 = load i64, ptr %v8
+  %v10 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  store i64 %v9, ptr %v10
+  %v11 = call i64 @tig_getchar()
+  %v12 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 5
+  store i64 %v11, ptr %v12
+  %v13 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 5
+  %v14 = load i64, ptr %v13
+  %v15 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  store i64 %v14, ptr %v15
+  %v16 = call i64 @readlist_L3(ptr %f)
+  %v17 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 6
+  store i64 %v16, ptr %v17
+  %v18 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 6
+  %v19 = load i64, ptr %v18
+  %v20 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  store i64 %v19, ptr %v20
+  %v21 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  %v22 = load i64, ptr %v21
+  %v23 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 7
+  store i64 %v22, ptr %v23
+  %v24 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  %v25 = load i64, ptr %v24
+  %v26 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 8
+  store i64 %v25, ptr %v26
+  %v27 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 7
+  %v28 = load i64, ptr %v27
+  %v29 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 8
+  %v30 = load i64, ptr %v29
+  %v31 = call i64 @merge_L4(ptr %f, i64 %v28, i64 %v30)
+  %v32 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 9
+  store i64 %v31, ptr %v32
+  %v33 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 9
+  %v34 = load i64, ptr %v33
+  call i64 @printlist_L6(ptr %f, i64 %v34)
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
+
+%readint_L0.frame = type { %tig_frame, ptr }
+%readint_L0.roots = type { %tig_frame, i64, i64, i64, i64, i64, i64 }
+
+define i64 @readint_L0(ptr %link, i64 %any_1) {
+entry:
+  %f = alloca %readint_L0.frame
+  %r = alloca %readint_L0.roots
+  %i_2.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %readint_L0.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %readint_L0.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %readint_L0.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str4 to i64), ptr %header.name
+  %header.descriptor = getelementptr %readint_L0.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %header.descriptor
+  store %readint_L0.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str6 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %readint_L0.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  %any_1.slot = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 1
+  store i64 %any_1, ptr %any_1.slot
+  store ptr %r, ptr @tig_c_frames
+  store i64 0, ptr %i_2.addr
+  call i64 @skipto_L2(ptr %f)
+  %v1 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 1
+  %v2 = load i64, ptr %v1
+  %v3 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 2
+  store i64 %v2, ptr %v3
+  %v4 = getelementptr %readint_L0.frame, ptr %f, i32 0, i32 1
+  %v5 = load ptr, ptr %v4
+  %v6 = getelementptr %tigermain.frame, ptr %v5, i32 0, i32 2
+  %v7 = load i64, ptr %v6
+  %v8 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 3
+  store i64 %v7, ptr %v8
+  %v9 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 3
+  %v10 = load i64, ptr %v9
+  %v11 = call i64 @isdigit_L1(ptr %f, i64 %v10)
+  %v12 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 2
+  %v13 = load i64, ptr %v12
+  %v14 = icmp eq i64 %v13, 0
+  br i1 %v14, label %b15, label %b16
+b15:
+  call void @tig_nilRecord(i64 917510)
+  unreachable
+b16:
+  %v17 = inttoptr i64 %v13 to ptr
+  %v18 = getelementptr i64, ptr %v17, i64 0
+  store i64 %v11, ptr %v18
+  br label %b19
+b19:
+  %v22 = getelementptr %readint_L0.frame, ptr %f, i32 0, i32 1
+  %v23 = load ptr, ptr %v22
+  %v24 = getelementptr %tigermain.frame, ptr %v23, i32 0, i32 2
+  %v25 = load i64, ptr %v24
+  %v26 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 4
+  store i64 %v25, ptr %v26
+  %v27 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 4
+  %v28 = load i64, ptr %v27
+  %v29 = call i64 @isdigit_L1(ptr %f, i64 %v28)
+  %v30 = icmp ne i64 %v29, 0
+  br i1 %v30, label %b20, label %b21
+b20:
+  %v31 = load i64, ptr %i_2.addr
+  %v32 = mul i64 %v31, 10
+  %v33 = getelementptr %readint_L0.frame, ptr %f, i32 0, i32 1
+  %v34 = load ptr, ptr %v33
+  %v35 = getelementptr %tigermain.frame, ptr %v34, i32 0, i32 2
+  %v36 = load i64, ptr %v35
+  %v37 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 5
+  store i64 %v36, ptr %v37
+  %v38 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 5
+  %v39 = load i64, ptr %v38
+  %v40 = call i64 @tig_ord(i64 %v39)
+  %v41 = add i64 %v32, %v40
+  %v42 = call i64 @tig_ord(i64 ptrtoint (ptr @str3 to i64))
+  %v43 = sub i64 %v41, %v42
+  store i64 %v43, ptr %i_2.addr
+  %v44 = call i64 @tig_getchar()
+  %v45 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 6
+  store i64 %v44, ptr %v45
+  %v46 = getelementptr %readint_L0.roots, ptr %r, i32 0, i32 6
+  %v47 = load i64, ptr %v46
+  %v48 = getelementptr %readint_L0.frame, ptr %f, i32 0, i32 1
+  %v49 = load ptr, ptr %v48
+  %v50 = getelementptr %tigermain.frame, ptr %v49, i32 0, i32 2
+  store i64 %v47, ptr %v50
+  br label %b19
+b21:
+  %v51 = load i64, ptr %i_2.addr
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v51
+}
+
+%readlist_L3.frame = type { %tig_frame, ptr }
+%readlist_L3.roots = type { %tig_frame, i64, i64, i64, i64, i64, i64, i64 }
+
+define i64 @readlist_L3(ptr %link) {
+entry:
+  %f = alloca %readlist_L3.frame
+  %r = alloca %readlist_L3.roots
+  %i_5.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %readlist_L3.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %readlist_L3.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %readlist_L3.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str7 to i64), ptr %header.name
+  %header.descriptor = getelementptr %readlist_L3.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %header.descriptor
+  store %readlist_L3.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str8 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %readlist_L3.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  %v1 = call i64 @tig_allocRecord(i64 8, i64 ptrtoint (ptr @str5 to i64))
+  %v2 = inttoptr i64 %v1 to ptr
+  %v3 = getelementptr i64, ptr %v2, i64 0
+  store i64 0, ptr %v3
+  %v4 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 2
+  store i64 %v1, ptr %v4
+  %v5 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 2
+  %v6 = load i64, ptr %v5
+  %v7 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 1
+  store i64 %v6, ptr %v7
+  %v8 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 1
+  %v9 = load i64, ptr %v8
+  %v10 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 3
+  store i64 %v9, ptr %v10
+  %v11 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 3
+  %v12 = load i64, ptr %v11
+  %v13 = getelementptr %readlist_L3.frame, ptr %f, i32 0, i32 1
+  %v14 = load ptr, ptr %v13
+  %v15 = call i64 @readint_L0(ptr %v14, i64 %v12)
+  store i64 %v15, ptr %i_5.addr
+  %v16 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 1
+  %v17 = load i64, ptr %v16
+  %v18 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 4
+  store i64 %v17, ptr %v18
+  %v19 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 4
+  %v20 = load i64, ptr %v19
+  %v21 = icmp eq i64 %v20, 0
+  br i1 %v21, label %b22, label %b23
+b22:
+  call void @tig_nilRecord(i64 1638412)
+  unreachable
+b23:
+  %v24 = inttoptr i64 %v20 to ptr
+  %v25 = getelementptr i64, ptr %v24, i64 0
+  %v26 = load i64, ptr %v25
+  %v27 = icmp ne i64 %v26, 0
+  br i1 %v27, label %b28, label %b29
+b28:
+  %v31 = load i64, ptr %i_5.addr
+  %v32 = getelementptr %readlist_L3.frame, ptr %f, i32 0, i32 1
+  %v33 = load ptr, ptr %v32
+  %v34 = call i64 @readlist_L3(ptr %v33)
+  %v35 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 6
+  store i64 %v34, ptr %v35
+  %v36 = call i64 @tig_allocRecord(i64 16, i64 ptrtoint (ptr @str1 to i64))
+  %v37 = inttoptr i64 %v36 to ptr
+  %v38 = getelementptr i64, ptr %v37, i64 0
+  store i64 %v31, ptr %v38
+  %v39 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 6
+  %v40 = load i64, ptr %v39
+  %v41 = getelementptr i64, ptr %v37, i64 1
+  store i64 %v40, ptr %v41
+  %v42 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 7
+  store i64 %v36, ptr %v42
+  %v43 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 7
+  %v44 = load i64, ptr %v43
+  %v45 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 5
+  store i64 %v44, ptr %v45
+  br label %b30
+b29:
+  %v46 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 5
+  store i64 0, ptr %v46
+  br label %b30
+b30:
+  %v47 = getelementptr %readlist_L3.roots, ptr %r, i32 0, i32 5
+  %v48 = load i64, ptr %v47
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v48
+}
+
+%merge_L4.frame = type { %tig_frame, ptr }
+%merge_L4.roots = type { %tig_frame, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64 }
+
+define i64 @merge_L4(ptr %link, i64 %a_6, i64 %b_7) {
+entry:
+  %f = alloca %merge_L4.frame
+  %r = alloca %merge_L4.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %merge_L4.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %merge_L4.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %merge_L4.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str9 to i64), ptr %header.name
+  %header.descriptor = getelementptr %merge_L4.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %header.descriptor
+  store %merge_L4.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str10 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %merge_L4.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  %a_6.slot = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 1
+  store i64 %a_6, ptr %a_6.slot
+  %b_7.slot = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 2
+  store i64 %b_7, ptr %b_7.slot
+  store ptr %r, ptr @tig_c_frames
+  %v1 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 1
+  %v2 = load i64, ptr %v1
+  %v3 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 3
+  store i64 %v2, ptr %v3
+  %v4 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 3
+  %v5 = load i64, ptr %v4
+  %v6 = icmp eq i64 %v5, 0
+  %v7 = zext i1 %v6 to i64
+  %v8 = icmp ne i64 %v7, 0
+  br i1 %v8, label %b9, label %b10
+b9:
+  %v12 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 2
+  %v13 = load i64, ptr %v12
+  %v14 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 5
+  store i64 %v13, ptr %v14
+  %v15 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 5
+  %v16 = load i64, ptr %v15
+  %v17 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 4
+  store i64 %v16, ptr %v17
+  br label %b11
+b10:
+  %v18 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 2
+  %v19 = load i64, ptr %v18
+  %v20 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 6
+  store i64 %v19, ptr %v20
+  %v21 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 6
+  %v22 = load i64, ptr %v21
+  %v23 = icmp eq i64 %v22, 0
+  %v24 = zext i1 %v23 to i64
+  %v25 = icmp ne i64 %v24, 0
+  br i1 %v25, label %b26, label %b27
+b26:
+  %v29 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 1
+  %v30 = load i64, ptr %v29
+  %v31 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 8
+  store i64 %v30, ptr %v31
+  %v32 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 8
+  %v33 = load i64, ptr %v32
+  %v34 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 7
+  store i64 %v33, ptr %v34
+  br label %b28
+b27:
+  %v35 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 1
+  %v36 = load i64, ptr %v35
+  %v37 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 9
+  store i64 %v36, ptr %v37
+  %v38 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 9
+  %v39 = load i64, ptr %v38
+  %v40 = icmp eq i64 %v39, 0
+  br i1 %v40, label %b41, label %b42
+b41:
+  call void @tig_nilRecord(i64 2162700)
+  unreachable
+b42:
+  %v43 = inttoptr i64 %v39 to ptr
+  %v44 = getelementptr i64, ptr %v43, i64 0
+  %v45 = load i64, ptr %v44
+  %v46 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 2
+  %v47 = load i64, ptr %v46
+  %v48 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 10
+  store i64 %v47, ptr %v48
+  %v49 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 10
+  %v50 = load i64, ptr %v49
+  %v51 = icmp eq i64 %v50, 0
+  br i1 %v51, label %b52, label %b53
+b52:
+  call void @tig_nilRecord(i64 2162710)
+  unreachable
+b53:
+  %v54 = inttoptr i64 %v50 to ptr
+  %v55 = getelementptr i64, ptr %v54, i64 0
+  %v56 = load i64, ptr %v55
+  %v57 = icmp slt i64 %v45, %v56
+  %v58 = zext i1 %v57 to i64
+  %v59 = icmp ne i64 %v58, 0
+  br i1 %v59, label %b60, label %b61
+b60:
+  %v63 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 1
+  %v64 = load i64, ptr %v63
+  %v65 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 12
+  store i64 %v64, ptr %v65
+  %v66 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 12
+  %v67 = load i64, ptr %v66
+  %v68 = icmp eq i64 %v67, 0
+  br i1 %v68, label %b69, label %b70
+b69:
+  call void @tig_nilRecord(i64 2228247)
+  unreachable
+b70:
+  %v71 = inttoptr i64 %v67 to ptr
+  %v72 = getelementptr i64, ptr %v71, i64 0
+  %v73 = load i64, ptr %v72
+  %v74 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 1
+  %v75 = load i64, ptr %v74
+  %v76 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 13
+  store i64 %v75, ptr %v76
+  %v77 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 13
+  %v78 = load i64, ptr %v77
+  %v79 = icmp eq i64 %v78, 0
+  br i1 %v79, label %b80, label %b81
+b80:
+  call void @tig_nilRecord(i64 2228266)
+  unreachable
+b81:
+  %v82 = inttoptr i64 %v78 to ptr
+  %v83 = getelementptr i64, ptr %v82, i64 1
+  %v84 = load i64, ptr %v83
+  %v85 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 14
+  store i64 %v84, ptr %v85
+  %v86 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 2
+  %v87 = load i64, ptr %v86
+  %v88 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 15
+  store i64 %v87, ptr %v88
+  %v89 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 14
+  %v90 = load i64, ptr %v89
+  %v91 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 15
+  %v92 = load i64, ptr %v91
+  %v93 = getelementptr %merge_L4.frame, ptr %f, i32 0, i32 1
+  %v94 = load ptr, ptr %v93
+  %v95 = call i64 @merge_L4(ptr %v94, i64 %v90, i64 %v92)
+  %v96 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 16
+  store i64 %v95, ptr %v96
+  %v97 = call i64 @tig_allocRecord(i64 16, i64 ptrtoint (ptr @str1 to i64))
+  %v98 = inttoptr i64 %v97 to ptr
+  %v99 = getelementptr i64, ptr %v98, i64 0
+  store i64 %v73, ptr %v99
+  %v100 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 16
+  %v101 = load i64, ptr %v100
+  %v102 = getelementptr i64, ptr %v98, i64 1
+  store i64 %v101, ptr %v102
+  %v103 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 17
+  store i64 %v97, ptr %v103
+  %v104 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 17
+  %v105 = load i64, ptr %v104
+  %v106 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 11
+  store i64 %v105, ptr %v106
+  br label %b62
+b61:
+  %v107 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 2
+  %v108 = load i64, ptr %v107
+  %v109 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 18
+  store i64 %v108, ptr %v109
+  %v110 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 18
+  %v111 = load i64, ptr %v110
+  %v112 = icmp eq i64 %v111, 0
+  br i1 %v112, label %b113, label %b114
+b113:
+  call void @tig_nilRecord(i64 2293783)
+  unreachable
+b114:
+  %v115 = inttoptr i64 %v111 to ptr
+  %v116 = getelementptr i64, ptr %v115, i64 0
+  %v117 = load i64, ptr %v116
+  %v118 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 1
+  %v119 = load i64, ptr %v118
+  %v120 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 19
+  store i64 %v119, ptr %v120
+  %v121 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 2
+  %v122 = load i64, ptr %v121
+  %v123 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 20
+  store i64 %v122, ptr %v123
+  %v124 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 20
+  %v125 = load i64, ptr %v124
+  %v126 = icmp eq i64 %v125, 0
+  br i1 %v126, label %b127, label %b128
+b127:
+  call void @tig_nilRecord(i64 2293804)
+  unreachable
+b128:
+  %v129 = inttoptr i64 %v125 to ptr
+  %v130 = getelementptr i64, ptr %v129, i64 1
+  %v131 = load i64, ptr %v130
+  %v132 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 21
+  store i64 %v131, ptr %v132
+  %v133 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 19
+  %v134 = load i64, ptr %v133
+  %v135 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 21
+  %v136 = load i64, ptr %v135
+  %v137 = getelementptr %merge_L4.frame, ptr %f, i32 0, i32 1
+  %v138 = load ptr, ptr %v137
+  %v139 = call i64 @merge_L4(ptr %v138, i64 %v134, i64 %v136)
+  %v140 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 22
+  store i64 %v139, ptr %v140
+  %v141 = call i64 @tig_allocRecord(i64 16, i64 ptrtoint (ptr @str1 to i64))
+  %v142 = inttoptr i64 %v141 to ptr
+  %v143 = getelementptr i64, ptr %v142, i64 0
+  store i64 %v117, ptr %v143
+  %v144 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 22
+  %v145 = load i64, ptr %v144
+  %v146 = getelementptr i64, ptr %v142, i64 1
+  store i64 %v145, ptr %v146
+  %v147 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 23
+  store i64 %v141, ptr %v147
+  %v148 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 23
+  %v149 = load i64, ptr %v148
+  %v150 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 11
+  store i64 %v149, ptr %v150
+  br label %b62
+b62:
+  %v151 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 11
+  %v152 = load i64, ptr %v151
+  %v153 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 7
+  store i64 %v152, ptr %v153
+  br label %b28
+b28:
+  %v154 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 7
+  %v155 = load i64, ptr %v154
+  %v156 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 4
+  store i64 %v155, ptr %v156
+  br label %b11
+b11:
+  %v157 = getelementptr %merge_L4.roots, ptr %r, i32 0, i32 4
+  %v158 = load i64, ptr %v157
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v158
+}
+
+%printint_L5.frame = type { %tig_frame, ptr }
+
+define i64 @printint_L5(ptr %link, i64 %i_8) {
+entry:
+  %f = alloca %printint_L5.frame
+  %i_8.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %printint_L5.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %printint_L5.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %printint_L5.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str12 to i64), ptr %header.name
+  %header.descriptor = getelementptr %printint_L5.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %header.descriptor
+  %f.link = getelementptr %printint_L5.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store i64 %i_8, ptr %i_8.addr
+  store ptr %f, ptr @tig_c_frames
+  %v1 = load i64, ptr %i_8.addr
+  %v2 = icmp slt i64 %v1, 0
+  %v3 = zext i1 %v2 to i64
+  %v4 = icmp ne i64 %v3, 0
+  br i1 %v4, label %b5, label %b6
+b5:
+  call void @tig_print(i64 ptrtoint (ptr @str11 to i64))
+  %v8 = load i64, ptr %i_8.addr
+  %v9 = sub i64 0, %v8
+  call i64 @f_L7(ptr %f, i64 %v9)
+  br label %b7
+b6:
+  %v10 = load i64, ptr %i_8.addr
+  %v11 = icmp sgt i64 %v10, 0
+  %v12 = zext i1 %v11 to i64
+  %v13 = icmp ne i64 %v12, 0
+  br i1 %v13, label %b14, label %b15
+b14:
+  %v17 = load i64, ptr %i_8.addr
+  call i64 @f_L7(ptr %f, i64 %v17)
+  br label %b16
+b15:
+  call void @tig_print(i64 ptrtoint (ptr @str3 to i64))
+  br label %b16
+b16:
+  br label %b7
+b7:
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
+
+%printlist_L6.frame = type { %tig_frame, ptr }
+%printlist_L6.roots = type { %tig_frame, i64, i64, i64, i64, i64 }
+
+define i64 @printlist_L6(ptr %link, i64 %l_10) {
+entry:
+  %f = alloca %printlist_L6.frame
+  %r = alloca %printlist_L6.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %printlist_L6.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %printlist_L6.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %printlist_L6.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str15 to i64), ptr %header.name
+  %header.descriptor = getelementptr %printlist_L6.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %header.descriptor
+  store %printlist_L6.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str16 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %printlist_L6.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  %l_10.slot = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 1
+  store i64 %l_10, ptr %l_10.slot
+  store ptr %r, ptr @tig_c_frames
+  %v1 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 1
+  %v2 = load i64, ptr %v1
+  %v3 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 2
+  store i64 %v2, ptr %v3
+  %v4 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 2
+  %v5 = load i64, ptr %v4
+  %v6 = icmp eq i64 %v5, 0
+  %v7 = zext i1 %v6 to i64
+  %v8 = icmp ne i64 %v7, 0
+  br i1 %v8, label %b9, label %b10
+b9:
+  call void @tig_print(i64 ptrtoint (ptr @str13 to i64))
+  br label %b11
+b10:
+  %v12 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 1
+  %v13 = load i64, ptr %v12
+  %v14 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 3
+  store i64 %v13, ptr %v14
+  %v15 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 3
+  %v16 = load i64, ptr %v15
+  %v17 = icmp eq i64 %v16, 0
+  br i1 %v17, label %b18, label %b19
+b18:
+  call void @tig_nilRecord(i64 3080211)
+  unreachable
+b19:
+  %v20 = inttoptr i64 %v16 to ptr
+  %v21 = getelementptr i64, ptr %v20, i64 0
+  %v22 = load i64, ptr %v21
+  %v23 = getelementptr %printlist_L6.frame, ptr %f, i32 0, i32 1
+  %v24 = load ptr, ptr %v23
+  call i64 @printint_L5(ptr %v24, i64 %v22)
+  call void @tig_print(i64 ptrtoint (ptr @str14 to i64))
+  %v25 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 1
+  %v26 = load i64, ptr %v25
+  %v27 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 4
+  store i64 %v26, ptr %v27
+  %v28 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 4
+  %v29 = load i64, ptr %v28
+  %v30 = icmp eq i64 %v29, 0
+  br i1 %v30, label %b31, label %b32
+b31:
+  call void @tig_nilRecord(i64 3080243)
+  unreachable
+b32:
+  %v33 = inttoptr i64 %v29 to ptr
+  %v34 = getelementptr i64, ptr %v33, i64 1
+  %v35 = load i64, ptr %v34
+  %v36 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 5
+  store i64 %v35, ptr %v36
+  %v37 = getelementptr %printlist_L6.roots, ptr %r, i32 0, i32 5
+  %v38 = load i64, ptr %v37
+  %v39 = getelementptr %printlist_L6.frame, ptr %f, i32 0, i32 1
+  %v40 = load ptr, ptr %v39
+  call i64 @printlist_L6(ptr %v40, i64 %v38)
+  br label %b11
+b11:
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
+
+%isdigit_L1.frame = type { %tig_frame, ptr }
+%isdigit_L1.roots = type { %tig_frame, i64, i64, i64 }
+
+define i64 @isdigit_L1(ptr %link, i64 %s_3) {
+entry:
+  %f = alloca %isdigit_L1.frame
+  %r = alloca %isdigit_L1.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %isdigit_L1.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %isdigit_L1.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %isdigit_L1.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str18 to i64), ptr %header.name
+  %header.descriptor = getelementptr %isdigit_L1.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %header.descriptor
+  store %isdigit_L1.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %isdigit_L1.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %isdigit_L1.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %isdigit_L1.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str19 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %isdigit_L1.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  %s_3.slot = getelementptr %isdigit_L1.roots, ptr %r, i32 0, i32 1
+  store i64 %s_3, ptr %s_3.slot
+  store ptr %r, ptr @tig_c_frames
+  %v1 = getelementptr %isdigit_L1.frame, ptr %f, i32 0, i32 1
+  %v2 = load ptr, ptr %v1
+  %v3 = getelementptr %readint_L0.frame, ptr %v2, i32 0, i32 1
+  %v4 = load ptr, ptr %v3
+  %v5 = getelementptr %tigermain.frame, ptr %v4, i32 0, i32 2
+  %v6 = load i64, ptr %v5
+  %v7 = getelementptr %isdigit_L1.roots, ptr %r, i32 0, i32 2
+  store i64 %v6, ptr %v7
+  %v8 = getelementptr %isdigit_L1.roots, ptr %r, i32 0, i32 2
+  %v9 = load i64, ptr %v8
+  %v10 = call i64 @tig_ord(i64 %v9)
+  %v11 = call i64 @tig_ord(i64 ptrtoint (ptr @str3 to i64))
+  %v12 = icmp sge i64 %v10, %v11
+  %v13 = zext i1 %v12 to i64
+  %v14 = icmp ne i64 %v13, 0
+  br i1 %v14, label %b15, label %b16
+b15:
+  %v17 = getelementptr %isdigit_L1.frame, ptr %f, i32 0, i32 1
+  %v18 = load ptr, ptr %v17
+  %v19 = getelementptr %readint_L0.frame, ptr %v18, i32 0, i32 1
+  %v20 = load ptr, ptr %v19
+  %v21 = getelementptr %tigermain.frame, ptr %v20, i32 0, i32 2
+  %v22 = load i64, ptr %v21
+  %v23 = getelementptr %isdigit_L1.roots, ptr %r, i32 0, i32 3
+  store i64 %v22, ptr %v23
+  %v24 = getelementptr %isdigit_L1.roots, ptr %r, i32 0, i32 3
+  %v25 = load i64, ptr %v24
+  %v26 = call i64 @tig_ord(i64 %v25)
+  %v27 = call i64 @tig_ord(i64 ptrtoint (ptr @str17 to i64))
+  %v28 = icmp sle i64 %v26, %v27
+  %v29 = zext i1 %v28 to i64
+  %v30 = icmp ne i64 %v29, 0
+  %v31 = zext i1 %v30 to i64
+  br label %b16
+b16:
+  %v32 = phi i64 [ 0, %entry ], [ %v31, %b15 ]
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v32
+}
+
+%skipto_L2.frame = type { %tig_frame, ptr }
+%skipto_L2.roots = type { %tig_frame, i64, i64, i64 }
+
+define i64 @skipto_L2(ptr %link) {
+entry:
+  %f = alloca %skipto_L2.frame
+  %r = alloca %skipto_L2.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %skipto_L2.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %skipto_L2.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %skipto_L2.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str20 to i64), ptr %header.name
+  %header.descriptor = getelementptr %skipto_L2.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %header.descriptor
+  store %skipto_L2.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str19 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %skipto_L2.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  br label %b1
+b1:
+  %v4 = getelementptr %skipto_L2.frame, ptr %f, i32 0, i32 1
+  %v5 = load ptr, ptr %v4
+  %v6 = getelementptr %readint_L0.frame, ptr %v5, i32 0, i32 1
+  %v7 = load ptr, ptr %v6
+  %v8 = getelementptr %tigermain.frame, ptr %v7, i32 0, i32 2
+  %v9 = load i64, ptr %v8
+  %v10 = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 1
+  store i64 %v9, ptr %v10
+  %v11 = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 1
+  %v12 = load i64, ptr %v11
+  %v14 = call i64 @tig_stringEqual(i64 %v12, i64 ptrtoint (ptr @str14 to i64))
+  %v13 = icmp ne i64 %v14, 0
+  %v15 = zext i1 %v13 to i64
+  %v16 = icmp ne i64 %v15, 0
+  br i1 %v16, label %b18, label %b17
+b17:
+  %v19 = getelementptr %skipto_L2.frame, ptr %f, i32 0, i32 1
+  %v20 = load ptr, ptr %v19
+  %v21 = getelementptr %readint_L0.frame, ptr %v20, i32 0, i32 1
+  %v22 = load ptr, ptr %v21
+  %v23 = getelementptr %tigermain.frame, ptr %v22, i32 0, i32 2
+  %v24 = load i64, ptr %v23
+  %v25 = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 2
+  store i64 %v24, ptr %v25
+  %v26 = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 2
+  %v27 = load i64, ptr %v26
+  %v29 = call i64 @tig_stringEqual(i64 %v27, i64 ptrtoint (ptr @str13 to i64))
+  %v28 = icmp ne i64 %v29, 0
+  %v30 = zext i1 %v28 to i64
+  %v31 = icmp ne i64 %v30, 0
+  %v32 = zext i1 %v31 to i64
+  br label %b18
+b18:
+  %v33 = phi i64 [ 1, %b1 ], [ %v32, %b17 ]
+  %v34 = icmp ne i64 %v33, 0
+  br i1 %v34, label %b2, label %b3
+b2:
+  %v35 = call i64 @tig_getchar()
+  %v36 = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 3
+  store i64 %v35, ptr %v36
+  %v37 = getelementptr %skipto_L2.roots, ptr %r, i32 0, i32 3
+  %v38 = load i64, ptr %v37
+  %v39 = getelementptr %skipto_L2.frame, ptr %f, i32 0, i32 1
+  %v40 = load ptr, ptr %v39
+  %v41 = getelementptr %readint_L0.frame, ptr %v40, i32 0, i32 1
+  %v42 = load ptr, ptr %v41
+  %v43 = getelementptr %tigermain.frame, ptr %v42, i32 0, i32 2
+  store i64 %v38, ptr %v43
+  br label %b1
+b3:
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
+
+%f_L7.frame = type { %tig_frame, ptr }
+%f_L7.roots = type { %tig_frame, i64 }
+
+define i64 @f_L7(ptr %link, i64 %i_9) {
+entry:
+  %f = alloca %f_L7.frame
+  %r = alloca %f_L7.roots
+  %i_9.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %f_L7.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %f_L7.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %f_L7.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str21 to i64), ptr %header.name
+  %header.descriptor = getelementptr %f_L7.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %header.descriptor
+  store %f_L7.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %f_L7.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %f_L7.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %f_L7.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str22 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %f_L7.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store i64 %i_9, ptr %i_9.addr
+  store ptr %r, ptr @tig_c_frames
+  %v1 = load i64, ptr %i_9.addr
+  %v2 = icmp sgt i64 %v1, 0
+  %v3 = zext i1 %v2 to i64
+  %v4 = icmp ne i64 %v3, 0
+  br i1 %v4, label %b5, label %b7
+b5:
+  %v8 = load i64, ptr %i_9.addr
+  %v10 = icmp eq i64 10, 0
+  br i1 %v10, label %b11, label %b12
+b11:
+  call void @tig_divisionByZero(i64 2555919)
+  unreachable
+b12:
+  %v13 = icmp eq i64 10, -1
+  %v14 = select i1 %v13, i64 1, i64 10
+  %v15 = sdiv i64 %v8, %v14
+  %v16 = sub i64 0, %v8
+  %v9 = select i1 %v13, i64 %v16, i64 %v15
+  %v17 = getelementptr %f_L7.frame, ptr %f, i32 0, i32 1
+  %v18 = load ptr, ptr %v17
+  call i64 @f_L7(ptr %v18, i64 %v9)
+  %v19 = load i64, ptr %i_9.addr
+  %v20 = load i64, ptr %i_9.addr
+  %v22 = icmp eq i64 10, 0
+  br i1 %v22, label %b23, label %b24
+b23:
+  call void @tig_divisionByZero(i64 2555938)
+  unreachable
+b24:
+  %v25 = icmp eq i64 10, -1
+  %v26 = select i1 %v25, i64 1, i64 10
+  %v27 = sdiv i64 %v20, %v26
+  %v28 = sub i64 0, %v20
+  %v21 = select i1 %v25, i64 %v28, i64 %v27
+  %v29 = mul i64 %v21, 10
+  %v30 = sub i64 %v19, %v29
+  %v31 = call i64 @tig_ord(i64 ptrtoint (ptr @str3 to i64))
+  %v32 = add i64 %v30, %v31
+  %v33 = call i64 @tig_chr(i64 %v32, i64 2555932)
+  %v34 = getelementptr %f_L7.roots, ptr %r, i32 0, i32 1
+  store i64 %v33, ptr %v34
+  %v35 = getelementptr %f_L7.roots, ptr %r, i32 0, i32 1
+  %v36 = load i64, ptr %v35
+  call void @tig_print(i64 %v36)
+  br label %b7
+b7:
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
@@ -0,0 +1,533 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str1 = private unnamed_addr constant { i64, [6 x i8] } { i64 6, [6 x i8] c"nnpppp" }, align 8
+@str2 = private unnamed_addr constant { i64, [4 x i8] } { i64 4, [4 x i8] c"pppp" }, align 8
+@str3 = private unnamed_addr constant { i64, [2 x i8] } { i64 2, [2 x i8] c" O" }, align 8
+@str4 = private unnamed_addr constant { i64, [2 x i8] } { i64 2, [2 x i8] c" ." }, align 8
+@str5 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"\0A" }, align 8
+@str6 = private unnamed_addr constant { i64, [10 x i8] } { i64 10, [10 x i8] c"printboard" }, align 8
+@str7 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str8 = private unnamed_addr constant { i64, [2 x i8] } { i64 2, [2 x i8] c"pp" }, align 8
+@str9 = private unnamed_addr constant { i64, [3 x i8] } { i64 3, [3 x i8] c"try" }, align 8
+@str10 = private unnamed_addr constant { i64, [10 x i8] } { i64 10, [10 x i8] c"pppppppppp" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr, i64, i64, i64, i64, i64 }
+%tigermain.roots = type { %tig_frame, i64, i64, i64, i64 }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %r = alloca %tigermain.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str0 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.descriptor
+  store %tigermain.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str2 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  %v1 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  store i64 8, ptr %v1
+  %v2 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  %v3 = load i64, ptr %v2
+  %v4 = call i64 @tig_initArray(i64 %v3, i64 0, i64 0, i64 524304)
+  %v5 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  store i64 %v4, ptr %v5
+  %v6 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  %v7 = load i64, ptr %v6
+  %v8 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 3
+  store i64 %v7, ptr %v8
+  %v9 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  %v10 = load i64, ptr %v9
+  %v11 = call i64 @tig_initArray(i64 %v10, i64 0, i64 0, i64 589840)
+  %v12 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  store i64 %v11, ptr %v12
+  %v13 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  %v14 = load i64, ptr %v13
+  %v15 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 4
+  store i64 %v14, ptr %v15
+  %v16 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  %v17 = load i64, ptr %v16
+  %v18 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  %v19 = load i64, ptr %v18
+  %v20 = add i64 %v17, %v19
+  %v21 = sub i64 %v20, 1
+  %v22 = call i64 @tig_initArray(i64 %v21, i64 0, i64 0, i64 655378)
+  %v23 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  store i64 %v22, ptr %v23
+  %v24 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  %v25 = load i64, ptr %v24
+  %v26 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 5
+  store i64 %v25, ptr %v26
+  %v27 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  %v28 = load i64, ptr %v27
+  %v29 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 2
+  %v30 = load i64, ptr %v29
+  %v31 = add i64 %v28, %v30
+  %v32 = sub i64 %v31, 1
+  %v33 = call i64 @tig_initArray(i64 %v32, i64 0, i64 0, i64 720914)
+  %v34 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 4
+  store i64 %v33, ptr %v34
+  %v35 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 4
+  %v36 = load i64, ptr %v35
+  %v37 = getelementptr %tigermain.frame, ptr %f, i32 0, i32 6
+  store i64 %v36, ptr %v37
+  call i64 @try_L1(ptr %f, i64 0)
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
+
+%printboard_L0.frame = type { %tig_frame, ptr }
+%printboard_L0.roots = type { %tig_frame, i64, i64 }
+
+define i64 @printboard_L0(ptr %link) {
+entry:
+  %f = alloca %printboard_L0.frame
+  %r = alloca %printboard_L0.roots
+  %i_5.addr = alloca i64
+  %j_6.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %printboard_L0.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %printboard_L0.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %printboard_L0.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str6 to i64), ptr %header.name
+  %header.descriptor = getelementptr %printboard_L0.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str7 to i64), ptr %header.descriptor
+  store %printboard_L0.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %printboard_L0.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %printboard_L0.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %printboard_L0.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str8 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %printboard_L0.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  store i64 0, ptr %i_5.addr
+  %v1 = getelementptr %printboard_L0.frame, ptr %f, i32 0, i32 1
+  %v2 = load ptr, ptr %v1
+  %v3 = getelementptr %tigermain.frame, ptr %v2, i32 0, i32 2
+  %v4 = load i64, ptr %v3
+  %v5 = sub i64 %v4, 1
+  %v9 = load i64, ptr %i_5.addr
+  %v10 = icmp sle i64 %v9, %v5
+  br i1 %v10, label %b6, label %b8
+b6:
+  store i64 0, ptr %j_6.addr
+  %v11 = getelementptr %printboard_L0.frame, ptr %f, i32 0, i32 1
+  %v12 = load ptr, ptr %v11
+  %v13 = getelementptr %tigermain.frame, ptr %v12, i32 0, i32 2
+  %v14 = load i64, ptr %v13
+  %v15 = sub i64 %v14, 1
+  %v19 = load i64, ptr %j_6.addr
+  %v20 = icmp sle i64 %v19, %v15
+  br i1 %v20, label %b16, label %b18
+b16:
+  %v21 = getelementptr %printboard_L0.frame, ptr %f, i32 0, i32 1
+  %v22 = load ptr, ptr %v21
+  %v23 = getelementptr %tigermain.frame, ptr %v22, i32 0, i32 4
+  %v24 = load i64, ptr %v23
+  %v25 = getelementptr %printboard_L0.roots, ptr %r, i32 0, i32 1
+  store i64 %v24, ptr %v25
+  %v26 = load i64, ptr %i_5.addr
+  %v27 = getelementptr %printboard_L0.roots, ptr %r, i32 0, i32 1
+  %v28 = load i64, ptr %v27
+  %v29 = inttoptr i64 %v28 to ptr
+  %v30 = load i64, ptr %v29
+  %v31 = icmp uge i64 %v26, %v30
+  br i1 %v31, label %b32, label %b33
+b32:
+  call void @tig_indexOutOfRange(i64 %v26, i64 %v30, i64 1048600)
+  unreachable
+b33:
+  %v34 = add i64 %v26, 1
+  %v35 = getelementptr i64, ptr %v29, i64 %v34
+  %v36 = load i64, ptr %v35
+  %v37 = load i64, ptr %j_6.addr
+  %v38 = icmp eq i64 %v36, %v37
+  %v39 = zext i1 %v38 to i64
+  %v40 = icmp ne i64 %v39, 0
+  br i1 %v40, label %b41, label %b42
+b41:
+  %v44 = getelementptr %printboard_L0.roots, ptr %r, i32 0, i32 2
+  store i64 ptrtoint (ptr @str3 to i64), ptr %v44
+  br label %b43
+b42:
+  %v45 = getelementptr %printboard_L0.roots, ptr %r, i32 0, i32 2
+  store i64 ptrtoint (ptr @str4 to i64), ptr %v45
+  br label %b43
+b43:
+  %v46 = getelementptr %printboard_L0.roots, ptr %r, i32 0, i32 2
+  %v47 = load i64, ptr %v46
+  call void @tig_print(i64 %v47)
+  %v48 = load i64, ptr %j_6.addr
+  %v49 = icmp sge i64 %v48, %v15
+  br i1 %v49, label %b18, label %b17
+b17:
+  %v50 = add i64 %v48, 1
+  store i64 %v50, ptr %j_6.addr
+  br label %b16
+b18:
+  call void @tig_print(i64 ptrtoint (ptr @str5 to i64))
+  %v51 = load i64, ptr %i_5.addr
+  %v52 = icmp sge i64 %v51, %v5
+  br i1 %v52, label %b8, label %b7
+b7:
+  %v53 = add i64 %v51, 1
+  store i64 %v53, ptr %i_5.addr
+  br label %b6
+b8:
+  call void @tig_print(i64 ptrtoint (ptr @str5 to i64))
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
+
+%try_L1.frame = type { %tig_frame, ptr }
+%try_L1.roots = type { %tig_frame, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64 }
+
+define i64 @try_L1(ptr %link, i64 %c_7) {
+entry:
+  %f = alloca %try_L1.frame
+  %r = alloca %try_L1.roots
+  %c_7.addr = alloca i64
+  %r_8.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %try_L1.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %try_L1.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %try_L1.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str9 to i64), ptr %header.name
+  %header.descriptor = getelementptr %try_L1.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str7 to i64), ptr %header.descriptor
+  store %try_L1.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %try_L1.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %try_L1.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %try_L1.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str10 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store i64 %c_7, ptr %c_7.addr
+  store ptr %r, ptr @tig_c_frames
+  %v1 = load i64, ptr %c_7.addr
+  %v2 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v3 = load ptr, ptr %v2
+  %v4 = getelementptr %tigermain.frame, ptr %v3, i32 0, i32 2
+  %v5 = load i64, ptr %v4
+  %v6 = icmp eq i64 %v1, %v5
+  %v7 = zext i1 %v6 to i64
+  %v8 = icmp ne i64 %v7, 0
+  br i1 %v8, label %b9, label %b10
+b9:
+  %v12 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v13 = load ptr, ptr %v12
+  call i64 @printboard_L0(ptr %v13)
+  br label %b11
+b10:
+  store i64 0, ptr %r_8.addr
+  %v14 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v15 = load ptr, ptr %v14
+  %v16 = getelementptr %tigermain.frame, ptr %v15, i32 0, i32 2
+  %v17 = load i64, ptr %v16
+  %v18 = sub i64 %v17, 1
+  %v22 = load i64, ptr %r_8.addr
+  %v23 = icmp sle i64 %v22, %v18
+  br i1 %v23, label %b19, label %b21
+b19:
+  %v24 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v25 = load ptr, ptr %v24
+  %v26 = getelementptr %tigermain.frame, ptr %v25, i32 0, i32 3
+  %v27 = load i64, ptr %v26
+  %v28 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 1
+  store i64 %v27, ptr %v28
+  %v29 = load i64, ptr %r_8.addr
+  %v30 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 1
+  %v31 = load i64, ptr %v30
+  %v32 = inttoptr i64 %v31 to ptr
+  %v33 = load i64, ptr %v32
+  %v34 = icmp uge i64 %v29, %v33
+  br i1 %v34, label %b35, label %b36
+b35:
+  call void @tig_indexOutOfRange(i64 %v29, i64 %v33, i64 1638415)
+  unreachable
+b36:
+  %v37 = add i64 %v29, 1
+  %v38 = getelementptr i64, ptr %v32, i64 %v37
+  %v39 = load i64, ptr %v38
+  %v40 = icmp eq i64 %v39, 0
+  %v41 = zext i1 %v40 to i64
+  %v42 = icmp ne i64 %v41, 0
+  br i1 %v42, label %b43, label %b44
+b43:
+  %v45 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v46 = load ptr, ptr %v45
+  %v47 = getelementptr %tigermain.frame, ptr %v46, i32 0, i32 5
+  %v48 = load i64, ptr %v47
+  %v49 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 2
+  store i64 %v48, ptr %v49
+  %v50 = load i64, ptr %r_8.addr
+  %v51 = load i64, ptr %c_7.addr
+  %v52 = add i64 %v50, %v51
+  %v53 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 2
+  %v54 = load i64, ptr %v53
+  %v55 = inttoptr i64 %v54 to ptr
+  %v56 = load i64, ptr %v55
+  %v57 = icmp uge i64 %v52, %v56
+  br i1 %v57, label %b58, label %b59
+b58:
+  call void @tig_indexOutOfRange(i64 %v52, i64 %v56, i64 1638428)
+  unreachable
+b59:
+  %v60 = add i64 %v52, 1
+  %v61 = getelementptr i64, ptr %v55, i64 %v60
+  %v62 = load i64, ptr %v61
+  %v63 = icmp eq i64 %v62, 0
+  %v64 = zext i1 %v63 to i64
+  %v65 = icmp ne i64 %v64, 0
+  %v66 = zext i1 %v65 to i64
+  br label %b44
+b44:
+  %v67 = phi i64 [ 0, %b36 ], [ %v66, %b59 ]
+  %v68 = icmp ne i64 %v67, 0
+  br i1 %v68, label %b69, label %b70
+b69:
+  %v71 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v72 = load ptr, ptr %v71
+  %v73 = getelementptr %tigermain.frame, ptr %v72, i32 0, i32 6
+  %v74 = load i64, ptr %v73
+  %v75 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 3
+  store i64 %v74, ptr %v75
+  %v76 = load i64, ptr %r_8.addr
+  %v77 = add i64 %v76, 7
+  %v78 = load i64, ptr %c_7.addr
+  %v79 = sub i64 %v77, %v78
+  %v80 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 3
+  %v81 = load i64, ptr %v80
+  %v82 = inttoptr i64 %v81 to ptr
+  %v83 = load i64, ptr %v82
+  %v84 = icmp uge i64 %v79, %v83
+  br i1 %v84, label %b85, label %b86
+b85:
+  call void @tig_indexOutOfRange(i64 %v79, i64 %v83, i64 1638443)
+  unreachable
+b86:
+  %v87 = add i64 %v79, 1
+  %v88 = getelementptr i64, ptr %v82, i64 %v87
+  %v89 = load i64, ptr %v88
+  %v90 = icmp eq i64 %v89, 0
+  %v91 = zext i1 %v90 to i64
+  %v92 = icmp ne i64 %v91, 0
+  %v93 = zext i1 %v92 to i64
+  br label %b70
+b70:
+  %v94 = phi i64 [ 0, %b44 ], [ %v93, %b86 ]
+  %v95 = icmp ne i64 %v94, 0
+  br i1 %v95, label %b96, label %b98
+b96:
+  %v99 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v100 = load ptr, ptr %v99
+  %v101 = getelementptr %tigermain.frame, ptr %v100, i32 0, i32 3
+  %v102 = load i64, ptr %v101
+  %v103 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 4
+  store i64 %v102, ptr %v103
+  %v104 = load i64, ptr %r_8.addr
+  %v105 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 4
+  %v106 = load i64, ptr %v105
+  %v107 = inttoptr i64 %v106 to ptr
+  %v108 = load i64, ptr %v107
+  %v109 = icmp uge i64 %v104, %v108
+  br i1 %v109, label %b110, label %b111
+b110:
+  call void @tig_indexOutOfRange(i64 %v104, i64 %v108, i64 1703959)
+  unreachable
+b111:
+  %v112 = add i64 %v104, 1
+  %v113 = getelementptr i64, ptr %v107, i64 %v112
+  store i64 1, ptr %v113
+  %v114 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v115 = load ptr, ptr %v114
+  %v116 = getelementptr %tigermain.frame, ptr %v115, i32 0, i32 5
+  %v117 = load i64, ptr %v116
+  %v118 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 5
+  store i64 %v117, ptr %v118
+  %v119 = load i64, ptr %r_8.addr
+  %v120 = load i64, ptr %c_7.addr
+  %v121 = add i64 %v119, %v120
+  %v122 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 5
+  %v123 = load i64, ptr %v122
+  %v124 = inttoptr i64 %v123 to ptr
+  %v125 = load i64, ptr %v124
+  %v126 = icmp uge i64 %v121, %v125
+  br i1 %v126, label %b127, label %b128
+b127:
+  call void @tig_indexOutOfRange(i64 %v121, i64 %v125, i64 1703972)
+  unreachable
+b128:
+  %v129 = add i64 %v121, 1
+  %v130 = getelementptr i64, ptr %v124, i64 %v129
+  store i64 1, ptr %v130
+  %v131 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v132 = load ptr, ptr %v131
+  %v133 = getelementptr %tigermain.frame, ptr %v132, i32 0, i32 6
+  %v134 = load i64, ptr %v133
+  %v135 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 6
+  store i64 %v134, ptr %v135
+  %v136 = load i64, ptr %r_8.addr
+  %v137 = add i64 %v136, 7
+  %v138 = load i64, ptr %c_7.addr
+  %v139 = sub i64 %v137, %v138
+  %v140 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 6
+  %v141 = load i64, ptr %v140
+  %v142 = inttoptr i64 %v141 to ptr
+  %v143 = load i64, ptr %v142
+  %v144 = icmp uge i64 %v139, %v143
+  br i1 %v144, label %b145, label %b146
+b145:
+  call void @tig_indexOutOfRange(i64 %v139, i64 %v143, i64 1703987)
+  unreachable
+b146:
+  %v147 = add i64 %v139, 1
+  %v148 = getelementptr i64, ptr %v142, i64 %v147
+  store i64 1, ptr %v148
+  %v149 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v150 = load ptr, ptr %v149
+  %v151 = getelementptr %tigermain.frame, ptr %v150, i32 0, i32 4
+  %v152 = load i64, ptr %v151
+  %v153 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 7
+  store i64 %v152, ptr %v153
+  %v154 = load i64, ptr %c_7.addr
+  %v155 = load i64, ptr %r_8.addr
+  %v156 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 7
+  %v157 = load i64, ptr %v156
+  %v158 = inttoptr i64 %v157 to ptr
+  %v159 = load i64, ptr %v158
+  %v160 = icmp uge i64 %v154, %v159
+  br i1 %v160, label %b161, label %b162
+b161:
+  call void @tig_indexOutOfRange(i64 %v154, i64 %v159, i64 1769488)
+  unreachable
+b162:
+  %v163 = add i64 %v154, 1
+  %v164 = getelementptr i64, ptr %v158, i64 %v163
+  store i64 %v155, ptr %v164
+  %v165 = load i64, ptr %c_7.addr
+  %v166 = add i64 %v165, 1
+  %v167 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v168 = load ptr, ptr %v167
+  call i64 @try_L1(ptr %v168, i64 %v166)
+  %v169 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v170 = load ptr, ptr %v169
+  %v171 = getelementptr %tigermain.frame, ptr %v170, i32 0, i32 3
+  %v172 = load i64, ptr %v171
+  %v173 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 8
+  store i64 %v172, ptr %v173
+  %v174 = load i64, ptr %r_8.addr
+  %v175 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 8
+  %v176 = load i64, ptr %v175
+  %v177 = inttoptr i64 %v176 to ptr
+  %v178 = load i64, ptr %v177
+  %v179 = icmp uge i64 %v174, %v178
+  br i1 %v179, label %b180, label %b181
+b180:
+  call void @tig_indexOutOfRange(i64 %v174, i64 %v178, i64 1900553)
+  unreachable
+b181:
+  %v182 = add i64 %v174, 1
+  %v183 = getelementptr i64, ptr %v177, i64 %v182
+  store i64 0, ptr %v183
+  %v184 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v185 = load ptr, ptr %v184
+  %v186 = getelementptr %tigermain.frame, ptr %v185, i32 0, i32 5
+  %v187 = load i64, ptr %v186
+  %v188 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 9
+  store i64 %v187, ptr %v188
+  %v189 = load i64, ptr %r_8.addr
+  %v190 = load i64, ptr %c_7.addr
+  %v191 = add i64 %v189, %v190
+  %v192 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 9
+  %v193 = load i64, ptr %v192
+  %v194 = inttoptr i64 %v193 to ptr
+  %v195 = load i64, ptr %v194
+  %v196 = icmp uge i64 %v191, %v195
+  br i1 %v196, label %b197, label %b198
+b197:
+  call void @tig_indexOutOfRange(i64 %v191, i64 %v195, i64 1900566)
+  unreachable
+b198:
+  %v199 = add i64 %v191, 1
+  %v200 = getelementptr i64, ptr %v194, i64 %v199
+  store i64 0, ptr %v200
+  %v201 = getelementptr %try_L1.frame, ptr %f, i32 0, i32 1
+  %v202 = load ptr, ptr %v201
+  %v203 = getelementptr %tigermain.frame, ptr %v202, i32 0, i32 6
+  %v204 = load i64, ptr %v203
+  %v205 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 10
+  store i64 %v204, ptr %v205
+  %v206 = load i64, ptr %r_8.addr
+  %v207 = add i64 %v206, 7
+  %v208 = load i64, ptr %c_7.addr
+  %v209 = sub i64 %v207, %v208
+  %v210 = getelementptr %try_L1.roots, ptr %r, i32 0, i32 10
+  %v211 = load i64, ptr %v210
+  %v212 = inttoptr i64 %v211 to ptr
+  %v213 = load i64, ptr %v212
+  %v214 = icmp uge i64 %v209, %v213
+  br i1 %v214, label %b215, label %b216
+b215:
+  call void @tig_indexOutOfRange(i64 %v209, i64 %v213, i64 1900581)
+  unreachable
+b216:
+  %v217 = add i64 %v209, 1
+  %v218 = getelementptr i64, ptr %v212, i64 %v217
+  store i64 0, ptr %v218
+  br label %b98
+b98:
+  %v219 = load i64, ptr %r_8.addr
+  %v220 = icmp sge i64 %v219, %v18
+  br i1 %v220, label %b21, label %b20
+b20:
+  %v221 = add i64 %v219, 1
+  store i64 %v221, ptr %r_8.addr
+  br label %b19
+b21:
+  br label %b11
+b11:
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
@@ -0,0 +1,66 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str1 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %a_0.addr = alloca i64
+  %i_1.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str0 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %f, ptr @tig_c_frames
+  store i64 0, ptr %a_0.addr
+  store i64 0, ptr %i_1.addr
+  %v4 = load i64, ptr %i_1.addr
+  %v5 = icmp sle i64 %v4, 100
+  br i1 %v5, label %b1, label %b3
+b1:
+  %v6 = load i64, ptr %a_0.addr
+  %v7 = add i64 %v6, 1
+  store i64 %v7, ptr %a_0.addr
+  %v8 = load i64, ptr %i_1.addr
+  %v9 = icmp sge i64 %v8, 100
+  br i1 %v9, label %b3, label %b2
+b2:
+  %v10 = add i64 %v8, 1
+  store i64 %v10, ptr %i_1.addr
+  br label %b1
+b3:
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
@@ -0,0 +1,70 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str1 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str2 = private unnamed_addr constant { i64, [3 x i8] } { i64 3, [3 x i8] c"ppp" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr }
+%tigermain.roots = type { %tig_frame, i64, i64, i64 }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %r = alloca %tigermain.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str0 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.descriptor
+  store %tigermain.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str2 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  %v1 = call i64 @tig_initArray(i64 10, i64 0, i64 0, i64 393238)
+  %v2 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  store i64 %v1, ptr %v2
+  %v3 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  %v4 = load i64, ptr %v3
+  %v5 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  store i64 %v4, ptr %v5
+  %v6 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  %v7 = load i64, ptr %v6
+  %v8 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  store i64 %v7, ptr %v8
+  %v9 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  %v10 = load i64, ptr %v9
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v10
+}
@@ -0,0 +1,74 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str1 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str2 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"g" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %a_0.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str0 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %f, ptr @tig_c_frames
+  store i64 0, ptr %a_0.addr
+  %v1 = call i64 @g_L0(ptr %f, i64 2)
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v1
+}
+
+%g_L0.frame = type { %tig_frame, ptr }
+
+define i64 @g_L0(ptr %link, i64 %a_1) {
+entry:
+  %f = alloca %g_L0.frame
+  %a_1.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %g_L0.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %g_L0.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %g_L0.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str2 to i64), ptr %header.name
+  %header.descriptor = getelementptr %g_L0.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.descriptor
+  %f.link = getelementptr %g_L0.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store i64 %a_1, ptr %a_1.addr
+  store ptr %f, ptr @tig_c_frames
+  %v1 = load i64, ptr %a_1.addr
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v1
+}
@@ -0,0 +1,93 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [6 x i8] } { i64 6, [6 x i8] c"Nobody" }, align 8
+@str1 = private unnamed_addr constant { i64, [2 x i8] } { i64 2, [2 x i8] c"pn" }, align 8
+@str2 = private unnamed_addr constant { i64, [8 x i8] } { i64 8, [8 x i8] c"Somebody" }, align 8
+@str3 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str4 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str5 = private unnamed_addr constant { i64, [4 x i8] } { i64 4, [4 x i8] c"pppp" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr }
+%tigermain.roots = type { %tig_frame, i64, i64, i64, i64 }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %r = alloca %tigermain.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str3 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str4 to i64), ptr %header.descriptor
+  store %tigermain.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str5 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  %v1 = call i64 @tig_allocRecord(i64 16, i64 ptrtoint (ptr @str1 to i64))
+  %v2 = inttoptr i64 %v1 to ptr
+  %v3 = getelementptr i64, ptr %v2, i64 0
+  store i64 ptrtoint (ptr @str0 to i64), ptr %v3
+  %v4 = getelementptr i64, ptr %v2, i64 1
+  store i64 1000, ptr %v4
+  %v5 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  store i64 %v1, ptr %v5
+  %v6 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  %v7 = load i64, ptr %v6
+  %v8 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  store i64 %v7, ptr %v8
+  %v9 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  %v10 = load i64, ptr %v9
+  %v11 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  store i64 %v10, ptr %v11
+  %v12 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  %v13 = load i64, ptr %v12
+  %v14 = icmp eq i64 %v13, 0
+  br i1 %v14, label %b15, label %b16
+b15:
+  call void @tig_nilRecord(i64 393218)
+  unreachable
+b16:
+  %v17 = inttoptr i64 %v13 to ptr
+  %v18 = getelementptr i64, ptr %v17, i64 0
+  store i64 ptrtoint (ptr @str2 to i64), ptr %v18
+  %v19 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  %v20 = load i64, ptr %v19
+  %v21 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 4
+  store i64 %v20, ptr %v21
+  %v22 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 4
+  %v23 = load i64, ptr %v22
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v23
+}
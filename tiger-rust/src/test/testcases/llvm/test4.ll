@@ -0,0 +1,89 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str1 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str2 = private unnamed_addr constant { i64, [7 x i8] } { i64 7, [7 x i8] c"nfactor" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str0 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %f, ptr @tig_c_frames
+  %v1 = call i64 @nfactor_L0(ptr %f, i64 10)
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v1
+}
+
+%nfactor_L0.frame = type { %tig_frame, ptr }
+
+define i64 @nfactor_L0(ptr %link, i64 %n_0) {
+entry:
+  %f = alloca %nfactor_L0.frame
+  %n_0.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %nfactor_L0.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %nfactor_L0.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %nfactor_L0.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str2 to i64), ptr %header.name
+  %header.descriptor = getelementptr %nfactor_L0.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.descriptor
+  %f.link = getelementptr %nfactor_L0.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store i64 %n_0, ptr %n_0.addr
+  store ptr %f, ptr @tig_c_frames
+  %v1 = load i64, ptr %n_0.addr
+  %v2 = icmp eq i64 %v1, 0
+  %v3 = zext i1 %v2 to i64
+  %v4 = icmp ne i64 %v3, 0
+  br i1 %v4, label %b5, label %b6
+b5:
+  br label %b7
+b6:
+  %v8 = load i64, ptr %n_0.addr
+  %v9 = load i64, ptr %n_0.addr
+  %v10 = sub i64 %v9, 1
+  %v11 = getelementptr %nfactor_L0.frame, ptr %f, i32 0, i32 1
+  %v12 = load ptr, ptr %v11
+  %v13 = call i64 @nfactor_L0(ptr %v12, i64 %v10)
+  %v14 = mul i64 %v8, %v13
+  br label %b7
+b7:
+  %v15 = phi i64 [ 1, %b5 ], [ %v14, %b6 ]
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v15
+}
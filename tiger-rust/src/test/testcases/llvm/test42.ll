@@ -0,0 +1,322 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [5 x i8] } { i64 5, [5 x i8] c"aname" }, align 8
+@str1 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"somewhere" }, align 8
+@str2 = private unnamed_addr constant { i64, [4 x i8] } { i64 4, [4 x i8] c"ppnn" }, align 8
+@str3 = private unnamed_addr constant { i64, [0 x i8] } { i64 0, [0 x i8] c"" }, align 8
+@str4 = private unnamed_addr constant { i64, [7 x i8] } { i64 7, [7 x i8] c"Kapoios" }, align 8
+@str5 = private unnamed_addr constant { i64, [5 x i8] } { i64 5, [5 x i8] c"Kapou" }, align 8
+@str6 = private unnamed_addr constant { i64, [5 x i8] } { i64 5, [5 x i8] c"Allos" }, align 8
+@str7 = private unnamed_addr constant { i64, [2 x i8] } { i64 2, [2 x i8] c"pp" }, align 8
+@str8 = private unnamed_addr constant { i64, [4 x i8] } { i64 4, [4 x i8] c"kati" }, align 8
+@str9 = private unnamed_addr constant { i64, [3 x i8] } { i64 3, [3 x i8] c"sfd" }, align 8
+@str10 = private unnamed_addr constant { i64, [3 x i8] } { i64 3, [3 x i8] c"sdf" }, align 8
+@str11 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str12 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str13 = private unnamed_addr constant { i64, [24 x i8] } { i64 24, [24 x i8] c"pppppppppppppppppppppppp" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr }
+%tigermain.roots = type { %tig_frame, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64, i64 }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %r = alloca %tigermain.roots
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str11 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str12 to i64), ptr %header.descriptor
+  store %tigermain.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str13 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  %v1 = call i64 @tig_initArray(i64 10, i64 0, i64 0, i64 720909)
+  %v2 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 6
+  store i64 %v1, ptr %v2
+  %v3 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 6
+  %v4 = load i64, ptr %v3
+  %v5 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  store i64 %v4, ptr %v5
+  %v6 = call i64 @tig_allocRecord(i64 32, i64 ptrtoint (ptr @str2 to i64))
+  %v7 = inttoptr i64 %v6 to ptr
+  %v8 = getelementptr i64, ptr %v7, i64 0
+  store i64 ptrtoint (ptr @str0 to i64), ptr %v8
+  %v9 = getelementptr i64, ptr %v7, i64 1
+  store i64 ptrtoint (ptr @str1 to i64), ptr %v9
+  %v10 = getelementptr i64, ptr %v7, i64 2
+  store i64 0, ptr %v10
+  %v11 = getelementptr i64, ptr %v7, i64 3
+  store i64 0, ptr %v11
+  %v12 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 7
+  store i64 %v6, ptr %v12
+  %v13 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 7
+  %v14 = load i64, ptr %v13
+  %v15 = call i64 @tig_initArray(i64 5, i64 %v14, i64 1, i64 786446)
+  %v16 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 8
+  store i64 %v15, ptr %v16
+  %v17 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 8
+  %v18 = load i64, ptr %v17
+  %v19 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  store i64 %v18, ptr %v19
+  %v20 = call i64 @tig_initArray(i64 100, i64 ptrtoint (ptr @str3 to i64), i64 1, i64 851990)
+  %v21 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 9
+  store i64 %v20, ptr %v21
+  %v22 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 9
+  %v23 = load i64, ptr %v22
+  %v24 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  store i64 %v23, ptr %v24
+  %v25 = call i64 @tig_allocRecord(i64 32, i64 ptrtoint (ptr @str2 to i64))
+  %v26 = inttoptr i64 %v25 to ptr
+  %v27 = getelementptr i64, ptr %v26, i64 0
+  store i64 ptrtoint (ptr @str4 to i64), ptr %v27
+  %v28 = getelementptr i64, ptr %v26, i64 1
+  store i64 ptrtoint (ptr @str5 to i64), ptr %v28
+  %v29 = getelementptr i64, ptr %v26, i64 2
+  store i64 2432, ptr %v29
+  %v30 = getelementptr i64, ptr %v26, i64 3
+  store i64 44, ptr %v30
+  %v31 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 10
+  store i64 %v25, ptr %v31
+  %v32 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 10
+  %v33 = load i64, ptr %v32
+  %v34 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 4
+  store i64 %v33, ptr %v34
+  %v35 = call i64 @tig_initArray(i64 3, i64 1900, i64 0, i64 1048620)
+  %v36 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 11
+  store i64 %v35, ptr %v36
+  %v37 = call i64 @tig_allocRecord(i64 16, i64 ptrtoint (ptr @str7 to i64))
+  %v38 = inttoptr i64 %v37 to ptr
+  %v39 = getelementptr i64, ptr %v38, i64 0
+  store i64 ptrtoint (ptr @str6 to i64), ptr %v39
+  %v40 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 11
+  %v41 = load i64, ptr %v40
+  %v42 = getelementptr i64, ptr %v38, i64 1
+  store i64 %v41, ptr %v42
+  %v43 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 12
+  store i64 %v37, ptr %v43
+  %v44 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 12
+  %v45 = load i64, ptr %v44
+  %v46 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 5
+  store i64 %v45, ptr %v46
+  %v47 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  %v48 = load i64, ptr %v47
+  %v49 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 13
+  store i64 %v48, ptr %v49
+  %v50 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 13
+  %v51 = load i64, ptr %v50
+  %v52 = inttoptr i64 %v51 to ptr
+  %v53 = load i64, ptr %v52
+  %v54 = icmp uge i64 0, %v53
+  br i1 %v54, label %b55, label %b56
+b55:
+  call void @tig_indexOutOfRange(i64 0, i64 %v53, i64 1310726)
+  unreachable
+b56:
+  %v57 = add i64 0, 1
+  %v58 = getelementptr i64, ptr %v52, i64 %v57
+  store i64 1, ptr %v58
+  %v59 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  %v60 = load i64, ptr %v59
+  %v61 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 14
+  store i64 %v60, ptr %v61
+  %v62 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 14
+  %v63 = load i64, ptr %v62
+  %v64 = inttoptr i64 %v63 to ptr
+  %v65 = load i64, ptr %v64
+  %v66 = icmp uge i64 9, %v65
+  br i1 %v66, label %b67, label %b68
+b67:
+  call void @tig_indexOutOfRange(i64 9, i64 %v65, i64 1376262)
+  unreachable
+b68:
+  %v69 = add i64 9, 1
+  %v70 = getelementptr i64, ptr %v64, i64 %v69
+  store i64 3, ptr %v70
+  %v71 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  %v72 = load i64, ptr %v71
+  %v73 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 15
+  store i64 %v72, ptr %v73
+  %v74 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 15
+  %v75 = load i64, ptr %v74
+  %v76 = inttoptr i64 %v75 to ptr
+  %v77 = load i64, ptr %v76
+  %v78 = icmp uge i64 3, %v77
+  br i1 %v78, label %b79, label %b80
+b79:
+  call void @tig_indexOutOfRange(i64 3, i64 %v77, i64 1441798)
+  unreachable
+b80:
+  %v81 = add i64 3, 1
+  %v82 = getelementptr i64, ptr %v76, i64 %v81
+  %v83 = load i64, ptr %v82
+  %v84 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 16
+  store i64 %v83, ptr %v84
+  %v85 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 16
+  %v86 = load i64, ptr %v85
+  %v87 = icmp eq i64 %v86, 0
+  br i1 %v87, label %b88, label %b89
+b88:
+  call void @tig_nilRecord(i64 1441793)
+  unreachable
+b89:
+  %v90 = inttoptr i64 %v86 to ptr
+  %v91 = getelementptr i64, ptr %v90, i64 0
+  store i64 ptrtoint (ptr @str8 to i64), ptr %v91
+  %v92 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 2
+  %v93 = load i64, ptr %v92
+  %v94 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 17
+  store i64 %v93, ptr %v94
+  %v95 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 17
+  %v96 = load i64, ptr %v95
+  %v97 = inttoptr i64 %v96 to ptr
+  %v98 = load i64, ptr %v97
+  %v99 = icmp uge i64 1, %v98
+  br i1 %v99, label %b100, label %b101
+b100:
+  call void @tig_indexOutOfRange(i64 1, i64 %v98, i64 1507334)
+  unreachable
+b101:
+  %v102 = add i64 1, 1
+  %v103 = getelementptr i64, ptr %v97, i64 %v102
+  %v104 = load i64, ptr %v103
+  %v105 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 18
+  store i64 %v104, ptr %v105
+  %v106 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 18
+  %v107 = load i64, ptr %v106
+  %v108 = icmp eq i64 %v107, 0
+  br i1 %v108, label %b109, label %b110
+b109:
+  call void @tig_nilRecord(i64 1507329)
+  unreachable
+b110:
+  %v111 = inttoptr i64 %v107 to ptr
+  %v112 = getelementptr i64, ptr %v111, i64 3
+  store i64 23, ptr %v112
+  %v113 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 3
+  %v114 = load i64, ptr %v113
+  %v115 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 19
+  store i64 %v114, ptr %v115
+  %v116 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 19
+  %v117 = load i64, ptr %v116
+  %v118 = inttoptr i64 %v117 to ptr
+  %v119 = load i64, ptr %v118
+  %v120 = icmp uge i64 34, %v119
+  br i1 %v120, label %b121, label %b122
+b121:
+  call void @tig_indexOutOfRange(i64 34, i64 %v119, i64 1572870)
+  unreachable
+b122:
+  %v123 = add i64 34, 1
+  %v124 = getelementptr i64, ptr %v118, i64 %v123
+  store i64 ptrtoint (ptr @str9 to i64), ptr %v124
+  %v125 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 4
+  %v126 = load i64, ptr %v125
+  %v127 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 20
+  store i64 %v126, ptr %v127
+  %v128 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 20
+  %v129 = load i64, ptr %v128
+  %v130 = icmp eq i64 %v129, 0
+  br i1 %v130, label %b131, label %b132
+b131:
+  call void @tig_nilRecord(i64 1703937)
+  unreachable
+b132:
+  %v133 = inttoptr i64 %v129 to ptr
+  %v134 = getelementptr i64, ptr %v133, i64 0
+  store i64 ptrtoint (ptr @str10 to i64), ptr %v134
+  %v135 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 5
+  %v136 = load i64, ptr %v135
+  %v137 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 21
+  store i64 %v136, ptr %v137
+  %v138 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 21
+  %v139 = load i64, ptr %v138
+  %v140 = icmp eq i64 %v139, 0
+  br i1 %v140, label %b141, label %b142
+b141:
+  call void @tig_nilRecord(i64 1769473)
+  unreachable
+b142:
+  %v143 = inttoptr i64 %v139 to ptr
+  %v144 = getelementptr i64, ptr %v143, i64 1
+  %v145 = load i64, ptr %v144
+  %v146 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 22
+  store i64 %v145, ptr %v146
+  %v147 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 22
+  %v148 = load i64, ptr %v147
+  %v149 = inttoptr i64 %v148 to ptr
+  %v150 = load i64, ptr %v149
+  %v151 = icmp uge i64 0, %v150
+  br i1 %v151, label %b152, label %b153
+b152:
+  call void @tig_indexOutOfRange(i64 0, i64 %v150, i64 1769484)
+  unreachable
+b153:
+  %v154 = add i64 0, 1
+  %v155 = getelementptr i64, ptr %v149, i64 %v154
+  store i64 2323, ptr %v155
+  %v156 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 5
+  %v157 = load i64, ptr %v156
+  %v158 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 23
+  store i64 %v157, ptr %v158
+  %v159 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 23
+  %v160 = load i64, ptr %v159
+  %v161 = icmp eq i64 %v160, 0
+  br i1 %v161, label %b162, label %b163
+b162:
+  call void @tig_nilRecord(i64 1835009)
+  unreachable
+b163:
+  %v164 = inttoptr i64 %v160 to ptr
+  %v165 = getelementptr i64, ptr %v164, i64 1
+  %v166 = load i64, ptr %v165
+  %v167 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 24
+  store i64 %v166, ptr %v167
+  %v168 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 24
+  %v169 = load i64, ptr %v168
+  %v170 = inttoptr i64 %v169 to ptr
+  %v171 = load i64, ptr %v170
+  %v172 = icmp uge i64 2, %v171
+  br i1 %v172, label %b173, label %b174
+b173:
+  call void @tig_indexOutOfRange(i64 2, i64 %v171, i64 1835020)
+  unreachable
+b174:
+  %v175 = add i64 2, 1
+  %v176 = getelementptr i64, ptr %v170, i64 %v175
+  store i64 2323, ptr %v176
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
@@ -0,0 +1,120 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [4 x i8] } { i64 4, [4 x i8] c"str2" }, align 8
+@str1 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str2 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str3 = private unnamed_addr constant { i64, [11 x i8] } { i64 11, [11 x i8] c"do_nothing1" }, align 8
+@str4 = private unnamed_addr constant { i64, [2 x i8] } { i64 2, [2 x i8] c"pp" }, align 8
+@str5 = private unnamed_addr constant { i64, [3 x i8] } { i64 3, [3 x i8] c"str" }, align 8
+@str6 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c" " }, align 8
+@str7 = private unnamed_addr constant { i64, [11 x i8] } { i64 11, [11 x i8] c"do_nothing2" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str1 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str2 to i64), ptr %header.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %f, ptr @tig_c_frames
+  %v1 = call i64 @do_nothing1_L0(ptr %f, i64 0, i64 ptrtoint (ptr @str0 to i64))
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 %v1
+}
+
+%do_nothing1_L0.frame = type { %tig_frame, ptr }
+%do_nothing1_L0.roots = type { %tig_frame, i64, i64 }
+
+define i64 @do_nothing1_L0(ptr %link, i64 %a_0, i64 %b_1) {
+entry:
+  %f = alloca %do_nothing1_L0.frame
+  %r = alloca %do_nothing1_L0.roots
+  %a_0.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %do_nothing1_L0.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %do_nothing1_L0.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %do_nothing1_L0.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str3 to i64), ptr %header.name
+  %header.descriptor = getelementptr %do_nothing1_L0.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str2 to i64), ptr %header.descriptor
+  store %do_nothing1_L0.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %do_nothing1_L0.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %do_nothing1_L0.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %do_nothing1_L0.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str4 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %do_nothing1_L0.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store i64 %a_0, ptr %a_0.addr
+  %b_1.slot = getelementptr %do_nothing1_L0.roots, ptr %r, i32 0, i32 1
+  store i64 %b_1, ptr %b_1.slot
+  store ptr %r, ptr @tig_c_frames
+  %v1 = load i64, ptr %a_0.addr
+  %v2 = add i64 %v1, 1
+  %v3 = getelementptr %do_nothing1_L0.frame, ptr %f, i32 0, i32 1
+  %v4 = load ptr, ptr %v3
+  %v5 = call i64 @do_nothing2_L1(ptr %v4, i64 %v2)
+  %v6 = getelementptr %do_nothing1_L0.roots, ptr %r, i32 0, i32 2
+  store i64 %v5, ptr %v6
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
+
+%do_nothing2_L1.frame = type { %tig_frame, ptr }
+
+define i64 @do_nothing2_L1(ptr %link, i64 %d_2) {
+entry:
+  %f = alloca %do_nothing2_L1.frame
+  %d_2.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %do_nothing2_L1.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %do_nothing2_L1.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %do_nothing2_L1.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str7 to i64), ptr %header.name
+  %header.descriptor = getelementptr %do_nothing2_L1.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str2 to i64), ptr %header.descriptor
+  %f.link = getelementptr %do_nothing2_L1.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store i64 %d_2, ptr %d_2.addr
+  store ptr %f, ptr @tig_c_frames
+  %v1 = load i64, ptr %d_2.addr
+  %v2 = getelementptr %do_nothing2_L1.frame, ptr %f, i32 0, i32 1
+  %v3 = load ptr, ptr %v2
+  %v4 = call i64 @do_nothing1_L0(ptr %v3, i64 %v1, i64 ptrtoint (ptr @str5 to i64))
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 ptrtoint (ptr @str6 to i64)
+}
@@ -0,0 +1,79 @@
+%tig_frame = type { ptr, i64, i64 }
+
+@tig_c_frames = external global ptr
+
+; No frames of native code to describe to the collector.
+@tig_frame_maps = constant [1 x ptr] zeroinitializer
+
+declare void @tig_print(i64)
+declare void @tig_flush()
+declare i64 @tig_getchar()
+declare i64 @tig_ord(i64)
+declare i64 @tig_chr(i64, i64)
+declare i64 @tig_size(i64)
+declare i64 @tig_substring(i64, i64, i64, i64)
+declare i64 @tig_concat(i64, i64)
+declare i64 @tig_not(i64)
+declare void @tig_exit(i64) noreturn
+declare i64 @tig_stringEqual(i64, i64)
+declare i64 @tig_stringCompare(i64, i64)
+declare i64 @tig_initArray(i64, i64, i64, i64)
+declare i64 @tig_allocRecord(i64, i64)
+declare void @tig_nilRecord(i64) noreturn
+declare void @tig_indexOutOfRange(i64, i64, i64) noreturn
+declare void @tig_divisionByZero(i64) noreturn
+
+@str0 = private unnamed_addr constant { i64, [3 x i8] } { i64 3, [3 x i8] c"32\0A" }, align 8
+@str1 = private unnamed_addr constant { i64, [3 x i8] } { i64 3, [3 x i8] c"64\0A" }, align 8
+@str2 = private unnamed_addr constant { i64, [9 x i8] } { i64 9, [9 x i8] c"tigermain" }, align 8
+@str3 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"n" }, align 8
+@str4 = private unnamed_addr constant { i64, [1 x i8] } { i64 1, [1 x i8] c"p" }, align 8
+
+%tigermain.frame = type { %tig_frame, ptr }
+%tigermain.roots = type { %tig_frame, i64 }
+
+define i64 @tigermain(ptr %link) {
+entry:
+  %f = alloca %tigermain.frame
+  %r = alloca %tigermain.roots
+  %big_0.addr = alloca i64
+  %prev = load ptr, ptr @tig_c_frames
+  store %tigermain.frame zeroinitializer, ptr %f
+  %header.prev = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 0
+  store ptr %prev, ptr %header.prev
+  %header.name = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 1
+  store i64 ptrtoint (ptr @str2 to i64), ptr %header.name
+  %header.descriptor = getelementptr %tigermain.frame, ptr %f, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str3 to i64), ptr %header.descriptor
+  store %tigermain.roots zeroinitializer, ptr %r
+  %roots.prev = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 0
+  store ptr %f, ptr %roots.prev
+  %roots.name = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 1
+  store i64 0, ptr %roots.name
+  %roots.descriptor = getelementptr %tigermain.roots, ptr %r, i32 0, i32 0, i32 2
+  store i64 ptrtoint (ptr @str4 to i64), ptr %roots.descriptor
+  %f.link = getelementptr %tigermain.frame, ptr %f, i32 0, i32 1
+  store ptr %link, ptr %f.link
+  store ptr %r, ptr @tig_c_frames
+  %v1 = mul i64 65536, 65536
+  store i64 %v1, ptr %big_0.addr
+  %v2 = load i64, ptr %big_0.addr
+  %v3 = icmp eq i64 %v2, 0
+  %v4 = zext i1 %v3 to i64
+  %v5 = icmp ne i64 %v4, 0
+  br i1 %v5, label %b6, label %b7
+b6:
+  %v9 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  store i64 ptrtoint (ptr @str0 to i64), ptr %v9
+  br label %b8
+b7:
+  %v10 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  store i64 ptrtoint (ptr @str1 to i64), ptr %v10
+  br label %b8
+b8:
+  %v11 = getelementptr %tigermain.roots, ptr %r, i32 0, i32 1
+  %v12 = load i64, ptr %v11
+  call void @tig_print(i64 %v12)
+  store ptr %prev, ptr @tig_c_frames
+  ret i64 0
+}
@@ -0,0 +1,770 @@
+	.text
+	.globl isdigit_L1
+	.type isdigit_L1, @function
+isdigit_L1:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -32
+L85:
+	sd s1, -16(s0)
+	sd s2, -24(s0)
+	sd a0, -8(s0)
+	li s2, 1
+	ld t0, -8(s0)
+	ld t0, -8(t0)
+	ld a0, -16(t0)
+	call tig_ord
+L88:
+	mv s1, a0
+	la a0, L8
+	call tig_ord
+L89:
+	bge s1, a0, L12
+L11:
+	li s2, 0
+L10:
+	mv a0, s2
+	ld s1, -16(s0)
+	ld s2, -24(s0)
+	j L84
+L12:
+	ld t0, -8(s0)
+	ld t0, -8(t0)
+	ld a0, -16(t0)
+	call tig_ord
+L90:
+	mv s1, a0
+	la a0, L9
+	call tig_ord
+L91:
+	ble s1, a0, L10
+L86:
+	j L11
+L84:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size isdigit_L1, .-isdigit_L1
+
+	.globl skipto_L2
+	.type skipto_L2, @function
+skipto_L2:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L93:
+	sd s1, -16(s0)
+	sd a0, -8(s0)
+L13:
+	ld t0, -8(s0)
+	ld t0, -8(t0)
+	ld a0, -16(t0)
+	la a1, L16
+	call tig_stringEqual
+L95:
+	bne a0, zero, L14
+L18:
+	ld t0, -8(s0)
+	ld t0, -8(t0)
+	ld a0, -16(t0)
+	la a1, L17
+	call tig_stringEqual
+L96:
+	bne a0, zero, L14
+L15:
+	ld s1, -16(s0)
+	j L92
+L14:
+	ld t0, -8(s0)
+	ld t0, -8(t0)
+	addi s1, t0, -16
+	call tig_getchar
+L97:
+	sd a0, 0(s1)
+	j L13
+L92:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size skipto_L2, .-skipto_L2
+
+	.globl readint_L0
+	.type readint_L0, @function
+readint_L0:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -48
+	sd zero, -16(s0)
+	sd zero, -24(s0)
+L99:
+	sd s1, -32(s0)
+	sd s2, -40(s0)
+	sd a0, -8(s0)
+	sd a1, -16(s0)
+	li s2, 0
+	mv a0, s0
+	call skipto_L2
+L101:
+	ld t0, -16(s0)
+	sd t0, -24(s0)
+	ld t0, -8(s0)
+	ld a1, -16(t0)
+	mv a0, s0
+	call isdigit_L1
+L102:
+	ld t0, -24(s0)
+	bne t0, zero, L19
+L20:
+	li a0, 917510
+	call tig_nilRecord
+L103:
+L21:
+	j L21
+L19:
+	ld t0, -24(s0)
+	sd a0, 0(t0)
+L22:
+	ld t0, -8(s0)
+	ld a1, -16(t0)
+	mv a0, s0
+	call isdigit_L1
+L104:
+	bne a0, zero, L23
+L24:
+	mv a0, s2
+	ld s1, -32(s0)
+	ld s2, -40(s0)
+	j L98
+L23:
+	li t0, 10
+	mul s1, s2, t0
+	ld t0, -8(s0)
+	ld a0, -16(t0)
+	call tig_ord
+L105:
+	add s1, s1, a0
+	la a0, L25
+	call tig_ord
+L106:
+	sub s2, s1, a0
+	ld t0, -8(s0)
+	addi s1, t0, -16
+	call tig_getchar
+L107:
+	sd a0, 0(s1)
+	j L22
+L98:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size readint_L0, .-readint_L0
+
+	.globl readlist_L3
+	.type readlist_L3, @function
+readlist_L3:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -32
+	sd zero, -16(s0)
+	sd zero, -24(s0)
+L109:
+	sd s1, -32(s0)
+	sd a0, -8(s0)
+	li a0, 8
+	la a1, L26
+	call tig_allocRecord
+L111:
+	sd zero, 0(a0)
+	sd a0, -16(s0)
+	ld a0, -8(s0)
+	ld a1, -16(s0)
+	call readint_L0
+L112:
+	mv s1, a0
+	ld t0, -16(s0)
+	bne t0, zero, L27
+L28:
+	li a0, 1638412
+	call tig_nilRecord
+L113:
+L29:
+	j L29
+L27:
+	ld t0, 0(t0)
+	bne t0, zero, L30
+L31:
+	li a0, 0
+L32:
+	ld s1, -32(s0)
+	j L108
+L30:
+	li a0, 16
+	la a1, L33
+	call tig_allocRecord
+L114:
+	sd a0, -24(s0)
+	mv t0, s1
+	ld t1, -24(s0)
+	sd t0, 0(t1)
+	ld a0, -8(s0)
+	call readlist_L3
+L115:
+	ld t0, -24(s0)
+	sd a0, 8(t0)
+	ld a0, -24(s0)
+	j L32
+L108:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size readlist_L3, .-readlist_L3
+
+	.globl merge_L4
+	.type merge_L4, @function
+merge_L4:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -48
+	sd zero, -16(s0)
+	sd zero, -24(s0)
+	sd zero, -32(s0)
+	sd zero, -40(s0)
+L117:
+	sd a0, -8(s0)
+	sd a1, -16(s0)
+	sd a2, -24(s0)
+	ld t0, -16(s0)
+	beq t0, zero, L34
+L35:
+	ld t0, -24(s0)
+	beq t0, zero, L37
+L38:
+	ld t0, -16(s0)
+	bne t0, zero, L40
+L41:
+	li a0, 2162700
+	call tig_nilRecord
+L119:
+L42:
+	j L42
+L34:
+	ld a0, -24(s0)
+L36:
+	j L116
+L37:
+	ld a0, -16(s0)
+L39:
+	j L36
+L40:
+	ld t1, 0(t0)
+	ld t0, -24(s0)
+	bne t0, zero, L43
+L44:
+	li a0, 2162710
+	call tig_nilRecord
+L120:
+L45:
+	j L45
+L43:
+	ld t0, 0(t0)
+	blt t1, t0, L46
+L47:
+	li a0, 16
+	la a1, L33
+	call tig_allocRecord
+L121:
+	sd a0, -40(s0)
+	ld t0, -24(s0)
+	bne t0, zero, L55
+L56:
+	li a0, 2293783
+	call tig_nilRecord
+L122:
+L57:
+	j L57
+L46:
+	li a0, 16
+	la a1, L33
+	call tig_allocRecord
+L123:
+	sd a0, -32(s0)
+	ld t0, -16(s0)
+	bne t0, zero, L49
+L50:
+	li a0, 2228247
+	call tig_nilRecord
+L124:
+L51:
+	j L51
+L49:
+	ld t1, 0(t0)
+	ld t0, -32(s0)
+	sd t1, 0(t0)
+	ld a0, -8(s0)
+	ld t0, -16(s0)
+	bne t0, zero, L52
+L53:
+	li a0, 2228266
+	call tig_nilRecord
+L125:
+L54:
+	j L54
+L52:
+	ld a1, 8(t0)
+	ld a2, -24(s0)
+	call merge_L4
+L126:
+	ld t0, -32(s0)
+	sd a0, 8(t0)
+	ld a0, -32(s0)
+L48:
+	j L39
+L55:
+	ld t1, 0(t0)
+	ld t0, -40(s0)
+	sd t1, 0(t0)
+	ld a0, -8(s0)
+	ld a1, -16(s0)
+	ld t0, -24(s0)
+	bne t0, zero, L58
+L59:
+	li a0, 2293804
+	call tig_nilRecord
+L127:
+L60:
+	j L60
+L58:
+	ld a2, 8(t0)
+	call merge_L4
+L128:
+	ld t0, -40(s0)
+	sd a0, 8(t0)
+	ld a0, -40(s0)
+	j L48
+L116:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size merge_L4, .-merge_L4
+
+	.globl f_L7
+	.type f_L7, @function
+f_L7:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L130:
+	sd s1, -16(s0)
+	sd a0, -8(s0)
+	mv s1, a1
+	bgt s1, zero, L61
+L63:
+	ld s1, -16(s0)
+	j L129
+L61:
+	ld a0, -8(s0)
+	li t0, 10
+	div a1, s1, t0
+	call f_L7
+L132:
+	li t0, 10
+	div t1, s1, t0
+	li t0, 10
+	mul t0, t1, t0
+	sub s1, s1, t0
+	la a0, L64
+	call tig_ord
+L133:
+	add a0, s1, a0
+	li a1, 2555932
+	call tig_chr
+L134:
+	call tig_print
+L135:
+	j L63
+L129:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size f_L7, .-f_L7
+
+	.globl printint_L5
+	.type printint_L5, @function
+printint_L5:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L137:
+	sd s1, -16(s0)
+	sd a0, -8(s0)
+	mv s1, a1
+	blt s1, zero, L65
+L66:
+	bgt s1, zero, L69
+L70:
+	la a0, L72
+	call tig_print
+L139:
+L71:
+L67:
+	ld s1, -16(s0)
+	j L136
+L65:
+	la a0, L68
+	call tig_print
+L140:
+	li t0, 0
+	sub a1, t0, s1
+	mv a0, s0
+	call f_L7
+L141:
+	j L67
+L69:
+	mv a0, s0
+	mv a1, s1
+	call f_L7
+L142:
+	j L71
+L136:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size printint_L5, .-printint_L5
+
+	.globl printlist_L6
+	.type printlist_L6, @function
+printlist_L6:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+	sd zero, -16(s0)
+L144:
+	sd a0, -8(s0)
+	sd a1, -16(s0)
+	ld t0, -16(s0)
+	beq t0, zero, L73
+L74:
+	ld a0, -8(s0)
+	ld t0, -16(s0)
+	bne t0, zero, L77
+L78:
+	li a0, 3080211
+	call tig_nilRecord
+L146:
+L79:
+	j L79
+L73:
+	la a0, L76
+	call tig_print
+L147:
+L75:
+	j L143
+L77:
+	ld a1, 0(t0)
+	call printint_L5
+L148:
+	la a0, L80
+	call tig_print
+L149:
+	ld a0, -8(s0)
+	ld t0, -16(s0)
+	bne t0, zero, L81
+L82:
+	li a0, 3080243
+	call tig_nilRecord
+L150:
+L83:
+	j L83
+L81:
+	ld a1, 8(t0)
+	call printlist_L6
+L151:
+	j L75
+L143:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size printlist_L6, .-printlist_L6
+
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -32
+	sd zero, -16(s0)
+	sd zero, -24(s0)
+L153:
+	sd s1, -32(s0)
+	sd a0, -8(s0)
+	addi s1, s0, -16
+	call tig_getchar
+L155:
+	sd a0, 0(s1)
+	mv a0, s0
+	call readlist_L3
+L156:
+	sd a0, -24(s0)
+	addi s1, s0, -16
+	call tig_getchar
+L157:
+	sd a0, 0(s1)
+	mv a0, s0
+	call readlist_L3
+L158:
+	mv a2, a0
+	ld a1, -24(s0)
+	mv a0, s0
+	call merge_L4
+L159:
+	mv a1, a0
+	mv a0, s0
+	call printlist_L6
+L160:
+	ld s1, -32(s0)
+L152:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.p2align 3
+L8:
+	.quad 1
+	.ascii "0"
+	.p2align 3
+L9:
+	.quad 1
+	.ascii "9"
+	.p2align 3
+L16:
+	.quad 1
+	.ascii " "
+	.p2align 3
+L17:
+	.quad 1
+	.ascii "\012"
+	.p2align 3
+L25:
+	.quad 1
+	.ascii "0"
+	.p2align 3
+L26:
+	.quad 1
+	.ascii "n"
+	.p2align 3
+L33:
+	.quad 2
+	.ascii "np"
+	.p2align 3
+L64:
+	.quad 1
+	.ascii "0"
+	.p2align 3
+L68:
+	.quad 1
+	.ascii "-"
+	.p2align 3
+L72:
+	.quad 1
+	.ascii "0"
+	.p2align 3
+L76:
+	.quad 1
+	.ascii "\012"
+	.p2align 3
+L80:
+	.quad 1
+	.ascii " "
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L87_name:
+	.quad 7
+	.ascii "isdigit"
+	.p2align 3
+L87:
+	.quad L87_name
+	.quad 0
+	.quad 4
+	.quad L88
+	.quad L89
+	.quad L90
+	.quad L91
+	.p2align 3
+L94_name:
+	.quad 6
+	.ascii "skipto"
+	.p2align 3
+L94:
+	.quad L94_name
+	.quad 0
+	.quad 3
+	.quad L95
+	.quad L96
+	.quad L97
+	.p2align 3
+L100_name:
+	.quad 7
+	.ascii "readint"
+	.p2align 3
+L100:
+	.quad L100_name
+	.quad 2
+	.quad -16
+	.quad -24
+	.quad 7
+	.quad L101, 0x1
+	.quad L102, 0x2
+	.quad L103, 0x0
+	.quad L104, 0x0
+	.quad L105, 0x0
+	.quad L106, 0x0
+	.quad L107, 0x0
+	.p2align 3
+L110_name:
+	.quad 8
+	.ascii "readlist"
+	.p2align 3
+L110:
+	.quad L110_name
+	.quad 2
+	.quad -16
+	.quad -24
+	.quad 5
+	.quad L111, 0x0
+	.quad L112, 0x1
+	.quad L113, 0x0
+	.quad L114, 0x0
+	.quad L115, 0x2
+	.p2align 3
+L118_name:
+	.quad 5
+	.ascii "merge"
+	.p2align 3
+L118:
+	.quad L118_name
+	.quad 4
+	.quad -16
+	.quad -24
+	.quad -32
+	.quad -40
+	.quad 10
+	.quad L119, 0x0
+	.quad L120, 0x0
+	.quad L121, 0x3
+	.quad L122, 0x0
+	.quad L123, 0x3
+	.quad L124, 0x0
+	.quad L125, 0x0
+	.quad L126, 0x4
+	.quad L127, 0x0
+	.quad L128, 0x8
+	.p2align 3
+L131_name:
+	.quad 1
+	.ascii "f"
+	.p2align 3
+L131:
+	.quad L131_name
+	.quad 0
+	.quad 4
+	.quad L132
+	.quad L133
+	.quad L134
+	.quad L135
+	.p2align 3
+L138_name:
+	.quad 8
+	.ascii "printint"
+	.p2align 3
+L138:
+	.quad L138_name
+	.quad 0
+	.quad 4
+	.quad L139
+	.quad L140
+	.quad L141
+	.quad L142
+	.p2align 3
+L145_name:
+	.quad 9
+	.ascii "printlist"
+	.p2align 3
+L145:
+	.quad L145_name
+	.quad 1
+	.quad -16
+	.quad 6
+	.quad L146, 0x0
+	.quad L147, 0x0
+	.quad L148, 0x1
+	.quad L149, 0x1
+	.quad L150, 0x0
+	.quad L151, 0x0
+	.p2align 3
+L154_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L154:
+	.quad L154_name
+	.quad 2
+	.quad -16
+	.quad -24
+	.quad 6
+	.quad L155, 0x1
+	.quad L156, 0x1
+	.quad L157, 0x3
+	.quad L158, 0x3
+	.quad L159, 0x1
+	.quad L160, 0x1
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L87
+	.quad L94
+	.quad L100
+	.quad L110
+	.quad L118
+	.quad L131
+	.quad L138
+	.quad L145
+	.quad L154
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,469 @@
+	.text
+	.globl printboard_L0
+	.type printboard_L0, @function
+printboard_L0:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -48
+L60:
+	sd s1, -16(s0)
+	sd s2, -24(s0)
+	sd s3, -32(s0)
+	sd s4, -40(s0)
+	sd a0, -8(s0)
+	li s4, 0
+	ld t0, -8(s0)
+	ld t0, -16(t0)
+	addi s3, t0, -1
+	ble s4, s3, L2
+L4:
+	la a0, L17
+	call tig_print
+L62:
+	ld s1, -16(s0)
+	ld s2, -24(s0)
+	ld s3, -32(s0)
+	ld s4, -40(s0)
+	j L59
+L2:
+	li s2, 0
+	ld t0, -8(s0)
+	ld t0, -16(t0)
+	addi s1, t0, -1
+	ble s2, s1, L5
+L7:
+	la a0, L16
+	call tig_print
+L63:
+	bge s4, s3, L4
+L3:
+	addi s4, s4, 1
+	j L2
+L5:
+	ld t0, -8(s0)
+	ld t0, -32(t0)
+	mv a0, s4
+	ld t1, 0(t0)
+	bltu a0, t1, L8
+L9:
+	ld a1, 0(t0)
+	li a2, 1048600
+	call tig_indexOutOfRange
+L64:
+L10:
+	j L10
+L8:
+	li t1, 8
+	mul t1, a0, t1
+	add t0, t0, t1
+	ld t0, 8(t0)
+	beq t0, s2, L11
+L12:
+	la a0, L15
+L13:
+	call tig_print
+L65:
+	bge s2, s1, L7
+L6:
+	addi s2, s2, 1
+	j L5
+L11:
+	la a0, L14
+	j L13
+L59:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size printboard_L0, .-printboard_L0
+
+	.globl try_L1
+	.type try_L1, @function
+try_L1:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -32
+L67:
+	sd s1, -16(s0)
+	sd s2, -24(s0)
+	sd s3, -32(s0)
+	sd a0, -8(s0)
+	mv s3, a1
+	ld t0, -8(s0)
+	ld t0, -16(t0)
+	beq s3, t0, L18
+L19:
+	li s2, 0
+	ld t0, -8(s0)
+	ld t0, -16(t0)
+	addi s1, t0, -1
+	ble s2, s1, L21
+L23:
+L20:
+	ld s1, -16(s0)
+	ld s2, -24(s0)
+	ld s3, -32(s0)
+	j L66
+L18:
+	ld a0, -8(s0)
+	call printboard_L0
+L69:
+	j L20
+L21:
+	ld t0, -8(s0)
+	ld t1, -24(t0)
+	mv t0, s2
+	ld t2, 0(t1)
+	bltu t0, t2, L24
+L25:
+	ld a1, 0(t1)
+	li a2, 1638415
+	mv a0, t0
+	call tig_indexOutOfRange
+L70:
+L26:
+	j L26
+L24:
+	li t2, 8
+	mul t0, t0, t2
+	add t0, t1, t0
+	ld t0, 8(t0)
+	beq t0, zero, L58
+L35:
+	bge s2, s1, L23
+L22:
+	addi s2, s2, 1
+	j L21
+L58:
+	ld t0, -8(s0)
+	ld t1, -40(t0)
+	add t0, s2, s3
+	ld t2, 0(t1)
+	bltu t0, t2, L27
+L28:
+	ld a1, 0(t1)
+	li a2, 1638428
+	mv a0, t0
+	call tig_indexOutOfRange
+L71:
+L29:
+	j L29
+L27:
+	li t2, 8
+	mul t0, t0, t2
+	add t0, t1, t0
+	ld t0, 8(t0)
+	bne t0, zero, L35
+L57:
+	ld t0, -8(s0)
+	ld t1, -48(t0)
+	addi t0, s2, 7
+	sub t0, t0, s3
+	ld t2, 0(t1)
+	bltu t0, t2, L30
+L31:
+	ld a1, 0(t1)
+	li a2, 1638443
+	mv a0, t0
+	call tig_indexOutOfRange
+L72:
+L32:
+	j L32
+L30:
+	li t2, 8
+	mul t0, t0, t2
+	add t0, t1, t0
+	ld t0, 8(t0)
+	bne t0, zero, L35
+L33:
+	ld t0, -8(s0)
+	ld t0, -24(t0)
+	mv a0, s2
+	ld t1, 0(t0)
+	bltu a0, t1, L36
+L37:
+	ld a1, 0(t0)
+	li a2, 1703959
+	call tig_indexOutOfRange
+L73:
+L38:
+	j L38
+L36:
+	li t1, 8
+	mul t1, a0, t1
+	add t1, t0, t1
+	li t0, 1
+	sd t0, 8(t1)
+	ld t0, -8(s0)
+	ld t0, -40(t0)
+	add a0, s2, s3
+	ld t1, 0(t0)
+	bltu a0, t1, L39
+L40:
+	ld a1, 0(t0)
+	li a2, 1703972
+	call tig_indexOutOfRange
+L74:
+L41:
+	j L41
+L39:
+	li t1, 8
+	mul t1, a0, t1
+	add t1, t0, t1
+	li t0, 1
+	sd t0, 8(t1)
+	ld t0, -8(s0)
+	ld t0, -48(t0)
+	addi t1, s2, 7
+	sub a0, t1, s3
+	ld t1, 0(t0)
+	bltu a0, t1, L42
+L43:
+	ld a1, 0(t0)
+	li a2, 1703987
+	call tig_indexOutOfRange
+L75:
+L44:
+	j L44
+L42:
+	li t1, 8
+	mul t1, a0, t1
+	add t1, t0, t1
+	li t0, 1
+	sd t0, 8(t1)
+	ld t0, -8(s0)
+	ld t1, -32(t0)
+	mv a0, s3
+	mv t0, s2
+	ld t2, 0(t1)
+	bltu a0, t2, L45
+L46:
+	ld a1, 0(t1)
+	li a2, 1769488
+	call tig_indexOutOfRange
+L76:
+L47:
+	j L47
+L45:
+	li t2, 8
+	mul t2, a0, t2
+	add t1, t1, t2
+	sd t0, 8(t1)
+	ld a0, -8(s0)
+	addi a1, s3, 1
+	call try_L1
+L77:
+	ld t0, -8(s0)
+	ld t1, -24(t0)
+	mv t0, s2
+	ld t2, 0(t1)
+	bltu t0, t2, L48
+L49:
+	ld a1, 0(t1)
+	li a2, 1900553
+	mv a0, t0
+	call tig_indexOutOfRange
+L78:
+L50:
+	j L50
+L48:
+	li t2, 8
+	mul t0, t0, t2
+	add t0, t1, t0
+	sd zero, 8(t0)
+	ld t0, -8(s0)
+	ld t1, -40(t0)
+	add t0, s2, s3
+	ld t2, 0(t1)
+	bltu t0, t2, L51
+L52:
+	ld a1, 0(t1)
+	li a2, 1900566
+	mv a0, t0
+	call tig_indexOutOfRange
+L79:
+L53:
+	j L53
+L51:
+	li t2, 8
+	mul t0, t0, t2
+	add t0, t1, t0
+	sd zero, 8(t0)
+	ld t0, -8(s0)
+	ld t1, -48(t0)
+	addi t0, s2, 7
+	sub t0, t0, s3
+	ld t2, 0(t1)
+	bltu t0, t2, L54
+L55:
+	ld a1, 0(t1)
+	li a2, 1900581
+	mv a0, t0
+	call tig_indexOutOfRange
+L80:
+L56:
+	j L56
+L54:
+	li t2, 8
+	mul t0, t0, t2
+	add t0, t1, t0
+	sd zero, 8(t0)
+	j L35
+L66:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size try_L1, .-try_L1
+
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -64
+	sd zero, -24(s0)
+	sd zero, -32(s0)
+	sd zero, -40(s0)
+	sd zero, -48(s0)
+L82:
+	sd s1, -56(s0)
+	sd a0, -8(s0)
+	li t0, 8
+	sd t0, -16(s0)
+	addi s1, s0, -24
+	ld a0, -16(s0)
+	li a1, 0
+	li a2, 0
+	li a3, 524304
+	call tig_initArray
+L84:
+	sd a0, 0(s1)
+	addi s1, s0, -32
+	ld a0, -16(s0)
+	li a1, 0
+	li a2, 0
+	li a3, 589840
+	call tig_initArray
+L85:
+	sd a0, 0(s1)
+	addi s1, s0, -40
+	ld t1, -16(s0)
+	ld t0, -16(s0)
+	add t0, t1, t0
+	addi a0, t0, -1
+	li a1, 0
+	li a2, 0
+	li a3, 655378
+	call tig_initArray
+L86:
+	sd a0, 0(s1)
+	addi s1, s0, -48
+	ld t1, -16(s0)
+	ld t0, -16(s0)
+	add t0, t1, t0
+	addi a0, t0, -1
+	li a1, 0
+	li a2, 0
+	li a3, 720914
+	call tig_initArray
+L87:
+	sd a0, 0(s1)
+	li a1, 0
+	mv a0, s0
+	call try_L1
+L88:
+	ld s1, -56(s0)
+L81:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.p2align 3
+L14:
+	.quad 2
+	.ascii " O"
+	.p2align 3
+L15:
+	.quad 2
+	.ascii " ."
+	.p2align 3
+L16:
+	.quad 1
+	.ascii "\012"
+	.p2align 3
+L17:
+	.quad 1
+	.ascii "\012"
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L61_name:
+	.quad 10
+	.ascii "printboard"
+	.p2align 3
+L61:
+	.quad L61_name
+	.quad 0
+	.quad 4
+	.quad L62
+	.quad L63
+	.quad L64
+	.quad L65
+	.p2align 3
+L68_name:
+	.quad 3
+	.ascii "try"
+	.p2align 3
+L68:
+	.quad L68_name
+	.quad 0
+	.quad 12
+	.quad L69
+	.quad L70
+	.quad L71
+	.quad L72
+	.quad L73
+	.quad L74
+	.quad L75
+	.quad L76
+	.quad L77
+	.quad L78
+	.quad L79
+	.quad L80
+	.p2align 3
+L83_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L83:
+	.quad L83_name
+	.quad 4
+	.quad -24
+	.quad -32
+	.quad -40
+	.quad -48
+	.quad 5
+	.quad L84, 0xf
+	.quad L85, 0xf
+	.quad L86, 0xf
+	.quad L87, 0xf
+	.quad L88, 0xf
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L61
+	.quad L68
+	.quad L83
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
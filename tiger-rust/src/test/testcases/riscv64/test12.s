@@ -0,0 +1,47 @@
+	.text
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L4:
+	sd a0, -8(s0)
+	li t2, 0
+	li t1, 0
+	li t0, 100
+	ble t1, t0, L0
+L2:
+	j L3
+L0:
+	addi t2, t2, 1
+	bge t1, t0, L2
+L1:
+	addi t1, t1, 1
+	j L0
+L3:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L5_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L5:
+	.quad L5_name
+	.quad 0
+	.quad 0
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L5
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
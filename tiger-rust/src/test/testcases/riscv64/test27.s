@@ -0,0 +1,70 @@
+	.text
+	.globl g_L0
+	.type g_L0, @function
+g_L0:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L2:
+	sd a0, -8(s0)
+	mv a0, a1
+L1:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size g_L0, .-g_L0
+
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L5:
+	sd a0, -8(s0)
+	li t0, 0
+	li a1, 2
+	mv a0, s0
+	call g_L0
+L7:
+L4:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L3_name:
+	.quad 1
+	.ascii "g"
+	.p2align 3
+L3:
+	.quad L3_name
+	.quad 0
+	.quad 0
+	.p2align 3
+L6_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L6:
+	.quad L6_name
+	.quad 0
+	.quad 1
+	.quad L7
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L3
+	.quad L6
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
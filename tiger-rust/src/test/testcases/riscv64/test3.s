@@ -0,0 +1,67 @@
+	.text
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L7:
+	sd a0, -8(s0)
+	li a0, 16
+	la a1, L0
+	call tig_allocRecord
+L9:
+	la t0, L1
+	sd t0, 0(a0)
+	li t0, 1000
+	sd t0, 8(a0)
+	bne a0, zero, L2
+L3:
+	li a0, 393218
+	call tig_nilRecord
+L10:
+L4:
+	j L4
+L2:
+	la t0, L5
+	sd t0, 0(a0)
+L6:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.p2align 3
+L0:
+	.quad 2
+	.ascii "pn"
+	.p2align 3
+L1:
+	.quad 6
+	.ascii "Nobody"
+	.p2align 3
+L5:
+	.quad 8
+	.ascii "Somebody"
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L8_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L8:
+	.quad L8_name
+	.quad 0
+	.quad 2
+	.quad L9
+	.quad L10
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L8
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
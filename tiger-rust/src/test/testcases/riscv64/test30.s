@@ -0,0 +1,61 @@
+	.text
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L4:
+	sd a0, -8(s0)
+	li a0, 10
+	li a1, 0
+	li a2, 0
+	li a3, 458769
+	call tig_initArray
+L6:
+	ld t1, 0(a0)
+	li t0, 2
+	bgtu t1, t0, L0
+L1:
+	li t0, 2
+	ld a1, 0(a0)
+	li a2, 589832
+	mv a0, t0
+	call tig_indexOutOfRange
+L7:
+L2:
+	j L2
+L0:
+	li t1, 2
+	li t0, 8
+	mul t0, t1, t0
+	add t0, a0, t0
+	ld a0, 8(t0)
+L3:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L5_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L5:
+	.quad L5_name
+	.quad 0
+	.quad 2
+	.quad L6
+	.quad L7
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L5
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,84 @@
+	.text
+	.globl nfactor_L0
+	.type nfactor_L0, @function
+nfactor_L0:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L5:
+	sd s1, -16(s0)
+	sd a0, -8(s0)
+	beq a1, zero, L1
+L2:
+	mv s1, a1
+	ld a0, -8(s0)
+	addi a1, a1, -1
+	call nfactor_L0
+L7:
+	mul a0, s1, a0
+L3:
+	ld s1, -16(s0)
+	j L4
+L1:
+	li a0, 1
+	j L3
+L4:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size nfactor_L0, .-nfactor_L0
+
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L9:
+	sd a0, -8(s0)
+	li a1, 10
+	mv a0, s0
+	call nfactor_L0
+L11:
+L8:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L6_name:
+	.quad 7
+	.ascii "nfactor"
+	.p2align 3
+L6:
+	.quad L6_name
+	.quad 0
+	.quad 1
+	.quad L7
+	.p2align 3
+L10_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L10:
+	.quad L10_name
+	.quad 0
+	.quad 1
+	.quad L11
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L6
+	.quad L10
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
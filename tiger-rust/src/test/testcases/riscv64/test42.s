@@ -0,0 +1,346 @@
+	.text
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -48
+	sd zero, -16(s0)
+	sd zero, -24(s0)
+	sd zero, -32(s0)
+	sd zero, -40(s0)
+	sd zero, -48(s0)
+L48:
+	sd a0, -8(s0)
+	li a0, 10
+	li a1, 0
+	li a2, 0
+	li a3, 720909
+	call tig_initArray
+L50:
+	sd a0, -16(s0)
+	li a0, 32
+	la a1, L0
+	call tig_allocRecord
+L51:
+	la t0, L1
+	sd t0, 0(a0)
+	la t0, L2
+	sd t0, 8(a0)
+	sd zero, 16(a0)
+	sd zero, 24(a0)
+	mv a1, a0
+	li a0, 5
+	li a2, 1
+	li a3, 786446
+	call tig_initArray
+L52:
+	sd a0, -24(s0)
+	li a0, 100
+	la a1, L3
+	li a2, 1
+	li a3, 851990
+	call tig_initArray
+L53:
+	sd a0, -32(s0)
+	li a0, 32
+	la a1, L0
+	call tig_allocRecord
+L54:
+	la t0, L4
+	sd t0, 0(a0)
+	la t0, L5
+	sd t0, 8(a0)
+	li t0, 2432
+	sd t0, 16(a0)
+	li t0, 44
+	sd t0, 24(a0)
+	sd a0, -40(s0)
+	li a0, 16
+	la a1, L6
+	call tig_allocRecord
+L55:
+	sd a0, -48(s0)
+	ld t1, -48(s0)
+	la t0, L7
+	sd t0, 0(t1)
+	li a0, 3
+	li a1, 1900
+	li a2, 0
+	li a3, 1048620
+	call tig_initArray
+L56:
+	mv t0, a0
+	ld t1, -48(s0)
+	sd t0, 8(t1)
+	ld t0, -48(s0)
+	ld t3, -16(s0)
+	ld t1, 0(t3)
+	bgtu t1, zero, L8
+L9:
+	li a0, 0
+	ld a1, 0(t3)
+	li a2, 1310726
+	call tig_indexOutOfRange
+L57:
+L10:
+	j L10
+L8:
+	li t2, 0
+	li t1, 8
+	mul t1, t2, t1
+	add t2, t3, t1
+	li t1, 1
+	sd t1, 8(t2)
+	ld t3, -16(s0)
+	ld t2, 0(t3)
+	li t1, 9
+	bgtu t2, t1, L11
+L12:
+	li a0, 9
+	ld a1, 0(t3)
+	li a2, 1376262
+	call tig_indexOutOfRange
+L58:
+L13:
+	j L13
+L11:
+	li t2, 9
+	li t1, 8
+	mul t1, t2, t1
+	add t2, t3, t1
+	li t1, 3
+	sd t1, 8(t2)
+	ld t3, -24(s0)
+	ld t2, 0(t3)
+	li t1, 3
+	bgtu t2, t1, L14
+L15:
+	li a0, 3
+	ld a1, 0(t3)
+	li a2, 1441798
+	call tig_indexOutOfRange
+L59:
+L16:
+	j L16
+L14:
+	li t2, 3
+	li t1, 8
+	mul t1, t2, t1
+	add t1, t3, t1
+	ld t1, 8(t1)
+	bne t1, zero, L17
+L18:
+	li a0, 1441793
+	call tig_nilRecord
+L60:
+L19:
+	j L19
+L17:
+	la t2, L20
+	sd t2, 0(t1)
+	ld t3, -24(s0)
+	ld t2, 0(t3)
+	li t1, 1
+	bgtu t2, t1, L21
+L22:
+	li a0, 1
+	ld a1, 0(t3)
+	li a2, 1507334
+	call tig_indexOutOfRange
+L61:
+L23:
+	j L23
+L21:
+	li t2, 1
+	li t1, 8
+	mul t1, t2, t1
+	add t1, t3, t1
+	ld t1, 8(t1)
+	bne t1, zero, L24
+L25:
+	li a0, 1507329
+	call tig_nilRecord
+L62:
+L26:
+	j L26
+L24:
+	li t2, 23
+	sd t2, 24(t1)
+	ld t3, -32(s0)
+	ld t2, 0(t3)
+	li t1, 34
+	bgtu t2, t1, L27
+L28:
+	li a0, 34
+	ld a1, 0(t3)
+	li a2, 1572870
+	call tig_indexOutOfRange
+L63:
+L29:
+	j L29
+L27:
+	li t2, 34
+	li t1, 8
+	mul t1, t2, t1
+	add t2, t3, t1
+	la t1, L30
+	sd t1, 8(t2)
+	ld t2, -40(s0)
+	bne t2, zero, L31
+L32:
+	li a0, 1703937
+	call tig_nilRecord
+L64:
+L33:
+	j L33
+L31:
+	la t1, L34
+	sd t1, 0(t2)
+	bne t0, zero, L35
+L36:
+	li a0, 1769473
+	call tig_nilRecord
+L65:
+L37:
+	j L37
+L35:
+	ld t1, 8(t0)
+	ld t2, 0(t1)
+	bgtu t2, zero, L38
+L39:
+	li a0, 0
+	ld a1, 0(t1)
+	li a2, 1769484
+	call tig_indexOutOfRange
+L66:
+L40:
+	j L40
+L38:
+	li t3, 0
+	li t2, 8
+	mul t2, t3, t2
+	add t2, t1, t2
+	li t1, 2323
+	sd t1, 8(t2)
+	bne t0, zero, L41
+L42:
+	li a0, 1835009
+	call tig_nilRecord
+L67:
+L43:
+	j L43
+L41:
+	ld t0, 8(t0)
+	ld t2, 0(t0)
+	li t1, 2
+	bgtu t2, t1, L44
+L45:
+	li a0, 2
+	ld a1, 0(t0)
+	li a2, 1835020
+	call tig_indexOutOfRange
+L68:
+L46:
+	j L46
+L44:
+	li t2, 2
+	li t1, 8
+	mul t1, t2, t1
+	add t1, t0, t1
+	li t0, 2323
+	sd t0, 8(t1)
+L47:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.p2align 3
+L0:
+	.quad 4
+	.ascii "ppnn"
+	.p2align 3
+L1:
+	.quad 5
+	.ascii "aname"
+	.p2align 3
+L2:
+	.quad 9
+	.ascii "somewhere"
+	.p2align 3
+L3:
+	.quad 0
+	.ascii ""
+	.p2align 3
+L4:
+	.quad 7
+	.ascii "Kapoios"
+	.p2align 3
+L5:
+	.quad 5
+	.ascii "Kapou"
+	.p2align 3
+L6:
+	.quad 2
+	.ascii "pp"
+	.p2align 3
+L7:
+	.quad 5
+	.ascii "Allos"
+	.p2align 3
+L20:
+	.quad 4
+	.ascii "kati"
+	.p2align 3
+L30:
+	.quad 3
+	.ascii "sfd"
+	.p2align 3
+L34:
+	.quad 3
+	.ascii "sdf"
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L49_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L49:
+	.quad L49_name
+	.quad 5
+	.quad -16
+	.quad -24
+	.quad -32
+	.quad -40
+	.quad -48
+	.quad 19
+	.quad L50, 0x0
+	.quad L51, 0x1
+	.quad L52, 0x1
+	.quad L53, 0x3
+	.quad L54, 0x7
+	.quad L55, 0xf
+	.quad L56, 0x1f
+	.quad L57, 0x0
+	.quad L58, 0x0
+	.quad L59, 0x0
+	.quad L60, 0x0
+	.quad L61, 0x0
+	.quad L62, 0x0
+	.quad L63, 0x0
+	.quad L64, 0x0
+	.quad L65, 0x0
+	.quad L66, 0x0
+	.quad L67, 0x0
+	.quad L68, 0x0
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L49
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
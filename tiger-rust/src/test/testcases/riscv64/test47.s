@@ -0,0 +1,37 @@
+	.text
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L1:
+	sd a0, -8(s0)
+	li t0, 4
+	li a0, 0
+L0:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L2_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L2:
+	.quad L2_name
+	.quad 0
+	.quad 0
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L2
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
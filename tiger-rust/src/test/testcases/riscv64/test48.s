@@ -0,0 +1,94 @@
+	.text
+	.globl g_L0
+	.type g_L0, @function
+g_L0:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L3:
+	sd a0, -8(s0)
+	mv a0, a1
+L2:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size g_L0, .-g_L0
+
+	.globl g_L1
+	.type g_L1, @function
+g_L1:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L6:
+	sd a0, -8(s0)
+	mv a0, a1
+L5:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size g_L1, .-g_L1
+
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L9:
+	sd a0, -8(s0)
+	li a0, 0
+L8:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L4_name:
+	.quad 1
+	.ascii "g"
+	.p2align 3
+L4:
+	.quad L4_name
+	.quad 0
+	.quad 0
+	.p2align 3
+L7_name:
+	.quad 1
+	.ascii "g"
+	.p2align 3
+L7:
+	.quad L7_name
+	.quad 0
+	.quad 0
+	.p2align 3
+L10_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L10:
+	.quad L10_name
+	.quad 0
+	.quad 0
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L4
+	.quad L7
+	.quad L10
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,46 @@
+	.text
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L2:
+	sd a0, -8(s0)
+	li a0, 16
+	la a1, L0
+	call tig_allocRecord
+L4:
+	sd zero, 0(a0)
+	sd zero, 8(a0)
+L1:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.p2align 3
+L0:
+	.quad 2
+	.ascii "np"
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L3_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L3:
+	.quad L3_name
+	.quad 0
+	.quad 1
+	.quad L4
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L3
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
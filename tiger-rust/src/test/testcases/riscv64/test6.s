@@ -0,0 +1,115 @@
+	.text
+	.globl do_nothing1_L0
+	.type do_nothing1_L0, @function
+do_nothing1_L0:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L5:
+	sd a0, -8(s0)
+	ld a0, -8(s0)
+	addi a1, a1, 1
+	call do_nothing2_L1
+L7:
+L4:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size do_nothing1_L0, .-do_nothing1_L0
+
+	.globl do_nothing2_L1
+	.type do_nothing2_L1, @function
+do_nothing2_L1:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L9:
+	sd a0, -8(s0)
+	ld a0, -8(s0)
+	la a2, L2
+	call do_nothing1_L0
+L11:
+L8:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size do_nothing2_L1, .-do_nothing2_L1
+
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L13:
+	sd a0, -8(s0)
+	li a1, 0
+	la a2, L3
+	mv a0, s0
+	call do_nothing1_L0
+L15:
+L12:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.p2align 3
+L2:
+	.quad 3
+	.ascii "str"
+	.p2align 3
+L3:
+	.quad 4
+	.ascii "str2"
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L6_name:
+	.quad 11
+	.ascii "do_nothing1"
+	.p2align 3
+L6:
+	.quad L6_name
+	.quad 0
+	.quad 1
+	.quad L7
+	.p2align 3
+L10_name:
+	.quad 11
+	.ascii "do_nothing2"
+	.p2align 3
+L10:
+	.quad L10_name
+	.quad 0
+	.quad 1
+	.quad L11
+	.p2align 3
+L14_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L14:
+	.quad L14_name
+	.quad 0
+	.quad 1
+	.quad L15
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L6
+	.quad L10
+	.quad L14
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,121 @@
+	.text
+	.globl do_nothing1_L0
+	.type do_nothing1_L0, @function
+do_nothing1_L0:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L6:
+	sd a0, -8(s0)
+	ld a0, -8(s0)
+	addi a1, a1, 1
+	call do_nothing2_L1
+L8:
+	li a0, 0
+L5:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size do_nothing1_L0, .-do_nothing1_L0
+
+	.globl do_nothing2_L1
+	.type do_nothing2_L1, @function
+do_nothing2_L1:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L10:
+	sd a0, -8(s0)
+	ld a0, -8(s0)
+	la a2, L2
+	call do_nothing1_L0
+L12:
+	la a0, L3
+L9:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size do_nothing2_L1, .-do_nothing2_L1
+
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L14:
+	sd a0, -8(s0)
+	li a1, 0
+	la a2, L4
+	mv a0, s0
+	call do_nothing1_L0
+L16:
+L13:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.p2align 3
+L2:
+	.quad 3
+	.ascii "str"
+	.p2align 3
+L3:
+	.quad 1
+	.ascii " "
+	.p2align 3
+L4:
+	.quad 4
+	.ascii "str2"
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L7_name:
+	.quad 11
+	.ascii "do_nothing1"
+	.p2align 3
+L7:
+	.quad L7_name
+	.quad 0
+	.quad 1
+	.quad L8
+	.p2align 3
+L11_name:
+	.quad 11
+	.ascii "do_nothing2"
+	.p2align 3
+L11:
+	.quad L11_name
+	.quad 0
+	.quad 1
+	.quad L12
+	.p2align 3
+L15_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L15:
+	.quad L15_name
+	.quad 0
+	.quad 1
+	.quad L16
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L7
+	.quad L11
+	.quad L15
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,57 @@
+	.text
+	.globl tigermain
+	.type tigermain, @function
+tigermain:
+	addi sp, sp, -16
+	sd ra, 8(sp)
+	sd s0, 0(sp)
+	mv s0, sp
+	addi sp, sp, -16
+L6:
+	sd a0, -8(s0)
+	li t1, 65536
+	li t0, 65536
+	mul t0, t1, t0
+	beq t0, zero, L0
+L1:
+	la a0, L4
+L2:
+	call tig_print
+L8:
+	j L5
+L0:
+	la a0, L3
+	j L2
+L5:
+	mv sp, s0
+	ld ra, 8(sp)
+	ld s0, 0(sp)
+	addi sp, sp, 16
+	ret
+	.size tigermain, .-tigermain
+
+	.section .rodata
+	.p2align 3
+L3:
+	.quad 3
+	.ascii "32\012"
+	.p2align 3
+L4:
+	.quad 3
+	.ascii "64\012"
+	.section .data.rel.ro,"aw"
+	.p2align 3
+L7_name:
+	.quad 9
+	.ascii "tigermain"
+	.p2align 3
+L7:
+	.quad L7_name
+	.quad 0
+	.quad 1
+	.quad L8
+	.globl tig_frame_maps
+tig_frame_maps:
+	.quad L7
+	.quad 0
+	.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,389 @@
+use std::fs::{self, metadata};
+
+use super::util::all_path;
+
+use crate::escape::escape::find_escape;
+use crate::frame::frame::{Access as FrameAccess, Frame};
+use crate::frame::x86_64::X86_64Frame;
+use crate::lexer::lexer::ValueInfo;
+use crate::parser::ast::*;
+use crate::parser::parser::compile;
+use crate::parser::visitor::{self, Visitor};
+use crate::semant::semant::trans_prog;
+use crate::semant::tast::FunKind;
+use crate::temp::temp::Label;
+use crate::translate::translate::{translate, Access, Frag, Level};
+use crate::tree::tree::{self, BinOp, Stm};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+/// Lays out every function of a program and records, for each variable use,
+/// how many static links lead to the declaring frame.
+struct Layout {
+    level: Level<X86_64Frame>,
+    env: Vec<(String, Access<X86_64Frame>)>,
+    uses: Vec<(String, usize, FrameAccess)>,
+}
+
+impl Layout {
+    fn run(input: &str) -> Layout {
+        let exp = compile(input).unwrap();
+        find_escape(&exp);
+        let mut layout = Layout {
+            level: Level::new_level(&Level::Outermost, Label::named("tigermain"), &[]),
+            env: Vec::new(),
+            uses: Vec::new(),
+        };
+        layout.visit_exp(&exp);
+        layout
+    }
+
+    fn use_var(&mut self, id: &str) {
+        let (_, access) = self.env.iter().rev().find(|(name, _)| name == id).unwrap();
+        let hops = self.level.static_links(&access.level).len();
+        self.uses.push((String::from(id), hops, access.access));
+    }
+
+    fn for_var(&mut self, id: &ValueInfo<String>, escape: &Escape, start: &Exp, end: &Exp) {
+        self.visit_exp(start);
+        self.visit_exp(end);
+        let access = self.level.alloc_local(escape.get());
+        self.env.push((id.v.clone(), access));
+    }
+
+    fn hops(&self, id: &str) -> Vec<usize> {
+        self.uses
+            .iter()
+            .filter(|(name, _, _)| name == id)
+            .map(|(_, hops, _)| *hops)
+            .collect()
+    }
+}
+
+fn fields(node: &TyFields) -> Vec<(&str, bool)> {
+    let mut fields = Vec::new();
+    let mut next = match node {
+        TyFields::Empty => None,
+        TyFields::Some(some) => Some(some.as_ref()),
+    };
+    while let Some(some) = next {
+        next = match some {
+            SomeTyFields::Some { id, escape, .. } => {
+                fields.push((id.v.as_str(), escape.get()));
+                None
+            }
+            SomeTyFields::List {
+                id, escape, tail, ..
+            } => {
+                fields.push((id.v.as_str(), escape.get()));
+                Some(tail)
+            }
+        };
+    }
+    fields
+}
+
+impl<'ast> Visitor<'ast> for Layout {
+    fn visit_type_dec(&mut self, _: &'ast TypeDec) {}
+
+    fn visit_fun_dec(&mut self, node: &'ast FunDec) {
+        let (id, params, body) = match node {
+            FunDec::Proc(id, params, body) => (id, params, body),
+            FunDec::Func {
+                id, fields, exp, ..
+            } => (id, fields, exp),
+        };
+        let params = fields(params);
+        let escapes: Vec<bool> = params.iter().map(|(_, escape)| *escape).collect();
+        let level = Level::new_level(&self.level, Label::named(&id.v), &escapes);
+        let parent = std::mem::replace(&mut self.level, level);
+        let scope = self.env.len();
+        for ((name, _), access) in params.iter().zip(self.level.formals()) {
+            self.env.push((String::from(*name), access));
+        }
+        self.visit_exp(body);
+        self.env.truncate(scope);
+        self.level = parent;
+    }
+
+    fn visit_var_dec(&mut self, node: &'ast VarDec) {
+        visitor::walk_var_dec(self, node);
+        let (id, escape) = match node {
+            VarDec::DefaultInit(id, _, escape) => (id, escape),
+            VarDec::TypeInit { var_id, escape, .. } => (var_id, escape),
+        };
+        let access = self.level.alloc_local(escape.get());
+        self.env.push((id.v.clone(), access));
+    }
+
+    fn visit_match(&mut self, node: &'ast Match) {
+        match node {
+            Match::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            } => {
+                self.for_var(id, escape, start, end);
+                self.visit_match(body);
+                self.env.pop();
+            }
+            _ => visitor::walk_match(self, node),
+        }
+    }
+
+    fn visit_unmatch(&mut self, node: &'ast Unmatch) {
+        match node {
+            Unmatch::For {
+                id,
+                escape,
+                start,
+                end,
+                body,
+            } => {
+                self.for_var(id, escape, start, end);
+                self.visit_unmatch(body);
+                self.env.pop();
+            }
+            _ => visitor::walk_unmatch(self, node),
+        }
+    }
+
+    fn visit_lvalue(&mut self, node: &'ast LValue) {
+        if let LValue::Id(id) = node {
+            self.use_var(&id.v);
+        }
+        visitor::walk_lvalue(self, node)
+    }
+
+    fn visit_refer(&mut self, node: &'ast Refer) {
+        if let Refer::Field(id, _) | Refer::Array(id, _) = node {
+            self.use_var(&id.v);
+        }
+        visitor::walk_refer(self, node)
+    }
+
+    fn visit_meta(&mut self, node: &'ast Meta) {
+        match node {
+            Meta::Id(id) => self.use_var(&id.v),
+            Meta::Let(_, _) => {
+                let scope = self.env.len();
+                visitor::walk_meta(self, node);
+                self.env.truncate(scope);
+                return;
+            }
+            _ => (),
+        }
+        visitor::walk_meta(self, node)
+    }
+}
+
+#[test]
+fn test_x86_64_frame() {
+    let mut frame = X86_64Frame::new(Label::named("f"), &[true, false, true]);
+    let formals = frame.formals().to_vec();
+    assert_eq!(formals[0], FrameAccess::InFrame(-8));
+    assert!(matches!(formals[1], FrameAccess::InReg(_)));
+    assert_eq!(formals[2], FrameAccess::InFrame(-16));
+    assert_eq!(frame.alloc_local(true), FrameAccess::InFrame(-24));
+    assert!(matches!(frame.alloc_local(false), FrameAccess::InReg(_)));
+    assert_eq!(frame.name(), &Label::named("f"));
+}
+
+#[test]
+fn test_level_static_link() {
+    let main = Level::<X86_64Frame>::new_level(&Level::Outermost, Label::named("main"), &[]);
+    let f = Level::new_level(&main, Label::named("f"), &[false, true]);
+    let g = Level::new_level(&f, Label::named("g"), &[]);
+    assert_eq!(f.static_link(), FrameAccess::InFrame(-8));
+    assert_eq!(f.formals().len(), 2);
+    assert!(matches!(f.formals()[0].access, FrameAccess::InReg(_)));
+    assert_eq!(f.formals()[1].access, FrameAccess::InFrame(-16));
+    assert!(f.formals()[1].level == f);
+    assert!(g.parent() == Some(&f));
+    assert_eq!(g.static_links(&g).len(), 0);
+    assert_eq!(g.static_links(&f), vec![g.static_link()]);
+    assert_eq!(
+        g.static_links(&main),
+        vec![g.static_link(), f.static_link()]
+    );
+}
+
+#[test]
+fn test_nested_access() {
+    let layout = Layout::run(
+        "let var a := 1 \
+         function f(x: int, y: int) : int = \
+           let function g(z: int) : int = a + x + z in g(y) end \
+         in f(a, 2) end",
+    );
+    assert_eq!(layout.hops("a"), vec![2, 0]);
+    assert_eq!(layout.hops("x"), vec![1]);
+    assert_eq!(layout.hops("y"), vec![0]);
+    assert_eq!(layout.hops("z"), vec![0]);
+    for (name, _, access) in &layout.uses {
+        let escapes = matches!(name.as_str(), "a" | "x");
+        assert_eq!(
+            matches!(access, FrameAccess::InFrame(_)),
+            escapes,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_queens_access() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let layout = Layout::run(&input);
+    assert!(layout.hops("N").iter().all(|hops| *hops == 1 || *hops == 0));
+    assert!(layout.hops("row").iter().all(|hops| *hops == 1));
+    assert!(layout.hops("c").iter().all(|hops| *hops == 0));
+    assert!(layout.hops("i").iter().all(|hops| *hops == 0));
+}
+
+fn translate_source(input: &str) -> Vec<Frag<X86_64Frame>> {
+    let program = trans_prog(&compile(input).unwrap()).unwrap();
+    translate(&program)
+}
+
+fn proc_body<'a>(frags: &'a [Frag<X86_64Frame>], name: &str) -> &'a Stm {
+    frags
+        .iter()
+        .find_map(|frag| match frag {
+            Frag::Proc { body, frame } if frame.name().name().starts_with(name) => Some(body),
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn flatten(stm: &Stm, out: &mut Vec<Stm>) {
+    match stm {
+        Stm::Seq(a, b) => {
+            flatten(a, out);
+            flatten(b, out);
+        }
+        Stm::Move(_, src) => {
+            if let tree::Exp::ESeq(s, _) = src.as_ref() {
+                flatten(s, out);
+            }
+            out.push(stm.clone());
+        }
+        _ => out.push(stm.clone()),
+    }
+}
+
+fn stm_calls(stm: &Stm, out: &mut Vec<(Label, Vec<tree::Exp>)>) {
+    match stm {
+        Stm::Seq(a, b) => {
+            stm_calls(a, out);
+            stm_calls(b, out);
+        }
+        Stm::Move(a, b) | Stm::CJump(_, a, b, _, _) => {
+            exp_calls(a, out);
+            exp_calls(b, out);
+        }
+        Stm::Exp(exp) | Stm::Jump(exp, _) => exp_calls(exp, out),
+        Stm::Label(_) | Stm::Pos(_) => (),
+    }
+}
+
+fn exp_calls(exp: &tree::Exp, out: &mut Vec<(Label, Vec<tree::Exp>)>) {
+    match exp {
+        tree::Exp::Call(f, args) => {
+            if let tree::Exp::Name(label) = f.as_ref() {
+                out.push((label.clone(), args.clone()));
+            }
+            args.iter().for_each(|arg| exp_calls(arg, out));
+        }
+        tree::Exp::BinOp(_, a, b) => {
+            exp_calls(a, out);
+            exp_calls(b, out);
+        }
+        tree::Exp::Mem(a) => exp_calls(a, out),
+        tree::Exp::ESeq(s, e) => {
+            stm_calls(s, out);
+            exp_calls(e, out);
+        }
+        _ => (),
+    }
+}
+
+#[test]
+fn test_translate_all() {
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        let program = match compile(&input).map(|exp| trans_prog(&exp)) {
+            Ok(Ok(program)) => program,
+            _ => continue,
+        };
+        println!("{}", input_path);
+        let frags = translate::<X86_64Frame>(&program);
+        let procs: Vec<&X86_64Frame> = frags
+            .iter()
+            .filter_map(|frag| match frag {
+                Frag::Proc { frame, .. } => Some(frame),
+                Frag::String { .. } => None,
+            })
+            .collect();
+        let user_funcs = program
+            .funcs
+            .iter()
+            .filter(|f| matches!(f.kind, FunKind::User { .. }))
+            .count();
+        assert_eq!(procs.len(), user_funcs);
+        assert_eq!(procs.last().unwrap().name(), &Label::named("tigermain"));
+    }
+}
+
+#[test]
+fn test_translate_short_circuit() {
+    let frags =
+        translate_source("let var a := 1 var b := 2 in if a < 1 & b | a then print(\"x\") end");
+    let ir = proc_body(&frags, "tigermain").to_tree_string();
+    assert!(!ir.contains("BINOP(AND") && !ir.contains("BINOP(OR"));
+    assert_eq!(ir.matches("CJUMP(").count(), 3);
+}
+
+#[test]
+fn test_translate_break() {
+    let frags = translate_source("while 1 do (print(\"x\"); break)");
+    let mut stms = Vec::new();
+    flatten(proc_body(&frags, "tigermain"), &mut stms);
+    let done = match stms.last() {
+        Some(Stm::Label(done)) => done.clone(),
+        _ => panic!("loop does not end with its exit label"),
+    };
+    assert!(stms
+        .iter()
+        .any(|stm| matches!(stm, Stm::Jump(_, labels) if labels[..] == [done.clone()])));
+}
+
+#[test]
+fn test_translate_static_link() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let frags = translate_source(&input);
+    let mut calls = Vec::new();
+    stm_calls(proc_body(&frags, "try"), &mut calls);
+    let (_, args) = calls
+        .iter()
+        .find(|(label, _)| label.name().starts_with("try_"))
+        .unwrap();
+    // try(c+1) passes the static link it was itself given.
+    let link = tree::Exp::Mem(Box::new(tree::Exp::BinOp(
+        BinOp::Plus,
+        Box::new(tree::Exp::Temp(X86_64Frame::fp())),
+        Box::new(tree::Exp::Const(-8)),
+    )));
+    assert_eq!(args[0], link);
+    let strings = frags
+        .iter()
+        .filter(|frag| matches!(frag, Frag::String { .. }))
+        .count();
+    assert_eq!(strings, 4);
+}
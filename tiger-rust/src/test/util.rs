@@ -3,6 +3,37 @@ use std::{
     fs::{metadata, read_dir},
 };
 
+#[cfg(test)]
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{self, Command, Output, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+};
+
+#[cfg(test)]
+use crate::codegen::link::find_runtime;
+#[cfg(test)]
+use crate::parser::parser::compile as parse;
+#[cfg(test)]
+use crate::semant::semant::trans_prog;
+
+pub const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+/// Testcases that recurse forever by design.
+pub const NON_TERMINATING: [&str; 2] = ["test6.tig", "test7.tig"];
+
+/// Testcases whose output depends on integers being 64 bits wide.
+pub const WORD_SIZE_DEPENDENT: [&str; 1] = ["wordsize.tig"];
+
+/// Collecting before every allocation, so a slot missing from a frame
+/// descriptor shows up as garbage.
+pub const TORTURE: [(&str, &str); 2] = [("TIGER_HEAP", "1024"), ("TIGER_GC_STRESS", "1")];
+
 pub fn all_path(root_path: &str) -> Result<Vec<String>, Box<dyn Error>> {
     let mut path_list = vec![String::from(root_path)];
     let mut start_index = 0;
@@ -25,3 +56,69 @@ pub fn all_path(root_path: &str) -> Result<Vec<String>, Box<dyn Error>> {
     }
     return Ok(path_list);
 }
+
+#[cfg(test)]
+/// The testcases that compile, by file name.
+pub fn testcases() -> Vec<(String, String)> {
+    let mut testcases = Vec::new();
+    for input_path in all_path(TEST_DATA_PATH).unwrap() {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path).unwrap();
+        if let Ok(Ok(_)) = parse(&input).map(|exp| trans_prog(&exp)) {
+            let file_name = input_path.strip_prefix(TEST_DATA_PATH).unwrap();
+            testcases.push((file_name.to_string(), input));
+        }
+    }
+    testcases
+}
+
+#[cfg(test)]
+/// A fresh path in the temporary directory for the file `name`, unique
+/// across the tests running at once.
+pub fn scratch_path(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let index = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("tiger-{}-{}-{}", process::id(), index, name))
+}
+
+#[cfg(test)]
+/// The runtime library, built once per test run.
+pub fn runtime() -> &'static Path {
+    static RUNTIME: OnceLock<PathBuf> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+        let status = Command::new(cargo)
+            .args(["build", "--quiet", "-p", "tiger-runtime"])
+            .status()
+            .unwrap();
+        assert!(status.success(), "cannot build the runtime");
+        find_runtime().unwrap()
+    })
+}
+
+#[cfg(test)]
+/// Runs the executable at `exe_path` on `stdin` with the environment
+/// variables `vars`, then removes it.
+pub fn run_exe(exe_path: &Path, stdin: &str, vars: &[(&str, &str)]) -> Output {
+    let mut child = Command::new(exe_path)
+        .envs(vars.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Programs that exit without reading all of stdin close the pipe early.
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    let output = child.wait_with_output().unwrap();
+    fs::remove_file(exe_path).unwrap();
+    output
+}
+
+#[cfg(test)]
+/// The standard output of a run, which must have succeeded.
+pub fn stdout(output: Output) -> String {
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
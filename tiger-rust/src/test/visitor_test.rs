@@ -0,0 +1,127 @@
+use std::fs::{self, metadata};
+
+use super::util::all_path;
+use crate::lexer::lexer::ValueInfo;
+use crate::parser::ast::{Exp, Item, Meta};
+use crate::parser::fold::{self, Fold};
+use crate::parser::parser::compile;
+use crate::parser::visitor::{self, Visitor, VisitorMut};
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+#[derive(Default)]
+struct CountInts {
+    ints: Vec<u64>,
+    exps: usize,
+}
+
+impl<'ast> Visitor<'ast> for CountInts {
+    fn visit_exp(&mut self, node: &'ast Exp) {
+        self.exps += 1;
+        visitor::walk_exp(self, node)
+    }
+
+    fn visit_meta(&mut self, node: &'ast Meta) {
+        if let Meta::Int(i) = node {
+            self.ints.push(i.v);
+        }
+        visitor::walk_meta(self, node)
+    }
+}
+
+struct DoubleInts;
+
+impl VisitorMut for DoubleInts {
+    fn visit_meta_mut(&mut self, node: &mut Meta) {
+        if let Meta::Int(i) = node {
+            i.v *= 2;
+        }
+        visitor::walk_meta_mut(self, node)
+    }
+}
+
+struct PlusToMinus;
+
+impl Fold for PlusToMinus {
+    fn fold_item(&mut self, node: Item) -> Item {
+        match fold::walk_item(self, node) {
+            Item::Plus(item, term) => Item::Minus(item, term),
+            other => other,
+        }
+    }
+}
+
+struct RenameIds<'a> {
+    from: &'a str,
+    to: &'a str,
+}
+
+impl<'a> Fold for RenameIds<'a> {
+    fn fold_meta(&mut self, node: Meta) -> Meta {
+        match node {
+            Meta::Id(id) if id.v == self.from => Meta::Id(ValueInfo {
+                info: id.info,
+                v: String::from(self.to),
+            }),
+            other => fold::walk_meta(self, other),
+        }
+    }
+}
+
+fn count(exp: &Exp) -> CountInts {
+    let mut counter = CountInts::default();
+    counter.visit_exp(exp);
+    counter
+}
+
+#[test]
+fn test_visitor() {
+    let exp = compile("let var a := 1 in f(2, a + 3); (4; if a then 5 else 6) end").unwrap();
+    let counter = count(&exp);
+    assert_eq!(counter.ints, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(counter.exps, 9);
+}
+
+#[test]
+fn test_visitor_mut() {
+    let mut exp = compile("a[1] := t[2] of 3 + 4").unwrap();
+    DoubleInts.visit_exp_mut(&mut exp);
+    assert_eq!(count(&exp).ints, vec![2, 4, 6, 8]);
+}
+
+#[test]
+fn test_fold() {
+    let exp = compile("a + 1 + (b + 2) * 3").unwrap();
+    let folded = PlusToMinus.fold_exp(*exp);
+    let expected = compile("a - 1 - (b - 2) * 3").unwrap();
+    assert_eq!(
+        folded.to_json_string().unwrap(),
+        expected.to_json_string().unwrap()
+    );
+
+    let exp = compile("let var a := a in a + b end").unwrap();
+    let renamed = RenameIds { from: "a", to: "c" }.fold_exp(*exp);
+    let json = renamed.to_json_string().unwrap();
+    assert!(json.contains("\"v\":\"c\""));
+    assert!(json.contains("\"v\":\"b\""));
+}
+
+#[test]
+fn test_identity_fold() {
+    struct Identity;
+    impl Fold for Identity {}
+
+    let input_paths = all_path(TEST_DATA_PATH).unwrap();
+    for input_path in input_paths {
+        if metadata(&input_path).unwrap().is_dir() {
+            continue;
+        }
+        let input = fs::read_to_string(&input_path)
+            .unwrap_or_else(|_| panic!("Something went wrong reading {}", input_path));
+        if let Ok(exp) = compile(&input) {
+            let json = exp.to_json_string().unwrap();
+            let folded = Identity.fold_exp(*exp);
+            assert_eq!(json, folded.to_json_string().unwrap());
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Runs the modules of the WebAssembly backend for its tests in an
+//! embedded interpreter, providing the host functions they import. Runtime
+//! errors are reported as the other engines report them, with the
+//! backtrace read off the frames in linear memory.
+
+use std::fmt;
+use std::io::{Read, Write};
+
+use wasmi::core::{HostError, Trap};
+use wasmi::{Caller, Engine, Extern, Linker, Module, Store};
+
+use crate::interp::interp::backtrace;
+use crate::interp::runtime::at;
+use crate::wasm::compile::{DYNAMIC_LINK, IMPORT_MODULE, NAME, STACK_TOP};
+
+struct Host {
+    input: Vec<u8>,
+    read: usize,
+    output: Vec<u8>,
+}
+
+/// Why the program stopped before returning from `main`.
+#[derive(Debug)]
+enum Stop {
+    Exit(i64),
+    Error(String),
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Exit(status) => write!(f, "exit({})", status),
+            Stop::Error(err) => f.write_str(err),
+        }
+    }
+}
+
+impl HostError for Stop {}
+
+fn word(memory: &[u8], address: u64) -> u64 {
+    let address = address as usize;
+    u64::from_le_bytes(memory[address..address + 8].try_into().unwrap())
+}
+
+fn string(memory: &[u8], address: i64) -> &[u8] {
+    let start = address as usize + 8;
+    &memory[start..start + word(memory, address as u64) as usize]
+}
+
+fn memory<'a>(caller: &'a mut Caller<'_, Host>) -> (&'a mut [u8], &'a mut Host) {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory);
+    memory.unwrap().data_and_store_mut(caller)
+}
+
+/// `msg` at `pos`, with the functions whose frames are on the stack.
+fn error(caller: &mut Caller<'_, Host>, pos: i64, msg: &str) -> Trap {
+    let sp = caller.get_export("sp").and_then(Extern::into_global);
+    let mut frame = sp.unwrap().get(&*caller).i32().unwrap() as u32 as u64;
+    let (memory, _) = memory(caller);
+    let mut err = at(pos, msg);
+    while frame != STACK_TOP as u64 {
+        let name = string(memory, word(memory, frame + NAME as u64) as i64);
+        err = backtrace(err, &String::from_utf8_lossy(name));
+        frame = word(memory, frame + DYNAMIC_LINK as u64);
+    }
+    Trap::from(Stop::Error(err))
+}
+
+/// Runs the binary module `binary` on `input`, returning its exit status.
+pub fn run(binary: &[u8], input: &mut dyn Read, output: &mut dyn Write) -> Result<i64, String> {
+    let engine = Engine::default();
+    let module = Module::new(&engine, binary).map_err(|err| err.to_string())?;
+    let mut host = Host {
+        input: Vec::new(),
+        read: 0,
+        output: Vec::new(),
+    };
+    input
+        .read_to_end(&mut host.input)
+        .map_err(|err| err.to_string())?;
+    let mut store = Store::new(&engine, host);
+    let mut linker = <Linker<Host>>::new(&engine);
+    let wrap_err = |err: wasmi::errors::LinkerError| err.to_string();
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            "print",
+            |mut caller: Caller<'_, Host>, s: i64| {
+                let (memory, host) = memory(&mut caller);
+                host.output.extend_from_slice(string(memory, s));
+            },
+        )
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(IMPORT_MODULE, "flush", || {})
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(IMPORT_MODULE, "getchar", |mut caller: Caller<'_, Host>| {
+            let host = caller.data_mut();
+            match host.input.get(host.read) {
+                Some(byte) => {
+                    host.read += 1;
+                    *byte as i64
+                }
+                None => -1,
+            }
+        })
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(IMPORT_MODULE, "exit", |status: i64| -> Result<(), Trap> {
+            Err(Trap::from(Stop::Exit(status)))
+        })
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            "nilRecord",
+            |mut caller: Caller<'_, Host>, pos: i64| -> Result<(), Trap> {
+                Err(error(&mut caller, pos, "nil record dereferenced"))
+            },
+        )
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            "indexOutOfRange",
+            |mut caller: Caller<'_, Host>, i: i64, size: i64, pos: i64| -> Result<(), Trap> {
+                let msg = format!("index {} out of range for array of size {}", i, size);
+                Err(error(&mut caller, pos, &msg))
+            },
+        )
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            "divisionByZero",
+            |mut caller: Caller<'_, Host>, pos: i64| -> Result<(), Trap> {
+                Err(error(&mut caller, pos, "division by zero"))
+            },
+        )
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            "chrOutOfRange",
+            |mut caller: Caller<'_, Host>, i: i64, pos: i64| -> Result<(), Trap> {
+                Err(error(&mut caller, pos, &format!("chr({}) out of range", i)))
+            },
+        )
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            "substringOutOfRange",
+            |mut caller: Caller<'_, Host>, s: i64, first: i64, n: i64, pos: i64| {
+                let s = {
+                    let (memory, _) = memory(&mut caller);
+                    String::from_utf8_lossy(string(memory, s)).into_owned()
+                };
+                let msg = format!("substring({:?}, {}, {}) out of range", s, first, n);
+                Err::<(), Trap>(error(&mut caller, pos, &msg))
+            },
+        )
+        .map_err(wrap_err)?;
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            "negativeArraySize",
+            |mut caller: Caller<'_, Host>, size: i64, pos: i64| -> Result<(), Trap> {
+                let msg = format!("array size {} is negative", size);
+                Err(error(&mut caller, pos, &msg))
+            },
+        )
+        .map_err(wrap_err)?;
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .and_then(|instance| instance.start(&mut store))
+        .map_err(|err| err.to_string())?;
+    let main = instance
+        .get_typed_func::<(), ()>(&store, "main")
+        .map_err(|err| err.to_string())?;
+    let status = match main.call(&mut store, ()) {
+        Ok(()) => Ok(0),
+        Err(trap) => match trap.downcast_ref::<Stop>() {
+            Some(Stop::Exit(status)) => Ok(*status),
+            Some(Stop::Error(err)) => Err(err.clone()),
+            None => Err(trap.to_string()),
+        },
+    };
+    output
+        .write_all(&store.data().output)
+        .map_err(|err| err.to_string())?;
+    status
+}
@@ -0,0 +1,103 @@
+use std::fs;
+
+use super::util::{testcases, NON_TERMINATING};
+use super::wasm_host::run;
+
+use crate::interp::interp;
+use crate::parser::parser::compile as parse;
+use crate::semant::semant::trans_prog;
+use crate::wasm::compile::compile;
+use crate::wasm::wat::to_wat;
+
+const TEST_DATA_PATH: &str = "./src/test/testcases/tigcases/";
+
+fn compile_wasm(input: &str) -> Vec<u8> {
+    compile(&trans_prog(&parse(input).unwrap()).unwrap()).encode()
+}
+
+/// Runs the module `binary` on `stdin`, returning its exit status and output.
+fn execute(binary: &[u8], stdin: &str) -> Result<(i64, String), String> {
+    let mut output = Vec::new();
+    let status = run(binary, &mut stdin.as_bytes(), &mut output)?;
+    Ok((status, String::from_utf8(output).unwrap()))
+}
+
+#[test]
+fn test_wasm_matches_interp() {
+    for (file_name, input) in testcases() {
+        if NON_TERMINATING.contains(&file_name.as_str()) {
+            continue;
+        }
+        let program = trans_prog(&parse(&input).unwrap()).unwrap();
+        let mut expected = Vec::new();
+        let status = interp::run(&program, &mut "".as_bytes(), &mut expected);
+        let expected = status.map(|status| (status, String::from_utf8(expected).unwrap()));
+        let module = compile(&program);
+        assert_eq!(execute(&module.encode(), ""), expected, "{}", file_name);
+        let binary = wat::parse_str(to_wat(&module)).unwrap();
+        assert_eq!(execute(&binary, ""), expected, "{}", file_name);
+    }
+}
+
+#[test]
+fn test_wasm_queens() {
+    let input = fs::read_to_string(format!("{}queens.tig", TEST_DATA_PATH)).unwrap();
+    let (status, output) = execute(&compile_wasm(&input), "").unwrap();
+    assert_eq!(status, 0);
+    assert_eq!(output.lines().count(), 92 * 9);
+    assert!(output.starts_with(" O . . . . . . .\n . . . . O . . .\n"));
+}
+
+#[test]
+fn test_wasm_merge() {
+    let input = fs::read_to_string(format!("{}merge.tig", TEST_DATA_PATH)).unwrap();
+    assert_eq!(
+        execute(&compile_wasm(&input), "1 4 9 ;\n2 3 10 11 ;\n"),
+        Ok((0, String::from("1 2 3 4 9 10 11 \n")))
+    );
+}
+
+#[test]
+fn test_wasm_calls() {
+    // Static links two levels up, and escaping parameters.
+    let input = "let var base := 100 \
+                   function many(a: int, b: int, c: int, d: int, e: int, \
+                                 f: int, g: int, h: int, i: int, j: int): int = \
+                     let function inner(x: int): int = x + base + j \
+                     in inner(a + b * 2 + c * 3 + d * 4 + e * 5 + f * 6 + g * 7 \
+                              + h * 8 + i * 9 + j * 10) end \
+                 in exit(many(1, 2, 3, 4, 5, 6, 7, 8, 9, 10) - 300) end";
+    assert_eq!(execute(&compile_wasm(input), ""), Ok((195, String::new())));
+}
+
+#[test]
+fn test_wasm_strings() {
+    let input = "let var s := getchar() var t := concat(s, getchar()) \
+                 in print(t); print(substring(\"hello\", 1, 3)); \
+                    print(chr(ord(\"a\") + size(t))); \
+                    if t < \"xz\" & \"ab\" < \"abc\" & \"b\" > \"abc\" & t = \"xy\" \
+                    then print(\"!\"); \
+                    print(getchar()); print(getchar()); print(\"|\"); \
+                    exit(ord(\"\") + 2 * not(0)) end";
+    assert_eq!(
+        execute(&compile_wasm(input), "xyz"),
+        Ok((1, String::from("xyellc!z|")))
+    );
+}
+
+#[test]
+fn test_wasm_wat() {
+    let wat = to_wat(&compile(
+        &trans_prog(&parse("let function f(x: int): int = x / 2 in print(\"a\\n\") end").unwrap())
+            .unwrap(),
+    ));
+    assert!(wat
+        .starts_with("(module\n  (import \"tiger\" \"print\" (func $tiger.print (param i64)))\n"));
+    assert!(
+        wat.contains("  (func $main (export \"main\")\n    i32.const 0\n    call $tigermain)\n")
+    );
+    assert!(
+        wat.contains("  (data (i32.const 1048576) \"\\02\\00\\00\\00\\00\\00\\00\\00a\\0a\")\n")
+    );
+    assert!(wat.ends_with(")\n"));
+}
@@ -0,0 +1,934 @@
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::error::error::{pack_pos, PosInfo};
+use crate::frame::frame::{self, Frame};
+use crate::semant::tast::*;
+use crate::semant::types::Type;
+use crate::temp::temp::{Label, Temp};
+use crate::tree::tree::{self, seq, BinOp, RelOp, Stm};
+
+/// Nesting level of a Tiger function.
+///
+/// Every level but the outermost owns a frame whose first formal is the
+/// static link: a pointer to the frame of the lexically enclosing function.
+pub enum Level<F: Frame> {
+    Outermost,
+    Level(Rc<LevelData<F>>),
+}
+
+pub struct LevelData<F: Frame> {
+    parent: Level<F>,
+    frame: RefCell<F>,
+}
+
+/// A variable together with the level that declared it.
+pub struct Access<F: Frame> {
+    pub level: Level<F>,
+    pub access: frame::Access,
+}
+
+impl<F: Frame> Clone for Level<F> {
+    fn clone(&self) -> Self {
+        match self {
+            Level::Outermost => Level::Outermost,
+            Level::Level(data) => Level::Level(Rc::clone(data)),
+        }
+    }
+}
+
+impl<F: Frame> PartialEq for Level<F> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Level::Outermost, Level::Outermost) => true,
+            (Level::Level(a), Level::Level(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl<F: Frame> Clone for Access<F> {
+    fn clone(&self) -> Self {
+        Access {
+            level: self.level.clone(),
+            access: self.access,
+        }
+    }
+}
+
+impl<F: Frame> Level<F> {
+    /// A function nested in `parent`; `formals` are the escape flags of its
+    /// parameters, not counting the static link.
+    pub fn new_level(parent: &Level<F>, name: Label, formals: &[bool]) -> Level<F> {
+        let mut escapes = vec![true];
+        escapes.extend_from_slice(formals);
+        Level::Level(Rc::new(LevelData {
+            parent: parent.clone(),
+            frame: RefCell::new(F::new(name, &escapes)),
+        }))
+    }
+
+    pub fn parent(&self) -> Option<&Level<F>> {
+        match self {
+            Level::Outermost => None,
+            Level::Level(data) => Some(&data.parent),
+        }
+    }
+
+    pub fn frame(&self) -> Ref<'_, F> {
+        self.data().frame.borrow()
+    }
+
+    /// Accesses of the declared parameters, without the static link.
+    pub fn formals(&self) -> Vec<Access<F>> {
+        self.frame().formals()[1..]
+            .iter()
+            .map(|access| Access {
+                level: self.clone(),
+                access: *access,
+            })
+            .collect()
+    }
+
+    pub fn static_link(&self) -> frame::Access {
+        self.frame().formals()[0]
+    }
+
+    pub fn alloc_local(&self, escape: bool) -> Access<F> {
+        Access {
+            level: self.clone(),
+            access: self.data().frame.borrow_mut().alloc_local(escape),
+        }
+    }
+
+    pub fn add_pointer(&self, access: frame::Access) {
+        self.data().frame.borrow_mut().add_pointer(access)
+    }
+
+    /// The static links to follow, innermost first, to reach the frame of
+    /// `decl` from code running at this level. Empty when `decl` is this
+    /// level; panics when `decl` does not enclose it.
+    pub fn static_links(&self, decl: &Level<F>) -> Vec<frame::Access> {
+        let mut links = Vec::new();
+        let mut level = self;
+        while level != decl {
+            links.push(level.static_link());
+            level = level
+                .parent()
+                .expect("declaring level does not enclose the use");
+        }
+        links
+    }
+
+    fn data(&self) -> &LevelData<F> {
+        match self {
+            Level::Outermost => panic!("the outermost level has no frame"),
+            Level::Level(data) => data,
+        }
+    }
+}
+
+/// A unit of output for the back end.
+pub enum Frag<F: Frame> {
+    /// A function body, with the frame it runs in. The body does not yet
+    /// save registers or move formals into place.
+    Proc { body: Stm, frame: F },
+    /// A string literal, with its decoded contents.
+    String { label: Label, value: String },
+}
+
+impl<F: Frame> Frag<F> {
+    pub fn to_tree_string(&self) -> String {
+        match self {
+            Frag::Proc { body, frame } => {
+                format!("PROC {}:\n{}", frame.name(), body.to_tree_string())
+            }
+            Frag::String { label, value } => format!("STRING {}: {:?}\n", label, value),
+        }
+    }
+}
+
+/// An expression being translated, kept in the form that suits how it is
+/// used: as a value, as a statement, or as a conditional jump.
+enum Exp {
+    Ex(tree::Exp),
+    Nx(Stm),
+    Cx(Box<dyn FnOnce(Label, Label) -> Stm>),
+}
+
+fn un_ex(exp: Exp) -> tree::Exp {
+    match exp {
+        Exp::Ex(exp) => exp,
+        Exp::Nx(stm) => tree::Exp::ESeq(Box::new(stm), Box::new(tree::Exp::Const(0))),
+        Exp::Cx(gen) => {
+            let r = Temp::new();
+            let (t, f) = (Label::new(), Label::new());
+            tree::Exp::ESeq(
+                Box::new(seq(vec![
+                    Stm::Move(Box::new(tree::Exp::Temp(r)), Box::new(tree::Exp::Const(1))),
+                    gen(t.clone(), f.clone()),
+                    Stm::Label(f),
+                    Stm::Move(Box::new(tree::Exp::Temp(r)), Box::new(tree::Exp::Const(0))),
+                    Stm::Label(t),
+                ])),
+                Box::new(tree::Exp::Temp(r)),
+            )
+        }
+    }
+}
+
+fn un_nx(exp: Exp) -> Stm {
+    match exp {
+        Exp::Ex(exp) => Stm::Exp(Box::new(exp)),
+        Exp::Nx(stm) => stm,
+        Exp::Cx(gen) => {
+            let join = Label::new();
+            seq(vec![gen(join.clone(), join.clone()), Stm::Label(join)])
+        }
+    }
+}
+
+fn un_cx(exp: Exp) -> Box<dyn FnOnce(Label, Label) -> Stm> {
+    match exp {
+        Exp::Ex(tree::Exp::Const(0)) => Box::new(|_, f| jump(f)),
+        Exp::Ex(tree::Exp::Const(_)) => Box::new(|t, _| jump(t)),
+        Exp::Ex(exp) => Box::new(move |t, f| {
+            Stm::CJump(
+                RelOp::Ne,
+                Box::new(exp),
+                Box::new(tree::Exp::Const(0)),
+                t,
+                f,
+            )
+        }),
+        Exp::Nx(_) => unreachable!("statement used as a condition"),
+        Exp::Cx(gen) => gen,
+    }
+}
+
+fn jump(label: Label) -> Stm {
+    Stm::Jump(Box::new(tree::Exp::Name(label.clone())), vec![label])
+}
+
+fn cjump(op: RelOp, left: tree::Exp, right: tree::Exp) -> Exp {
+    Exp::Cx(Box::new(move |t, f| {
+        Stm::CJump(op, Box::new(left), Box::new(right), t, f)
+    }))
+}
+
+fn binop(op: BinOp, left: tree::Exp, right: tree::Exp) -> tree::Exp {
+    tree::Exp::BinOp(op, Box::new(left), Box::new(right))
+}
+
+fn mv(dst: tree::Exp, src: tree::Exp) -> Stm {
+    Stm::Move(Box::new(dst), Box::new(src))
+}
+
+/// Field `index` of the record at `base`.
+fn field<F: Frame>(base: tree::Exp, index: usize) -> tree::Exp {
+    tree::Exp::Mem(Box::new(binop(
+        BinOp::Plus,
+        base,
+        tree::Exp::Const(index as i64 * F::WORD_SIZE),
+    )))
+}
+
+/// Element `index` of the array at `base`, past the length word.
+fn element<F: Frame>(base: tree::Exp, index: tree::Exp) -> tree::Exp {
+    tree::Exp::Mem(Box::new(binop(
+        BinOp::Plus,
+        binop(
+            BinOp::Plus,
+            base,
+            binop(BinOp::Mul, index, tree::Exp::Const(F::WORD_SIZE)),
+        ),
+        tree::Exp::Const(F::WORD_SIZE),
+    )))
+}
+
+/// The builtins that can fail, and so take the position of the call as an
+/// extra last argument.
+const FALLIBLE: [&str; 2] = ["chr", "substring"];
+
+/// How many operators of a chain nest in one tree before the value so far
+/// goes to a temp.
+const CHAIN_DEPTH: usize = 64;
+
+/// A test that `left op right` holds, calling the runtime's `name` with
+/// `args` and `pos` to report the error otherwise. The runtime does not
+/// return from the call; a loop of its own after it keeps the values used
+/// past the check from being live across it.
+fn check<F: Frame>(
+    op: RelOp,
+    left: tree::Exp,
+    right: tree::Exp,
+    name: &str,
+    mut args: Vec<tree::Exp>,
+    pos: &PosInfo,
+) -> Stm {
+    let (ok, fail, hang) = (Label::new(), Label::new(), Label::new());
+    args.push(tree::Exp::Const(pack_pos(pos)));
+    seq(vec![
+        Stm::CJump(
+            op,
+            Box::new(left),
+            Box::new(right),
+            ok.clone(),
+            fail.clone(),
+        ),
+        Stm::Label(fail),
+        Stm::Exp(Box::new(F::external_call(name, args))),
+        Stm::Label(hang.clone()),
+        jump(hang),
+        Stm::Label(ok),
+    ])
+}
+
+fn nil_check<F: Frame>(record: tree::Exp, pos: &PosInfo) -> Stm {
+    let nil = tree::Exp::Const(0);
+    check::<F>(RelOp::Ne, record, nil, "nilRecord", vec![], pos)
+}
+
+fn bounds_check<F: Frame>(array: tree::Exp, index: tree::Exp, pos: &PosInfo) -> Stm {
+    let size = tree::Exp::Mem(Box::new(array));
+    let args = vec![index.clone(), size.clone()];
+    check::<F>(RelOp::Ult, index, size, "indexOutOfRange", args, pos)
+}
+
+/// Whether canon can use `exp` as it is, without a temp to hold its value.
+fn is_atom(exp: &tree::Exp) -> bool {
+    match exp {
+        tree::Exp::Temp(_) | tree::Exp::Name(_) | tree::Exp::Const(_) => true,
+        tree::Exp::ESeq(_, exp) => is_atom(exp),
+        _ => false,
+    }
+}
+
+/// Whether evaluating `exp` may call a function. The tree is walked with a
+/// work list, since operator chains can nest deeply.
+fn has_call(exp: &tree::Exp) -> bool {
+    enum Node<'a> {
+        Exp(&'a tree::Exp),
+        Stm(&'a Stm),
+    }
+    let mut work = vec![Node::Exp(exp)];
+    while let Some(node) = work.pop() {
+        match node {
+            Node::Exp(tree::Exp::Call(..)) => return true,
+            Node::Exp(tree::Exp::BinOp(_, a, b)) => work.extend([Node::Exp(a), Node::Exp(b)]),
+            Node::Exp(tree::Exp::Mem(a)) => work.push(Node::Exp(a)),
+            Node::Exp(tree::Exp::ESeq(stm, exp)) => work.extend([Node::Stm(stm), Node::Exp(exp)]),
+            Node::Exp(tree::Exp::Const(_) | tree::Exp::Name(_) | tree::Exp::Temp(_)) => (),
+            Node::Stm(Stm::Move(a, b) | Stm::CJump(_, a, b, _, _)) => {
+                work.extend([Node::Exp(a), Node::Exp(b)])
+            }
+            Node::Stm(Stm::Exp(exp) | Stm::Jump(exp, _)) => work.push(Node::Exp(exp)),
+            Node::Stm(Stm::Seq(a, b)) => work.extend([Node::Stm(a), Node::Stm(b)]),
+            Node::Stm(Stm::Label(_) | Stm::Pos(_)) => (),
+        }
+    }
+    false
+}
+
+/// Translates a type-checked program into fragments for frame type `F`. The
+/// main program comes last, as a procedure labelled `tigermain`.
+pub fn translate<F: Frame>(program: &Program) -> Vec<Frag<F>> {
+    let main = program.func(program.main);
+    let level = Level::new_level(&Level::Outermost, main.label.clone(), &[]);
+    let mut translator = Translator {
+        program,
+        frags: Vec::new(),
+        levels: HashMap::new(),
+        vars: HashMap::new(),
+        descriptors: HashMap::new(),
+        level: level.clone(),
+        done: None,
+    };
+    translator.levels.insert(program.main, level);
+    translator.function(program.main);
+    translator.frags
+}
+
+struct Translator<'p, F: Frame> {
+    program: &'p Program,
+    frags: Vec<Frag<F>>,
+    levels: HashMap<FunId, Level<F>>,
+    vars: HashMap<VarId, Access<F>>,
+    /// Record descriptors emitted so far, by contents.
+    descriptors: HashMap<String, Label>,
+    level: Level<F>,
+    /// Where `break` jumps to in the innermost enclosing loop.
+    done: Option<Label>,
+}
+
+impl<'p, F: Frame> Translator<'p, F> {
+    /// The address of the frame of `level`, seen from the current level.
+    fn frame_address(&self, level: &Level<F>) -> tree::Exp {
+        self.level
+            .static_links(level)
+            .into_iter()
+            .fold(tree::Exp::Temp(F::fp()), |fp, link| F::exp(link, fp))
+    }
+
+    fn simple_var(&self, var: VarId) -> tree::Exp {
+        let access = &self.vars[&var];
+        F::exp(access.access, self.frame_address(&access.level))
+    }
+
+    fn alloc_var(&mut self, var: VarId) -> tree::Exp {
+        let info = self.program.var(var);
+        let access = self.level.alloc_local(info.escape);
+        if self.program.types.is_pointer(info.ty) {
+            self.level.add_pointer(access.access);
+        }
+        self.vars.insert(var, access);
+        self.simple_var(var)
+    }
+
+    /// A fresh temp, noted in the frame if it holds a heap pointer.
+    fn temp(&self, pointer: bool) -> Temp {
+        let temp = Temp::new();
+        if pointer {
+            self.level.add_pointer(frame::Access::InReg(temp));
+        }
+        temp
+    }
+
+    /// `exp` copied into a fresh temp by a statement added to `stms`, unless
+    /// it is a constant.
+    fn atom(&self, exp: tree::Exp, pointer: bool, stms: &mut Vec<Stm>) -> tree::Exp {
+        match exp {
+            tree::Exp::Const(_) | tree::Exp::Name(_) => exp,
+            exp => {
+                let temp = tree::Exp::Temp(self.temp(pointer));
+                stms.push(mv(temp.clone(), exp));
+                temp
+            }
+        }
+    }
+
+    /// The call `call` makes of `args`, each with whether it is a heap
+    /// pointer, marked as made at `pos`. Arguments that call out are moved
+    /// to temps first, so that the mark comes after their own.
+    fn call_at(
+        &self,
+        args: Vec<(tree::Exp, bool)>,
+        pos: &PosInfo,
+        call: impl FnOnce(Vec<tree::Exp>) -> tree::Exp,
+    ) -> tree::Exp {
+        let mut stms = Vec::new();
+        let args = match args.iter().any(|(arg, _)| has_call(arg)) {
+            true => args
+                .into_iter()
+                .map(|(arg, pointer)| self.atom(arg, pointer, &mut stms))
+                .collect(),
+            false => args.into_iter().map(|(arg, _)| arg).collect(),
+        };
+        stms.push(Stm::Pos(pack_pos(pos)));
+        tree::Exp::ESeq(Box::new(seq(stms)), Box::new(call(args)))
+    }
+
+    /// The label of a string with one `p` or `n` per field of `ty`, telling
+    /// the collector which fields hold pointers.
+    fn descriptor(&mut self, ty: Type) -> Label {
+        let types = &self.program.types;
+        let value: String = types
+            .record_fields(ty)
+            .expect("record of non-record type")
+            .iter()
+            .map(|(_, ty)| if types.is_pointer(*ty) { 'p' } else { 'n' })
+            .collect();
+        if let Some(label) = self.descriptors.get(&value) {
+            return label.clone();
+        }
+        let label = Label::new();
+        self.frags.push(Frag::String {
+            label: label.clone(),
+            value: value.clone(),
+        });
+        self.descriptors.insert(value, label.clone());
+        label
+    }
+
+    fn function(&mut self, fun: FunId) {
+        let info = self.program.func(fun);
+        let body = match &info.kind {
+            FunKind::User { body } => body,
+            FunKind::Builtin => unreachable!("builtins have no body"),
+        };
+        let body_pos = body.pos.clone();
+        let level = self.levels[&fun].clone();
+        for (param, access) in info.params.iter().zip(level.formals()) {
+            if self.program.types.is_pointer(self.program.var(*param).ty) {
+                level.add_pointer(access.access);
+            }
+            self.vars.insert(*param, access);
+        }
+        let outer = std::mem::replace(&mut self.level, level);
+        let done = self.done.take();
+        let exp = self.exp(body);
+        let body = if info.result == Type::Unit {
+            un_nx(exp)
+        } else {
+            mv(tree::Exp::Temp(F::rv()), un_ex(exp))
+        };
+        let body = seq(vec![Stm::Pos(pack_pos(&body_pos)), body]);
+        let frame = self.level.frame().clone();
+        self.frags.push(Frag::Proc { body, frame });
+        self.level = outer;
+        self.done = done;
+    }
+
+    fn var(&mut self, var: &TVar) -> tree::Exp {
+        let mut stms = Vec::new();
+        let value = match &var.kind {
+            TVarKind::Simple(id) => return self.simple_var(*id),
+            TVarKind::Field(record, index) => {
+                let base = self.ex(record);
+                let base = self.atom(base, true, &mut stms);
+                stms.push(nil_check::<F>(base.clone(), &record.pos));
+                field::<F>(base, *index)
+            }
+            TVarKind::Subscript(array, index) => {
+                let base = self.ex(array);
+                let base = self.atom(base, true, &mut stms);
+                let i = self.ex(index);
+                let i = self.atom(i, false, &mut stms);
+                stms.push(bounds_check::<F>(base.clone(), i.clone(), &index.pos));
+                element::<F>(base, i)
+            }
+        };
+        tree::Exp::ESeq(Box::new(seq(stms)), Box::new(value))
+    }
+
+    /// The value of `exp`. A heap pointer is always left in a temp noted in
+    /// the frame, so that whatever temp canon copies it to can be traced
+    /// back to one.
+    fn ex(&mut self, exp: &TExp) -> tree::Exp {
+        let value = un_ex(self.exp(exp));
+        if !self.program.types.is_pointer(exp.ty) || is_atom(&value) {
+            return value;
+        }
+        let temp = tree::Exp::Temp(self.temp(true));
+        tree::Exp::ESeq(Box::new(mv(temp.clone(), value)), Box::new(temp))
+    }
+
+    fn nx(&mut self, exp: &TExp) -> Stm {
+        let exp = self.exp(exp);
+        un_nx(exp)
+    }
+
+    fn exp(&mut self, exp: &TExp) -> Exp {
+        match &exp.kind {
+            TExpKind::Int(i) => Exp::Ex(tree::Exp::Const(*i)),
+            TExpKind::String(value) => {
+                let label = Label::new();
+                self.frags.push(Frag::String {
+                    label: label.clone(),
+                    value: value.clone(),
+                });
+                Exp::Ex(tree::Exp::Name(label))
+            }
+            TExpKind::Nil => Exp::Ex(tree::Exp::Const(0)),
+            TExpKind::Var(var) => Exp::Ex(self.var(var)),
+            TExpKind::Call(fun, args) => {
+                let mut args: Vec<(tree::Exp, bool)> = args
+                    .iter()
+                    .map(|arg| (self.ex(arg), self.program.types.is_pointer(arg.ty)))
+                    .collect();
+                let info = self.program.func(*fun);
+                let label = info.label.clone();
+                Exp::Ex(match (&info.kind, info.parent) {
+                    (FunKind::User { .. }, Some(parent)) => {
+                        let link = self.frame_address(&self.levels[&parent]);
+                        args.insert(0, (link, false));
+                        self.call_at(args, &exp.pos, |args| {
+                            tree::Exp::Call(Box::new(tree::Exp::Name(label)), args)
+                        })
+                    }
+                    _ => {
+                        if FALLIBLE.contains(&label.name()) {
+                            args.push((tree::Exp::Const(pack_pos(&exp.pos)), false));
+                        }
+                        self.call_at(args, &exp.pos, |args| F::external_call(label.name(), args))
+                    }
+                })
+            }
+            TExpKind::Op(..) => self.op_chain(exp),
+            TExpKind::And(..) | TExpKind::Or(..) => self.logic_chain(exp),
+            TExpKind::Record(_, fields) => {
+                let r = self.temp(true);
+                let size = fields.len() as i64 * F::WORD_SIZE;
+                let descriptor = self.descriptor(exp.ty);
+                let mut stms = vec![
+                    Stm::Pos(pack_pos(&exp.pos)),
+                    mv(
+                        tree::Exp::Temp(r),
+                        F::external_call(
+                            "allocRecord",
+                            vec![tree::Exp::Const(size), tree::Exp::Name(descriptor)],
+                        ),
+                    ),
+                ];
+                for (index, value) in fields.iter().enumerate() {
+                    let pointer = self.program.types.is_pointer(value.ty);
+                    let value = self.ex(value);
+                    let value = self.atom(value, pointer, &mut stms);
+                    stms.push(mv(field::<F>(tree::Exp::Temp(r), index), value));
+                }
+                Exp::Ex(tree::Exp::ESeq(
+                    Box::new(seq(stms)),
+                    Box::new(tree::Exp::Temp(r)),
+                ))
+            }
+            TExpKind::Array(_, size, init) => {
+                let pointers = self.program.types.is_pointer(init.ty);
+                let args = vec![
+                    (self.ex(size), false),
+                    (self.ex(init), pointers),
+                    (tree::Exp::Const(pointers as i64), false),
+                    (tree::Exp::Const(pack_pos(&exp.pos)), false),
+                ];
+                Exp::Ex(self.call_at(args, &exp.pos, |args| F::external_call("initArray", args)))
+            }
+            TExpKind::Seq(exps) => match exps.split_last() {
+                None => Exp::Nx(Stm::Exp(Box::new(tree::Exp::Const(0)))),
+                Some((last, [])) => self.exp(last),
+                Some((last, init)) => {
+                    let mut stms: Vec<Stm> = init.iter().map(|exp| self.nx(exp)).collect();
+                    if exp.ty == Type::Unit {
+                        stms.push(self.nx(last));
+                        Exp::Nx(seq(stms))
+                    } else {
+                        let last = self.ex(last);
+                        Exp::Ex(tree::Exp::ESeq(Box::new(seq(stms)), Box::new(last)))
+                    }
+                }
+            },
+            TExpKind::Assign(var, value) => {
+                // A store into the heap takes its base, index and value from
+                // fresh temps, in that order, so canon never has to keep the
+                // address of a field across a call: the collector may move
+                // the object meanwhile. The location is checked once all
+                // three are known.
+                let mut stms = Vec::new();
+                let (dst, location) = match &var.kind {
+                    TVarKind::Simple(id) => (self.simple_var(*id), None),
+                    TVarKind::Field(record, index) => {
+                        let base = self.ex(record);
+                        let base = self.atom(base, true, &mut stms);
+                        let check = nil_check::<F>(base.clone(), &record.pos);
+                        (field::<F>(base, *index), Some(check))
+                    }
+                    TVarKind::Subscript(array, index) => {
+                        let base = self.ex(array);
+                        let base = self.atom(base, true, &mut stms);
+                        let i = self.ex(index);
+                        let i = self.atom(i, false, &mut stms);
+                        let check = bounds_check::<F>(base.clone(), i.clone(), &index.pos);
+                        (element::<F>(base, i), Some(check))
+                    }
+                };
+                let pointer = self.program.types.is_pointer(value.ty);
+                let value = self.ex(value);
+                let value = match location {
+                    None => value,
+                    Some(check) => {
+                        let value = self.atom(value, pointer, &mut stms);
+                        stms.push(check);
+                        value
+                    }
+                };
+                stms.push(mv(dst, value));
+                Exp::Nx(seq(stms))
+            }
+            TExpKind::If(test, then, other) => self.if_exp(exp.ty, test, then, other.as_deref()),
+            TExpKind::While(test, body) => {
+                let (test_label, body_label, done) = (Label::new(), Label::new(), Label::new());
+                let test = un_cx(self.exp(test));
+                let outer = self.done.replace(done.clone());
+                let body = self.nx(body);
+                self.done = outer;
+                Exp::Nx(seq(vec![
+                    Stm::Label(test_label.clone()),
+                    Stm::Pos(pack_pos(&exp.pos)),
+                    test(body_label.clone(), done.clone()),
+                    Stm::Label(body_label),
+                    body,
+                    jump(test_label),
+                    Stm::Label(done),
+                ]))
+            }
+            TExpKind::For(var, lo, hi, body) => {
+                let lo = self.ex(lo);
+                let hi = self.ex(hi);
+                let i = self.alloc_var(*var);
+                let limit = tree::Exp::Temp(Temp::new());
+                let (body_label, next, done) = (Label::new(), Label::new(), Label::new());
+                let outer = self.done.replace(done.clone());
+                let body = self.nx(body);
+                self.done = outer;
+                // Test before incrementing so a limit of the largest int does
+                // not overflow the counter.
+                Exp::Nx(seq(vec![
+                    mv(i.clone(), lo),
+                    mv(limit.clone(), hi),
+                    Stm::CJump(
+                        RelOp::Le,
+                        Box::new(i.clone()),
+                        Box::new(limit.clone()),
+                        body_label.clone(),
+                        done.clone(),
+                    ),
+                    Stm::Label(body_label.clone()),
+                    Stm::Pos(pack_pos(&exp.pos)),
+                    body,
+                    Stm::CJump(
+                        RelOp::Lt,
+                        Box::new(i.clone()),
+                        Box::new(limit),
+                        next.clone(),
+                        done.clone(),
+                    ),
+                    Stm::Label(next),
+                    mv(i.clone(), binop(BinOp::Plus, i, tree::Exp::Const(1))),
+                    jump(body_label),
+                    Stm::Label(done),
+                ]))
+            }
+            TExpKind::Break => {
+                let done = self.done.clone().expect("break outside of a loop");
+                Exp::Nx(jump(done))
+            }
+            TExpKind::Let(decs, body) => {
+                let mut stms = Vec::new();
+                for dec in decs {
+                    match dec {
+                        TDec::Var(var, init) => {
+                            let init = self.ex(init);
+                            let var = self.alloc_var(*var);
+                            stms.push(mv(var, init));
+                        }
+                        TDec::Funcs(funs) => {
+                            for fun in funs {
+                                let info = self.program.func(*fun);
+                                let escapes: Vec<bool> = info
+                                    .params
+                                    .iter()
+                                    .map(|param| self.program.var(*param).escape)
+                                    .collect();
+                                let level =
+                                    Level::new_level(&self.level, info.label.clone(), &escapes);
+                                self.levels.insert(*fun, level);
+                            }
+                            for fun in funs {
+                                self.function(*fun);
+                            }
+                        }
+                    }
+                }
+                let body = self.exp(body);
+                if stms.is_empty() {
+                    return body;
+                }
+                match body {
+                    Exp::Ex(body) => Exp::Ex(tree::Exp::ESeq(Box::new(seq(stms)), Box::new(body))),
+                    body => {
+                        stms.push(un_nx(body));
+                        Exp::Nx(seq(stms))
+                    }
+                }
+            }
+        }
+    }
+
+    /// A chain of operators, which nests down its left operands. The spine
+    /// is walked in a loop, and every [`CHAIN_DEPTH`] operators the value so
+    /// far is moved to a temp, so the tree stays shallow for the passes
+    /// after this one.
+    fn op_chain(&mut self, exp: &TExp) -> Exp {
+        let mut spine = Vec::new();
+        let mut node = exp;
+        while let TExpKind::Op(oper, left, right) = &node.kind {
+            spine.push((*oper, left.ty, right.as_ref(), &node.pos));
+            node = left;
+        }
+        let mut stms = Vec::new();
+        let mut left = Exp::Ex(self.ex(node));
+        for (depth, (oper, ty, right, pos)) in spine.into_iter().rev().enumerate() {
+            let mut l = un_ex(left);
+            if depth > 0 && depth % CHAIN_DEPTH == 0 {
+                let temp = tree::Exp::Temp(self.temp(false));
+                stms.push(mv(temp.clone(), l));
+                l = temp;
+            }
+            left = self.op(oper, ty, l, right, pos);
+        }
+        match (stms.is_empty(), left) {
+            (true, left) => left,
+            (false, left) => Exp::Ex(tree::Exp::ESeq(Box::new(seq(stms)), Box::new(un_ex(left)))),
+        }
+    }
+
+    /// A chain of `&` or `|`, which nests down its left operands, as one
+    /// test per operand.
+    fn logic_chain(&mut self, exp: &TExp) -> Exp {
+        let and = matches!(exp.kind, TExpKind::And(..));
+        let mut rights = Vec::new();
+        let mut node = exp;
+        while let TExpKind::And(left, right) | TExpKind::Or(left, right) = &node.kind {
+            if matches!(node.kind, TExpKind::And(..)) != and {
+                break;
+            }
+            rights.push(right.as_ref());
+            node = left;
+        }
+        let mut tests = vec![un_cx(self.exp(node))];
+        for right in rights.into_iter().rev() {
+            tests.push(un_cx(self.exp(right)));
+        }
+        Exp::Cx(Box::new(move |t, f| {
+            // The labels between the tests, made outermost first as the
+            // nested form would.
+            let mut labels: Vec<Label> = (1..tests.len()).map(|_| Label::new()).collect();
+            labels.reverse();
+            let mut stms = Vec::new();
+            let mut tests = tests.into_iter();
+            for z in labels {
+                let test = tests.next().unwrap();
+                stms.push(match and {
+                    true => test(z.clone(), f.clone()),
+                    false => test(t.clone(), z.clone()),
+                });
+                stms.push(Stm::Label(z));
+            }
+            stms.push(tests.next().unwrap()(t, f));
+            seq(stms)
+        }))
+    }
+
+    /// `l oper right`, where `l` is the value of a left operand of type `ty`.
+    fn op(&mut self, oper: Oper, ty: Type, l: tree::Exp, right: &TExp, pos: &PosInfo) -> Exp {
+        let string = self.program.types.actual(ty) == Type::String;
+        let r = self.ex(right);
+        let relop = match oper {
+            Oper::Plus => return Exp::Ex(binop(BinOp::Plus, l, r)),
+            Oper::Minus => return Exp::Ex(binop(BinOp::Minus, l, r)),
+            Oper::Times => return Exp::Ex(binop(BinOp::Mul, l, r)),
+            Oper::Divide => return Exp::Ex(self.divide(l, r, pos)),
+            Oper::Eq => RelOp::Eq,
+            Oper::Neq => RelOp::Ne,
+            Oper::Lt => RelOp::Lt,
+            Oper::Le => RelOp::Le,
+            Oper::Gt => RelOp::Gt,
+            Oper::Ge => RelOp::Ge,
+        };
+        if !string {
+            return cjump(relop, l, r);
+        }
+        match oper {
+            Oper::Eq => cjump(
+                RelOp::Ne,
+                F::external_call("stringEqual", vec![l, r]),
+                tree::Exp::Const(0),
+            ),
+            Oper::Neq => cjump(
+                RelOp::Eq,
+                F::external_call("stringEqual", vec![l, r]),
+                tree::Exp::Const(0),
+            ),
+            _ => cjump(
+                relop,
+                F::external_call("stringCompare", vec![l, r]),
+                tree::Exp::Const(0),
+            ),
+        }
+    }
+
+    /// `l / r`, checking `r` once both are known unless it is a nonzero
+    /// constant. Dividing by -1 negates instead, so that the least integer
+    /// wraps around as in the interpreter rather than trapping.
+    fn divide(&self, l: tree::Exp, r: tree::Exp, pos: &PosInfo) -> tree::Exp {
+        let zero = tree::Exp::Const(0);
+        match r {
+            tree::Exp::Const(-1) => return binop(BinOp::Minus, zero, l),
+            tree::Exp::Const(c) if c != 0 => return binop(BinOp::Div, l, r),
+            _ => (),
+        }
+        let mut stms = Vec::new();
+        let l = self.atom(l, false, &mut stms);
+        let r = self.atom(r, false, &mut stms);
+        stms.push(check::<F>(
+            RelOp::Ne,
+            r.clone(),
+            zero.clone(),
+            "divisionByZero",
+            vec![],
+            pos,
+        ));
+        let quotient = tree::Exp::Temp(self.temp(false));
+        let (negate, divide, join) = (Label::new(), Label::new(), Label::new());
+        stms.extend([
+            Stm::CJump(
+                RelOp::Eq,
+                Box::new(r.clone()),
+                Box::new(tree::Exp::Const(-1)),
+                negate.clone(),
+                divide.clone(),
+            ),
+            Stm::Label(negate),
+            mv(quotient.clone(), binop(BinOp::Minus, zero, l.clone())),
+            jump(join.clone()),
+            Stm::Label(divide),
+            mv(quotient.clone(), binop(BinOp::Div, l, r)),
+            Stm::Label(join),
+        ]);
+        tree::Exp::ESeq(Box::new(seq(stms)), Box::new(quotient))
+    }
+
+    fn if_exp(&mut self, ty: Type, test: &TExp, then: &TExp, other: Option<&TExp>) -> Exp {
+        let test = un_cx(self.exp(test));
+        let (t, f, join) = (Label::new(), Label::new(), Label::new());
+        let other = match other {
+            None => {
+                let then = self.nx(then);
+                return Exp::Nx(seq(vec![
+                    test(t.clone(), join.clone()),
+                    Stm::Label(t),
+                    then,
+                    Stm::Label(join),
+                ]));
+            }
+            Some(other) => other,
+        };
+        if ty == Type::Unit {
+            let then = self.nx(then);
+            let other = self.nx(other);
+            return Exp::Nx(seq(vec![
+                test(t.clone(), f.clone()),
+                Stm::Label(t),
+                then,
+                jump(join.clone()),
+                Stm::Label(f),
+                other,
+                Stm::Label(join),
+            ]));
+        }
+        let r = tree::Exp::Temp(self.temp(self.program.types.is_pointer(ty)));
+        let then = self.ex(then);
+        let other = self.ex(other);
+        Exp::Ex(tree::Exp::ESeq(
+            Box::new(seq(vec![
+                test(t.clone(), f.clone()),
+                Stm::Label(t),
+                mv(r.clone(), then),
+                jump(join.clone()),
+                Stm::Label(f),
+                mv(r.clone(), other),
+                Stm::Label(join),
+            ])),
+            Box::new(r),
+        ))
+    }
+}
@@ -0,0 +1,168 @@
+use crate::tree::tree::{BinOp, Exp, RelOp, Stm};
+
+/// Prints trees in the layout of Appel's `Printtree`.
+struct TreePrinter {
+    out: String,
+}
+
+impl TreePrinter {
+    fn indent(&mut self, d: usize) {
+        for _ in 0..d {
+            self.out.push(' ');
+        }
+    }
+
+    fn say(&mut self, s: &str) {
+        self.out.push_str(s);
+    }
+
+    fn sayln(&mut self, s: &str) {
+        self.out.push_str(s);
+        self.out.push('\n');
+    }
+
+    fn stm(&mut self, node: &Stm, d: usize) {
+        self.indent(d);
+        match node {
+            Stm::Seq(a, b) => {
+                self.sayln("SEQ(");
+                self.stm(a, d + 1);
+                self.sayln(",");
+                self.stm(b, d + 1);
+                self.say(")")
+            }
+            Stm::Label(label) => {
+                self.say("LABEL ");
+                self.say(label.name())
+            }
+            Stm::Jump(exp, _) => {
+                self.sayln("JUMP(");
+                self.exp(exp, d + 1);
+                self.say(")")
+            }
+            Stm::CJump(op, a, b, t, f) => {
+                self.say("CJUMP(");
+                self.say(relop(*op));
+                self.sayln(",");
+                self.exp(a, d + 1);
+                self.sayln(",");
+                self.exp(b, d + 1);
+                self.sayln(",");
+                self.indent(d + 1);
+                self.say(t.name());
+                self.say(",");
+                self.say(f.name());
+                self.say(")")
+            }
+            Stm::Move(a, b) => {
+                self.sayln("MOVE(");
+                self.exp(a, d + 1);
+                self.sayln(",");
+                self.exp(b, d + 1);
+                self.say(")")
+            }
+            Stm::Exp(exp) => {
+                self.sayln("EXP(");
+                self.exp(exp, d + 1);
+                self.say(")")
+            }
+            Stm::Pos(pos) => self.say(&format!("POS {}.{}", pos >> 16, pos & 0xffff)),
+        }
+    }
+
+    fn exp(&mut self, node: &Exp, d: usize) {
+        self.indent(d);
+        match node {
+            Exp::BinOp(op, a, b) => {
+                self.say("BINOP(");
+                self.say(binop(*op));
+                self.sayln(",");
+                self.exp(a, d + 1);
+                self.sayln(",");
+                self.exp(b, d + 1);
+                self.say(")")
+            }
+            Exp::Mem(exp) => {
+                self.sayln("MEM(");
+                self.exp(exp, d + 1);
+                self.say(")")
+            }
+            Exp::Temp(temp) => {
+                self.say("TEMP ");
+                self.say(&temp.to_string())
+            }
+            Exp::ESeq(stm, exp) => {
+                self.sayln("ESEQ(");
+                self.stm(stm, d + 1);
+                self.sayln(",");
+                self.exp(exp, d + 1);
+                self.say(")")
+            }
+            Exp::Name(label) => {
+                self.say("NAME ");
+                self.say(label.name())
+            }
+            Exp::Const(i) => {
+                self.say("CONST ");
+                self.say(&i.to_string())
+            }
+            Exp::Call(f, args) => {
+                self.sayln("CALL(");
+                self.exp(f, d + 1);
+                for arg in args {
+                    self.sayln(",");
+                    self.exp(arg, d + 2);
+                }
+                self.say(")")
+            }
+        }
+    }
+}
+
+fn binop(op: BinOp) -> &'static str {
+    match op {
+        BinOp::Plus => "PLUS",
+        BinOp::Minus => "MINUS",
+        BinOp::Mul => "MUL",
+        BinOp::Div => "DIV",
+        BinOp::And => "AND",
+        BinOp::Or => "OR",
+        BinOp::LShift => "LSHIFT",
+        BinOp::RShift => "RSHIFT",
+        BinOp::ArShift => "ARSHIFT",
+        BinOp::Xor => "XOR",
+    }
+}
+
+fn relop(op: RelOp) -> &'static str {
+    match op {
+        RelOp::Eq => "EQ",
+        RelOp::Ne => "NE",
+        RelOp::Lt => "LT",
+        RelOp::Gt => "GT",
+        RelOp::Le => "LE",
+        RelOp::Ge => "GE",
+        RelOp::Ult => "ULT",
+        RelOp::Ule => "ULE",
+        RelOp::Ugt => "UGT",
+        RelOp::Uge => "UGE",
+    }
+}
+
+impl Stm {
+    pub fn to_tree_string(&self) -> String {
+        let mut printer = TreePrinter { out: String::new() };
+        printer.stm(self, 0);
+        printer.sayln("");
+        printer.out
+    }
+}
+
+impl Exp {
+    pub fn to_tree_string(&self) -> String {
+        let mut printer = TreePrinter { out: String::new() };
+        printer.exp(self, 0);
+        printer.sayln("");
+        printer.out
+    }
+}
@@ -0,0 +1,105 @@
+use crate::temp::temp::{Label, Temp};
+
+/// Expressions of Appel's intermediate representation; they compute a value,
+/// possibly with side effects.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exp {
+    Const(i64),
+    Name(Label),
+    Temp(Temp),
+    BinOp(BinOp, Box<Exp>, Box<Exp>),
+    /// The word at this address; a store when it is the target of a `Move`.
+    Mem(Box<Exp>),
+    Call(Box<Exp>, Vec<Exp>),
+    /// Runs the statement, then yields the expression.
+    ESeq(Box<Stm>, Box<Exp>),
+}
+
+/// Statements of the intermediate representation: side effects and control
+/// flow.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stm {
+    /// Stores the source (second) into the `Temp` or `Mem` target (first).
+    Move(Box<Exp>, Box<Exp>),
+    Exp(Box<Exp>),
+    /// Jumps to the computed address, which is one of the listed labels.
+    Jump(Box<Exp>, Vec<Label>),
+    CJump(RelOp, Box<Exp>, Box<Exp>, Label, Label),
+    Seq(Box<Stm>, Box<Stm>),
+    Label(Label),
+    /// Marks the code that follows as coming from this source position,
+    /// packed as for the runtime. It does nothing itself.
+    Pos(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinOp {
+    Plus,
+    Minus,
+    Mul,
+    Div,
+    And,
+    Or,
+    LShift,
+    RShift,
+    ArShift,
+    Xor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelOp {
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Ult,
+    Ule,
+    Ugt,
+    Uge,
+}
+
+impl RelOp {
+    /// The relation that holds exactly when this one does not.
+    pub fn negate(self) -> RelOp {
+        match self {
+            RelOp::Eq => RelOp::Ne,
+            RelOp::Ne => RelOp::Eq,
+            RelOp::Lt => RelOp::Ge,
+            RelOp::Ge => RelOp::Lt,
+            RelOp::Gt => RelOp::Le,
+            RelOp::Le => RelOp::Gt,
+            RelOp::Ult => RelOp::Uge,
+            RelOp::Uge => RelOp::Ult,
+            RelOp::Ugt => RelOp::Ule,
+            RelOp::Ule => RelOp::Ugt,
+        }
+    }
+
+    /// The relation with its operands swapped.
+    pub fn commute(self) -> RelOp {
+        match self {
+            RelOp::Eq => RelOp::Eq,
+            RelOp::Ne => RelOp::Ne,
+            RelOp::Lt => RelOp::Gt,
+            RelOp::Ge => RelOp::Le,
+            RelOp::Gt => RelOp::Lt,
+            RelOp::Le => RelOp::Ge,
+            RelOp::Ult => RelOp::Ugt,
+            RelOp::Uge => RelOp::Ule,
+            RelOp::Ugt => RelOp::Ult,
+            RelOp::Ule => RelOp::Uge,
+        }
+    }
+}
+
+/// Joins statements into a right-leaning `Seq` chain; an empty list becomes
+/// a no-op.
+pub fn seq(stms: Vec<Stm>) -> Stm {
+    let mut stms = stms.into_iter().rev();
+    let last = stms
+        .next()
+        .unwrap_or_else(|| Stm::Exp(Box::new(Exp::Const(0))));
+    stms.fold(last, |rest, stm| Stm::Seq(Box::new(stm), Box::new(rest)))
+}
@@ -0,0 +1,1314 @@
+//! Compiles the typed AST to a WebAssembly module.
+//!
+//! Every Tiger value is an i64: strings, records and arrays are addresses
+//! in linear memory, nil is 0. A string holds its length and then its
+//! bytes, an array its size and then its elements. Functions keep the
+//! variables that escape in frames on a stack in memory below
+//! [`STACK_TOP`], pointed to by the exported global `sp`, and take the
+//! frame of their enclosing function as an explicit first parameter.
+//! Static data sits above the stack and the heap above it.
+
+use std::collections::HashMap;
+
+use crate::error::error::{pack_pos, PosInfo};
+use crate::semant::tast::{
+    FunId, FunKind, Oper, Program, TDec, TExp, TExpKind, TVar, TVarKind, VarId,
+};
+use crate::semant::types::Type;
+use crate::wasm::wasm::{
+    BlockType, Func, FuncType, Global, Import, Instr, Module, Numeric, ValType, Width,
+};
+
+/// Where the stack starts, growing down.
+pub const STACK_TOP: u32 = 1 << 20;
+
+/// Offsets of the header of every frame: the frame of the enclosing
+/// function, the address of the function's name as a string, and the
+/// frame of the caller, which is [`STACK_TOP`] for the main program's.
+pub const STATIC_LINK: u32 = 0;
+pub const NAME: u32 = 8;
+pub const DYNAMIC_LINK: u32 = 16;
+const HEADER: u32 = 24;
+
+const PAGE_SIZE: u32 = 1 << 16;
+
+/// Module and names of the functions the host provides, with their
+/// numbers of i64 parameters. Only `getchar` returns a value: the byte
+/// read, or -1 at the end of the input. The others after `exit` report
+/// runtime errors at a position packed as `translate` packs it, and
+/// never return.
+pub const IMPORT_MODULE: &str = "tiger";
+pub const IMPORTS: [(&str, usize); 10] = [
+    ("print", 1),
+    ("flush", 0),
+    ("getchar", 0),
+    ("exit", 1),
+    ("nilRecord", 1),
+    ("indexOutOfRange", 3),
+    ("divisionByZero", 1),
+    ("chrOutOfRange", 2),
+    ("substringOutOfRange", 4),
+    ("negativeArraySize", 2),
+];
+
+const PRINT: u32 = 0;
+const FLUSH: u32 = 1;
+const GETCHAR: u32 = 2;
+const EXIT: u32 = 3;
+const NIL_RECORD: u32 = 4;
+const INDEX_OUT_OF_RANGE: u32 = 5;
+const DIVISION_BY_ZERO: u32 = 6;
+const CHR_OUT_OF_RANGE: u32 = 7;
+const SUBSTRING_OUT_OF_RANGE: u32 = 8;
+const NEGATIVE_ARRAY_SIZE: u32 = 9;
+
+const SP: u32 = 0;
+const HEAP: u32 = 1;
+
+/// Functions of every module, after the imports.
+#[derive(Debug, Clone, Copy)]
+enum Helper {
+    Alloc,
+    String,
+    Copy,
+    Getchar,
+    Ord,
+    Chr,
+    Size,
+    Substring,
+    Concat,
+    Not,
+    Compare,
+    Equal,
+    Record,
+    Element,
+    Array,
+    Div,
+    Main,
+}
+
+const HELPERS: [Helper; 17] = [
+    Helper::Alloc,
+    Helper::String,
+    Helper::Copy,
+    Helper::Getchar,
+    Helper::Ord,
+    Helper::Chr,
+    Helper::Size,
+    Helper::Substring,
+    Helper::Concat,
+    Helper::Not,
+    Helper::Compare,
+    Helper::Equal,
+    Helper::Record,
+    Helper::Element,
+    Helper::Array,
+    Helper::Div,
+    Helper::Main,
+];
+
+impl Helper {
+    fn index(self) -> u32 {
+        (IMPORTS.len() + self as usize) as u32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Helper::Alloc => "alloc",
+            Helper::String => "string",
+            Helper::Copy => "copy",
+            Helper::Getchar => "getchar",
+            Helper::Ord => "ord",
+            Helper::Chr => "chr",
+            Helper::Size => "size",
+            Helper::Substring => "substring",
+            Helper::Concat => "concat",
+            Helper::Not => "not",
+            Helper::Compare => "stringCompare",
+            Helper::Equal => "stringEqual",
+            Helper::Record => "record",
+            Helper::Element => "element",
+            Helper::Array => "initArray",
+            Helper::Div => "div",
+            Helper::Main => "main",
+        }
+    }
+}
+
+/// Compiles a type-checked program to a module exporting its memory, the
+/// stack pointer `sp` and `main`, which runs the program.
+pub fn compile(program: &Program) -> Module {
+    let mut compiler = Compiler {
+        program,
+        data: Vec::new(),
+        strings: HashMap::new(),
+        data_end: STACK_TOP,
+        funcs: HashMap::new(),
+        queue: Vec::new(),
+        places: HashMap::new(),
+    };
+    compiler.index(program.main);
+    let mut funcs: Vec<Func> = HELPERS
+        .iter()
+        .map(|helper| compiler.helper(*helper))
+        .collect();
+    let mut compiled = 0;
+    while compiled < compiler.queue.len() {
+        let fun = compiler.queue[compiled];
+        funcs.push(compiler.function(fun));
+        compiled += 1;
+    }
+    let heap = compiler.data_end;
+    Module {
+        imports: IMPORTS
+            .iter()
+            .enumerate()
+            .map(|(index, (name, params))| Import {
+                module: String::from(IMPORT_MODULE),
+                name: String::from(*name),
+                ty: FuncType {
+                    params: vec![ValType::I64; *params],
+                    results: if index as u32 == GETCHAR {
+                        vec![ValType::I64]
+                    } else {
+                        Vec::new()
+                    },
+                },
+            })
+            .collect(),
+        funcs,
+        globals: vec![
+            Global {
+                name: String::from("sp"),
+                ty: ValType::I32,
+                mutable: true,
+                init: STACK_TOP as i64,
+                export: Some(String::from("sp")),
+            },
+            Global {
+                name: String::from("heap"),
+                ty: ValType::I32,
+                mutable: true,
+                init: heap as i64,
+                export: None,
+            },
+        ],
+        pages: heap.div_ceil(PAGE_SIZE),
+        memory_export: String::from("memory"),
+        data: compiler.data,
+    }
+}
+
+/// Where a variable lives.
+#[derive(Debug, Clone, Copy)]
+enum Place {
+    Local(u32),
+    /// At an offset in the frame of its function.
+    Frame(u32),
+}
+
+struct Compiler<'a> {
+    program: &'a Program,
+    data: Vec<(u32, Vec<u8>)>,
+    /// Address of each string placed in the data.
+    strings: HashMap<Vec<u8>, u32>,
+    data_end: u32,
+    /// Index in the function index space of each function seen so far.
+    funcs: HashMap<FunId, u32>,
+    /// The functions in index order; those past the ones compiled so far
+    /// are still to be compiled.
+    queue: Vec<FunId>,
+    places: HashMap<VarId, Place>,
+}
+
+/// The instructions and locals of the function being compiled.
+struct Body {
+    fun: FunId,
+    instrs: Vec<Instr>,
+    /// Number of parameters, the static link first.
+    params: u32,
+    locals: Vec<ValType>,
+    /// Bytes of the frame so far.
+    frame: u32,
+    /// Blocks open at this point of the code.
+    depth: u32,
+    /// For each enclosing loop, the depth of the block it breaks out of.
+    loops: Vec<u32>,
+}
+
+impl Body {
+    fn emit(&mut self, instrs: &[Instr]) {
+        self.instrs.extend_from_slice(instrs);
+    }
+
+    fn op(&mut self, op: Numeric) {
+        self.instrs.push(Instr::Op(op));
+    }
+
+    /// Opens a `block`, `loop` or `if`.
+    fn open(&mut self, instr: Instr) {
+        self.instrs.push(instr);
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.instrs.push(Instr::End);
+        self.depth -= 1;
+    }
+
+    fn local(&mut self, ty: ValType) -> u32 {
+        self.locals.push(ty);
+        self.params + self.locals.len() as u32 - 1
+    }
+
+    /// The local holding the address of the frame.
+    fn fp(&self) -> u32 {
+        self.params
+    }
+
+    fn slot(&mut self) -> u32 {
+        self.frame += 8;
+        self.frame - 8
+    }
+}
+
+/// Pushes `pos`, packed as the runtime helpers take it.
+fn packed(pos: &PosInfo) -> Instr {
+    Instr::I64Const(pack_pos(pos))
+}
+
+impl Compiler<'_> {
+    /// The index of `fun`, queueing it for compilation the first time.
+    fn index(&mut self, fun: FunId) -> u32 {
+        if let Some(index) = self.funcs.get(&fun) {
+            return *index;
+        }
+        let index = (IMPORTS.len() + HELPERS.len() + self.queue.len()) as u32;
+        self.funcs.insert(fun, index);
+        self.queue.push(fun);
+        index
+    }
+
+    /// The address of a string with contents `value` in the data.
+    fn string(&mut self, value: &[u8]) -> u32 {
+        if let Some(address) = self.strings.get(value) {
+            return *address;
+        }
+        let address = self.data_end;
+        let mut bytes = (value.len() as i64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(value);
+        self.data_end = (address + bytes.len() as u32).next_multiple_of(8);
+        self.data.push((address, bytes));
+        self.strings.insert(value.to_vec(), address);
+        address
+    }
+
+    fn has_value(&self, exp: &TExp) -> bool {
+        self.program.types.actual(exp.ty) != Type::Unit
+    }
+
+    fn returns(&self, fun: FunId) -> bool {
+        self.program.types.actual(self.program.func(fun).result) != Type::Unit
+    }
+
+    fn function(&mut self, fun: FunId) -> Func {
+        let info = self.program.func(fun);
+        let FunKind::User { body } = &info.kind else {
+            unreachable!("builtins are not compiled")
+        };
+        let mut code = Body {
+            fun,
+            instrs: Vec::new(),
+            params: 1 + info.params.len() as u32,
+            locals: vec![ValType::I32],
+            frame: HEADER,
+            depth: 0,
+            loops: Vec::new(),
+        };
+        let fp = code.fp();
+        let mut prolog = Vec::new();
+        for (i, param) in info.params.iter().enumerate() {
+            let local = 1 + i as u32;
+            let place = if self.program.var(*param).escape {
+                let offset = code.slot();
+                prolog.extend([
+                    Instr::LocalGet(fp),
+                    Instr::LocalGet(local),
+                    Instr::Store(Width::Word, offset),
+                ]);
+                Place::Frame(offset)
+            } else {
+                Place::Local(local)
+            };
+            self.places.insert(*param, place);
+        }
+        let returns = self.returns(fun);
+        if returns {
+            self.value(&mut code, body);
+        } else {
+            self.exp(&mut code, body);
+            if self.has_value(body) {
+                code.emit(&[Instr::Drop]);
+            }
+        }
+        let name = self.string(info.name.as_bytes());
+        let size = code.frame as i32;
+        let mut instrs = vec![
+            Instr::GlobalGet(SP),
+            Instr::I32Const(size),
+            Instr::Op(Numeric::I32Sub),
+            Instr::LocalTee(fp),
+            Instr::GlobalSet(SP),
+            Instr::LocalGet(fp),
+            Instr::LocalGet(0),
+            Instr::Op(Numeric::I64ExtendI32U),
+            Instr::Store(Width::Word, STATIC_LINK),
+            Instr::LocalGet(fp),
+            Instr::I64Const(name as i64),
+            Instr::Store(Width::Word, NAME),
+            Instr::LocalGet(fp),
+            Instr::LocalGet(fp),
+            Instr::I32Const(size),
+            Instr::Op(Numeric::I32Add),
+            Instr::Op(Numeric::I64ExtendI32U),
+            Instr::Store(Width::Word, DYNAMIC_LINK),
+        ];
+        instrs.extend(prolog);
+        instrs.extend(code.instrs);
+        instrs.extend([
+            Instr::LocalGet(fp),
+            Instr::I32Const(size),
+            Instr::Op(Numeric::I32Add),
+            Instr::GlobalSet(SP),
+        ]);
+        let mut params = vec![ValType::I32];
+        params.extend(vec![ValType::I64; info.params.len()]);
+        Func {
+            name: info.label.name().to_string(),
+            ty: FuncType {
+                params,
+                results: if returns {
+                    vec![ValType::I64]
+                } else {
+                    Vec::new()
+                },
+            },
+            locals: code.locals,
+            body: instrs,
+            export: None,
+        }
+    }
+
+    /// Compiles `exp`, leaving its value on the stack if it has one.
+    fn exp(&mut self, code: &mut Body, exp: &TExp) {
+        let pos = &exp.pos;
+        match &exp.kind {
+            TExpKind::Int(i) => code.emit(&[Instr::I64Const(*i)]),
+            TExpKind::String(value) => {
+                let address = self.string(value.as_bytes());
+                code.emit(&[Instr::I64Const(address as i64)]);
+            }
+            TExpKind::Nil => code.emit(&[Instr::I64Const(0)]),
+            TExpKind::Var(var) => self.var(code, var),
+            TExpKind::Call(fun, args) => self.call(code, *fun, args, pos),
+            TExpKind::Op(oper, left, right) => {
+                self.exp(code, left);
+                self.exp(code, right);
+                let string = self.program.types.actual(left.ty) == Type::String;
+                let compare = match (oper, string) {
+                    (Oper::Plus, _) => return code.op(Numeric::I64Add),
+                    (Oper::Minus, _) => return code.op(Numeric::I64Sub),
+                    (Oper::Times, _) => return code.op(Numeric::I64Mul),
+                    (Oper::Divide, _) => {
+                        return code.emit(&[packed(pos), Instr::Call(Helper::Div.index())])
+                    }
+                    (Oper::Eq, true) => {
+                        return code.emit(&[Instr::Call(Helper::Equal.index())]);
+                    }
+                    (Oper::Neq, true) => {
+                        code.emit(&[Instr::Call(Helper::Equal.index())]);
+                        Numeric::I64Eqz
+                    }
+                    (Oper::Eq, false) => Numeric::I64Eq,
+                    (Oper::Neq, false) => Numeric::I64Ne,
+                    (Oper::Lt, false) => Numeric::I64LtS,
+                    (Oper::Le, false) => Numeric::I64LeS,
+                    (Oper::Gt, false) => Numeric::I64GtS,
+                    (Oper::Ge, false) => Numeric::I64GeS,
+                    (_, true) => {
+                        code.emit(&[Instr::Call(Helper::Compare.index()), Instr::I64Const(0)]);
+                        match oper {
+                            Oper::Lt => Numeric::I64LtS,
+                            Oper::Le => Numeric::I64LeS,
+                            Oper::Gt => Numeric::I64GtS,
+                            _ => Numeric::I64GeS,
+                        }
+                    }
+                };
+                code.op(compare);
+                code.op(Numeric::I64ExtendI32U);
+            }
+            TExpKind::And(left, right) => {
+                self.test(code, left);
+                code.open(Instr::If(BlockType::Value(ValType::I64)));
+                self.test(code, right);
+                code.op(Numeric::I64ExtendI32U);
+                code.emit(&[Instr::Else, Instr::I64Const(0)]);
+                code.close();
+            }
+            TExpKind::Or(left, right) => {
+                self.test(code, left);
+                code.open(Instr::If(BlockType::Value(ValType::I64)));
+                code.emit(&[Instr::I64Const(1), Instr::Else]);
+                self.test(code, right);
+                code.op(Numeric::I64ExtendI32U);
+                code.close();
+            }
+            TExpKind::Record(_, fields) => {
+                let record = code.local(ValType::I32);
+                let size = 8 * fields.len().max(1) as i64;
+                code.emit(&[
+                    Instr::I64Const(size),
+                    Instr::Call(Helper::Alloc.index()),
+                    Instr::LocalSet(record),
+                ]);
+                for (i, field) in fields.iter().enumerate() {
+                    code.emit(&[Instr::LocalGet(record)]);
+                    self.value(code, field);
+                    code.emit(&[Instr::Store(Width::Word, 8 * i as u32)]);
+                }
+                code.emit(&[Instr::LocalGet(record)]);
+                code.op(Numeric::I64ExtendI32U);
+            }
+            TExpKind::Array(_, size, init) => {
+                self.exp(code, size);
+                self.value(code, init);
+                code.emit(&[packed(pos), Instr::Call(Helper::Array.index())]);
+            }
+            TExpKind::Seq(exps) => {
+                for (i, exp) in exps.iter().enumerate() {
+                    self.exp(code, exp);
+                    if i + 1 < exps.len() && self.has_value(exp) {
+                        code.emit(&[Instr::Drop]);
+                    }
+                }
+            }
+            TExpKind::Assign(var, value) => self.assign(code, var, value),
+            TExpKind::If(test, then, els) => {
+                self.test(code, test);
+                match els {
+                    Some(els) if self.has_value(exp) => {
+                        code.open(Instr::If(BlockType::Value(ValType::I64)));
+                        self.value(code, then);
+                        code.emit(&[Instr::Else]);
+                        self.value(code, els);
+                    }
+                    _ => {
+                        code.open(Instr::If(BlockType::Empty));
+                        self.statement(code, then);
+                        if let Some(els) = els {
+                            code.emit(&[Instr::Else]);
+                            self.statement(code, els);
+                        }
+                    }
+                }
+                code.close();
+            }
+            TExpKind::While(test, body) => {
+                code.open(Instr::Block(BlockType::Empty));
+                code.loops.push(code.depth);
+                code.open(Instr::Loop(BlockType::Empty));
+                self.test(code, test);
+                code.op(Numeric::I32Eqz);
+                code.emit(&[Instr::BrIf(1)]);
+                self.statement(code, body);
+                code.emit(&[Instr::Br(0)]);
+                code.close();
+                code.close();
+                code.loops.pop();
+            }
+            TExpKind::For(var, lo, hi, body) => {
+                let limit = code.local(ValType::I64);
+                let place = self.place(code, *var);
+                self.store(code, place, lo);
+                self.exp(code, hi);
+                code.emit(&[Instr::LocalSet(limit)]);
+                code.open(Instr::Block(BlockType::Empty));
+                code.loops.push(code.depth);
+                self.load(code, place, 0);
+                code.emit(&[Instr::LocalGet(limit)]);
+                code.op(Numeric::I64GtS);
+                code.emit(&[Instr::BrIf(0)]);
+                code.open(Instr::Loop(BlockType::Empty));
+                self.statement(code, body);
+                self.load(code, place, 0);
+                code.emit(&[Instr::LocalGet(limit)]);
+                code.op(Numeric::I64GeS);
+                code.emit(&[Instr::BrIf(1)]);
+                self.address(code, place, 0);
+                self.load(code, place, 0);
+                code.emit(&[Instr::I64Const(1)]);
+                code.op(Numeric::I64Add);
+                self.set(code, place);
+                code.emit(&[Instr::Br(0)]);
+                code.close();
+                code.close();
+                code.loops.pop();
+            }
+            TExpKind::Break => {
+                let target = *code.loops.last().expect("break outside a loop");
+                code.emit(&[Instr::Br(code.depth - target)]);
+            }
+            TExpKind::Let(decs, body) => {
+                for dec in decs {
+                    match dec {
+                        TDec::Var(var, init) => {
+                            let place = self.place(code, *var);
+                            self.store(code, place, init);
+                        }
+                        TDec::Funcs(funs) => {
+                            for fun in funs {
+                                self.index(*fun);
+                            }
+                        }
+                    }
+                }
+                self.exp(code, body);
+            }
+        }
+    }
+
+    /// Compiles `exp`, pushing 0 in place of a missing value.
+    fn value(&mut self, code: &mut Body, exp: &TExp) {
+        self.exp(code, exp);
+        if !self.has_value(exp) {
+            code.emit(&[Instr::I64Const(0)]);
+        }
+    }
+
+    /// Compiles `exp`, dropping its value if it has one.
+    fn statement(&mut self, code: &mut Body, exp: &TExp) {
+        self.exp(code, exp);
+        if self.has_value(exp) {
+            code.emit(&[Instr::Drop]);
+        }
+    }
+
+    /// Compiles `exp` as a condition, leaving an i32 on the stack.
+    fn test(&mut self, code: &mut Body, exp: &TExp) {
+        self.exp(code, exp);
+        code.emit(&[Instr::I64Const(0)]);
+        code.op(Numeric::I64Ne);
+    }
+
+    fn call(&mut self, code: &mut Body, fun: FunId, args: &[TExp], pos: &PosInfo) {
+        let info = self.program.func(fun);
+        if let FunKind::Builtin = info.kind {
+            for arg in args {
+                self.value(code, arg);
+            }
+            let index = match info.name.as_str() {
+                "print" => PRINT,
+                "flush" => FLUSH,
+                "getchar" => Helper::Getchar.index(),
+                "ord" => Helper::Ord.index(),
+                "chr" => Helper::Chr.index(),
+                "size" => Helper::Size.index(),
+                "substring" => Helper::Substring.index(),
+                "concat" => Helper::Concat.index(),
+                "not" => Helper::Not.index(),
+                "exit" => EXIT,
+                name => unreachable!("unknown builtin {}", name),
+            };
+            if matches!(info.name.as_str(), "chr" | "substring") {
+                code.emit(&[packed(pos)]);
+            }
+            code.emit(&[Instr::Call(index)]);
+            if index == EXIT {
+                code.emit(&[Instr::Unreachable]);
+            }
+            return;
+        }
+        let hops = self.program.depth(code.fun) + 1 - self.program.depth(fun);
+        code.emit(&[Instr::LocalGet(code.fp())]);
+        for _ in 0..hops {
+            code.emit(&[
+                Instr::Load(Width::Word, STATIC_LINK),
+                Instr::Op(Numeric::I32WrapI64),
+            ]);
+        }
+        for arg in args {
+            self.value(code, arg);
+        }
+        let index = self.index(fun);
+        code.emit(&[Instr::Call(index)]);
+    }
+
+    /// Gives a new variable of the function being compiled its place.
+    fn place(&mut self, code: &mut Body, var: VarId) -> Place {
+        let place = if self.program.var(var).escape {
+            Place::Frame(code.slot())
+        } else {
+            Place::Local(code.local(ValType::I64))
+        };
+        self.places.insert(var, place);
+        place
+    }
+
+    /// Pushes the address of the frame `hops` static links away, if
+    /// `place` is in a frame.
+    fn address(&self, code: &mut Body, place: Place, hops: usize) {
+        match place {
+            Place::Local(_) => {
+                assert_eq!(hops, 0, "non-escaping variable used in a nested function")
+            }
+            Place::Frame(_) => {
+                code.emit(&[Instr::LocalGet(code.fp())]);
+                for _ in 0..hops {
+                    code.emit(&[
+                        Instr::Load(Width::Word, STATIC_LINK),
+                        Instr::Op(Numeric::I32WrapI64),
+                    ]);
+                }
+            }
+        }
+    }
+
+    fn load(&self, code: &mut Body, place: Place, hops: usize) {
+        self.address(code, place, hops);
+        code.emit(&[match place {
+            Place::Local(local) => Instr::LocalGet(local),
+            Place::Frame(offset) => Instr::Load(Width::Word, offset),
+        }]);
+    }
+
+    /// Sets `place` to the value on the stack, above the address pushed
+    /// by [`Compiler::address`].
+    fn set(&self, code: &mut Body, place: Place) {
+        code.emit(&[match place {
+            Place::Local(local) => Instr::LocalSet(local),
+            Place::Frame(offset) => Instr::Store(Width::Word, offset),
+        }]);
+    }
+
+    /// Sets a variable of the function being compiled to `value`.
+    fn store(&mut self, code: &mut Body, place: Place, value: &TExp) {
+        self.address(code, place, 0);
+        self.value(code, value);
+        self.set(code, place);
+    }
+
+    /// The place of `var` and how many static links away its frame is.
+    fn local(&self, code: &Body, var: VarId) -> (Place, usize) {
+        let hops = self.program.depth(code.fun) - self.program.depth(self.program.var(var).fun);
+        (self.places[&var], hops)
+    }
+
+    fn var(&mut self, code: &mut Body, var: &TVar) {
+        match &var.kind {
+            TVarKind::Simple(id) => {
+                let (place, hops) = self.local(code, *id);
+                self.load(code, place, hops);
+            }
+            TVarKind::Field(record, index) => {
+                self.exp(code, record);
+                code.emit(&[
+                    packed(&record.pos),
+                    Instr::Call(Helper::Record.index()),
+                    Instr::Load(Width::Word, 8 * *index as u32),
+                ]);
+            }
+            TVarKind::Subscript(array, index) => {
+                self.exp(code, array);
+                self.exp(code, index);
+                code.emit(&[
+                    packed(&index.pos),
+                    Instr::Call(Helper::Element.index()),
+                    Instr::Load(Width::Word, 0),
+                ]);
+            }
+        }
+    }
+
+    /// Compiles an assignment. The record or array and the index go to
+    /// locals while the value is computed, so the `record` and `element`
+    /// helpers check them after it, in the interpreter's order.
+    fn assign(&mut self, code: &mut Body, var: &TVar, value: &TExp) {
+        match &var.kind {
+            TVarKind::Simple(id) => {
+                let (place, hops) = self.local(code, *id);
+                self.address(code, place, hops);
+                self.value(code, value);
+                self.set(code, place);
+            }
+            TVarKind::Field(record, index) => {
+                let (target, temp) = (code.local(ValType::I64), code.local(ValType::I64));
+                self.exp(code, record);
+                code.emit(&[Instr::LocalSet(target)]);
+                self.value(code, value);
+                code.emit(&[
+                    Instr::LocalSet(temp),
+                    Instr::LocalGet(target),
+                    packed(&record.pos),
+                    Instr::Call(Helper::Record.index()),
+                    Instr::LocalGet(temp),
+                    Instr::Store(Width::Word, 8 * *index as u32),
+                ]);
+            }
+            TVarKind::Subscript(array, index) => {
+                let target = code.local(ValType::I64);
+                let i = code.local(ValType::I64);
+                let temp = code.local(ValType::I64);
+                self.exp(code, array);
+                code.emit(&[Instr::LocalSet(target)]);
+                self.exp(code, index);
+                code.emit(&[Instr::LocalSet(i)]);
+                self.value(code, value);
+                code.emit(&[
+                    Instr::LocalSet(temp),
+                    Instr::LocalGet(target),
+                    Instr::LocalGet(i),
+                    packed(&index.pos),
+                    Instr::Call(Helper::Element.index()),
+                    Instr::LocalGet(temp),
+                    Instr::Store(Width::Word, 0),
+                ]);
+            }
+        }
+    }
+
+    fn helper(&mut self, helper: Helper) -> Func {
+        use Instr::*;
+        use Numeric::*;
+        use ValType::{I32, I64};
+        let wrap = Op(I32WrapI64);
+        let extend = Op(I64ExtendI32U);
+        let (params, results, locals, body) = match helper {
+            // (bytes) -> address, growing the memory as needed.
+            Helper::Alloc => (
+                vec![I64],
+                vec![I32],
+                vec![I32, I32],
+                vec![
+                    LocalGet(0),
+                    I64Const(0x7fff_0000),
+                    Op(I64GtU),
+                    If(BlockType::Empty),
+                    Unreachable,
+                    End,
+                    GlobalGet(HEAP),
+                    LocalTee(1),
+                    LocalGet(0),
+                    wrap,
+                    Op(I32Add),
+                    I32Const(7),
+                    Op(I32Add),
+                    I32Const(-8),
+                    Op(I32And),
+                    LocalTee(2),
+                    GlobalSet(HEAP),
+                    LocalGet(2),
+                    MemorySize,
+                    I32Const(16),
+                    Op(I32Shl),
+                    Op(I32GtU),
+                    If(BlockType::Empty),
+                    LocalGet(2),
+                    MemorySize,
+                    I32Const(16),
+                    Op(I32Shl),
+                    Op(I32Sub),
+                    I32Const(PAGE_SIZE as i32 - 1),
+                    Op(I32Add),
+                    I32Const(16),
+                    Op(I32ShrU),
+                    MemoryGrow,
+                    I32Const(-1),
+                    Op(I32Eq),
+                    If(BlockType::Empty),
+                    Unreachable,
+                    End,
+                    End,
+                    LocalGet(1),
+                ],
+            ),
+            // (length) -> a string of that length.
+            Helper::String => (
+                vec![I64],
+                vec![I64],
+                vec![I32],
+                vec![
+                    LocalGet(0),
+                    I64Const(8),
+                    Op(I64Add),
+                    Call(Helper::Alloc.index()),
+                    LocalTee(1),
+                    LocalGet(0),
+                    Store(Width::Word, 0),
+                    LocalGet(1),
+                    extend,
+                ],
+            ),
+            // (from, to, bytes)
+            Helper::Copy => (
+                vec![I32, I32, I32],
+                Vec::new(),
+                Vec::new(),
+                vec![
+                    Block(BlockType::Empty),
+                    Loop(BlockType::Empty),
+                    LocalGet(2),
+                    Op(I32Eqz),
+                    BrIf(1),
+                    LocalGet(1),
+                    LocalGet(0),
+                    Load(Width::Byte, 0),
+                    Store(Width::Byte, 0),
+                    LocalGet(0),
+                    I32Const(1),
+                    Op(I32Add),
+                    LocalSet(0),
+                    LocalGet(1),
+                    I32Const(1),
+                    Op(I32Add),
+                    LocalSet(1),
+                    LocalGet(2),
+                    I32Const(1),
+                    Op(I32Sub),
+                    LocalSet(2),
+                    Br(0),
+                    End,
+                    End,
+                ],
+            ),
+            Helper::Getchar => (
+                Vec::new(),
+                vec![I64],
+                vec![I64, I64],
+                vec![
+                    Call(GETCHAR),
+                    LocalTee(0),
+                    I64Const(0),
+                    Op(I64LtS),
+                    If(BlockType::Value(I64)),
+                    I64Const(0),
+                    Call(Helper::String.index()),
+                    Else,
+                    I64Const(1),
+                    Call(Helper::String.index()),
+                    LocalTee(1),
+                    wrap,
+                    LocalGet(0),
+                    Store(Width::Byte, 8),
+                    LocalGet(1),
+                    End,
+                ],
+            ),
+            Helper::Ord => (
+                vec![I64],
+                vec![I64],
+                Vec::new(),
+                vec![
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    Op(I64Eqz),
+                    If(BlockType::Value(I64)),
+                    I64Const(-1),
+                    Else,
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Byte, 8),
+                    End,
+                ],
+            ),
+            // (code, pos)
+            Helper::Chr => (
+                vec![I64, I64],
+                vec![I64],
+                vec![I64],
+                vec![
+                    LocalGet(0),
+                    I64Const(255),
+                    Op(I64GtU),
+                    If(BlockType::Empty),
+                    LocalGet(0),
+                    LocalGet(1),
+                    Call(CHR_OUT_OF_RANGE),
+                    Unreachable,
+                    End,
+                    I64Const(1),
+                    Call(Helper::String.index()),
+                    LocalTee(2),
+                    wrap,
+                    LocalGet(0),
+                    Store(Width::Byte, 8),
+                    LocalGet(2),
+                ],
+            ),
+            Helper::Size => (
+                vec![I64],
+                vec![I64],
+                Vec::new(),
+                vec![LocalGet(0), wrap, Load(Width::Word, 0)],
+            ),
+            // (string, first, n, pos)
+            Helper::Substring => (
+                vec![I64, I64, I64, I64],
+                vec![I64],
+                vec![I64],
+                vec![
+                    LocalGet(1),
+                    I64Const(0),
+                    Op(I64LtS),
+                    LocalGet(2),
+                    I64Const(0),
+                    Op(I64LtS),
+                    Op(I32Or),
+                    LocalGet(1),
+                    LocalGet(2),
+                    Op(I64Add),
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    Op(I64GtU),
+                    Op(I32Or),
+                    If(BlockType::Empty),
+                    LocalGet(0),
+                    LocalGet(1),
+                    LocalGet(2),
+                    LocalGet(3),
+                    Call(SUBSTRING_OUT_OF_RANGE),
+                    Unreachable,
+                    End,
+                    LocalGet(2),
+                    Call(Helper::String.index()),
+                    LocalSet(4),
+                    LocalGet(0),
+                    wrap,
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalGet(1),
+                    wrap,
+                    Op(I32Add),
+                    LocalGet(4),
+                    wrap,
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalGet(2),
+                    wrap,
+                    Call(Helper::Copy.index()),
+                    LocalGet(4),
+                ],
+            ),
+            Helper::Concat => (
+                vec![I64, I64],
+                vec![I64],
+                vec![I64],
+                vec![
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    LocalGet(1),
+                    wrap,
+                    Load(Width::Word, 0),
+                    Op(I64Add),
+                    Call(Helper::String.index()),
+                    LocalSet(2),
+                    LocalGet(0),
+                    wrap,
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalGet(2),
+                    wrap,
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    wrap,
+                    Call(Helper::Copy.index()),
+                    LocalGet(1),
+                    wrap,
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalGet(2),
+                    wrap,
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    wrap,
+                    Op(I32Add),
+                    LocalGet(1),
+                    wrap,
+                    Load(Width::Word, 0),
+                    wrap,
+                    Call(Helper::Copy.index()),
+                    LocalGet(2),
+                ],
+            ),
+            Helper::Not => (
+                vec![I64],
+                vec![I64],
+                Vec::new(),
+                vec![LocalGet(0), Op(I64Eqz), extend],
+            ),
+            // (a, b) -> -1, 0 or 1 as a sorts before, with or after b.
+            // Locals: the index, the length of the shorter string, and
+            // the bytes compared.
+            Helper::Compare => {
+                let sign = |x: u32, y: u32, unsigned: bool| {
+                    let (gt, lt) = if unsigned {
+                        (I64GtU, I64LtU)
+                    } else {
+                        (I64GtS, I64LtS)
+                    };
+                    vec![
+                        LocalGet(x),
+                        LocalGet(y),
+                        Op(gt),
+                        extend,
+                        LocalGet(x),
+                        LocalGet(y),
+                        Op(lt),
+                        extend,
+                        Op(I64Sub),
+                    ]
+                };
+                let mut body = vec![
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    LocalGet(1),
+                    wrap,
+                    Load(Width::Word, 0),
+                    Op(I64LtS),
+                    If(BlockType::Value(I64)),
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    Else,
+                    LocalGet(1),
+                    wrap,
+                    Load(Width::Word, 0),
+                    End,
+                    wrap,
+                    LocalSet(3),
+                    Block(BlockType::Empty),
+                    Loop(BlockType::Empty),
+                    LocalGet(2),
+                    LocalGet(3),
+                    Op(I32Eq),
+                    BrIf(1),
+                    LocalGet(0),
+                    wrap,
+                    LocalGet(2),
+                    Op(I32Add),
+                    Load(Width::Byte, 8),
+                    LocalSet(4),
+                    LocalGet(1),
+                    wrap,
+                    LocalGet(2),
+                    Op(I32Add),
+                    Load(Width::Byte, 8),
+                    LocalSet(5),
+                    LocalGet(4),
+                    LocalGet(5),
+                    Op(I64Ne),
+                    If(BlockType::Empty),
+                ];
+                body.extend(sign(4, 5, true));
+                body.extend([
+                    Return,
+                    End,
+                    LocalGet(2),
+                    I32Const(1),
+                    Op(I32Add),
+                    LocalSet(2),
+                    Br(0),
+                    End,
+                    End,
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    LocalSet(4),
+                    LocalGet(1),
+                    wrap,
+                    Load(Width::Word, 0),
+                    LocalSet(5),
+                ]);
+                body.extend(sign(4, 5, false));
+                (vec![I64, I64], vec![I64], vec![I32, I32, I64, I64], body)
+            }
+            Helper::Equal => (
+                vec![I64, I64],
+                vec![I64],
+                Vec::new(),
+                vec![
+                    LocalGet(0),
+                    LocalGet(1),
+                    Call(Helper::Compare.index()),
+                    Op(I64Eqz),
+                    extend,
+                ],
+            ),
+            // (record, pos) -> the address of the record, checked not nil.
+            Helper::Record => (
+                vec![I64, I64],
+                vec![I32],
+                Vec::new(),
+                vec![
+                    LocalGet(0),
+                    Op(I64Eqz),
+                    If(BlockType::Empty),
+                    LocalGet(1),
+                    Call(NIL_RECORD),
+                    Unreachable,
+                    End,
+                    LocalGet(0),
+                    wrap,
+                ],
+            ),
+            // (array, index, pos) -> the address of the element, checked
+            // in range.
+            Helper::Element => (
+                vec![I64, I64, I64],
+                vec![I32],
+                Vec::new(),
+                vec![
+                    LocalGet(1),
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    Op(I64GeU),
+                    If(BlockType::Empty),
+                    LocalGet(1),
+                    LocalGet(0),
+                    wrap,
+                    Load(Width::Word, 0),
+                    LocalGet(2),
+                    Call(INDEX_OUT_OF_RANGE),
+                    Unreachable,
+                    End,
+                    LocalGet(0),
+                    wrap,
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalGet(1),
+                    wrap,
+                    I32Const(3),
+                    Op(I32Shl),
+                    Op(I32Add),
+                ],
+            ),
+            // (size, init, pos) -> a new array. Locals: the array, the
+            // element to set and the end of the array.
+            Helper::Array => (
+                vec![I64, I64, I64],
+                vec![I64],
+                vec![I32, I32, I32],
+                vec![
+                    LocalGet(0),
+                    I64Const(0),
+                    Op(I64LtS),
+                    If(BlockType::Empty),
+                    LocalGet(0),
+                    LocalGet(2),
+                    Call(NEGATIVE_ARRAY_SIZE),
+                    Unreachable,
+                    End,
+                    LocalGet(0),
+                    I64Const(0x1000_0000),
+                    Op(I64GeS),
+                    If(BlockType::Empty),
+                    Unreachable,
+                    End,
+                    LocalGet(0),
+                    I64Const(3),
+                    Op(I64Shl),
+                    I64Const(8),
+                    Op(I64Add),
+                    Call(Helper::Alloc.index()),
+                    LocalTee(3),
+                    LocalGet(0),
+                    Store(Width::Word, 0),
+                    LocalGet(3),
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalTee(4),
+                    LocalGet(0),
+                    wrap,
+                    I32Const(3),
+                    Op(I32Shl),
+                    Op(I32Add),
+                    LocalSet(5),
+                    Block(BlockType::Empty),
+                    Loop(BlockType::Empty),
+                    LocalGet(4),
+                    LocalGet(5),
+                    Op(I32Eq),
+                    BrIf(1),
+                    LocalGet(4),
+                    LocalGet(1),
+                    Store(Width::Word, 0),
+                    LocalGet(4),
+                    I32Const(8),
+                    Op(I32Add),
+                    LocalSet(4),
+                    Br(0),
+                    End,
+                    End,
+                    LocalGet(3),
+                    extend,
+                ],
+            ),
+            // (a, b, pos) -> a / b, wrapping as the interpreter does.
+            Helper::Div => (
+                vec![I64, I64, I64],
+                vec![I64],
+                Vec::new(),
+                vec![
+                    LocalGet(1),
+                    Op(I64Eqz),
+                    If(BlockType::Empty),
+                    LocalGet(2),
+                    Call(DIVISION_BY_ZERO),
+                    Unreachable,
+                    End,
+                    LocalGet(1),
+                    I64Const(-1),
+                    Op(I64Eq),
+                    If(BlockType::Value(I64)),
+                    I64Const(0),
+                    LocalGet(0),
+                    Op(I64Sub),
+                    Else,
+                    LocalGet(0),
+                    LocalGet(1),
+                    Op(I64DivS),
+                    End,
+                ],
+            ),
+            Helper::Main => {
+                let main = self.program.main;
+                let mut body = vec![I32Const(0), Call(self.index(main))];
+                if self.returns(main) {
+                    body.push(Drop);
+                }
+                (Vec::new(), Vec::new(), Vec::new(), body)
+            }
+        };
+        Func {
+            name: String::from(helper.name()),
+            ty: FuncType { params, results },
+            locals,
+            body,
+            export: match helper {
+                Helper::Main => Some(String::from("main")),
+                _ => None,
+            },
+        }
+    }
+}
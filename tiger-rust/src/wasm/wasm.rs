@@ -0,0 +1,433 @@
+//! A WebAssembly module as the backend builds it, and its binary encoding.
+//! Only the instructions the backend needs are here.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValType {
+    I32,
+    I64,
+}
+
+impl ValType {
+    pub fn name(self) -> &'static str {
+        match self {
+            ValType::I32 => "i32",
+            ValType::I64 => "i64",
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            ValType::I32 => 0x7f,
+            ValType::I64 => 0x7e,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FuncType {
+    pub params: Vec<ValType>,
+    pub results: Vec<ValType>,
+}
+
+/// The result of a `block`, `loop` or `if`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockType {
+    Empty,
+    Value(ValType),
+}
+
+/// The width of a memory access; values are i64 either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Width {
+    Word,
+    Byte,
+}
+
+/// Instructions without immediates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Numeric {
+    I32Eqz,
+    I32Eq,
+    I32Ne,
+    I32LtS,
+    I32GtU,
+    I32Add,
+    I32Sub,
+    I32And,
+    I32Or,
+    I32Shl,
+    I32ShrU,
+    I64Eqz,
+    I64Eq,
+    I64Ne,
+    I64LtS,
+    I64LtU,
+    I64GtS,
+    I64GtU,
+    I64LeS,
+    I64GeS,
+    I64GeU,
+    I64Add,
+    I64Sub,
+    I64Mul,
+    I64DivS,
+    I64Shl,
+    I32WrapI64,
+    I64ExtendI32U,
+}
+
+impl Numeric {
+    fn opcode(self) -> u8 {
+        match self {
+            Numeric::I32Eqz => 0x45,
+            Numeric::I32Eq => 0x46,
+            Numeric::I32Ne => 0x47,
+            Numeric::I32LtS => 0x48,
+            Numeric::I32GtU => 0x4b,
+            Numeric::I64Eqz => 0x50,
+            Numeric::I64Eq => 0x51,
+            Numeric::I64Ne => 0x52,
+            Numeric::I64LtS => 0x53,
+            Numeric::I64LtU => 0x54,
+            Numeric::I64GtS => 0x55,
+            Numeric::I64GtU => 0x56,
+            Numeric::I64LeS => 0x57,
+            Numeric::I64GeS => 0x59,
+            Numeric::I64GeU => 0x5a,
+            Numeric::I32Add => 0x6a,
+            Numeric::I32Sub => 0x6b,
+            Numeric::I32And => 0x71,
+            Numeric::I32Or => 0x72,
+            Numeric::I32Shl => 0x74,
+            Numeric::I32ShrU => 0x76,
+            Numeric::I64Add => 0x7c,
+            Numeric::I64Sub => 0x7d,
+            Numeric::I64Mul => 0x7e,
+            Numeric::I64DivS => 0x7f,
+            Numeric::I64Shl => 0x86,
+            Numeric::I32WrapI64 => 0xa7,
+            Numeric::I64ExtendI32U => 0xad,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Numeric::I32Eqz => "i32.eqz",
+            Numeric::I32Eq => "i32.eq",
+            Numeric::I32Ne => "i32.ne",
+            Numeric::I32LtS => "i32.lt_s",
+            Numeric::I32GtU => "i32.gt_u",
+            Numeric::I32Add => "i32.add",
+            Numeric::I32Sub => "i32.sub",
+            Numeric::I32And => "i32.and",
+            Numeric::I32Or => "i32.or",
+            Numeric::I32Shl => "i32.shl",
+            Numeric::I32ShrU => "i32.shr_u",
+            Numeric::I64Eqz => "i64.eqz",
+            Numeric::I64Eq => "i64.eq",
+            Numeric::I64Ne => "i64.ne",
+            Numeric::I64LtS => "i64.lt_s",
+            Numeric::I64LtU => "i64.lt_u",
+            Numeric::I64GtS => "i64.gt_s",
+            Numeric::I64GtU => "i64.gt_u",
+            Numeric::I64LeS => "i64.le_s",
+            Numeric::I64GeS => "i64.ge_s",
+            Numeric::I64GeU => "i64.ge_u",
+            Numeric::I64Add => "i64.add",
+            Numeric::I64Sub => "i64.sub",
+            Numeric::I64Mul => "i64.mul",
+            Numeric::I64DivS => "i64.div_s",
+            Numeric::I64Shl => "i64.shl",
+            Numeric::I32WrapI64 => "i32.wrap_i64",
+            Numeric::I64ExtendI32U => "i64.extend_i32_u",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instr {
+    Unreachable,
+    Block(BlockType),
+    Loop(BlockType),
+    If(BlockType),
+    Else,
+    End,
+    Br(u32),
+    BrIf(u32),
+    Return,
+    Call(u32),
+    Drop,
+    LocalGet(u32),
+    LocalSet(u32),
+    LocalTee(u32),
+    GlobalGet(u32),
+    GlobalSet(u32),
+    /// An i64 load from an i32 address plus the offset.
+    Load(Width, u32),
+    /// An i64 store to an i32 address plus the offset.
+    Store(Width, u32),
+    MemorySize,
+    MemoryGrow,
+    I32Const(i32),
+    I64Const(i64),
+    Op(Numeric),
+}
+
+/// A function the host provides.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub module: String,
+    pub name: String,
+    pub ty: FuncType,
+}
+
+#[derive(Debug, Clone)]
+pub struct Func {
+    pub name: String,
+    pub ty: FuncType,
+    /// Locals past the parameters.
+    pub locals: Vec<ValType>,
+    pub body: Vec<Instr>,
+    pub export: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Global {
+    pub name: String,
+    pub ty: ValType,
+    pub mutable: bool,
+    pub init: i64,
+    pub export: Option<String>,
+}
+
+/// A module with one memory. Imported functions come first in the
+/// function index space, then `funcs`.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub imports: Vec<Import>,
+    pub funcs: Vec<Func>,
+    pub globals: Vec<Global>,
+    /// Initial size of the memory, in 64 KiB pages.
+    pub pages: u32,
+    pub memory_export: String,
+    /// Bytes placed in memory at each address.
+    pub data: Vec<(u32, Vec<u8>)>,
+}
+
+impl Module {
+    /// The distinct function types, in order of first use.
+    pub fn types(&self) -> Vec<&FuncType> {
+        let mut types: Vec<&FuncType> = Vec::new();
+        let used = self.imports.iter().map(|import| &import.ty);
+        for ty in used.chain(self.funcs.iter().map(|func| &func.ty)) {
+            if !types.contains(&ty) {
+                types.push(ty);
+            }
+        }
+        types
+    }
+
+    /// The module in the binary format.
+    pub fn encode(&self) -> Vec<u8> {
+        let types = self.types();
+        let type_index = |ty: &FuncType| types.iter().position(|t| *t == ty).unwrap() as u32;
+        let mut out = b"\0asm\x01\0\0\0".to_vec();
+
+        let mut section = Vec::new();
+        vec_len(&mut section, types.len());
+        for ty in &types {
+            section.push(0x60);
+            vec_len(&mut section, ty.params.len());
+            section.extend(ty.params.iter().map(|t| t.code()));
+            vec_len(&mut section, ty.results.len());
+            section.extend(ty.results.iter().map(|t| t.code()));
+        }
+        push_section(&mut out, 1, &section);
+
+        let mut section = Vec::new();
+        vec_len(&mut section, self.imports.len());
+        for import in &self.imports {
+            name(&mut section, &import.module);
+            name(&mut section, &import.name);
+            section.push(0x00);
+            uleb(&mut section, type_index(&import.ty) as u64);
+        }
+        push_section(&mut out, 2, &section);
+
+        let mut section = Vec::new();
+        vec_len(&mut section, self.funcs.len());
+        for func in &self.funcs {
+            uleb(&mut section, type_index(&func.ty) as u64);
+        }
+        push_section(&mut out, 3, &section);
+
+        let mut section = vec![1, 0x00];
+        uleb(&mut section, self.pages as u64);
+        push_section(&mut out, 5, &section);
+
+        let mut section = Vec::new();
+        vec_len(&mut section, self.globals.len());
+        for global in &self.globals {
+            section.push(global.ty.code());
+            section.push(global.mutable as u8);
+            match global.ty {
+                ValType::I32 => Instr::I32Const(global.init as i32),
+                ValType::I64 => Instr::I64Const(global.init),
+            }
+            .encode(&mut section);
+            Instr::End.encode(&mut section);
+        }
+        push_section(&mut out, 6, &section);
+
+        let mut exports = vec![(self.memory_export.as_str(), 0x02, 0)];
+        for (index, func) in self.funcs.iter().enumerate() {
+            if let Some(export) = &func.export {
+                exports.push((export, 0x00, self.imports.len() + index));
+            }
+        }
+        for (index, global) in self.globals.iter().enumerate() {
+            if let Some(export) = &global.export {
+                exports.push((export, 0x03, index));
+            }
+        }
+        let mut section = Vec::new();
+        vec_len(&mut section, exports.len());
+        for (export, kind, index) in exports {
+            name(&mut section, export);
+            section.push(kind);
+            uleb(&mut section, index as u64);
+        }
+        push_section(&mut out, 7, &section);
+
+        let mut section = Vec::new();
+        vec_len(&mut section, self.funcs.len());
+        for func in &self.funcs {
+            let mut code = Vec::new();
+            let mut runs: Vec<(u32, ValType)> = Vec::new();
+            for local in &func.locals {
+                match runs.last_mut() {
+                    Some((count, ty)) if ty == local => *count += 1,
+                    _ => runs.push((1, *local)),
+                }
+            }
+            vec_len(&mut code, runs.len());
+            for (count, ty) in runs {
+                uleb(&mut code, count as u64);
+                code.push(ty.code());
+            }
+            for instr in &func.body {
+                instr.encode(&mut code);
+            }
+            Instr::End.encode(&mut code);
+            vec_len(&mut section, code.len());
+            section.extend(code);
+        }
+        push_section(&mut out, 10, &section);
+
+        let mut section = Vec::new();
+        vec_len(&mut section, self.data.len());
+        for (address, bytes) in &self.data {
+            section.push(0x00);
+            Instr::I32Const(*address as i32).encode(&mut section);
+            Instr::End.encode(&mut section);
+            vec_len(&mut section, bytes.len());
+            section.extend(bytes);
+        }
+        push_section(&mut out, 11, &section);
+        out
+    }
+}
+
+impl Instr {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let block = |out: &mut Vec<u8>, opcode: u8, ty: &BlockType| {
+            out.push(opcode);
+            out.push(match ty {
+                BlockType::Empty => 0x40,
+                BlockType::Value(ty) => ty.code(),
+            });
+        };
+        let memory = |out: &mut Vec<u8>, opcode: u8, width: &Width, offset: &u32| {
+            out.push(opcode);
+            uleb(out, if *width == Width::Word { 3 } else { 0 });
+            uleb(out, *offset as u64);
+        };
+        let indexed = |out: &mut Vec<u8>, opcode: u8, index: &u32| {
+            out.push(opcode);
+            uleb(out, *index as u64);
+        };
+        match self {
+            Instr::Unreachable => out.push(0x00),
+            Instr::Block(ty) => block(out, 0x02, ty),
+            Instr::Loop(ty) => block(out, 0x03, ty),
+            Instr::If(ty) => block(out, 0x04, ty),
+            Instr::Else => out.push(0x05),
+            Instr::End => out.push(0x0b),
+            Instr::Br(depth) => indexed(out, 0x0c, depth),
+            Instr::BrIf(depth) => indexed(out, 0x0d, depth),
+            Instr::Return => out.push(0x0f),
+            Instr::Call(func) => indexed(out, 0x10, func),
+            Instr::Drop => out.push(0x1a),
+            Instr::LocalGet(local) => indexed(out, 0x20, local),
+            Instr::LocalSet(local) => indexed(out, 0x21, local),
+            Instr::LocalTee(local) => indexed(out, 0x22, local),
+            Instr::GlobalGet(global) => indexed(out, 0x23, global),
+            Instr::GlobalSet(global) => indexed(out, 0x24, global),
+            Instr::Load(Width::Word, offset) => memory(out, 0x29, &Width::Word, offset),
+            Instr::Load(Width::Byte, offset) => memory(out, 0x31, &Width::Byte, offset),
+            Instr::Store(Width::Word, offset) => memory(out, 0x37, &Width::Word, offset),
+            Instr::Store(Width::Byte, offset) => memory(out, 0x3c, &Width::Byte, offset),
+            Instr::MemorySize => out.extend([0x3f, 0x00]),
+            Instr::MemoryGrow => out.extend([0x40, 0x00]),
+            Instr::I32Const(value) => {
+                out.push(0x41);
+                sleb(out, *value as i64);
+            }
+            Instr::I64Const(value) => {
+                out.push(0x42);
+                sleb(out, *value);
+            }
+            Instr::Op(numeric) => out.push(numeric.opcode()),
+        }
+    }
+}
+
+fn uleb(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn sleb(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn vec_len(out: &mut Vec<u8>, len: usize) {
+    uleb(out, len as u64);
+}
+
+fn name(out: &mut Vec<u8>, name: &str) {
+    vec_len(out, name.len());
+    out.extend(name.bytes());
+}
+
+fn push_section(out: &mut Vec<u8>, id: u8, section: &[u8]) {
+    out.push(id);
+    vec_len(out, section.len());
+    out.extend(section);
+}
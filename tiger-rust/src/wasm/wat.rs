@@ -0,0 +1,165 @@
+use std::fmt::Write;
+
+use crate::wasm::wasm::{BlockType, FuncType, Instr, Module, Width};
+
+/// The identifier of the function at `index`; imports are named after
+/// their module so they do not clash with functions of the module.
+fn func_id(module: &Module, index: u32) -> String {
+    let index = index as usize;
+    match module.imports.get(index) {
+        Some(import) => format!("${}.{}", import.module, import.name),
+        None => format!("${}", module.funcs[index - module.imports.len()].name),
+    }
+}
+
+fn signature(ty: &FuncType) -> String {
+    let mut out = String::new();
+    for (keyword, types) in [("param", &ty.params), ("result", &ty.results)] {
+        if !types.is_empty() {
+            let names: Vec<_> = types.iter().map(|ty| ty.name()).collect();
+            write!(out, " ({} {})", keyword, names.join(" ")).unwrap();
+        }
+    }
+    out
+}
+
+fn block_type(ty: &BlockType) -> String {
+    match ty {
+        BlockType::Empty => String::new(),
+        BlockType::Value(ty) => format!(" (result {})", ty.name()),
+    }
+}
+
+/// `bytes` as a string literal.
+fn literal(bytes: &[u8]) -> String {
+    let mut out = String::from("\"");
+    for byte in bytes {
+        match byte {
+            b' '..=b'~' if *byte != b'"' && *byte != b'\\' => out.push(*byte as char),
+            _ => write!(out, "\\{:02x}", byte).unwrap(),
+        }
+    }
+    out + "\""
+}
+
+fn instr(module: &Module, instr: &Instr) -> String {
+    let memory = |name: &str, offset: &u32| match offset {
+        0 => String::from(name),
+        offset => format!("{} offset={}", name, offset),
+    };
+    match instr {
+        Instr::Unreachable => String::from("unreachable"),
+        Instr::Block(ty) => format!("block{}", block_type(ty)),
+        Instr::Loop(ty) => format!("loop{}", block_type(ty)),
+        Instr::If(ty) => format!("if{}", block_type(ty)),
+        Instr::Else => String::from("else"),
+        Instr::End => String::from("end"),
+        Instr::Br(depth) => format!("br {}", depth),
+        Instr::BrIf(depth) => format!("br_if {}", depth),
+        Instr::Return => String::from("return"),
+        Instr::Call(func) => format!("call {}", func_id(module, *func)),
+        Instr::Drop => String::from("drop"),
+        Instr::LocalGet(local) => format!("local.get {}", local),
+        Instr::LocalSet(local) => format!("local.set {}", local),
+        Instr::LocalTee(local) => format!("local.tee {}", local),
+        Instr::GlobalGet(global) => {
+            format!("global.get ${}", module.globals[*global as usize].name)
+        }
+        Instr::GlobalSet(global) => {
+            format!("global.set ${}", module.globals[*global as usize].name)
+        }
+        Instr::Load(Width::Word, offset) => memory("i64.load", offset),
+        Instr::Load(Width::Byte, offset) => memory("i64.load8_u", offset),
+        Instr::Store(Width::Word, offset) => memory("i64.store", offset),
+        Instr::Store(Width::Byte, offset) => memory("i64.store8", offset),
+        Instr::MemorySize => String::from("memory.size"),
+        Instr::MemoryGrow => String::from("memory.grow"),
+        Instr::I32Const(value) => format!("i32.const {}", value),
+        Instr::I64Const(value) => format!("i64.const {}", value),
+        Instr::Op(op) => String::from(op.name()),
+    }
+}
+
+/// `module` in the text format, with one instruction per line indented
+/// by block.
+pub fn to_wat(module: &Module) -> String {
+    let mut out = String::from("(module\n");
+    for import in &module.imports {
+        writeln!(
+            out,
+            "  (import \"{}\" \"{}\" (func ${}.{}{}))",
+            import.module,
+            import.name,
+            import.module,
+            import.name,
+            signature(&import.ty)
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "  (memory (export \"{}\") {})",
+        module.memory_export, module.pages
+    )
+    .unwrap();
+    for global in &module.globals {
+        let export = match &global.export {
+            Some(name) => format!(" (export \"{}\")", name),
+            None => String::new(),
+        };
+        let ty = match global.mutable {
+            true => format!("(mut {})", global.ty.name()),
+            false => String::from(global.ty.name()),
+        };
+        writeln!(
+            out,
+            "  (global ${}{} {} ({}.const {}))",
+            global.name,
+            export,
+            ty,
+            global.ty.name(),
+            global.init
+        )
+        .unwrap();
+    }
+    for func in &module.funcs {
+        let export = match &func.export {
+            Some(name) => format!(" (export \"{}\")", name),
+            None => String::new(),
+        };
+        write!(
+            out,
+            "  (func ${}{}{}",
+            func.name,
+            export,
+            signature(&func.ty)
+        )
+        .unwrap();
+        if !func.locals.is_empty() {
+            let names: Vec<_> = func.locals.iter().map(|ty| ty.name()).collect();
+            write!(out, " (local {})", names.join(" ")).unwrap();
+        }
+        out.push('\n');
+        let mut depth = 2;
+        for i in &func.body {
+            if matches!(i, Instr::End | Instr::Else) {
+                depth -= 1;
+            }
+            writeln!(out, "{:width$}{}", "", instr(module, i), width = 2 * depth).unwrap();
+            if matches!(
+                i,
+                Instr::Block(_) | Instr::Loop(_) | Instr::If(_) | Instr::Else
+            ) {
+                depth += 1;
+            }
+        }
+        out.truncate(out.len() - 1);
+        out.push_str(")\n");
+    }
+    for (address, bytes) in &module.data {
+        writeln!(out, "  (data (i32.const {}) {})", address, literal(bytes)).unwrap();
+    }
+    out.truncate(out.len() - 1);
+    out.push_str(")\n");
+    out
+}